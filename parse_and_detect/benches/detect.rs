@@ -0,0 +1,147 @@
+use rusty::detect::{DetectorConfig, detect_parallel, detect_wide_attacks};
+use rusty::detector::Window;
+use rusty::parser::pumpfun::{ExecutionOutcome, ParsedTransaction, TradeType};
+use std::collections::{BTreeMap, HashSet};
+use std::time::Instant;
+
+const TRADES_PER_SLOT: u64 = 40;
+const BOT_SIGNERS: u64 = 25;
+const MINTS: usize = 8;
+
+struct Lcg(u64);
+
+impl Lcg {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        self.0 >> 33
+    }
+}
+
+fn synthetic_trades(count: usize, mints: usize) -> Vec<ParsedTransaction> {
+    let mut rng = Lcg(42);
+    (0..count as u64)
+        .map(|i| {
+            let is_bot = rng.next().is_multiple_of(3);
+            let signer = if is_bot {
+                format!("bot{}", rng.next() % BOT_SIGNERS)
+            } else {
+                format!("user{}", i)
+            };
+            let trade_type = if rng.next().is_multiple_of(2) { TradeType::Buy } else { TradeType::Sell };
+            let lamports = 10_000_000 + (rng.next() % 2_000_000_000) as i64;
+            let tokens = lamports * 30;
+            let (sol_change, token_change) = match trade_type {
+                TradeType::Buy => (-lamports, tokens),
+                TradeType::Sell => (lamports, -tokens),
+            };
+            ParsedTransaction {
+                signature: format!("sig{:08}", i).into(),
+                slot: 300_000_000 + i / TRADES_PER_SLOT,
+                block_time: None,
+                tx_index: None,
+                signer: signer.into(),
+                fee_payer: None,
+                mint: format!("BenchMint{}", i as usize % mints).into(),
+                leg_index: 0,
+                venue: Default::default(),
+                trade_type,
+                token_amount_requested: (tokens as u64) + rng.next() % 1_000_000,
+                sol_limit_specified: (lamports as u64).saturating_sub(rng.next() % 1_000_000),
+                sol_change,
+                token_change,
+                fee: 5_000,
+                priority_fee: 0,
+                tip: 0,
+                compute_unit_price: 0,
+                ata_rent: 0,
+                outcome: ExecutionOutcome::Succeeded,
+                curve: None,
+                hop_token_changes: Default::default(),
+            }
+        })
+        .collect()
+}
+
+// The lookup detection did before the leg index: every executed trade scans each slot within
+// `max_slot_gap` of it for bot legs on its mint, bought on its side before it or sold after it.
+// Kept as the baseline `Window::new`'s indexed lookup is measured against.
+fn per_victim_scan(trades: &[ParsedTransaction], bots: &HashSet<String>, cfg: &DetectorConfig) -> usize {
+    let mut by_slot: BTreeMap<u64, Vec<&ParsedTransaction>> = BTreeMap::new();
+    for tx in trades {
+        by_slot.entry(tx.slot).or_default().push(tx);
+    }
+    let key = |tx: &ParsedTransaction| (tx.slot, tx.signature.clone(), tx.leg_index);
+    let mut found = 0;
+    for victim in trades.iter().filter(|tx| tx.outcome != ExecutionOutcome::Reverted) {
+        let range = victim.slot.saturating_sub(cfg.max_slot_gap)..=victim.slot + cfg.max_slot_gap;
+        let mut frontruns: Vec<ParsedTransaction> = Vec::new();
+        let mut backruns: Vec<ParsedTransaction> = Vec::new();
+        for tx in by_slot.range(range).flat_map(|(_, txs)| txs) {
+            if tx.mint != victim.mint || tx.outcome == ExecutionOutcome::Reverted || !bots.contains(&*tx.signer) {
+                continue;
+            }
+            let before = key(tx) < key(victim);
+            if before && tx.trade_type == victim.trade_type {
+                frontruns.push((*tx).clone());
+            } else if key(tx) > key(victim) && tx.trade_type != victim.trade_type {
+                backruns.push((*tx).clone());
+            }
+        }
+        found += frontruns.len() + backruns.len();
+    }
+    found
+}
+
+fn main() {
+    let cfg = DetectorConfig::default();
+    println!("{:>8} {:>12} {:>14} {:>10}", "trades", "elapsed ms", "ns per trade", "sandwiches");
+
+    for &count in &[10_000usize, 25_000, 50_000, 100_000] {
+        let trades = synthetic_trades(count, 1);
+        let started = Instant::now();
+        let summary = detect_wide_attacks(&trades, &cfg);
+        let elapsed = started.elapsed();
+        println!(
+            "{:>8} {:>12.1} {:>14.0} {:>10}",
+            count,
+            elapsed.as_secs_f64() * 1_000.0,
+            elapsed.as_nanos() as f64 / count as f64,
+            summary.sandwiches.len()
+        );
+    }
+
+    println!("\n{} mints, {:>8} {:>14} {:>14}", MINTS, "trades", "sequential ms", "parallel ms");
+    for &count in &[100_000usize, 400_000] {
+        let trades = synthetic_trades(count, MINTS);
+        let started = Instant::now();
+        let sequential = detect_wide_attacks(&trades, &cfg);
+        let sequential_elapsed = started.elapsed();
+        let started = Instant::now();
+        let parallel = detect_parallel(&trades, &cfg);
+        let parallel_elapsed = started.elapsed();
+        assert_eq!(sequential.sandwiches.len(), parallel.sandwiches.len());
+        println!(
+            "{:>16} {:>14.1} {:>14.1}",
+            count,
+            sequential_elapsed.as_secs_f64() * 1_000.0,
+            parallel_elapsed.as_secs_f64() * 1_000.0
+        );
+    }
+
+    println!("\nleg lookup, 1 mint: {:>8} {:>20} {:>14}", "trades", "per-victim scan ms", "window ms");
+    for &count in &[25_000usize, 100_000] {
+        let trades = synthetic_trades(count, 1);
+        let started = Instant::now();
+        let window = Window::new(&trades, &cfg);
+        let window_elapsed = started.elapsed();
+        let started = Instant::now();
+        per_victim_scan(&trades, window.bot_signers(), &cfg);
+        let scan_elapsed = started.elapsed();
+        println!(
+            "{:>28} {:>20.1} {:>14.1}",
+            count,
+            scan_elapsed.as_secs_f64() * 1_000.0,
+            window_elapsed.as_secs_f64() * 1_000.0
+        );
+    }
+}
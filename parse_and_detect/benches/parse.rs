@@ -0,0 +1,87 @@
+use rusty::network::PUMP_PROGRAM_ID;
+use rusty::parser::pumpfun::parse_transaction;
+use serde_json::{Value, json};
+use solana_transaction_status::{EncodedConfirmedTransactionWithStatusMeta, EncodedTransaction};
+use std::fs;
+use std::time::Instant;
+
+const MINT: &str = "4LTksEDzHp7X2keAjU8kumFHtydEDkX2td43xx9w8B3G";
+const FIXTURES: [&str; 8] = [
+    "buy",
+    "sell",
+    "cpi_buy",
+    "failed_buy",
+    "multi_leg",
+    "sandwich_front",
+    "sandwich_victim",
+    "sandwich_back",
+];
+const ROUNDS: usize = 2_000;
+
+fn fixture(name: &str) -> Value {
+    let path = format!("{}/fixtures/{}.json", env!("CARGO_MANIFEST_DIR"), name);
+    let raw = fs::read_to_string(&path).unwrap_or_else(|e| panic!("reading {}: {}", path, e));
+    serde_json::from_str(&raw).unwrap_or_else(|e| panic!("decoding {}: {}", path, e))
+}
+
+fn decode(value: Value) -> (EncodedConfirmedTransactionWithStatusMeta, String) {
+    let tx: EncodedConfirmedTransactionWithStatusMeta =
+        serde_json::from_value(value).unwrap_or_else(|e| panic!("decoding fixture: {}", e));
+    let EncodedTransaction::Json(ui) = &tx.transaction.transaction else {
+        panic!("fixture is not JSON-encoded");
+    };
+    let signature = ui.signatures[0].clone();
+    (tx, signature)
+}
+
+// the CPI buy wrapped in a router that also runs `extra` unrelated inner instructions, the
+// shape of aggregator and bot transactions where most instructions are not pump.fun's
+fn routed_buy(extra: usize) -> Value {
+    let mut value = fixture("cpi_buy");
+    let filler: Vec<Value> = (0..extra)
+        .map(|i| {
+            // transfer-sized data, like most of a router's system and token instructions
+            let data: Vec<u8> = (0..12).map(|byte| (byte * 7 + i * 13) as u8).collect();
+            json!({
+                "programIdIndex": 7,
+                "accounts": [0, 2, 3, 4, 5, 6],
+                "data": bs58::encode(data).into_string(),
+                "stackHeight": 3,
+            })
+        })
+        .collect();
+    let instructions = value["meta"]["innerInstructions"][0]["instructions"]
+        .as_array_mut()
+        .unwrap_or_else(|| panic!("cpi_buy has no inner instructions"));
+    instructions.extend(filler);
+    value
+}
+
+fn time(label: &str, txs: &[(EncodedConfirmedTransactionWithStatusMeta, String)]) {
+    let started = Instant::now();
+    let mut legs = 0;
+    for _ in 0..ROUNDS {
+        for (tx, signature) in txs {
+            legs += parse_transaction(tx, signature, MINT, PUMP_PROGRAM_ID).map(|legs| legs.len()).unwrap_or(0);
+        }
+    }
+    let elapsed = started.elapsed();
+    let parsed = ROUNDS * txs.len();
+    println!(
+        "{:<22} {:>10} {:>12.1} {:>12.0} {:>8}",
+        label,
+        parsed,
+        elapsed.as_secs_f64() * 1_000.0,
+        elapsed.as_nanos() as f64 / parsed as f64,
+        legs / ROUNDS
+    );
+}
+
+fn main() {
+    println!("{:<22} {:>10} {:>12} {:>12} {:>8}", "transactions", "parsed", "elapsed ms", "ns per tx", "legs");
+    let fixtures: Vec<_> = FIXTURES.iter().map(|name| decode(fixture(name))).collect();
+    time("fixtures", &fixtures);
+    for extra in [16, 64] {
+        time(&format!("router + {} inner", extra), &[decode(routed_buy(extra))]);
+    }
+}
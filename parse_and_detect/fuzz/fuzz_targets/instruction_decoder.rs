@@ -0,0 +1,26 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rusty::parser::pumpfun::{LifecycleKind, decode_instruction_data};
+
+const BUY: [u8; 8] = [102, 6, 61, 18, 1, 218, 235, 234];
+const SELL: [u8; 8] = [51, 230, 133, 164, 1, 127, 131, 173];
+
+// Raw instruction data, base58-encoded the way the RPC returns it, then the same bytes as a
+// string that may not be base58 at all. Decoding must never panic, and what it accepts must be
+// a leading discriminator followed by a full argument payload.
+fuzz_target!(|data: &[u8]| {
+    let accounts: Vec<String> = (0..8).map(|index| format!("account{}", index)).collect();
+    let decoded = decode_instruction_data(&bs58::encode(data).into_string(), &accounts);
+
+    if decoded.trade.is_some() {
+        assert!(data.get(..8).is_some_and(|tag| tag == BUY || tag == SELL) && data.len() >= 24);
+    }
+    if let Some(LifecycleKind::Create { name, symbol, uri, .. }) = &decoded.lifecycle {
+        assert!(data.len() >= 8 + 12 + name.len() + symbol.len() + uri.len());
+    }
+
+    if let Ok(text) = std::str::from_utf8(data) {
+        decode_instruction_data(text, &accounts);
+    }
+});
@@ -0,0 +1,44 @@
+#ifndef RUSTY_H
+#define RUSTY_H
+
+/* Generated by cbindgen from src/ffi.rs; do not edit. */
+
+#include <stddef.h>
+#include <stdint.h>
+
+// Link against the library from
+//   cargo build --release --lib --no-default-features --features ffi
+// Inputs are UTF-8 JSON buffers with a length. Every returned string is NUL-terminated JSON owned
+// by the caller, to be released with rusty_string_free. A failed call returns NULL, and
+// rusty_last_error says why.
+
+// A `getTransaction` response (JSON encoding) -> a JSON array of its trades on `mint`.
+//
+// # Safety
+// `response` and `mint` must point to `response_len` and `mint_len` readable bytes.
+char *rusty_parse_transaction(const uint8_t *response,
+                              size_t response_len,
+                              const uint8_t *mint,
+                              size_t mint_len);
+
+// A JSON array of trades or a `--save-trades` document -> the detection summary as JSON.
+// `options` is a JSON object of `DetectOptions` keys; NULL or empty keeps the defaults.
+//
+// # Safety
+// `trades` and `options` must point to `trades_len` and `options_len` readable bytes.
+char *rusty_detect(const uint8_t *trades,
+                   size_t trades_len,
+                   const uint8_t *options,
+                   size_t options_len);
+
+// The message of this thread's last failed call, or NULL. It stays valid until the thread's next
+// failed call.
+const char *rusty_last_error(void);
+
+// Frees a string returned by this library; NULL is ignored.
+//
+// # Safety
+// `json` must come from this library and not have been freed already.
+void rusty_string_free(char *json);
+
+#endif  /* RUSTY_H */
@@ -0,0 +1,135 @@
+use crate::error::Error;
+use crate::fetch::TransactionSource;
+use solana_address_lookup_table_interface::state::AddressLookupTable;
+use solana_sdk::pubkey::Pubkey;
+use solana_transaction_status::option_serializer::OptionSerializer;
+use solana_transaction_status::{
+    EncodedConfirmedTransactionWithStatusMeta, EncodedTransaction, UiLoadedAddresses, UiMessage,
+};
+use std::collections::HashMap;
+use std::str::FromStr;
+
+struct TableLookup {
+    table: String,
+    writable_indexes: Vec<u8>,
+    readonly_indexes: Vec<u8>,
+}
+
+#[derive(Default)]
+pub struct LookupTableCache {
+    tables: HashMap<String, Vec<String>>,
+}
+
+impl LookupTableCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn resolve_loaded_addresses(
+        &mut self,
+        source: &dyn TransactionSource,
+        tx: &mut EncodedConfirmedTransactionWithStatusMeta,
+    ) -> Result<(), Error> {
+        let Some(meta) = tx.transaction.meta.as_mut() else {
+            return Ok(());
+        };
+        if let OptionSerializer::Some(loaded) = &meta.loaded_addresses
+            && (!loaded.writable.is_empty() || !loaded.readonly.is_empty())
+        {
+            return Ok(());
+        }
+
+        let lookups = collect_lookups(&tx.transaction.transaction);
+        if lookups.is_empty() {
+            return Ok(());
+        }
+
+        let mut loaded = UiLoadedAddresses {
+            writable: Vec::new(),
+            readonly: Vec::new(),
+        };
+        for lookup in &lookups {
+            let addresses = self.table_addresses(source, &lookup.table)?;
+            loaded
+                .writable
+                .extend(select_addresses(addresses, &lookup.writable_indexes, &lookup.table)?);
+        }
+        for lookup in &lookups {
+            let addresses = self.table_addresses(source, &lookup.table)?;
+            loaded
+                .readonly
+                .extend(select_addresses(addresses, &lookup.readonly_indexes, &lookup.table)?);
+        }
+
+        meta.loaded_addresses = OptionSerializer::Some(loaded);
+        Ok(())
+    }
+
+    fn table_addresses(&mut self, source: &dyn TransactionSource, table: &str) -> Result<&[String], Error> {
+        if !self.tables.contains_key(table) {
+            let key = Pubkey::from_str(table)
+                .map_err(|e| lookup_error(table, format!("invalid address: {}", e)))?;
+            let data = source
+                .account_data(&key)
+                .map_err(|e| lookup_error(table, format!("fetch failed: {}", e)))?;
+            let state = AddressLookupTable::deserialize(&data)
+                .map_err(|e| lookup_error(table, format!("decode failed: {}", e)))?;
+            let addresses = state.addresses.iter().map(|key| key.to_string()).collect();
+            self.tables.insert(table.to_string(), addresses);
+        }
+        Ok(self.tables[table].as_slice())
+    }
+}
+
+fn collect_lookups(transaction: &EncodedTransaction) -> Vec<TableLookup> {
+    match transaction {
+        EncodedTransaction::Json(tx_json) => match &tx_json.message {
+            UiMessage::Raw(message) => message
+                .address_table_lookups
+                .iter()
+                .flatten()
+                .map(|lookup| TableLookup {
+                    table: lookup.account_key.clone(),
+                    writable_indexes: lookup.writable_indexes.clone(),
+                    readonly_indexes: lookup.readonly_indexes.clone(),
+                })
+                .collect(),
+            UiMessage::Parsed(_) => Vec::new(),
+        },
+        EncodedTransaction::Binary(..) | EncodedTransaction::LegacyBinary(_) => transaction
+            .decode()
+            .and_then(|versioned| {
+                versioned.message.address_table_lookups().map(|lookups| {
+                    lookups
+                        .iter()
+                        .map(|lookup| TableLookup {
+                            table: lookup.account_key.to_string(),
+                            writable_indexes: lookup.writable_indexes.clone(),
+                            readonly_indexes: lookup.readonly_indexes.clone(),
+                        })
+                        .collect()
+                })
+            })
+            .unwrap_or_default(),
+        EncodedTransaction::Accounts(_) => Vec::new(),
+    }
+}
+
+fn select_addresses(addresses: &[String], indexes: &[u8], table: &str) -> Result<Vec<String>, Error> {
+    indexes
+        .iter()
+        .map(|&index| {
+            addresses
+                .get(index as usize)
+                .cloned()
+                .ok_or_else(|| lookup_error(table, format!("no entry at index {}", index)))
+        })
+        .collect()
+}
+
+fn lookup_error(table: &str, reason: String) -> Error {
+    Error::LookupTable {
+        table: table.to_string(),
+        reason,
+    }
+}
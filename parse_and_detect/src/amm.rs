@@ -0,0 +1,398 @@
+use crate::error::Error;
+use crate::parser::pumpfun::{ParsedTransaction, TradeType};
+use crate::rugpull::PUMP_TOKEN_SUPPLY;
+use serde::{Deserialize, Serialize};
+
+pub const LAMPORTS_PER_SOL: u64 = 1_000_000_000;
+pub const TOKEN_DECIMALS: u64 = 1_000_000;
+pub const INITIAL_VIRTUAL_SOL: u64 = 30 * LAMPORTS_PER_SOL;
+pub const INITIAL_VIRTUAL_TOKEN: u64 = 1_073_000_000 * TOKEN_DECIMALS;
+pub const INITIAL_REAL_SOL: u64 = 0;
+pub const INITIAL_REAL_TOKEN: u64 = 793_100_000 * TOKEN_DECIMALS;
+
+// First slot of pump.fun's creator-fee era (mid-May 2025); approximate, override with a custom schedule
+pub const CREATOR_FEE_START_SLOT: u64 = 340_000_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeeTier {
+    pub from_slot: u64,
+    pub protocol_bps: u64,
+    pub creator_bps: u64,
+}
+
+impl FeeTier {
+    pub fn total_bps(&self) -> u64 {
+        self.protocol_bps + self.creator_bps
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeeSchedule {
+    pub tiers: Vec<FeeTier>,
+}
+
+impl Default for FeeSchedule {
+    fn default() -> Self {
+        Self {
+            tiers: vec![
+                FeeTier {
+                    from_slot: 0,
+                    protocol_bps: 100,
+                    creator_bps: 0,
+                },
+                FeeTier {
+                    from_slot: CREATOR_FEE_START_SLOT,
+                    protocol_bps: 95,
+                    creator_bps: 5,
+                },
+            ],
+        }
+    }
+}
+
+impl FeeSchedule {
+    pub fn flat(protocol_bps: u64, creator_bps: u64) -> Self {
+        Self {
+            tiers: vec![FeeTier {
+                from_slot: 0,
+                protocol_bps,
+                creator_bps,
+            }],
+        }
+    }
+
+    // "slot:protocol_bps:creator_bps,..." e.g. "0:100:0,340000000:95:5"
+    pub fn parse(spec: &str) -> Result<Self, Error> {
+        let mut tiers = spec
+            .split(',')
+            .map(|tier| {
+                let parts: Vec<&str> = tier.trim().split(':').collect();
+                let [slot, protocol, creator] = parts.as_slice() else {
+                    return Err(Error::Config(format!(
+                        "Invalid fee tier '{}'. Expected slot:protocol_bps:creator_bps",
+                        tier
+                    )));
+                };
+                let number = |value: &str| {
+                    value
+                        .parse::<u64>()
+                        .map_err(|e| Error::Config(format!("Invalid value '{}' in fee tier '{}': {}", value, tier, e)))
+                };
+                Ok(FeeTier {
+                    from_slot: number(slot)?,
+                    protocol_bps: number(protocol)?,
+                    creator_bps: number(creator)?,
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        tiers.sort_by_key(|tier| tier.from_slot);
+        if tiers.iter().any(|tier| tier.total_bps() >= 10_000) {
+            return Err(Error::Config("Fee tiers must total less than 10000 bps".to_string()));
+        }
+        Ok(Self { tiers })
+    }
+
+    pub fn at(&self, slot: u64) -> FeeTier {
+        self.tiers
+            .iter()
+            .rev()
+            .find(|tier| tier.from_slot <= slot)
+            .or(self.tiers.first())
+            .copied()
+            .unwrap_or(FeeTier {
+                from_slot: 0,
+                protocol_bps: 0,
+                creator_bps: 0,
+            })
+    }
+
+    pub fn current(&self) -> FeeTier {
+        self.at(u64::MAX)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PumpAmmState {
+    pub virtual_sol: u64,
+    pub virtual_token: u64,
+    pub real_sol: u64,
+    pub real_token: u64,
+    pub fee: FeeTier,
+    pub protocol_fees: u64,
+    pub creator_fees: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AmmCheckpoint {
+    virtual_sol: u64,
+    virtual_token: u64,
+    real_sol: u64,
+    real_token: u64,
+    protocol_fees: u64,
+    creator_fees: u64,
+}
+
+impl Default for PumpAmmState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PumpAmmState {
+    pub fn new() -> Self {
+        Self::from_reserves(INITIAL_VIRTUAL_SOL, INITIAL_VIRTUAL_TOKEN, INITIAL_REAL_SOL, INITIAL_REAL_TOKEN)
+    }
+
+    pub fn from_reserves(virtual_sol: u64, virtual_token: u64, real_sol: u64, real_token: u64) -> Self {
+        Self {
+            virtual_sol,
+            virtual_token,
+            real_sol,
+            real_token,
+            fee: FeeSchedule::default().current(),
+            protocol_fees: 0,
+            creator_fees: 0,
+        }
+    }
+
+    pub fn with_fee(mut self, fee: FeeTier) -> Self {
+        self.fee = fee;
+        self
+    }
+
+    // Pump.fun reserves keep virtual_sol * virtual_token at the launch constant, so one observed
+    // trade is enough to solve for the reserves it executed against.
+    pub fn from_observed_trade(leg: &ParsedTransaction, schedule: &FeeSchedule) -> Option<Self> {
+        let fee = schedule.at(leg.slot);
+        let k = INITIAL_VIRTUAL_SOL as f64 * INITIAL_VIRTUAL_TOKEN as f64;
+        let sol = leg.trade_sol_change().unsigned_abs() as f64;
+        let tokens = leg.token_change.unsigned_abs() as f64;
+        if sol == 0.0 || tokens == 0.0 {
+            return None;
+        }
+
+        let fee_factor = (10_000 - fee.total_bps()) as f64 / 10_000.0;
+        let (virtual_sol, virtual_token) = match leg.trade_type {
+            TradeType::Buy => {
+                let sol_in = sol * fee_factor;
+                let vs = (-sol_in + (sol_in * sol_in + 4.0 * k * sol_in / tokens).sqrt()) / 2.0;
+                (vs, k / vs)
+            }
+            TradeType::Sell => {
+                let sol_out = sol / fee_factor;
+                let vt = (-tokens + (tokens * tokens + 4.0 * k * tokens / sol_out).sqrt()) / 2.0;
+                (k / vt, vt)
+            }
+        };
+        if !virtual_sol.is_finite() || !virtual_token.is_finite() || virtual_sol <= 0.0 {
+            return None;
+        }
+
+        let virtual_sol = virtual_sol as u64;
+        let virtual_token = virtual_token as u64;
+        Some(
+            Self::from_reserves(
+                virtual_sol,
+                virtual_token,
+                virtual_sol.saturating_sub(INITIAL_VIRTUAL_SOL),
+                virtual_token.saturating_sub(INITIAL_VIRTUAL_TOKEN - INITIAL_REAL_TOKEN),
+            )
+            .with_fee(fee),
+        )
+    }
+
+    // Exact reserves before the leg, from the bonding curve's token balance: it holds the real
+    // token reserves plus the allocation kept back for migration. None when the parser couldn't
+    // attribute the curve account to this leg.
+    pub fn from_curve_snapshot(leg: &ParsedTransaction, schedule: &FeeSchedule) -> Option<Self> {
+        let held = leg.curve.as_ref()?.pre_token_balance?;
+        let real_token = held.checked_sub(PUMP_TOKEN_SUPPLY - INITIAL_REAL_TOKEN)?;
+        let virtual_token = real_token + (INITIAL_VIRTUAL_TOKEN - INITIAL_REAL_TOKEN);
+        if virtual_token == 0 {
+            return None;
+        }
+        let k = INITIAL_VIRTUAL_SOL as u128 * INITIAL_VIRTUAL_TOKEN as u128;
+        let virtual_sol = u64::try_from(k / virtual_token as u128).ok()?;
+        Some(
+            Self::from_reserves(
+                virtual_sol,
+                virtual_token,
+                virtual_sol.saturating_sub(INITIAL_VIRTUAL_SOL),
+                real_token,
+            )
+            .with_fee(schedule.at(leg.slot)),
+        )
+    }
+
+    // the curve snapshot when the parser recorded one, otherwise the reserves solved from the fill
+    pub fn before_trade(leg: &ParsedTransaction, schedule: &FeeSchedule) -> Option<Self> {
+        Self::from_curve_snapshot(leg, schedule).or_else(|| Self::from_observed_trade(leg, schedule))
+    }
+
+    pub fn checkpoint(&self) -> AmmCheckpoint {
+        AmmCheckpoint {
+            virtual_sol: self.virtual_sol,
+            virtual_token: self.virtual_token,
+            real_sol: self.real_sol,
+            real_token: self.real_token,
+            protocol_fees: self.protocol_fees,
+            creator_fees: self.creator_fees,
+        }
+    }
+
+    pub fn restore(&mut self, checkpoint: &AmmCheckpoint) {
+        self.virtual_sol = checkpoint.virtual_sol;
+        self.virtual_token = checkpoint.virtual_token;
+        self.real_sol = checkpoint.real_sol;
+        self.real_token = checkpoint.real_token;
+        self.protocol_fees = checkpoint.protocol_fees;
+        self.creator_fees = checkpoint.creator_fees;
+    }
+
+    pub fn price(&self) -> f64 {
+        if self.virtual_token == 0 {
+            0.0
+        } else {
+            (self.virtual_sol as f64) / (self.virtual_token as f64)
+        }
+    }
+
+    pub fn quote_buy(&self, sol_in: u64) -> u64 {
+        let sol_in_after_fee = sol_in.saturating_sub(self.fee_on(sol_in));
+        if self.virtual_sol == 0 {
+            0
+        } else {
+            (sol_in_after_fee as u128 * self.virtual_token as u128
+                / (self.virtual_sol as u128 + sol_in_after_fee as u128)) as u64
+        }
+    }
+
+    pub fn quote_sell(&self, tokens_in: u64) -> u64 {
+        let gross = self.gross_sol_out(tokens_in);
+        gross.saturating_sub(self.fee_on(gross))
+    }
+
+    fn gross_sol_out(&self, tokens_in: u64) -> u64 {
+        if self.virtual_token == 0 {
+            0
+        } else {
+            (tokens_in as u128 * self.virtual_sol as u128 / (self.virtual_token as u128 + tokens_in as u128)) as u64
+        }
+    }
+
+    pub fn sol_for_tokens(&self, tokens_out: u64) -> u64 {
+        if tokens_out >= self.virtual_token {
+            return u64::MAX;
+        }
+        let sol_after_fee = (tokens_out as u128 * self.virtual_sol as u128)
+            .div_ceil((self.virtual_token - tokens_out) as u128);
+        // past u64 no input buys them, the same answer as draining the curve
+        sol_after_fee
+            .checked_mul(10_000)
+            .and_then(|gross| u64::try_from(gross.div_ceil((10_000 - self.fee.total_bps()) as u128)).ok())
+            .map_or(u64::MAX, |sol| sol.saturating_add(1))
+    }
+
+    // percentage move of the spot price caused by the trade
+    pub fn buy_price_impact_pct(&self, sol_in: u64) -> f64 {
+        let mut after = self.clone();
+        after.simulate_buy(sol_in, 0);
+        price_change_pct(self.price(), after.price())
+    }
+
+    pub fn sell_price_impact_pct(&self, tokens_in: u64) -> f64 {
+        let mut after = self.clone();
+        after.simulate_sell(tokens_in, 0);
+        price_change_pct(self.price(), after.price())
+    }
+
+    pub fn simulate_buy(&mut self, sol_in: u64, min_tokens_out: u64) -> (u64, u64) {
+        let fee = self.fee_on(sol_in);
+        let sol_in_after_fee = sol_in.saturating_sub(fee);
+        let tokens_out = self.quote_buy(sol_in);
+
+        // the program's checked math fails a trade the reserves can't hold, like a missed limit
+        let reserves = self.virtual_sol.checked_add(sol_in_after_fee).zip(self.real_sol.checked_add(sol_in_after_fee));
+        let tokens_out = if tokens_out < min_tokens_out || reserves.is_none() {
+            0
+        } else {
+            tokens_out
+        };
+
+        if tokens_out > 0
+            && let Some((virtual_sol, real_sol)) = reserves
+        {
+            self.virtual_sol = virtual_sol;
+            self.virtual_token = self.virtual_token.saturating_sub(tokens_out);
+            self.real_sol = real_sol;
+            self.real_token = self.real_token.saturating_sub(tokens_out);
+            self.collect_fee(fee);
+        }
+
+        (tokens_out, sol_in)
+    }
+
+    pub fn simulate_sell(&mut self, tokens_in: u64, min_sol_out: u64) -> u64 {
+        let gross = self.gross_sol_out(tokens_in);
+        let fee = self.fee_on(gross);
+        let sol_out = gross.saturating_sub(fee);
+
+        let reserves = self.virtual_token.checked_add(tokens_in).zip(self.real_token.checked_add(tokens_in));
+        let sol_out = if sol_out < min_sol_out || reserves.is_none() {
+            0
+        } else {
+            sol_out
+        };
+
+        if sol_out > 0
+            && let Some((virtual_token, real_token)) = reserves
+        {
+            self.virtual_sol = self.virtual_sol.saturating_sub(gross);
+            self.virtual_token = virtual_token;
+            self.real_sol = self.real_sol.saturating_sub(gross);
+            self.real_token = real_token;
+            self.collect_fee(fee);
+        }
+
+        sol_out
+    }
+
+    // replays an observed leg against the curve and returns the trader's SOL delta
+    pub fn apply_trade(&mut self, tx: &ParsedTransaction) -> i64 {
+        match tx.trade_type {
+            TradeType::Buy => {
+                let sol_in = tx.trade_sol_change().unsigned_abs();
+                self.simulate_buy(sol_in, 0);
+                -(sol_in as i64)
+            }
+            TradeType::Sell => self.simulate_sell(tx.token_change.unsigned_abs(), 0) as i64,
+        }
+    }
+
+    fn fee_on(&self, amount: u64) -> u64 {
+        let total = self.fee.total_bps();
+        if total == 0 {
+            return 0;
+        }
+        ((amount as u128 * total as u128 / 10_000) as u64).max(1)
+    }
+
+    fn collect_fee(&mut self, fee: u64) {
+        let total = self.fee.total_bps();
+        if total == 0 {
+            return;
+        }
+        let creator = (fee as u128 * self.fee.creator_bps as u128 / total as u128) as u64;
+        self.creator_fees = self.creator_fees.saturating_add(creator);
+        self.protocol_fees = self.protocol_fees.saturating_add(fee - creator);
+    }
+}
+
+fn price_change_pct(before: f64, after: f64) -> f64 {
+    if before == 0.0 {
+        0.0
+    } else {
+        (after - before) * 100.0 / before
+    }
+}
@@ -0,0 +1,143 @@
+use crate::error::Error;
+use crate::fetch::{TransactionSource, at_least_confirmed};
+use serde_json::{Value, json};
+use solana_client::rpc_client::RpcClient;
+use solana_commitment_config::CommitmentConfig;
+use solana_sdk::epoch_schedule::EpochSchedule;
+use solana_sdk::pubkey::Pubkey;
+use solana_transaction_status::{EncodedConfirmedTransactionWithStatusMeta, UiTransactionEncoding};
+use std::collections::HashMap;
+use std::time::Duration;
+
+// An RPC endpoint that sends `getTransaction` for many signatures as one JSON-RPC batch;
+// everything else goes through the regular client
+pub struct BatchRpcClient {
+    client: RpcClient,
+    http: reqwest::blocking::Client,
+    url: String,
+}
+
+impl BatchRpcClient {
+    pub fn new(url: &str, timeout: Duration, commitment: CommitmentConfig) -> Result<Self, Error> {
+        let http = reqwest::blocking::Client::builder()
+            .timeout(timeout)
+            .build()
+            .map_err(|e| Error::Rpc(e.to_string()))?;
+        Ok(Self {
+            client: RpcClient::new_with_timeout_and_commitment(url.to_string(), timeout, commitment),
+            http,
+            url: url.to_string(),
+        })
+    }
+}
+
+impl TransactionSource for BatchRpcClient {
+    fn signatures_page(
+        &self,
+        address: &Pubkey,
+        before: Option<&str>,
+        until: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<String>, Error> {
+        self.client.signatures_page(address, before, until, limit)
+    }
+
+    fn transaction(
+        &self,
+        signature: &str,
+        encoding: UiTransactionEncoding,
+    ) -> Result<EncodedConfirmedTransactionWithStatusMeta, Error> {
+        self.client.transaction(signature, encoding)
+    }
+
+    fn transactions(
+        &self,
+        signatures: &[String],
+        encoding: UiTransactionEncoding,
+    ) -> Result<Vec<Result<EncodedConfirmedTransactionWithStatusMeta, Error>>, Error> {
+        let commitment = at_least_confirmed(self.client.commitment()).commitment;
+        let requests: Vec<Value> = signatures
+            .iter()
+            .enumerate()
+            .map(|(id, signature)| {
+                json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "method": "getTransaction",
+                    "params": [signature, {
+                        "encoding": encoding,
+                        "commitment": commitment,
+                        "maxSupportedTransactionVersion": 0,
+                    }],
+                })
+            })
+            .collect();
+        let body = self
+            .http
+            .post(&self.url)
+            .json(&requests)
+            .send()
+            .and_then(|response| response.error_for_status())
+            .and_then(|response| response.text())
+            .map_err(|e| Error::Rpc(format!("Batch of {} transactions failed: {}", signatures.len(), e)))?;
+        let Ok(Value::Array(responses)) = serde_json::from_str::<Value>(&body) else {
+            return Err(Error::Rpc(format!("Batch request rejected: {}", body.trim())));
+        };
+
+        let mut results: Vec<Option<Result<EncodedConfirmedTransactionWithStatusMeta, Error>>> =
+            signatures.iter().map(|_| None).collect();
+        for response in responses {
+            let Some(id) = response.get("id").and_then(Value::as_u64).map(|id| id as usize) else {
+                continue;
+            };
+            let (Some(slot), Some(signature)) = (results.get_mut(id), signatures.get(id)) else {
+                continue;
+            };
+            *slot = Some(batch_result(signature, response));
+        }
+        Ok(results
+            .into_iter()
+            .zip(signatures)
+            .map(|(result, signature)| {
+                result.unwrap_or_else(|| {
+                    Err(Error::Rpc(format!("Batch response is missing transaction {}", signature)))
+                })
+            })
+            .collect())
+    }
+
+    fn account_data(&self, address: &Pubkey) -> Result<Vec<u8>, Error> {
+        self.client.account_data(address)
+    }
+
+    fn block_signatures(&self, slot: u64) -> Result<Vec<String>, Error> {
+        self.client.block_signatures(slot)
+    }
+
+    fn block_compute_unit_prices(&self, slot: u64) -> Result<Vec<u64>, Error> {
+        self.client.block_compute_unit_prices(slot)
+    }
+
+    fn slot_leaders(&self, start_slot: u64, limit: u64) -> Result<Vec<String>, Error> {
+        self.client.slot_leaders(start_slot, limit)
+    }
+
+    fn epoch_schedule(&self) -> Result<EpochSchedule, Error> {
+        self.client.epoch_schedule()
+    }
+
+    fn leader_schedule(&self, slot: u64) -> Result<Option<HashMap<String, Vec<usize>>>, Error> {
+        self.client.leader_schedule(slot)
+    }
+}
+
+fn batch_result(signature: &str, mut response: Value) -> Result<EncodedConfirmedTransactionWithStatusMeta, Error> {
+    if let Some(error) = response.get("error") {
+        return Err(Error::Rpc(format!("Failed to fetch transaction {}: {}", signature, error)));
+    }
+    match response.get_mut("result").map(Value::take) {
+        None | Some(Value::Null) => Err(Error::Rpc(format!("Transaction {} not found", signature))),
+        Some(result) => serde_json::from_value(result)
+            .map_err(|e| Error::json(format!("transaction {}", signature), e)),
+    }
+}
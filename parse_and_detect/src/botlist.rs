@@ -0,0 +1,79 @@
+use crate::error::{Error, Result};
+use solana_sdk::pubkey::Pubkey;
+use std::collections::BTreeMap;
+use std::fs;
+use std::str::FromStr;
+use std::time::Duration;
+
+// Curated wallets of known Solana sandwich bots. Lists use one `<pubkey> [label]` per line with
+// `#` comments; the bundled one ships with the crate, and files or URLs loaded at startup
+// extend it, so the list can be updated without a new release.
+const BUNDLED: &str = include_str!("../known_bots.txt");
+
+#[derive(Debug, Clone, Default)]
+pub struct BotList {
+    // pubkey -> label, empty when the list gives none
+    pub bots: BTreeMap<String, String>,
+}
+
+impl BotList {
+    pub fn bundled() -> Result<Self> {
+        Self::parse(BUNDLED, "bundled bot list")
+    }
+
+    pub fn parse(raw: &str, origin: &str) -> Result<Self> {
+        let mut list = Self::default();
+        for (number, line) in raw.lines().enumerate() {
+            let entry = line.split('#').next().unwrap_or_default().trim();
+            let (wallet, label) = entry.split_once(char::is_whitespace).unwrap_or((entry, ""));
+            if wallet.is_empty() {
+                continue;
+            }
+            if Pubkey::from_str(wallet).is_err() {
+                return Err(Error::Config(format!(
+                    "Invalid wallet '{}' on line {} of {}",
+                    wallet,
+                    number + 1,
+                    origin
+                )));
+            }
+            list.bots.insert(wallet.to_string(), label.trim().to_string());
+        }
+        Ok(list)
+    }
+
+    // `source` is a path or an http(s) URL
+    pub fn load(source: &str, timeout: Duration) -> Result<Self> {
+        let raw = if is_url(source) {
+            download(source, timeout)?
+        } else {
+            fs::read_to_string(source).map_err(|e| Error::io("read bot list", source, e))?
+        };
+        Self::parse(&raw, &format!("'{}'", source))
+    }
+
+    // labels from later lists win
+    pub fn merge(&mut self, other: BotList) {
+        self.bots.extend(other.bots);
+    }
+}
+
+pub fn is_url(source: &str) -> bool {
+    source.starts_with("http://") || source.starts_with("https://")
+}
+
+#[cfg(feature = "rpc")]
+fn download(url: &str, timeout: Duration) -> Result<String> {
+    reqwest::blocking::Client::builder()
+        .timeout(timeout)
+        .build()
+        .and_then(|client| client.get(url).send())
+        .and_then(|response| response.error_for_status())
+        .and_then(|response| response.text())
+        .map_err(|e| Error::Config(format!("Could not download bot list '{}': {}", url, e)))
+}
+
+#[cfg(not(feature = "rpc"))]
+fn download(url: &str, _timeout: Duration) -> Result<String> {
+    Err(Error::Config(format!("Could not download bot list '{}': built without the `rpc` feature", url)))
+}
@@ -0,0 +1,422 @@
+use rusty::detect::{DirectionRule, Severity, VictimMode, direction_profile};
+use rusty::error::Error;
+use rusty::network::Network;
+use rusty::trends::Period;
+use solana_commitment_config::CommitmentLevel;
+use solana_transaction_status::UiTransactionEncoding;
+use std::str::FromStr;
+
+const USAGE: &str = "Usage: cargo run <MINT_ADDRESS> | daemon <MINT_ADDRESS>... [--mints-file FILE] \
+[--interval SECS] [--state PATH] [--events PATH] [--once] [--serve ADDR] | report [MINT_ADDRESS] [--events PATH] [--period daily|weekly] \
+| diff <LEFT.json> <RIGHT.json> \
+| sweep <MINT_ADDRESS> [--gaps N,..] [--profits SOL,..] [--victim-sols SOL,..] [--config FILE] [--encoding json-parsed|json|base64|base58] [--commitment processed|confirmed|finalized] \
+[--network mainnet|devnet|custom] [--program-id PUBKEY] [--rpc-url URL]... [--limit N] [--max-slot-gap N] [--adaptive-gap] [--strict-ordering] [--direction-profile sandwich|inverse|any] [--victim-mode limits|price_impact] [--min-victim-sol SOL] [--min-victim-tokens TOKENS] \
+[--min-profit-sol SOL] [--min-bot-trades N] [--output text|json|html|markdown|enriched|tui] [--output-file PATH] [--victims-file PATH] [--dataset PATH] [--dune-file PATH] [--min-severity low|medium|high|critical] [--token-image] [--scorer-cmd CMD | --scorer-url URL] [--min-score X] [--registry PATH] [--exclude-wallets FILE] [--bot-wallets FILE] [--bot-list PATH|URL] [--no-bundled-bots] [--fixtures DIR] [--input TRADES.json] [--save-trades PATH] [--stream] [--rps N] [--burst N] [--batch-size N] [--threads N] [--tx-index] [--slot-leaders] [--leader-cache PATH] [--block-cu-prices] [--usd] [--sol-usd PRICE] [--price-cache PATH] [--trace-funding] [--funding-depth N] [--nats URL] [--nats-subject PREFIX] [--redis URL] [--redis-stream KEY] [--redis-max-len N] [--quiet]";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OutputFormat {
+    Text,
+    Json,
+    Html,
+    Markdown,
+    // JSON with token metadata, USD values and explorer links on every detection
+    Enriched,
+    // interactive terminal dashboard over the scan results
+    Tui,
+}
+
+#[derive(Debug, Clone)]
+pub struct DaemonArgs {
+    pub mints: Vec<String>,
+    pub mints_file: Option<String>,
+    pub interval_secs: u64,
+    pub state_path: String,
+    pub events_path: String,
+    pub once: bool,
+    // address for the HTTP API and dashboard, off unless set
+    pub serve: Option<String>,
+}
+
+// `report`: detections from the daemon's events log, bucketed by period
+#[derive(Debug, Clone)]
+pub struct ReportArgs {
+    pub mint: Option<String>,
+    pub events_path: String,
+    pub period: Period,
+}
+
+impl Default for ReportArgs {
+    fn default() -> Self {
+        Self {
+            mint: None,
+            events_path: DaemonArgs::default().events_path,
+            period: Period::Daily,
+        }
+    }
+}
+
+// `diff`: two saved `--output json` summaries
+#[derive(Debug, Clone, Default)]
+pub struct DiffArgs {
+    pub left: String,
+    pub right: String,
+}
+
+// `sweep`: one fetch, detection re-run over a grid of thresholds
+#[derive(Debug, Clone, Default)]
+pub struct SweepArgs {
+    pub max_slot_gaps: Vec<u64>,
+    pub min_profit_sol: Vec<f64>,
+    pub min_victim_abs_sol: Vec<f64>,
+}
+
+impl Default for DaemonArgs {
+    fn default() -> Self {
+        Self {
+            mints: Vec::new(),
+            mints_file: None,
+            interval_secs: 60,
+            state_path: "daemon-state.json".to_string(),
+            events_path: "detections.jsonl".to_string(),
+            once: false,
+            serve: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CliArgs {
+    pub mint: String,
+    pub config_path: Option<String>,
+    pub encoding: Option<UiTransactionEncoding>,
+    pub rpc_urls: Vec<String>,
+    pub signature_limit: Option<usize>,
+    pub max_slot_gap: Option<u64>,
+    pub adaptive_slot_gap: bool,
+    pub strict_ordering: bool,
+    // (frontrun, backrun) rules of a named profile
+    pub direction_profile: Option<(DirectionRule, DirectionRule)>,
+    pub victim_mode: Option<VictimMode>,
+    pub min_victim_abs_sol: Option<f64>,
+    // whole tokens
+    pub min_victim_tokens: Option<f64>,
+    pub min_profit_sol: Option<f64>,
+    pub min_bot_trades: Option<usize>,
+    pub output_format: Option<OutputFormat>,
+    pub output_file: Option<String>,
+    pub victims_file: Option<String>,
+    pub dataset_file: Option<String>,
+    pub dune_file: Option<String>,
+    pub min_severity: Option<Severity>,
+    pub token_image: bool,
+    pub scorer_command: Option<String>,
+    pub scorer_url: Option<String>,
+    pub min_score: Option<f64>,
+    pub registry_path: Option<String>,
+    pub exclude_wallets: Option<String>,
+    pub bot_wallets: Option<String>,
+    pub bot_lists: Vec<String>,
+    pub no_bundled_bots: bool,
+    pub fixtures_dir: Option<String>,
+    pub input: Option<String>,
+    pub save_trades: Option<String>,
+    pub stream: bool,
+    pub requests_per_second: Option<f64>,
+    pub burst: Option<u32>,
+    pub batch_size: Option<usize>,
+    // detection threads; None detects on the calling thread
+    pub threads: Option<usize>,
+    pub commitment: Option<CommitmentLevel>,
+    pub network: Option<Network>,
+    pub pump_program_id: Option<String>,
+    pub tx_index: bool,
+    pub slot_leaders: bool,
+    pub leader_cache: Option<String>,
+    pub block_cu_prices: bool,
+    pub usd: bool,
+    pub sol_usd: Option<f64>,
+    pub price_cache: Option<String>,
+    pub trace_funding: bool,
+    pub funding_depth: Option<usize>,
+    pub nats_url: Option<String>,
+    pub nats_subject: Option<String>,
+    pub redis_url: Option<String>,
+    pub redis_stream: Option<String>,
+    pub redis_max_len: Option<u64>,
+    pub quiet: bool,
+    pub daemon: Option<DaemonArgs>,
+    pub report: Option<ReportArgs>,
+    pub diff: Option<DiffArgs>,
+    pub sweep: Option<SweepArgs>,
+}
+
+pub fn parse_args(args: &[String]) -> Result<CliArgs, Error> {
+    let mut cli = CliArgs::default();
+    let mut mint: Option<String> = None;
+
+    let mut iter = args.iter().skip(1).peekable();
+    if iter.next_if(|arg| arg.as_str() == "daemon").is_some() {
+        cli.daemon = Some(DaemonArgs::default());
+    } else if iter.next_if(|arg| arg.as_str() == "report").is_some() {
+        cli.report = Some(ReportArgs::default());
+    } else if iter.next_if(|arg| arg.as_str() == "diff").is_some() {
+        cli.diff = Some(DiffArgs::default());
+    } else if iter.next_if(|arg| arg.as_str() == "sweep").is_some() {
+        cli.sweep = Some(SweepArgs::default());
+    }
+    while let Some(arg) = iter.next() {
+        let flag = arg.as_str();
+        let mut value = || {
+            iter.next()
+                .cloned()
+                .ok_or_else(|| Error::Usage(format!("{} requires a value. {}", flag, USAGE)))
+        };
+        match flag {
+            "--config" => cli.config_path = Some(value()?),
+            "--network" => cli.network = Some(Network::parse(&value()?).map_err(Error::Usage)?),
+            "--program-id" => cli.pump_program_id = Some(value()?),
+            "--commitment" => cli.commitment = Some(parse_commitment(&value()?).map_err(Error::Usage)?),
+            "--encoding" => cli.encoding = Some(parse_encoding(&value()?).map_err(Error::Usage)?),
+            "--rpc-url" => cli.rpc_urls.push(value()?),
+            "--limit" => cli.signature_limit = Some(parse_number(flag, &value()?)?),
+            "--max-slot-gap" => cli.max_slot_gap = Some(parse_number(flag, &value()?)?),
+            "--adaptive-gap" => cli.adaptive_slot_gap = true,
+            "--strict-ordering" => cli.strict_ordering = true,
+            "--direction-profile" => cli.direction_profile = Some(direction_profile(&value()?).map_err(Error::Usage)?),
+            "--victim-mode" => cli.victim_mode = Some(VictimMode::parse(&value()?).map_err(Error::Usage)?),
+            "--min-victim-sol" => cli.min_victim_abs_sol = Some(parse_number(flag, &value()?)?),
+            "--min-victim-tokens" => cli.min_victim_tokens = Some(parse_number(flag, &value()?)?),
+            "--min-profit-sol" => cli.min_profit_sol = Some(parse_number(flag, &value()?)?),
+            // the base-unit spellings; a silent reinterpretation would shift thresholds by 10^decimals
+            "--min-victim-token" | "--min-profit-lamports" => {
+                let replacement = if flag == "--min-victim-token" {
+                    "--min-victim-tokens, in whole tokens"
+                } else {
+                    "--min-profit-sol, in SOL"
+                };
+                return Err(Error::Usage(format!("{} was replaced by {}", flag, replacement)));
+            }
+            "--min-bot-trades" => cli.min_bot_trades = Some(parse_number(flag, &value()?)?),
+            "--output" => cli.output_format = Some(parse_output_format(&value()?).map_err(Error::Usage)?),
+            "--output-file" => cli.output_file = Some(value()?),
+            "--victims-file" => cli.victims_file = Some(value()?),
+            "--dataset" => cli.dataset_file = Some(value()?),
+            "--dune-file" => cli.dune_file = Some(value()?),
+            "--min-severity" => cli.min_severity = Some(Severity::parse(&value()?).map_err(Error::Usage)?),
+            "--token-image" => cli.token_image = true,
+            "--scorer-cmd" => cli.scorer_command = Some(value()?),
+            "--scorer-url" => cli.scorer_url = Some(value()?),
+            "--min-score" => cli.min_score = Some(parse_number(flag, &value()?)?),
+            "--registry" => cli.registry_path = Some(value()?),
+            "--exclude-wallets" => cli.exclude_wallets = Some(value()?),
+            "--bot-wallets" => cli.bot_wallets = Some(value()?),
+            "--bot-list" => cli.bot_lists.push(value()?),
+            "--no-bundled-bots" => cli.no_bundled_bots = true,
+            "--fixtures" => cli.fixtures_dir = Some(value()?),
+            "--input" => cli.input = Some(value()?),
+            "--save-trades" => cli.save_trades = Some(value()?),
+            "--stream" => cli.stream = true,
+            "--rps" => cli.requests_per_second = Some(parse_number(flag, &value()?)?),
+            "--burst" => cli.burst = Some(parse_number(flag, &value()?)?),
+            "--batch-size" => cli.batch_size = Some(parse_number(flag, &value()?)?),
+            "--threads" => cli.threads = Some(parse_number(flag, &value()?)?),
+            "--tx-index" => cli.tx_index = true,
+            "--slot-leaders" => cli.slot_leaders = true,
+            "--leader-cache" => cli.leader_cache = Some(value()?),
+            "--block-cu-prices" => cli.block_cu_prices = true,
+            "--usd" => cli.usd = true,
+            "--sol-usd" => cli.sol_usd = Some(parse_number(flag, &value()?)?),
+            "--price-cache" => cli.price_cache = Some(value()?),
+            "--trace-funding" => cli.trace_funding = true,
+            "--funding-depth" => cli.funding_depth = Some(parse_number(flag, &value()?)?),
+            "--nats" => cli.nats_url = Some(value()?),
+            "--nats-subject" => cli.nats_subject = Some(value()?),
+            "--redis" => cli.redis_url = Some(value()?),
+            "--redis-stream" => cli.redis_stream = Some(value()?),
+            "--redis-max-len" => cli.redis_max_len = Some(parse_number(flag, &value()?)?),
+            "--quiet" | "-q" => cli.quiet = true,
+            "--period" if cli.report.is_none() => {
+                return Err(Error::Usage(format!("{} is only valid with the report command. {}", flag, USAGE)));
+            }
+            "--events" if cli.report.is_some() => report_args(&mut cli).events_path = value()?,
+            "--period" => report_args(&mut cli).period = parse_period(&value()?).map_err(Error::Usage)?,
+            "--gaps" | "--profits" | "--victim-sols" if cli.sweep.is_none() => {
+                return Err(Error::Usage(format!("{} is only valid with the sweep command. {}", flag, USAGE)));
+            }
+            "--gaps" => sweep_args(&mut cli).max_slot_gaps = parse_list(flag, &value()?)?,
+            "--profits" => sweep_args(&mut cli).min_profit_sol = parse_list(flag, &value()?)?,
+            "--victim-sols" => sweep_args(&mut cli).min_victim_abs_sol = parse_list(flag, &value()?)?,
+            "--mints-file" | "--interval" | "--state" | "--events" | "--once" | "--serve" if cli.daemon.is_none() => {
+                return Err(Error::Usage(format!("{} is only valid with the daemon command. {}", flag, USAGE)));
+            }
+            "--mints-file" => daemon_args(&mut cli).mints_file = Some(value()?),
+            "--interval" => daemon_args(&mut cli).interval_secs = parse_number(flag, &value()?)?,
+            "--state" => daemon_args(&mut cli).state_path = value()?,
+            "--events" => daemon_args(&mut cli).events_path = value()?,
+            "--once" => daemon_args(&mut cli).once = true,
+            "--serve" => daemon_args(&mut cli).serve = Some(value()?),
+            flag if flag.starts_with("--") => {
+                return Err(Error::Usage(format!("Unknown flag '{}'. {}", flag, USAGE)));
+            }
+            positional => {
+                if let Some(daemon) = cli.daemon.as_mut() {
+                    daemon.mints.push(positional.to_string());
+                    continue;
+                }
+                if let Some(report) = cli.report.as_mut()
+                    && report.mint.is_none()
+                {
+                    report.mint = Some(positional.to_string());
+                    continue;
+                }
+                if let Some(diff) = cli.diff.as_mut() {
+                    if diff.left.is_empty() {
+                        diff.left = positional.to_string();
+                        continue;
+                    }
+                    if diff.right.is_empty() {
+                        diff.right = positional.to_string();
+                        continue;
+                    }
+                    return Err(Error::Usage(format!("Unexpected argument '{}'. {}", positional, USAGE)));
+                }
+                if mint.is_some() {
+                    return Err(Error::Usage(format!("Unexpected argument '{}'. {}", positional, USAGE)));
+                }
+                mint = Some(positional.to_string());
+            }
+        }
+    }
+
+    if cli.requests_per_second.is_some_and(|rps| rps <= 0.0) {
+        return Err(Error::Usage("--rps must be positive".to_string()));
+    }
+    if cli.stream && (cli.input.is_some() || cli.save_trades.is_some() || cli.sweep.is_some() || cli.daemon.is_some()) {
+        return Err(Error::Usage(
+            "--stream keeps no trades, so it cannot be combined with --input, --save-trades, sweep or daemon"
+                .to_string(),
+        ));
+    }
+    if cli.batch_size == Some(0) {
+        return Err(Error::Usage("--batch-size must be at least 1".to_string()));
+    }
+    if cli.threads == Some(0) {
+        return Err(Error::Usage("--threads must be at least 1".to_string()));
+    }
+    if cli.threads.is_some() && (cli.stream || cli.daemon.is_some()) {
+        return Err(Error::Usage(
+            "--threads sizes the batch detector; --stream and daemon detect trade by trade".to_string(),
+        ));
+    }
+    if cli.scorer_command.is_some() && cli.scorer_url.is_some() {
+        return Err(Error::Usage("use either --scorer-cmd or --scorer-url".to_string()));
+    }
+    if cli.sol_usd.is_some_and(|price| price <= 0.0) {
+        return Err(Error::Usage("--sol-usd must be positive".to_string()));
+    }
+    if let Some(daemon) = &cli.daemon {
+        if cli.input.is_some() {
+            return Err(Error::Usage("--input replays a single saved run, not daemon mode".to_string()));
+        }
+        if daemon.mints.is_empty() && daemon.mints_file.is_none() {
+            return Err(Error::Usage(format!("daemon needs at least one mint or --mints-file. {}", USAGE)));
+        }
+        if daemon.interval_secs == 0 {
+            return Err(Error::Usage("--interval must be at least 1 second".to_string()));
+        }
+        return Ok(cli);
+    }
+    if cli.report.is_some() {
+        return Ok(cli);
+    }
+    if let Some(diff) = &cli.diff {
+        if diff.right.is_empty() {
+            return Err(Error::Usage(format!("diff needs two summary files. {}", USAGE)));
+        }
+        return Ok(cli);
+    }
+    // a replay takes its mint from the saved file
+    if cli.input.is_some() {
+        cli.mint = mint.unwrap_or_default();
+        return Ok(cli);
+    }
+    cli.mint = mint.ok_or_else(|| Error::Usage(format!("Missing token mint address argument. {}", USAGE)))?;
+    Ok(cli)
+}
+
+fn daemon_args(cli: &mut CliArgs) -> &mut DaemonArgs {
+    cli.daemon.get_or_insert_with(DaemonArgs::default)
+}
+
+fn report_args(cli: &mut CliArgs) -> &mut ReportArgs {
+    cli.report.get_or_insert_with(ReportArgs::default)
+}
+
+fn sweep_args(cli: &mut CliArgs) -> &mut SweepArgs {
+    cli.sweep.get_or_insert_with(SweepArgs::default)
+}
+
+// comma-separated, e.g. "1,2,3"
+fn parse_list<T: FromStr>(flag: &str, value: &str) -> Result<Vec<T>, Error>
+where
+    T::Err: std::fmt::Display,
+{
+    value.split(',').map(|item| parse_number(flag, item.trim())).collect()
+}
+
+fn parse_number<T: FromStr>(flag: &str, value: &str) -> Result<T, Error>
+where
+    T::Err: std::fmt::Display,
+{
+    value
+        .parse()
+        .map_err(|e| Error::Usage(format!("Invalid value '{}' for {}: {}", value, flag, e)))
+}
+
+pub fn parse_commitment(value: &str) -> Result<CommitmentLevel, String> {
+    match value {
+        "processed" => Ok(CommitmentLevel::Processed),
+        "confirmed" => Ok(CommitmentLevel::Confirmed),
+        "finalized" => Ok(CommitmentLevel::Finalized),
+        other => Err(format!(
+            "Unsupported commitment '{}'. Expected processed, confirmed or finalized",
+            other
+        )),
+    }
+}
+
+pub fn parse_encoding(value: &str) -> Result<UiTransactionEncoding, String> {
+    match value {
+        "json-parsed" | "jsonParsed" => Ok(UiTransactionEncoding::JsonParsed),
+        "json" => Ok(UiTransactionEncoding::Json),
+        "base64" => Ok(UiTransactionEncoding::Base64),
+        "base58" => Ok(UiTransactionEncoding::Base58),
+        other => Err(format!(
+            "Unsupported encoding '{}'. Expected json-parsed, json, base64 or base58",
+            other
+        )),
+    }
+}
+
+pub fn parse_period(value: &str) -> Result<Period, String> {
+    match value {
+        "daily" => Ok(Period::Daily),
+        "weekly" => Ok(Period::Weekly),
+        other => Err(format!("Unsupported period '{}'. Expected daily or weekly", other)),
+    }
+}
+
+pub fn parse_output_format(value: &str) -> Result<OutputFormat, String> {
+    match value {
+        "text" => Ok(OutputFormat::Text),
+        "json" => Ok(OutputFormat::Json),
+        "html" => Ok(OutputFormat::Html),
+        "markdown" | "md" => Ok(OutputFormat::Markdown),
+        "enriched" => Ok(OutputFormat::Enriched),
+        "tui" => Ok(OutputFormat::Tui),
+        other => Err(format!(
+            "Unsupported output format '{}'. Expected text, json, html, markdown, enriched or tui",
+            other
+        )),
+    }
+}
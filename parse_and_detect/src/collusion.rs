@@ -0,0 +1,155 @@
+use crate::detect::SandwichDetection;
+use crate::error::Error;
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+use std::str::FromStr;
+
+// Two wallets splitting sandwiches between them: one frontruns, the other backruns, and
+// neither runs both sides. A pair seen together often enough is treated as one attacker.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ColludingPair {
+    pub frontrunner: String,
+    pub backrunner: String,
+    pub sandwiches: usize,
+    // summed over the sandwiches the pair shared
+    pub net_profit_lamports: i64,
+}
+
+pub fn colluding_pairs(sandwiches: &[SandwichDetection], min_sandwiches: usize) -> Vec<ColludingPair> {
+    let mut tallies: BTreeMap<(&str, &str), (usize, i64)> = BTreeMap::new();
+    for det in sandwiches {
+        let front: BTreeSet<&str> = det.frontruns.iter().map(|leg| leg.signer.as_ref()).collect();
+        let back: BTreeSet<&str> = det.backruns.iter().map(|leg| leg.signer.as_ref()).collect();
+        for frontrunner in front.difference(&back) {
+            for backrunner in back.difference(&front) {
+                let tally = tallies.entry((frontrunner, backrunner)).or_default();
+                tally.0 += 1;
+                tally.1 += det.net_profit_sol;
+            }
+        }
+    }
+
+    let mut pairs: Vec<ColludingPair> = tallies
+        .into_iter()
+        .filter(|(_, (sandwiches, _))| *sandwiches >= min_sandwiches.max(1))
+        .map(|((frontrunner, backrunner), (sandwiches, profit))| ColludingPair {
+            frontrunner: frontrunner.to_string(),
+            backrunner: backrunner.to_string(),
+            sandwiches,
+            net_profit_lamports: profit,
+        })
+        .collect();
+    pairs.sort_by_key(|pair| std::cmp::Reverse(pair.sandwiches));
+    pairs
+}
+
+// Wallets grouped into attacker entities, by colluding pairs and by the linked-wallet list
+// (wallets known to share a funding source). Each group is named after its smallest pubkey,
+// so the same group gets the same name on every run.
+#[derive(Debug, Clone, Default)]
+pub struct AttackerEntities {
+    representative: HashMap<String, String>,
+}
+
+impl AttackerEntities {
+    pub fn new(linked: &HashMap<String, String>, pairs: &[ColludingPair]) -> Self {
+        let edges = linked
+            .iter()
+            .map(|(wallet, entity)| (wallet.as_str(), entity.as_str()))
+            .chain(pairs.iter().map(|pair| (pair.frontrunner.as_str(), pair.backrunner.as_str())));
+        Self {
+            representative: group(edges),
+        }
+    }
+
+    pub fn entity<'a>(&'a self, wallet: &'a str) -> &'a str {
+        self.representative.get(wallet).map(String::as_str).unwrap_or(wallet)
+    }
+
+    pub fn same(&self, a: &str, b: &str) -> bool {
+        self.entity(a) == self.entity(b)
+    }
+
+    // the other wallets in `entity`'s group, sorted
+    pub fn members(&self, entity: &str) -> Vec<String> {
+        let mut members: Vec<String> = self
+            .representative
+            .iter()
+            .filter(|(wallet, rep)| rep.as_str() == entity && wallet.as_str() != entity)
+            .map(|(wallet, _)| wallet.clone())
+            .collect();
+        members.sort();
+        members
+    }
+}
+
+// `linked` with every wallet of each group joined into one entity, e.g. attackers found to share a
+// funder
+pub fn link_groups(linked: &HashMap<String, String>, groups: &[Vec<String>]) -> HashMap<String, String> {
+    let edges = linked
+        .iter()
+        .map(|(wallet, entity)| (wallet.as_str(), entity.as_str()))
+        .chain(groups.iter().flat_map(|wallets| wallets.windows(2).map(|pair| (pair[0].as_str(), pair[1].as_str()))));
+    group(edges)
+}
+
+// wallet -> smallest wallet of its connected group, for every wallet on an edge
+fn group<'a>(edges: impl Iterator<Item = (&'a str, &'a str)>) -> HashMap<String, String> {
+    let mut parent: HashMap<&str, &str> = HashMap::new();
+    for (a, b) in edges {
+        parent.entry(a).or_insert(a);
+        parent.entry(b).or_insert(b);
+        let (ra, rb) = (root(&parent, a), root(&parent, b));
+        // the smaller root wins, so every group ends up rooted at its smallest wallet
+        if ra < rb {
+            parent.insert(rb, ra);
+        } else if rb < ra {
+            parent.insert(ra, rb);
+        }
+    }
+    parent
+        .keys()
+        .map(|&wallet| (wallet.to_string(), root(&parent, wallet).to_string()))
+        .collect()
+}
+
+fn root<'a>(parent: &HashMap<&'a str, &'a str>, mut wallet: &'a str) -> &'a str {
+    while let Some(&next) = parent.get(wallet)
+        && next != wallet
+    {
+        wallet = next;
+    }
+    wallet
+}
+
+// One group of wallets per line, separated by whitespace or commas; `#` starts a comment.
+// A wallet on several lines joins them into one group.
+pub fn load_linked_wallets(path: &str) -> Result<HashMap<String, String>, Error> {
+    let raw = fs::read_to_string(path).map_err(|e| Error::io("read linked wallets", path, e))?;
+    let mut lines: Vec<Vec<&str>> = Vec::new();
+    for (number, line) in raw.lines().enumerate() {
+        let wallets: Vec<&str> = line
+            .split('#')
+            .next()
+            .unwrap_or_default()
+            .split(|c: char| c.is_whitespace() || c == ',')
+            .filter(|wallet| !wallet.is_empty())
+            .collect();
+        if let Some(wallet) = wallets.iter().find(|wallet| Pubkey::from_str(wallet).is_err()) {
+            return Err(Error::Config(format!(
+                "Invalid wallet '{}' on line {} of '{}'",
+                wallet,
+                number + 1,
+                path
+            )));
+        }
+        lines.push(wallets);
+    }
+    Ok(group(
+        lines
+            .iter()
+            .flat_map(|wallets| wallets.windows(2).map(|pair| (pair[0], pair[1]))),
+    ))
+}
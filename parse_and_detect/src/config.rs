@@ -0,0 +1,656 @@
+use crate::cli::{parse_commitment, parse_encoding, parse_output_format, CliArgs, OutputFormat};
+use rusty::detect::{DetectorConfig, DirectionRule, Severity, VictimMode, direction_profile};
+use rusty::error::Error;
+use rusty::network::{Network, PUMP_PROGRAM_ID};
+use solana_commitment_config::CommitmentLevel;
+use solana_transaction_status::UiTransactionEncoding;
+use std::fs;
+use toml_edit::{Document, Item, TableLike};
+
+#[derive(Debug, Clone)]
+pub struct RpcSettings {
+    pub urls: Vec<String>,
+    pub signature_limit: usize,
+    pub encoding: UiTransactionEncoding,
+    pub requests_per_second: f64,
+    pub burst: u32,
+    pub timeout_secs: u64,
+    // look up each trade's position in its block, one getBlock per slot
+    pub tx_index: bool,
+    // look up the validator that produced each sandwich's block
+    pub slot_leaders: bool,
+    // rank sandwich legs by compute-unit price against their whole block, one full getBlock
+    // per victim slot; otherwise only against the scanned trades in that slot
+    pub block_cu_prices: bool,
+    // epoch leader schedules kept between runs; without it they are fetched again every run
+    pub leader_schedule_cache: Option<String>,
+    // getTransaction calls sent per JSON-RPC batch; 1 sends them one by one
+    pub batch_size: usize,
+    // for getSignaturesForAddress and account reads; transactions and blocks need `confirmed`
+    pub commitment: CommitmentLevel,
+}
+
+impl Default for RpcSettings {
+    fn default() -> Self {
+        Self {
+            urls: Vec::new(),
+            signature_limit: 50,
+            encoding: UiTransactionEncoding::JsonParsed,
+            requests_per_second: 10.0,
+            burst: 10,
+            timeout_secs: 30,
+            tx_index: false,
+            slot_leaders: false,
+            block_cu_prices: false,
+            leader_schedule_cache: None,
+            batch_size: 1,
+            commitment: CommitmentLevel::Finalized,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct OutputSettings {
+    pub format: OutputFormat,
+    pub file: Option<String>,
+    // per-victim loss export, JSON when the path ends in .json and CSV otherwise
+    pub victims_file: Option<String>,
+    // labeled candidate features for classifier training, CSV unless the path ends in .json
+    pub dataset_file: Option<String>,
+    // one row per attack leg in Dune's column naming, CSV unless the path ends in .json
+    pub dune_file: Option<String>,
+    // sandwiches below this tier are left out of every output
+    pub min_severity: Severity,
+    // fetch the token image from the metadata uri, a host the token's creator picked
+    pub token_image: bool,
+}
+
+impl Default for OutputSettings {
+    fn default() -> Self {
+        Self {
+            format: OutputFormat::Text,
+            file: None,
+            victims_file: None,
+            dataset_file: None,
+            dune_file: None,
+            min_severity: Severity::Low,
+            token_image: false,
+        }
+    }
+}
+
+// where SOL/USD prices for valuing detections come from
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum PriceSource {
+    #[default]
+    None,
+    CoinGecko,
+    Fixed(f64),
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PriceSettings {
+    pub source: PriceSource,
+    pub cache_path: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SinkSettings {
+    pub nats_url: Option<String>,
+    pub nats_subject: String,
+    pub redis_url: Option<String>,
+    pub redis_stream: String,
+    pub redis_max_len: Option<u64>,
+}
+
+impl Default for SinkSettings {
+    fn default() -> Self {
+        Self {
+            nats_url: None,
+            nats_subject: "pumpfun".to_string(),
+            redis_url: None,
+            redis_stream: "pumpfun:detections".to_string(),
+            redis_max_len: Some(10_000),
+        }
+    }
+}
+
+// pubkey files loaded into DetectorConfig::excluded_wallets, forced_bots and linked_wallets,
+// and the known-bot lists (paths or URLs) loaded into known_bots on top of the bundled one
+#[derive(Debug, Clone)]
+pub struct WalletSettings {
+    pub exclude_file: Option<String>,
+    pub bots_file: Option<String>,
+    pub linked_file: Option<String>,
+    pub bundled_bot_list: bool,
+    pub bot_lists: Vec<String>,
+}
+
+impl Default for WalletSettings {
+    fn default() -> Self {
+        Self {
+            exclude_file: None,
+            bots_file: None,
+            linked_file: None,
+            bundled_bot_list: true,
+            bot_lists: Vec::new(),
+        }
+    }
+}
+
+// an external scorer filtering sandwich candidates, see `rusty::scoring`
+#[derive(Debug, Clone, Default)]
+pub struct ScoringSettings {
+    pub command: Option<String>,
+    pub url: Option<String>,
+    pub min_score: f64,
+}
+
+// funding-source tracing of the detected attackers, see `rusty::funding`; the label lists use
+// the bot-list format, `<pubkey> [label]` per line, from a path or URL
+#[derive(Debug, Clone)]
+pub struct FundingSettings {
+    pub enabled: bool,
+    pub max_depth: usize,
+    pub signature_limit: usize,
+    pub min_sol: f64,
+    pub labels: Vec<String>,
+}
+
+impl Default for FundingSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_depth: 3,
+            signature_limit: 100,
+            min_sol: 0.1,
+            labels: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct NetworkSettings {
+    pub network: Network,
+    // the bonding-curve program, for custom deployments
+    pub pump_program_id: String,
+}
+
+impl Default for NetworkSettings {
+    fn default() -> Self {
+        Self {
+            network: Network::Mainnet,
+            pump_program_id: PUMP_PROGRAM_ID.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct RunConfig {
+    pub detector: DetectorConfig,
+    pub rpc: RpcSettings,
+    pub output: OutputSettings,
+    pub price: PriceSettings,
+    pub sink: SinkSettings,
+    pub wallets: WalletSettings,
+    pub scoring: ScoringSettings,
+    pub funding: FundingSettings,
+    pub network: NetworkSettings,
+    pub registry_path: Option<String>,
+}
+
+impl RunConfig {
+    pub fn load(path: &str) -> Result<Self, Error> {
+        let raw = fs::read_to_string(path).map_err(|e| Error::io("read config file", path, e))?;
+        Self::from_toml(&raw).map_err(|e| Error::Config(format!("Invalid config file '{}': {}", path, e)))
+    }
+
+    pub fn from_toml(raw: &str) -> Result<Self, String> {
+        let doc = Document::parse(raw).map_err(|e| e.to_string())?;
+        let mut config = RunConfig::default();
+
+        for (section, item) in doc.as_table().iter() {
+            let table = item
+                .as_table_like()
+                .ok_or_else(|| format!("'{}' must be a table", section))?;
+            match section {
+                "detector" => apply_detector_section(&mut config.detector, table)?,
+                "rpc" => apply_rpc_section(&mut config.rpc, table)?,
+                "output" => apply_output_section(&mut config.output, table)?,
+                "registry" => apply_registry_section(&mut config.registry_path, table)?,
+                "price" => apply_price_section(&mut config.price, table)?,
+                "sink" => apply_sink_section(&mut config.sink, table)?,
+                "wallets" => apply_wallets_section(&mut config.wallets, table)?,
+                "scoring" => apply_scoring_section(&mut config.scoring, table)?,
+                "funding" => apply_funding_section(&mut config.funding, table)?,
+                "network" => apply_network_section(&mut config.network, table)?,
+                other => return Err(format!("unknown section [{}]", other)),
+            }
+        }
+
+        Ok(config)
+    }
+
+    pub fn apply_cli(&mut self, cli: &CliArgs) {
+        if let Some(network) = cli.network {
+            self.network.network = network;
+        }
+        if let Some(program) = &cli.pump_program_id {
+            self.network.pump_program_id = program.clone();
+        }
+        if let Some(encoding) = cli.encoding {
+            self.rpc.encoding = encoding;
+        }
+        if !cli.rpc_urls.is_empty() {
+            self.rpc.urls = cli.rpc_urls.clone();
+        }
+        if let Some(limit) = cli.signature_limit {
+            self.rpc.signature_limit = limit;
+        }
+        if let Some(gap) = cli.max_slot_gap {
+            self.detector.max_slot_gap = gap;
+        }
+        if cli.adaptive_slot_gap {
+            self.detector.adaptive_slot_gap = true;
+        }
+        if cli.strict_ordering {
+            self.detector.strict_ordering = true;
+        }
+        if let Some((frontrun, backrun)) = cli.direction_profile {
+            self.detector.frontrun_direction = frontrun;
+            self.detector.backrun_direction = backrun;
+        }
+        if let Some(mode) = cli.victim_mode {
+            self.detector.victim_mode = mode;
+        }
+        if let Some(sol) = cli.min_victim_abs_sol {
+            self.detector.min_victim_abs_sol = sol;
+        }
+        if let Some(tokens) = cli.min_victim_tokens {
+            self.detector.min_victim_tokens = tokens;
+        }
+        if let Some(sol) = cli.min_profit_sol {
+            self.detector.min_profit_sol = sol;
+        }
+        if let Some(trades) = cli.min_bot_trades {
+            self.detector.min_bot_trades = trades;
+        }
+        if let Some(format) = cli.output_format {
+            self.output.format = format;
+        }
+        if let Some(file) = &cli.output_file {
+            self.output.file = Some(file.clone());
+        }
+        if let Some(path) = &cli.victims_file {
+            self.output.victims_file = Some(path.clone());
+        }
+        if let Some(path) = &cli.dataset_file {
+            self.output.dataset_file = Some(path.clone());
+        }
+        if let Some(path) = &cli.dune_file {
+            self.output.dune_file = Some(path.clone());
+        }
+        if let Some(severity) = cli.min_severity {
+            self.output.min_severity = severity;
+        }
+        if cli.token_image {
+            self.output.token_image = true;
+        }
+        if let Some(command) = &cli.scorer_command {
+            self.scoring.command = Some(command.clone());
+            self.scoring.url = None;
+        }
+        if let Some(url) = &cli.scorer_url {
+            self.scoring.url = Some(url.clone());
+            self.scoring.command = None;
+        }
+        if let Some(score) = cli.min_score {
+            self.scoring.min_score = score;
+        }
+        if let Some(path) = &cli.registry_path {
+            self.registry_path = Some(path.clone());
+        }
+        if let Some(path) = &cli.exclude_wallets {
+            self.wallets.exclude_file = Some(path.clone());
+        }
+        if let Some(path) = &cli.bot_wallets {
+            self.wallets.bots_file = Some(path.clone());
+        }
+        self.wallets.bot_lists.extend(cli.bot_lists.iter().cloned());
+        if cli.no_bundled_bots {
+            self.wallets.bundled_bot_list = false;
+        }
+        if let Some(rps) = cli.requests_per_second {
+            self.rpc.requests_per_second = rps;
+        }
+        if let Some(burst) = cli.burst {
+            self.rpc.burst = burst;
+        }
+        if cli.tx_index {
+            self.rpc.tx_index = true;
+        }
+        if cli.slot_leaders {
+            self.rpc.slot_leaders = true;
+        }
+        if cli.block_cu_prices {
+            self.rpc.block_cu_prices = true;
+        }
+        if let Some(path) = &cli.leader_cache {
+            self.rpc.leader_schedule_cache = Some(path.clone());
+        }
+        if let Some(commitment) = cli.commitment {
+            self.rpc.commitment = commitment;
+        }
+        if let Some(size) = cli.batch_size {
+            self.rpc.batch_size = size;
+        }
+        if let Some(price) = cli.sol_usd {
+            self.price.source = PriceSource::Fixed(price);
+        } else if cli.usd {
+            self.price.source = PriceSource::CoinGecko;
+        }
+        if let Some(path) = &cli.price_cache {
+            self.price.cache_path = Some(path.clone());
+        }
+        if cli.trace_funding {
+            self.funding.enabled = true;
+        }
+        if let Some(depth) = cli.funding_depth {
+            self.funding.max_depth = depth;
+        }
+        if let Some(url) = &cli.nats_url {
+            self.sink.nats_url = Some(url.clone());
+        }
+        if let Some(subject) = &cli.nats_subject {
+            self.sink.nats_subject = subject.clone();
+        }
+        if let Some(url) = &cli.redis_url {
+            self.sink.redis_url = Some(url.clone());
+        }
+        if let Some(stream) = &cli.redis_stream {
+            self.sink.redis_stream = stream.clone();
+        }
+        if let Some(max_len) = cli.redis_max_len {
+            // 0 disables trimming
+            self.sink.redis_max_len = (max_len > 0).then_some(max_len);
+        }
+    }
+}
+
+fn apply_detector_section(detector: &mut DetectorConfig, table: &dyn TableLike) -> Result<(), String> {
+    // the profile first, so frontrun_direction / backrun_direction refine it wherever they appear
+    if let Some(item) = table.get("direction_profile") {
+        (detector.frontrun_direction, detector.backrun_direction) =
+            direction_profile(string("direction_profile", item)?)?;
+    }
+    for (key, item) in table.iter() {
+        match key {
+            "direction_profile" => {}
+            "frontrun_direction" => detector.frontrun_direction = DirectionRule::parse(string(key, item)?)?,
+            "backrun_direction" => detector.backrun_direction = DirectionRule::parse(string(key, item)?)?,
+            "victim_mode" => detector.victim_mode = VictimMode::parse(string(key, item)?)?,
+            "min_impact_loss_pct" => detector.min_impact_loss_pct = float(key, item)?,
+            "max_slot_gap" => detector.max_slot_gap = unsigned(key, item)?,
+            "adaptive_slot_gap" => {
+                detector.adaptive_slot_gap = item
+                    .as_bool()
+                    .ok_or_else(|| format!("'{}' must be a boolean", key))?
+            }
+            "strict_ordering" => {
+                detector.strict_ordering = item
+                    .as_bool()
+                    .ok_or_else(|| format!("'{}' must be a boolean", key))?
+            }
+            "adaptive_gap_factor" => detector.adaptive_gap_factor = float(key, item)?,
+            "min_adaptive_slot_gap" => detector.min_adaptive_slot_gap = unsigned(key, item)?,
+            "max_adaptive_slot_gap" => detector.max_adaptive_slot_gap = unsigned(key, item)?,
+            "min_victim_abs_sol" => detector.min_victim_abs_sol = float(key, item)?,
+            "min_victim_tokens" => detector.min_victim_tokens = float(key, item)?,
+            "min_profit_sol" => detector.min_profit_sol = float(key, item)?,
+            "min_victim_abs_token" => return Err("detector.min_victim_abs_token was replaced by min_victim_tokens, in whole tokens".to_string()),
+            "min_profit_lamports" => return Err("detector.min_profit_lamports was replaced by min_profit_sol, in SOL".to_string()),
+            "min_bot_trades" => detector.min_bot_trades = unsigned(key, item)? as usize,
+            "known_attacker_min_trades" => {
+                detector.known_attacker_min_trades = unsigned(key, item)? as usize
+            }
+            "copy_trade_max_lag_slots" => detector.copy_trade_max_lag_slots = unsigned(key, item)?,
+            "copy_trade_min_matches" => detector.copy_trade_min_matches = unsigned(key, item)? as usize,
+            "copy_trade_min_consistency" => detector.copy_trade_min_consistency = float(key, item)?,
+            "copy_trade_max_size_cv" => detector.copy_trade_max_size_cv = float(key, item)?,
+            "early_holder_slots" => detector.early_holder_slots = unsigned(key, item)?,
+            "dump_window_slots" => detector.dump_window_slots = unsigned(key, item)?,
+            "dump_min_supply_pct" => detector.dump_min_supply_pct = float(key, item)?,
+            "snipe_max_slot_delay" => detector.snipe_max_slot_delay = unsigned(key, item)?,
+            "snipe_min_sol" => detector.snipe_min_sol = float(key, item)?,
+            "backrun_min_victim_sol" => detector.backrun_min_victim_sol = float(key, item)?,
+            "limit_breach_weight" => detector.weights.limit_breach = float(key, item)?,
+            "breach_margin_pct" => detector.weights.breach_margin_pct = float(key, item)?,
+            "victim_size_weight" => detector.weights.victim_size = float(key, item)?,
+            "min_victim_score" => detector.weights.min_victim_score = float(key, item)?,
+            "direction_weight" => detector.weights.direction = float(key, item)?,
+            "proximity_weight" => detector.weights.proximity = float(key, item)?,
+            "min_leg_score" => detector.weights.min_leg_score = float(key, item)?,
+            "collusion_min_sandwiches" => {
+                detector.collusion_min_sandwiches = unsigned(key, item)? as usize
+            }
+            other => return Err(format!("unknown key detector.{}", other)),
+        }
+    }
+    Ok(())
+}
+
+fn apply_rpc_section(rpc: &mut RpcSettings, table: &dyn TableLike) -> Result<(), String> {
+    for (key, item) in table.iter() {
+        match key {
+            "url" => rpc.urls = vec![string(key, item)?.to_string()],
+            "urls" => rpc.urls = strings(key, item)?,
+            "signature_limit" => rpc.signature_limit = unsigned(key, item)? as usize,
+            "encoding" => rpc.encoding = parse_encoding(string(key, item)?)?,
+            "requests_per_second" => {
+                rpc.requests_per_second = float(key, item)?;
+                if rpc.requests_per_second <= 0.0 {
+                    return Err("'requests_per_second' must be positive".to_string());
+                }
+            }
+            "burst" => {
+                rpc.burst = u32::try_from(unsigned(key, item)?)
+                    .map_err(|_| format!("'{}' must be at most {}", key, u32::MAX))?
+            }
+            "timeout_secs" => rpc.timeout_secs = unsigned(key, item)?,
+            "tx_index" => {
+                rpc.tx_index = item
+                    .as_bool()
+                    .ok_or_else(|| format!("'{}' must be a boolean", key))?
+            }
+            "slot_leaders" => {
+                rpc.slot_leaders = item
+                    .as_bool()
+                    .ok_or_else(|| format!("'{}' must be a boolean", key))?
+            }
+            "block_cu_prices" => {
+                rpc.block_cu_prices = item
+                    .as_bool()
+                    .ok_or_else(|| format!("'{}' must be a boolean", key))?
+            }
+            "leader_schedule_cache" => rpc.leader_schedule_cache = Some(string(key, item)?.to_string()),
+            "commitment" => rpc.commitment = parse_commitment(string(key, item)?)?,
+            "batch_size" => {
+                rpc.batch_size = unsigned(key, item)? as usize;
+                if rpc.batch_size == 0 {
+                    return Err("'batch_size' must be at least 1".to_string());
+                }
+            }
+            other => return Err(format!("unknown key rpc.{}", other)),
+        }
+    }
+    Ok(())
+}
+
+fn apply_output_section(output: &mut OutputSettings, table: &dyn TableLike) -> Result<(), String> {
+    for (key, item) in table.iter() {
+        match key {
+            "format" => output.format = parse_output_format(string(key, item)?)?,
+            "file" => output.file = Some(string(key, item)?.to_string()),
+            "victims_file" => output.victims_file = Some(string(key, item)?.to_string()),
+            "dataset_file" => output.dataset_file = Some(string(key, item)?.to_string()),
+            "dune_file" => output.dune_file = Some(string(key, item)?.to_string()),
+            "min_severity" => output.min_severity = Severity::parse(string(key, item)?)?,
+            "token_image" => {
+                output.token_image = item
+                    .as_bool()
+                    .ok_or_else(|| format!("'{}' must be a boolean", key))?
+            }
+            other => return Err(format!("unknown key output.{}", other)),
+        }
+    }
+    Ok(())
+}
+
+fn apply_registry_section(registry_path: &mut Option<String>, table: &dyn TableLike) -> Result<(), String> {
+    for (key, item) in table.iter() {
+        match key {
+            "path" => *registry_path = Some(string(key, item)?.to_string()),
+            other => return Err(format!("unknown key registry.{}", other)),
+        }
+    }
+    Ok(())
+}
+
+fn apply_price_section(price: &mut PriceSettings, table: &dyn TableLike) -> Result<(), String> {
+    for (key, item) in table.iter() {
+        match key {
+            "source" => {
+                price.source = match string(key, item)? {
+                    "coingecko" => PriceSource::CoinGecko,
+                    "none" => PriceSource::None,
+                    other => return Err(format!("unknown price source '{}'. Expected coingecko or none", other)),
+                }
+            }
+            "sol_usd" => {
+                let value = float(key, item)?;
+                if value <= 0.0 {
+                    return Err("'sol_usd' must be positive".to_string());
+                }
+                price.source = PriceSource::Fixed(value);
+            }
+            "cache" => price.cache_path = Some(string(key, item)?.to_string()),
+            other => return Err(format!("unknown key price.{}", other)),
+        }
+    }
+    Ok(())
+}
+
+fn apply_sink_section(sink: &mut SinkSettings, table: &dyn TableLike) -> Result<(), String> {
+    for (key, item) in table.iter() {
+        match key {
+            "nats_url" => sink.nats_url = Some(string(key, item)?.to_string()),
+            "nats_subject" => sink.nats_subject = string(key, item)?.to_string(),
+            "redis_url" => sink.redis_url = Some(string(key, item)?.to_string()),
+            "redis_stream" => sink.redis_stream = string(key, item)?.to_string(),
+            "redis_max_len" => {
+                let max_len = unsigned(key, item)?;
+                sink.redis_max_len = (max_len > 0).then_some(max_len);
+            }
+            other => return Err(format!("unknown key sink.{}", other)),
+        }
+    }
+    Ok(())
+}
+
+fn apply_network_section(network: &mut NetworkSettings, table: &dyn TableLike) -> Result<(), String> {
+    for (key, item) in table.iter() {
+        match key {
+            "name" => network.network = Network::parse(string(key, item)?)?,
+            "pump_program_id" => network.pump_program_id = string(key, item)?.to_string(),
+            other => return Err(format!("unknown key network.{}", other)),
+        }
+    }
+    Ok(())
+}
+
+fn apply_scoring_section(scoring: &mut ScoringSettings, table: &dyn TableLike) -> Result<(), String> {
+    for (key, item) in table.iter() {
+        match key {
+            "command" => scoring.command = Some(string(key, item)?.to_string()),
+            "url" => scoring.url = Some(string(key, item)?.to_string()),
+            "min_score" => scoring.min_score = float(key, item)?,
+            other => return Err(format!("unknown key scoring.{}", other)),
+        }
+    }
+    if scoring.command.is_some() && scoring.url.is_some() {
+        return Err("set either scoring.command or scoring.url, not both".to_string());
+    }
+    Ok(())
+}
+
+fn apply_funding_section(funding: &mut FundingSettings, table: &dyn TableLike) -> Result<(), String> {
+    for (key, item) in table.iter() {
+        match key {
+            "enabled" => {
+                funding.enabled = item
+                    .as_bool()
+                    .ok_or_else(|| format!("'{}' must be a boolean", key))?
+            }
+            "max_depth" => funding.max_depth = unsigned(key, item)? as usize,
+            "signature_limit" => funding.signature_limit = unsigned(key, item)? as usize,
+            "min_sol" => funding.min_sol = float(key, item)?,
+            "labels" => funding.labels = strings(key, item)?,
+            other => return Err(format!("unknown key funding.{}", other)),
+        }
+    }
+    Ok(())
+}
+
+fn apply_wallets_section(wallets: &mut WalletSettings, table: &dyn TableLike) -> Result<(), String> {
+    for (key, item) in table.iter() {
+        match key {
+            "exclude" => wallets.exclude_file = Some(string(key, item)?.to_string()),
+            "bots" => wallets.bots_file = Some(string(key, item)?.to_string()),
+            "linked" => wallets.linked_file = Some(string(key, item)?.to_string()),
+            "bundled_bot_list" => {
+                wallets.bundled_bot_list = item
+                    .as_bool()
+                    .ok_or_else(|| format!("'{}' must be a boolean", key))?
+            }
+            "bot_lists" => wallets.bot_lists = strings(key, item)?,
+            other => return Err(format!("unknown key wallets.{}", other)),
+        }
+    }
+    Ok(())
+}
+
+fn integer(key: &str, item: &Item) -> Result<i64, String> {
+    item.as_integer()
+        .ok_or_else(|| format!("'{}' must be an integer", key))
+}
+
+fn unsigned(key: &str, item: &Item) -> Result<u64, String> {
+    let value = integer(key, item)?;
+    u64::try_from(value).map_err(|_| format!("'{}' must not be negative", key))
+}
+
+fn float(key: &str, item: &Item) -> Result<f64, String> {
+    item.as_float()
+        .or_else(|| item.as_integer().map(|value| value as f64))
+        .ok_or_else(|| format!("'{}' must be a number", key))
+}
+
+fn string<'a>(key: &str, item: &'a Item) -> Result<&'a str, String> {
+    item.as_str()
+        .ok_or_else(|| format!("'{}' must be a string", key))
+}
+
+fn strings(key: &str, item: &Item) -> Result<Vec<String>, String> {
+    let array = item
+        .as_array()
+        .ok_or_else(|| format!("'{}' must be an array of strings", key))?;
+    array
+        .iter()
+        .map(|value| {
+            value
+                .as_str()
+                .map(str::to_string)
+                .ok_or_else(|| format!("'{}' must be an array of strings", key))
+        })
+        .collect()
+}
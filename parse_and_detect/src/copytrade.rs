@@ -0,0 +1,104 @@
+use crate::detect::DetectorConfig;
+use crate::parser::pumpfun::{ExecutionOutcome, ParsedTransaction, TradeType};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CopyTradePair {
+    pub leader: String,
+    pub follower: String,
+    pub matched_trades: usize,
+    pub follower_trades: usize,
+    pub mean_size_ratio: f64,
+    pub mints: Vec<String>,
+}
+
+#[derive(Default)]
+struct PairTally<'a> {
+    ratios: HashMap<(&'a str, usize), f64>,
+    mints: BTreeSet<&'a str>,
+}
+
+pub fn detect_copy_traders(trades: &[ParsedTransaction], cfg: &DetectorConfig) -> Vec<CopyTradePair> {
+    let mut legs: HashMap<(&str, TradeType), Vec<&ParsedTransaction>> = HashMap::new();
+    let mut trades_per_signer: HashMap<&str, usize> = HashMap::new();
+    for tx in trades {
+        if tx.outcome == ExecutionOutcome::Reverted {
+            continue;
+        }
+        legs.entry((tx.mint.as_ref(), tx.trade_type)).or_default().push(tx);
+        *trades_per_signer.entry(tx.signer.as_ref()).or_default() += 1;
+    }
+
+    let mut pairs: HashMap<(&str, &str), PairTally> = HashMap::new();
+    for sorted in legs.values_mut() {
+        sorted.sort_by(|a, b| (a.slot, &a.signature, a.leg_index).cmp(&(b.slot, &b.signature, b.leg_index)));
+
+        for (idx, leader) in sorted.iter().enumerate() {
+            let leader_size = trade_size(leader);
+            if leader_size == 0 {
+                continue;
+            }
+            let max_slot = leader.slot.saturating_add(cfg.copy_trade_max_lag_slots);
+            for follower in sorted[idx + 1..].iter().take_while(|tx| tx.slot <= max_slot) {
+                if follower.signer == leader.signer {
+                    continue;
+                }
+                let tally = pairs
+                    .entry((leader.signer.as_ref(), follower.signer.as_ref()))
+                    .or_default();
+                tally
+                    .ratios
+                    .entry((follower.signature.as_ref(), follower.leg_index))
+                    .or_insert(trade_size(follower) as f64 / leader_size as f64);
+                tally.mints.insert(leader.mint.as_ref());
+            }
+        }
+    }
+
+    let mut found: Vec<CopyTradePair> = pairs
+        .into_iter()
+        .filter_map(|((leader, follower), tally)| {
+            let matched = tally.ratios.len();
+            let follower_trades = trades_per_signer.get(follower).copied().unwrap_or(0);
+            if matched < cfg.copy_trade_min_matches || follower_trades == 0 {
+                return None;
+            }
+            if (matched as f64 / follower_trades as f64) < cfg.copy_trade_min_consistency {
+                return None;
+            }
+
+            let mean = tally.ratios.values().sum::<f64>() / matched as f64;
+            let variance = tally
+                .ratios
+                .values()
+                .map(|ratio| (ratio - mean).powi(2))
+                .sum::<f64>()
+                / matched as f64;
+            if mean <= 0.0 || variance.sqrt() / mean > cfg.copy_trade_max_size_cv {
+                return None;
+            }
+
+            Some(CopyTradePair {
+                leader: leader.to_string(),
+                follower: follower.to_string(),
+                matched_trades: matched,
+                follower_trades,
+                mean_size_ratio: mean,
+                mints: tally.mints.into_iter().map(str::to_string).collect(),
+            })
+        })
+        .collect();
+
+    found.sort_by(|a, b| {
+        b.matched_trades
+            .cmp(&a.matched_trades)
+            .then_with(|| a.follower.cmp(&b.follower))
+            .then_with(|| a.leader.cmp(&b.leader))
+    });
+    found
+}
+
+fn trade_size(tx: &ParsedTransaction) -> u64 {
+    tx.trade_sol_change().unsigned_abs()
+}
@@ -0,0 +1,113 @@
+use crate::detect::DetectionSummary;
+use crate::error::Error;
+use crate::fetch::TransactionSource;
+use crate::parser::pumpfun::ParsedTransaction;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+// Where the victim and the attacker legs in its slot rank by compute-unit price among that
+// slot's transactions. A frontrun bidding well above the victim (and the rest of the block) is
+// evidence it was placed ahead of the victim on purpose rather than landing there by chance.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CuPriceRanking {
+    // the transactions ranked against: the whole block when it was fetched, otherwise the
+    // scanned trades in that slot
+    pub ranked_transactions: usize,
+    pub full_block: bool,
+    pub victim_cu_price: u64,
+    pub victim_percentile: f64,
+    // highest-bidding attacker leg on each side, in the victim's slot only
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub frontrun_cu_price: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub frontrun_percentile: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backrun_cu_price: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backrun_percentile: Option<f64>,
+}
+
+// sorted compute-unit prices of one slot
+#[derive(Debug, Clone, Default)]
+pub struct SlotPrices {
+    pub prices: Vec<u64>,
+    pub full_block: bool,
+}
+
+impl SlotPrices {
+    fn new(mut prices: Vec<u64>, full_block: bool) -> Self {
+        prices.sort_unstable();
+        Self { prices, full_block }
+    }
+
+    // share of the slot paying less, counting ties as half, so the median price sits at 50
+    pub fn percentile(&self, price: u64) -> f64 {
+        if self.prices.is_empty() {
+            return 0.0;
+        }
+        let below = self.prices.partition_point(|&other| other < price);
+        let equal = self.prices.partition_point(|&other| other <= price) - below;
+        (below as f64 + equal as f64 / 2.0) / self.prices.len() as f64 * 100.0
+    }
+}
+
+// one price per transaction (legs of the same transaction share it), by slot
+pub fn trade_cu_prices(trades: &[ParsedTransaction]) -> BTreeMap<u64, SlotPrices> {
+    let mut seen: HashSet<&str> = HashSet::new();
+    let mut by_slot: BTreeMap<u64, Vec<u64>> = BTreeMap::new();
+    for trade in trades {
+        if seen.insert(&trade.signature) {
+            by_slot.entry(trade.slot).or_default().push(trade.compute_unit_price);
+        }
+    }
+    by_slot
+        .into_iter()
+        .map(|(slot, prices)| (slot, SlotPrices::new(prices, false)))
+        .collect()
+}
+
+// Replaces the scanned-trade prices of every victim slot with its full block, one getBlock per
+// slot. Slots whose block can't be fetched keep the scanned prices.
+pub fn fetch_block_cu_prices(
+    source: &dyn TransactionSource,
+    summary: &DetectionSummary,
+    prices: &mut BTreeMap<u64, SlotPrices>,
+) -> Vec<Error> {
+    let slots: BTreeSet<u64> = summary.sandwiches.iter().map(|det| det.victim.slot).collect();
+    let mut failures = Vec::new();
+    for slot in slots {
+        match source.block_compute_unit_prices(slot) {
+            Ok(block) => {
+                prices.insert(slot, SlotPrices::new(block, true));
+            }
+            Err(e) => failures.push(e),
+        }
+    }
+    failures
+}
+
+pub fn rank_cu_prices(summary: &mut DetectionSummary, prices: &BTreeMap<u64, SlotPrices>) {
+    for det in &mut summary.sandwiches {
+        let slot = det.victim.slot;
+        let Some(slot_prices) = prices.get(&slot) else {
+            continue;
+        };
+        let highest = |legs: &[ParsedTransaction]| {
+            legs.iter()
+                .filter(|leg| leg.slot == slot)
+                .map(|leg| leg.compute_unit_price)
+                .max()
+        };
+        let (front, back) = (highest(&det.frontruns), highest(&det.backruns));
+        det.cu_price = Some(CuPriceRanking {
+            ranked_transactions: slot_prices.prices.len(),
+            full_block: slot_prices.full_block,
+            victim_cu_price: det.victim.compute_unit_price,
+            victim_percentile: slot_prices.percentile(det.victim.compute_unit_price),
+            frontrun_cu_price: front,
+            frontrun_percentile: front.map(|price| slot_prices.percentile(price)),
+            backrun_cu_price: back,
+            backrun_percentile: back.map(|price| slot_prices.percentile(price)),
+        });
+    }
+}
@@ -0,0 +1,331 @@
+use crate::cli::DaemonArgs;
+use crate::config::RunConfig;
+use crate::{dashboard, fetch_trades, report};
+use indicatif::ProgressBar;
+use rusty::alt::LookupTableCache;
+use rusty::detect::DetectorConfig;
+use rusty::error::Error;
+use rusty::fetch::{self, SIGNATURE_PAGE, TransactionSource};
+use rusty::sink::{self, EventSink};
+use rusty::streaming::{DetectionEvent, StreamingDetector};
+use rusty::token::{self, MintMetadata};
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+// Keeps a set of mints under surveillance: every cycle fetches the signatures newer than each
+// mint's checkpoint, feeds the new trades through a streaming detector, appends every detection
+// to the events log and prints an alert for sandwiches.
+
+// signatures remembered per mint, newest first, to skip ones a later page hands out again
+const RECENT_SIGNATURES: usize = 1_000;
+
+// how far back a cycle lists to reach its checkpoint; a longer backlog leaves a gap, reported
+const MAX_BACKLOG: usize = 100 * SIGNATURE_PAGE;
+
+// a front- or back-run event is re-emitted as its victim gathers legs: (kind, victim signature, leg)
+type GrowingKey = (&'static str, Arc<str>, usize);
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct MintCheckpoint {
+    last_signature: Option<String>,
+    last_slot: u64,
+    scans: u64,
+    detections: u64,
+    #[serde(default)]
+    duplicates: u64,
+    #[serde(default)]
+    recent_signatures: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct DaemonState {
+    mints: BTreeMap<String, MintCheckpoint>,
+}
+
+impl DaemonState {
+    fn load(path: &str) -> Result<Self, Error> {
+        if !Path::new(path).exists() {
+            return Ok(Self::default());
+        }
+        let raw = fs::read_to_string(path).map_err(|e| Error::io("read daemon state", path, e))?;
+        serde_json::from_str(&raw).map_err(|e| Error::json(format!("daemon state '{}'", path), e))
+    }
+
+    fn save(&self, path: &str) -> Result<(), Error> {
+        let json = serde_json::to_string_pretty(self).map_err(|e| Error::json("daemon state", e))?;
+        fs::write(path, json).map_err(|e| Error::io("write daemon state", path, e))
+    }
+}
+
+struct Watcher<'a> {
+    source: &'a dyn TransactionSource,
+    config: &'a RunConfig,
+    events_path: &'a str,
+    sinks: &'a mut [Box<dyn EventSink>],
+    // detector windows live in memory only; after a restart detection resumes from the checkpoint
+    detectors: HashMap<String, StreamingDetector>,
+    // per mint, the growing events already logged and their victim's slot, while it can still grow
+    logged: HashMap<String, HashMap<GrowingKey, u64>>,
+    lookup_tables: LookupTableCache,
+    // looked up on a mint's first scan; a failed lookup is not retried and alerts show the mint
+    metadata: HashMap<String, Option<MintMetadata>>,
+}
+
+pub fn run(
+    args: &DaemonArgs,
+    source: &dyn TransactionSource,
+    config: &RunConfig,
+    sinks: &mut [Box<dyn EventSink>],
+    quiet: bool,
+) -> Result<(), Error> {
+    let mints = watched_mints(args)?;
+    let mut state = DaemonState::load(&args.state_path)?;
+    let mut watcher = Watcher {
+        source,
+        config,
+        events_path: &args.events_path,
+        sinks,
+        detectors: HashMap::new(),
+        logged: HashMap::new(),
+        lookup_tables: LookupTableCache::new(),
+        metadata: HashMap::new(),
+    };
+
+    report::status(
+        quiet,
+        &format!("Watching {} mints every {}s", mints.len(), args.interval_secs),
+    );
+    if let Some(addr) = &args.serve {
+        dashboard::spawn(addr, &args.events_path, args.interval_secs)?;
+        report::status(quiet, &format!("Dashboard at http://{}/", addr));
+    }
+    loop {
+        for (mint_str, mint) in &mints {
+            let checkpoint = state.mints.entry(mint_str.clone()).or_default();
+            // a failing mint is retried next cycle instead of stopping the others
+            match watcher.scan(mint_str, mint, checkpoint) {
+                Ok(0) => {}
+                Ok(count) => report::status(quiet, &format!("{}: {} new detections", mint_str, count)),
+                Err(e) => eprintln!("Scan of {} failed: {}", mint_str, e),
+            }
+        }
+        state.save(&args.state_path)?;
+
+        if args.once {
+            return Ok(());
+        }
+        thread::sleep(Duration::from_secs(args.interval_secs));
+    }
+}
+
+fn watched_mints(args: &DaemonArgs) -> Result<Vec<(String, Pubkey)>, Error> {
+    let mut names = args.mints.clone();
+    if let Some(path) = &args.mints_file {
+        let raw = fs::read_to_string(path).map_err(|e| Error::io("read mints file", path, e))?;
+        names.extend(
+            raw.lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                .map(str::to_string),
+        );
+    }
+    names.sort();
+    names.dedup();
+    if names.is_empty() {
+        return Err(Error::Usage("No mints to watch".to_string()));
+    }
+    names
+        .into_iter()
+        .map(|name| {
+            let mint = Pubkey::from_str(&name).map_err(|_| Error::InvalidMint(name.clone()))?;
+            Ok((name, mint))
+        })
+        .collect()
+}
+
+impl Watcher<'_> {
+    fn scan(&mut self, mint_str: &str, mint: &Pubkey, checkpoint: &mut MintCheckpoint) -> Result<usize, Error> {
+        let limit = self.config.rpc.signature_limit;
+        let mut signatures = match &checkpoint.last_signature {
+            // Everything back to the checkpoint, so the cursor never jumps over signatures nobody
+            // fetched; a backlog over `limit` is worked off oldest first across cycles.
+            Some(until) => {
+                let mut newer = self.source.signatures_until(mint, until, MAX_BACKLOG)?;
+                if newer.len() == MAX_BACKLOG {
+                    eprintln!(
+                        "{}: over {} signatures since the checkpoint, older ones are skipped",
+                        mint_str, MAX_BACKLOG
+                    );
+                }
+                newer.drain(..newer.len().saturating_sub(limit));
+                newer
+            }
+            None => self.source.signatures(mint, limit)?,
+        };
+        checkpoint.scans += 1;
+        let Some(newest) = signatures.first().cloned() else {
+            return Ok(0);
+        };
+        let mut seen: HashSet<String> = checkpoint.recent_signatures.iter().cloned().collect();
+        checkpoint.duplicates += fetch::drop_seen_signatures(&mut signatures, &mut seen) as u64;
+        // the cursor still moves past a page of nothing but repeats
+        if signatures.is_empty() {
+            checkpoint.last_signature = Some(newest);
+            return Ok(0);
+        }
+
+        let fetched = fetch_trades(
+            self.source,
+            &signatures,
+            mint_str,
+            &self.config.network.pump_program_id,
+            &self.config.rpc,
+            &mut self.lookup_tables,
+            &ProgressBar::hidden(),
+        );
+        let mut trades = fetched.trades;
+        // signatures arrive newest first, the detector expects chain order
+        trades.reverse();
+        trades.sort_by_key(|trade| trade.slot);
+        if let Some(last) = trades.last() {
+            checkpoint.last_slot = checkpoint.last_slot.max(last.slot);
+        }
+
+        let new_trades = trades.clone();
+        let detector = self
+            .detectors
+            .entry(mint_str.to_string())
+            .or_insert_with(|| {
+                let decimals = token::fetch_decimals(self.source, mint).unwrap_or_else(|e| {
+                    eprintln!("Mint decimals for {} unavailable, assuming {}: {}", mint_str, token::DEFAULT_DECIMALS, e);
+                    token::DEFAULT_DECIMALS
+                });
+                StreamingDetector::new(DetectorConfig {
+                    token_decimals: decimals,
+                    ..self.config.detector.clone()
+                })
+            });
+        let min_severity = self.config.output.min_severity;
+        let events: Vec<DetectionEvent> = trades
+            .into_iter()
+            .flat_map(|trade| detector.push(trade))
+            .filter(|event| !matches!(event, DetectionEvent::Sandwich(det) if det.severity < min_severity))
+            .collect();
+        let window = self.config.detector.widest_slot_gap();
+        let logged = self.logged.entry(mint_str.to_string()).or_default();
+        let events = drop_repeats(events, logged, checkpoint.last_slot, window);
+        append_events(self.events_path, mint_str, &events)?;
+        // the events log is the record; a sink that is down only misses this cycle
+        for sink in self.sinks.iter_mut() {
+            let published = sink::publish_trades(sink.as_mut(), mint_str, &new_trades)
+                .and_then(|()| sink::publish_events(sink.as_mut(), mint_str, &events))
+                .and_then(|()| sink.flush());
+            if let Err(e) = published {
+                eprintln!("Publishing {} detections failed: {}", mint_str, e);
+            }
+        }
+        let source = self.source;
+        let metadata = self.metadata.entry(mint_str.to_string()).or_insert_with(|| {
+            token::fetch_metadata(source, mint)
+                .map_err(|e| eprintln!("Token metadata for {} unavailable: {}", mint_str, e))
+                .ok()
+        });
+        let label = report::mint_label(mint_str, metadata.as_ref());
+        for event in &events {
+            report::print_alert(&label, event);
+        }
+
+        checkpoint.last_signature = Some(newest);
+        checkpoint.detections += events.len() as u64;
+        checkpoint.recent_signatures.splice(0..0, signatures.iter().cloned());
+        checkpoint.recent_signatures.truncate(RECENT_SIGNATURES);
+        Ok(events.len())
+    }
+}
+
+fn growing_key(event: &DetectionEvent) -> Option<(GrowingKey, u64)> {
+    let (kind, victim) = match event {
+        DetectionEvent::FrontRun(event) => ("front_run", &event.victim),
+        DetectionEvent::BackRun(event) => ("back_run", &event.victim),
+        _ => return None,
+    };
+    Some(((kind, victim.signature.clone(), victim.leg_index), victim.slot))
+}
+
+// A cycle's events with each growing one once, in its latest form; one an earlier cycle already
+// logged is skipped. Keys are forgotten once their victim is more than `window` slots behind.
+fn drop_repeats(
+    events: Vec<DetectionEvent>,
+    logged: &mut HashMap<GrowingKey, u64>,
+    last_slot: u64,
+    window: u64,
+) -> Vec<DetectionEvent> {
+    let mut kept: Vec<DetectionEvent> = Vec::new();
+    let mut positions: HashMap<GrowingKey, usize> = HashMap::new();
+    for event in events {
+        let Some((key, slot)) = growing_key(&event) else {
+            kept.push(event);
+            continue;
+        };
+        if let Some(&position) = positions.get(&key) {
+            kept[position] = event;
+        } else if let Entry::Vacant(entry) = logged.entry(key.clone()) {
+            entry.insert(slot);
+            positions.insert(key, kept.len());
+            kept.push(event);
+        }
+    }
+    logged.retain(|_, slot| slot.saturating_add(window) >= last_slot);
+    kept
+}
+
+// a line of the events log, as written by `append_events`
+#[derive(Deserialize)]
+pub struct LoggedEvent {
+    pub mint: String,
+    pub detected_at: u64,
+    pub event: DetectionEvent,
+}
+
+pub fn load_events(path: &str) -> Result<Vec<LoggedEvent>, Error> {
+    let raw = fs::read_to_string(path).map_err(|e| Error::io("read events log", path, e))?;
+    raw.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(number, line)| {
+            serde_json::from_str(line).map_err(|e| Error::json(format!("line {} of events log '{}'", number + 1, path), e))
+        })
+        .collect()
+}
+
+// one JSON object per line, so consumers can tail the file
+fn append_events(path: &str, mint: &str, events: &[DetectionEvent]) -> Result<(), Error> {
+    if events.is_empty() {
+        return Ok(());
+    }
+    let detected_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default();
+    let mut lines = String::new();
+    for event in events {
+        let record = serde_json::json!({ "mint": mint, "detected_at": detected_at, "event": event });
+        lines.push_str(&record.to_string());
+        lines.push('\n');
+    }
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut file| file.write_all(lines.as_bytes()))
+        .map_err(|e| Error::io("append to events log", path, e))
+}
@@ -0,0 +1,73 @@
+use rusty::error::Error;
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::thread;
+
+// The daemon's HTTP API: `GET /api/events` returns the events log as a JSON array, and `GET /`
+// the bundled dashboard, which polls it once per scan interval. Read-only and unauthenticated,
+// so bind it to localhost unless the network in front of it is trusted.
+
+const PAGE: &str = include_str!("dashboard.html");
+
+pub fn spawn(addr: &str, events_path: &str, refresh_secs: u64) -> Result<(), Error> {
+    let listener = TcpListener::bind(addr).map_err(|e| Error::io("listen on", addr, e))?;
+    let page = PAGE.replace("__REFRESH_SECS__", &refresh_secs.to_string());
+    let events_path = events_path.to_string();
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            // a client that hangs up mid-request only loses its own response
+            if let Ok(stream) = stream
+                && let Err(e) = respond(stream, &page, &events_path)
+            {
+                eprintln!("Dashboard request failed: {}", e);
+            }
+        }
+    });
+    Ok(())
+}
+
+fn respond(mut stream: TcpStream, page: &str, events_path: &str) -> std::io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    // headers are read and ignored
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 2 {
+        header.clear();
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let (method, target) = (parts.next().unwrap_or(""), parts.next().unwrap_or(""));
+    let path = target.split('?').next().unwrap_or("");
+    let (status, content_type, body) = match (method, path) {
+        ("GET", "/" | "/index.html") => ("200 OK", "text/html; charset=utf-8", page.to_string()),
+        ("GET", "/api/events") => match events_json(events_path) {
+            Ok(json) => ("200 OK", "application/json", json),
+            Err(e) => ("500 Internal Server Error", "text/plain", e.to_string()),
+        },
+        ("GET", _) => ("404 Not Found", "text/plain", "not found".to_string()),
+        _ => ("405 Method Not Allowed", "text/plain", "only GET is served".to_string()),
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nCache-Control: no-store\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    )?;
+    stream.flush()
+}
+
+// the log's lines are already JSON objects, so they are joined rather than parsed; no log yet
+// means no detections yet
+fn events_json(path: &str) -> Result<String, Error> {
+    let raw = match fs::read_to_string(path) {
+        Ok(raw) => raw,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(Error::io("read events log", path, e)),
+    };
+    let lines: Vec<&str> = raw.lines().map(str::trim).filter(|line| !line.is_empty()).collect();
+    Ok(format!("[{}]", lines.join(",")))
+}
@@ -0,0 +1,113 @@
+use crate::detect::{DetectionSummary, DetectorConfig, LegIndex, bot_signers, leg_trade_value, order_key, same_leg};
+use crate::parser::pumpfun::{ExecutionOutcome, ParsedTransaction};
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+
+// One frontrun/victim/backrun triple the detector considered, with the features a classifier
+// would see and whether the heuristics called it a sandwich.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CandidateFeatures {
+    pub victim_signature: String,
+    pub victim_leg: usize,
+    pub front_signature: String,
+    pub back_signature: String,
+    pub front_slot_gap: u64,
+    pub back_slot_gap: u64,
+    // frontrun SOL over victim SOL
+    pub front_victim_size_ratio: f64,
+    // backrun tokens over frontrun tokens, 1.0 when the bot sold exactly what it bought
+    pub back_front_token_ratio: f64,
+    // frontrun priority fee minus the victim's, in lamports
+    pub priority_fee_delta: i64,
+    pub same_signer: bool,
+    pub profit_lamports: i64,
+    pub label: bool,
+}
+
+// Every recognised-bot leg on the victim's side before it, paired with that signer's first
+// backrun after it or, failing that, the first backrun by anyone. Labels come from `summary`,
+// which should be the detection run over the same trades.
+pub fn candidate_features(
+    trades: &[ParsedTransaction],
+    cfg: &DetectorConfig,
+    summary: &DetectionSummary,
+) -> Vec<CandidateFeatures> {
+    let bots = bot_signers(trades, cfg);
+    let index = LegIndex::build(trades, &bots, cfg);
+    let mut victims: Vec<&ParsedTransaction> = trades
+        .iter()
+        .filter(|tx| tx.outcome != ExecutionOutcome::Reverted)
+        .collect();
+    victims.sort_by(|a, b| order_key(a).cmp(&order_key(b)));
+
+    let mut rows = Vec::new();
+    for victim in victims {
+        let backruns = index.backruns(victim, cfg);
+        for front in index.frontruns(victim, cfg) {
+            if front.signer == victim.signer {
+                continue;
+            }
+            let Some(back) = backruns
+                .iter()
+                .find(|back| back.signer == front.signer)
+                .or_else(|| backruns.first())
+            else {
+                continue;
+            };
+            let label = summary.sandwiches.iter().any(|det| {
+                same_leg(&det.victim, victim)
+                    && det.frontruns.iter().any(|leg| same_leg(leg, &front))
+                    && det.backruns.iter().any(|leg| same_leg(leg, back))
+            });
+            let costs = (front.costs() + back.costs()) as i64;
+            rows.push(CandidateFeatures {
+                victim_signature: victim.signature.to_string(),
+                victim_leg: victim.leg_index,
+                front_signature: front.signature.to_string(),
+                back_signature: back.signature.to_string(),
+                front_slot_gap: victim.slot - front.slot,
+                back_slot_gap: back.slot - victim.slot,
+                front_victim_size_ratio: ratio(leg_trade_value(&front), leg_trade_value(victim)),
+                back_front_token_ratio: ratio(back.token_change, front.token_change),
+                priority_fee_delta: front.priority_fee as i64 - victim.priority_fee as i64,
+                same_signer: front.signer == back.signer,
+                profit_lamports: leg_trade_value(&front) + leg_trade_value(back) - costs,
+                label,
+            });
+        }
+    }
+    rows
+}
+
+pub fn features_csv(rows: &[CandidateFeatures]) -> String {
+    let mut csv = String::from(
+        "victim_signature,victim_leg,front_signature,back_signature,front_slot_gap,back_slot_gap,\
+front_victim_size_ratio,back_front_token_ratio,priority_fee_delta,same_signer,profit_lamports,label\n",
+    );
+    for row in rows {
+        let _ = writeln!(
+            csv,
+            "{},{},{},{},{},{},{:.6},{:.6},{},{},{},{}",
+            row.victim_signature,
+            row.victim_leg,
+            row.front_signature,
+            row.back_signature,
+            row.front_slot_gap,
+            row.back_slot_gap,
+            row.front_victim_size_ratio,
+            row.back_front_token_ratio,
+            row.priority_fee_delta,
+            u8::from(row.same_signer),
+            row.profit_lamports,
+            u8::from(row.label)
+        );
+    }
+    csv
+}
+
+fn ratio(numerator: i64, denominator: i64) -> f64 {
+    if denominator == 0 {
+        return 0.0;
+    }
+    (numerator as f64 / denominator as f64).abs()
+}
@@ -1,227 +1,1321 @@
-use crate::parser::pumpfun::{ParsedTransaction, TradeType};
-use std::collections::{BTreeMap, HashMap, HashSet};
+use crate::amm::{FeeSchedule, LAMPORTS_PER_SOL, PumpAmmState};
+use crate::collusion::{AttackerEntities, ColludingPair, colluding_pairs};
+use crate::copytrade::{CopyTradePair, detect_copy_traders};
+use crate::cuprice::CuPriceRanking;
+use crate::detector::{CustomDetection, Detector, Window, builtin_detectors};
+use crate::funding::{FundingChain, SharedFunder};
+use crate::latency::{LatencyDistribution, SandwichLatency, latency_distribution};
+use crate::leaderboard::{AttackerPerformance, AttackerStats, attacker_leaderboard, attacker_performance};
+use crate::leaders::{LeaderStats, LeaderTargeting, leader_stats};
+use crate::parser::pumpfun::{ExecutionOutcome, ParsedTransaction, TradeType, Venue};
+use crate::price::UsdValuation;
+use crate::rugpull::DevDumpEvent;
+use crate::snapshot::ReserveSnapshot;
+use crate::sniping::SniperEvent;
+use crate::streaming::DetectionEvent;
+use crate::tips::{TipStatistics, tip_statistics};
+use crate::token::{self, DEFAULT_DECIMALS, MintMetadata};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::sync::Arc;
 
-#[derive(Debug, Clone)]
+// percentage points added to a sandwich's confidence when an attacker is a known bot
+const KNOWN_BOT_CONFIDENCE_BOOST: u32 = 20;
+// a frontrun in the victim's slot that bid a higher compute-unit price to get ahead of it
+const OUTBID_CONFIDENCE_BOOST: u32 = 10;
+
+// (tier, minimum victim loss in lamports, minimum loss as % of the victim's trade); a sandwich
+// takes the highest tier either measure reaches
+const SEVERITY_TIERS: [(Severity, u64, f64); 3] = [
+    (Severity::Critical, 5_000_000_000, 20.0),
+    (Severity::High, 1_000_000_000, 10.0),
+    (Severity::Medium, 100_000_000, 3.0),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize, Deserialize)]
+pub enum Severity {
+    #[default]
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+impl Severity {
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value.to_ascii_lowercase().as_str() {
+            "low" => Ok(Severity::Low),
+            "medium" => Ok(Severity::Medium),
+            "high" => Ok(Severity::High),
+            "critical" => Ok(Severity::Critical),
+            other => Err(format!("Unknown severity '{}': use low, medium, high or critical", other)),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Severity::Low => "low",
+            Severity::Medium => "medium",
+            Severity::High => "high",
+            Severity::Critical => "critical",
+        }
+    }
+
+    // the victim's loss against what it traded, fees and tips excluded
+    pub fn classify(victim: &ParsedTransaction) -> Self {
+        Self::classify_loss(victim, victim_loss_lamports(victim))
+    }
+
+    pub fn classify_loss(victim: &ParsedTransaction, loss: u64) -> Self {
+        let traded = victim.trade_sol_change().unsigned_abs();
+        let loss_pct = if traded == 0 { 0.0 } else { loss as f64 * 100.0 / traded as f64 };
+        SEVERITY_TIERS
+            .iter()
+            .find(|(_, min_loss, min_pct)| loss >= *min_loss || loss_pct >= *min_pct)
+            .map_or(Severity::Low, |(tier, _, _)| *tier)
+    }
+}
+
+// A sandwich nothing could have landed inside: the attacker legs share the victim's transaction
+// (its swap was wrapped into the attacker's), or sit in the block positions right before and
+// after it, the way a bundle lands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Atomicity {
+    Transaction,
+    Bundle,
+}
+
+impl Atomicity {
+    pub fn name(self) -> &'static str {
+        match self {
+            Atomicity::Transaction => "single transaction",
+            Atomicity::Bundle => "bundle",
+        }
+    }
+
+    // bundle adjacency needs block positions, see `fetch::assign_tx_indexes`
+    pub fn classify(
+        victim: &ParsedTransaction,
+        frontruns: &[ParsedTransaction],
+        backruns: &[ParsedTransaction],
+    ) -> Option<Self> {
+        let inside = |legs: &[ParsedTransaction]| legs.iter().any(|leg| leg.signature == victim.signature);
+        if inside(frontruns) && inside(backruns) {
+            return Some(Atomicity::Transaction);
+        }
+        let position = victim.tx_index?;
+        // either side may also sit inside the victim's own transaction
+        let adjacent = |legs: &[ParsedTransaction], offset: isize| {
+            legs.iter().any(|leg| {
+                leg.slot == victim.slot
+                    && (leg.signature == victim.signature
+                        || leg.tx_index.is_some_and(|index| index as isize == position as isize + offset))
+            })
+        };
+        (adjacent(frontruns, -1) && adjacent(backruns, 1)).then_some(Atomicity::Bundle)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SandwichDetection {
     pub victim: ParsedTransaction,
     pub frontruns: Vec<ParsedTransaction>,
     pub backruns: Vec<ParsedTransaction>,
+    // curve P&L of the attacker legs, before their costs
+    #[serde(default)]
+    pub gross_profit_sol: i64,
+    #[serde(default)]
+    pub costs: AttackCosts,
+    // gross profit minus costs, what the attackers' balances actually gained
     pub net_profit_sol: i64,
     pub net_token_delta: i64,
+    // attacker wallets found on the known-bot list
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub known_attackers: Vec<String>,
+    // 0..=1, how closely the legs match a textbook sandwich
+    #[serde(default)]
+    pub confidence: f64,
+    #[serde(default)]
+    pub severity: Severity,
+    // intermediate mints the attacker legs routed through, and the attacker's net change in each
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub route_token_deltas: BTreeMap<String, i64>,
+    #[serde(default)]
+    pub latency: SandwichLatency,
+    // from the external scorer, when one is configured
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub external_score: Option<f64>,
+    // pool state the victim traded against, see `snapshot::attach_reserve_snapshots`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub victim_reserves: Option<ReserveSnapshot>,
+    // validator that produced the victim's block, see `leaders::attach_slot_leaders`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub leader: Option<String>,
+    // compute-unit price percentiles in the victim's slot, see `cuprice::rank_cu_prices`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cu_price: Option<CuPriceRanking>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub atomic: Option<Atomicity>,
+    // the legs traded on more than one venue, e.g. a curve frontrun closed on the graduated pool
+    #[serde(default)]
+    pub cross_venue: bool,
+    // with `VictimMode::PriceImpact`: what the victim lost against the pool before the frontruns
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub impact_loss_lamports: Option<u64>,
 }
 
-#[derive(Debug, Clone)]
+impl SandwichDetection {
+    // the price-impact loss when it was measured, otherwise the shortfall past the victim's limits
+    pub fn victim_loss(&self) -> u64 {
+        self.impact_loss_lamports.unwrap_or_else(|| victim_loss_lamports(&self.victim))
+    }
+}
+
+// what the attacker legs paid on top of their curve trades, in lamports
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AttackCosts {
+    pub fees: u64,
+    pub priority_fees: u64,
+    pub ata_rent: u64,
+    pub tips: u64,
+}
+
+impl AttackCosts {
+    // priority fees are already part of `fees`
+    pub fn total(&self) -> u64 {
+        self.fees.saturating_add(self.ata_rent).saturating_add(self.tips)
+    }
+
+    fn add_leg(&mut self, tx: &ParsedTransaction) {
+        self.fees = self.fees.saturating_add(tx.fee);
+        self.priority_fees = self.priority_fees.saturating_add(tx.priority_fee);
+        self.ata_rent = self.ata_rent.saturating_add(tx.ata_rent);
+        self.tips = self.tips.saturating_add(tx.tip);
+    }
+
+    // lamport deltas come from RPC balances, so sums saturate rather than wrap
+    fn deduct_from(&self, gross: i64) -> i64 {
+        gross.saturating_sub(i64::try_from(self.total()).unwrap_or(i64::MAX))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FrontRunEvent {
     pub victim: ParsedTransaction,
     pub frontruns: Vec<ParsedTransaction>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BackRunEvent {
     pub victim: ParsedTransaction,
     pub backruns: Vec<ParsedTransaction>,
 }
 
-#[derive(Debug, Clone, Default)]
+// A bot trading against a large victim right after it, with no frontrun, and unwinding later.
+// Profit covers the part of the position the closing leg unwound.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackrunArbEvent {
+    pub victim: ParsedTransaction,
+    pub backrun: ParsedTransaction,
+    pub close: ParsedTransaction,
+    pub gross_profit_sol: i64,
+    pub costs: AttackCosts,
+    pub net_profit_sol: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RevertedSandwichEvent {
+    pub victim: ParsedTransaction,
+    pub frontruns: Vec<ParsedTransaction>,
+    pub backruns: Vec<ParsedTransaction>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MintStats {
+    pub mint: String,
+    pub total_trades: usize,
+    pub total_victims: usize,
+    pub total_sol_extracted: i64,
+    pub unique_attackers: usize,
+    pub attack_rate_pct: f64,
+    pub median_victim_loss: u64,
+    // the slot window sandwiches on this mint were matched in
+    #[serde(default)]
+    pub slot_gap: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct DetectionSummary {
     pub front_runs: Vec<FrontRunEvent>,
     pub back_runs: Vec<BackRunEvent>,
     pub sandwiches: Vec<SandwichDetection>,
+    #[serde(default)]
+    pub backrun_arbs: Vec<BackrunArbEvent>,
+    pub reverted_sandwiches: Vec<RevertedSandwichEvent>,
+    pub mint_stats: Vec<MintStats>,
+    pub attacker_leaderboard: Vec<AttackerStats>,
+    #[serde(default)]
+    pub attacker_performance: Vec<AttackerPerformance>,
+    #[serde(default)]
+    pub colluding_pairs: Vec<ColludingPair>,
+    pub copy_traders: Vec<CopyTradePair>,
+    // attackers' funding chains, when `funding::trace_funding` ran
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub funding: Vec<FundingChain>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub shared_funders: Vec<SharedFunder>,
+    pub dev_dumps: Vec<DevDumpEvent>,
+    pub snipers: Vec<SniperEvent>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub custom: Vec<CustomDetection>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latency: Option<LatencyDistribution>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tips: Option<TipStatistics>,
+    // sandwiches per block leader, when leaders were looked up
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub leaders: Vec<LeaderStats>,
+    // attackers whose sandwiches cluster on one validator's blocks, from the cached schedules
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub leader_targeting: Vec<LeaderTargeting>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usd: Option<UsdValuation>,
+    // the scanned mint's decimals, when its account could be read
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_decimals: Option<u8>,
+    // the mint's Metaplex name and symbol, when its metadata account could be read
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_metadata: Option<MintMetadata>,
 }
 
 #[derive(Debug, Clone)]
 pub struct DetectorConfig {
     pub max_slot_gap: u64,
+    // derive the window per mint from how often it trades instead of using max_slot_gap
+    pub adaptive_slot_gap: bool,
+    // window = median slot distance between trading slots times this factor
+    pub adaptive_gap_factor: f64,
+    pub min_adaptive_slot_gap: u64,
+    pub max_adaptive_slot_gap: u64,
+    // same-slot frontruns and backruns only count when their block position is known (or they
+    // share the victim's transaction); without it, order within a slot falls back to signatures
+    pub strict_ordering: bool,
+    // Thresholds are set in SOL and whole tokens; detectors compare them through the lamport
+    // and raw-unit accessors below, never by converting the fields themselves.
     pub min_victim_abs_sol: f64,
-    pub min_victim_abs_token: f64,
-    pub min_profit_lamports: i64,
+    pub min_victim_tokens: f64,
+    // net of fees and tips
+    pub min_profit_sol: f64,
+    // the scanned mint's, for the thresholds given in whole tokens
+    pub token_decimals: u8,
+    // SOL a victim must move before a lone backrun on it counts as arbitrage
+    pub backrun_min_victim_sol: f64,
     pub min_bot_trades: usize,
+    pub known_attacker_min_trades: usize,
+    pub known_attackers: HashSet<String>,
+    // curated sandwich bots (`botlist`), pubkey -> label
+    pub known_bots: HashMap<String, String>,
+    // wallets never treated as attackers, e.g. market makers and protocol accounts
+    pub excluded_wallets: HashSet<String>,
+    // wallets treated as bots from their first trade, whatever min_bot_trades says
+    pub forced_bots: HashSet<String>,
+    // wallets known to act as one attacker (e.g. a shared funding source), wallet -> the
+    // group's smallest wallet; see `collusion::load_linked_wallets`
+    pub linked_wallets: HashMap<String, String>,
+    // sandwiches a frontrun/backrun wallet pair must share before it counts as one attacker
+    pub collusion_min_sandwiches: usize,
+    pub copy_trade_max_lag_slots: u64,
+    pub copy_trade_min_matches: usize,
+    pub copy_trade_min_consistency: f64,
+    pub copy_trade_max_size_cv: f64,
+    pub early_holder_slots: u64,
+    pub dump_window_slots: u64,
+    pub dump_min_supply_pct: f64,
+    pub snipe_max_slot_delay: u64,
+    pub snipe_min_sol: f64,
+    pub weights: SignalWeights,
+    // which side an attacker leg must trade, relative to the victim, to count as its frontrun
+    // or backrun; see `direction_profile`
+    pub frontrun_direction: DirectionRule,
+    pub backrun_direction: DirectionRule,
+    pub victim_mode: VictimMode,
+    // with `VictimMode::PriceImpact`, the smallest loss that makes a victim, as % of its trade
+    pub min_impact_loss_pct: f64,
+}
+
+// How a victim is recognised. `Limits` needs its fill past the limits it set, which misses victims
+// whose tight slippage held while the frontrun still cost them. `PriceImpact` re-prices the
+// victim's order on the pool as it stood before the first frontrun and takes the difference, so a
+// victim counts by what the frontruns cost it, limits breached or not.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VictimMode {
+    Limits,
+    PriceImpact,
+}
+
+impl VictimMode {
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value.to_ascii_lowercase().replace('-', "_").as_str() {
+            "limits" => Ok(VictimMode::Limits),
+            "price_impact" => Ok(VictimMode::PriceImpact),
+            other => Err(format!("Unknown victim mode '{}': use limits or price_impact", other)),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            VictimMode::Limits => "limits",
+            VictimMode::PriceImpact => "price_impact",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DirectionRule {
+    Same,
+    Opposite,
+    Any,
+}
+
+impl DirectionRule {
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value.to_ascii_lowercase().as_str() {
+            "same" => Ok(DirectionRule::Same),
+            "opposite" => Ok(DirectionRule::Opposite),
+            "any" => Ok(DirectionRule::Any),
+            other => Err(format!("Unknown direction '{}': use same, opposite or any", other)),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            DirectionRule::Same => "same",
+            DirectionRule::Opposite => "opposite",
+            DirectionRule::Any => "any",
+        }
+    }
+
+    fn matches(self, leg: TradeType, victim: TradeType) -> bool {
+        match self {
+            DirectionRule::Same => leg == victim,
+            DirectionRule::Opposite => leg != victim,
+            DirectionRule::Any => true,
+        }
+    }
+}
+
+// Named (frontrun, backrun) rules. `sandwich` is the classic buy-buy-sell around a buy. `inverse`
+// flips both legs: a bot trading against the victim's side first pulls the price its way, e.g.
+// dumping into a buyer's path to make it revert, then re-enters on the victim's side. `any`
+// matches every bot leg in the window, for surveying which shapes occur at all.
+pub fn direction_profile(name: &str) -> Result<(DirectionRule, DirectionRule), String> {
+    match name.to_ascii_lowercase().as_str() {
+        "sandwich" => Ok((DirectionRule::Same, DirectionRule::Opposite)),
+        "inverse" => Ok((DirectionRule::Opposite, DirectionRule::Same)),
+        "any" => Ok((DirectionRule::Any, DirectionRule::Any)),
+        other => Err(format!("Unknown direction profile '{}': use sandwich, inverse or any", other)),
+    }
+}
+
+// How the victim and attacker-leg signals combine. Each signal is in 0..=1 and a candidate's
+// score is their weighted mean, so a trade that only just misses one signal scores a little lower
+// instead of dropping out. The defaults need a breached limit and at least half the minimum
+// victim size, and an attacker leg on the matching side.
+#[derive(Debug, Clone)]
+pub struct SignalWeights {
+    // the victim's fill against its own limits: 1 past a limit, falling to 0 as the fill clears
+    // it by `breach_margin_pct`
+    pub limit_breach: f64,
+    pub breach_margin_pct: f64,
+    // the victim's size over min_victim_abs_sol / min_victim_tokens, capped at 1
+    pub victim_size: f64,
+    pub min_victim_score: f64,
+    // the leg trades the side `frontrun_direction` / `backrun_direction` asks for
+    pub direction: f64,
+    // 1 in the victim's slot, falling linearly to 0 just past the slot window
+    pub proximity: f64,
+    pub min_leg_score: f64,
+}
+
+impl Default for SignalWeights {
+    fn default() -> Self {
+        Self {
+            limit_breach: 0.6,
+            breach_margin_pct: 0.0,
+            victim_size: 0.4,
+            min_victim_score: 0.8,
+            direction: 0.6,
+            proximity: 0.4,
+            min_leg_score: 0.6,
+        }
+    }
+}
+
+// rounded to the nearest lamport, so 0.0015 SOL is exactly 1_500_000
+fn sol_to_lamports(sol: f64) -> i64 {
+    (sol * LAMPORTS_PER_SOL as f64).round() as i64
+}
+
+impl DetectorConfig {
+    // the same wallet, or two wallets in one linked-wallet group
+    pub fn same_attacker(&self, a: &str, b: &str) -> bool {
+        a == b || matches!((self.linked_wallets.get(a), self.linked_wallets.get(b)), (Some(x), Some(y)) if x == y)
+    }
+
+    // may be negative, to keep sandwiches that lost money
+    pub fn min_profit_lamports(&self) -> i64 {
+        sol_to_lamports(self.min_profit_sol)
+    }
+
+    pub fn min_victim_lamports(&self) -> u64 {
+        sol_to_lamports(self.min_victim_abs_sol).max(0) as u64
+    }
+
+    pub fn backrun_min_victim_lamports(&self) -> u64 {
+        sol_to_lamports(self.backrun_min_victim_sol).max(0) as u64
+    }
+
+    pub fn snipe_min_lamports(&self) -> u64 {
+        sol_to_lamports(self.snipe_min_sol).max(0) as u64
+    }
+
+    // the largest window any mint can get: `max_slot_gap`, or the adaptive upper bound
+    pub fn widest_slot_gap(&self) -> u64 {
+        if self.adaptive_slot_gap {
+            self.max_adaptive_slot_gap.max(self.min_adaptive_slot_gap)
+        } else {
+            self.max_slot_gap
+        }
+    }
+
+    // `min_victim_tokens` in raw token units
+    pub fn min_victim_token_units(&self) -> f64 {
+        self.min_victim_tokens * token::unit(self.token_decimals) as f64
+    }
+
+    // Rejects thresholds that can't mean anything, before a run spends its RPC budget on them.
+    pub fn validate(&self) -> Result<(), String> {
+        for (key, value) in [
+            ("min_victim_abs_sol", self.min_victim_abs_sol),
+            ("min_victim_tokens", self.min_victim_tokens),
+            ("backrun_min_victim_sol", self.backrun_min_victim_sol),
+            ("snipe_min_sol", self.snipe_min_sol),
+            ("copy_trade_max_size_cv", self.copy_trade_max_size_cv),
+            ("limit_breach_weight", self.weights.limit_breach),
+            ("breach_margin_pct", self.weights.breach_margin_pct),
+            ("victim_size_weight", self.weights.victim_size),
+            ("direction_weight", self.weights.direction),
+            ("proximity_weight", self.weights.proximity),
+            ("min_impact_loss_pct", self.min_impact_loss_pct),
+        ] {
+            if !value.is_finite() || value < 0.0 {
+                return Err(format!("{} must be a non-negative number, got {}", key, value));
+            }
+        }
+        // a negative floor is allowed, to also list sandwiches that lost money
+        if !self.min_profit_sol.is_finite() {
+            return Err(format!("min_profit_sol must be a number, got {}", self.min_profit_sol));
+        }
+        if !self.adaptive_gap_factor.is_finite() || self.adaptive_gap_factor <= 0.0 {
+            return Err(format!("adaptive_gap_factor must be positive, got {}", self.adaptive_gap_factor));
+        }
+        if self.min_adaptive_slot_gap > self.max_adaptive_slot_gap {
+            return Err(format!(
+                "min_adaptive_slot_gap ({}) is above max_adaptive_slot_gap ({})",
+                self.min_adaptive_slot_gap, self.max_adaptive_slot_gap
+            ));
+        }
+        if !(0.0..=1.0).contains(&self.copy_trade_min_consistency) {
+            return Err(format!("copy_trade_min_consistency must be between 0 and 1, got {}", self.copy_trade_min_consistency));
+        }
+        for (key, value) in [
+            ("min_victim_score", self.weights.min_victim_score),
+            ("min_leg_score", self.weights.min_leg_score),
+        ] {
+            if !(0.0..=1.0).contains(&value) {
+                return Err(format!("{} must be between 0 and 1, got {}", key, value));
+            }
+        }
+        if self.weights.limit_breach + self.weights.victim_size <= 0.0 {
+            return Err("limit_breach_weight and victim_size_weight can't both be 0".to_string());
+        }
+        if self.weights.direction + self.weights.proximity <= 0.0 {
+            return Err("direction_weight and proximity_weight can't both be 0".to_string());
+        }
+        if !(0.0..=100.0).contains(&self.dump_min_supply_pct) {
+            return Err(format!("dump_min_supply_pct must be between 0 and 100, got {}", self.dump_min_supply_pct));
+        }
+        for (key, value) in [
+            ("min_bot_trades", self.min_bot_trades),
+            ("known_attacker_min_trades", self.known_attacker_min_trades),
+            ("collusion_min_sandwiches", self.collusion_min_sandwiches),
+            ("copy_trade_min_matches", self.copy_trade_min_matches),
+        ] {
+            if value == 0 {
+                return Err(format!("{} must be at least 1", key));
+            }
+        }
+        Ok(())
+    }
 }
 
 impl Default for DetectorConfig {
     fn default() -> Self {
         Self {
             max_slot_gap: 3,
+            adaptive_slot_gap: false,
+            adaptive_gap_factor: 2.0,
+            min_adaptive_slot_gap: 1,
+            max_adaptive_slot_gap: 20,
+            strict_ordering: false,
             min_victim_abs_sol: 0.01,
-            min_victim_abs_token: 100_000_000.0,  
-            min_profit_lamports: 10_000,
+            min_victim_tokens: 100.0,
+            min_profit_sol: 0.00001,
+            token_decimals: DEFAULT_DECIMALS,
+            backrun_min_victim_sol: 1.0,
             min_bot_trades: 2, 
+            known_attacker_min_trades: 1,
+            known_attackers: HashSet::new(),
+            known_bots: HashMap::new(),
+            excluded_wallets: HashSet::new(),
+            forced_bots: HashSet::new(),
+            linked_wallets: HashMap::new(),
+            collusion_min_sandwiches: 3,
+            copy_trade_max_lag_slots: 2,
+            copy_trade_min_matches: 3,
+            copy_trade_min_consistency: 0.6,
+            copy_trade_max_size_cv: 0.5,
+            early_holder_slots: 5,
+            dump_window_slots: 10,
+            dump_min_supply_pct: 5.0,
+            snipe_max_slot_delay: 1,
+            snipe_min_sol: 0.5,
+            weights: SignalWeights::default(),
+            frontrun_direction: DirectionRule::Same,
+            backrun_direction: DirectionRule::Opposite,
+            victim_mode: VictimMode::Limits,
+            min_impact_loss_pct: 0.1,
         }
     }
 }
 
 pub fn detect_wide_attacks(trades: &[ParsedTransaction], cfg: &DetectorConfig) -> DetectionSummary {
+    detect_with(trades, cfg, &[])
+}
+
+// Same result as `detect_wide_attacks`, on rayon's pool: each mint's window is indexed once, then
+// every built-in detector runs on it as its own task, so one mint still spreads over five threads.
+// Bots are still recognised from their trades on every mint. Mints are not split further into
+// slot chunks, since a backrun arbitrage may close any number of slots later.
+pub fn detect_parallel(trades: &[ParsedTransaction], cfg: &DetectorConfig) -> DetectionSummary {
     if trades.is_empty() {
         return DetectionSummary::default();
     }
 
-    let mut signer_counts: HashMap<String, usize> = HashMap::new();
+    let per_mint = DetectorConfig {
+        forced_bots: bot_signers(trades, cfg),
+        ..cfg.clone()
+    };
+    let mut by_mint: BTreeMap<&str, Vec<ParsedTransaction>> = BTreeMap::new();
     for tx in trades {
-        *signer_counts.entry(tx.signer.clone()).or_default() += 1;
+        by_mint.entry(tx.mint.as_ref()).or_default().push(tx.clone());
     }
-    let bot_signers: HashSet<String> = signer_counts
-        .into_iter()
-        .filter_map(|(signer, count)| (count >= cfg.min_bot_trades).then_some(signer))
+    let windows: Vec<Window> = by_mint.par_iter().map(|(_, trades)| Window::new(trades, &per_mint)).collect();
+    let detectors = builtin_detectors();
+    let tasks: Vec<(&Window, &dyn Detector)> = windows
+        .iter()
+        .flat_map(|window| detectors.iter().map(move |detector| (window, detector.as_ref())))
+        .collect();
+    let parts: Vec<DetectionSummary> = tasks
+        .into_par_iter()
+        .map(|(window, detector)| {
+            let mut part = DetectionSummary::default();
+            for event in detector.on_window(window) {
+                part.record(event);
+            }
+            part
+        })
         .collect();
 
-    let mut by_slot: BTreeMap<u64, Vec<ParsedTransaction>> = BTreeMap::new();
-    for tx in trades.iter().cloned() {
-        by_slot.entry(tx.slot).or_default().push(tx);
+    let mut summary = DetectionSummary::default();
+    for part in parts {
+        summary.front_runs.extend(part.front_runs);
+        summary.back_runs.extend(part.back_runs);
+        summary.sandwiches.extend(part.sandwiches);
+        summary.backrun_arbs.extend(part.backrun_arbs);
+        summary.reverted_sandwiches.extend(part.reverted_sandwiches);
     }
+    // back into chain order across mints, as a single pass would emit them
+    summary.front_runs.sort_by(|a, b| order_key(&a.victim).cmp(&order_key(&b.victim)));
+    summary.back_runs.sort_by(|a, b| order_key(&a.victim).cmp(&order_key(&b.victim)));
+    summary.sandwiches.sort_by(|a, b| order_key(&a.victim).cmp(&order_key(&b.victim)));
+    summary.backrun_arbs.sort_by(|a, b| order_key(&a.victim).cmp(&order_key(&b.victim)));
+    summary
+        .reverted_sandwiches
+        .sort_by(|a, b| order_key(&a.victim).cmp(&order_key(&b.victim)));
+
+    summary.rebuild_aggregates(trades, cfg);
+    summary.copy_traders = detect_copy_traders(trades, cfg);
+    summary
+}
 
-    let slot_keys: Vec<u64> = by_slot.keys().cloned().collect();
+// the built-in detectors plus any extra heuristics, whose events land in `summary.custom`
+pub fn detect_with(
+    trades: &[ParsedTransaction],
+    cfg: &DetectorConfig,
+    extra: &[Box<dyn Detector>],
+) -> DetectionSummary {
+    if trades.is_empty() {
+        return DetectionSummary::default();
+    }
+
+    let window = Window::new(trades, cfg);
     let mut summary = DetectionSummary::default();
+    for detector in builtin_detectors().iter().chain(extra) {
+        for event in detector.on_window(&window) {
+            summary.record(event);
+        }
+    }
 
-    for &slot in &slot_keys {
-        let Some(current) = by_slot.get(&slot) else {
-            continue;
-        };
+    summary.rebuild_aggregates(trades, cfg);
+    summary.copy_traders = detect_copy_traders(trades, cfg);
+    summary
+}
 
-        for victim in current.iter() {
-            let execution = analyze_execution(victim);
-            if !execution.any() {
-                continue;
-            }
+impl DetectionSummary {
+    pub fn decimals(&self) -> u8 {
+        self.token_decimals.unwrap_or(DEFAULT_DECIMALS)
+    }
 
-            if !magnitude_exceeds(victim, cfg) {
-                continue;
-            }
+    // recomputes what is derived from `sandwiches`, after they were filtered
+    pub fn rebuild_aggregates(&mut self, trades: &[ParsedTransaction], cfg: &DetectorConfig) {
+        let mut trade_counts: BTreeMap<&str, usize> = BTreeMap::new();
+        for tx in trades {
+            *trade_counts.entry(tx.mint.as_ref()).or_default() += 1;
+        }
+        self.rebuild_with_counts(&trade_counts, &slot_gaps(trades, cfg), cfg);
+    }
 
-            let start_slot = slot.saturating_sub(cfg.max_slot_gap);
-            let end_slot = slot.saturating_add(cfg.max_slot_gap);
-
-            let mut frontruns: Vec<ParsedTransaction> = Vec::new();
-            for (&prev_slot, txs) in by_slot.range(start_slot..=slot) {
-                for tx in txs {
-                    if tx.signature == victim.signature {
-                        continue;
-                    }
-                    if tx.mint != victim.mint {
-                        continue;
-                    }
-                    if prev_slot == slot && !occurs_before(tx, victim) {
-                        continue;
-                    }
-                    if !bot_signers.contains(&tx.signer) {
-                        continue;
-                    }
-                    if is_frontrun_candidate(tx, victim) {
-                        frontruns.push(tx.clone());
-                    }
-                }
-            }
+    // Drops sandwiches below `min`. The per-mint trade counts and windows are taken from the
+    // existing stats, so this also works on summaries from a stream that kept no trades.
+    pub fn retain_min_severity(&mut self, min: Severity, cfg: &DetectorConfig) {
+        if min == Severity::Low {
+            return;
+        }
+        self.sandwiches.retain(|det| det.severity >= min);
+        self.rebuild_from_stats(cfg);
+    }
 
-            if !frontruns.is_empty() {
-                summary.front_runs.push(FrontRunEvent {
-                    victim: victim.clone(),
-                    frontruns: frontruns.clone(),
-                });
-            }
+    // the aggregates again, with the trade counts and windows of the existing stats; also for
+    // re-attributing the same sandwiches after `cfg.linked_wallets` grew
+    pub fn rebuild_from_stats(&mut self, cfg: &DetectorConfig) {
+        let stats = std::mem::take(&mut self.mint_stats);
+        let trade_counts: BTreeMap<&str, usize> =
+            stats.iter().map(|stats| (stats.mint.as_str(), stats.total_trades)).collect();
+        let gaps: HashMap<&str, u64> = stats.iter().map(|stats| (stats.mint.as_str(), stats.slot_gap)).collect();
+        self.rebuild_with_counts(&trade_counts, &gaps, cfg);
+    }
 
-            let mut backruns: Vec<ParsedTransaction> = Vec::new();
-            for (&next_slot, txs) in by_slot.range(slot..=end_slot) {
-                for tx in txs {
-                    if tx.signature == victim.signature {
-                        continue;
-                    }
-                    if tx.mint != victim.mint {
-                        continue;
-                    }
-                    if next_slot == slot && !occurs_after(tx, victim) {
-                        continue;
-                    }
-                    if !bot_signers.contains(&tx.signer) {
-                        continue;
-                    }
-                    if is_backrun_candidate(tx, victim) {
-                        backruns.push(tx.clone());
-                    }
-                }
-            }
+    // for callers that only counted the trades per mint instead of keeping them
+    pub(crate) fn rebuild_with_counts(
+        &mut self,
+        trade_counts: &BTreeMap<&str, usize>,
+        gaps: &HashMap<&str, u64>,
+        cfg: &DetectorConfig,
+    ) {
+        // pairs only show up across sandwiches, so they are folded in after detection
+        self.colluding_pairs = colluding_pairs(&self.sandwiches, cfg.collusion_min_sandwiches);
+        let entities = AttackerEntities::new(&cfg.linked_wallets, &self.colluding_pairs);
+        for det in &mut self.sandwiches {
+            det.confidence = sandwich_confidence(
+                &det.victim,
+                &det.frontruns,
+                &det.backruns,
+                det.net_token_delta,
+                !det.known_attackers.is_empty(),
+                cfg,
+                |a, b| entities.same(a, b),
+            );
+        }
+        self.mint_stats = compute_mint_stats(trade_counts, gaps, &self.sandwiches, &entities, cfg);
+        self.attacker_leaderboard =
+            attacker_leaderboard(&self.sandwiches, &cfg.known_attackers, &cfg.known_bots, &entities);
+        self.attacker_performance =
+            attacker_performance(&self.front_runs, &self.sandwiches, &self.reverted_sandwiches, &entities);
+        self.latency = latency_distribution(&self.sandwiches);
+        self.tips = tip_statistics(&self.sandwiches);
+        self.leaders = leader_stats(&self.sandwiches);
+    }
 
-            if !backruns.is_empty() {
-                summary.back_runs.push(BackRunEvent {
-                    victim: victim.clone(),
-                    backruns: backruns.clone(),
-                });
-            }
+    pub fn record(&mut self, event: DetectionEvent) {
+        match event {
+            DetectionEvent::FrontRun(event) => self.front_runs.push(event),
+            DetectionEvent::BackRun(event) => self.back_runs.push(event),
+            DetectionEvent::Sandwich(det) => self.sandwiches.push(*det),
+            DetectionEvent::BackrunArb(event) => self.backrun_arbs.push(*event),
+            DetectionEvent::RevertedSandwich(event) => self.reverted_sandwiches.push(event),
+            DetectionEvent::Custom(detection) => self.custom.push(detection),
+        }
+    }
+}
 
-            let mut net_sol: i64 = 0;
-            let mut net_tokens: i64 = 0;
-            for tx in frontruns.iter().chain(backruns.iter()) {
-                net_sol += tx.sol_change;
-                net_tokens += tx.token_change;
-            }
+pub(crate) fn bot_signers(trades: &[ParsedTransaction], cfg: &DetectorConfig) -> HashSet<String> {
+    let mut signer_counts: HashMap<&str, usize> = HashMap::new();
+    for tx in trades {
+        *signer_counts.entry(tx.signer.as_ref()).or_default() += 1;
+    }
+    signer_counts
+        .into_iter()
+        .filter(|&(signer, count)| is_bot(signer, count, cfg))
+        .map(|(signer, _)| signer.to_string())
+        .collect()
+}
 
-            if !frontruns.is_empty() && !backruns.is_empty() {
-                if net_sol >= cfg.min_profit_lamports {
-                    summary.sandwiches.push(SandwichDetection {
-                        victim: victim.clone(),
-                        frontruns: frontruns.clone(),
-                        backruns: backruns.clone(),
-                        net_profit_sol: net_sol,
-                        net_token_delta: net_tokens,
-                    });
-                }
+fn compute_mint_stats(
+    trade_counts: &BTreeMap<&str, usize>,
+    gaps: &HashMap<&str, u64>,
+    sandwiches: &[SandwichDetection],
+    entities: &AttackerEntities,
+    cfg: &DetectorConfig,
+) -> Vec<MintStats> {
+    trade_counts
+        .iter()
+        .map(|(&mint, &total_trades)| {
+            let attacks: Vec<&SandwichDetection> =
+                sandwiches.iter().filter(|det| &*det.victim.mint == mint).collect();
+            let victims: HashSet<(&str, usize)> = attacks
+                .iter()
+                .map(|det| (det.victim.signature.as_ref(), det.victim.leg_index))
+                .collect();
+            let attackers: HashSet<&str> = attacks
+                .iter()
+                .flat_map(|det| det.frontruns.iter().chain(det.backruns.iter()))
+                .map(|tx| entities.entity(&tx.signer))
+                .collect();
+            let mut losses: Vec<u64> = attacks.iter().map(|det| det.victim_loss()).collect();
+            losses.sort_unstable();
+
+            MintStats {
+                mint: mint.to_string(),
+                total_trades,
+                total_victims: victims.len(),
+                total_sol_extracted: attacks.iter().map(|det| det.net_profit_sol).sum(),
+                unique_attackers: attackers.len(),
+                attack_rate_pct: victims.len() as f64 * 100.0 / total_trades as f64,
+                median_victim_loss: median(&losses),
+                slot_gap: gaps.get(mint).copied().unwrap_or(cfg.max_slot_gap),
             }
+        })
+        .collect()
+}
+
+// Per-mint windows for `adaptive_slot_gap`, empty when it is off. A busy mint trading every
+// slot gets a tight window, a quiet one a wide window, both within the adaptive bounds.
+pub(crate) fn slot_gaps<'a>(trades: &'a [ParsedTransaction], cfg: &DetectorConfig) -> HashMap<&'a str, u64> {
+    if !cfg.adaptive_slot_gap {
+        return HashMap::new();
+    }
+    let mut slots: HashMap<&str, BTreeSet<u64>> = HashMap::new();
+    for tx in trades {
+        slots.entry(tx.mint.as_ref()).or_default().insert(tx.slot);
+    }
+    slots
+        .into_iter()
+        .map(|(mint, slots)| {
+            let slots: Vec<u64> = slots.into_iter().collect();
+            let mut distances: Vec<u64> = slots.windows(2).map(|pair| pair[1] - pair[0]).collect();
+            distances.sort_unstable();
+            (mint, adaptive_gap((!distances.is_empty()).then(|| median(&distances)), cfg))
+        })
+        .collect()
+}
+
+// the window for a mint whose distinct trading slots lie a median `distance` apart; a mint
+// seen in one slot only has none and gets `max_slot_gap`, clamped like the rest
+pub(crate) fn adaptive_gap(distance: Option<u64>, cfg: &DetectorConfig) -> u64 {
+    let gap = match distance {
+        Some(distance) => (distance as f64 * cfg.adaptive_gap_factor).ceil() as u64,
+        None => cfg.max_slot_gap,
+    };
+    gap.clamp(cfg.min_adaptive_slot_gap, cfg.widest_slot_gap())
+}
+
+fn median(sorted: &[u64]) -> u64 {
+    match sorted.len() {
+        0 => 0,
+        len if len % 2 == 1 => sorted[len / 2],
+        len => (sorted[len / 2 - 1] + sorted[len / 2]) / 2,
+    }
+}
+
+pub fn victim_loss_lamports(tx: &ParsedTransaction) -> u64 {
+    match tx.trade_type {
+        TradeType::Buy => negative_amount(tx.trade_sol_change()).saturating_sub(tx.sol_limit_specified),
+        TradeType::Sell => tx
+            .sol_limit_specified
+            .saturating_sub(positive_amount(tx.trade_sol_change())),
+    }
+}
+
+// lamports paid or received per raw token unit
+pub fn execution_price(tx: &ParsedTransaction) -> Option<f64> {
+    if tx.token_change == 0 {
+        return None;
+    }
+    Some(tx.trade_sol_change().unsigned_abs() as f64 / tx.token_change.unsigned_abs() as f64)
+}
+
+pub(crate) fn is_bot(signer: &str, trades: usize, cfg: &DetectorConfig) -> bool {
+    if cfg.excluded_wallets.contains(signer) {
+        return false;
+    }
+    cfg.forced_bots.contains(signer)
+        || trades >= cfg.min_bot_trades
+        || ((cfg.known_attackers.contains(signer) || cfg.known_bots.contains_key(signer))
+            && trades >= cfg.known_attacker_min_trades)
+}
+
+pub(crate) fn is_candidate_victim(victim: &ParsedTransaction, cfg: &DetectorConfig) -> bool {
+    victim_score(victim, cfg) >= cfg.weights.min_victim_score
+}
+
+// by price impact the limits don't matter; the loss is checked once the frontruns are known
+pub(crate) fn victim_score(victim: &ParsedTransaction, cfg: &DetectorConfig) -> f64 {
+    let weights = &cfg.weights;
+    let breach_weight = match cfg.victim_mode {
+        VictimMode::Limits => weights.limit_breach,
+        VictimMode::PriceImpact => 0.0,
+    };
+    weighted_mean(&[
+        (breach_weight, breach_signal(victim, weights.breach_margin_pct)),
+        (weights.victim_size, size_signal(victim, cfg)),
+    ])
+}
+
+// What the victim's order would have got on the bonding curve before the first frontrun, less
+// what it got: tokens short (valued at its own fill price) for a buy, SOL short for a sell. None
+// when those reserves can't be reconstructed, or for PumpSwap and routed trades.
+pub fn price_impact_loss(victim: &ParsedTransaction, frontruns: &[ParsedTransaction]) -> Option<u64> {
+    if victim.venue != Venue::PumpFun || victim.is_routed() {
+        return None;
+    }
+    let first = frontruns
+        .iter()
+        .filter(|leg| leg.venue == Venue::PumpFun)
+        .min_by(|a, b| order_key(a).cmp(&order_key(b)))?;
+    let pool = PumpAmmState::before_trade(first, &FeeSchedule::default())?;
+    let sol = victim.trade_sol_change().unsigned_abs();
+    let tokens = victim.token_change.unsigned_abs();
+    if sol == 0 || tokens == 0 {
+        return None;
+    }
+    Some(match victim.trade_type {
+        TradeType::Buy => {
+            let shortfall = pool.quote_buy(sol).saturating_sub(tokens);
+            (shortfall as u128 * sol as u128 / tokens as u128) as u64
+        }
+        TradeType::Sell => pool.quote_sell(tokens).saturating_sub(sol),
+    })
+}
+
+// Whether the victim counts under `cfg.victim_mode` given its frontruns, and its price-impact
+// loss when that is how it was judged
+pub(crate) fn victim_mode_loss(
+    victim: &ParsedTransaction,
+    frontruns: &[ParsedTransaction],
+    cfg: &DetectorConfig,
+) -> Option<Option<u64>> {
+    match cfg.victim_mode {
+        VictimMode::Limits => Some(None),
+        VictimMode::PriceImpact => {
+            let loss = price_impact_loss(victim, frontruns)?;
+            let traded = victim.trade_sol_change().unsigned_abs();
+            (loss > 0 && loss as f64 * 100.0 >= cfg.min_impact_loss_pct * traded as f64).then_some(Some(loss))
         }
     }
+}
 
-    summary
+// an attacker leg `gap` slots at most from the victim, scored against it as a frontrun (or a
+// backrun) of it
+fn leg_score(leg: &ParsedTransaction, victim: &ParsedTransaction, frontrun: bool, gap: u64, cfg: &DetectorConfig) -> f64 {
+    let rule = if frontrun { cfg.frontrun_direction } else { cfg.backrun_direction };
+    let direction = rule.matches(leg.trade_type, victim.trade_type);
+    let distance = leg.slot.abs_diff(victim.slot);
+    weighted_mean(&[
+        (cfg.weights.direction, if direction { 1.0 } else { 0.0 }),
+        (cfg.weights.proximity, 1.0 - distance as f64 / (gap + 1) as f64),
+    ])
+}
+
+fn weighted_mean(signals: &[(f64, f64)]) -> f64 {
+    let total: f64 = signals.iter().map(|(weight, _)| weight).sum();
+    if total <= 0.0 {
+        return 0.0;
+    }
+    signals.iter().map(|(weight, signal)| weight * signal.clamp(0.0, 1.0)).sum::<f64>() / total
+}
+
+pub(crate) fn build_sandwich(
+    victim: &ParsedTransaction,
+    frontruns: &[ParsedTransaction],
+    backruns: &[ParsedTransaction],
+    cfg: &DetectorConfig,
+) -> Option<SandwichDetection> {
+    if frontruns.is_empty() || backruns.is_empty() {
+        return None;
+    }
+    let impact_loss = victim_mode_loss(victim, frontruns, cfg)?;
+
+    let mut gross_sol: i64 = 0;
+    let mut net_tokens: i64 = 0;
+    let mut costs = AttackCosts::default();
+    let mut route_token_deltas: BTreeMap<String, i64> = BTreeMap::new();
+    for tx in frontruns.iter().chain(backruns.iter()) {
+        gross_sol = gross_sol.saturating_add(leg_trade_value(tx));
+        net_tokens = net_tokens.saturating_add(tx.token_change);
+        for (mint, change) in &tx.hop_token_changes {
+            let delta = route_token_deltas.entry(mint.clone()).or_default();
+            *delta = delta.saturating_add(*change);
+        }
+        costs.add_leg(tx);
+    }
+    let net_sol = costs.deduct_from(gross_sol);
+
+    // a sandwich that only breaks even before fees and tips is not worth flagging
+    if net_sol < cfg.min_profit_lamports() {
+        return None;
+    }
+    let mut known_attackers: Vec<String> = frontruns
+        .iter()
+        .chain(backruns.iter())
+        .filter(|tx| cfg.known_bots.contains_key(&*tx.signer))
+        .map(|tx| tx.signer.to_string())
+        .collect();
+    known_attackers.sort();
+    known_attackers.dedup();
+    Some(SandwichDetection {
+        victim: victim.clone(),
+        frontruns: frontruns.to_vec(),
+        backruns: backruns.to_vec(),
+        gross_profit_sol: gross_sol,
+        costs,
+        net_profit_sol: net_sol,
+        net_token_delta: net_tokens,
+        confidence: sandwich_confidence(victim, frontruns, backruns, net_tokens, !known_attackers.is_empty(), cfg, |a, b| {
+            cfg.same_attacker(a, b)
+        }),
+        severity: match impact_loss {
+            Some(loss) => Severity::classify_loss(victim, loss),
+            None => Severity::classify(victim),
+        },
+        known_attackers,
+        route_token_deltas,
+        latency: SandwichLatency::measure(victim, frontruns, backruns).unwrap_or_default(),
+        external_score: None,
+        victim_reserves: None,
+        leader: None,
+        cu_price: None,
+        atomic: Atomicity::classify(victim, frontruns, backruns),
+        cross_venue: frontruns.iter().chain(backruns).any(|leg| leg.venue != victim.venue),
+        impact_loss_lamports: impact_loss,
+    })
 }
 
-fn is_frontrun_candidate(front: &ParsedTransaction, victim: &ParsedTransaction) -> bool {
-    occurs_before(front, victim) && front.trade_type == victim.trade_type
+// The backrun and closing legs scaled to the tokens both of them moved, so a close that only
+// unwinds part of the position (or more than it) is not counted in full.
+pub(crate) fn backrun_arb(
+    victim: &ParsedTransaction,
+    backrun: &ParsedTransaction,
+    close: &ParsedTransaction,
+    cfg: &DetectorConfig,
+) -> Option<BackrunArbEvent> {
+    let opened = backrun.token_change.unsigned_abs();
+    let closed = close.token_change.unsigned_abs();
+    let matched = opened.min(closed);
+    if matched == 0 {
+        return None;
+    }
+    // matched <= tokens, so the share never exceeds the leg's own value
+    let share = |value: i64, tokens: u64| (value as i128 * matched as i128 / tokens as i128) as i64;
+    let gross_sol = share(leg_trade_value(backrun), opened).saturating_add(share(leg_trade_value(close), closed));
+    let mut costs = AttackCosts::default();
+    for tx in [backrun, close] {
+        costs.add_leg(tx);
+    }
+    let net_sol = costs.deduct_from(gross_sol);
+    if net_sol < cfg.min_profit_lamports() {
+        return None;
+    }
+    Some(BackrunArbEvent {
+        victim: victim.clone(),
+        backrun: backrun.clone(),
+        close: close.clone(),
+        gross_profit_sol: gross_sol,
+        costs,
+        net_profit_sol: net_sol,
+    })
 }
 
-fn is_backrun_candidate(back: &ParsedTransaction, victim: &ParsedTransaction) -> bool {
-    occurs_after(back, victim) && back.trade_type != victim.trade_type
+// `cfg` for arbitrage legs: an arbitrage trades against the victim whatever the direction
+// profile says
+pub(crate) fn arb_config(cfg: &DetectorConfig) -> DetectorConfig {
+    DetectorConfig {
+        frontrun_direction: DirectionRule::Same,
+        backrun_direction: DirectionRule::Opposite,
+        ..cfg.clone()
+    }
 }
 
-fn occurs_before(a: &ParsedTransaction, b: &ParsedTransaction) -> bool {
-    (a.slot < b.slot) || (a.slot == b.slot && a.signature < b.signature)
+// The backruns of a large victim that may be arbitrage, one per signer: signers that also
+// frontran it are sandwiching, and the victim's own legs don't count. `seen` holds the signers
+// already taken for this victim and gains the new ones.
+pub(crate) fn arb_backruns(
+    index: &LegIndex,
+    victim: &ParsedTransaction,
+    arb_cfg: &DetectorConfig,
+    seen: &mut Vec<Arc<str>>,
+) -> Vec<ParsedTransaction> {
+    if !is_large_victim(victim, arb_cfg) {
+        return Vec::new();
+    }
+    let frontruns = index.frontruns(victim, arb_cfg);
+    let mut backruns = Vec::new();
+    for backrun in index.backruns(victim, arb_cfg) {
+        if backrun.signer == victim.signer
+            || frontruns.iter().any(|fr| arb_cfg.same_attacker(&fr.signer, &backrun.signer))
+            || seen.contains(&backrun.signer)
+        {
+            continue;
+        }
+        seen.push(backrun.signer.clone());
+        backruns.push(backrun);
+    }
+    backruns
 }
 
-fn occurs_after(a: &ParsedTransaction, b: &ParsedTransaction) -> bool {
-    (a.slot > b.slot) || (a.slot == b.slot && a.signature > b.signature)
+pub(crate) fn is_large_victim(victim: &ParsedTransaction, cfg: &DetectorConfig) -> bool {
+    victim.outcome != ExecutionOutcome::Reverted && leg_trade_value(victim).unsigned_abs() >= cfg.backrun_min_victim_lamports()
 }
 
-#[derive(Clone, Copy, Debug)]
-struct ExecutionBreach {
-    price_limit: bool,
-    amount_limit: bool,
+// A leg routed through another token (SOL -> tokenA -> target) barely moves the signer's SOL;
+// what it was worth is the SOL the curve took or paid out, with pump.fun's fee on the signer's side.
+pub(crate) fn leg_trade_value(tx: &ParsedTransaction) -> i64 {
+    match &tx.curve {
+        Some(curve) if tx.is_routed() => {
+            let fee_bps = FeeSchedule::default().at(tx.slot).total_bps() as i128;
+            let curve_sol = curve.sol_change as i128;
+            (-curve_sol - curve_sol.abs() * fee_bps / 10_000).clamp(i64::MIN as i128, i64::MAX as i128) as i64
+        }
+        _ => tx.trade_sol_change(),
+    }
 }
 
-impl ExecutionBreach {
-    fn any(self) -> bool {
-        self.price_limit || self.amount_limit
+// starts from the shape of the attack and adds a boost when an attacker is a known bot or
+// outbid the victim for its place in the block; a borderline victim scales it down rather than
+// dropping the sandwich
+fn sandwich_confidence(
+    victim: &ParsedTransaction,
+    frontruns: &[ParsedTransaction],
+    backruns: &[ParsedTransaction],
+    net_tokens: i64,
+    known_bot: bool,
+    cfg: &DetectorConfig,
+    same_attacker: impl Fn(&str, &str) -> bool,
+) -> f64 {
+    let mut points: u32 = 50;
+    if frontruns
+        .iter()
+        .any(|fr| backruns.iter().any(|br| same_attacker(&br.signer, &fr.signer)))
+    {
+        points += 20;
     }
+    // the bot sold what it bought around the victim
+    if net_tokens == 0 {
+        points += 10;
+    }
+    if known_bot {
+        points += KNOWN_BOT_CONFIDENCE_BOOST;
+    }
+    if frontruns
+        .iter()
+        .any(|fr| fr.slot == victim.slot && fr.compute_unit_price > victim.compute_unit_price)
+    {
+        points += OUTBID_CONFIDENCE_BOOST;
+    }
+    f64::from(points.min(100)) / 100.0 * victim_score(victim, cfg)
 }
 
-fn analyze_execution(tx: &ParsedTransaction) -> ExecutionBreach {
-    match tx.trade_type {
-        TradeType::Buy => {
-            let actual_spent = negative_amount(tx.sol_change);
-            let tokens_received = positive_amount(tx.token_change);
-            ExecutionBreach {
-                price_limit: actual_spent > tx.sol_limit_specified,
-                amount_limit: tokens_received < tx.token_amount_requested,
+pub(crate) fn reverted_sandwich(
+    victim: &ParsedTransaction,
+    frontruns: &[ParsedTransaction],
+    backruns: &[ParsedTransaction],
+    cfg: &DetectorConfig,
+) -> Option<RevertedSandwichEvent> {
+    if victim.sol_limit_specified < cfg.min_victim_lamports()
+        && (victim.token_amount_requested as f64) < cfg.min_victim_token_units()
+    {
+        return None;
+    }
+
+    let attackers: HashSet<&str> = frontruns
+        .iter()
+        .map(|tx| &*tx.signer)
+        .filter(|signer| backruns.iter().any(|tx| cfg.same_attacker(&tx.signer, signer)))
+        .collect();
+    if attackers.is_empty() {
+        return None;
+    }
+
+    Some(RevertedSandwichEvent {
+        victim: victim.clone(),
+        frontruns: frontruns
+            .iter()
+            .filter(|tx| attackers.contains(&*tx.signer))
+            .cloned()
+            .collect(),
+        backruns: backruns
+            .iter()
+            .filter(|tx| attackers.iter().any(|attacker| cfg.same_attacker(&tx.signer, attacker)))
+            .cloned()
+            .collect(),
+    })
+}
+
+// bot legs per mint in chain order; frontruns and backruns are the ones before or after a
+// victim, within the mint's slot window, that score as such
+pub(crate) struct LegIndex<'a> {
+    legs: HashMap<&'a str, Vec<&'a ParsedTransaction>>,
+    gaps: HashMap<&'a str, u64>,
+}
+
+impl<'a> LegIndex<'a> {
+    pub(crate) fn build(trades: &'a [ParsedTransaction], bot_signers: &HashSet<String>, cfg: &DetectorConfig) -> Self {
+        Self::from_legs(trades, bot_signers, slot_gaps(trades, cfg))
+    }
+
+    // over any set of legs, with windows worked out elsewhere; a mint missing from `gaps` uses
+    // `max_slot_gap`
+    pub(crate) fn from_legs(
+        trades: impl IntoIterator<Item = &'a ParsedTransaction>,
+        bot_signers: &HashSet<String>,
+        gaps: HashMap<&'a str, u64>,
+    ) -> Self {
+        let mut legs: HashMap<&'a str, Vec<&'a ParsedTransaction>> = HashMap::new();
+        for tx in trades {
+            if tx.outcome == ExecutionOutcome::Reverted || !bot_signers.contains(&*tx.signer) {
+                continue;
             }
+            legs.entry(tx.mint.as_ref()).or_default().push(tx);
         }
-        TradeType::Sell => {
-            let sol_received = positive_amount(tx.sol_change);
-            let tokens_sold = negative_amount(tx.token_change);
-            ExecutionBreach {
-                price_limit: sol_received < tx.sol_limit_specified,
-                amount_limit: tokens_sold > tx.token_amount_requested,
-            }
+        for sorted in legs.values_mut() {
+            sorted.sort_by(|a, b| order_key(a).cmp(&order_key(b)));
         }
+        Self { legs, gaps }
+    }
+
+    fn slot_gap(&self, mint: &str, cfg: &DetectorConfig) -> u64 {
+        self.gaps.get(mint).copied().unwrap_or(cfg.max_slot_gap)
+    }
+
+    pub(crate) fn frontruns(&self, victim: &ParsedTransaction, cfg: &DetectorConfig) -> Vec<ParsedTransaction> {
+        let Some(legs) = self.legs.get(victim.mint.as_ref()) else {
+            return Vec::new();
+        };
+        let gap = self.slot_gap(&victim.mint, cfg);
+        let start_slot = victim.slot.saturating_sub(gap);
+        let start = legs.partition_point(|tx| tx.slot < start_slot);
+        let end = legs.partition_point(|tx| order_key(tx) < order_key(victim));
+        legs[start..end.max(start)]
+            .iter()
+            .filter(|tx| ordering_trusted(tx, victim, cfg) && leg_score(tx, victim, true, gap, cfg) >= cfg.weights.min_leg_score)
+            .map(|tx| (*tx).clone())
+            .collect()
+    }
+
+    pub(crate) fn backruns(&self, victim: &ParsedTransaction, cfg: &DetectorConfig) -> Vec<ParsedTransaction> {
+        let Some(legs) = self.legs.get(victim.mint.as_ref()) else {
+            return Vec::new();
+        };
+        let gap = self.slot_gap(&victim.mint, cfg);
+        let end_slot = victim.slot.saturating_add(gap);
+        let start = legs.partition_point(|tx| order_key(tx) <= order_key(victim));
+        let end = legs.partition_point(|tx| tx.slot <= end_slot);
+        legs[start..end.max(start)]
+            .iter()
+            .filter(|tx| ordering_trusted(tx, victim, cfg) && leg_score(tx, victim, false, gap, cfg) >= cfg.weights.min_leg_score)
+            .map(|tx| (*tx).clone())
+            .collect()
+    }
+
+    // the signer's first leg after `leg` on the same mint and the opposite side
+    pub(crate) fn closing_leg(&self, leg: &ParsedTransaction) -> Option<&'a ParsedTransaction> {
+        let legs = self.legs.get(leg.mint.as_ref())?;
+        let start = legs.partition_point(|tx| order_key(tx) <= order_key(leg));
+        legs[start..]
+            .iter()
+            .find(|tx| tx.signer == leg.signer && tx.trade_type == leg.trade_type.opposite())
+            .copied()
+    }
+}
+
+// chain order where the block position is known; within a slot without it, signatures only
+// give a stable, arbitrary order
+pub(crate) fn order_key(tx: &ParsedTransaction) -> (u64, Option<usize>, &str, usize) {
+    (tx.slot, tx.tx_index, tx.signature.as_ref(), tx.leg_index)
+}
+
+// Whether `order_key` reflects how `a` and `b` actually executed: always across slots and within
+// one transaction, and within a slot only from both block positions. Outside `strict_ordering`
+// the signature tiebreak is trusted too.
+fn ordering_trusted(a: &ParsedTransaction, b: &ParsedTransaction, cfg: &DetectorConfig) -> bool {
+    !cfg.strict_ordering
+        || a.slot != b.slot
+        || a.signature == b.signature
+        || (a.tx_index.is_some() && b.tx_index.is_some())
+}
+
+pub(crate) fn same_leg(a: &ParsedTransaction, b: &ParsedTransaction) -> bool {
+    a.signature == b.signature && a.leg_index == b.leg_index
+}
+
+// How far the fill got past the limits the trader set, as the larger of the two overshoots: SOL
+// over its price limit and tokens short of (past, for a sell) the amount. 1 once either limit is
+// breached; a fill inside its limits scores by how close it got, reaching 0 at `margin_pct`.
+fn breach_signal(tx: &ParsedTransaction, margin_pct: f64) -> f64 {
+    let (sol_slack, amount_slack) = match tx.trade_type {
+        TradeType::Buy => (
+            slack(tx.sol_limit_specified, negative_amount(tx.trade_sol_change())),
+            slack(positive_amount(tx.token_change), tx.token_amount_requested),
+        ),
+        TradeType::Sell => (
+            slack(positive_amount(tx.trade_sol_change()), tx.sol_limit_specified),
+            slack(tx.token_amount_requested, negative_amount(tx.token_change)),
+        ),
+    };
+    let closest = sol_slack.min(amount_slack);
+    if closest < 0.0 {
+        1.0
+    } else if margin_pct > 0.0 {
+        (1.0 - closest * 100.0 / margin_pct).max(0.0)
+    } else {
+        0.0
+    }
+}
+
+// how far `value` stays above `floor`, relative to it; negative once it dropped below
+fn slack(value: u64, floor: u64) -> f64 {
+    if floor == 0 {
+        return f64::INFINITY;
     }
+    (value as f64 - floor as f64) / floor as f64
 }
 
-fn magnitude_exceeds(tx: &ParsedTransaction, cfg: &DetectorConfig) -> bool {
-    tx.sol_change.abs_as_sol() >= cfg.min_victim_abs_sol
-        || (tx.token_change as f64).abs() >= cfg.min_victim_abs_token
+fn size_signal(tx: &ParsedTransaction, cfg: &DetectorConfig) -> f64 {
+    let ratio = |value: f64, threshold: f64| if threshold > 0.0 { value / threshold } else { f64::INFINITY };
+    ratio(tx.trade_sol_change().unsigned_abs() as f64, cfg.min_victim_lamports() as f64)
+        .max(ratio((tx.token_change as f64).abs(), cfg.min_victim_token_units()))
+        .min(1.0)
 }
 
 fn positive_amount(value: i64) -> u64 {
@@ -229,7 +1323,7 @@ fn positive_amount(value: i64) -> u64 {
 }
 
 fn negative_amount(value: i64) -> u64 {
-    if value < 0 { (-value) as u64 } else { 0 }
+    value.min(0).unsigned_abs()
 }
 
 pub trait LamportsExt {
@@ -0,0 +1,210 @@
+use crate::detect::{
+    BackRunEvent, DetectorConfig, FrontRunEvent, LegIndex, arb_backruns, arb_config, backrun_arb, bot_signers,
+    build_sandwich, is_candidate_victim, order_key, reverted_sandwich, victim_mode_loss,
+};
+use crate::parser::pumpfun::{ExecutionOutcome, ParsedTransaction};
+use crate::streaming::DetectionEvent;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+// A heuristic run over a window of trades. The built-in front-run, back-run, sandwich,
+// backrun-arbitrage and reverted-victim rules implement it; extra detectors passed to
+// `detect_with` run alongside them and report through `DetectionEvent::Custom`. `detect_parallel`
+// shares one instance across its threads, hence Send + Sync.
+pub trait Detector: Send + Sync {
+    fn name(&self) -> &str;
+    fn on_window(&self, window: &Window<'_>) -> Vec<DetectionEvent>;
+}
+
+// One window of trades as every detector sees it. The recognised bots, their legs indexed per
+// mint, the victims in chain order and the legs around each victim are worked out once here and
+// shared by all detectors.
+pub struct Window<'a> {
+    pub trades: &'a [ParsedTransaction],
+    pub cfg: &'a DetectorConfig,
+    bots: HashSet<String>,
+    index: LegIndex<'a>,
+    victims: Vec<Victim<'a>>,
+}
+
+// a trade with the bot legs scoring as its frontruns and backruns; only looked up for candidate
+// and reverted victims, the only ones the sandwich rules consider
+struct Victim<'a> {
+    tx: &'a ParsedTransaction,
+    frontruns: Vec<ParsedTransaction>,
+    backruns: Vec<ParsedTransaction>,
+}
+
+impl<'a> Window<'a> {
+    pub fn new(trades: &'a [ParsedTransaction], cfg: &'a DetectorConfig) -> Self {
+        let bots = bot_signers(trades, cfg);
+        let index = LegIndex::build(trades, &bots, cfg);
+        let mut sorted: Vec<&ParsedTransaction> = trades.iter().collect();
+        sorted.sort_by(|a, b| order_key(a).cmp(&order_key(b)));
+        let victims = sorted
+            .into_iter()
+            .map(|tx| {
+                let scanned = tx.outcome == ExecutionOutcome::Reverted || is_candidate_victim(tx, cfg);
+                let (frontruns, backruns) = if scanned {
+                    (index.frontruns(tx, cfg), index.backruns(tx, cfg))
+                } else {
+                    (Vec::new(), Vec::new())
+                };
+                Victim { tx, frontruns, backruns }
+            })
+            .collect();
+        Self {
+            trades,
+            cfg,
+            bots,
+            index,
+            victims,
+        }
+    }
+
+    // signers the window counts as bots: seen often enough, known or forced
+    pub fn bot_signers(&self) -> &HashSet<String> {
+        &self.bots
+    }
+}
+
+// what a user-supplied detector flagged; `transactions` are the legs that triggered it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustomDetection {
+    pub detector: String,
+    pub description: String,
+    pub transactions: Vec<ParsedTransaction>,
+}
+
+pub struct FrontRunDetector;
+
+pub struct BackRunDetector;
+
+pub struct SandwichDetector;
+
+pub struct BackrunArbDetector;
+
+pub struct RevertedSandwichDetector;
+
+pub fn builtin_detectors() -> Vec<Box<dyn Detector>> {
+    vec![
+        Box::new(FrontRunDetector),
+        Box::new(BackRunDetector),
+        Box::new(SandwichDetector),
+        Box::new(BackrunArbDetector),
+        Box::new(RevertedSandwichDetector),
+    ]
+}
+
+fn is_executed_victim(victim: &ParsedTransaction, cfg: &DetectorConfig) -> bool {
+    victim.outcome != ExecutionOutcome::Reverted && is_candidate_victim(victim, cfg)
+}
+
+impl Detector for FrontRunDetector {
+    fn name(&self) -> &str {
+        "front_run"
+    }
+
+    fn on_window(&self, window: &Window<'_>) -> Vec<DetectionEvent> {
+        let cfg = window.cfg;
+        let mut events = Vec::new();
+        for Victim { tx: victim, frontruns, .. } in &window.victims {
+            if !is_executed_victim(victim, cfg) {
+                continue;
+            }
+            if !frontruns.is_empty() && victim_mode_loss(victim, frontruns, cfg).is_some() {
+                events.push(DetectionEvent::FrontRun(FrontRunEvent {
+                    victim: (*victim).clone(),
+                    frontruns: frontruns.clone(),
+                }));
+            }
+        }
+        events
+    }
+}
+
+impl Detector for BackRunDetector {
+    fn name(&self) -> &str {
+        "back_run"
+    }
+
+    fn on_window(&self, window: &Window<'_>) -> Vec<DetectionEvent> {
+        let cfg = window.cfg;
+        let mut events = Vec::new();
+        for Victim { tx: victim, frontruns, backruns } in &window.victims {
+            if !is_executed_victim(victim, cfg) {
+                continue;
+            }
+            if !backruns.is_empty() && victim_mode_loss(victim, frontruns, cfg).is_some() {
+                events.push(DetectionEvent::BackRun(BackRunEvent {
+                    victim: (*victim).clone(),
+                    backruns: backruns.clone(),
+                }));
+            }
+        }
+        events
+    }
+}
+
+impl Detector for SandwichDetector {
+    fn name(&self) -> &str {
+        "sandwich"
+    }
+
+    fn on_window(&self, window: &Window<'_>) -> Vec<DetectionEvent> {
+        let cfg = window.cfg;
+        let mut events = Vec::new();
+        for Victim { tx: victim, frontruns, backruns } in &window.victims {
+            if !is_executed_victim(victim, cfg) {
+                continue;
+            }
+            if let Some(sandwich) = build_sandwich(victim, frontruns, backruns, cfg) {
+                events.push(DetectionEvent::Sandwich(Box::new(sandwich)));
+            }
+        }
+        events
+    }
+}
+
+impl Detector for BackrunArbDetector {
+    fn name(&self) -> &str {
+        "backrun_arb"
+    }
+
+    // any large trade counts, slippage breach or not
+    fn on_window(&self, window: &Window<'_>) -> Vec<DetectionEvent> {
+        let arb_cfg = arb_config(window.cfg);
+        let mut events = Vec::new();
+        for &Victim { tx: victim, .. } in &window.victims {
+            for backrun in arb_backruns(&window.index, victim, &arb_cfg, &mut Vec::new()) {
+                let Some(close) = window.index.closing_leg(&backrun) else {
+                    continue;
+                };
+                if let Some(event) = backrun_arb(victim, &backrun, close, window.cfg) {
+                    events.push(DetectionEvent::BackrunArb(Box::new(event)));
+                }
+            }
+        }
+        events
+    }
+}
+
+impl Detector for RevertedSandwichDetector {
+    fn name(&self) -> &str {
+        "reverted_sandwich"
+    }
+
+    fn on_window(&self, window: &Window<'_>) -> Vec<DetectionEvent> {
+        let cfg = window.cfg;
+        let mut events = Vec::new();
+        for Victim { tx: victim, frontruns, backruns } in &window.victims {
+            if victim.outcome != ExecutionOutcome::Reverted {
+                continue;
+            }
+            if let Some(event) = reverted_sandwich(victim, frontruns, backruns, cfg) {
+                events.push(DetectionEvent::RevertedSandwich(event));
+            }
+        }
+        events
+    }
+}
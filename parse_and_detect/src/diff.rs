@@ -0,0 +1,54 @@
+use crate::detect::{DetectionSummary, SandwichDetection};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+// a sandwich both runs found, whose attacker profit differs between them
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProfitChange {
+    pub victim_signature: String,
+    pub leg_index: usize,
+    pub left_profit_sol: i64,
+    pub right_profit_sol: i64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SummaryDiff {
+    pub only_left: Vec<SandwichDetection>,
+    pub only_right: Vec<SandwichDetection>,
+    pub in_both: usize,
+    pub profit_changes: Vec<ProfitChange>,
+}
+
+// Sandwiches are matched by the victim's leg, so the same attack found with different
+// attacker legs still counts as common.
+pub fn diff_summaries(left: &DetectionSummary, right: &DetectionSummary) -> SummaryDiff {
+    let by_victim = |summary: &DetectionSummary| -> BTreeMap<(Arc<str>, usize), SandwichDetection> {
+        summary
+            .sandwiches
+            .iter()
+            .map(|det| ((det.victim.signature.clone(), det.victim.leg_index), det.clone()))
+            .collect()
+    };
+    let left = by_victim(left);
+    let mut right = by_victim(right);
+
+    let mut diff = SummaryDiff::default();
+    for (key, det) in left {
+        let Some(other) = right.remove(&key) else {
+            diff.only_left.push(det);
+            continue;
+        };
+        diff.in_both += 1;
+        if det.net_profit_sol != other.net_profit_sol {
+            diff.profit_changes.push(ProfitChange {
+                victim_signature: key.0.to_string(),
+                leg_index: key.1,
+                left_profit_sol: det.net_profit_sol,
+                right_profit_sol: other.net_profit_sol,
+            });
+        }
+    }
+    diff.only_right = right.into_values().collect();
+    diff
+}
@@ -0,0 +1,141 @@
+use crate::detect::{DetectionSummary, LamportsExt};
+use crate::parser::pumpfun::{ExecutionOutcome, ParsedTransaction};
+use crate::price::format_utc;
+use crate::token;
+use serde::Serialize;
+use std::fmt::Write;
+
+// One leg of one attack, flattened the way Dune and ClickHouse tables are laid out: column names
+// follow Dune's `solana.transactions` (`block_slot`, `block_time`, `tx_id`) so rows join on
+// them directly. Attack-level figures are repeated on every leg.
+#[derive(Debug, Clone, Serialize)]
+pub struct AttackLegRow {
+    // `<attack_type>:<victim tx_id>:<victim leg>`, shared by every leg of the attack
+    pub attack_id: String,
+    pub attack_type: &'static str,
+    // frontrun, victim, backrun or close
+    pub role: &'static str,
+    pub block_slot: u64,
+    // "YYYY-MM-DD HH:MM:SS" in UTC, empty when the block time is unknown
+    pub block_time: String,
+    pub tx_id: String,
+    pub leg_index: usize,
+    pub wallet: String,
+    pub token_mint_address: String,
+    pub trade_type: &'static str,
+    pub amount_sol: f64,
+    // in whole tokens at the mint's decimals, like Dune's `token_bought_amount`; the base units are
+    // in `amount_token_raw`
+    pub amount_token: f64,
+    pub amount_token_raw: i64,
+    pub token_decimals: u8,
+    pub fee_lamports: u64,
+    pub priority_fee_lamports: u64,
+    pub tip_lamports: u64,
+    pub succeeded: bool,
+    pub attack_net_profit_sol: f64,
+}
+
+// sandwiches, backrun arbitrages and reverted sandwiches, in that order
+pub fn attack_leg_rows(summary: &DetectionSummary) -> Vec<AttackLegRow> {
+    let decimals = summary.decimals();
+    let mut rows = Vec::new();
+    for det in &summary.sandwiches {
+        let attack = Attack::new("sandwich", &det.victim, det.net_profit_sol, decimals);
+        rows.extend(det.frontruns.iter().map(|leg| attack.row("frontrun", leg)));
+        rows.push(attack.row("victim", &det.victim));
+        rows.extend(det.backruns.iter().map(|leg| attack.row("backrun", leg)));
+    }
+    for arb in &summary.backrun_arbs {
+        let attack = Attack::new("backrun_arb", &arb.victim, arb.net_profit_sol, decimals);
+        rows.push(attack.row("victim", &arb.victim));
+        rows.push(attack.row("backrun", &arb.backrun));
+        rows.push(attack.row("close", &arb.close));
+    }
+    for det in &summary.reverted_sandwiches {
+        let attack = Attack::new("reverted_sandwich", &det.victim, 0, decimals);
+        rows.extend(det.frontruns.iter().map(|leg| attack.row("frontrun", leg)));
+        rows.push(attack.row("victim", &det.victim));
+        rows.extend(det.backruns.iter().map(|leg| attack.row("backrun", leg)));
+    }
+    rows
+}
+
+struct Attack {
+    id: String,
+    kind: &'static str,
+    net_profit: i64,
+    decimals: u8,
+}
+
+impl Attack {
+    fn new(kind: &'static str, victim: &ParsedTransaction, net_profit: i64, decimals: u8) -> Self {
+        Self {
+            id: format!("{}:{}:{}", kind, victim.signature, victim.leg_index),
+            kind,
+            net_profit,
+            decimals,
+        }
+    }
+
+    fn row(&self, role: &'static str, leg: &ParsedTransaction) -> AttackLegRow {
+        AttackLegRow {
+            attack_id: self.id.clone(),
+            attack_type: self.kind,
+            role,
+            block_slot: leg.slot,
+            block_time: leg
+                .block_time
+                .map(|time| format_utc(time).trim_end_matches(" UTC").to_string())
+                .unwrap_or_default(),
+            tx_id: leg.signature.to_string(),
+            leg_index: leg.leg_index,
+            wallet: leg.signer.to_string(),
+            token_mint_address: leg.mint.to_string(),
+            trade_type: leg.trade_type.name(),
+            amount_sol: leg.sol_change.as_sol(),
+            amount_token: token::ui_amount(leg.token_change, self.decimals),
+            amount_token_raw: leg.token_change,
+            token_decimals: self.decimals,
+            fee_lamports: leg.fee,
+            priority_fee_lamports: leg.priority_fee,
+            tip_lamports: leg.tip,
+            succeeded: leg.outcome == ExecutionOutcome::Succeeded,
+            attack_net_profit_sol: self.net_profit.as_sol(),
+        }
+    }
+}
+
+// a header row, then one line per leg; no field contains a comma, so nothing is quoted
+pub fn attack_legs_csv(rows: &[AttackLegRow]) -> String {
+    let mut csv = String::from(
+        "attack_id,attack_type,role,block_slot,block_time,tx_id,leg_index,wallet,token_mint_address,trade_type,\
+amount_sol,amount_token,amount_token_raw,token_decimals,fee_lamports,priority_fee_lamports,tip_lamports,succeeded,attack_net_profit_sol\n",
+    );
+    for row in rows {
+        let _ = writeln!(
+            csv,
+            "{},{},{},{},{},{},{},{},{},{},{:.9},{},{},{},{},{},{},{},{:.9}",
+            row.attack_id,
+            row.attack_type,
+            row.role,
+            row.block_slot,
+            row.block_time,
+            row.tx_id,
+            row.leg_index,
+            row.wallet,
+            row.token_mint_address,
+            row.trade_type,
+            row.amount_sol,
+            token::format_tokens(row.amount_token_raw, row.token_decimals),
+            row.amount_token_raw,
+            row.token_decimals,
+            row.fee_lamports,
+            row.priority_fee_lamports,
+            row.tip_lamports,
+            row.succeeded,
+            row.attack_net_profit_sol
+        );
+    }
+    csv
+}
@@ -0,0 +1,87 @@
+use crate::cuprice;
+use crate::detect::{DetectionSummary, DetectorConfig, detect_wide_attacks};
+use crate::error::Error;
+use crate::fetch::{SavedTrades, decode_transaction};
+use crate::network::PUMP_PROGRAM_ID;
+use crate::parser::pumpfun::{self, ParsedTransaction};
+use crate::rugpull::detect_dev_dumps;
+use crate::sniping::detect_snipers;
+use serde::Deserialize;
+
+// The JSON-in, JSON-out pipeline behind the language bindings (`wasm`, `python`, `ffi`). Nothing
+// here touches the network: the caller supplies the transactions.
+
+// The detector thresholds a binding can set; anything left out keeps the CLI default.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DetectOptions {
+    pub max_slot_gap: Option<u64>,
+    pub adaptive_slot_gap: Option<bool>,
+    pub strict_ordering: Option<bool>,
+    pub min_victim_abs_sol: Option<f64>,
+    pub min_victim_tokens: Option<f64>,
+    pub min_profit_sol: Option<f64>,
+    pub min_bot_trades: Option<usize>,
+}
+
+impl DetectOptions {
+    // a JSON object of these keys; blank is the defaults
+    pub fn from_json(raw: &str) -> Result<Self, Error> {
+        if raw.trim().is_empty() {
+            return Ok(Self::default());
+        }
+        serde_json::from_str(raw).map_err(|e| Error::json("detector options", e))
+    }
+
+    pub fn apply(self, cfg: &mut DetectorConfig) {
+        cfg.max_slot_gap = self.max_slot_gap.unwrap_or(cfg.max_slot_gap);
+        cfg.adaptive_slot_gap = self.adaptive_slot_gap.unwrap_or(cfg.adaptive_slot_gap);
+        cfg.strict_ordering = self.strict_ordering.unwrap_or(cfg.strict_ordering);
+        cfg.min_victim_abs_sol = self.min_victim_abs_sol.unwrap_or(cfg.min_victim_abs_sol);
+        cfg.min_victim_tokens = self.min_victim_tokens.unwrap_or(cfg.min_victim_tokens);
+        cfg.min_profit_sol = self.min_profit_sol.unwrap_or(cfg.min_profit_sol);
+        cfg.min_bot_trades = self.min_bot_trades.unwrap_or(cfg.min_bot_trades);
+    }
+}
+
+// A `getTransaction` response (JSON encoding) -> its trades on `mint` through the mainnet pump.fun
+// program or PumpSwap
+pub fn parse_transaction_json(response: &str, mint: &str) -> Result<Vec<ParsedTransaction>, Error> {
+    let (tx, signature) = decode_transaction(response)?;
+    pumpfun::parse_transaction(&tx, &signature, mint, PUMP_PROGRAM_ID)
+}
+
+// A `--save-trades` file, or just the array of trades in one; a bare array takes its mint from
+// the first trade and carries no lifecycle events or token details.
+pub fn read_trades(raw: &str) -> Result<SavedTrades, Error> {
+    if !raw.trim_start().starts_with('[') {
+        return SavedTrades::from_json(raw, "saved trades");
+    }
+    let trades: Vec<ParsedTransaction> = serde_json::from_str(raw).map_err(|e| Error::json("trades", e))?;
+    Ok(SavedTrades {
+        mint: trades.first().map(|trade| trade.mint.to_string()).unwrap_or_default(),
+        trades,
+        lifecycle_events: Vec::new(),
+        token_decimals: None,
+        token_metadata: None,
+    })
+}
+
+// The summary `--output json` prints for a replay of `saved`, less what needs the chain (curve
+// snapshots, block prices, funding).
+pub fn detect_saved(saved: SavedTrades, options: DetectOptions) -> Result<DetectionSummary, Error> {
+    let mut cfg = DetectorConfig::default();
+    options.apply(&mut cfg);
+    if let Some(decimals) = saved.token_decimals {
+        cfg.token_decimals = decimals;
+    }
+    cfg.validate().map_err(Error::Config)?;
+
+    let mut summary = detect_wide_attacks(&saved.trades, &cfg);
+    summary.token_decimals = saved.token_decimals;
+    summary.token_metadata = saved.token_metadata;
+    summary.dev_dumps = detect_dev_dumps(&saved.trades, &saved.lifecycle_events, &cfg);
+    summary.snipers = detect_snipers(&saved.trades, &saved.lifecycle_events, &cfg);
+    cuprice::rank_cu_prices(&mut summary, &cuprice::trade_cu_prices(&saved.trades));
+    Ok(summary)
+}
@@ -0,0 +1,235 @@
+use crate::detect::{Atomicity, DetectionSummary, LamportsExt, Severity, victim_loss_lamports};
+use crate::network::Network;
+use crate::parser::pumpfun::{ExecutionOutcome, LifecycleEvent, LifecycleKind, ParsedTransaction};
+use crate::price::{UsdValuation, format_utc};
+use crate::token::{self, MintMetadata};
+use serde::Serialize;
+
+pub const SCHEMA: &str = "rusty/enriched-detections/v1";
+
+// A self-describing document for labeling pipelines: every detection carries the token it hit,
+// USD values and explorer links, so a record still makes sense once split out of the file.
+// Fields that can't be known (no create in the scanned history, no price feed, a private
+// cluster) are left out rather than guessed.
+#[derive(Debug, Clone, Serialize)]
+pub struct EnrichedExport {
+    pub schema: &'static str,
+    pub generator: String,
+    pub network: &'static str,
+    pub mint: String,
+    pub trades_parsed: usize,
+    pub detections: Vec<EnrichedDetection>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TokenMetadata {
+    pub mint: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub symbol: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uri: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub creator: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub explorer_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image: Option<String>,
+    pub decimals: u8,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EnrichedDetection {
+    // `<type>:<victim signature>:<victim leg>`, as in the Dune export
+    pub id: String,
+    #[serde(rename = "type")]
+    pub kind: &'static str,
+    pub slot: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub block_time: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub block_time_utc: Option<String>,
+    pub token: TokenMetadata,
+    pub victim: EnrichedLeg,
+    pub attacker_legs: Vec<EnrichedLeg>,
+    pub victim_loss_sol: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub victim_loss_usd: Option<f64>,
+    // reverted sandwiches made no profit to report
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub net_profit_sol: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub net_profit_usd: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub severity: Option<Severity>,
+    // validator that produced the victim's block
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub leader: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub atomic: Option<Atomicity>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EnrichedLeg {
+    pub role: &'static str,
+    pub signature: String,
+    pub leg_index: usize,
+    pub slot: u64,
+    pub wallet: String,
+    pub venue: &'static str,
+    pub trade_type: &'static str,
+    pub succeeded: bool,
+    pub sol_change: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sol_change_usd: Option<f64>,
+    pub token_change: i64,
+    // `token_change` in whole tokens
+    pub token_amount: f64,
+    // paid to Jito tip accounts
+    pub tip_sol: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sol_usd: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tx_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wallet_url: Option<String>,
+}
+
+// Sandwiches, backrun arbitrages and reverted sandwiches, in that order. Token metadata comes
+// from the mint's create instruction when the scan reached back that far, and from its Metaplex
+// metadata account otherwise.
+pub fn enrich(
+    summary: &DetectionSummary,
+    mint: &str,
+    trades_parsed: usize,
+    lifecycle: &[LifecycleEvent],
+    network: Network,
+) -> EnrichedExport {
+    let enricher = Enricher {
+        usd: summary.usd.as_ref(),
+        lifecycle,
+        network,
+        decimals: summary.decimals(),
+        metadata: summary.token_metadata.as_ref().map(|metadata| (mint, metadata)),
+    };
+    let mut detections = Vec::new();
+    for det in &summary.sandwiches {
+        let attackers = det.frontruns.iter().map(|leg| ("frontrun", leg)).chain(det.backruns.iter().map(|leg| ("backrun", leg)));
+        let mut record = enricher.detection("sandwich", &det.victim, attackers, Some(det.net_profit_sol));
+        record.confidence = Some(det.confidence);
+        record.severity = Some(det.severity);
+        record.leader = det.leader.clone();
+        record.atomic = det.atomic;
+        detections.push(record);
+    }
+    for arb in &summary.backrun_arbs {
+        let attackers = [("backrun", &arb.backrun), ("close", &arb.close)].into_iter();
+        detections.push(enricher.detection("backrun_arb", &arb.victim, attackers, Some(arb.net_profit_sol)));
+    }
+    for det in &summary.reverted_sandwiches {
+        let attackers = det.frontruns.iter().map(|leg| ("frontrun", leg)).chain(det.backruns.iter().map(|leg| ("backrun", leg)));
+        detections.push(enricher.detection("reverted_sandwich", &det.victim, attackers, None));
+    }
+
+    EnrichedExport {
+        schema: SCHEMA,
+        generator: format!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION")),
+        network: network.name(),
+        mint: mint.to_string(),
+        trades_parsed,
+        detections,
+    }
+}
+
+struct Enricher<'a> {
+    usd: Option<&'a UsdValuation>,
+    lifecycle: &'a [LifecycleEvent],
+    network: Network,
+    decimals: u8,
+    // the scanned mint's Metaplex metadata
+    metadata: Option<(&'a str, &'a MintMetadata)>,
+}
+
+impl Enricher<'_> {
+    fn detection<'t>(
+        &self,
+        kind: &'static str,
+        victim: &ParsedTransaction,
+        attackers: impl Iterator<Item = (&'static str, &'t ParsedTransaction)>,
+        net_profit: Option<i64>,
+    ) -> EnrichedDetection {
+        let attacker_legs: Vec<EnrichedLeg> = attackers.map(|(role, leg)| self.leg(role, leg)).collect();
+        let loss = victim_loss_lamports(victim) as i64;
+        // each attacker leg at its own block's price, like `UsdValuation::sandwich_profit`
+        let net_profit_usd = net_profit
+            .and_then(|_| attacker_legs.iter().map(|leg| leg.sol_change_usd).sum::<Option<f64>>());
+        EnrichedDetection {
+            id: format!("{}:{}:{}", kind, victim.signature, victim.leg_index),
+            kind,
+            slot: victim.slot,
+            block_time: victim.block_time,
+            block_time_utc: victim.block_time.map(format_utc),
+            token: self.token(&victim.mint),
+            victim: self.leg("victim", victim),
+            attacker_legs,
+            victim_loss_sol: loss.as_sol(),
+            victim_loss_usd: self.usd.and_then(|usd| usd.usd(&victim.signature, loss)),
+            net_profit_sol: net_profit.map(|lamports| lamports.as_sol()),
+            net_profit_usd,
+            confidence: None,
+            severity: None,
+            leader: None,
+            atomic: None,
+        }
+    }
+
+    fn leg(&self, role: &'static str, leg: &ParsedTransaction) -> EnrichedLeg {
+        EnrichedLeg {
+            role,
+            signature: leg.signature.to_string(),
+            leg_index: leg.leg_index,
+            slot: leg.slot,
+            wallet: leg.signer.to_string(),
+            venue: leg.venue.name(),
+            trade_type: leg.trade_type.name(),
+            succeeded: leg.outcome == ExecutionOutcome::Succeeded,
+            sol_change: leg.sol_change.as_sol(),
+            sol_change_usd: self.usd.and_then(|usd| usd.usd(&leg.signature, leg.sol_change)),
+            token_change: leg.token_change,
+            token_amount: token::ui_amount(leg.token_change, self.decimals),
+            tip_sol: (leg.tip as i64).as_sol(),
+            sol_usd: self.usd.and_then(|usd| usd.price(&leg.signature)),
+            tx_url: self.network.explorer_url(&format!("tx/{}", leg.signature)),
+            wallet_url: self.network.explorer_url(&format!("account/{}", leg.signer)),
+        }
+    }
+
+    fn token(&self, mint: &str) -> TokenMetadata {
+        let created = self.lifecycle.iter().find_map(|event| match &event.kind {
+            LifecycleKind::Create {
+                creator,
+                name,
+                symbol,
+                uri,
+            } if event.mint == mint => Some((creator, name, symbol, uri)),
+            _ => None,
+        });
+        let metadata = self.metadata.filter(|(scanned, _)| *scanned == mint).map(|(_, metadata)| metadata);
+        let resolved = |field: fn(&MintMetadata) -> &String| {
+            metadata.map(field).filter(|value| !value.is_empty()).cloned()
+        };
+        TokenMetadata {
+            mint: mint.to_string(),
+            name: created.map(|(_, name, _, _)| name.clone()).or_else(|| resolved(|m| &m.name)),
+            symbol: created.map(|(_, _, symbol, _)| symbol.clone()).or_else(|| resolved(|m| &m.symbol)),
+            uri: created.map(|(_, _, _, uri)| uri.clone()).or_else(|| resolved(|m| &m.uri)),
+            creator: created.map(|(creator, _, _, _)| creator.clone()),
+            explorer_url: self.network.explorer_url(&format!("token/{}", mint)),
+            image: metadata.and_then(|metadata| metadata.image.clone()),
+            decimals: self.decimals,
+        }
+    }
+}
@@ -0,0 +1,88 @@
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("{0}")]
+    Usage(String),
+    #[error("{0}")]
+    Config(String),
+    #[error("Invalid token mint address '{0}'")]
+    InvalidMint(String),
+    #[error("{0} environment variable must be set in .env file")]
+    MissingEnv(&'static str),
+    #[error("Invalid signature format '{signature}': {reason}")]
+    InvalidSignature { signature: String, reason: String },
+    #[error("{0}")]
+    Rpc(String),
+    #[error("Failed to decode transaction {signature}: {reason}")]
+    Decode { signature: String, reason: String },
+    #[error("Lookup table {table}: {reason}")]
+    LookupTable { table: String, reason: String },
+    #[error("Failed to {action} '{path}': {source}")]
+    Io {
+        action: &'static str,
+        path: String,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("Price feed: {0}")]
+    Price(String),
+    #[error("Event sink: {0}")]
+    Sink(String),
+    #[error("Scorer: {0}")]
+    Scorer(String),
+    #[error("Invalid JSON in {context}: {source}")]
+    Json {
+        context: String,
+        #[source]
+        source: serde_json::Error,
+    },
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Error {
+    pub fn io(action: &'static str, path: &str, source: std::io::Error) -> Self {
+        Error::Io {
+            action,
+            path: path.to_string(),
+            source,
+        }
+    }
+
+    pub fn json(context: impl Into<String>, source: serde_json::Error) -> Self {
+        Error::Json {
+            context: context.into(),
+            source,
+        }
+    }
+
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Error::Usage(_) => "usage",
+            Error::Config(_) => "config",
+            Error::InvalidMint(_) => "invalid mint",
+            Error::MissingEnv(_) => "missing env",
+            Error::InvalidSignature { .. } => "invalid signature",
+            Error::Rpc(_) => "rpc",
+            Error::Decode { .. } => "decode",
+            Error::LookupTable { .. } => "lookup table",
+            Error::Price(_) => "price",
+            Error::Sink(_) => "sink",
+            Error::Scorer(_) => "scorer",
+            Error::Io { .. } => "io",
+            Error::Json { .. } => "json",
+        }
+    }
+
+    // process exit status for a run aborted by this error
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            Error::Usage(_) | Error::Config(_) | Error::InvalidMint(_) => 2,
+            Error::MissingEnv(_) => 3,
+            Error::Rpc(_) | Error::InvalidSignature { .. } | Error::LookupTable { .. } | Error::Price(_) | Error::Sink(_) | Error::Scorer(_) => 4,
+            Error::Decode { .. } => 5,
+            Error::Io { .. } | Error::Json { .. } => 6,
+        }
+    }
+}
@@ -0,0 +1,188 @@
+use crate::error::Error;
+use crate::fetch::TransactionSource;
+use solana_sdk::epoch_schedule::EpochSchedule;
+use solana_sdk::pubkey::Pubkey;
+use solana_transaction_status::{EncodedConfirmedTransactionWithStatusMeta, UiTransactionEncoding};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+// an endpoint failing this many calls in a row is skipped for the cooldown
+const MAX_CONSECUTIVE_FAILURES: u32 = 3;
+const COOLDOWN: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone)]
+pub struct EndpointHealth {
+    pub name: String,
+    pub successes: u64,
+    pub failures: u64,
+    pub consecutive_failures: u32,
+    pub available: bool,
+}
+
+struct Endpoint<S> {
+    name: String,
+    source: S,
+    health: Mutex<Health>,
+}
+
+#[derive(Default)]
+struct Health {
+    successes: u64,
+    failures: u64,
+    consecutive_failures: u32,
+    down_until: Option<Instant>,
+}
+
+impl Health {
+    fn available(&self, now: Instant) -> bool {
+        self.down_until.is_none_or(|until| now >= until)
+    }
+}
+
+// Round-robins calls over several endpoints and moves on to the next one when a call fails
+pub struct FailoverSource<S> {
+    endpoints: Vec<Endpoint<S>>,
+    next: AtomicUsize,
+}
+
+impl<S: TransactionSource> FailoverSource<S> {
+    pub fn new(endpoints: Vec<(String, S)>) -> Self {
+        Self {
+            endpoints: endpoints
+                .into_iter()
+                .map(|(name, source)| Endpoint {
+                    name,
+                    source,
+                    health: Mutex::new(Health::default()),
+                })
+                .collect(),
+            next: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.endpoints.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.endpoints.is_empty()
+    }
+
+    pub fn health(&self) -> Vec<EndpointHealth> {
+        let now = Instant::now();
+        self.endpoints
+            .iter()
+            .map(|endpoint| {
+                let health = lock(&endpoint.health);
+                EndpointHealth {
+                    name: endpoint.name.clone(),
+                    successes: health.successes,
+                    failures: health.failures,
+                    consecutive_failures: health.consecutive_failures,
+                    available: health.available(now),
+                }
+            })
+            .collect()
+    }
+
+    // only RPC failures move on to the next endpoint; bad input fails the same everywhere
+    fn call<T>(&self, request: impl Fn(&S) -> Result<T, Error>) -> Result<T, Error> {
+        if self.endpoints.is_empty() {
+            return Err(Error::Rpc("No RPC endpoints configured".to_string()));
+        }
+        let start = self.next.fetch_add(1, Ordering::Relaxed) % self.endpoints.len();
+        let now = Instant::now();
+        let (mut order, cooling): (Vec<&Endpoint<S>>, Vec<&Endpoint<S>>) = self.endpoints[start..]
+            .iter()
+            .chain(&self.endpoints[..start])
+            .partition(|endpoint| lock(&endpoint.health).available(now));
+        order.extend(cooling);
+
+        let mut last_error = None;
+        for endpoint in order {
+            match request(&endpoint.source) {
+                Ok(value) => {
+                    let mut health = lock(&endpoint.health);
+                    health.successes += 1;
+                    health.consecutive_failures = 0;
+                    health.down_until = None;
+                    return Ok(value);
+                }
+                Err(Error::Rpc(message)) => {
+                    let mut health = lock(&endpoint.health);
+                    health.failures += 1;
+                    health.consecutive_failures += 1;
+                    if health.consecutive_failures >= MAX_CONSECUTIVE_FAILURES {
+                        health.down_until = Some(Instant::now() + COOLDOWN);
+                    }
+                    last_error = Some(Error::Rpc(format!("{}: {}", endpoint.name, message)));
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        Err(last_error.unwrap_or_else(|| Error::Rpc("No RPC endpoint answered".to_string())))
+    }
+}
+
+impl<S: TransactionSource> TransactionSource for FailoverSource<S> {
+    fn signatures_page(
+        &self,
+        address: &Pubkey,
+        before: Option<&str>,
+        until: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<String>, Error> {
+        self.call(|source| source.signatures_page(address, before, until, limit))
+    }
+
+    fn transaction(
+        &self,
+        signature: &str,
+        encoding: UiTransactionEncoding,
+    ) -> Result<EncodedConfirmedTransactionWithStatusMeta, Error> {
+        self.call(|source| source.transaction(signature, encoding))
+    }
+
+    fn transactions(
+        &self,
+        signatures: &[String],
+        encoding: UiTransactionEncoding,
+    ) -> Result<Vec<Result<EncodedConfirmedTransactionWithStatusMeta, Error>>, Error> {
+        self.call(|source| source.transactions(signatures, encoding))
+    }
+
+    fn account_data(&self, address: &Pubkey) -> Result<Vec<u8>, Error> {
+        self.call(|source| source.account_data(address))
+    }
+
+    fn block_signatures(&self, slot: u64) -> Result<Vec<String>, Error> {
+        self.call(|source| source.block_signatures(slot))
+    }
+
+    fn block_compute_unit_prices(&self, slot: u64) -> Result<Vec<u64>, Error> {
+        self.call(|source| source.block_compute_unit_prices(slot))
+    }
+
+    fn slot_leaders(&self, start_slot: u64, limit: u64) -> Result<Vec<String>, Error> {
+        self.call(|source| source.slot_leaders(start_slot, limit))
+    }
+
+    fn epoch_schedule(&self) -> Result<EpochSchedule, Error> {
+        self.call(|source| source.epoch_schedule())
+    }
+
+    fn leader_schedule(&self, slot: u64) -> Result<Option<HashMap<String, Vec<usize>>>, Error> {
+        self.call(|source| source.leader_schedule(slot))
+    }
+}
+
+// endpoint label without the query string, which usually carries the API key
+pub fn endpoint_name(url: &str) -> String {
+    url.split('?').next().unwrap_or(url).to_string()
+}
+
+fn lock(health: &Mutex<Health>) -> std::sync::MutexGuard<'_, Health> {
+    health.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
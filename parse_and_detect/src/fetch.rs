@@ -0,0 +1,315 @@
+use crate::error::Error;
+use crate::parser::pumpfun::{LifecycleEvent, ParsedTransaction};
+use crate::token::MintMetadata;
+use serde::{Deserialize, Serialize};
+use solana_commitment_config::{CommitmentConfig, CommitmentLevel};
+use solana_sdk::epoch_schedule::EpochSchedule;
+use solana_sdk::pubkey::Pubkey;
+use solana_transaction_status::{
+    EncodedConfirmedTransactionWithStatusMeta, EncodedTransaction, UiTransactionEncoding,
+};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fs;
+
+// getSignaturesForAddress hands out at most this many per call
+pub const SIGNATURE_PAGE: usize = 1_000;
+
+// Where the detector gets its chain data from: a live RPC node or recorded fixtures
+pub trait TransactionSource {
+    // One getSignaturesForAddress call: newest first, older than `before` and newer than `until`
+    // (both excluded), at most `limit`.
+    fn signatures_page(
+        &self,
+        address: &Pubkey,
+        before: Option<&str>,
+        until: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<String>, Error>;
+    // the newest `limit`, a page at a time
+    fn signatures(&self, address: &Pubkey, limit: usize) -> Result<Vec<String>, Error> {
+        page_signatures(self, address, None, limit)
+    }
+    // every signature newer than `until` (excluded), a page at a time, up to `limit`
+    fn signatures_until(&self, address: &Pubkey, until: &str, limit: usize) -> Result<Vec<String>, Error> {
+        page_signatures(self, address, Some(until), limit)
+    }
+    fn transaction(
+        &self,
+        signature: &str,
+        encoding: UiTransactionEncoding,
+    ) -> Result<EncodedConfirmedTransactionWithStatusMeta, Error>;
+    // The outer error means the whole request failed, the inner ones are per signature. Sources
+    // without a batch call fetch one transaction at a time.
+    fn transactions(
+        &self,
+        signatures: &[String],
+        encoding: UiTransactionEncoding,
+    ) -> Result<Vec<Result<EncodedConfirmedTransactionWithStatusMeta, Error>>, Error> {
+        Ok(signatures.iter().map(|sig| self.transaction(sig, encoding)).collect())
+    }
+    fn account_data(&self, address: &Pubkey) -> Result<Vec<u8>, Error>;
+    // every signature in the block, in execution order
+    fn block_signatures(&self, slot: u64) -> Result<Vec<String>, Error>;
+    // the compute-unit price of every transaction in the block, in micro-lamports
+    fn block_compute_unit_prices(&self, slot: u64) -> Result<Vec<u64>, Error>;
+    // the validator identity scheduled for each of `limit` slots from `start_slot`
+    fn slot_leaders(&self, start_slot: u64, limit: u64) -> Result<Vec<String>, Error>;
+    fn epoch_schedule(&self) -> Result<EpochSchedule, Error>;
+    // validator identity -> its slot indices in the epoch containing `slot`; None when the node
+    // no longer has that epoch's schedule
+    fn leader_schedule(&self, slot: u64) -> Result<Option<HashMap<String, Vec<usize>>>, Error>;
+}
+
+// Walks back from the newest signature with `before` until a short page, `until` or `limit`
+fn page_signatures<S: TransactionSource + ?Sized>(
+    source: &S,
+    address: &Pubkey,
+    until: Option<&str>,
+    limit: usize,
+) -> Result<Vec<String>, Error> {
+    let mut signatures: Vec<String> = Vec::new();
+    while signatures.len() < limit {
+        let wanted = (limit - signatures.len()).min(SIGNATURE_PAGE);
+        let page = source.signatures_page(address, signatures.last().map(String::as_str), until, wanted)?;
+        let last_page = page.len() < wanted;
+        signatures.extend(page);
+        if last_page {
+            break;
+        }
+    }
+    Ok(signatures)
+}
+
+// Overlapping pages and resumed cursors can hand out a signature again; only its first
+// occurrence is kept, so a transaction is never fetched or counted twice. Returns how many were
+// dropped.
+pub fn drop_seen_signatures(signatures: &mut Vec<String>, seen: &mut HashSet<String>) -> usize {
+    let before = signatures.len();
+    signatures.retain(|signature| seen.insert(signature.clone()));
+    before - signatures.len()
+}
+
+// getTransaction and getBlock reject `processed`, so those calls wait for `confirmed`
+pub fn at_least_confirmed(commitment: CommitmentConfig) -> CommitmentConfig {
+    match commitment.commitment {
+        CommitmentLevel::Processed => CommitmentConfig::confirmed(),
+        _ => commitment,
+    }
+}
+
+// Serves every `getTransaction` response saved as JSON in a directory, whatever encoding is asked for
+pub struct FixtureSource {
+    // signature -> (slot, raw response); decoded again on every fetch, as the RPC client would
+    transactions: HashMap<String, (u64, String)>,
+}
+
+impl FixtureSource {
+    pub fn load(dir: &str) -> Result<Self, Error> {
+        let entries = fs::read_dir(dir).map_err(|e| Error::io("read fixtures directory", dir, e))?;
+        let mut transactions = HashMap::new();
+        for entry in entries {
+            let path = entry.map_err(|e| Error::io("read fixtures directory", dir, e))?.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+                continue;
+            }
+            let display = path.display().to_string();
+            let raw = fs::read_to_string(&path).map_err(|e| Error::io("read fixture", &display, e))?;
+            let tx = decode_fixture(&raw, &display)?;
+            let signature = first_signature(&tx).ok_or_else(|| Error::Decode {
+                signature: display,
+                reason: "fixture has no transaction signature".to_string(),
+            })?;
+            transactions.insert(signature, (tx.slot, raw));
+        }
+        Ok(Self { transactions })
+    }
+
+    fn newest_first(&self) -> impl Iterator<Item = &String> {
+        let mut signatures: Vec<(u64, &String)> =
+            self.transactions.iter().map(|(signature, (slot, _))| (*slot, signature)).collect();
+        signatures.sort_by(|a, b| b.cmp(a));
+        signatures.into_iter().map(|(_, signature)| signature)
+    }
+}
+
+impl TransactionSource for FixtureSource {
+    fn signatures_page(
+        &self,
+        _address: &Pubkey,
+        before: Option<&str>,
+        until: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<String>, Error> {
+        let mut newest_first = self.newest_first().peekable();
+        if let Some(before) = before {
+            while newest_first.next().is_some_and(|signature| signature.as_str() != before) {}
+        }
+        Ok(newest_first
+            .take_while(|signature| Some(signature.as_str()) != until)
+            .take(limit)
+            .cloned()
+            .collect())
+    }
+
+    fn transaction(
+        &self,
+        signature: &str,
+        _encoding: UiTransactionEncoding,
+    ) -> Result<EncodedConfirmedTransactionWithStatusMeta, Error> {
+        let (_, raw) = self
+            .transactions
+            .get(signature)
+            .ok_or_else(|| Error::Rpc(format!("No fixture for transaction {}", signature)))?;
+        decode_fixture(raw, signature)
+    }
+
+    fn account_data(&self, address: &Pubkey) -> Result<Vec<u8>, Error> {
+        Err(Error::Rpc(format!("No fixture for account {}", address)))
+    }
+
+    fn block_signatures(&self, slot: u64) -> Result<Vec<String>, Error> {
+        Err(Error::Rpc(format!("No fixture for block {}", slot)))
+    }
+
+    fn block_compute_unit_prices(&self, slot: u64) -> Result<Vec<u64>, Error> {
+        Err(Error::Rpc(format!("No fixture for block {}", slot)))
+    }
+
+    fn slot_leaders(&self, start_slot: u64, _limit: u64) -> Result<Vec<String>, Error> {
+        Err(Error::Rpc(format!("No fixture for the leaders from slot {}", start_slot)))
+    }
+
+    fn epoch_schedule(&self) -> Result<EpochSchedule, Error> {
+        Err(Error::Rpc("No fixture for the epoch schedule".to_string()))
+    }
+
+    fn leader_schedule(&self, slot: u64) -> Result<Option<HashMap<String, Vec<usize>>>, Error> {
+        Err(Error::Rpc(format!("No fixture for the leader schedule of slot {}", slot)))
+    }
+}
+
+// Trades parsed by an earlier run (`--save-trades`), replayed with `--input` without touching
+// the network.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedTrades {
+    pub mint: String,
+    pub trades: Vec<ParsedTransaction>,
+    #[serde(default)]
+    pub lifecycle_events: Vec<LifecycleEvent>,
+    // so a replay renders token amounts without fetching the mint again
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_decimals: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_metadata: Option<MintMetadata>,
+}
+
+impl SavedTrades {
+    pub fn load(path: &str) -> Result<Self, Error> {
+        let raw = fs::read_to_string(path).map_err(|e| Error::io("read saved trades", path, e))?;
+        Self::from_json(&raw, &format!("saved trades '{}'", path))
+    }
+
+    pub fn from_json(raw: &str, context: &str) -> Result<Self, Error> {
+        serde_json::from_str(raw).map_err(|e| Error::json(context, e))
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), Error> {
+        let json = serde_json::to_string(self).map_err(|e| Error::json("saved trades", e))?;
+        fs::write(path, json).map_err(|e| Error::io("write saved trades", path, e))
+    }
+}
+
+// the source behind a `--input` replay; every chain lookup fails instead of going online
+pub struct OfflineSource;
+
+impl TransactionSource for OfflineSource {
+    fn signatures_page(
+        &self,
+        address: &Pubkey,
+        _before: Option<&str>,
+        _until: Option<&str>,
+        _limit: usize,
+    ) -> Result<Vec<String>, Error> {
+        Err(Error::Rpc(format!("offline replay: no signatures for {}", address)))
+    }
+
+    fn transaction(
+        &self,
+        signature: &str,
+        _encoding: UiTransactionEncoding,
+    ) -> Result<EncodedConfirmedTransactionWithStatusMeta, Error> {
+        Err(Error::Rpc(format!("offline replay: no transaction {}", signature)))
+    }
+
+    fn account_data(&self, address: &Pubkey) -> Result<Vec<u8>, Error> {
+        Err(Error::Rpc(format!("offline replay: no account data for {}", address)))
+    }
+
+    fn block_signatures(&self, slot: u64) -> Result<Vec<String>, Error> {
+        Err(Error::Rpc(format!("offline replay: no block {}", slot)))
+    }
+
+    fn block_compute_unit_prices(&self, slot: u64) -> Result<Vec<u64>, Error> {
+        Err(Error::Rpc(format!("offline replay: no block {}", slot)))
+    }
+
+    fn slot_leaders(&self, start_slot: u64, _limit: u64) -> Result<Vec<String>, Error> {
+        Err(Error::Rpc(format!("offline replay: no leaders from slot {}", start_slot)))
+    }
+
+    fn epoch_schedule(&self) -> Result<EpochSchedule, Error> {
+        Err(Error::Rpc("offline replay: no epoch schedule".to_string()))
+    }
+
+    fn leader_schedule(&self, slot: u64) -> Result<Option<HashMap<String, Vec<usize>>>, Error> {
+        Err(Error::Rpc(format!("offline replay: no leader schedule for slot {}", slot)))
+    }
+}
+
+// one getBlock per distinct slot; trades in a block that can't be fetched keep `tx_index: None`
+pub fn assign_tx_indexes(source: &dyn TransactionSource, trades: &mut [ParsedTransaction]) -> Vec<Error> {
+    let slots: BTreeSet<u64> = trades.iter().map(|trade| trade.slot).collect();
+    let mut positions: HashMap<String, usize> = HashMap::new();
+    let mut failures = Vec::new();
+    for slot in slots {
+        match source.block_signatures(slot) {
+            Ok(signatures) => positions.extend(signatures.into_iter().enumerate().map(|(index, sig)| (sig, index))),
+            Err(e) => failures.push(e),
+        }
+    }
+    for trade in trades.iter_mut() {
+        trade.tx_index = positions.get(&*trade.signature).copied();
+    }
+    failures
+}
+
+// One `getTransaction` response handed over as text, bare or still in its JSON-RPC envelope,
+// with the signature it was fetched by.
+pub fn decode_transaction(raw: &str) -> Result<(EncodedConfirmedTransactionWithStatusMeta, String), Error> {
+    let mut value: serde_json::Value = serde_json::from_str(raw).map_err(|e| Error::json("transaction", e))?;
+    if let Some(result) = value.get_mut("result") {
+        value = result.take();
+    }
+    let tx: EncodedConfirmedTransactionWithStatusMeta =
+        serde_json::from_value(value).map_err(|e| Error::json("transaction", e))?;
+    let signature = first_signature(&tx).ok_or_else(|| Error::Decode {
+        signature: "transaction".to_string(),
+        reason: "response has no transaction signature".to_string(),
+    })?;
+    Ok((tx, signature))
+}
+
+fn decode_fixture(raw: &str, name: &str) -> Result<EncodedConfirmedTransactionWithStatusMeta, Error> {
+    serde_json::from_str(raw).map_err(|e| Error::json(format!("fixture '{}'", name), e))
+}
+
+fn first_signature(tx: &EncodedConfirmedTransactionWithStatusMeta) -> Option<String> {
+    match &tx.transaction.transaction {
+        EncodedTransaction::Json(ui) => ui.signatures.first().cloned(),
+        _ => tx
+            .transaction
+            .transaction
+            .decode()
+            .and_then(|versioned| versioned.signatures.first().map(|signature| signature.to_string())),
+    }
+}
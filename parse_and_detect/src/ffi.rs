@@ -0,0 +1,107 @@
+use crate::embed::{self, DetectOptions};
+use crate::error::Error;
+use serde::Serialize;
+use std::cell::RefCell;
+use std::ffi::{CString, c_char};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+
+// The C API of the shared library (`--features ffi`), declared in include/rusty.h. Inputs are
+// UTF-8 JSON buffers with a length, outputs NUL-terminated JSON strings the caller hands back to
+// `rusty_string_free`. A failed call returns NULL and leaves its message for `rusty_last_error`.
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(message: String) {
+    // an interior NUL would cut the message short, so it goes
+    let message = CString::new(message.replace('\0', "")).unwrap_or_default();
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+}
+
+// NULL with a zero length reads as empty
+unsafe fn read_buffer<'a>(data: *const u8, len: usize, what: &str) -> Result<&'a str, Error> {
+    if data.is_null() {
+        return if len == 0 {
+            Ok("")
+        } else {
+            Err(Error::Usage(format!("{} is NULL", what)))
+        };
+    }
+    let bytes = unsafe { std::slice::from_raw_parts(data, len) };
+    std::str::from_utf8(bytes).map_err(|e| Error::Usage(format!("{} is not UTF-8: {}", what, e)))
+}
+
+// runs `call` and returns its result as an owned JSON string; errors and panics become NULL
+fn json_call<T: Serialize>(context: &str, call: impl FnOnce() -> Result<T, Error>) -> *mut c_char {
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        let value = call()?;
+        serde_json::to_string(&value).map_err(|e| Error::json(context, e))
+    }));
+    let message = match result {
+        Ok(Ok(json)) => match CString::new(json) {
+            Ok(json) => return json.into_raw(),
+            Err(e) => format!("{} holds a NUL byte at {}", context, e.nul_position()),
+        },
+        Ok(Err(e)) => e.to_string(),
+        Err(_) => format!("panicked while building {}", context),
+    };
+    set_last_error(message);
+    ptr::null_mut()
+}
+
+/// A `getTransaction` response (JSON encoding) -> a JSON array of its trades on `mint`.
+///
+/// # Safety
+/// `response` and `mint` must point to `response_len` and `mint_len` readable bytes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rusty_parse_transaction(
+    response: *const u8,
+    response_len: usize,
+    mint: *const u8,
+    mint_len: usize,
+) -> *mut c_char {
+    json_call("trades", || {
+        let response = unsafe { read_buffer(response, response_len, "response") }?;
+        let mint = unsafe { read_buffer(mint, mint_len, "mint") }?;
+        embed::parse_transaction_json(response, mint)
+    })
+}
+
+/// A JSON array of trades or a `--save-trades` document -> the detection summary as JSON.
+/// `options` is a JSON object of `DetectOptions` keys; NULL or empty keeps the defaults.
+///
+/// # Safety
+/// `trades` and `options` must point to `trades_len` and `options_len` readable bytes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rusty_detect(
+    trades: *const u8,
+    trades_len: usize,
+    options: *const u8,
+    options_len: usize,
+) -> *mut c_char {
+    json_call("detection summary", || {
+        let trades = unsafe { read_buffer(trades, trades_len, "trades") }?;
+        let options = unsafe { read_buffer(options, options_len, "options") }?;
+        embed::detect_saved(embed::read_trades(trades)?, DetectOptions::from_json(options)?)
+    })
+}
+
+/// The message of this thread's last failed call, or NULL. It stays valid until the thread's next
+/// failed call.
+#[unsafe(no_mangle)]
+pub extern "C" fn rusty_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| last.borrow().as_ref().map_or(ptr::null(), |message| message.as_ptr()))
+}
+
+/// Frees a string returned by this library; NULL is ignored.
+///
+/// # Safety
+/// `json` must come from this library and not have been freed already.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rusty_string_free(json: *mut c_char) {
+    if !json.is_null() {
+        drop(unsafe { CString::from_raw(json) });
+    }
+}
@@ -0,0 +1,173 @@
+use crate::error::Error;
+use crate::fetch::TransactionSource;
+use crate::parser::pumpfun::account_keys;
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+use solana_transaction_status::{EncodedConfirmedTransactionWithStatusMeta, UiTransactionEncoding};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::str::FromStr;
+
+// Where an attacker's SOL came from. Each hop is the largest incoming transfer in the funded
+// wallet's recent history; the chain is followed up to `TraceOptions::max_depth` hops and stops
+// early at a labeled funder (an exchange, bridge or known master wallet), since those fund
+// everyone and tracing past them says nothing about the attacker.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FundingChain {
+    pub wallet: String,
+    // nearest funder first
+    pub hops: Vec<FundingHop>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FundingHop {
+    pub funder: String,
+    pub funded: String,
+    pub signature: String,
+    pub slot: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub block_time: Option<i64>,
+    pub lamports: u64,
+    // from the funder label lists; may be empty for an unnamed entry
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+}
+
+// an unlabeled wallet found in the funding chains of several attackers, or an attacker found in
+// another one's
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SharedFunder {
+    pub funder: String,
+    pub wallets: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct TraceOptions {
+    pub max_depth: usize,
+    // history searched per wallet, newest first
+    pub signature_limit: usize,
+    // smaller transfers are change and fee top-ups, not funding
+    pub min_lamports: u64,
+    pub encoding: UiTransactionEncoding,
+    pub batch_size: usize,
+}
+
+impl Default for TraceOptions {
+    fn default() -> Self {
+        Self {
+            max_depth: 3,
+            signature_limit: 100,
+            min_lamports: 100_000_000,
+            encoding: UiTransactionEncoding::JsonParsed,
+            batch_size: 1,
+        }
+    }
+}
+
+pub fn trace_funding(
+    source: &dyn TransactionSource,
+    wallet: &str,
+    options: &TraceOptions,
+    labels: &BTreeMap<String, String>,
+) -> Result<FundingChain, Error> {
+    let mut hops: Vec<FundingHop> = Vec::new();
+    let mut visited: HashSet<String> = HashSet::from([wallet.to_string()]);
+    let mut current = wallet.to_string();
+    while hops.len() < options.max_depth {
+        let Some(mut hop) = largest_incoming(source, &current, options)? else {
+            break;
+        };
+        hop.label = labels.get(&hop.funder).cloned();
+        let next = hop.funder.clone();
+        let labeled = hop.label.is_some();
+        hops.push(hop);
+        if labeled || !visited.insert(next.clone()) {
+            break;
+        }
+        current = next;
+    }
+    Ok(FundingChain {
+        wallet: wallet.to_string(),
+        hops,
+    })
+}
+
+fn largest_incoming(
+    source: &dyn TransactionSource,
+    wallet: &str,
+    options: &TraceOptions,
+) -> Result<Option<FundingHop>, Error> {
+    let address = Pubkey::from_str(wallet).map_err(|_| Error::Config(format!("Invalid wallet '{}'", wallet)))?;
+    let signatures = source.signatures(&address, options.signature_limit)?;
+    let mut largest: Option<FundingHop> = None;
+    for batch in signatures.chunks(options.batch_size.max(1)) {
+        for (signature, tx) in batch.iter().zip(source.transactions(batch, options.encoding)?) {
+            // a transaction that can't be fetched only leaves a gap in the history
+            let Ok(tx) = tx else {
+                continue;
+            };
+            if let Some(hop) = incoming_transfer(&tx, signature, wallet)
+                && hop.lamports >= options.min_lamports
+                && largest.as_ref().is_none_or(|largest| hop.lamports > largest.lamports)
+            {
+                largest = Some(hop);
+            }
+        }
+    }
+    Ok(largest)
+}
+
+// The fee payer funded `wallet` in this transaction when its SOL went down and the wallet's
+// went up. Balances rather than instructions, so exchange withdrawals and program-routed
+// transfers count too.
+pub fn incoming_transfer(
+    tx: &EncodedConfirmedTransactionWithStatusMeta,
+    signature: &str,
+    wallet: &str,
+) -> Option<FundingHop> {
+    let meta = tx.transaction.meta.as_ref()?;
+    if meta.err.is_some() {
+        return None;
+    }
+    let keys = account_keys(tx, signature).ok()?;
+    let funder = keys.first()?;
+    if funder == wallet {
+        return None;
+    }
+    let change = |account: &str| {
+        let index = keys.iter().position(|key| key == account)?;
+        Some(*meta.post_balances.get(index)? as i128 - *meta.pre_balances.get(index)? as i128)
+    };
+    let received = change(wallet)?;
+    if received <= 0 || change(funder)? >= 0 {
+        return None;
+    }
+    Some(FundingHop {
+        funder: funder.clone(),
+        funded: wallet.to_string(),
+        signature: signature.to_string(),
+        slot: tx.slot,
+        block_time: tx.block_time,
+        lamports: u64::try_from(received).unwrap_or(u64::MAX),
+        label: None,
+    })
+}
+
+// Funders behind more than one attacker, counting an attacker that funded another one. Labeled
+// funders are left out: an exchange paying out to two wallets doesn't tie them together.
+pub fn shared_funders(chains: &[FundingChain]) -> Vec<SharedFunder> {
+    let mut funded: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+    for chain in chains {
+        funded.entry(chain.wallet.as_str()).or_default().insert(chain.wallet.as_str());
+        for hop in chain.hops.iter().filter(|hop| hop.label.is_none()) {
+            funded.entry(hop.funder.as_str()).or_default().insert(chain.wallet.as_str());
+        }
+    }
+    funded
+        .into_iter()
+        .filter(|(_, wallets)| wallets.len() > 1)
+        .map(|(funder, wallets)| SharedFunder {
+            funder: funder.to_string(),
+            wallets: wallets.into_iter().map(str::to_string).collect(),
+        })
+        .collect()
+}
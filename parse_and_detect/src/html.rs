@@ -0,0 +1,237 @@
+use rusty::detect::{DetectionSummary, LamportsExt};
+use rusty::parser::pumpfun::TradeType;
+use rusty::price::{format_usd, format_utc};
+use std::fmt::Write;
+
+const SOLSCAN: &str = "https://solscan.io";
+
+const STYLE: &str = "body{font-family:system-ui,sans-serif;margin:2rem;color:#1d1d1f;background:#fafafa}\
+h1{font-size:1.5rem}h2{font-size:1.15rem;margin-top:2rem}\
+table{border-collapse:collapse;width:100%;background:#fff;font-size:.9rem}\
+th,td{border:1px solid #ddd;padding:.4rem .6rem;text-align:left;vertical-align:top}\
+th{background:#f0f0f3}td.num{text-align:right;font-variant-numeric:tabular-nums}\
+a{color:#5b3cc4;text-decoration:none}a:hover{text-decoration:underline}\
+.cards{display:flex;flex-wrap:wrap;gap:1rem}\
+.card{background:#fff;border:1px solid #ddd;border-radius:6px;padding:.8rem 1.2rem}\
+.card b{display:block;font-size:1.4rem}\
+.bar{background:#eee;width:10rem;height:.8rem;border-radius:3px}\
+.bar span{display:block;height:100%;background:#d9534f;border-radius:3px}\
+.buy{color:#2e7d32}.sell{color:#c62828}\
+.logo{height:2.2rem;vertical-align:middle;margin-right:.6rem;border-radius:50%}";
+
+// Self-contained page (inline CSS, no scripts) for sharing outside the terminal
+pub fn render_html(mint: &str, trade_count: usize, summary: &DetectionSummary) -> String {
+    let mut html = String::new();
+    let extracted: i64 = summary.sandwiches.iter().map(|det| det.net_profit_sol).sum();
+
+    let _ = write!(
+        html,
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+<title>Sandwich report for {label}{mint}</title>\n<style>{STYLE}</style>\n</head>\n<body>\n\
+<h1>{image}Sandwich report for {label}{mint_link}</h1>\n<div class=\"cards\">\n",
+        label = escape(&token_label(summary)),
+        image = token_image(summary),
+        mint = escape(mint),
+        mint_link = token_link(mint),
+    );
+    for (label, value) in [
+        ("Trades parsed", trade_count.to_string()),
+        ("Sandwiches", summary.sandwiches.len().to_string()),
+        ("Reverted victims", summary.reverted_sandwiches.len().to_string()),
+        ("Attackers", summary.attacker_leaderboard.len().to_string()),
+        ("SOL extracted", format!("{:.4}", extracted.as_sol())),
+    ] {
+        let _ = writeln!(html, "<div class=\"card\">{}<b>{}</b></div>", label, value);
+    }
+    if let Some(usd) = &summary.usd {
+        let _ = writeln!(
+            html,
+            "<div class=\"card\">USD extracted<b>{}</b></div>\n<div class=\"card\">Victim losses<b>{}</b></div>",
+            format_usd(usd.total_extracted_usd),
+            format_usd(usd.total_victim_loss_usd)
+        );
+    }
+    html.push_str("</div>\n");
+
+    render_sandwiches(&mut html, summary);
+    render_leaderboard(&mut html, summary);
+    render_mint_stats(&mut html, summary);
+
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+fn render_sandwiches(html: &mut String, summary: &DetectionSummary) {
+    html.push_str("<h2>Sandwiches</h2>\n");
+    if summary.sandwiches.is_empty() {
+        html.push_str("<p>No sandwiches detected.</p>\n");
+        return;
+    }
+
+    let max_loss = summary
+        .sandwiches
+        .iter()
+        .map(|det| det.victim_loss())
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    html.push_str(
+        "<table>\n<tr><th>#</th><th>Slot</th><th>Time</th><th>Victim</th><th>Victim tx</th><th>Side</th>\
+<th>Victim ΔSOL</th><th>Victim loss (SOL)</th><th>Impact</th><th>Attackers</th><th>Attacker profit (SOL)</th></tr>\n",
+    );
+    let usd = summary.usd.as_ref();
+    for (idx, det) in summary.sandwiches.iter().enumerate() {
+        let loss = det.victim_loss();
+        let mut attackers: Vec<&str> = det
+            .frontruns
+            .iter()
+            .chain(det.backruns.iter())
+            .map(|leg| leg.signer.as_ref())
+            .collect();
+        attackers.sort_unstable();
+        attackers.dedup();
+
+        let _ = writeln!(
+            html,
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td class=\"num\">{:+.6}</td>\
+<td class=\"num\">{:.6}{}</td><td><div class=\"bar\"><span style=\"width:{:.1}%\"></span></div></td>\
+<td>{}</td><td class=\"num\">{:.6}{}</td></tr>",
+            idx + 1,
+            det.victim.slot,
+            det.victim.block_time.map(format_utc).unwrap_or_default(),
+            account_link(&det.victim.signer),
+            tx_link(&det.victim.signature),
+            side(det.victim.trade_type),
+            det.victim.sol_change.as_sol(),
+            (loss as i64).as_sol(),
+            with_usd(usd.and_then(|usd| usd.usd(&det.victim.signature, loss as i64))),
+            loss as f64 * 100.0 / max_loss as f64,
+            attackers.iter().map(|signer| account_link(signer)).collect::<Vec<_>>().join("<br>"),
+            det.net_profit_sol.as_sol(),
+            with_usd(usd.and_then(|usd| usd.sandwich_profit(det)))
+        );
+    }
+    html.push_str("</table>\n");
+}
+
+fn render_leaderboard(html: &mut String, summary: &DetectionSummary) {
+    if summary.attacker_leaderboard.is_empty() {
+        return;
+    }
+    html.push_str(
+        "<h2>Attacker leaderboard</h2>\n<table>\n<tr><th>#</th><th>Attacker</th><th>Profit (SOL)</th>\
+<th>Attacks</th><th>Victims</th><th>Mints</th><th>Avg SOL/attack</th></tr>\n",
+    );
+    for (rank, attacker) in summary.attacker_leaderboard.iter().enumerate() {
+        let _ = writeln!(
+            html,
+            "<tr><td>{}</td><td>{}{}</td><td class=\"num\">{:.6}{}</td><td class=\"num\">{}</td>\
+<td class=\"num\">{}</td><td class=\"num\">{}</td><td class=\"num\">{:.6}</td></tr>",
+            rank + 1,
+            account_link(&attacker.attacker),
+            escape(&attacker.tag()),
+            attacker.total_profit_lamports.as_sol(),
+            with_usd(
+                summary
+                    .usd
+                    .as_ref()
+                    .and_then(|usd| usd.attacker_profit(attacker))
+            ),
+            attacker.attacks,
+            attacker.victims,
+            attacker.mints.len(),
+            attacker.avg_profit_lamports.as_sol()
+        );
+    }
+    html.push_str("</table>\n");
+}
+
+fn render_mint_stats(html: &mut String, summary: &DetectionSummary) {
+    if summary.mint_stats.is_empty() {
+        return;
+    }
+    html.push_str(
+        "<h2>Per-mint summary</h2>\n<table>\n<tr><th>Mint</th><th>Trades</th><th>Victims</th>\
+<th>Attack rate</th><th>Extracted (SOL)</th><th>Attackers</th><th>Median victim loss (SOL)</th></tr>\n",
+    );
+    for stats in &summary.mint_stats {
+        let _ = writeln!(
+            html,
+            "<tr><td>{}</td><td class=\"num\">{}</td><td class=\"num\">{}</td><td class=\"num\">{:.2}%</td>\
+<td class=\"num\">{:.6}</td><td class=\"num\">{}</td><td class=\"num\">{:.6}</td></tr>",
+            token_link(&stats.mint),
+            stats.total_trades,
+            stats.total_victims,
+            stats.attack_rate_pct,
+            stats.total_sol_extracted.as_sol(),
+            stats.unique_attackers,
+            (stats.median_victim_loss as i64).as_sol()
+        );
+    }
+    html.push_str("</table>\n");
+}
+
+fn with_usd(usd: Option<f64>) -> String {
+    usd.map(|usd| format!("<br><small>{}</small>", format_usd(usd))).unwrap_or_default()
+}
+
+fn side(trade: TradeType) -> &'static str {
+    match trade {
+        TradeType::Buy => "<span class=\"buy\">BUY</span>",
+        TradeType::Sell => "<span class=\"sell\">SELL</span>",
+    }
+}
+
+fn account_link(address: &str) -> String {
+    link(&format!("{}/account/{}", SOLSCAN, address), address)
+}
+
+// "$WIF " ahead of the mint, when the metadata was resolved
+fn token_label(summary: &DetectionSummary) -> String {
+    let label = summary.token_metadata.as_ref().and_then(|metadata| metadata.label());
+    label.map(|label| format!("{} ", label)).unwrap_or_default()
+}
+
+// the only external resource on the page, so an offline viewer just loses the logo
+fn token_image(summary: &DetectionSummary) -> String {
+    let image = summary.token_metadata.as_ref().and_then(|metadata| metadata.image.as_deref());
+    match image {
+        Some(image) if image.starts_with("https://") => {
+            format!("<img class=\"logo\" src=\"{}\" alt=\"\">", escape(image))
+        }
+        _ => String::new(),
+    }
+}
+
+fn token_link(mint: &str) -> String {
+    link(&format!("{}/token/{}", SOLSCAN, mint), mint)
+}
+
+fn tx_link(signature: &str) -> String {
+    link(&format!("{}/tx/{}", SOLSCAN, signature), signature)
+}
+
+fn link(url: &str, label: &str) -> String {
+    // by characters, so a label that isn't base58 can't be cut inside one
+    let chars: Vec<char> = label.chars().collect();
+    let short = if chars.len() <= 12 {
+        label.to_string()
+    } else {
+        let head: String = chars[..6].iter().collect();
+        let tail: String = chars[chars.len() - 6..].iter().collect();
+        format!("{}…{}", head, tail)
+    };
+    format!(
+        "<a href=\"{}\" title=\"{}\">{}</a>",
+        escape(url),
+        escape(label),
+        escape(&short)
+    )
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,87 @@
+use crate::detect::{SandwichDetection, order_key};
+use crate::parser::pumpfun::ParsedTransaction;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+// Gaps between the attacker legs nearest the victim and the victim itself. Atomic bundles land
+// all three in one block; statistical bots that react to the mempool show up slots later.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SandwichLatency {
+    pub front_slots: u64,
+    pub back_slots: u64,
+    // seconds from block_time, when both blocks report one
+    pub front_secs: Option<i64>,
+    pub back_secs: Option<i64>,
+}
+
+impl SandwichLatency {
+    pub fn measure(
+        victim: &ParsedTransaction,
+        frontruns: &[ParsedTransaction],
+        backruns: &[ParsedTransaction],
+    ) -> Option<Self> {
+        let front = frontruns.iter().max_by(|a, b| order_key(a).cmp(&order_key(b)))?;
+        let back = backruns.iter().min_by(|a, b| order_key(a).cmp(&order_key(b)))?;
+        Some(Self {
+            front_slots: victim.slot.saturating_sub(front.slot),
+            back_slots: back.slot.saturating_sub(victim.slot),
+            front_secs: seconds_between(front, victim),
+            back_secs: seconds_between(victim, back),
+        })
+    }
+
+    pub fn span_slots(&self) -> u64 {
+        self.front_slots + self.back_slots
+    }
+
+    pub fn span_secs(&self) -> Option<i64> {
+        Some(self.front_secs? + self.back_secs?)
+    }
+
+    pub fn same_block(&self) -> bool {
+        self.span_slots() == 0
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LatencyDistribution {
+    pub sandwiches: usize,
+    pub same_block: usize,
+    // front-to-back span in slots -> sandwiches
+    pub span_slots: BTreeMap<u64, usize>,
+    pub median_span_slots: u64,
+    pub max_span_slots: u64,
+    // over the sandwiches whose legs all have a block time
+    pub median_span_secs: Option<i64>,
+}
+
+pub fn latency_distribution(sandwiches: &[SandwichDetection]) -> Option<LatencyDistribution> {
+    if sandwiches.is_empty() {
+        return None;
+    }
+    let mut distribution = LatencyDistribution {
+        sandwiches: sandwiches.len(),
+        ..LatencyDistribution::default()
+    };
+    let mut spans: Vec<u64> = Vec::new();
+    let mut secs: Vec<i64> = Vec::new();
+    for det in sandwiches {
+        let latency = det.latency;
+        if latency.same_block() {
+            distribution.same_block += 1;
+        }
+        *distribution.span_slots.entry(latency.span_slots()).or_default() += 1;
+        spans.push(latency.span_slots());
+        secs.extend(latency.span_secs());
+    }
+    spans.sort_unstable();
+    secs.sort_unstable();
+    distribution.median_span_slots = spans[spans.len() / 2];
+    distribution.max_span_slots = spans[spans.len() - 1];
+    distribution.median_span_secs = secs.get(secs.len() / 2).copied();
+    Some(distribution)
+}
+
+fn seconds_between(earlier: &ParsedTransaction, later: &ParsedTransaction) -> Option<i64> {
+    Some(later.block_time? - earlier.block_time?)
+}
@@ -0,0 +1,215 @@
+use crate::collusion::AttackerEntities;
+use crate::detect::{FrontRunEvent, RevertedSandwichEvent, SandwichDetection};
+use crate::parser::pumpfun::ParsedTransaction;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AttackerStats {
+    pub attacker: String,
+    pub total_profit_lamports: i64,
+    pub attacks: usize,
+    pub victims: usize,
+    pub mints: Vec<String>,
+    pub avg_profit_lamports: i64,
+    pub previously_seen: bool,
+    // set when the attacker is on the known-bot list; the label may be empty
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub known_bot: Option<String>,
+    // the other wallets `attacker` stands for when it is a colluding or linked group
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub linked_wallets: Vec<String>,
+}
+
+impl AttackerStats {
+    pub fn tag(&self) -> String {
+        let known = match (&self.known_bot, self.previously_seen) {
+            (Some(label), _) if !label.is_empty() => format!(" (known attacker: {})", label),
+            (Some(_), _) => " (known attacker)".to_string(),
+            (None, true) => " (known)".to_string(),
+            (None, false) => String::new(),
+        };
+        match self.linked_wallets.len() {
+            0 => known,
+            linked => format!("{} (+{} linked)", known, linked),
+        }
+    }
+
+    pub fn wallets(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(self.attacker.as_str()).chain(self.linked_wallets.iter().map(String::as_str))
+    }
+}
+
+#[derive(Default)]
+struct AttackerTally<'a> {
+    profit: i64,
+    attacks: usize,
+    victims: HashSet<&'a str>,
+    mints: BTreeSet<&'a str>,
+}
+
+pub fn attacker_leaderboard(
+    sandwiches: &[SandwichDetection],
+    known_attackers: &HashSet<String>,
+    known_bots: &HashMap<String, String>,
+    entities: &AttackerEntities,
+) -> Vec<AttackerStats> {
+    let mut tallies: HashMap<&str, AttackerTally> = HashMap::new();
+
+    for det in sandwiches {
+        // a colluding pair's legs add up to one attacker
+        let mut profit_by_signer: HashMap<&str, i64> = HashMap::new();
+        for leg in det.frontruns.iter().chain(det.backruns.iter()) {
+            let profit = profit_by_signer.entry(entities.entity(&leg.signer)).or_default();
+            *profit = profit.saturating_add(leg.sol_change);
+        }
+
+        for (signer, profit) in profit_by_signer {
+            let tally = tallies.entry(signer).or_default();
+            tally.profit = tally.profit.saturating_add(profit);
+            tally.attacks += 1;
+            tally.victims.insert(det.victim.signer.as_ref());
+            tally.mints.insert(det.victim.mint.as_ref());
+        }
+    }
+
+    let mut board: Vec<AttackerStats> = tallies
+        .into_iter()
+        .map(|(attacker, tally)| {
+            let linked_wallets = entities.members(attacker);
+            let wallets = || std::iter::once(attacker).chain(linked_wallets.iter().map(String::as_str));
+            AttackerStats {
+                attacker: attacker.to_string(),
+                total_profit_lamports: tally.profit,
+                attacks: tally.attacks,
+                victims: tally.victims.len(),
+                mints: tally.mints.into_iter().map(str::to_string).collect(),
+                avg_profit_lamports: tally.profit / tally.attacks as i64,
+                previously_seen: wallets().any(|wallet| known_attackers.contains(wallet)),
+                known_bot: wallets().find_map(|wallet| known_bots.get(wallet).cloned()),
+                linked_wallets,
+            }
+        })
+        .collect();
+
+    board.sort_by(|a, b| {
+        b.total_profit_lamports
+            .cmp(&a.total_profit_lamports)
+            .then_with(|| a.attacker.cmp(&b.attacker))
+    });
+    board
+}
+
+// How each attacker fared across the scanned window. An attempt is a victim the wallet was seen
+// frontrunning, whether or not the sandwich closed; colluding or linked wallets count as one
+// attacker. Capital is the SOL its frontrun legs moved.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AttackerPerformance {
+    pub attacker: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub linked_wallets: Vec<String>,
+    pub attempted: usize,
+    pub completed: usize,
+    pub win_rate: f64,
+    pub capital_deployed_lamports: u64,
+    pub total_profit_lamports: i64,
+    pub roi_pct: f64,
+    // frontrun to backrun, over the completed sandwiches the wallet held both sides of
+    pub avg_holding_slots: f64,
+}
+
+#[derive(Default)]
+struct PerformanceTally<'a> {
+    attempts: HashSet<(&'a str, usize)>,
+    completed: usize,
+    capital: u64,
+    profit: i64,
+    holding_slots: u64,
+    held: usize,
+}
+
+pub fn attacker_performance(
+    front_runs: &[FrontRunEvent],
+    sandwiches: &[SandwichDetection],
+    reverted: &[RevertedSandwichEvent],
+    entities: &AttackerEntities,
+) -> Vec<AttackerPerformance> {
+    let mut tallies: HashMap<&str, PerformanceTally> = HashMap::new();
+    for event in front_runs {
+        record_attempt(&mut tallies, &event.victim, &event.frontruns, entities);
+    }
+    for event in reverted {
+        record_attempt(&mut tallies, &event.victim, &event.frontruns, entities);
+    }
+
+    for det in sandwiches {
+        record_attempt(&mut tallies, &det.victim, &det.frontruns, entities);
+        let mut signers: BTreeSet<&str> = BTreeSet::new();
+        for leg in det.frontruns.iter().chain(det.backruns.iter()) {
+            signers.insert(entities.entity(&leg.signer));
+        }
+        for signer in signers {
+            let tally = tallies.entry(signer).or_default();
+            // a wallet that only backran someone else's frontrun still shares in the sandwich
+            tally.attempts.insert((det.victim.signature.as_ref(), det.victim.leg_index));
+            tally.completed += 1;
+            let frontruns = det.frontruns.iter().filter(|leg| entities.entity(&leg.signer) == signer);
+            let backruns = det.backruns.iter().filter(|leg| entities.entity(&leg.signer) == signer);
+            tally.capital = frontruns
+                .clone()
+                .fold(tally.capital, |capital, leg| capital.saturating_add(leg.sol_change.unsigned_abs()));
+            tally.profit = frontruns
+                .clone()
+                .chain(backruns.clone())
+                .fold(tally.profit, |profit, leg| profit.saturating_add(leg.sol_change));
+            if let (Some(opened), Some(closed)) =
+                (frontruns.map(|leg| leg.slot).min(), backruns.map(|leg| leg.slot).max())
+            {
+                tally.holding_slots += closed.saturating_sub(opened);
+                tally.held += 1;
+            }
+        }
+    }
+
+    let mut stats: Vec<AttackerPerformance> = tallies
+        .into_iter()
+        .map(|(attacker, tally)| AttackerPerformance {
+            attacker: attacker.to_string(),
+            linked_wallets: entities.members(attacker),
+            attempted: tally.attempts.len(),
+            completed: tally.completed,
+            win_rate: tally.completed as f64 / tally.attempts.len() as f64,
+            capital_deployed_lamports: tally.capital,
+            total_profit_lamports: tally.profit,
+            roi_pct: if tally.capital > 0 {
+                tally.profit as f64 / tally.capital as f64 * 100.0
+            } else {
+                0.0
+            },
+            avg_holding_slots: if tally.held > 0 {
+                tally.holding_slots as f64 / tally.held as f64
+            } else {
+                0.0
+            },
+        })
+        .collect();
+
+    stats.sort_by(|a, b| {
+        b.total_profit_lamports
+            .cmp(&a.total_profit_lamports)
+            .then_with(|| a.attacker.cmp(&b.attacker))
+    });
+    stats
+}
+
+fn record_attempt<'a>(
+    tallies: &mut HashMap<&'a str, PerformanceTally<'a>>,
+    victim: &'a ParsedTransaction,
+    frontruns: &'a [ParsedTransaction],
+    entities: &'a AttackerEntities,
+) {
+    for leg in frontruns {
+        let tally = tallies.entry(entities.entity(&leg.signer)).or_default();
+        tally.attempts.insert((victim.signature.as_ref(), victim.leg_index));
+    }
+}
@@ -0,0 +1,180 @@
+use crate::detect::{DetectionSummary, SandwichDetection};
+use crate::error::Error;
+use crate::fetch::TransactionSource;
+use crate::leaderschedule::{LeaderScheduleCache, LeaderSchedules};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+// the most slots one getSlotLeaders call answers for
+pub const MAX_SLOT_LEADERS: u64 = 5_000;
+
+// Sandwiches per validator whose block the victim landed in. A validator running a modified
+// client or a private mempool shows up as hosting far more than its share.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LeaderStats {
+    pub leader: String,
+    pub sandwiches: usize,
+    pub victim_loss_lamports: u64,
+    pub extracted_lamports: i64,
+}
+
+// attackers need this many sandwiches with a known leader before their spread means anything
+pub const TARGETING_MIN_SANDWICHES: usize = 5;
+
+// The validator hosting most of an attacker's sandwiches, next to the share of slots it was
+// scheduled to lead in the same epochs. A lift well above 1 means the attacker picks its
+// leaders (a validator running its bundles or mempool) rather than landing wherever it can.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LeaderTargeting {
+    pub attacker: String,
+    pub sandwiches: usize,
+    pub leader: String,
+    pub leader_sandwiches: usize,
+    pub share_pct: f64,
+    pub scheduled_share_pct: f64,
+    pub lift: f64,
+}
+
+// Sets `leader` on every sandwich. Slots are looked up in the cached epoch schedules first;
+// epochs the node has no schedule for fall back to one getSlotLeaders call per run of victim
+// slots. Slots whose lookup failed are left without a leader and the failures returned.
+pub fn attach_slot_leaders(
+    summary: &mut DetectionSummary,
+    source: &dyn TransactionSource,
+    schedules: &mut LeaderSchedules,
+) -> Vec<Error> {
+    let slots: BTreeSet<u64> = summary.sandwiches.iter().map(|det| det.victim.slot).collect();
+    let mut leaders: HashMap<u64, String> = HashMap::new();
+    let mut failures = Vec::new();
+    let mut unscheduled: Vec<u64> = Vec::new();
+    for &slot in &slots {
+        // once the schedule lookup fails, every remaining slot takes the fallback
+        if !failures.is_empty() {
+            unscheduled.push(slot);
+            continue;
+        }
+        match schedules.epoch(source, slot) {
+            Ok(Some(epoch)) => {
+                if let Some(leader) = epoch.leader(slot) {
+                    leaders.insert(slot, leader.to_string());
+                }
+            }
+            Ok(None) => unscheduled.push(slot),
+            Err(e) => {
+                failures.push(e);
+                unscheduled.push(slot);
+            }
+        }
+    }
+    if let Err(e) = schedules.save() {
+        failures.push(e);
+    }
+
+    let mut pending = unscheduled.into_iter().peekable();
+    while let Some(start) = pending.next() {
+        let mut end = start;
+        while let Some(&next) = pending.peek()
+            && next < start + MAX_SLOT_LEADERS
+        {
+            end = next;
+            pending.next();
+        }
+        match source.slot_leaders(start, end - start + 1) {
+            Ok(scheduled) => leaders.extend((start..).zip(scheduled)),
+            Err(e) => failures.push(e),
+        }
+    }
+    for det in &mut summary.sandwiches {
+        det.leader = leaders.get(&det.victim.slot).cloned();
+    }
+    summary.leaders = leader_stats(&summary.sandwiches);
+    summary.leader_targeting = leader_targeting(summary, schedules.cache());
+    failures
+}
+
+// most sandwiches first, ties in leader address order; sandwiches without a known leader are
+// left out
+pub fn leader_stats(sandwiches: &[SandwichDetection]) -> Vec<LeaderStats> {
+    let mut by_leader: BTreeMap<&str, LeaderStats> = BTreeMap::new();
+    for det in sandwiches {
+        let Some(leader) = det.leader.as_deref() else {
+            continue;
+        };
+        let stats = by_leader.entry(leader).or_insert_with(|| LeaderStats {
+            leader: leader.to_string(),
+            sandwiches: 0,
+            victim_loss_lamports: 0,
+            extracted_lamports: 0,
+        });
+        stats.sandwiches += 1;
+        stats.victim_loss_lamports += det.victim_loss();
+        stats.extracted_lamports += det.net_profit_sol;
+    }
+    let mut stats: Vec<LeaderStats> = by_leader.into_values().collect();
+    stats.sort_by_key(|stats| std::cmp::Reverse(stats.sandwiches));
+    stats
+}
+
+// One record per attacker (as grouped in `attacker_performance`) with enough sandwiches in
+// cached epochs, highest lift first.
+pub fn leader_targeting(summary: &DetectionSummary, cache: &LeaderScheduleCache) -> Vec<LeaderTargeting> {
+    let mut entity: HashMap<&str, &str> = HashMap::new();
+    for performance in &summary.attacker_performance {
+        entity.insert(&performance.attacker, &performance.attacker);
+        for wallet in &performance.linked_wallets {
+            entity.insert(wallet, &performance.attacker);
+        }
+    }
+    let slot_counts: HashMap<u64, HashMap<&str, usize>> =
+        cache.epochs.iter().map(|(&epoch, leaders)| (epoch, leaders.slot_counts())).collect();
+
+    // attacker -> (leader -> sandwiches, epochs they fell in)
+    let mut by_attacker: BTreeMap<&str, (BTreeMap<&str, usize>, BTreeSet<u64>)> = BTreeMap::new();
+    for det in &summary.sandwiches {
+        let (Some(leader), Some(epoch)) = (det.leader.as_deref(), cache.epoch_of(det.victim.slot)) else {
+            continue;
+        };
+        if !slot_counts.contains_key(&epoch) {
+            continue;
+        }
+        let attackers: BTreeSet<&str> = det
+            .frontruns
+            .iter()
+            .chain(det.backruns.iter())
+            .map(|leg| entity.get(leg.signer.as_ref()).copied().unwrap_or(leg.signer.as_ref()))
+            .collect();
+        for attacker in attackers {
+            let (leaders, epochs) = by_attacker.entry(attacker).or_default();
+            *leaders.entry(leader).or_default() += 1;
+            epochs.insert(epoch);
+        }
+    }
+
+    let mut targeting: Vec<LeaderTargeting> = Vec::new();
+    for (attacker, (leaders, epochs)) in by_attacker {
+        let sandwiches: usize = leaders.values().sum();
+        let Some((&leader, &leader_sandwiches)) = leaders.iter().max_by_key(|(_, count)| **count) else {
+            continue;
+        };
+        if sandwiches < TARGETING_MIN_SANDWICHES {
+            continue;
+        }
+        let counts = epochs.iter().filter_map(|epoch| slot_counts.get(epoch));
+        let (led, scheduled) = counts.fold((0, 0), |(led, scheduled), counts| {
+            (led + counts.get(leader).copied().unwrap_or_default(), scheduled + counts.values().sum::<usize>())
+        });
+        let share_pct = leader_sandwiches as f64 / sandwiches as f64 * 100.0;
+        let scheduled_share_pct = if scheduled == 0 { 0.0 } else { led as f64 / scheduled as f64 * 100.0 };
+        targeting.push(LeaderTargeting {
+            attacker: attacker.to_string(),
+            sandwiches,
+            leader: leader.to_string(),
+            leader_sandwiches,
+            share_pct,
+            scheduled_share_pct,
+            lift: if scheduled_share_pct > 0.0 { share_pct / scheduled_share_pct } else { 0.0 },
+        });
+    }
+    targeting.sort_by(|a, b| b.lift.total_cmp(&a.lift));
+    targeting
+}
@@ -0,0 +1,157 @@
+use crate::error::{Error, Result};
+use crate::fetch::TransactionSource;
+use serde::{Deserialize, Serialize};
+use solana_sdk::epoch_schedule::EpochSchedule;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::Path;
+
+// epochs kept in the cache file; older ones are dropped as new ones are fetched
+pub const MAX_CACHED_EPOCHS: usize = 8;
+
+// Epoch leader schedules persisted between runs, so attributing blocks to validators costs one
+// getLeaderSchedule per epoch instead of a getSlotLeaders per range of slots. An epoch's
+// schedule is fixed once the epoch starts, so a cached one never goes stale; a slot in an epoch
+// not cached yet (the chain rolled over since the last run) fetches that epoch's schedule.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LeaderScheduleCache {
+    #[serde(default)]
+    pub epoch_schedule: Option<EpochSchedule>,
+    pub epochs: BTreeMap<u64, EpochLeaders>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EpochLeaders {
+    pub first_slot: u64,
+    pub validators: Vec<String>,
+    // index into `validators` for every slot of the epoch, u32::MAX where none was scheduled
+    pub slots: Vec<u32>,
+}
+
+impl EpochLeaders {
+    pub fn from_schedule(first_slot: u64, slots_in_epoch: u64, schedule: &HashMap<String, Vec<usize>>) -> Self {
+        let mut validators: Vec<String> = schedule.keys().cloned().collect();
+        validators.sort();
+        let mut slots = vec![u32::MAX; slots_in_epoch as usize];
+        for (index, validator) in validators.iter().enumerate() {
+            for &slot_index in &schedule[validator] {
+                if let Some(slot) = slots.get_mut(slot_index) {
+                    *slot = index as u32;
+                }
+            }
+        }
+        Self {
+            first_slot,
+            validators,
+            slots,
+        }
+    }
+
+    pub fn leader(&self, slot: u64) -> Option<&str> {
+        let index = *self.slots.get(usize::try_from(slot.checked_sub(self.first_slot)?).ok()?)?;
+        self.validators.get(index as usize).map(String::as_str)
+    }
+
+    // how many of the epoch's slots each validator leads
+    pub fn slot_counts(&self) -> HashMap<&str, usize> {
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        for &index in &self.slots {
+            if let Some(validator) = self.validators.get(index as usize) {
+                *counts.entry(validator.as_str()).or_default() += 1;
+            }
+        }
+        counts
+    }
+}
+
+impl LeaderScheduleCache {
+    pub fn load(path: &str) -> Result<Self> {
+        if !Path::new(path).exists() {
+            return Ok(Self::default());
+        }
+        let raw = fs::read_to_string(path).map_err(|e| Error::io("read leader schedule cache", path, e))?;
+        serde_json::from_str(&raw).map_err(|e| Error::json(format!("leader schedule cache '{}'", path), e))
+    }
+
+    pub fn save(&self, path: &str) -> Result<()> {
+        let json = serde_json::to_string(self).map_err(|e| Error::json("leader schedule cache", e))?;
+        fs::write(path, json).map_err(|e| Error::io("write leader schedule cache", path, e))
+    }
+
+    pub fn epoch_of(&self, slot: u64) -> Option<u64> {
+        self.epoch_schedule.as_ref().map(|schedule| schedule.get_epoch(slot))
+    }
+}
+
+// The cache plus where it is saved. Epochs the node has no schedule for any more are remembered
+// for the run, so their slots go straight to the getSlotLeaders fallback.
+pub struct LeaderSchedules {
+    cache: LeaderScheduleCache,
+    path: Option<String>,
+    unavailable: Vec<u64>,
+    changed: bool,
+}
+
+impl LeaderSchedules {
+    // without a path the schedules only live for this run
+    pub fn load(path: Option<&str>) -> Result<Self> {
+        Ok(Self {
+            cache: match path {
+                Some(path) => LeaderScheduleCache::load(path)?,
+                None => LeaderScheduleCache::default(),
+            },
+            path: path.map(str::to_string),
+            unavailable: Vec::new(),
+            changed: false,
+        })
+    }
+
+    pub fn cache(&self) -> &LeaderScheduleCache {
+        &self.cache
+    }
+
+    // the schedule of the epoch containing `slot`, fetched on first use. Ok(None) when the node
+    // has no schedule for that epoch.
+    pub fn epoch(&mut self, source: &dyn TransactionSource, slot: u64) -> Result<Option<&EpochLeaders>> {
+        let schedule = match &self.cache.epoch_schedule {
+            Some(schedule) => schedule.clone(),
+            None => {
+                let schedule = source.epoch_schedule()?;
+                self.cache.epoch_schedule = Some(schedule.clone());
+                self.changed = true;
+                schedule
+            }
+        };
+        let epoch = schedule.get_epoch(slot);
+        if self.unavailable.contains(&epoch) {
+            return Ok(None);
+        }
+        if !self.cache.epochs.contains_key(&epoch) {
+            let first_slot = schedule.get_first_slot_in_epoch(epoch);
+            let Some(leaders) = source.leader_schedule(first_slot)? else {
+                self.unavailable.push(epoch);
+                return Ok(None);
+            };
+            while self.cache.epochs.len() >= MAX_CACHED_EPOCHS {
+                self.cache.epochs.pop_first();
+            }
+            let slots_in_epoch = schedule.get_slots_in_epoch(epoch);
+            self.cache
+                .epochs
+                .insert(epoch, EpochLeaders::from_schedule(first_slot, slots_in_epoch, &leaders));
+            self.changed = true;
+        }
+        Ok(self.cache.epochs.get(&epoch))
+    }
+
+    // writes the cache back if anything was fetched
+    pub fn save(&mut self) -> Result<()> {
+        if let Some(path) = &self.path
+            && self.changed
+        {
+            self.cache.save(path)?;
+            self.changed = false;
+        }
+        Ok(())
+    }
+}
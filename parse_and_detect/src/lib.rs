@@ -0,0 +1,49 @@
+pub mod alt;
+pub mod amm;
+#[cfg(feature = "rpc")]
+pub mod batch;
+pub mod botlist;
+pub mod collusion;
+pub mod copytrade;
+pub mod cuprice;
+pub mod dataset;
+pub mod detect;
+pub mod detector;
+pub mod diff;
+pub mod dune;
+pub mod embed;
+pub mod enriched;
+pub mod error;
+pub mod failover;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod fetch;
+pub mod funding;
+pub mod latency;
+pub mod leaderboard;
+pub mod leaders;
+pub mod leaderschedule;
+pub mod network;
+pub mod parser;
+pub mod price;
+pub mod pricehistory;
+pub mod pumpswap;
+#[cfg(feature = "python")]
+pub mod python;
+pub mod ratelimit;
+#[cfg(feature = "rpc")]
+pub mod rpc;
+pub mod registry;
+pub mod rugpull;
+pub mod scoring;
+pub mod sink;
+pub mod snapshot;
+pub mod sniping;
+pub mod streaming;
+pub mod sweep;
+pub mod tips;
+pub mod token;
+pub mod trends;
+pub mod victims;
+#[cfg(feature = "wasm")]
+pub mod wasm;
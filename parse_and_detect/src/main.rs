@@ -1,246 +1,759 @@
-use solana_client::rpc_client::{GetConfirmedSignaturesForAddress2Config, RpcClient};
-use solana_client::rpc_config::RpcTransactionConfig;
+use solana_commitment_config::CommitmentConfig;
 use solana_sdk::pubkey::Pubkey;
-use solana_sdk::signature::Signature;
-use solana_transaction_status::UiTransactionEncoding;
+use solana_transaction_status::EncodedConfirmedTransactionWithStatusMeta;
+use std::collections::BTreeSet;
 use std::env;
+use std::fs;
+use std::process::ExitCode;
+use std::sync::mpsc::sync_channel;
+use std::thread;
+use std::time::Duration;
 use std::str::FromStr;
 use dotenvy::dotenv;
+use indicatif::{ProgressBar, ProgressStyle};
 
-mod detect;
-mod parser;
-use detect::{DetectorConfig, LamportsExt, detect_wide_attacks};
-use parser::pumpfun::TradeType;
+mod cli;
+mod config;
+mod daemon;
+mod dashboard;
+mod html;
+mod markdown;
+mod pipeline;
+mod report;
+mod tui;
+use cli::{DiffArgs, OutputFormat, ReportArgs};
+use config::{OutputSettings, PriceSettings, PriceSource, RpcSettings, RunConfig, ScoringSettings, SinkSettings};
+use rusty::alt;
+use rusty::amm::LAMPORTS_PER_SOL;
+use rusty::batch::BatchRpcClient;
+use rusty::dataset;
+use rusty::dune;
+use rusty::enriched;
+use rusty::botlist::{self, BotList};
+use rusty::collusion;
+use rusty::cuprice;
+use rusty::detect::{DetectionSummary, SandwichDetection, detect_parallel, detect_wide_attacks};
+use rusty::diff;
+use rusty::error::Error;
+use rusty::failover::{FailoverSource, endpoint_name};
+use rusty::funding::{self, TraceOptions};
+use rusty::fetch::{self, FixtureSource, OfflineSource, SavedTrades, TransactionSource};
+use rusty::leaders;
+use rusty::leaderschedule::LeaderSchedules;
+use rusty::network::Network;
+use rusty::parser;
+use rusty::ratelimit::{RateLimitedSource, RateLimiter};
+use rusty::registry::{self, AttackerRegistry};
+use rusty::sink::{self, EventSink, NatsSink, RedisSink};
+use rusty::parser::pumpfun::{LifecycleEvent, ParsedTransaction};
+use rusty::price::{self, CoinGecko, FixedPrice, PriceFeed};
+use rusty::pricehistory::CachedFeed;
+use rusty::rugpull::detect_dev_dumps;
+use rusty::sniping::detect_snipers;
+use rusty::scoring::{self, CommandScorer, HttpScorer, Scorer};
+use rusty::snapshot;
+use rusty::streaming::DetectionEvent;
+use rusty::sweep::{self, SweepGrid};
+use rusty::token::{self, MintMetadata};
+use rusty::trends;
+use rusty::victims;
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
+fn main() -> ExitCode {
     dotenv().ok();
 
+    match run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            ExitCode::from(e.exit_code())
+        }
+    }
+}
+
+fn run() -> Result<(), Error> {
     let args: Vec<String> = env::args().collect();
-    let mint_address_str = args.get(1).ok_or("Error: Missing token mint address argument. Usage: cargo run <MINT_ADDRESS>")?;
-    let mint_address = Pubkey::from_str(mint_address_str).map_err(|_| "Error: Invalid token mint address format")?;
-
-    let api_key = env::var("HELIUS_API_KEY").map_err(|_| "Error: HELIUS_API_KEY environment variable must be set in .env file")?;
-    let rpc_url = format!("https://mainnet.helius-rpc.com/?api-key={}", api_key);
-    let client = RpcClient::new(rpc_url.to_string());
-
-    let signatures_config = GetConfirmedSignaturesForAddress2Config {
-        limit: Some(50),
-        before: None,
-        until: None,
-        commitment: None,
+    let cli = cli::parse_args(&args)?;
+
+    let mut run_config = match &cli.config_path {
+        Some(path) => RunConfig::load(path)?,
+        None => RunConfig::default(),
     };
+    run_config.apply_cli(&cli);
+    if let Some(report_args) = &cli.report {
+        return period_report(report_args, &run_config.output);
+    }
+    if let Some(diff_args) = &cli.diff {
+        return diff_report(diff_args, &run_config.output);
+    }
+    run_config.detector.validate().map_err(Error::Config)?;
 
-    let mut parsed_trades: Vec<parser::pumpfun::ParsedTransaction> = Vec::new();
+    let mut registry = match &run_config.registry_path {
+        Some(path) => Some(AttackerRegistry::load(path)?),
+        None => None,
+    };
+    if let Some(registry) = &registry {
+        run_config.detector.known_attackers = registry.known_signers();
+    }
+    if let Some(path) = &run_config.wallets.exclude_file {
+        run_config.detector.excluded_wallets = registry::load_wallet_list(path)?;
+    }
+    if let Some(path) = &run_config.wallets.bots_file {
+        run_config.detector.forced_bots = registry::load_wallet_list(path)?;
+    }
+    if let Some(path) = &run_config.wallets.linked_file {
+        run_config.detector.linked_wallets = collusion::load_linked_wallets(path)?;
+    }
+    run_config.detector.known_bots = known_bots(&run_config)?.bots.into_iter().collect();
 
-    let signatures = client
-        .get_signatures_for_address_with_config(&mint_address, signatures_config)
-        .map_err(|e| format!("Error: Failed to fetch transaction signatures: {}", e))?;
+    let pump_program = Pubkey::from_str(&run_config.network.pump_program_id)
+        .map_err(|_| Error::Config(format!("Invalid pump program id '{}'", run_config.network.pump_program_id)))?;
+    if run_config.network.network != Network::Mainnet {
+        report::status(cli.quiet, &format!("Network: {}", run_config.network.network.name()));
+    }
 
-    println!(
-        "Found {} signatures. Fetching transactions...",
-        signatures.len()
-    );
+    // the dataset export writes CSV or JSON only; refused before the fetch rather than written
+    // as CSV under a Parquet name
+    if let Some(path) = &run_config.output.dataset_file
+        && path.ends_with(".parquet")
+    {
+        return Err(Error::Usage(format!(
+            "--dataset writes CSV or JSON, not Parquet; write '{}.csv' and convert it, e.g. with DuckDB",
+            path.trim_end_matches(".parquet")
+        )));
+    }
 
-    for tx_info in signatures {
-        let signature = Signature::from_str(&tx_info.signature)
-            .map_err(|e| format!("Error: Invalid signature format '{}': {}", tx_info.signature, e))?;
+    // --input, --save-trades and sweep are refused with the other arguments; these can come from
+    // the config file too
+    if cli.stream {
+        let needs_trades: Vec<&str> = [
+            (run_config.rpc.tx_index, "--tx-index"),
+            (run_config.rpc.block_cu_prices, "--block-cu-prices"),
+            (run_config.output.dataset_file.is_some(), "the dataset export"),
+            (run_config.scoring.command.is_some() || run_config.scoring.url.is_some(), "scoring"),
+            (run_config.sink.nats_url.is_some(), "--nats, which publishes every trade"),
+        ]
+        .into_iter()
+        .filter_map(|(set, name)| set.then_some(name))
+        .collect();
+        if !needs_trades.is_empty() {
+            return Err(Error::Usage(format!(
+                "--stream keeps no trades, so it can't be combined with {}",
+                needs_trades.join(", ")
+            )));
+        }
+    }
 
-        let config = RpcTransactionConfig {
-            encoding: Some(UiTransactionEncoding::JsonParsed),
-            max_supported_transaction_version: Some(0),
-            commitment: None,
-        };
+    if run_config.output.format == OutputFormat::Tui && cli.daemon.is_none() && cli.sweep.is_none() {
+        tui::require_terminal()?;
+    }
 
-        match client.get_transaction_with_config(&signature, config) {
-            Ok(tx) => {
-                let result = parser::pumpfun::parse_transaction(
-                    &tx,
-                    &signature.to_string(),
-                    mint_address_str,
-                );
+    let saved = cli.input.as_deref().map(SavedTrades::load).transpose()?;
+    let fixtures = cli.fixtures_dir.as_deref().map(FixtureSource::load).transpose()?;
+    let mut rpc = None;
+    let source: &dyn TransactionSource = match (&fixtures, &saved) {
+        (Some(fixtures), _) => fixtures,
+        (None, Some(_)) => &OfflineSource,
+        (None, None) => rpc.insert(rpc_source(&run_config.rpc, run_config.network.network)?),
+    };
 
-                if let Some(parsed_tx) = result {
-                    parsed_trades.push(parsed_tx);
-                }
+    let mut sinks = event_sinks(&run_config.sink)?;
+    if let Some(daemon_args) = &cli.daemon {
+        // each watched mint's detector takes that mint's decimals
+        report::print_detector_config(cli.quiet, &run_config.detector);
+        return daemon::run(daemon_args, source, &run_config, &mut sinks, cli.quiet);
+    }
+
+    // before the fetch, since a streaming scan detects as it goes
+    let token_decimals = match &saved {
+        Some(saved) => saved.token_decimals.or_else(|| mint_decimals(source, &saved.mint)),
+        None => Pubkey::from_str(&cli.mint).ok().and_then(|_| mint_decimals(source, &cli.mint)),
+    };
+    run_config.detector.token_decimals = token_decimals.unwrap_or(token::DEFAULT_DECIMALS);
+    report::print_detector_config(cli.quiet, &run_config.detector);
+
+    let mut streamed = None;
+    let saved_metadata = saved.as_ref().and_then(|saved| saved.token_metadata.clone());
+    let (mint_address_str, parsed_trades, lifecycle_events) = match saved {
+        Some(saved) => {
+            if !cli.mint.is_empty() && cli.mint != saved.mint {
+                return Err(Error::Usage(format!(
+                    "--input holds trades for {}, not {}",
+                    saved.mint, cli.mint
+                )));
             }
-            Err(e) => eprintln!("Failed {}: {}", signature, e),
-        }
-    }
-
-    println!(
-        "Successfully parsed {} pump.fun trades.",
-        parsed_trades.len()
-    );
-    println!("need to do sandwich attack analysis now");
-
-    let config = DetectorConfig::default();
-    let summary = detect_wide_attacks(&parsed_trades, &config);
-
-    println!("---- Detection Summary ----");
-    println!("Total trades parsed: {}", parsed_trades.len());
-    println!("Wide front-run candidates: {}", summary.front_runs.len());
-    println!("Wide back-run candidates: {}", summary.back_runs.len());
-    println!("Wide sandwich candidates: {}", summary.sandwiches.len());
-
-    if !summary.front_runs.is_empty() {
-        println!("\n-- Front-run Events --");
-        for (idx, event) in summary.front_runs.iter().enumerate() {
-            println!(
-                "#{:02} Victim {} | slot {} | {} | ΔSOL {:+.4} SOL | Δtoken {} | Wanted: {} tokens (SOL limit {})",
-                idx + 1,
-                short_sig(&event.victim.signature),
-                event.victim.slot,
-                trade_badge(event.victim.trade_type),
-                event.victim.sol_change.as_sol(),
-                event.victim.token_change,
-                event.victim.token_amount_requested,
-                event.victim.sol_limit_specified
+            report::status(
+                cli.quiet,
+                &format!("Replaying {} saved trades for {}.", saved.trades.len(), saved.mint),
             );
-            println!("Impact:{}", format_attack_impact(&event.victim));
-            for (leg_idx, fr) in event.frontruns.iter().enumerate() {
-                println!(
-                    "FR{:02} [{}] slot {} signer {} | ΔSOL {:+.4} SOL | Δtoken {}",
-                    leg_idx + 1,
-                    trade_badge(fr.trade_type),
-                    fr.slot,
-                    short_sig(&fr.signer),
-                    fr.sol_change.as_sol(),
-                    fr.token_change
-                );
-            }
+            (saved.mint, saved.trades, saved.lifecycle_events)
         }
-    }
+        None => {
+            let mint_address =
+                Pubkey::from_str(&cli.mint).map_err(|_| Error::InvalidMint(cli.mint.clone()))?;
+            let signatures = source.signatures(&mint_address, run_config.rpc.signature_limit)?;
 
-    if !summary.back_runs.is_empty() {
-        println!("\n-- Back-run Events --");
-        for (idx, event) in summary.back_runs.iter().enumerate() {
-            println!(
-                "#{:02} Victim {} | slot {} | {} | ΔSOL {:+.4} SOL | Δtoken {} | Wanted: {} tokens (SOL limit {})",
-                idx + 1,
-                short_sig(&event.victim.signature),
-                event.victim.slot,
-                trade_badge(event.victim.trade_type),
-                event.victim.sol_change.as_sol(),
-                event.victim.token_change,
-                event.victim.token_amount_requested,
-                event.victim.sol_limit_specified
+            report::status(
+                cli.quiet,
+                &format!("Found {} signatures. Fetching transactions...", signatures.len()),
             );
-            println!("Impact:{}", format_attack_impact(&event.victim));
-            for (leg_idx, br) in event.backruns.iter().enumerate() {
-                println!(
-                    "BR{:02} [{}] slot {} signer {} | ΔSOL {:+.4} SOL | Δtoken {}",
-                    leg_idx + 1,
-                    trade_badge(br.trade_type),
-                    br.slot,
-                    short_sig(&br.signer),
-                    br.sol_change.as_sol(),
-                    br.token_change
+
+            // a live dashboard draws on the terminal the progress bar would use
+            let live_dashboard = cli.stream && run_config.output.format == OutputFormat::Tui;
+            let progress = fetch_progress(signatures.len() as u64, cli.quiet || live_dashboard);
+            if cli.stream {
+                let scan = if live_dashboard {
+                    let (sender, receiver) = sync_channel(pipeline::CHANNEL_BOUND);
+                    thread::scope(|scope| {
+                        let dashboard = scope.spawn(|| {
+                            tui::live(&cli.mint, &run_config.detector, run_config.output.min_severity, receiver)
+                        });
+                        let scan = pipeline::scan(source, &signatures, &cli.mint, &run_config, &progress, Some(sender));
+                        dashboard
+                            .join()
+                            .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+                            .map(|()| scan)
+                    })?
+                } else {
+                    pipeline::scan(source, &signatures, &cli.mint, &run_config, &progress, None)
+                };
+                report::status(cli.quiet, &format!("Streamed {} pump.fun trades.", scan.trades));
+                report::status(
+                    cli.quiet,
+                    "Insider dumps, launch snipers and compute-unit price ranks need every trade and are skipped.",
+                );
+                report::print_error_summary(&scan.failures);
+                streamed = Some(scan);
+                (cli.mint.clone(), Vec::new(), Vec::new())
+            } else {
+                let mut lookup_tables = alt::LookupTableCache::new();
+                let Fetched {
+                    trades,
+                    lifecycle_events,
+                    failures,
+                } = fetch_trades(
+                    source,
+                    &signatures,
+                    &cli.mint,
+                    &run_config.network.pump_program_id,
+                    &run_config.rpc,
+                    &mut lookup_tables,
+                    &progress,
                 );
+
+                report::status(cli.quiet, &format!("Successfully parsed {} pump.fun trades.", trades.len()));
+                report::print_error_summary(&failures);
+                (cli.mint.clone(), trades, lifecycle_events)
             }
         }
+    };
+    let trade_count = streamed.as_ref().map_or(parsed_trades.len(), |scan| scan.trades);
+    let mint_address_str = mint_address_str.as_str();
+    let token_metadata = saved_metadata.or_else(|| mint_metadata(source, mint_address_str, &run_config));
+    if let Some(path) = &cli.save_trades {
+        let saved = SavedTrades {
+            mint: mint_address_str.to_string(),
+            trades: parsed_trades.clone(),
+            lifecycle_events: lifecycle_events.clone(),
+            token_decimals,
+            token_metadata: token_metadata.clone(),
+        };
+        saved.save(path)?;
+        report::status(cli.quiet, &format!("Saved {} trades to {}", parsed_trades.len(), path));
     }
 
-    if !summary.sandwiches.is_empty() {
-        println!("\n-- Sandwich Events --");
-        for (idx, det) in summary.sandwiches.iter().enumerate() {
-            println!(
-                "#{} Victim {} @ slot {} | {} | ΔSOL {:+.4} SOL | Δtoken {} | Wanted: {} tokens (SOL limit {})",
-                idx + 1,
-                short_sig(&det.victim.signature),
-                det.victim.slot,
-                trade_badge(det.victim.trade_type),
-                det.victim.sol_change.as_sol(),
-                det.victim.token_change,
-                det.victim.token_amount_requested,
-                det.victim.sol_limit_specified
-            );
-            println!("Impact:{}", format_attack_impact(&det.victim));
-            println!("Frontruns: {}", det.frontruns.len());
-            println!("Backruns: {}", det.backruns.len());
-            println!(
-                "Profit (SOL): {:.6}, net tokens {}",
-                det.net_profit_sol.abs_as_sol(),
-                det.net_token_delta
-            );
-            for (leg_idx, fr) in det.frontruns.iter().enumerate() {
-                println!(
-                    "FR{:02} [{}] slot {} signer {} | ΔSOL {:+.4} SOL | Δtoken {}",
-                    leg_idx + 1,
-                    trade_badge(fr.trade_type),
-                    fr.slot,
-                    short_sig(&fr.signer),
-                    fr.sol_change.as_sol(),
-                    fr.token_change
-                );
+    if let Some(sweep_args) = &cli.sweep {
+        let grid = SweepGrid {
+            max_slot_gaps: sweep_args.max_slot_gaps.clone(),
+            min_profit_sol: sweep_args.min_profit_sol.clone(),
+            min_victim_abs_sol: sweep_args.min_victim_abs_sol.clone(),
+        };
+        let points = match detection_pool(cli.threads)? {
+            Some(pool) => pool.install(|| sweep::run_sweep(&parsed_trades, &run_config.detector, &grid)),
+            None => sweep::run_sweep(&parsed_trades, &run_config.detector, &grid),
+        };
+        return match run_config.output.format {
+            OutputFormat::Json => {
+                let json = serde_json::to_string_pretty(&points).map_err(|e| Error::json("sweep results", e))?;
+                write_output(run_config.output.file.as_deref(), &json)
             }
-            for (leg_idx, br) in det.backruns.iter().enumerate() {
-                println!(
-                    "BR{:02} [{}] slot {} signer {} | ΔSOL {:+.4} SOL | Δtoken {}",
-                    leg_idx + 1,
-                    trade_badge(br.trade_type),
-                    br.slot,
-                    short_sig(&br.signer),
-                    br.sol_change.as_sol(),
-                    br.token_change
-                );
+            _ => {
+                report::print_sweep(&points);
+                Ok(())
+            }
+        };
+    }
+
+    let mut summary = match streamed {
+        Some(scan) => scan.summary,
+        None => match detection_pool(cli.threads)? {
+            Some(pool) => pool.install(|| detect_parallel(&parsed_trades, &run_config.detector)),
+            None => detect_wide_attacks(&parsed_trades, &run_config.detector),
+        },
+    };
+    if let Some(scorer) = scorer(&run_config.scoring, &run_config.rpc)? {
+        let failures = scoring::apply_scorer(
+            &mut summary,
+            &parsed_trades,
+            &run_config.detector,
+            scorer.as_ref(),
+            run_config.scoring.min_score,
+        );
+        for e in failures {
+            eprintln!("Scoring failed, candidate kept: {}", e);
+        }
+    }
+    summary.retain_min_severity(run_config.output.min_severity, &run_config.detector);
+    summary.token_decimals = token_decimals;
+    summary.token_metadata = token_metadata;
+    summary.dev_dumps = detect_dev_dumps(&parsed_trades, &lifecycle_events, &run_config.detector);
+    summary.snipers = detect_snipers(&parsed_trades, &lifecycle_events, &run_config.detector);
+    for e in snapshot::attach_reserve_snapshots(&mut summary, &parsed_trades, source, &pump_program) {
+        eprintln!("Curve snapshot unavailable: {}", e);
+    }
+    let mut cu_prices = cuprice::trade_cu_prices(&parsed_trades);
+    if run_config.rpc.block_cu_prices {
+        for e in cuprice::fetch_block_cu_prices(source, &summary, &mut cu_prices) {
+            eprintln!("Block compute-unit prices unavailable: {}", e);
+        }
+    }
+    cuprice::rank_cu_prices(&mut summary, &cu_prices);
+    if run_config.funding.enabled {
+        trace_attacker_funding(source, &run_config, &mut summary, cli.quiet)?;
+    }
+    // after the funding trace, so leader targeting sees the attackers it linked
+    if run_config.rpc.slot_leaders {
+        let mut schedules = LeaderSchedules::load(run_config.rpc.leader_schedule_cache.as_deref())?;
+        for e in leaders::attach_slot_leaders(&mut summary, source, &mut schedules) {
+            eprintln!("Slot leaders unavailable: {}", e);
+        }
+    }
+    // after the curve account, block, leader and funding lookups, so they are counted too
+    if let Some(rpc) = &rpc
+        && !cli.quiet
+    {
+        report::print_endpoint_health(&rpc.health());
+    }
+
+    if let Some(feed) = price_feed(&run_config.price, &run_config.rpc)? {
+        match price::value_summary(feed.as_ref(), &summary) {
+            Ok(valuation) => summary.usd = Some(valuation),
+            Err(e) => eprintln!("Skipping USD valuation: {}", e),
+        }
+    }
+
+    let events = sink::summary_events(&summary);
+    for sink in sinks.iter_mut() {
+        sink::publish_trades(sink.as_mut(), mint_address_str, &parsed_trades)?;
+        sink::publish_events(sink.as_mut(), mint_address_str, &events)?;
+        sink.flush()?;
+    }
+
+    if let (Some(registry), Some(path)) = (registry.as_mut(), &run_config.registry_path) {
+        registry.record(&summary.attacker_leaderboard);
+        registry.save(path)?;
+    }
+
+    if let Some(path) = &run_config.output.victims_file {
+        let victims = victims::victim_losses(&summary);
+        let export = if path.ends_with(".json") {
+            serde_json::to_string_pretty(&victims).map_err(|e| Error::json("victim export", e))?
+        } else {
+            victims::victims_csv(&victims)
+        };
+        fs::write(path, export).map_err(|e| Error::io("write victim export", path, e))?;
+        report::status(cli.quiet, &format!("Wrote {} victim wallets to {}", victims.len(), path));
+    }
+
+    if let Some(path) = &run_config.output.dataset_file {
+        let rows = dataset::candidate_features(&parsed_trades, &run_config.detector, &summary);
+        let export = if path.ends_with(".json") {
+            serde_json::to_string_pretty(&rows).map_err(|e| Error::json("dataset export", e))?
+        } else {
+            dataset::features_csv(&rows)
+        };
+        fs::write(path, export).map_err(|e| Error::io("write dataset", path, e))?;
+        report::status(cli.quiet, &format!("Wrote {} labeled candidates to {}", rows.len(), path));
+    }
+
+    if let Some(path) = &run_config.output.dune_file {
+        let rows = dune::attack_leg_rows(&summary);
+        let export = if path.ends_with(".json") {
+            serde_json::to_string_pretty(&rows).map_err(|e| Error::json("Dune export", e))?
+        } else {
+            dune::attack_legs_csv(&rows)
+        };
+        fs::write(path, export).map_err(|e| Error::io("write Dune export", path, e))?;
+        report::status(cli.quiet, &format!("Wrote {} attack legs to {}", rows.len(), path));
+    }
+
+    match run_config.output.format {
+        OutputFormat::Text => {
+            if !cli.quiet {
+                for leg in &parsed_trades {
+                    report::print_leg_report(leg, summary.decimals());
+                }
             }
-            println!();
+            report::print_text_report(trade_count, &lifecycle_events, &summary);
+        }
+        OutputFormat::Json => {
+            let json = serde_json::to_string_pretty(&summary).map_err(|e| Error::json("detection summary", e))?;
+            write_output(run_config.output.file.as_deref(), &json)?;
         }
+        OutputFormat::Html => {
+            let page = html::render_html(mint_address_str, trade_count, &summary);
+            write_output(run_config.output.file.as_deref(), &page)?;
+        }
+        OutputFormat::Markdown => {
+            let document = markdown::render_markdown(mint_address_str, trade_count, &summary);
+            write_output(run_config.output.file.as_deref(), &document)?;
+        }
+        OutputFormat::Enriched => {
+            let export = enriched::enrich(
+                &summary,
+                mint_address_str,
+                trade_count,
+                &lifecycle_events,
+                run_config.network.network,
+            );
+            let json = serde_json::to_string_pretty(&export).map_err(|e| Error::json("enriched export", e))?;
+            write_output(run_config.output.file.as_deref(), &json)?;
+        }
+        // a streamed scan was shown live, while it ran
+        OutputFormat::Tui if cli.stream => {}
+        OutputFormat::Tui => tui::run(mint_address_str, &parsed_trades, &summary)?,
     }
 
     Ok(())
 }
 
-fn short_sig(sig: &str) -> String {
-    if sig.len() <= 8 {
-        sig.to_string()
-    } else {
-        format!("{}…{}", &sig[..4], &sig[sig.len() - 4..])
+// sandwiches from the daemon's events log, timed by the victim's block or else by when the
+// daemon logged them
+fn period_report(args: &ReportArgs, output: &OutputSettings) -> Result<(), Error> {
+    let sandwiches: Vec<(i64, SandwichDetection)> = daemon::load_events(&args.events_path)?
+        .into_iter()
+        .filter(|logged| args.mint.as_ref().is_none_or(|mint| *mint == logged.mint))
+        .filter_map(|logged| match logged.event {
+            DetectionEvent::Sandwich(det) => Some((det.victim.block_time.unwrap_or(logged.detected_at as i64), *det)),
+            _ => None,
+        })
+        .collect();
+    let stats = trends::period_stats(&sandwiches, args.period);
+    match output.format {
+        OutputFormat::Text => report::print_period_report(args.period, &stats),
+        OutputFormat::Json => {
+            let json = serde_json::to_string_pretty(&stats).map_err(|e| Error::json("period report", e))?;
+            write_output(output.file.as_deref(), &json)?;
+        }
+        OutputFormat::Markdown => {
+            let document = markdown::render_period_report(args.mint.as_deref(), args.period, &stats);
+            write_output(output.file.as_deref(), &document)?;
+        }
+        OutputFormat::Html | OutputFormat::Enriched | OutputFormat::Tui => {
+            return Err(Error::Usage("report supports text, json or markdown output".to_string()));
+        }
     }
+    Ok(())
 }
 
-fn trade_badge(trade: TradeType) -> &'static str {
-    match trade {
-        TradeType::Buy => "BUY",
-        TradeType::Sell => "SELL",
+fn diff_report(args: &DiffArgs, output: &OutputSettings) -> Result<(), Error> {
+    let load = |path: &str| -> Result<DetectionSummary, Error> {
+        let raw = fs::read_to_string(path).map_err(|e| Error::io("read detection summary", path, e))?;
+        serde_json::from_str(&raw).map_err(|e| Error::json(format!("detection summary '{}'", path), e))
+    };
+    let diff = diff::diff_summaries(&load(&args.left)?, &load(&args.right)?);
+    match output.format {
+        OutputFormat::Text => report::print_summary_diff(&args.left, &args.right, &diff),
+        OutputFormat::Json => {
+            let json = serde_json::to_string_pretty(&diff).map_err(|e| Error::json("summary diff", e))?;
+            write_output(output.file.as_deref(), &json)?;
+        }
+        OutputFormat::Html | OutputFormat::Markdown | OutputFormat::Enriched | OutputFormat::Tui => {
+            return Err(Error::Usage("diff supports text or json output".to_string()));
+        }
     }
+    Ok(())
 }
 
-fn format_attack_impact(tx: &parser::pumpfun::ParsedTransaction) -> String {
-    let mut impact = String::new();
+struct Fetched {
+    trades: Vec<ParsedTransaction>,
+    lifecycle_events: Vec<LifecycleEvent>,
+    failures: Vec<Error>,
+}
 
-    match tx.trade_type {
-        TradeType::Buy => {
-            let actual_sol_spent = if tx.sol_change < 0 { -tx.sol_change } else { 0 };
-            let tokens_received = if tx.token_change > 0 { tx.token_change } else { 0 };
+// per-transaction failures are reported above the progress bar and collected, never fatal
+fn fetch_trades(
+    source: &dyn TransactionSource,
+    signatures: &[String],
+    mint: &str,
+    pump_program: &str,
+    rpc: &RpcSettings,
+    lookup_tables: &mut alt::LookupTableCache,
+    progress: &ProgressBar,
+) -> Fetched {
+    let mut fetched = Fetched {
+        trades: Vec::new(),
+        lifecycle_events: Vec::new(),
+        failures: Vec::new(),
+    };
+    let mut parsed_count = 0;
+    for batch in signatures.chunks(rpc.batch_size.max(1)) {
+        let responses = fetch_batch(source, batch, rpc, progress);
+        for (signature, response) in batch.iter().zip(responses) {
+            let signature = signature.as_str();
+            progress.set_message(format!("parsed {} | failed {}", parsed_count, fetched.failures.len()));
+            progress.inc(1);
+
+            let mut tx = match response {
+                Ok(tx) => tx,
+                Err(e) => {
+                    progress.suspend(|| eprintln!("Failed {}: {}", signature, e));
+                    fetched.failures.push(e);
+                    continue;
+                }
+            };
 
-            if actual_sol_spent > tx.sol_limit_specified as i64 {
-                let overpaid = actual_sol_spent - tx.sol_limit_specified as i64;
-                impact.push_str(&format!("OVERPAID {:.6} SOL", overpaid as f64 / 1_000_000_000.0));
+            if let Err(e) = lookup_tables.resolve_loaded_addresses(source, &mut tx) {
+                progress.suspend(|| eprintln!("Lookup tables unresolved for {}: {}", signature, e));
+                fetched.failures.push(e);
             }
-            if tokens_received < tx.token_amount_requested as i64 {
-                let shortage = tx.token_amount_requested as i64 - tokens_received;
-                impact.push_str(&format!("GOT {} FEWER TOKENS", shortage));
+
+            let parsed = parser::pumpfun::parse_transaction(&tx, signature, mint, pump_program).and_then(|legs| {
+                let events = parser::pumpfun::parse_lifecycle_events(&tx, signature, mint, pump_program)?;
+                Ok((legs, events))
+            });
+            match parsed {
+                Ok((legs, events)) => {
+                    parsed_count += 1;
+                    fetched.trades.extend(legs);
+                    fetched.lifecycle_events.extend(events);
+                }
+                Err(e) => {
+                    progress.suspend(|| eprintln!("Skipped {}: {}", signature, e));
+                    fetched.failures.push(e);
+                }
             }
         }
-        TradeType::Sell => {
-            let actual_sol_received = if tx.sol_change > 0 { tx.sol_change } else { 0 };
-            let tokens_sold = if tx.token_change < 0 { -tx.token_change } else { 0 };
+    }
+    if rpc.tx_index {
+        let failures = fetch::assign_tx_indexes(source, &mut fetched.trades);
+        fetched.failures.extend(failures);
+    }
+    progress.finish_with_message(format!("parsed {} | failed {}", parsed_count, fetched.failures.len()));
+    fetched
+}
 
-            if actual_sol_received < tx.sol_limit_specified as i64 {
-                let underpaid = tx.sol_limit_specified as i64 - actual_sol_received;
-                impact.push_str(&format!("RECEIVED {:.6} SOL LESS", underpaid as f64 / 1_000_000_000.0));
-            }
-            if tokens_sold > tx.token_amount_requested as i64 {
-                let oversold = tokens_sold - tx.token_amount_requested as i64;
-                impact.push_str(&format!("SOLD {} MORE TOKENS", oversold));
-            }
+// an endpoint that rejects batches still gets every transaction, one call each
+fn fetch_batch(
+    source: &dyn TransactionSource,
+    batch: &[String],
+    rpc: &RpcSettings,
+    progress: &ProgressBar,
+) -> Vec<Result<EncodedConfirmedTransactionWithStatusMeta, Error>> {
+    match batch {
+        [signature] => vec![source.transaction(signature, rpc.encoding)],
+        _ => source.transactions(batch, rpc.encoding).unwrap_or_else(|e| {
+            progress.suspend(|| eprintln!("Batch failed, fetching one by one: {}", e));
+            batch.iter().map(|sig| source.transaction(sig, rpc.encoding)).collect()
+        }),
+    }
+}
+
+// on-chain name and symbol, plus the image from the off-chain JSON when asked for and reachable
+fn mint_metadata(source: &dyn TransactionSource, mint: &str, config: &RunConfig) -> Option<MintMetadata> {
+    let metadata = Pubkey::from_str(mint)
+        .map_err(|_| Error::InvalidMint(mint.to_string()))
+        .and_then(|mint| token::fetch_metadata(source, &mint));
+    let mut metadata = match metadata {
+        Ok(metadata) => metadata,
+        Err(e) => {
+            eprintln!("Token metadata unavailable: {}", e);
+            return None;
+        }
+    };
+    if config.output.token_image {
+        match token::fetch_image(&metadata.uri, Duration::from_secs(config.rpc.timeout_secs)) {
+            Ok(image) => metadata.image = image,
+            Err(e) => eprintln!("Token image unavailable: {}", e),
+        }
+    }
+    Some(metadata)
+}
+
+// the mint's decimals, read once per run; reports fall back to pump.fun's 6 without them
+fn mint_decimals(source: &dyn TransactionSource, mint: &str) -> Option<u8> {
+    let decimals = Pubkey::from_str(mint)
+        .map_err(|_| Error::InvalidMint(mint.to_string()))
+        .and_then(|mint| token::fetch_decimals(source, &mint));
+    match decimals {
+        Ok(decimals) => Some(decimals),
+        Err(e) => {
+            eprintln!("Mint decimals unavailable, assuming {}: {}", token::DEFAULT_DECIMALS, e);
+            None
+        }
+    }
+}
+
+fn write_output(file: Option<&str>, content: &str) -> Result<(), Error> {
+    match file {
+        Some(path) => fs::write(path, content).map_err(|e| Error::io("write output file", path, e)),
+        None => {
+            println!("{}", content);
+            Ok(())
         }
     }
+}
+
+type RpcSource = FailoverSource<RateLimitedSource<BatchRpcClient>>;
+
+// each endpoint gets its own rate limit, since quotas are per provider
+fn rpc_source(settings: &RpcSettings, network: Network) -> Result<RpcSource, Error> {
+    let urls = if settings.urls.is_empty() {
+        let api_key = env::var("HELIUS_API_KEY").ok();
+        let url = network.default_rpc_url(api_key.as_deref()).ok_or(match network {
+            Network::Custom => Error::Config("--network custom needs --rpc-url or [rpc] urls".to_string()),
+            _ => Error::MissingEnv("HELIUS_API_KEY"),
+        })?;
+        vec![url]
+    } else {
+        settings.urls.clone()
+    };
+    let endpoints = urls
+        .into_iter()
+        .map(|url| {
+            let client = BatchRpcClient::new(
+                &url,
+                Duration::from_secs(settings.timeout_secs),
+                CommitmentConfig {
+                    commitment: settings.commitment,
+                },
+            )?;
+            let limiter = RateLimiter::new(settings.requests_per_second, settings.burst);
+            Ok((endpoint_name(&url), RateLimitedSource::new(client, limiter)))
+        })
+        .collect::<Result<_, Error>>()?;
+    Ok(FailoverSource::new(endpoints))
+}
+
+fn event_sinks(settings: &SinkSettings) -> Result<Vec<Box<dyn EventSink>>, Error> {
+    let mut sinks: Vec<Box<dyn EventSink>> = Vec::new();
+    if let Some(url) = &settings.nats_url {
+        sinks.push(Box::new(NatsSink::connect(url, &settings.nats_subject)?));
+    }
+    if let Some(url) = &settings.redis_url {
+        sinks.push(Box::new(RedisSink::connect(url, &settings.redis_stream, settings.redis_max_len)?));
+    }
+    Ok(sinks)
+}
 
-    if impact.is_empty() {
-        "FAIR EXECUTION".to_string()
+// `--threads N` detects (and sweeps) on a pool of its own; without it a scan detects on this
+// thread and a sweep uses rayon's global pool
+fn detection_pool(threads: Option<usize>) -> Result<Option<rayon::ThreadPool>, Error> {
+    let Some(threads) = threads else {
+        return Ok(None);
+    };
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .build()
+        .map(Some)
+        .map_err(|e| Error::Config(format!("Cannot start {} detection threads: {}", threads, e)))
+}
+
+// bundled list plus configured ones; a list URL that cannot be reached is skipped
+fn known_bots(config: &RunConfig) -> Result<BotList, Error> {
+    let mut list = if config.wallets.bundled_bot_list {
+        BotList::bundled()?
     } else {
-        impact
+        BotList::default()
+    };
+    let timeout = Duration::from_secs(config.rpc.timeout_secs);
+    for source in &config.wallets.bot_lists {
+        match BotList::load(source, timeout) {
+            Ok(loaded) => list.merge(loaded),
+            Err(e) if botlist::is_url(source) => eprintln!("Skipping bot list: {}", e),
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(list)
+}
+
+// Funding chains for every wallet on the leaderboard. Attackers behind the same unlabeled funder
+// are linked into one attacker and the aggregates rebuilt with the link.
+fn trace_attacker_funding(
+    source: &dyn TransactionSource,
+    config: &RunConfig,
+    summary: &mut DetectionSummary,
+    quiet: bool,
+) -> Result<(), Error> {
+    let timeout = Duration::from_secs(config.rpc.timeout_secs);
+    let mut labels = BotList::default();
+    for list in &config.funding.labels {
+        match BotList::load(list, timeout) {
+            Ok(loaded) => labels.merge(loaded),
+            Err(e) if botlist::is_url(list) => eprintln!("Skipping funder labels: {}", e),
+            Err(e) => return Err(e),
+        }
+    }
+    let options = TraceOptions {
+        max_depth: config.funding.max_depth,
+        signature_limit: config.funding.signature_limit,
+        min_lamports: (config.funding.min_sol * LAMPORTS_PER_SOL as f64) as u64,
+        encoding: config.rpc.encoding,
+        batch_size: config.rpc.batch_size,
+    };
+    let wallets: BTreeSet<&str> = summary.attacker_leaderboard.iter().flat_map(|stats| stats.wallets()).collect();
+    report::status(quiet, &format!("Tracing the funding of {} attacker wallets", wallets.len()));
+    let mut chains = Vec::new();
+    for wallet in wallets {
+        // one wallet's history failing to load leaves the others traced
+        match funding::trace_funding(source, wallet, &options, &labels.bots) {
+            Ok(chain) => chains.push(chain),
+            Err(e) => eprintln!("Funding trace of {} failed: {}", wallet, e),
+        }
+    }
+    summary.shared_funders = funding::shared_funders(&chains);
+    summary.funding = chains;
+    if !summary.shared_funders.is_empty() {
+        let groups: Vec<Vec<String>> = summary.shared_funders.iter().map(|shared| shared.wallets.clone()).collect();
+        let mut detector = config.detector.clone();
+        detector.linked_wallets = collusion::link_groups(&detector.linked_wallets, &groups);
+        summary.rebuild_from_stats(&detector);
+    }
+    Ok(())
+}
+
+fn price_feed(settings: &PriceSettings, rpc: &RpcSettings) -> Result<Option<Box<dyn PriceFeed>>, Error> {
+    Ok(match settings.source {
+        PriceSource::None => None,
+        PriceSource::Fixed(price) => Some(Box::new(FixedPrice(price))),
+        PriceSource::CoinGecko => {
+            let api_key = env::var("COINGECKO_API_KEY").ok();
+            let feed = CoinGecko::new(api_key, Duration::from_secs(rpc.timeout_secs))?;
+            match &settings.cache_path {
+                Some(path) => Some(Box::new(CachedFeed::load(feed, path)?)),
+                None => Some(Box::new(feed)),
+            }
+        }
+    })
+}
+
+fn scorer(settings: &ScoringSettings, rpc: &RpcSettings) -> Result<Option<Box<dyn Scorer>>, Error> {
+    Ok(match (&settings.command, &settings.url) {
+        (Some(command), _) => Some(Box::new(CommandScorer::parse(command)?)),
+        (None, Some(url)) => Some(Box::new(HttpScorer::new(url, Duration::from_secs(rpc.timeout_secs))?)),
+        (None, None) => None,
+    })
+}
+
+// drawn on stderr and hidden automatically when it isn't a terminal
+fn fetch_progress(total: u64, quiet: bool) -> ProgressBar {
+    if quiet {
+        return ProgressBar::hidden();
+    }
+    let progress = ProgressBar::new(total);
+    if let Ok(style) =
+        ProgressStyle::with_template("[{elapsed_precise}] {bar:40} {pos}/{len} signatures, ETA {eta} | {msg}")
+    {
+        progress.set_style(style);
     }
+    progress
 }
@@ -0,0 +1,282 @@
+use rusty::detect::{DetectionSummary, LamportsExt};
+use rusty::parser::pumpfun::{ParsedTransaction, TradeType};
+use rusty::price::{UsdValuation, format_usd, format_utc};
+use rusty::token;
+use rusty::trends::{Period, PeriodStats};
+use std::fmt::Write;
+
+const SOLSCAN: &str = "https://solscan.io";
+
+// GitHub-flavoured tables; links keep the full address while the label stays short
+pub fn render_markdown(mint: &str, trade_count: usize, summary: &DetectionSummary) -> String {
+    let mut md = String::new();
+    let extracted: i64 = summary.sandwiches.iter().map(|det| det.net_profit_sol).sum();
+    let usd = summary.usd.as_ref();
+
+    let label = summary.token_metadata.as_ref().and_then(|metadata| metadata.label());
+    let label = label.map(|label| format!("{} ", escape(&label))).unwrap_or_default();
+    let _ = writeln!(md, "# Sandwich report for {}{}\n", label, token_link(mint));
+    let _ = writeln!(md, "| Trades parsed | Sandwiches | Front-runs | Back-runs | Reverted victims | SOL extracted |");
+    let _ = writeln!(md, "|---:|---:|---:|---:|---:|---:|");
+    let _ = writeln!(
+        md,
+        "| {} | {} | {} | {} | {} | {:.4}{} |",
+        trade_count,
+        summary.sandwiches.len(),
+        summary.front_runs.len(),
+        summary.back_runs.len(),
+        summary.reverted_sandwiches.len(),
+        extracted.as_sol(),
+        with_usd(usd.map(|usd| usd.total_extracted_usd))
+    );
+
+    md.push_str("\n## Sandwiches\n\n");
+    if summary.sandwiches.is_empty() {
+        md.push_str("No sandwiches detected.\n");
+    } else {
+        md.push_str("| # | Slot | Time | Victim | Tx | Side | Victim ΔSOL | Victim loss (SOL) | Front/back legs | Attacker profit (SOL) |\n");
+        md.push_str("|---:|---:|---|---|---|---|---:|---:|---:|---:|\n");
+        for (idx, det) in summary.sandwiches.iter().enumerate() {
+            let _ = writeln!(
+                md,
+                "| {} | {} | {} | {} | {} | {} | {:+.6} | {:.6}{} | {}/{} | {:.6}{} |",
+                idx + 1,
+                det.victim.slot,
+                time_of(&det.victim),
+                account_link(&det.victim.signer),
+                tx_link(&det.victim.signature),
+                side(det.victim.trade_type),
+                det.victim.sol_change.as_sol(),
+                (det.victim_loss() as i64).as_sol(),
+                with_usd(usd.and_then(|usd| usd.usd(&det.victim.signature, det.victim_loss() as i64))),
+                det.frontruns.len(),
+                det.backruns.len(),
+                det.net_profit_sol.as_sol(),
+                with_usd(usd.and_then(|usd| usd.sandwich_profit(det)))
+            );
+        }
+    }
+
+    let frontruns = summary
+        .front_runs
+        .iter()
+        .flat_map(|event| event.frontruns.iter().map(move |leg| (&event.victim, leg)));
+    render_legs(&mut md, "Front-runs", frontruns, usd, summary.decimals());
+    let backruns = summary
+        .back_runs
+        .iter()
+        .flat_map(|event| event.backruns.iter().map(move |leg| (&event.victim, leg)));
+    render_legs(&mut md, "Back-runs", backruns, usd, summary.decimals());
+
+    if !summary.backrun_arbs.is_empty() {
+        md.push_str("\n## Backrun arbitrage\n\n");
+        md.push_str("| # | Slot | Time | Victim tx | Victim ΔSOL | Bot | Backrun | Close | Arb profit (SOL) |\n");
+        md.push_str("|---:|---:|---|---|---:|---|---|---|---:|\n");
+        for (idx, event) in summary.backrun_arbs.iter().enumerate() {
+            let _ = writeln!(
+                md,
+                "| {} | {} | {} | {} | {:+.6} | {} | {} | {} | {:.6}{} |",
+                idx + 1,
+                event.victim.slot,
+                time_of(&event.victim),
+                tx_link(&event.victim.signature),
+                event.victim.sol_change.as_sol(),
+                account_link(&event.backrun.signer),
+                tx_link(&event.backrun.signature),
+                tx_link(&event.close.signature),
+                event.net_profit_sol.as_sol(),
+                with_usd(usd.and_then(|usd| usd.usd(&event.close.signature, event.net_profit_sol)))
+            );
+        }
+    }
+
+    if !summary.attacker_leaderboard.is_empty() {
+        md.push_str("\n## Attacker leaderboard\n\n");
+        md.push_str("| # | Attacker | Profit (SOL) | Attacks | Victims | Mints | Avg SOL/attack |\n");
+        md.push_str("|---:|---|---:|---:|---:|---:|---:|\n");
+        for (rank, attacker) in summary.attacker_leaderboard.iter().enumerate() {
+            let _ = writeln!(
+                md,
+                "| {} | {}{} | {:.6}{} | {} | {} | {} | {:.6} |",
+                rank + 1,
+                account_link(&attacker.attacker),
+                escape(&attacker.tag()),
+                attacker.total_profit_lamports.as_sol(),
+                with_usd(usd.and_then(|usd| usd.attacker_profit(attacker))),
+                attacker.attacks,
+                attacker.victims,
+                attacker.mints.len(),
+                attacker.avg_profit_lamports.as_sol()
+            );
+        }
+    }
+
+    if !summary.attacker_performance.is_empty() {
+        md.push_str("\n## Attacker performance\n\n");
+        md.push_str("| Attacker | Attempted | Completed | Win rate | Capital (SOL) | Profit (SOL) | ROI | Avg hold (slots) |\n");
+        md.push_str("|---|---:|---:|---:|---:|---:|---:|---:|\n");
+        for stats in &summary.attacker_performance {
+            let _ = writeln!(
+                md,
+                "| {} | {} | {} | {:.1}% | {:.6} | {:.6} | {:.1}% | {:.1} |",
+                account_link(&stats.attacker),
+                stats.attempted,
+                stats.completed,
+                stats.win_rate * 100.0,
+                (stats.capital_deployed_lamports as i64).as_sol(),
+                stats.total_profit_lamports.as_sol(),
+                stats.roi_pct,
+                stats.avg_holding_slots
+            );
+        }
+    }
+
+    md
+}
+
+pub fn render_period_report(mint: Option<&str>, period: Period, stats: &[PeriodStats]) -> String {
+    let mut md = String::new();
+    let title = match period {
+        Period::Daily => "Daily",
+        Period::Weekly => "Weekly",
+    };
+    match mint {
+        Some(mint) => {
+            let _ = writeln!(md, "# {} sandwich report for {}\n", title, token_link(mint));
+        }
+        None => {
+            let _ = writeln!(md, "# {} sandwich report\n", title);
+        }
+    }
+    if stats.is_empty() {
+        md.push_str("No sandwiches in the events log.\n");
+        return md;
+    }
+    md.push_str("| Period start | Attacks | SOL extracted | Attackers | Victims | Top victim losses (SOL) |\n");
+    md.push_str("|---|---:|---:|---:|---:|---|\n");
+    for bucket in stats {
+        let losses: Vec<String> = bucket
+            .top_victim_losses
+            .iter()
+            .map(|loss| format!("{} {:.6}", account_link(&loss.victim), (loss.loss_lamports as i64).as_sol()))
+            .collect();
+        let start = format_utc(bucket.period_start);
+        let _ = writeln!(
+            md,
+            "| {} | {} | {:.6} | {} | {} | {} |",
+            start.split(' ').next().unwrap_or_default(),
+            bucket.attacks,
+            bucket.sol_extracted.as_sol(),
+            bucket.unique_attackers,
+            bucket.unique_victims,
+            losses.join("<br>")
+        );
+    }
+    md
+}
+
+fn render_legs<'a>(
+    md: &mut String,
+    title: &str,
+    legs: impl Iterator<Item = (&'a ParsedTransaction, &'a ParsedTransaction)>,
+    usd: Option<&UsdValuation>,
+    decimals: u8,
+) {
+    let mut legs = legs.peekable();
+    if legs.peek().is_none() {
+        return;
+    }
+    let _ = writeln!(md, "\n## {}\n", title);
+    md.push_str("| Victim tx | Slot | Time | Signer | Side | ΔSOL | Δtoken | Tx |\n");
+    md.push_str("|---|---:|---|---|---|---:|---:|---|\n");
+    for (victim, leg) in legs {
+        let _ = writeln!(
+            md,
+            "| {} | {} | {} | {} | {} | {:+.6}{} | {} | {} |",
+            tx_link(&victim.signature),
+            leg.slot,
+            time_of(leg),
+            account_link(&leg.signer),
+            side(leg.trade_type),
+            leg.sol_change.as_sol(),
+            with_usd(usd.and_then(|usd| usd.usd(&leg.signature, leg.sol_change))),
+            token::format_tokens(leg.token_change, decimals),
+            tx_link(&leg.signature)
+        );
+    }
+}
+
+fn time_of(tx: &ParsedTransaction) -> String {
+    tx.block_time.map(format_utc).unwrap_or_default()
+}
+
+fn with_usd(usd: Option<f64>) -> String {
+    usd.map(|usd| format!(" ({})", format_usd(usd))).unwrap_or_default()
+}
+
+fn side(trade: TradeType) -> &'static str {
+    match trade {
+        TradeType::Buy => "BUY",
+        TradeType::Sell => "SELL",
+    }
+}
+
+fn account_link(address: &str) -> String {
+    link(&format!("{}/account/{}", SOLSCAN, address), address)
+}
+
+fn token_link(mint: &str) -> String {
+    link(&format!("{}/token/{}", SOLSCAN, mint), mint)
+}
+
+fn tx_link(signature: &str) -> String {
+    link(&format!("{}/tx/{}", SOLSCAN, signature), signature)
+}
+
+fn link(url: &str, label: &str) -> String {
+    let chars: Vec<char> = label.chars().collect();
+    let short = if chars.len() <= 12 {
+        label.to_string()
+    } else {
+        let head: String = chars[..6].iter().collect();
+        let tail: String = chars[chars.len() - 6..].iter().collect();
+        format!("{}…{}", head, tail)
+    };
+    format!("[{}]({})", code(&short), encode_url(url))
+}
+
+// Chain strings are only base58 when they come from the chain; replayed or listed ones may hold
+// anything. A `|` ends a table cell even inside a code span, and a backtick ends the span.
+fn code(text: &str) -> String {
+    let text = text.replace('|', "\\|");
+    if text.contains('`') {
+        format!("`` {} ``", text)
+    } else {
+        format!("`{}`", text)
+    }
+}
+
+// markdown punctuation as literal text
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if "\\`*_[]<>|#!".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+// a link target can't carry spaces, parentheses or table pipes
+fn encode_url(url: &str) -> String {
+    let mut encoded = String::with_capacity(url.len());
+    for byte in url.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~:/?#@!$&'*+,;=%".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            let _ = write!(encoded, "%{:02X}", byte);
+        }
+    }
+    encoded
+}
@@ -0,0 +1,58 @@
+use serde::{Deserialize, Serialize};
+
+pub const PUMP_PROGRAM_ID: &str = "6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P";
+
+const DEVNET_PUBLIC_RPC: &str = "https://api.devnet.solana.com";
+const SOLSCAN: &str = "https://solscan.io";
+
+// Which cluster the scan runs against. pump.fun keeps the same program id on devnet, so only the
+// endpoint changes unless a custom deployment names its own program with `--program-id`; the
+// parser only reads instructions sent to that program or PumpSwap.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Network {
+    #[default]
+    Mainnet,
+    Devnet,
+    // a local validator or private cluster; needs its own RPC URL
+    Custom,
+}
+
+impl Network {
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value {
+            "mainnet" | "mainnet-beta" => Ok(Network::Mainnet),
+            "devnet" => Ok(Network::Devnet),
+            "custom" => Ok(Network::Custom),
+            other => Err(format!("Unknown network '{}': use mainnet, devnet or custom", other)),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Network::Mainnet => "mainnet",
+            Network::Devnet => "devnet",
+            Network::Custom => "custom",
+        }
+    }
+
+    // Helius when a key is given; only devnet falls back to the public endpoint
+    pub fn default_rpc_url(self, helius_api_key: Option<&str>) -> Option<String> {
+        match (self, helius_api_key) {
+            (Network::Mainnet, Some(key)) => Some(format!("https://mainnet.helius-rpc.com/?api-key={}", key)),
+            (Network::Devnet, Some(key)) => Some(format!("https://devnet.helius-rpc.com/?api-key={}", key)),
+            (Network::Devnet, None) => Some(DEVNET_PUBLIC_RPC.to_string()),
+            (Network::Mainnet, None) | (Network::Custom, _) => None,
+        }
+    }
+
+    // Solscan page for `tx/<signature>`, `account/<address>` or `token/<mint>`; a custom
+    // cluster has no public explorer
+    pub fn explorer_url(self, path: &str) -> Option<String> {
+        match self {
+            Network::Mainnet => Some(format!("{}/{}", SOLSCAN, path)),
+            Network::Devnet => Some(format!("{}/{}?cluster=devnet", SOLSCAN, path)),
+            Network::Custom => None,
+        }
+    }
+}
@@ -1,31 +1,200 @@
+use crate::error::Error;
 use borsh::BorshDeserialize;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::Arc;
 use bs58;
 use solana_transaction_status::{
     EncodedConfirmedTransactionWithStatusMeta, EncodedTransaction, UiInnerInstructions,
-    UiInstruction, UiMessage, UiParsedInstruction, UiParsedMessage, UiTransactionStatusMeta,
+    UiInstruction, UiMessage, UiParsedInstruction, UiTransactionStatusMeta,
     UiTransactionTokenBalance,
 };
 
 const BUY_DISCRIMINATOR: [u8; 8] = [102, 6, 61, 18, 1, 218, 235, 234];
 const SELL_DISCRIMINATOR: [u8; 8] = [51, 230, 133, 164, 1, 127, 131, 173];
+const CREATE_DISCRIMINATOR: [u8; 8] = [24, 30, 200, 40, 5, 28, 7, 119];
+const CREATE_V2_DISCRIMINATOR: [u8; 8] = [214, 144, 76, 236, 95, 139, 49, 180];
+const MIGRATE_DISCRIMINATOR: [u8; 8] = [155, 234, 231, 146, 236, 158, 162, 30];
+const LAMPORTS_PER_SIGNATURE: u64 = 5_000;
+const PUMP_SWAP_PROGRAM: &str = "pAMMBay6oceH9fJKBRHGP5D4bD4sWpmSwMn52FMfXEA";
+const COMPUTE_BUDGET_PROGRAM: &str = "ComputeBudget111111111111111111111111111111";
+// ComputeBudgetInstruction::SetComputeUnitPrice, followed by the price as a little-endian u64
+const SET_COMPUTE_UNIT_PRICE: u8 = 3;
+const WSOL_MINT: &str = "So11111111111111111111111111111111111111112";
+// Jito's tip payment accounts; lamports they receive in a transaction are the bundle tip
+const JITO_TIP_ACCOUNTS: [&str; 8] = [
+    "96gYZGLnJYVFmbjzopPSU6QiEV5fGqZNyN9nmNhvrZU5",
+    "HFqU5x63VTqvQss8hp11i4wVV8bD44PvwucfZ2bU7gRe",
+    "Cw8CFyM9FkoMi7K7Crf6HNQqf4uEMzpKw6QNghXLvLkY",
+    "ADaUMid9yfUytqMBgopwjb2DTLSokTSzL1zt6iGPaS49",
+    "DfXygSm4jCyNCybVYYK6DwvWqjKee8pbDmJGcLWNDXjh",
+    "ADuUkR4vqLUMWXxW9gh6D6L8pMSawimctcNZ5pGwDcEt",
+    "DttWaMuVvTiduZRnguLF7jNxTgiMBZ1hyAumKUiL2KRL",
+    "3AVi9Tg9Uo68tJfuvoKvqKNWKc5wPdSSdeBnizKZ6jT",
+];
 
-#[derive(Debug, PartialEq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Serialize, Deserialize)]
 pub enum TradeType {
     Buy,
     Sell,
 }
 
-#[derive(Debug, Clone)]
+impl TradeType {
+    pub fn opposite(self) -> Self {
+        match self {
+            TradeType::Buy => TradeType::Sell,
+            TradeType::Sell => TradeType::Buy,
+        }
+    }
+    pub fn name(self) -> &'static str {
+        match self {
+            TradeType::Buy => "buy",
+            TradeType::Sell => "sell",
+        }
+    }
+}
+
+// The program a leg traded through. PumpSwap's buy and sell share pump.fun's discriminators and
+// argument layout, but trade against the graduated pool with wrapped SOL. pump.fun's program id
+// differs per deployment, so the caller names it; any other program carrying those
+// discriminators is someone else's Anchor method and is not read at all.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Default, Serialize, Deserialize)]
+pub enum Venue {
+    #[default]
+    PumpFun,
+    PumpSwap,
+}
+
+impl Venue {
+    fn of(program: Option<&str>, pump_program: &str) -> Option<Self> {
+        match program? {
+            program if program == pump_program => Some(Venue::PumpFun),
+            PUMP_SWAP_PROGRAM => Some(Venue::PumpSwap),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Venue::PumpFun => "pump.fun",
+            Venue::PumpSwap => "PumpSwap",
+        }
+    }
+
+    // (pool or curve, user, mint) positions in the buy and sell accounts; PumpSwap's mint is the
+    // pool's base mint
+    fn account_positions(self) -> (usize, usize, usize) {
+        match self {
+            Venue::PumpFun => (3, 6, 2),
+            Venue::PumpSwap => (0, 1, 3),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub enum ExecutionOutcome {
+    Succeeded,
+    Reverted,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ParsedTransaction {
-    pub signature: String,
+    pub signature: Arc<str>,
     pub slot: u64,
-    pub signer: String,
-    pub mint: String,
+    // unix seconds, when the node reports it
+    #[serde(default)]
+    pub block_time: Option<i64>,
+    // position within the block, filled in by `fetch::assign_tx_indexes`
+    #[serde(default)]
+    pub tx_index: Option<usize>,
+    // the wallet that traded this leg: the pump.fun instruction's user, which is the fee payer
+    // unless someone else's swap was wrapped into the transaction
+    pub signer: Arc<str>,
+    // the transaction's fee payer, when it isn't `signer`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fee_payer: Option<Arc<str>>,
+    pub mint: Arc<str>,
+    pub leg_index: usize,
+    #[serde(default)]
+    pub venue: Venue,
     pub trade_type: TradeType,
     pub token_amount_requested: u64,
     pub sol_limit_specified: u64,
     pub sol_change: i64,
     pub token_change: i64,
+    pub fee: u64,
+    // the part of `fee` above the per-signature base fee
+    #[serde(default)]
+    pub priority_fee: u64,
+    // lamports paid to Jito tip accounts
+    #[serde(default)]
+    pub tip: u64,
+    // micro-lamports per compute unit from SetComputeUnitPrice, 0 when the transaction set none;
+    // transaction-wide, so every leg carries it
+    #[serde(default)]
+    pub compute_unit_price: u64,
+    pub ata_rent: u64,
+    pub outcome: ExecutionOutcome,
+    // None when the curve account is missing or other legs of the transaction traded on it too
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub curve: Option<CurveDelta>,
+    // the signer's balance changes in other mints in the same transaction, e.g. the token a
+    // router swapped through to pay for this leg; wrapped SOL is left out
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub hop_token_changes: BTreeMap<String, i64>,
+}
+
+// the bonding-curve PDA's side of the trade: its lamports and the tokens held by its
+// associated token account, which are the curve's real reserves plus the migration allocation
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CurveDelta {
+    pub account: String,
+    pub sol_change: i64,
+    pub token_change: i64,
+    pub pre_token_balance: Option<u64>,
+}
+
+impl ParsedTransaction {
+    // the signer's SOL delta on the curve alone, before fees, ATA rent and tips
+    pub fn trade_sol_change(&self) -> i64 {
+        self.sol_change.saturating_add(self.costs() as i64)
+    }
+
+    pub fn is_routed(&self) -> bool {
+        !self.hop_token_changes.is_empty()
+    }
+
+    pub fn costs(&self) -> u64 {
+        self.fee.saturating_add(self.ata_rent).saturating_add(self.tip)
+    }
+
+    // Every token leaving the curve reaches the signer and the curve never moves more SOL than
+    // the signer paid or received (the difference is the protocol and creator fee). None when
+    // the curve side wasn't recorded.
+    pub fn matches_curve(&self) -> Option<bool> {
+        let curve = self.curve.as_ref()?;
+        Some(curve.token_change == -self.token_change && curve.sol_change + self.trade_sol_change() <= 0)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum LifecycleKind {
+    Create {
+        creator: String,
+        name: String,
+        symbol: String,
+        uri: String,
+    },
+    Migrate,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LifecycleEvent {
+    pub signature: String,
+    pub slot: u64,
+    pub signer: String,
+    pub mint: String,
+    pub kind: LifecycleKind,
 }
 
 #[derive(BorshDeserialize, Debug)]
@@ -40,180 +209,616 @@ struct SellArgs {
     pub min_sol_output: u64,
 }
 
+#[derive(BorshDeserialize, Debug)]
+struct CreateArgs {
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+}
+
 pub fn parse_transaction(
     tx: &EncodedConfirmedTransactionWithStatusMeta,
     signature: &str,
     mint_address: &str,
-) -> Option<ParsedTransaction> {
-    let message = match &tx.transaction.transaction {
-        EncodedTransaction::Json(tx_json) => match &tx_json.message {
-            UiMessage::Parsed(message) => message,
-            _ => return None,
-        },
-        _ => return None,
-    };
-
-    let signer = message.account_keys.first()?.pubkey.clone();
+    pump_program: &str,
+) -> Result<Vec<ParsedTransaction>, Error> {
+    let meta = tx.transaction.meta.as_ref();
+    let (account_keys, steps) = decode_steps(tx, signature, pump_program)?;
+    let signer = account_keys.first().cloned().ok_or_else(|| no_signer(signature))?;
     let slot = tx.slot;
 
-    let mut decoded = scan_instruction_stream(message.instructions.iter().enumerate());
-
-    if decoded.is_none() {
-        if let Some(meta) = &tx.transaction.meta {
-            if let Some(inner_groups) = meta.inner_instructions.as_slice() {
-                for UiInnerInstructions {
-                    index: _index,
-                    instructions,
-                } in inner_groups
-                {
-                    for (_inner_idx, instruction) in instructions.iter().enumerate() {
-                        if let Some(hit) = decode_pump_instruction(instruction) {
-                            decoded = Some(hit);
-                            break;
-                        }
-                    }
-                    if decoded.is_some() {
-                        break;
-                    }
-                }
-            }
+    // Legs on other tokens still take their share of a user's balance change below, so that
+    // share isn't credited to a leg on this mint, but are not reported.
+    let leg_ranges = pump_leg_ranges(&steps);
+    let on_mint: Vec<bool> = leg_ranges
+        .iter()
+        .map(|(step_index, _)| steps[*step_index].mint.as_deref() == Some(mint_address))
+        .collect();
+    if !on_mint.contains(&true) {
+        return Ok(Vec::new());
+    }
+
+    let (sol_change, token_change, fee, ata_rent, tip) = meta
+        .map(|meta| {
+            (
+                trader_sol_change(meta, &account_keys, &signer).unwrap_or(0),
+                compute_token_change(meta, &signer, mint_address).unwrap_or(0),
+                meta.fee,
+                compute_ata_rent(meta, &signer, mint_address),
+                compute_tip(meta, &account_keys),
+            )
+        })
+        .unwrap_or((0, 0, 0, 0, 0));
+    let priority_fee = fee.saturating_sub(LAMPORTS_PER_SIGNATURE * signature_count(tx));
+    let compute_unit_price = transaction_compute_unit_price(tx, &account_keys);
+    let hop_token_changes = meta
+        .map(|meta| compute_hop_token_changes(meta, &signer, mint_address))
+        .unwrap_or_default();
+    let trade_sol_change = sol_change
+        .saturating_add(fee as i64)
+        .saturating_add(ata_rent as i64)
+        .saturating_add(tip as i64);
+    let outcome = match meta.and_then(|meta| meta.err.as_ref()) {
+        Some(_) => ExecutionOutcome::Reverted,
+        None => ExecutionOutcome::Succeeded,
+    };
+
+    // A leg traded by someone other than the fee payer (a swap a bot wrapped into its own
+    // transaction) belongs to that user and is valued from the user's balances. Each user's
+    // balance change is split over its legs by the transfers inside them. Only a user that signed
+    // the transaction counts; a router's own accounts sit in the same position.
+    let signers = &account_keys[..(signature_count(tx) as usize).min(account_keys.len())];
+    let leg_users: Vec<&str> = leg_ranges
+        .iter()
+        .map(|(step_index, _)| {
+            steps[*step_index]
+                .user
+                .as_deref()
+                .filter(|user| signers.iter().any(|key| key == user))
+                .unwrap_or(&signer)
+        })
+        .collect();
+    let mut leg_sol = vec![0; leg_ranges.len()];
+    let mut leg_tokens = vec![0; leg_ranges.len()];
+    let users: BTreeSet<&str> = leg_users.iter().copied().collect();
+    for user in users {
+        let legs: Vec<usize> = (0..leg_ranges.len()).filter(|&leg| leg_users[leg] == user).collect();
+        let (user_sol, user_tokens) = if user == signer {
+            (trade_sol_change, token_change)
+        } else {
+            meta.map(|meta| {
+                (
+                    trader_sol_change(meta, &account_keys, user).unwrap_or(0),
+                    compute_token_change(meta, user, mint_address).unwrap_or(0),
+                )
+            })
+            .unwrap_or((0, 0))
+        };
+        let (sol, tokens) = if legs.len() == 1 {
+            (vec![user_sol], vec![user_tokens])
+        } else {
+            let token_accounts = meta
+                .map(|meta| signer_token_accounts(meta, &account_keys, user, mint_address))
+                .unwrap_or_default();
+            let flows: Vec<LegFlow> = legs
+                .iter()
+                .map(|&leg| leg_flow(&steps[leg_ranges[leg].1.clone()], user, &token_accounts))
+                .collect();
+            (
+                split_delta(flows.iter().map(|flow| flow.lamports).collect(), user_sol),
+                split_delta(flows.iter().map(|flow| flow.tokens).collect(), user_tokens),
+            )
+        };
+        for (position, &leg) in legs.iter().enumerate() {
+            leg_sol[leg] = sol[position];
+            leg_tokens[leg] = tokens[position];
         }
     }
+    // transaction-wide costs are charged to the fee payer's first leg on this mint
+    let cost_leg = (0..leg_ranges.len()).find(|&leg| on_mint[leg] && leg_users[leg] == signer);
 
-    match decoded {
-        Some(decoded) => {
-            let (sol_change, token_change) = tx
-                .transaction
-                .meta
-                .as_ref()
-                .map(|meta| {
-                    (
-                        compute_sol_change(meta, message, &signer).unwrap_or(0),
-                        compute_token_change(meta, &signer, mint_address).unwrap_or(0),
-                    )
-                })
-                .unwrap_or((0, 0));
-
-            println!("----------");
-            println!("signature: {}", signature);
-            println!("signer: {}", signer);
-            println!("mint: {}", mint_address);
-            println!(
-                "wanted: {:?} {} tokens (SOL limit {})",
-                decoded.trade_type, decoded.token_amount_requested, decoded.sol_limit_specified
-            );
-            println!("executed: ΔSOL {} | Δtoken {}", sol_change, token_change);
-
-            match decoded.trade_type {
-                TradeType::Buy => {
-                    let actual_sol_spent = if sol_change < 0 { -sol_change } else { 0 };
-                    let tokens_received = if token_change > 0 { token_change } else { 0 };
-
-                    println!("BUY IMPACT:");
-                    if actual_sol_spent > decoded.sol_limit_specified as i64 {
-                        let overpaid = actual_sol_spent - decoded.sol_limit_specified as i64;
-                        println!("  Overpaid by {} lamports ({:.6} SOL) - limit breached!",
-                                overpaid, overpaid as f64 / 1_000_000_000.0);
-                    } else {
-                        println!("  SOL spend within limit");
-                    }
-                    if tokens_received < decoded.token_amount_requested as i64 {
-                        let shortage = decoded.token_amount_requested as i64 - tokens_received;
-                        println!("  Got {} fewer tokens than requested!",
-                                shortage);
-                    } else {
-                        println!("  Received requested token amount");
-                    }
-                }
-                TradeType::Sell => {
-                    let actual_sol_received = if sol_change > 0 { sol_change } else { 0 };
-                    let tokens_sold = if token_change < 0 { -token_change } else { 0 };
-
-                    println!("SELL IMPACT:");
-                    if actual_sol_received < decoded.sol_limit_specified as i64 {
-                        let underpaid = decoded.sol_limit_specified as i64 - actual_sol_received;
-                        println!("  Received {} fewer lamports than expected ({:.6} SOL shortfall)!",
-                                underpaid, underpaid as f64 / 1_000_000_000.0);
-                    } else {
-                        println!("  SOL received meets expectation");
-                    }
-                    if tokens_sold > decoded.token_amount_requested as i64 {
-                        let oversold = tokens_sold - decoded.token_amount_requested as i64;
-                        println!("  Sold {} more tokens than planned!",
-                                oversold);
-                    } else {
-                        println!("  Sold planned token amount");
-                    }
+    // shared by every leg instead of copied into each
+    let signature_id: Arc<str> = Arc::from(signature);
+    let signer_id: Arc<str> = Arc::from(signer.as_str());
+    let mint_id: Arc<str> = Arc::from(mint_address);
+    let legs = leg_ranges
+        .iter()
+        .enumerate()
+        .filter(|(leg_index, _)| on_mint[*leg_index])
+        .filter_map(|(leg_index, (step_index, _))| {
+            let decoded = steps[*step_index].decoded.as_ref()?;
+            let charged = cost_leg == Some(leg_index);
+            let (leg_fee, leg_priority_fee, leg_rent, leg_tip) =
+                if charged { (fee, priority_fee, ata_rent, tip) } else { (0, 0, 0, 0) };
+            let hops = if charged { hop_token_changes.clone() } else { BTreeMap::new() };
+            let wrapped = leg_users[leg_index] != signer;
+            let curve = steps[*step_index].bonding_curve.as_ref().and_then(|account| {
+                // balance deltas are per transaction, so a curve shared by several legs can't be split
+                let shared = leg_ranges
+                    .iter()
+                    .filter(|(other, _)| steps[*other].bonding_curve.as_ref() == Some(account))
+                    .count()
+                    > 1;
+                if shared {
+                    return None;
                 }
-            }
-            println!("----------");
-
-            Some(ParsedTransaction {                signature: signature.to_string(),
+                compute_curve_delta(meta?, &account_keys, account, mint_address)
+            });
+            Some(ParsedTransaction {
+                signature: signature_id.clone(),
                 slot,
-                signer,
-                mint: mint_address.to_string(),
+                block_time: tx.block_time,
+                tx_index: None,
+                signer: if wrapped { Arc::from(leg_users[leg_index]) } else { signer_id.clone() },
+                fee_payer: wrapped.then(|| signer_id.clone()),
+                mint: mint_id.clone(),
+                leg_index,
+                venue: steps[*step_index].venue,
                 trade_type: decoded.trade_type,
                 token_amount_requested: decoded.token_amount_requested,
                 sol_limit_specified: decoded.sol_limit_specified,
-                sol_change,
-                token_change,
+                sol_change: leg_sol[leg_index]
+                    .saturating_sub(leg_fee as i64)
+                    .saturating_sub(leg_rent as i64)
+                    .saturating_sub(leg_tip as i64),
+                token_change: leg_tokens[leg_index],
+                fee: leg_fee,
+                priority_fee: leg_priority_fee,
+                tip: leg_tip,
+                compute_unit_price,
+                ata_rent: leg_rent,
+                outcome,
+                curve,
+                hop_token_changes: hops,
             })
+        })
+        .collect();
+    Ok(legs)
+}
+
+pub fn parse_lifecycle_events(
+    tx: &EncodedConfirmedTransactionWithStatusMeta,
+    signature: &str,
+    mint_address: &str,
+    pump_program: &str,
+) -> Result<Vec<LifecycleEvent>, Error> {
+    let (account_keys, steps) = decode_steps(tx, signature, pump_program)?;
+    let signer = account_keys.first().ok_or_else(|| no_signer(signature))?;
+
+    let events = steps
+        .iter()
+        .filter_map(|step| step.lifecycle.as_ref())
+        .filter(|lifecycle| lifecycle.mint == mint_address)
+        .map(|lifecycle| LifecycleEvent {
+            signature: signature.to_string(),
+            slot: tx.slot,
+            signer: signer.clone(),
+            mint: lifecycle.mint.clone(),
+            kind: lifecycle.kind.clone(),
+        })
+        .collect();
+    Ok(events)
+}
+
+fn no_signer(signature: &str) -> Error {
+    Error::Decode {
+        signature: signature.to_string(),
+        reason: "transaction has no account keys".to_string(),
+    }
+}
+
+fn decode_steps(
+    tx: &EncodedConfirmedTransactionWithStatusMeta,
+    signature: &str,
+    pump_program: &str,
+) -> Result<(Vec<String>, Vec<InstructionStep>), Error> {
+    let undecodable = |reason: &str| Error::Decode {
+        signature: signature.to_string(),
+        reason: reason.to_string(),
+    };
+    let meta = tx.transaction.meta.as_ref();
+    let mut buffer = Vec::new();
+    let (account_keys, outer_steps) = match &tx.transaction.transaction {
+        EncodedTransaction::Json(tx_json) => match &tx_json.message {
+            UiMessage::Parsed(message) => {
+                let keys: Vec<String> = message
+                    .account_keys
+                    .iter()
+                    .map(|account| account.pubkey.clone())
+                    .collect();
+                let steps = message
+                    .instructions
+                    .iter()
+                    .map(|ix| ui_step(ix, &keys, 1, pump_program, &mut buffer))
+                    .collect::<Vec<_>>();
+                (keys, steps)
+            }
+            UiMessage::Raw(message) => {
+                let keys = with_loaded_addresses(message.account_keys.clone(), meta);
+                let steps = message
+                    .instructions
+                    .iter()
+                    .map(|ix| {
+                        let data = decode_base58(&ix.data, &mut buffer);
+                        let program = keys.get(ix.program_id_index as usize).map(String::as_str);
+                        raw_step(data, &LazyAccounts::Indexed(&ix.accounts, &keys), program, pump_program)
+                    })
+                    .collect();
+                (keys, steps)
+            }
+        },
+        EncodedTransaction::Binary(..) | EncodedTransaction::LegacyBinary(_) => {
+            let versioned = tx
+                .transaction
+                .transaction
+                .decode()
+                .ok_or_else(|| undecodable("binary transaction payload is malformed"))?;
+            let static_keys = versioned
+                .message
+                .static_account_keys()
+                .iter()
+                .map(|key| key.to_string())
+                .collect();
+            let keys = with_loaded_addresses(static_keys, meta);
+            let steps = versioned
+                .message
+                .instructions()
+                .iter()
+                .map(|ix| {
+                    let program = keys.get(ix.program_id_index as usize).map(String::as_str);
+                    raw_step(&ix.data, &LazyAccounts::Indexed(&ix.accounts, &keys), program, pump_program)
+                })
+                .collect();
+            (keys, steps)
+        }
+        EncodedTransaction::Accounts(_) => {
+            return Err(undecodable("accounts-only encoding carries no instructions"));
         }
-        None => None,
+    };
+
+    let steps = flatten_steps(outer_steps, &account_keys, meta, pump_program, &mut buffer);
+    Ok((account_keys, steps))
+}
+
+struct InstructionStep {
+    stack_height: u32,
+    decoded: Option<DecodedInstruction>,
+    venue: Venue,
+    // pump.fun buy and sell pass the bonding curve as their fourth account and the trader as
+    // their seventh; PumpSwap has no curve and passes the trader second
+    bonding_curve: Option<String>,
+    user: Option<String>,
+    // the token a buy or sell trades: pump.fun's third account, PumpSwap's fourth
+    mint: Option<String>,
+    lifecycle: Option<DecodedLifecycle>,
+    transfer: Option<Transfer>,
+}
+
+struct DecodedLifecycle {
+    mint: String,
+    kind: LifecycleKind,
+}
+
+enum Transfer {
+    Lamports {
+        source: String,
+        destination: String,
+        lamports: u64,
+    },
+    Tokens {
+        source: String,
+        destination: String,
+        amount: u64,
+    },
+}
+
+#[derive(Default)]
+struct LegFlow {
+    lamports: Option<i64>,
+    tokens: Option<i64>,
+}
+
+// `buffer` holds the decoded data of one instruction at a time and is reused for the whole
+// transaction; accounts are only resolved for pump.fun and PumpSwap instructions carrying one of
+// their discriminators.
+fn ui_step(
+    instruction: &UiInstruction,
+    account_keys: &[String],
+    default_height: u32,
+    pump_program: &str,
+    buffer: &mut Vec<u8>,
+) -> InstructionStep {
+    let (stack_height, mut step) = match instruction {
+        UiInstruction::Compiled(compiled) => (
+            compiled.stack_height,
+            raw_step(
+                decode_base58(&compiled.data, buffer),
+                &LazyAccounts::Indexed(&compiled.accounts, account_keys),
+                account_keys.get(compiled.program_id_index as usize).map(String::as_str),
+                pump_program,
+            ),
+        ),
+        UiInstruction::Parsed(UiParsedInstruction::PartiallyDecoded(partial)) => (
+            partial.stack_height,
+            raw_step(
+                decode_base58(&partial.data, buffer),
+                &LazyAccounts::Named(&partial.accounts),
+                Some(&partial.program_id),
+                pump_program,
+            ),
+        ),
+        UiInstruction::Parsed(UiParsedInstruction::Parsed(parsed)) => (
+            parsed.stack_height,
+            raw_step(&[], &LazyAccounts::Named(&[]), None, pump_program),
+        ),
+    };
+    step.stack_height = stack_height.unwrap_or(default_height);
+    step.transfer = parse_transfer(instruction);
+    step
+}
+
+fn raw_step(data: &[u8], accounts: &LazyAccounts, program: Option<&str>, pump_program: &str) -> InstructionStep {
+    let mut step = InstructionStep {
+        stack_height: 1,
+        venue: Venue::default(),
+        decoded: None,
+        bonding_curve: None,
+        user: None,
+        mint: None,
+        lifecycle: None,
+        transfer: None,
+    };
+    let Some(venue) = Venue::of(program, pump_program).filter(|_| has_pump_discriminator(data)) else {
+        return step;
+    };
+    step.venue = venue;
+    let accounts = accounts.resolve();
+    step.decoded = decode_instruction_bytes(data);
+    let (curve_position, user_position, mint_position) = venue.account_positions();
+    if venue == Venue::PumpFun {
+        step.bonding_curve = step.decoded.as_ref().and_then(|_| accounts.get(curve_position).cloned());
     }
+    step.user = step.decoded.as_ref().and_then(|_| accounts.get(user_position).cloned());
+    step.mint = step.decoded.as_ref().and_then(|_| accounts.get(mint_position).cloned());
+    step.lifecycle = decode_lifecycle_instruction(data, &accounts);
+    step
 }
 
-fn scan_instruction_stream<'a, I>(iter: I) -> Option<DecodedInstruction>
-where
-    I: Iterator<Item = (usize, &'a UiInstruction)>,
-{
-    for (_idx, instruction) in iter {
-        if let Some(decoded) = decode_pump_instruction(instruction) {
-            return Some(decoded);
+// an instruction's accounts, as indexes into the transaction's keys or already named
+enum LazyAccounts<'a> {
+    Indexed(&'a [u8], &'a [String]),
+    Named(&'a [String]),
+}
+
+impl LazyAccounts<'_> {
+    fn resolve(&self) -> Vec<String> {
+        match self {
+            LazyAccounts::Indexed(indexes, account_keys) => resolve_accounts(indexes, account_keys),
+            LazyAccounts::Named(accounts) => accounts.to_vec(),
         }
     }
-    None
 }
 
+fn resolve_accounts(indexes: &[u8], account_keys: &[String]) -> Vec<String> {
+    indexes
+        .iter()
+        .filter_map(|&index| account_keys.get(index as usize).cloned())
+        .collect()
+}
 
-struct DecodedInstruction {
-    trade_type: TradeType,
-    token_amount_requested: u64,
-    sol_limit_specified: u64,
+// undecodable data reads as empty, which matches no discriminator
+fn decode_base58<'a>(data: &str, buffer: &'a mut Vec<u8>) -> &'a [u8] {
+    buffer.clear();
+    if bs58::decode(data).onto(&mut *buffer).is_err() {
+        buffer.clear();
+    }
+    buffer
 }
 
-fn decode_pump_instruction(instruction: &UiInstruction) -> Option<DecodedInstruction> {
-    match instruction {
-        UiInstruction::Compiled(compiled) => decode_instruction_data(&compiled.data),
-        UiInstruction::Parsed(parsed) => match parsed {
-            UiParsedInstruction::PartiallyDecoded(partial) => {
-                decode_instruction_data(&partial.data)
+fn flatten_steps(
+    outer_steps: Vec<InstructionStep>,
+    account_keys: &[String],
+    meta: Option<&UiTransactionStatusMeta>,
+    pump_program: &str,
+    buffer: &mut Vec<u8>,
+) -> Vec<InstructionStep> {
+    let inner_groups = meta
+        .and_then(|meta| meta.inner_instructions.as_slice())
+        .unwrap_or(&[]);
+
+    let mut steps = Vec::new();
+    for (outer_index, step) in outer_steps.into_iter().enumerate() {
+        steps.push(step);
+        for UiInnerInstructions { index, instructions } in inner_groups {
+            if *index as usize == outer_index {
+                steps.extend(instructions.iter().map(|ix| ui_step(ix, account_keys, 2, pump_program, buffer)));
             }
-            UiParsedInstruction::Parsed(_parsed_instruction) => None,
-        },
+        }
     }
+    steps
 }
 
-fn decode_instruction_data(data_b58: &str) -> Option<DecodedInstruction> {
-    let raw = bs58::decode(data_b58).into_vec().ok()?;
-    if raw.len() < 8 {
+fn pump_leg_ranges(steps: &[InstructionStep]) -> Vec<(usize, std::ops::Range<usize>)> {
+    steps
+        .iter()
+        .enumerate()
+        .filter(|(_, step)| step.decoded.is_some())
+        .map(|(start, step)| {
+            let end = steps[start + 1..]
+                .iter()
+                .position(|child| child.stack_height <= step.stack_height)
+                .map(|offset| start + 1 + offset)
+                .unwrap_or(steps.len());
+            (start, start + 1..end)
+        })
+        .collect()
+}
+
+fn parse_transfer(instruction: &UiInstruction) -> Option<Transfer> {
+    let UiInstruction::Parsed(UiParsedInstruction::Parsed(parsed)) = instruction else {
         return None;
+    };
+    let kind = parsed.parsed.get("type")?.as_str()?;
+    let info = parsed.parsed.get("info")?;
+    let field = |name: &str| info.get(name).and_then(|value| value.as_str()).map(str::to_string);
+
+    match (parsed.program.as_str(), kind) {
+        ("system", "transfer") => Some(Transfer::Lamports {
+            source: field("source")?,
+            destination: field("destination")?,
+            lamports: info.get("lamports")?.as_u64()?,
+        }),
+        ("spl-token" | "spl-token-2022", "transfer") => Some(Transfer::Tokens {
+            source: field("source")?,
+            destination: field("destination")?,
+            amount: field("amount")?.parse().ok()?,
+        }),
+        ("spl-token" | "spl-token-2022", "transferChecked") => Some(Transfer::Tokens {
+            source: field("source")?,
+            destination: field("destination")?,
+            amount: info
+                .get("tokenAmount")?
+                .get("amount")?
+                .as_str()?
+                .parse()
+                .ok()?,
+        }),
+        _ => None,
     }
+}
 
-    try_decode(&raw[..8], &raw[8..]).or_else(|| {
-        if raw.len() >= 9 {
-            try_decode(&raw[1..9], &raw[9..])
-        } else {
-            None
+// transfer amounts are u64 and a hostile transaction can make them anything, so the sums run in i128
+fn leg_flow(steps: &[InstructionStep], signer: &str, token_accounts: &[String]) -> LegFlow {
+    let mut lamports: Option<i128> = None;
+    let mut tokens: Option<i128> = None;
+    for step in steps {
+        match &step.transfer {
+            Some(Transfer::Lamports {
+                source,
+                destination,
+                lamports: amount,
+            }) => {
+                let amount = *amount as i128;
+                if source == signer {
+                    *lamports.get_or_insert(0) -= amount;
+                }
+                if destination == signer {
+                    *lamports.get_or_insert(0) += amount;
+                }
+            }
+            Some(Transfer::Tokens {
+                source,
+                destination,
+                amount,
+            }) => {
+                let amount = *amount as i128;
+                if token_accounts.contains(source) {
+                    *tokens.get_or_insert(0) -= amount;
+                }
+                if token_accounts.contains(destination) {
+                    *tokens.get_or_insert(0) += amount;
+                }
+            }
+            None => {}
         }
-    })
+    }
+    LegFlow {
+        lamports: lamports.map(i128_to_i64),
+        tokens: tokens.map(i128_to_i64),
+    }
+}
+
+fn split_delta(observed: Vec<Option<i64>>, total: i64) -> Vec<i64> {
+    let observed_sum: i128 = observed.iter().flatten().map(|&value| value as i128).sum();
+    let residual = total as i128 - observed_sum;
+    let unobserved: Vec<usize> = observed
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, value)| value.is_none().then_some(idx))
+        .collect();
+
+    let mut split: Vec<i128> = observed.iter().map(|value| value.unwrap_or(0) as i128).collect();
+    if unobserved.is_empty() {
+        split[0] += residual;
+    } else {
+        let share = residual / unobserved.len() as i128;
+        for &idx in &unobserved {
+            split[idx] += share;
+        }
+        let last = unobserved[unobserved.len() - 1];
+        split[last] += residual - share * unobserved.len() as i128;
+    }
+    split.into_iter().map(i128_to_i64).collect()
 }
 
-fn try_decode(disc_slice: &[u8], payload: &[u8]) -> Option<DecodedInstruction> {
-    let disc: [u8; 8] = disc_slice.try_into().ok()?;
+fn signer_token_accounts(
+    meta: &UiTransactionStatusMeta,
+    account_keys: &[String],
+    owner: &str,
+    mint: &str,
+) -> Vec<String> {
+    let mut accounts = Vec::new();
+    let balances = meta
+        .pre_token_balances
+        .as_slice()
+        .unwrap_or(&[])
+        .iter()
+        .chain(meta.post_token_balances.as_slice().unwrap_or(&[]));
+    for balance in balances {
+        if balance.mint != mint {
+            continue;
+        }
+        if let OptionSerializer::Some(balance_owner) = balance.owner.as_ref()
+            && balance_owner == owner
+            && let Some(key) = account_keys.get(balance.account_index as usize)
+            && !accounts.contains(key)
+        {
+            accounts.push(key.clone());
+        }
+    }
+    accounts
+}
 
-    if disc == BUY_DISCRIMINATOR {
-        let args = BuyArgs::try_from_slice(payload).ok()?;
+// One instruction's base58 data read the way `parse_transaction` reads it: a buy or sell's
+// (side, token amount, SOL limit) and the create or migrate it carries. The entry point of the
+// fuzz target in `fuzz/`; `accounts` stand in for the instruction's resolved accounts.
+#[derive(Debug, Default, PartialEq)]
+pub struct DecodedData {
+    pub trade: Option<(TradeType, u64, u64)>,
+    pub lifecycle: Option<LifecycleKind>,
+}
+
+pub fn decode_instruction_data(data: &str, accounts: &[String]) -> DecodedData {
+    let mut buffer = Vec::new();
+    let raw = decode_base58(data, &mut buffer);
+    if !has_pump_discriminator(raw) {
+        return DecodedData::default();
+    }
+    DecodedData {
+        trade: decode_instruction_bytes(raw)
+            .map(|decoded| (decoded.trade_type, decoded.token_amount_requested, decoded.sol_limit_specified)),
+        lifecycle: decode_lifecycle_instruction(raw, accounts).map(|lifecycle| lifecycle.kind),
+    }
+}
+
+struct DecodedInstruction {
+    trade_type: TradeType,
+    token_amount_requested: u64,
+    sol_limit_specified: u64,
+}
+
+fn has_pump_discriminator(raw: &[u8]) -> bool {
+    raw.starts_with(&BUY_DISCRIMINATOR)
+        || raw.starts_with(&SELL_DISCRIMINATOR)
+        || raw.starts_with(&CREATE_DISCRIMINATOR)
+        || raw.starts_with(&CREATE_V2_DISCRIMINATOR)
+        || raw.starts_with(&MIGRATE_DISCRIMINATOR)
+}
+
+fn decode_instruction_bytes(data: &[u8]) -> Option<DecodedInstruction> {
+    // trailing arguments (PumpSwap's track_volume flag) are ignored
+    if let Some(mut payload) = data.strip_prefix(&BUY_DISCRIMINATOR) {
+        let args = BuyArgs::deserialize(&mut payload).ok()?;
         return Some(DecodedInstruction {
             trade_type: TradeType::Buy,
             token_amount_requested: args.amount,
@@ -221,8 +826,8 @@ fn try_decode(disc_slice: &[u8], payload: &[u8]) -> Option<DecodedInstruction> {
         });
     }
 
-    if disc == SELL_DISCRIMINATOR {
-        let args = SellArgs::try_from_slice(payload).ok()?;
+    if let Some(mut payload) = data.strip_prefix(&SELL_DISCRIMINATOR) {
+        let args = SellArgs::deserialize(&mut payload).ok()?;
         return Some(DecodedInstruction {
             trade_type: TradeType::Sell,
             token_amount_requested: args.amount,
@@ -233,6 +838,40 @@ fn try_decode(disc_slice: &[u8], payload: &[u8]) -> Option<DecodedInstruction> {
     None
 }
 
+fn decode_lifecycle_instruction(raw: &[u8], accounts: &[String]) -> Option<DecodedLifecycle> {
+    let create = raw
+        .strip_prefix(&CREATE_DISCRIMINATOR)
+        .map(|payload| (7, payload))
+        .or_else(|| raw.strip_prefix(&CREATE_V2_DISCRIMINATOR).map(|payload| (5, payload)));
+
+    if let Some((user_index, mut payload)) = create {
+        let args = CreateArgs::deserialize(&mut payload).ok()?;
+        let creator = match BorshDeserialize::deserialize(&mut payload) {
+            Ok(creator) => bs58::encode::<[u8; 32]>(creator).into_string(),
+            Err(_) => accounts.get(user_index)?.clone(),
+        };
+        return Some(DecodedLifecycle {
+            mint: accounts.first()?.clone(),
+            kind: LifecycleKind::Create {
+                creator,
+                name: args.name,
+                symbol: args.symbol,
+                uri: args.uri,
+            },
+        });
+    }
+
+    if raw.starts_with(&MIGRATE_DISCRIMINATOR) {
+        return Some(DecodedLifecycle {
+            mint: accounts.get(2)?.clone(),
+            kind: LifecycleKind::Migrate,
+        });
+    }
+
+    None
+}
+
+use solana_sdk::transaction::VersionedTransaction;
 use solana_transaction_status::option_serializer::OptionSerializer;
 
 trait OptionSerializerExt<T> {
@@ -248,20 +887,163 @@ impl<T> OptionSerializerExt<T> for OptionSerializer<Vec<T>> {
     }
 }
 
+// every account the transaction touched, in the order its balances are listed
+pub fn account_keys(tx: &EncodedConfirmedTransactionWithStatusMeta, signature: &str) -> Result<Vec<String>, Error> {
+    let meta = tx.transaction.meta.as_ref();
+    let keys = match &tx.transaction.transaction {
+        EncodedTransaction::Json(tx_json) => match &tx_json.message {
+            UiMessage::Parsed(message) => message.account_keys.iter().map(|account| account.pubkey.clone()).collect(),
+            UiMessage::Raw(message) => with_loaded_addresses(message.account_keys.clone(), meta),
+        },
+        encoded => {
+            let versioned = encoded.decode().ok_or_else(|| Error::Decode {
+                signature: signature.to_string(),
+                reason: "transaction payload carries no account keys".to_string(),
+            })?;
+            let static_keys = versioned
+                .message
+                .static_account_keys()
+                .iter()
+                .map(|key| key.to_string())
+                .collect();
+            with_loaded_addresses(static_keys, meta)
+        }
+    };
+    Ok(keys)
+}
+
+// The price set by the transaction's top-level SetComputeUnitPrice; the runtime only reads
+// compute budget instructions there.
+fn transaction_compute_unit_price(tx: &EncodedConfirmedTransactionWithStatusMeta, account_keys: &[String]) -> u64 {
+    let mut buffer = Vec::new();
+    let price = match &tx.transaction.transaction {
+        EncodedTransaction::Json(tx_json) => match &tx_json.message {
+            UiMessage::Parsed(message) => message.instructions.iter().find_map(|ix| match ix {
+                UiInstruction::Parsed(UiParsedInstruction::PartiallyDecoded(partial)) => {
+                    compute_unit_price(&partial.program_id, decode_base58(&partial.data, &mut buffer))
+                }
+                UiInstruction::Compiled(compiled) => compute_unit_price(
+                    account_keys.get(compiled.program_id_index as usize)?,
+                    decode_base58(&compiled.data, &mut buffer),
+                ),
+                UiInstruction::Parsed(UiParsedInstruction::Parsed(_)) => None,
+            }),
+            UiMessage::Raw(message) => message.instructions.iter().find_map(|ix| {
+                compute_unit_price(
+                    account_keys.get(ix.program_id_index as usize)?,
+                    decode_base58(&ix.data, &mut buffer),
+                )
+            }),
+        },
+        encoded => return encoded.decode().map(|versioned| versioned_compute_unit_price(&versioned)).unwrap_or(0),
+    };
+    price.unwrap_or(0)
+}
+
+// for decoded transactions, e.g. the ones of a full block
+pub fn versioned_compute_unit_price(tx: &VersionedTransaction) -> u64 {
+    let keys = tx.message.static_account_keys();
+    tx.message
+        .instructions()
+        .iter()
+        .find_map(|ix| {
+            // the compute budget program is always a static key
+            let program = keys.get(ix.program_id_index as usize)?.to_string();
+            compute_unit_price(&program, &ix.data)
+        })
+        .unwrap_or(0)
+}
+
+fn compute_unit_price(program: &str, data: &[u8]) -> Option<u64> {
+    if program != COMPUTE_BUDGET_PROGRAM || data.first() != Some(&SET_COMPUTE_UNIT_PRICE) {
+        return None;
+    }
+    Some(u64::from_le_bytes(data.get(1..9)?.try_into().ok()?))
+}
+
+fn with_loaded_addresses(
+    mut account_keys: Vec<String>,
+    meta: Option<&UiTransactionStatusMeta>,
+) -> Vec<String> {
+    if let Some(meta) = meta
+        && let OptionSerializer::Some(loaded) = &meta.loaded_addresses
+    {
+        account_keys.extend(loaded.writable.iter().cloned());
+        account_keys.extend(loaded.readonly.iter().cloned());
+    }
+    account_keys
+}
+
 fn compute_sol_change(
     meta: &UiTransactionStatusMeta,
-    message: &UiParsedMessage,
+    account_keys: &[String],
     signer: &str,
 ) -> Option<i64> {
-    let account_index = message
-        .account_keys
-        .iter()
-        .position(|account| account.pubkey == signer)?;
+    let account_index = account_keys.iter().position(|account| account == signer)?;
     let pre = *meta.pre_balances.get(account_index)? as i128;
     let post = *meta.post_balances.get(account_index)? as i128;
     Some(i128_to_i64(post - pre))
 }
 
+// native lamports plus wrapped SOL, which PumpSwap pays and is paid in
+fn trader_sol_change(meta: &UiTransactionStatusMeta, account_keys: &[String], trader: &str) -> Option<i64> {
+    let wrapped = compute_token_change(meta, trader, WSOL_MINT).unwrap_or(0);
+    Some(compute_sol_change(meta, account_keys, trader)?.saturating_add(wrapped))
+}
+
+fn compute_hop_token_changes(meta: &UiTransactionStatusMeta, owner: &str, mint: &str) -> BTreeMap<String, i64> {
+    let mints: BTreeSet<&str> = meta
+        .pre_token_balances
+        .as_slice()
+        .unwrap_or(&[])
+        .iter()
+        .chain(meta.post_token_balances.as_slice().unwrap_or(&[]))
+        .map(|balance| balance.mint.as_ref())
+        .filter(|other| *other != mint && *other != WSOL_MINT)
+        .collect();
+    mints
+        .into_iter()
+        .filter_map(|other| Some((other.to_string(), compute_token_change(meta, owner, other)?)))
+        .filter(|(_, change)| *change != 0)
+        .collect()
+}
+
+fn compute_tip(meta: &UiTransactionStatusMeta, account_keys: &[String]) -> u64 {
+    JITO_TIP_ACCOUNTS
+        .iter()
+        .filter_map(|tip_account| compute_sol_change(meta, account_keys, tip_account))
+        .map(|received| received.max(0) as u64)
+        .sum()
+}
+
+fn signature_count(tx: &EncodedConfirmedTransactionWithStatusMeta) -> u64 {
+    let count = match &tx.transaction.transaction {
+        EncodedTransaction::Json(ui) => ui.signatures.len(),
+        encoded => encoded.decode().map(|versioned| versioned.signatures.len()).unwrap_or(1),
+    };
+    count.max(1) as u64
+}
+
+fn compute_curve_delta(
+    meta: &UiTransactionStatusMeta,
+    account_keys: &[String],
+    curve: &str,
+    mint: &str,
+) -> Option<CurveDelta> {
+    let sol_change = compute_sol_change(meta, account_keys, curve)?;
+    let pre_tokens = extract_token_total(meta.pre_token_balances.as_slice(), curve, mint);
+    let post_tokens = extract_token_total(meta.post_token_balances.as_slice(), curve, mint);
+    if pre_tokens.is_none() && post_tokens.is_none() {
+        return None;
+    }
+    Some(CurveDelta {
+        account: curve.to_string(),
+        sol_change,
+        token_change: i128_to_i64(post_tokens.unwrap_or(0) - pre_tokens.unwrap_or(0)),
+        pre_token_balance: pre_tokens.and_then(|tokens| u64::try_from(tokens).ok()),
+    })
+}
+
 fn compute_token_change(meta: &UiTransactionStatusMeta, owner: &str, mint: &str) -> Option<i64> {
     let pre = extract_token_total(meta.pre_token_balances.as_slice(), owner, mint);
     let post = extract_token_total(meta.post_token_balances.as_slice(), owner, mint);
@@ -293,11 +1075,11 @@ fn extract_token_total(
                 OptionSerializer::Skip | OptionSerializer::None => continue,
             };
 
-            if balance_owner == owner {
-                if let Ok(amount) = balance.ui_token_amount.amount.parse::<i128>() {
-                    total += amount;
-                    found = true;
-                }
+            if balance_owner == owner
+                && let Ok(amount) = balance.ui_token_amount.amount.parse::<i128>()
+            {
+                total += amount;
+                found = true;
             }
         }
     }
@@ -305,6 +1087,36 @@ fn extract_token_total(
     if found { Some(total) } else { None }
 }
 
+fn compute_ata_rent(meta: &UiTransactionStatusMeta, owner: &str, mint: &str) -> u64 {
+    let pre_accounts: Vec<u8> = meta
+        .pre_token_balances
+        .as_slice()
+        .unwrap_or(&[])
+        .iter()
+        .map(|balance| balance.account_index)
+        .collect();
+
+    let mut rent: u64 = 0;
+    for balance in meta.post_token_balances.as_slice().unwrap_or(&[]) {
+        if balance.mint != mint || pre_accounts.contains(&balance.account_index) {
+            continue;
+        }
+        let balance_owner = match balance.owner.as_ref() {
+            OptionSerializer::Some(owner_str) => owner_str,
+            OptionSerializer::Skip | OptionSerializer::None => continue,
+        };
+        if balance_owner != owner {
+            continue;
+        }
+
+        let index = balance.account_index as usize;
+        let pre = meta.pre_balances.get(index).copied().unwrap_or(0);
+        let post = meta.post_balances.get(index).copied().unwrap_or(0);
+        rent = rent.saturating_add(post.saturating_sub(pre));
+    }
+    rent
+}
+
 fn i128_to_i64(value: i128) -> i64 {
     if value > i64::MAX as i128 {
         i64::MAX
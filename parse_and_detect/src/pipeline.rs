@@ -0,0 +1,130 @@
+use crate::config::RunConfig;
+use crate::fetch_batch;
+use crate::tui::Feed;
+use indicatif::ProgressBar;
+use rusty::alt::LookupTableCache;
+use rusty::detect::DetectionSummary;
+use rusty::error::Error;
+use rusty::fetch::TransactionSource;
+use rusty::parser::pumpfun::{self, ParsedTransaction};
+use rusty::streaming::{StreamingDetector, SummaryCollector};
+use solana_transaction_status::EncodedConfirmedTransactionWithStatusMeta;
+use std::sync::mpsc::{SyncSender, sync_channel};
+use std::thread;
+
+// Bounded-memory scan: fetching, parsing and detection run as three stages joined by bounded
+// channels, so only a few hundred transactions, the detector's slot window and the detections
+// are held at once. The fetcher stays on the calling thread, which owns the source; a full
+// channel blocks it until the stages downstream catch up. With a `feed` the detector stage also
+// hands every leg and detection to the dashboard, and skipped transactions are reported there
+// instead of on stderr; a dashboard that went away stops the scan.
+
+// transactions or legs queued between two stages
+pub const CHANNEL_BOUND: usize = 256;
+
+pub struct Streamed {
+    pub summary: DetectionSummary,
+    pub trades: usize,
+    pub failures: Vec<Error>,
+}
+
+pub fn scan(
+    source: &dyn TransactionSource,
+    signatures: &[String],
+    mint: &str,
+    config: &RunConfig,
+    progress: &ProgressBar,
+    feed: Option<SyncSender<Feed>>,
+) -> Streamed {
+    // the detector expects chain order, signatures arrive newest first
+    let mut oldest_first = signatures.to_vec();
+    oldest_first.reverse();
+
+    let (raw_sender, raw_receiver) = sync_channel::<(String, EncodedConfirmedTransactionWithStatusMeta)>(CHANNEL_BOUND);
+    let (leg_sender, leg_receiver) = sync_channel::<ParsedTransaction>(CHANNEL_BOUND);
+
+    thread::scope(|scope| {
+        let pump_program = config.network.pump_program_id.as_str();
+        let warn = |message: String| match &feed {
+            Some(feed) => drop(feed.send(Feed::Failure(message))),
+            None => progress.suspend(|| eprintln!("{}", message)),
+        };
+        let parser = scope.spawn(move || {
+            let mut failures = Vec::new();
+            for (signature, tx) in raw_receiver {
+                match pumpfun::parse_transaction(&tx, &signature, mint, pump_program) {
+                    Ok(legs) => {
+                        for leg in legs {
+                            if leg_sender.send(leg).is_err() {
+                                return failures;
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        warn(format!("Skipped {}: {}", signature, e));
+                        failures.push(e);
+                    }
+                }
+            }
+            failures
+        });
+
+        let cfg = &config.detector;
+        let dashboard = feed.clone();
+        let detector = scope.spawn(move || {
+            let mut detector = StreamingDetector::new(cfg.clone());
+            let mut collector = SummaryCollector::default();
+            for leg in leg_receiver {
+                collector.count_trade(&leg);
+                if let Some(feed) = &dashboard
+                    && feed.send(Feed::Trade(leg.clone())).is_err()
+                {
+                    break;
+                }
+                for event in detector.push(leg) {
+                    if let Some(feed) = &dashboard {
+                        // the dashboard being gone is noticed with the next leg
+                        let _ = feed.send(Feed::Event(event.clone()));
+                    }
+                    collector.record(event);
+                }
+            }
+            let trades = collector.trades();
+            (collector.finish(cfg, &detector.slot_gaps()), trades)
+        });
+
+        let mut failures = Vec::new();
+        let mut lookup_tables = LookupTableCache::new();
+        'fetch: for batch in oldest_first.chunks(config.rpc.batch_size.max(1)) {
+            for (signature, response) in batch.iter().zip(fetch_batch(source, batch, &config.rpc, progress)) {
+                progress.inc(1);
+                let mut tx = match response {
+                    Ok(tx) => tx,
+                    Err(e) => {
+                        warn(format!("Failed {}: {}", signature, e));
+                        failures.push(e);
+                        continue;
+                    }
+                };
+                if let Err(e) = lookup_tables.resolve_loaded_addresses(source, &mut tx) {
+                    warn(format!("Lookup tables unresolved for {}: {}", signature, e));
+                    failures.push(e);
+                }
+                if raw_sender.send((signature.clone(), tx)).is_err() {
+                    break 'fetch;
+                }
+            }
+            progress.set_message(format!("failed {}", failures.len()));
+        }
+        drop(raw_sender);
+
+        failures.extend(parser.join().unwrap_or_else(|panic| std::panic::resume_unwind(panic)));
+        let (summary, trades) = detector.join().unwrap_or_else(|panic| std::panic::resume_unwind(panic));
+        progress.finish_with_message(format!("parsed {} legs | failed {}", trades, failures.len()));
+        Streamed {
+            summary,
+            trades,
+            failures,
+        }
+    })
+}
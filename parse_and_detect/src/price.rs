@@ -0,0 +1,228 @@
+use crate::detect::{DetectionSummary, LamportsExt, SandwichDetection};
+use crate::error::{Error, Result};
+use crate::leaderboard::AttackerStats;
+use crate::parser::pumpfun::ParsedTransaction;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+#[cfg(feature = "rpc")]
+use std::time::Duration;
+
+#[cfg(feature = "rpc")]
+const COINGECKO_RANGE_URL: &str = "https://api.coingecko.com/api/v3/coins/solana/market_chart/range";
+// widen short ranges so the feed always has a point on either side
+const RANGE_PADDING_SECS: i64 = 3_600;
+
+pub trait PriceFeed {
+    // (unix seconds, SOL/USD) points covering [from, to], in any order
+    fn sol_usd_history(&self, from: i64, to: i64) -> Result<Vec<(i64, f64)>>;
+}
+
+#[cfg(feature = "rpc")]
+pub struct CoinGecko {
+    client: reqwest::blocking::Client,
+    api_key: Option<String>,
+}
+
+#[cfg(feature = "rpc")]
+impl CoinGecko {
+    pub fn new(api_key: Option<String>, timeout: Duration) -> Result<Self> {
+        let client = reqwest::blocking::Client::builder()
+            .timeout(timeout)
+            .build()
+            .map_err(|e| Error::Price(e.to_string()))?;
+        Ok(Self { client, api_key })
+    }
+}
+
+#[cfg(feature = "rpc")]
+#[derive(Deserialize)]
+struct MarketChart {
+    prices: Vec<(f64, f64)>,
+}
+
+#[cfg(feature = "rpc")]
+impl PriceFeed for CoinGecko {
+    fn sol_usd_history(&self, from: i64, to: i64) -> Result<Vec<(i64, f64)>> {
+        let mut request = self.client.get(COINGECKO_RANGE_URL).query(&[
+            ("vs_currency", "usd".to_string()),
+            ("from", from.to_string()),
+            ("to", to.to_string()),
+        ]);
+        if let Some(key) = &self.api_key {
+            request = request.header("x-cg-demo-api-key", key);
+        }
+        let chart: MarketChart = request
+            .send()
+            .and_then(|response| response.error_for_status())
+            .and_then(|response| response.json())
+            .map_err(|e| Error::Price(format!("CoinGecko: {}", e)))?;
+        Ok(chart
+            .prices
+            .into_iter()
+            .map(|(millis, price)| ((millis / 1_000.0) as i64, price))
+            .collect())
+    }
+}
+
+// one price for every block time, for offline runs or a manual override
+pub struct FixedPrice(pub f64);
+
+impl PriceFeed for FixedPrice {
+    fn sol_usd_history(&self, from: i64, _to: i64) -> Result<Vec<(i64, f64)>> {
+        Ok(vec![(from, self.0)])
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UsdValuation {
+    // SOL/USD at the block time of each detected transaction
+    pub sol_usd: BTreeMap<String, f64>,
+    pub attacker_profit_usd: BTreeMap<String, f64>,
+    pub total_extracted_usd: f64,
+    pub total_victim_loss_usd: f64,
+}
+
+impl UsdValuation {
+    pub fn price(&self, signature: &str) -> Option<f64> {
+        self.sol_usd.get(signature).copied()
+    }
+
+    pub fn usd(&self, signature: &str, lamports: i64) -> Option<f64> {
+        self.price(signature)
+            .map(|price| lamports.as_sol() * price)
+    }
+
+    // each leg is valued at its own block time, so this can differ from net_profit_sol * one price
+    pub fn sandwich_profit(&self, det: &SandwichDetection) -> Option<f64> {
+        det.frontruns
+            .iter()
+            .chain(det.backruns.iter())
+            .map(|leg| self.usd(&leg.signature, leg.sol_change))
+            .sum()
+    }
+
+    // summed over every wallet the leaderboard entry stands for
+    pub fn attacker_profit(&self, stats: &AttackerStats) -> Option<f64> {
+        stats
+            .wallets()
+            .filter_map(|wallet| self.attacker_profit_usd.get(wallet))
+            .fold(None, |total, usd| Some(total.unwrap_or(0.0) + usd))
+    }
+}
+
+// values each detection at the SOL price of its own block time; transactions without a
+// block time are left unpriced rather than guessed
+pub fn value_summary(
+    feed: &dyn PriceFeed,
+    summary: &DetectionSummary,
+) -> Result<UsdValuation> {
+    let mut times: BTreeMap<&str, i64> = BTreeMap::new();
+    for tx in detected_transactions(summary) {
+        if let Some(block_time) = tx.block_time {
+            times.insert(tx.signature.as_ref(), block_time);
+        }
+    }
+
+    let mut valuation = UsdValuation::default();
+    let (Some(&from), Some(&to)) = (times.values().min(), times.values().max()) else {
+        return Ok(valuation);
+    };
+    let mut history = feed.sol_usd_history(from - RANGE_PADDING_SECS, to + RANGE_PADDING_SECS)?;
+    if history.is_empty() {
+        return Err(Error::Price(format!("no SOL/USD prices between {} and {}", from, to)));
+    }
+    history.sort_by_key(|&(time, _)| time);
+
+    for (signature, block_time) in times {
+        valuation
+            .sol_usd
+            .insert(signature.to_string(), nearest_price(&history, block_time));
+    }
+
+    for det in &summary.sandwiches {
+        if let Some(loss) = valuation.usd(&det.victim.signature, det.victim_loss() as i64) {
+            valuation.total_victim_loss_usd += loss;
+        }
+        for leg in det.frontruns.iter().chain(det.backruns.iter()) {
+            if let Some(profit) = valuation.usd(&leg.signature, leg.sol_change) {
+                *valuation.attacker_profit_usd.entry(leg.signer.to_string()).or_default() += profit;
+                valuation.total_extracted_usd += profit;
+            }
+        }
+    }
+
+    Ok(valuation)
+}
+
+fn detected_transactions(summary: &DetectionSummary) -> impl Iterator<Item = &ParsedTransaction> {
+    let sandwiches = summary
+        .sandwiches
+        .iter()
+        .flat_map(|det| std::iter::once(&det.victim).chain(&det.frontruns).chain(&det.backruns));
+    let front_runs = summary
+        .front_runs
+        .iter()
+        .flat_map(|event| std::iter::once(&event.victim).chain(&event.frontruns));
+    let back_runs = summary
+        .back_runs
+        .iter()
+        .flat_map(|event| std::iter::once(&event.victim).chain(&event.backruns));
+    let backrun_arbs = summary
+        .backrun_arbs
+        .iter()
+        .flat_map(|event| [&event.victim, &event.backrun, &event.close]);
+    let reverted = summary
+        .reverted_sandwiches
+        .iter()
+        .flat_map(|event| std::iter::once(&event.victim).chain(&event.frontruns).chain(&event.backruns));
+    sandwiches
+        .chain(front_runs)
+        .chain(back_runs)
+        .chain(backrun_arbs)
+        .chain(reverted)
+}
+
+// history must be sorted by time and non-empty
+fn nearest_price(history: &[(i64, f64)], time: i64) -> f64 {
+    let idx = history.partition_point(|&(point, _)| point < time);
+    match (idx.checked_sub(1).map(|before| history[before]), history.get(idx).copied()) {
+        (Some(before), Some(after)) => {
+            if time - before.0 <= after.0 - time { before.1 } else { after.1 }
+        }
+        (Some(only), None) | (None, Some(only)) => only.1,
+        (None, None) => 0.0,
+    }
+}
+
+// "2025-05-12 10:00:00 UTC" from unix seconds
+pub fn format_utc(unix_secs: i64) -> String {
+    let days = unix_secs.div_euclid(86_400);
+    let secs = unix_secs.rem_euclid(86_400);
+    // civil-from-days over 400-year eras, proleptic Gregorian
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02} UTC",
+        year,
+        month,
+        day,
+        secs / 3_600,
+        secs % 3_600 / 60,
+        secs % 60
+    )
+}
+
+pub fn format_usd(usd: f64) -> String {
+    if usd < 0.0 {
+        format!("-${:.2}", -usd)
+    } else {
+        format!("${:.2}", usd)
+    }
+}
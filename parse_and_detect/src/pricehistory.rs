@@ -0,0 +1,94 @@
+use crate::error::{Error, Result};
+use crate::price::PriceFeed;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const HOUR_SECS: i64 = 3_600;
+const DAY_SECS: i64 = 86_400;
+
+// SOL/USD history persisted between runs, so long backfills fetch each day once.
+// The feed decides the resolution: hourly for recent ranges, daily for old ones.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PriceHistory {
+    // days (unix seconds / 86400) already fetched, including ones the feed had no prices for
+    pub days: BTreeSet<i64>,
+    // SOL/USD keyed by the start of the hour it was quoted in
+    pub hourly: BTreeMap<i64, f64>,
+}
+
+impl PriceHistory {
+    pub fn load(path: &str) -> Result<Self> {
+        if !Path::new(path).exists() {
+            return Ok(Self::default());
+        }
+        let raw = fs::read_to_string(path).map_err(|e| Error::io("read price history", path, e))?;
+        serde_json::from_str(&raw).map_err(|e| Error::json(format!("price history '{}'", path), e))
+    }
+
+    pub fn save(&self, path: &str) -> Result<()> {
+        let json = serde_json::to_string_pretty(self).map_err(|e| Error::json("price history", e))?;
+        fs::write(path, json).map_err(|e| Error::io("write price history", path, e))
+    }
+
+    // first and last day in [from, to] that haven't been fetched yet
+    pub fn missing_days(&self, from: i64, to: i64) -> Option<(i64, i64)> {
+        let mut missing = (from.div_euclid(DAY_SECS)..=to.div_euclid(DAY_SECS)).filter(|day| !self.days.contains(day));
+        let first = missing.next()?;
+        Some((first, missing.next_back().unwrap_or(first)))
+    }
+
+    // the current day keeps receiving prices, so it is never marked as complete
+    pub fn record(&mut self, first_day: i64, last_day: i64, points: &[(i64, f64)], now: i64) {
+        for &(time, price) in points {
+            self.hourly.entry(time - time.rem_euclid(HOUR_SECS)).or_insert(price);
+        }
+        let today = now.div_euclid(DAY_SECS);
+        self.days.extend((first_day..=last_day).filter(|&day| day < today));
+    }
+
+    // one day of slack either side, so daily-resolution history still brackets the range
+    pub fn points(&self, from: i64, to: i64) -> Vec<(i64, f64)> {
+        self.hourly
+            .range(from - DAY_SECS..=to + DAY_SECS)
+            .map(|(&time, &price)| (time, price))
+            .collect()
+    }
+}
+
+pub struct CachedFeed<F> {
+    inner: F,
+    history: Mutex<PriceHistory>,
+    path: String,
+}
+
+impl<F: PriceFeed> CachedFeed<F> {
+    pub fn load(inner: F, path: &str) -> Result<Self> {
+        Ok(Self {
+            inner,
+            history: Mutex::new(PriceHistory::load(path)?),
+            path: path.to_string(),
+        })
+    }
+}
+
+impl<F: PriceFeed> PriceFeed for CachedFeed<F> {
+    fn sol_usd_history(&self, from: i64, to: i64) -> Result<Vec<(i64, f64)>> {
+        let mut history = self.history.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Some((first_day, last_day)) = history.missing_days(from, to) {
+            let points = self
+                .inner
+                .sol_usd_history(first_day * DAY_SECS, (last_day + 1) * DAY_SECS - 1)?;
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|elapsed| elapsed.as_secs() as i64)
+                .unwrap_or(i64::MAX);
+            history.record(first_day, last_day, &points, now);
+            history.save(&self.path)?;
+        }
+        Ok(history.points(from, to))
+    }
+}
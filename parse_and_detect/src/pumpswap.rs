@@ -0,0 +1,196 @@
+use crate::amm::{INITIAL_REAL_TOKEN, INITIAL_VIRTUAL_TOKEN, PumpAmmState, TOKEN_DECIMALS};
+use serde::{Deserialize, Serialize};
+
+// Tokens held back from the curve and deposited into the pool at migration
+pub const MIGRATION_RESERVED_TOKENS: u64 = 206_900_000 * TOKEN_DECIMALS;
+pub const MIGRATION_FEE_LAMPORTS: u64 = 15_000_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PoolFees {
+    pub lp_bps: u64,
+    pub protocol_bps: u64,
+    pub creator_bps: u64,
+}
+
+impl Default for PoolFees {
+    fn default() -> Self {
+        Self {
+            lp_bps: 20,
+            protocol_bps: 5,
+            creator_bps: 5,
+        }
+    }
+}
+
+impl PoolFees {
+    pub fn total_bps(&self) -> u64 {
+        self.lp_bps + self.protocol_bps + self.creator_bps
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PumpSwapPool {
+    pub base_reserve: u64,
+    pub quote_reserve: u64,
+    pub fees: PoolFees,
+}
+
+impl PumpSwapPool {
+    pub fn migrate(curve: &PumpAmmState, fees: PoolFees) -> Self {
+        Self {
+            base_reserve: curve.real_token + MIGRATION_RESERVED_TOKENS,
+            quote_reserve: curve.real_sol.saturating_sub(MIGRATION_FEE_LAMPORTS),
+            fees,
+        }
+    }
+
+    pub fn price(&self) -> f64 {
+        if self.base_reserve == 0 {
+            0.0
+        } else {
+            self.quote_reserve as f64 / self.base_reserve as f64
+        }
+    }
+
+    // LP fees stay in the pool; protocol and creator fees leave it
+    pub fn buy(&mut self, sol_in: u64, min_tokens_out: u64) -> u64 {
+        let fee = sol_in as u128 * self.fees.total_bps() as u128 / 10_000;
+        let lp_fee = sol_in as u128 * self.fees.lp_bps as u128 / 10_000;
+        let sol_after_fee = sol_in as u128 - fee;
+        let tokens_out = (sol_after_fee * self.base_reserve as u128
+            / (self.quote_reserve as u128 + sol_after_fee)) as u64;
+        if tokens_out == 0 || tokens_out < min_tokens_out {
+            return 0;
+        }
+
+        self.quote_reserve += (sol_after_fee + lp_fee) as u64;
+        self.base_reserve -= tokens_out;
+        tokens_out
+    }
+
+    pub fn sell(&mut self, tokens_in: u64, min_sol_out: u64) -> u64 {
+        let gross = (tokens_in as u128 * self.quote_reserve as u128
+            / (self.base_reserve as u128 + tokens_in as u128)) as u64;
+        let fee = (gross as u128 * self.fees.total_bps() as u128 / 10_000) as u64;
+        let lp_fee = (gross as u128 * self.fees.lp_bps as u128 / 10_000) as u64;
+        let sol_out = gross - fee;
+        if sol_out == 0 || sol_out < min_sol_out {
+            return 0;
+        }
+
+        self.quote_reserve -= gross - lp_fee;
+        self.base_reserve += tokens_in;
+        sol_out
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Venue {
+    BondingCurve,
+    PumpSwap,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BuyFill {
+    pub tokens_out: u64,
+    pub sol_spent: u64,
+    pub sol_refunded: u64,
+    pub venue: Venue,
+    pub graduated: bool,
+}
+
+// A token's market across its lifetime: the bonding curve until its real tokens sell out,
+// then the PumpSwap pool it migrates into.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Market {
+    BondingCurve { curve: PumpAmmState, pool_fees: PoolFees },
+    PumpSwap(PumpSwapPool),
+}
+
+impl Market {
+    pub fn new(curve: PumpAmmState, pool_fees: PoolFees) -> Self {
+        Market::BondingCurve { curve, pool_fees }
+    }
+
+    // curve state from which buying `sol_in` (before fees) completes the curve exactly
+    pub fn near_graduation(sol_in: u64, template: &PumpAmmState) -> PumpAmmState {
+        let k = template.virtual_sol as u128 * template.virtual_token as u128;
+        let final_virtual_token = (INITIAL_VIRTUAL_TOKEN - INITIAL_REAL_TOKEN) as u128;
+        let final_virtual_sol = k / final_virtual_token;
+        let after_fee = sol_in as u128 * (10_000 - template.fee.total_bps()) as u128 / 10_000;
+        let virtual_sol = final_virtual_sol.saturating_sub(after_fee).max(1);
+        let virtual_token = (k / virtual_sol) as u64;
+        let virtual_sol = virtual_sol as u64;
+
+        PumpAmmState::from_reserves(
+            virtual_sol,
+            virtual_token,
+            virtual_sol.saturating_sub(template.virtual_sol - template.real_sol),
+            virtual_token.saturating_sub(INITIAL_VIRTUAL_TOKEN - INITIAL_REAL_TOKEN),
+        )
+        .with_fee(template.fee)
+    }
+
+    pub fn venue(&self) -> Venue {
+        match self {
+            Market::BondingCurve { .. } => Venue::BondingCurve,
+            Market::PumpSwap(_) => Venue::PumpSwap,
+        }
+    }
+
+    pub fn price(&self) -> f64 {
+        match self {
+            Market::BondingCurve { curve, .. } => curve.price(),
+            Market::PumpSwap(pool) => pool.price(),
+        }
+    }
+
+    pub fn buy(&mut self, sol_in: u64, min_tokens_out: u64) -> BuyFill {
+        let venue = self.venue();
+        match self {
+            Market::PumpSwap(pool) => {
+                let tokens_out = pool.buy(sol_in, min_tokens_out);
+                BuyFill {
+                    tokens_out,
+                    sol_spent: if tokens_out > 0 { sol_in } else { 0 },
+                    sol_refunded: 0,
+                    venue,
+                    graduated: false,
+                }
+            }
+            Market::BondingCurve { curve, pool_fees } => {
+                let remaining = curve.real_token;
+                let quoted = curve.quote_buy(sol_in);
+                // the final buy on the curve is capped at the remaining tokens and the excess SOL refunded
+                let (sol_spent, min_out) = if quoted >= remaining {
+                    (curve.sol_for_tokens(remaining).min(sol_in), min_tokens_out.min(remaining))
+                } else {
+                    (sol_in, min_tokens_out)
+                };
+                let (tokens_out, _) = curve.simulate_buy(sol_spent, min_out);
+                let tokens_out = tokens_out.min(remaining);
+                let sol_spent = if tokens_out > 0 { sol_spent } else { 0 };
+                let graduated = tokens_out > 0 && curve.real_token == 0;
+
+                let fill = BuyFill {
+                    tokens_out,
+                    sol_spent,
+                    sol_refunded: sol_in - sol_spent,
+                    venue,
+                    graduated,
+                };
+                if graduated {
+                    *self = Market::PumpSwap(PumpSwapPool::migrate(curve, *pool_fees));
+                }
+                fill
+            }
+        }
+    }
+
+    pub fn sell(&mut self, tokens_in: u64, min_sol_out: u64) -> u64 {
+        match self {
+            Market::BondingCurve { curve, .. } => curve.simulate_sell(tokens_in, min_sol_out),
+            Market::PumpSwap(pool) => pool.sell(tokens_in, min_sol_out),
+        }
+    }
+}
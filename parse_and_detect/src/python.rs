@@ -0,0 +1,205 @@
+use crate::amm::{FeeSchedule, PumpAmmState};
+use crate::embed::{self, DetectOptions};
+use crate::error::Error;
+use crate::parser::pumpfun::ParsedTransaction;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyString};
+use serde::Serialize;
+
+// `import rusty` (`--features python`, built with maturin). Trades and summaries cross as the
+// dicts and lists `json.loads` gives for their JSON form, and a failure raises ValueError.
+
+fn raised(error: Error) -> PyErr {
+    PyValueError::new_err(error.to_string())
+}
+
+// a str is taken as JSON already; anything else goes through json.dumps
+fn to_json(value: &Bound<'_, PyAny>) -> PyResult<String> {
+    if let Ok(text) = value.cast::<PyString>() {
+        return Ok(text.to_str()?.to_string());
+    }
+    value.py().import("json")?.call_method1("dumps", (value,))?.extract()
+}
+
+fn to_python<'py>(py: Python<'py>, value: &impl Serialize, context: &str) -> PyResult<Bound<'py, PyAny>> {
+    let json = serde_json::to_string(value).map_err(|e| raised(Error::json(context, e)))?;
+    py.import("json")?.call_method1("loads", (json,))
+}
+
+fn to_trade(trade: &Bound<'_, PyAny>) -> PyResult<ParsedTransaction> {
+    serde_json::from_str(&to_json(trade)?).map_err(|e| raised(Error::json("trade", e)))
+}
+
+// A `getTransaction` response (JSON encoding, as text or a dict) -> its trades on `mint`
+#[pyfunction]
+fn parse_transaction<'py>(py: Python<'py>, response: &Bound<'py, PyAny>, mint: &str) -> PyResult<Bound<'py, PyAny>> {
+    let trades = embed::parse_transaction_json(&to_json(response)?, mint).map_err(raised)?;
+    to_python(py, &trades, "trades")
+}
+
+// A list of trades or a `--save-trades` document -> the detection summary. Keyword arguments
+// are `DetectOptions` keys.
+#[pyfunction]
+#[pyo3(signature = (trades, **options))]
+fn detect_wide_attacks<'py>(
+    py: Python<'py>,
+    trades: &Bound<'py, PyAny>,
+    options: Option<&Bound<'py, PyDict>>,
+) -> PyResult<Bound<'py, PyAny>> {
+    let saved = embed::read_trades(&to_json(trades)?).map_err(raised)?;
+    let options = match options {
+        Some(options) => DetectOptions::from_json(&to_json(options.as_any())?).map_err(raised)?,
+        None => DetectOptions::default(),
+    };
+    let summary = embed::detect_saved(saved, options).map_err(raised)?;
+    to_python(py, &summary, "detection summary")
+}
+
+// The bonding-curve model the simulator trades against. Amounts are lamports and raw token units.
+#[pyclass(name = "PumpCurve", skip_from_py_object)]
+#[derive(Clone)]
+struct PumpCurve {
+    amm: PumpAmmState,
+}
+
+#[pymethods]
+impl PumpCurve {
+    // A fresh curve unless reserves are given. The fee is pump.fun's at `slot` (the latest tier
+    // by default), or flat when either bps is given.
+    #[new]
+    #[pyo3(signature = (
+        virtual_sol = None,
+        virtual_token = None,
+        real_sol = None,
+        real_token = None,
+        slot = None,
+        protocol_fee_bps = None,
+        creator_fee_bps = None,
+    ))]
+    fn new(
+        virtual_sol: Option<u64>,
+        virtual_token: Option<u64>,
+        real_sol: Option<u64>,
+        real_token: Option<u64>,
+        slot: Option<u64>,
+        protocol_fee_bps: Option<u64>,
+        creator_fee_bps: Option<u64>,
+    ) -> Self {
+        let fresh = PumpAmmState::new();
+        let schedule = match (protocol_fee_bps, creator_fee_bps) {
+            (None, None) => FeeSchedule::default(),
+            (protocol, creator) => FeeSchedule::flat(protocol.unwrap_or(0), creator.unwrap_or(0)),
+        };
+        let amm = PumpAmmState::from_reserves(
+            virtual_sol.unwrap_or(fresh.virtual_sol),
+            virtual_token.unwrap_or(fresh.virtual_token),
+            real_sol.unwrap_or(fresh.real_sol),
+            real_token.unwrap_or(fresh.real_token),
+        )
+        .with_fee(slot.map_or_else(|| schedule.current(), |slot| schedule.at(slot)));
+        Self { amm }
+    }
+
+    // the curve a parsed trade executed against, when its leg carries enough to rebuild it
+    #[staticmethod]
+    fn before_trade(trade: &Bound<'_, PyAny>) -> PyResult<Option<Self>> {
+        let leg = to_trade(trade)?;
+        Ok(PumpAmmState::before_trade(&leg, &FeeSchedule::default()).map(|amm| Self { amm }))
+    }
+
+    #[getter]
+    fn virtual_sol(&self) -> u64 {
+        self.amm.virtual_sol
+    }
+
+    #[getter]
+    fn virtual_token(&self) -> u64 {
+        self.amm.virtual_token
+    }
+
+    #[getter]
+    fn real_sol(&self) -> u64 {
+        self.amm.real_sol
+    }
+
+    #[getter]
+    fn real_token(&self) -> u64 {
+        self.amm.real_token
+    }
+
+    #[getter]
+    fn protocol_fees(&self) -> u64 {
+        self.amm.protocol_fees
+    }
+
+    #[getter]
+    fn creator_fees(&self) -> u64 {
+        self.amm.creator_fees
+    }
+
+    // lamports per raw token unit
+    fn price(&self) -> f64 {
+        self.amm.price()
+    }
+
+    fn quote_buy(&self, sol_in: u64) -> u64 {
+        self.amm.quote_buy(sol_in)
+    }
+
+    fn quote_sell(&self, tokens_in: u64) -> u64 {
+        self.amm.quote_sell(tokens_in)
+    }
+
+    fn sol_for_tokens(&self, tokens_out: u64) -> u64 {
+        self.amm.sol_for_tokens(tokens_out)
+    }
+
+    fn buy_price_impact_pct(&self, sol_in: u64) -> f64 {
+        self.amm.buy_price_impact_pct(sol_in)
+    }
+
+    fn sell_price_impact_pct(&self, tokens_in: u64) -> f64 {
+        self.amm.sell_price_impact_pct(tokens_in)
+    }
+
+    // (tokens out, SOL in); a missed limit or overflow gets no tokens and leaves the curve as it was
+    #[pyo3(signature = (sol_in, min_tokens_out = 0))]
+    fn buy(&mut self, sol_in: u64, min_tokens_out: u64) -> (u64, u64) {
+        self.amm.simulate_buy(sol_in, min_tokens_out)
+    }
+
+    // SOL out, 0 when the trade fails
+    #[pyo3(signature = (tokens_in, min_sol_out = 0))]
+    fn sell(&mut self, tokens_in: u64, min_sol_out: u64) -> u64 {
+        self.amm.simulate_sell(tokens_in, min_sol_out)
+    }
+
+    // replays a parsed trade and returns the trader's SOL delta
+    fn apply_trade(&mut self, trade: &Bound<'_, PyAny>) -> PyResult<i64> {
+        Ok(self.amm.apply_trade(&to_trade(trade)?))
+    }
+
+    fn copy(&self) -> Self {
+        self.clone()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "PumpCurve(virtual_sol={}, virtual_token={}, real_sol={}, real_token={}, fee_bps={})",
+            self.amm.virtual_sol,
+            self.amm.virtual_token,
+            self.amm.real_sol,
+            self.amm.real_token,
+            self.amm.fee.total_bps()
+        )
+    }
+}
+
+#[pymodule]
+fn rusty(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_function(wrap_pyfunction!(parse_transaction, module)?)?;
+    module.add_function(wrap_pyfunction!(detect_wide_attacks, module)?)?;
+    module.add_class::<PumpCurve>()?;
+    Ok(())
+}
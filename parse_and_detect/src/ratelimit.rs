@@ -0,0 +1,186 @@
+use crate::error::Error;
+use crate::fetch::TransactionSource;
+use solana_sdk::epoch_schedule::EpochSchedule;
+use solana_sdk::pubkey::Pubkey;
+use solana_transaction_status::{EncodedConfirmedTransactionWithStatusMeta, UiTransactionEncoding};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+// a throttled rate never drops below this share of the configured one
+const MIN_RATE_FRACTION: f64 = 0.1;
+// consecutive successful calls before a throttled rate is stepped back up
+const RECOVERY_CALLS: u32 = 20;
+const RECOVERY_FACTOR: f64 = 1.25;
+const MAX_RETRIES: u32 = 3;
+
+// Token bucket shared by every RPC call; the rate halves on each 429 and recovers gradually
+pub struct RateLimiter {
+    target_rate: f64,
+    burst: f64,
+    bucket: Mutex<Bucket>,
+}
+
+struct Bucket {
+    rate: f64,
+    tokens: f64,
+    refilled_at: Instant,
+    successes: u32,
+}
+
+impl RateLimiter {
+    pub fn new(requests_per_second: f64, burst: u32) -> Self {
+        let burst = burst.max(1) as f64;
+        Self {
+            target_rate: requests_per_second,
+            burst,
+            bucket: Mutex::new(Bucket {
+                rate: requests_per_second,
+                tokens: burst,
+                refilled_at: Instant::now(),
+                successes: 0,
+            }),
+        }
+    }
+
+    pub fn rate(&self) -> f64 {
+        self.lock().rate
+    }
+
+    // blocks until a request may be sent
+    pub fn acquire(&self) {
+        loop {
+            let wait = {
+                let mut bucket = self.lock();
+                let now = Instant::now();
+                let elapsed = now.duration_since(bucket.refilled_at).as_secs_f64();
+                bucket.tokens = (bucket.tokens + elapsed * bucket.rate).min(self.burst);
+                bucket.refilled_at = now;
+                if bucket.tokens >= 1.0 {
+                    bucket.tokens -= 1.0;
+                    return;
+                }
+                (1.0 - bucket.tokens) / bucket.rate
+            };
+            thread::sleep(Duration::from_secs_f64(wait));
+        }
+    }
+
+    pub fn on_rate_limited(&self) {
+        let mut bucket = self.lock();
+        bucket.rate = (bucket.rate / 2.0).max(self.target_rate * MIN_RATE_FRACTION);
+        bucket.tokens = 0.0;
+        bucket.successes = 0;
+    }
+
+    pub fn on_success(&self) {
+        let mut bucket = self.lock();
+        if bucket.rate >= self.target_rate {
+            return;
+        }
+        bucket.successes += 1;
+        if bucket.successes >= RECOVERY_CALLS {
+            bucket.rate = (bucket.rate * RECOVERY_FACTOR).min(self.target_rate);
+            bucket.successes = 0;
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Bucket> {
+        self.bucket.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+pub fn is_rate_limited(error: &Error) -> bool {
+    match error {
+        Error::Rpc(message) => message.contains("429") || message.to_lowercase().contains("too many requests"),
+        _ => false,
+    }
+}
+
+pub struct RateLimitedSource<S> {
+    inner: S,
+    limiter: RateLimiter,
+}
+
+impl<S: TransactionSource> RateLimitedSource<S> {
+    pub fn new(inner: S, limiter: RateLimiter) -> Self {
+        Self { inner, limiter }
+    }
+
+    pub fn limiter(&self) -> &RateLimiter {
+        &self.limiter
+    }
+
+    // 429s are retried at the reduced rate; any other error is returned as is
+    fn call<T>(&self, request: impl Fn(&S) -> Result<T, Error>) -> Result<T, Error> {
+        let mut attempt = 0;
+        loop {
+            self.limiter.acquire();
+            match request(&self.inner) {
+                Ok(value) => {
+                    self.limiter.on_success();
+                    return Ok(value);
+                }
+                Err(e) if is_rate_limited(&e) && attempt < MAX_RETRIES => {
+                    self.limiter.on_rate_limited();
+                    attempt += 1;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+impl<S: TransactionSource> TransactionSource for RateLimitedSource<S> {
+    fn signatures_page(
+        &self,
+        address: &Pubkey,
+        before: Option<&str>,
+        until: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<String>, Error> {
+        self.call(|inner| inner.signatures_page(address, before, until, limit))
+    }
+
+    fn transaction(
+        &self,
+        signature: &str,
+        encoding: UiTransactionEncoding,
+    ) -> Result<EncodedConfirmedTransactionWithStatusMeta, Error> {
+        self.call(|inner| inner.transaction(signature, encoding))
+    }
+
+    // a batch is one HTTP request and takes one token
+    fn transactions(
+        &self,
+        signatures: &[String],
+        encoding: UiTransactionEncoding,
+    ) -> Result<Vec<Result<EncodedConfirmedTransactionWithStatusMeta, Error>>, Error> {
+        self.call(|inner| inner.transactions(signatures, encoding))
+    }
+
+    fn account_data(&self, address: &Pubkey) -> Result<Vec<u8>, Error> {
+        self.call(|inner| inner.account_data(address))
+    }
+
+    fn block_signatures(&self, slot: u64) -> Result<Vec<String>, Error> {
+        self.call(|inner| inner.block_signatures(slot))
+    }
+
+    fn block_compute_unit_prices(&self, slot: u64) -> Result<Vec<u64>, Error> {
+        self.call(|inner| inner.block_compute_unit_prices(slot))
+    }
+
+    fn slot_leaders(&self, start_slot: u64, limit: u64) -> Result<Vec<String>, Error> {
+        self.call(|inner| inner.slot_leaders(start_slot, limit))
+    }
+
+    fn epoch_schedule(&self) -> Result<EpochSchedule, Error> {
+        self.call(|inner| inner.epoch_schedule())
+    }
+
+    fn leader_schedule(&self, slot: u64) -> Result<Option<HashMap<String, Vec<usize>>>, Error> {
+        self.call(|inner| inner.leader_schedule(slot))
+    }
+}
@@ -0,0 +1,74 @@
+use crate::error::Error;
+use crate::leaderboard::AttackerStats;
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AttackerRecord {
+    pub total_profit_lamports: i64,
+    pub attacks: usize,
+    pub victims: usize,
+    pub mints: BTreeSet<String>,
+    pub runs: usize,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AttackerRegistry {
+    pub attackers: BTreeMap<String, AttackerRecord>,
+}
+
+impl AttackerRegistry {
+    pub fn load(path: &str) -> Result<Self, Error> {
+        if !Path::new(path).exists() {
+            return Ok(Self::default());
+        }
+        let raw = fs::read_to_string(path).map_err(|e| Error::io("read attacker registry", path, e))?;
+        serde_json::from_str(&raw).map_err(|e| Error::json(format!("attacker registry '{}'", path), e))
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), Error> {
+        let json = serde_json::to_string_pretty(self).map_err(|e| Error::json("attacker registry", e))?;
+        fs::write(path, json).map_err(|e| Error::io("write attacker registry", path, e))
+    }
+
+    pub fn known_signers(&self) -> HashSet<String> {
+        self.attackers.keys().cloned().collect()
+    }
+
+    pub fn record(&mut self, leaderboard: &[AttackerStats]) {
+        for stats in leaderboard {
+            let record = self.attackers.entry(stats.attacker.clone()).or_default();
+            record.total_profit_lamports += stats.total_profit_lamports;
+            record.attacks += stats.attacks;
+            record.victims += stats.victims;
+            record.mints.extend(stats.mints.iter().cloned());
+            record.runs += 1;
+        }
+    }
+}
+
+// one base58 pubkey per line; blank lines and `#` comments are skipped
+pub fn load_wallet_list(path: &str) -> Result<HashSet<String>, Error> {
+    let raw = fs::read_to_string(path).map_err(|e| Error::io("read wallet list", path, e))?;
+    let mut wallets = HashSet::new();
+    for (number, line) in raw.lines().enumerate() {
+        let wallet = line.split('#').next().unwrap_or_default().trim();
+        if wallet.is_empty() {
+            continue;
+        }
+        if Pubkey::from_str(wallet).is_err() {
+            return Err(Error::Config(format!(
+                "Invalid wallet '{}' on line {} of '{}'",
+                wallet,
+                number + 1,
+                path
+            )));
+        }
+        wallets.insert(wallet.to_string());
+    }
+    Ok(wallets)
+}
@@ -0,0 +1,1000 @@
+use rusty::amm::LAMPORTS_PER_SOL;
+use rusty::detect::{DetectionSummary, DetectorConfig, LamportsExt, VictimMode};
+use rusty::diff::SummaryDiff;
+use rusty::error::Error;
+use rusty::failover::EndpointHealth;
+use rusty::parser;
+use rusty::price::{format_usd, format_utc};
+use rusty::parser::pumpfun::{ExecutionOutcome, LifecycleEvent, LifecycleKind, ParsedTransaction, TradeType, Venue};
+use rusty::rugpull::InsiderRole;
+use rusty::streaming::DetectionEvent;
+use rusty::sweep::SweepPoint;
+use rusty::tips;
+use rusty::token::{self, MintMetadata};
+use rusty::trends::{Period, PeriodStats};
+use std::collections::{BTreeMap, BTreeSet};
+
+// All user-facing output. Results go to stdout; progress and diagnostics go to stderr
+// and are dropped with --quiet, so stdout stays clean for piping.
+
+pub fn status(quiet: bool, message: &str) {
+    if !quiet {
+        eprintln!("{}", message);
+    }
+}
+
+// what each transaction wanted vs. what it executed
+pub fn print_leg_report(leg: &ParsedTransaction, decimals: u8) {
+    let trade_sol_change = leg.trade_sol_change();
+    let token_change = leg.token_change;
+
+    println!("----------");
+    println!("signature: {} (leg {})", leg.signature, leg.leg_index);
+    println!(
+        "block: slot {} | time {} | index {}",
+        leg.slot,
+        leg.block_time.map(format_utc).unwrap_or_else(|| "unknown".to_string()),
+        leg.tx_index.map(|index| index.to_string()).unwrap_or_else(|| "unknown".to_string())
+    );
+    println!("signer: {}", leg.signer);
+    if let Some(fee_payer) = &leg.fee_payer {
+        println!("fee payer: {}", fee_payer);
+    }
+    println!("mint: {}", leg.mint);
+    if leg.venue != Venue::PumpFun {
+        println!("venue: {}", leg.venue.name());
+    }
+    println!(
+        "wanted: {:?} {} tokens (SOL limit {})",
+        leg.trade_type,
+        token::format_tokens(leg.token_amount_requested as i64, decimals),
+        leg.sol_limit_specified
+    );
+    println!("executed: ΔSOL {} | Δtoken {}", leg.sol_change, token::format_tokens(token_change, decimals));
+    println!(
+        "costs: fee {} (priority {}) | ATA rent {} | tip {}",
+        leg.fee, leg.priority_fee, leg.ata_rent, leg.tip
+    );
+    if let Some(curve) = &leg.curve {
+        println!(
+            "curve: ΔSOL {} | Δtoken {}{}",
+            curve.sol_change,
+            token::format_tokens(curve.token_change, decimals),
+            if leg.matches_curve() == Some(false) { " (does not match the signer's fill)" } else { "" }
+        );
+    }
+    if leg.outcome == ExecutionOutcome::Reverted {
+        println!("REVERTED: transaction failed on-chain");
+        println!("----------");
+        return;
+    }
+
+    match leg.trade_type {
+        TradeType::Buy => {
+            let actual_sol_spent = if trade_sol_change < 0 { -trade_sol_change } else { 0 };
+            let tokens_received = if token_change > 0 { token_change } else { 0 };
+
+            println!("BUY IMPACT:");
+            if actual_sol_spent > leg.sol_limit_specified as i64 {
+                let overpaid = actual_sol_spent - leg.sol_limit_specified as i64;
+                println!("  Overpaid by {} lamports ({:.6} SOL) - limit breached!",
+                        overpaid, overpaid as f64 / 1_000_000_000.0);
+            } else {
+                println!("  SOL spend within limit");
+            }
+            if tokens_received < leg.token_amount_requested as i64 {
+                let shortage = leg.token_amount_requested as i64 - tokens_received;
+                println!("  Got {} fewer tokens than requested!",
+                        token::format_tokens(shortage, decimals));
+            } else {
+                println!("  Received requested token amount");
+            }
+        }
+        TradeType::Sell => {
+            let actual_sol_received = if trade_sol_change > 0 { trade_sol_change } else { 0 };
+            let tokens_sold = if token_change < 0 { -token_change } else { 0 };
+
+            println!("SELL IMPACT:");
+            if actual_sol_received < leg.sol_limit_specified as i64 {
+                let underpaid = leg.sol_limit_specified as i64 - actual_sol_received;
+                println!("  Received {} fewer lamports than expected ({:.6} SOL shortfall)!",
+                        underpaid, underpaid as f64 / 1_000_000_000.0);
+            } else {
+                println!("  SOL received meets expectation");
+            }
+            if tokens_sold > leg.token_amount_requested as i64 {
+                let oversold = tokens_sold - leg.token_amount_requested as i64;
+                println!("  Sold {} more tokens than planned!",
+                        token::format_tokens(oversold, decimals));
+            } else {
+                println!("  Sold planned token amount");
+            }
+        }
+    }
+    println!("----------");
+}
+
+// the thresholds after the config file and flags were applied, in the units they were given in
+pub fn print_detector_config(quiet: bool, cfg: &DetectorConfig) {
+    let window = if cfg.adaptive_slot_gap {
+        format!(
+            "adaptive ({}x the median slot distance, {}-{} slots)",
+            cfg.adaptive_gap_factor, cfg.min_adaptive_slot_gap, cfg.max_adaptive_slot_gap
+        )
+    } else {
+        format!("{} slots", cfg.max_slot_gap)
+    };
+    status(
+        quiet,
+        &format!(
+            "Detector: window {}{} | frontruns {} side, backruns {} side | victims from {} SOL or {} tokens ({} decimals) | min net profit {} SOL",
+            window,
+            if cfg.strict_ordering { ", strict ordering" } else { "" },
+            cfg.frontrun_direction.name(),
+            cfg.backrun_direction.name(),
+            cfg.min_victim_abs_sol,
+            cfg.min_victim_tokens,
+            cfg.token_decimals,
+            cfg.min_profit_sol
+        ),
+    );
+    status(
+        quiet,
+        &format!(
+            "Bots after {} trades ({} if known) | backrun arbs on victims from {} SOL | collusion after {} shared sandwiches",
+            cfg.min_bot_trades, cfg.known_attacker_min_trades, cfg.backrun_min_victim_sol, cfg.collusion_min_sandwiches
+        ),
+    );
+    let weights = &cfg.weights;
+    status(
+        quiet,
+        &format!(
+            "Scores: victim {}x breach (margin {}%) + {}x size >= {} | legs {}x direction + {}x proximity >= {}{}",
+            weights.limit_breach,
+            weights.breach_margin_pct,
+            weights.victim_size,
+            weights.min_victim_score,
+            weights.direction,
+            weights.proximity,
+            weights.min_leg_score,
+            match cfg.victim_mode {
+                VictimMode::Limits => String::new(),
+                VictimMode::PriceImpact => format!(
+                    " | victims by price impact, breach ignored, from a {}% loss",
+                    cfg.min_impact_loss_pct
+                ),
+            }
+        ),
+    );
+}
+
+// per-transaction failures don't abort the run; report how many of each kind were skipped
+pub fn print_error_summary(failures: &[Error]) {
+    if failures.is_empty() {
+        return;
+    }
+    let mut by_kind: BTreeMap<&str, usize> = BTreeMap::new();
+    for failure in failures {
+        *by_kind.entry(failure.kind()).or_default() += 1;
+    }
+    eprintln!("\n-- Error Summary --");
+    for (kind, count) in by_kind {
+        eprintln!("{}: {}", kind, count);
+    }
+}
+
+pub fn print_text_report(
+    trade_count: usize,
+    lifecycle_events: &[LifecycleEvent],
+    summary: &DetectionSummary,
+) {
+    let decimals = summary.decimals();
+    println!("---- Detection Summary ----");
+    if let Some(metadata) = &summary.token_metadata {
+        println!("Token: {}", describe_token(metadata));
+    }
+    println!("Total trades parsed: {}", trade_count);
+    println!("Wide front-run candidates: {}", summary.front_runs.len());
+    println!("Wide back-run candidates: {}", summary.back_runs.len());
+    println!("Wide sandwich candidates: {}", summary.sandwiches.len());
+    println!("Backrun arbitrages: {}", summary.backrun_arbs.len());
+    println!("Reverted-victim sandwiches: {}", summary.reverted_sandwiches.len());
+    println!("Insider dumps: {}", summary.dev_dumps.len());
+    println!("Launch snipers: {}", summary.snipers.len());
+    if let Some(usd) = &summary.usd {
+        println!("Value extracted: {}", format_usd(usd.total_extracted_usd));
+        println!("Victim losses: {}", format_usd(usd.total_victim_loss_usd));
+    }
+
+    if !lifecycle_events.is_empty() {
+        println!("\n-- Lifecycle Events --");
+        for event in lifecycle_events {
+            match &event.kind {
+                LifecycleKind::Create {
+                    creator,
+                    name,
+                    symbol,
+                    ..
+                } => println!(
+                    "CREATE slot {} | {} ({}) | creator {} | tx {}",
+                    event.slot,
+                    name,
+                    symbol,
+                    short_sig(creator),
+                    short_sig(&event.signature)
+                ),
+                LifecycleKind::Migrate => println!(
+                    "MIGRATE slot {} | by {} | tx {}",
+                    event.slot,
+                    short_sig(&event.signer),
+                    short_sig(&event.signature)
+                ),
+            }
+        }
+    }
+
+    if !summary.front_runs.is_empty() {
+        println!("\n-- Front-run Events --");
+        for (idx, event) in summary.front_runs.iter().enumerate() {
+            println!(
+                "#{:02} Victim {} | slot {} | {} | ΔSOL {:+.4} SOL{} | Δtoken {} | Wanted: {} tokens (SOL limit {})",
+                idx + 1,
+                short_sig(&event.victim.signature),
+                at_slot(&event.victim),
+                trade_badge(event.victim.trade_type),
+                event.victim.sol_change.as_sol(),
+                usd_note(summary, &event.victim.signature, event.victim.sol_change),
+                token::format_tokens(event.victim.token_change, decimals),
+                token::format_tokens(event.victim.token_amount_requested as i64, decimals),
+                event.victim.sol_limit_specified
+            );
+            println!("Impact:{}", format_attack_impact(&event.victim, price_of(summary, &event.victim.signature), decimals));
+            for (leg_idx, fr) in event.frontruns.iter().enumerate() {
+                println!(
+                    "FR{:02} [{}] slot {} signer {} | ΔSOL {:+.4} SOL{} | Δtoken {}",
+                    leg_idx + 1,
+                    trade_badge(fr.trade_type),
+                    at_slot(fr),
+                    short_sig(&fr.signer),
+                    fr.sol_change.as_sol(),
+                    usd_note(summary, &fr.signature, fr.sol_change),
+                    token::format_tokens(fr.token_change, decimals)
+                );
+            }
+        }
+    }
+
+    if !summary.back_runs.is_empty() {
+        println!("\n-- Back-run Events --");
+        for (idx, event) in summary.back_runs.iter().enumerate() {
+            println!(
+                "#{:02} Victim {} | slot {} | {} | ΔSOL {:+.4} SOL{} | Δtoken {} | Wanted: {} tokens (SOL limit {})",
+                idx + 1,
+                short_sig(&event.victim.signature),
+                at_slot(&event.victim),
+                trade_badge(event.victim.trade_type),
+                event.victim.sol_change.as_sol(),
+                usd_note(summary, &event.victim.signature, event.victim.sol_change),
+                token::format_tokens(event.victim.token_change, decimals),
+                token::format_tokens(event.victim.token_amount_requested as i64, decimals),
+                event.victim.sol_limit_specified
+            );
+            println!("Impact:{}", format_attack_impact(&event.victim, price_of(summary, &event.victim.signature), decimals));
+            for (leg_idx, br) in event.backruns.iter().enumerate() {
+                println!(
+                    "BR{:02} [{}] slot {} signer {} | ΔSOL {:+.4} SOL{} | Δtoken {}",
+                    leg_idx + 1,
+                    trade_badge(br.trade_type),
+                    at_slot(br),
+                    short_sig(&br.signer),
+                    br.sol_change.as_sol(),
+                    usd_note(summary, &br.signature, br.sol_change),
+                    token::format_tokens(br.token_change, decimals)
+                );
+            }
+        }
+    }
+
+    if !summary.sandwiches.is_empty() {
+        println!("\n-- Sandwich Events --");
+        for (idx, det) in summary.sandwiches.iter().enumerate() {
+            println!(
+                "#{} Victim {} @ slot {} ({}) | {} | ΔSOL {:+.4} SOL{} | Δtoken {} | Wanted: {} tokens (SOL limit {})",
+                idx + 1,
+                short_sig(&det.victim.signature),
+                at_slot(&det.victim),
+                curve_phase(&det.victim, lifecycle_events),
+                trade_badge(det.victim.trade_type),
+                det.victim.sol_change.as_sol(),
+                usd_note(summary, &det.victim.signature, det.victim.sol_change),
+                token::format_tokens(det.victim.token_change, decimals),
+                token::format_tokens(det.victim.token_amount_requested as i64, decimals),
+                det.victim.sol_limit_specified
+            );
+            println!("Impact:{}", format_attack_impact(&det.victim, price_of(summary, &det.victim.signature), decimals));
+            println!("Frontruns: {}", det.frontruns.len());
+            println!("Backruns: {}", det.backruns.len());
+            let profit_usd = summary.usd.as_ref().and_then(|usd| usd.sandwich_profit(det));
+            println!(
+                "Profit (SOL): {:.6}{}, net tokens {}",
+                det.net_profit_sol.abs_as_sol(),
+                profit_usd.map(|usd| format!(" ({})", format_usd(usd))).unwrap_or_default(),
+                token::format_tokens(det.net_token_delta, decimals)
+            );
+            println!(
+                "Gross {:.6} SOL | fees {:.6} (priority {:.6}) | ATA rent {:.6} | tips {:.6} | before tips {:.6}",
+                det.gross_profit_sol.as_sol(),
+                (det.costs.fees as i64).as_sol(),
+                (det.costs.priority_fees as i64).as_sol(),
+                (det.costs.ata_rent as i64).as_sol(),
+                (det.costs.tips as i64).as_sol(),
+                tips::profit_before_tips(det).as_sol()
+            );
+            if det.cross_venue {
+                let venues: BTreeSet<&str> = std::iter::once(&det.victim)
+                    .chain(&det.frontruns)
+                    .chain(&det.backruns)
+                    .map(|leg| leg.venue.name())
+                    .collect();
+                println!("Cross-venue: {}", venues.into_iter().collect::<Vec<_>>().join(" / "));
+            }
+            if let Some(atomic) = det.atomic {
+                println!("Atomic: {}", atomic.name());
+            }
+            if let Some(leader) = &det.leader {
+                println!("Block leader: {}", leader);
+            }
+            if let Some(ranking) = &det.cu_price {
+                let side = |price: Option<u64>, percentile: Option<f64>| match (price, percentile) {
+                    (Some(price), Some(percentile)) => format!("{} (p{:.0})", price, percentile),
+                    _ => "-".to_string(),
+                };
+                println!(
+                    "CU price (µlamports/CU): victim {} (p{:.0}) | frontrun {} | backrun {} | of {} {}",
+                    ranking.victim_cu_price,
+                    ranking.victim_percentile,
+                    side(ranking.frontrun_cu_price, ranking.frontrun_percentile),
+                    side(ranking.backrun_cu_price, ranking.backrun_percentile),
+                    ranking.ranked_transactions,
+                    if ranking.full_block { "block transactions" } else { "scanned trades in the slot" }
+                );
+            }
+            if !det.route_token_deltas.is_empty() {
+                let hops: Vec<String> = det
+                    .route_token_deltas
+                    .iter()
+                    .map(|(mint, change)| format!("{} (net {:+})", short_sig(mint), change))
+                    .collect();
+                println!("Routed via: {}", hops.join(", "));
+            }
+            if let Some(pool) = &det.victim_reserves {
+                println!(
+                    "Pool before victim: virtual {:.4} SOL / {} tokens | real {:.4} SOL / {} tokens | price {:.9} SOL/token ({:?})",
+                    (pool.virtual_sol as i64).as_sol(),
+                    pool.virtual_token / token::unit(decimals),
+                    (pool.real_sol as i64).as_sol(),
+                    pool.real_token / token::unit(decimals),
+                    pool.price() * token::unit(decimals) as f64 / LAMPORTS_PER_SOL as f64,
+                    pool.source
+                );
+            }
+            let external = det
+                .external_score
+                .map(|score| format!(" | external score {:.3}", score))
+                .unwrap_or_default();
+            if det.known_attackers.is_empty() {
+                println!("Severity: {} | confidence: {:.2}{}", det.severity.name(), det.confidence, external);
+            } else {
+                let known: Vec<String> = det.known_attackers.iter().map(|signer| short_sig(signer)).collect();
+                println!(
+                    "Severity: {} | confidence: {:.2}{} | known attackers: {}",
+                    det.severity.name(),
+                    det.confidence,
+                    external,
+                    known.join(", ")
+                );
+            }
+            println!(
+                "Latency: front -{} slots{} | back +{} slots{}{}",
+                det.latency.front_slots,
+                secs_note(det.latency.front_secs),
+                det.latency.back_slots,
+                secs_note(det.latency.back_secs),
+                if det.latency.same_block() { " | same block" } else { "" }
+            );
+            for (leg_idx, fr) in det.frontruns.iter().enumerate() {
+                println!(
+                    "FR{:02} [{}] slot {} signer {} | ΔSOL {:+.4} SOL{} | Δtoken {}",
+                    leg_idx + 1,
+                    trade_badge(fr.trade_type),
+                    at_slot(fr),
+                    short_sig(&fr.signer),
+                    fr.sol_change.as_sol(),
+                    usd_note(summary, &fr.signature, fr.sol_change),
+                    token::format_tokens(fr.token_change, decimals)
+                );
+            }
+            for (leg_idx, br) in det.backruns.iter().enumerate() {
+                println!(
+                    "BR{:02} [{}] slot {} signer {} | ΔSOL {:+.4} SOL{} | Δtoken {}",
+                    leg_idx + 1,
+                    trade_badge(br.trade_type),
+                    at_slot(br),
+                    short_sig(&br.signer),
+                    br.sol_change.as_sol(),
+                    usd_note(summary, &br.signature, br.sol_change),
+                    token::format_tokens(br.token_change, decimals)
+                );
+            }
+            println!();
+        }
+    }
+
+    if !summary.backrun_arbs.is_empty() {
+        println!("\n-- Backrun Arbitrage Events --");
+        for (idx, event) in summary.backrun_arbs.iter().enumerate() {
+            println!(
+                "#{:02} Victim {} | slot {} | {} | ΔSOL {:+.4} SOL{} | Δtoken {}",
+                idx + 1,
+                short_sig(&event.victim.signature),
+                at_slot(&event.victim),
+                trade_badge(event.victim.trade_type),
+                event.victim.sol_change.as_sol(),
+                usd_note(summary, &event.victim.signature, event.victim.sol_change),
+                token::format_tokens(event.victim.token_change, decimals)
+            );
+            println!(
+                "Arb profit (SOL): {:.6}{} | gross {:.6} | costs {:.6}",
+                event.net_profit_sol.as_sol(),
+                usd_note(summary, &event.close.signature, event.net_profit_sol),
+                event.gross_profit_sol.as_sol(),
+                (event.costs.total() as i64).as_sol()
+            );
+            for (label, leg) in [("BR", &event.backrun), ("CL", &event.close)] {
+                println!(
+                    "{} [{}] slot {} signer {} | ΔSOL {:+.4} SOL{} | Δtoken {}",
+                    label,
+                    trade_badge(leg.trade_type),
+                    at_slot(leg),
+                    short_sig(&leg.signer),
+                    leg.sol_change.as_sol(),
+                    usd_note(summary, &leg.signature, leg.sol_change),
+                    token::format_tokens(leg.token_change, decimals)
+                );
+            }
+        }
+    }
+
+    if let Some(latency) = &summary.latency {
+        println!("\n-- Sandwich Latency --");
+        println!(
+            "Same block: {} of {} | median span {} slots{} | max span {} slots",
+            latency.same_block,
+            latency.sandwiches,
+            latency.median_span_slots,
+            secs_note(latency.median_span_secs),
+            latency.max_span_slots
+        );
+        for (span, count) in &latency.span_slots {
+            println!("{:>3} slots: {}", span, count);
+        }
+    }
+
+    if let Some(tips) = &summary.tips {
+        println!("\n-- Jito Tips --");
+        println!(
+            "Tipped: {} of {} sandwiches | tips {:.6} SOL = {:.2}% of {:.6} SOL gross revenue",
+            tips.tipped_sandwiches,
+            tips.sandwiches,
+            (tips.total_tips_lamports as i64).as_sol(),
+            tips.tip_share_pct,
+            tips.gross_revenue_lamports.as_sol()
+        );
+        println!(
+            "Profit before tips {:.6} SOL | after tips {:.6} SOL",
+            tips.profit_before_tips_lamports.as_sol(),
+            tips.profit_after_tips_lamports.as_sol()
+        );
+        if tips.tipped_sandwiches > 0 {
+            println!(
+                "Per tipped sandwich: median tip {:.6} SOL ({:.2}% of its revenue) | max {:.6} SOL",
+                (tips.median_tip_lamports as i64).as_sol(),
+                tips.median_tip_share_pct,
+                (tips.max_tip_lamports as i64).as_sol()
+            );
+        }
+    }
+
+    if !summary.leaders.is_empty() {
+        println!("\n-- Block Leaders --");
+        for stats in &summary.leaders {
+            println!(
+                "{} | {} sandwiches | victim loss {:.6} SOL | extracted {:.6} SOL",
+                stats.leader,
+                stats.sandwiches,
+                (stats.victim_loss_lamports as i64).as_sol(),
+                stats.extracted_lamports.as_sol()
+            );
+        }
+    }
+
+    if !summary.leader_targeting.is_empty() {
+        println!("\n-- Leader Targeting --");
+        for targeting in &summary.leader_targeting {
+            println!(
+                "{} | {} of {} sandwiches on {} ({:.1}%) | scheduled {:.2}% of slots | lift {:.1}x",
+                targeting.attacker,
+                targeting.leader_sandwiches,
+                targeting.sandwiches,
+                short_sig(&targeting.leader),
+                targeting.share_pct,
+                targeting.scheduled_share_pct,
+                targeting.lift
+            );
+        }
+    }
+
+    if !summary.reverted_sandwiches.is_empty() {
+        println!("\n-- Reverted Victim Events --");
+        for (idx, event) in summary.reverted_sandwiches.iter().enumerate() {
+            println!(
+                "#{:02} Victim {} | slot {} | {} REVERTED | Wanted: {} tokens (SOL limit {}) | fee lost {:.6} SOL{}",
+                idx + 1,
+                short_sig(&event.victim.signature),
+                at_slot(&event.victim),
+                trade_badge(event.victim.trade_type),
+                token::format_tokens(event.victim.token_amount_requested as i64, decimals),
+                event.victim.sol_limit_specified,
+                (event.victim.fee as i64).as_sol(),
+                usd_note(summary, &event.victim.signature, event.victim.fee as i64)
+            );
+            for (leg_idx, fr) in event.frontruns.iter().enumerate() {
+                println!(
+                    "FR{:02} [{}] slot {} signer {} | ΔSOL {:+.4} SOL{} | Δtoken {}",
+                    leg_idx + 1,
+                    trade_badge(fr.trade_type),
+                    at_slot(fr),
+                    short_sig(&fr.signer),
+                    fr.sol_change.as_sol(),
+                    usd_note(summary, &fr.signature, fr.sol_change),
+                    token::format_tokens(fr.token_change, decimals)
+                );
+            }
+            for (leg_idx, br) in event.backruns.iter().enumerate() {
+                println!(
+                    "BR{:02} [{}] slot {} signer {} | ΔSOL {:+.4} SOL{} | Δtoken {}",
+                    leg_idx + 1,
+                    trade_badge(br.trade_type),
+                    at_slot(br),
+                    short_sig(&br.signer),
+                    br.sol_change.as_sol(),
+                    usd_note(summary, &br.signature, br.sol_change),
+                    token::format_tokens(br.token_change, decimals)
+                );
+            }
+        }
+    }
+
+
+    if !summary.dev_dumps.is_empty() {
+        println!("\n-- Insider Dump Events --");
+        for (idx, dump) in summary.dev_dumps.iter().enumerate() {
+            let role = match dump.role {
+                InsiderRole::Creator => "CREATOR",
+                InsiderRole::EarlyHolder => "EARLY HOLDER",
+            };
+            println!(
+                "#{:02} {} {} | slots {}-{} | {} sells | {:.2}% of supply | extracted {:.6} SOL | price impact {:+.2}%",
+                idx + 1,
+                role,
+                short_sig(&dump.seller),
+                dump.start_slot,
+                dump.end_slot,
+                dump.sells.len(),
+                dump.supply_pct,
+                dump.sol_extracted.as_sol(),
+                dump.price_impact_pct
+            );
+        }
+    }
+
+    if !summary.snipers.is_empty() {
+        println!("\n-- Launch Snipers --");
+        for (idx, sniper) in summary.snipers.iter().enumerate() {
+            println!(
+                "#{:02} {} | entry slot {} (+{}) | spent {:.6} SOL for {} tokens | entry price {:.9} SOL/token | received {:.6} SOL | profit {:+.6} SOL | holding {}",
+                idx + 1,
+                short_sig(&sniper.sniper),
+                sniper.entry_slot,
+                sniper.entry_slot - sniper.create_slot,
+                (sniper.sol_spent as i64).as_sol(),
+                token::format_tokens(sniper.tokens_bought as i64, decimals),
+                sniper.entry_price * token::unit(decimals) as f64 / LAMPORTS_PER_SOL as f64,
+                (sniper.sol_received as i64).as_sol(),
+                sniper.exit_profit_lamports.as_sol(),
+                token::format_tokens(sniper.tokens_remaining, decimals)
+            );
+        }
+    }
+
+    if !summary.custom.is_empty() {
+        println!("\n-- Custom Detections --");
+        for (idx, detection) in summary.custom.iter().enumerate() {
+            let signatures: Vec<String> = detection.transactions.iter().map(|tx| short_sig(&tx.signature)).collect();
+            println!(
+                "#{:02} [{}] {} | txs {}",
+                idx + 1,
+                detection.detector,
+                detection.description,
+                signatures.join(", ")
+            );
+        }
+    }
+
+    if !summary.attacker_leaderboard.is_empty() {
+        println!("\n-- Attacker Leaderboard --");
+        for (rank, attacker) in summary.attacker_leaderboard.iter().enumerate() {
+            println!(
+                "#{:02} {}{} | profit {:.6} SOL{} | attacks {} | victims {} | mints {} | avg {:.6} SOL/attack",
+                rank + 1,
+                short_sig(&attacker.attacker),
+                attacker.tag(),
+                attacker.total_profit_lamports.as_sol(),
+                summary
+                    .usd
+                    .as_ref()
+                    .and_then(|usd| usd.attacker_profit(attacker))
+                    .map(|usd| format!(" ({})", format_usd(usd)))
+                    .unwrap_or_default(),
+                attacker.attacks,
+                attacker.victims,
+                attacker.mints.len(),
+                attacker.avg_profit_lamports.as_sol()
+            );
+        }
+    }
+
+    if !summary.attacker_performance.is_empty() {
+        println!("\n-- Attacker Performance --");
+        println!(
+            "{:<12} {:>9} {:>9} {:>8} {:>14} {:>14} {:>9} {:>8}",
+            "attacker", "attempted", "completed", "win", "capital SOL", "profit SOL", "ROI", "hold"
+        );
+        for stats in &summary.attacker_performance {
+            println!(
+                "{:<12} {:>9} {:>9} {:>7.1}% {:>14.6} {:>14.6} {:>8.1}% {:>8.1}",
+                short_sig(&stats.attacker),
+                stats.attempted,
+                stats.completed,
+                stats.win_rate * 100.0,
+                (stats.capital_deployed_lamports as i64).as_sol(),
+                stats.total_profit_lamports.as_sol(),
+                stats.roi_pct,
+                stats.avg_holding_slots
+            );
+        }
+    }
+
+    if !summary.colluding_pairs.is_empty() {
+        println!("\n-- Colluding Wallets --");
+        for pair in &summary.colluding_pairs {
+            println!(
+                "{} frontruns, {} backruns | {} sandwiches together | profit {:.6} SOL",
+                short_sig(&pair.frontrunner),
+                short_sig(&pair.backrunner),
+                pair.sandwiches,
+                pair.net_profit_lamports.as_sol()
+            );
+        }
+    }
+
+    if !summary.funding.is_empty() {
+        println!("\n-- Funding Sources --");
+        for chain in &summary.funding {
+            if chain.hops.is_empty() {
+                println!("{} | no incoming funding found", chain.wallet);
+                continue;
+            }
+            let hops: Vec<String> = chain
+                .hops
+                .iter()
+                .map(|hop| {
+                    let label = match hop.label.as_deref() {
+                        Some("") => " known funder".to_string(),
+                        Some(label) => format!(" {}", label),
+                        None => String::new(),
+                    };
+                    format!("{} ({:.3} SOL{})", hop.funder, (hop.lamports as i64).as_sol(), label)
+                })
+                .collect();
+            println!("{} <- {}", chain.wallet, hops.join(" <- "));
+        }
+        for shared in &summary.shared_funders {
+            let wallets: Vec<String> = shared.wallets.iter().map(|wallet| short_sig(wallet)).collect();
+            println!("{} funded {} attackers: {}", shared.funder, shared.wallets.len(), wallets.join(", "));
+        }
+    }
+
+    if !summary.copy_traders.is_empty() {
+        println!("\n-- Copy-Trading Wallets --");
+        for pair in &summary.copy_traders {
+            println!(
+                "{} copies {} | {}/{} trades mirrored | size ratio {:.2} | mints {}",
+                short_sig(&pair.follower),
+                short_sig(&pair.leader),
+                pair.matched_trades,
+                pair.follower_trades,
+                pair.mean_size_ratio,
+                pair.mints.len()
+            );
+        }
+    }
+
+    if !summary.mint_stats.is_empty() {
+        println!("\n-- Mint Statistics --");
+        for stats in &summary.mint_stats {
+            println!(
+                "{} | trades {} | victims {} | attack rate {:.2}% | extracted {:.6} SOL | attackers {} | median victim loss {:.6} SOL | window {} slots",
+                short_sig(&stats.mint),
+                stats.total_trades,
+                stats.total_victims,
+                stats.attack_rate_pct,
+                stats.total_sol_extracted.as_sol(),
+                stats.unique_attackers,
+                (stats.median_victim_loss as i64).as_sol(),
+                stats.slot_gap
+            );
+        }
+    }
+}
+
+pub fn short_sig(sig: &str) -> String {
+    if sig.len() <= 8 {
+        sig.to_string()
+    } else {
+        format!("{}…{}", &sig[..4], &sig[sig.len() - 4..])
+    }
+}
+
+fn price_of(summary: &DetectionSummary, signature: &str) -> Option<f64> {
+    summary.usd.as_ref().and_then(|usd| usd.price(signature))
+}
+
+// " ($12.34)" next to a SOL figure when the run was priced at block time
+fn usd_note(summary: &DetectionSummary, signature: &str, lamports: i64) -> String {
+    usd_suffix(price_of(summary, signature), lamports)
+}
+
+// the slot, with the block's UTC time when known
+fn at_slot(tx: &ParsedTransaction) -> String {
+    match tx.block_time {
+        Some(time) => format!("{} ({})", tx.slot, format_utc(time)),
+        None => tx.slot.to_string(),
+    }
+}
+
+fn secs_note(secs: Option<i64>) -> String {
+    secs.map(|secs| format!(" ({}s)", secs)).unwrap_or_default()
+}
+
+fn usd_suffix(sol_usd: Option<f64>, lamports: i64) -> String {
+    match sol_usd {
+        Some(price) => format!(" ({})", format_usd(lamports.as_sol() * price)),
+        None => String::new(),
+    }
+}
+
+fn curve_phase(tx: &parser::pumpfun::ParsedTransaction, lifecycle: &[LifecycleEvent]) -> &'static str {
+    let graduated = lifecycle.iter().any(|event| {
+        event.kind == LifecycleKind::Migrate && *event.mint == *tx.mint && event.slot <= tx.slot
+    });
+    if graduated { "post-graduation" } else { "pre-graduation" }
+}
+
+fn trade_badge(trade: TradeType) -> &'static str {
+    match trade {
+        TradeType::Buy => "BUY",
+        TradeType::Sell => "SELL",
+    }
+}
+
+fn format_attack_impact(tx: &parser::pumpfun::ParsedTransaction, sol_usd: Option<f64>, decimals: u8) -> String {
+    let mut impact = String::new();
+
+    match tx.trade_type {
+        TradeType::Buy => {
+            let trade_sol_change = tx.trade_sol_change();
+            let actual_sol_spent = if trade_sol_change < 0 { -trade_sol_change } else { 0 };
+            let tokens_received = if tx.token_change > 0 { tx.token_change } else { 0 };
+
+            if actual_sol_spent > tx.sol_limit_specified as i64 {
+                let overpaid = actual_sol_spent - tx.sol_limit_specified as i64;
+                impact.push_str(&format!(
+                    "OVERPAID {:.6} SOL{}",
+                    overpaid as f64 / 1_000_000_000.0,
+                    usd_suffix(sol_usd, overpaid)
+                ));
+            }
+            if tokens_received < tx.token_amount_requested as i64 {
+                let shortage = tx.token_amount_requested as i64 - tokens_received;
+                impact.push_str(&format!("GOT {} FEWER TOKENS", token::format_tokens(shortage, decimals)));
+            }
+        }
+        TradeType::Sell => {
+            let trade_sol_change = tx.trade_sol_change();
+            let actual_sol_received = if trade_sol_change > 0 { trade_sol_change } else { 0 };
+            let tokens_sold = if tx.token_change < 0 { -tx.token_change } else { 0 };
+
+            if actual_sol_received < tx.sol_limit_specified as i64 {
+                let underpaid = tx.sol_limit_specified as i64 - actual_sol_received;
+                impact.push_str(&format!(
+                    "RECEIVED {:.6} SOL{} LESS",
+                    underpaid as f64 / 1_000_000_000.0,
+                    usd_suffix(sol_usd, underpaid)
+                ));
+            }
+            if tokens_sold > tx.token_amount_requested as i64 {
+                let oversold = tokens_sold - tx.token_amount_requested as i64;
+                impact.push_str(&format!("SOLD {} MORE TOKENS", token::format_tokens(oversold, decimals)));
+            }
+        }
+    }
+
+    if impact.is_empty() {
+        "FAIR EXECUTION".to_string()
+    } else {
+        impact
+    }
+}
+
+pub fn print_period_report(period: Period, stats: &[PeriodStats]) {
+    println!("---- {:?} Sandwich Report ----", period);
+    if stats.is_empty() {
+        println!("No sandwiches in the events log.");
+        return;
+    }
+    for bucket in stats {
+        let start = format_utc(bucket.period_start);
+        println!(
+            "{} | {} attacks | {:.6} SOL extracted | {} attackers | {} victims",
+            start.split(' ').next().unwrap_or_default(),
+            bucket.attacks,
+            bucket.sol_extracted.as_sol(),
+            bucket.unique_attackers,
+            bucket.unique_victims
+        );
+        for loss in &bucket.top_victim_losses {
+            println!(
+                "    victim {} lost {:.6} SOL | tx {}",
+                short_sig(&loss.victim),
+                (loss.loss_lamports as i64).as_sol(),
+                short_sig(&loss.signature)
+            );
+        }
+    }
+}
+
+pub fn print_summary_diff(left: &str, right: &str, diff: &SummaryDiff) {
+    println!("---- Detection Diff ----");
+    println!("In both: {}", diff.in_both);
+    for (path, only) in [(left, &diff.only_left), (right, &diff.only_right)] {
+        println!("\nOnly in {}: {}", path, only.len());
+        for det in only {
+            println!(
+                "  victim {} slot {} | {} | profit {:.6} SOL | tx {}",
+                short_sig(&det.victim.signer),
+                at_slot(&det.victim),
+                trade_badge(det.victim.trade_type),
+                det.net_profit_sol.as_sol(),
+                det.victim.signature
+            );
+        }
+    }
+    if !diff.profit_changes.is_empty() {
+        println!("\nProfit changed: {}", diff.profit_changes.len());
+        for change in &diff.profit_changes {
+            println!(
+                "  tx {} | {:.6} -> {:.6} SOL",
+                change.victim_signature,
+                change.left_profit_sol.as_sol(),
+                change.right_profit_sol.as_sol()
+            );
+        }
+    }
+}
+
+pub fn print_sweep(points: &[SweepPoint]) {
+    println!("---- Threshold Sweep ----");
+    println!("gap | min profit (SOL) | min victim (SOL) | sandwiches | front-runs | back-runs | shared with base | overlap");
+    for point in points {
+        println!(
+            "{:>3} | {:>16.6} | {:>16.4} | {:>10} | {:>10} | {:>9} | {:>16} | {:.2}",
+            point.max_slot_gap,
+            point.min_profit_sol,
+            point.min_victim_abs_sol,
+            point.sandwiches,
+            point.front_runs,
+            point.back_runs,
+            point.shared_with_base,
+            point.overlap
+        );
+    }
+}
+
+pub fn print_endpoint_health(endpoints: &[EndpointHealth]) {
+    if endpoints.len() < 2 {
+        return;
+    }
+    eprintln!("\n-- RPC Endpoints --");
+    for endpoint in endpoints {
+        eprintln!(
+            "{}: {} ok, {} failed{}",
+            endpoint.name,
+            endpoint.successes,
+            endpoint.failures,
+            if endpoint.available { "" } else { " (cooling down)" }
+        );
+    }
+}
+
+// "$WIF (dogwifhat)", or whichever of the two is set
+fn describe_token(metadata: &MintMetadata) -> String {
+    match metadata.label() {
+        Some(label) if !metadata.symbol.is_empty() && !metadata.name.is_empty() => {
+            format!("{} ({})", label, metadata.name)
+        }
+        Some(label) => label,
+        None => "unnamed".to_string(),
+    }
+}
+
+// the symbol when the mint's metadata resolved, the shortened mint otherwise
+pub fn mint_label(mint: &str, metadata: Option<&MintMetadata>) -> String {
+    metadata.and_then(MintMetadata::label).unwrap_or_else(|| short_sig(mint))
+}
+
+// one line per completed sandwich for the daemon, `mint` as labelled by `mint_label`; front- and
+// back-run candidates only go to the events log
+pub fn print_alert(mint: &str, event: &DetectionEvent) {
+    match event {
+        DetectionEvent::Sandwich(det) => println!(
+            "ALERT sandwich on {} | victim {} slot {} | {} ΔSOL {:+.4} SOL | attacker profit {:.6} SOL | confidence {:.2}{} | tx {}",
+            mint,
+            short_sig(&det.victim.signer),
+            at_slot(&det.victim),
+            trade_badge(det.victim.trade_type),
+            det.victim.sol_change.as_sol(),
+            det.net_profit_sol.as_sol(),
+            det.confidence,
+            if det.known_attackers.is_empty() { "" } else { " (known attacker)" },
+            det.victim.signature
+        ),
+        DetectionEvent::BackrunArb(event) => println!(
+            "ALERT backrun arb on {} | victim {} slot {} | {} ΔSOL {:+.4} SOL | bot {} profit {:.6} SOL | tx {}",
+            mint,
+            short_sig(&event.victim.signer),
+            at_slot(&event.victim),
+            trade_badge(event.victim.trade_type),
+            event.victim.sol_change.as_sol(),
+            short_sig(&event.backrun.signer),
+            event.net_profit_sol.as_sol(),
+            event.backrun.signature
+        ),
+        DetectionEvent::RevertedSandwich(event) => println!(
+            "ALERT reverted victim on {} | victim {} slot {} | fee lost {:.6} SOL | tx {}",
+            mint,
+            short_sig(&event.victim.signer),
+            at_slot(&event.victim),
+            (event.victim.fee as i64).as_sol(),
+            event.victim.signature
+        ),
+        DetectionEvent::Custom(detection) => println!(
+            "ALERT {} on {} | {} | {} transactions",
+            detection.detector,
+            mint,
+            detection.description,
+            detection.transactions.len()
+        ),
+        DetectionEvent::FrontRun(_) | DetectionEvent::BackRun(_) => {}
+    }
+}
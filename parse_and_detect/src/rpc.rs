@@ -0,0 +1,110 @@
+use crate::error::Error;
+use crate::fetch::{TransactionSource, at_least_confirmed};
+use crate::parser::pumpfun::versioned_compute_unit_price;
+use solana_client::rpc_client::{GetConfirmedSignaturesForAddress2Config, RpcClient};
+use solana_client::rpc_config::{RpcBlockConfig, RpcTransactionConfig};
+use solana_sdk::epoch_schedule::EpochSchedule;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_transaction_status::{EncodedConfirmedTransactionWithStatusMeta, TransactionDetails, UiTransactionEncoding};
+use std::collections::HashMap;
+use std::str::FromStr;
+
+// a live node, through the Solana RPC client
+impl TransactionSource for RpcClient {
+    fn signatures_page(
+        &self,
+        address: &Pubkey,
+        before: Option<&str>,
+        until: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<String>, Error> {
+        let config = GetConfirmedSignaturesForAddress2Config {
+            limit: Some(limit),
+            before: before.map(parse_signature).transpose()?,
+            until: until.map(parse_signature).transpose()?,
+            commitment: Some(self.commitment()),
+        };
+        let statuses = self
+            .get_signatures_for_address_with_config(address, config)
+            .map_err(|e| Error::Rpc(format!("Failed to fetch transaction signatures: {}", e)))?;
+        Ok(statuses.into_iter().map(|status| status.signature).collect())
+    }
+
+    fn transaction(
+        &self,
+        signature: &str,
+        encoding: UiTransactionEncoding,
+    ) -> Result<EncodedConfirmedTransactionWithStatusMeta, Error> {
+        let parsed = parse_signature(signature)?;
+        let config = RpcTransactionConfig {
+            encoding: Some(encoding),
+            max_supported_transaction_version: Some(0),
+            commitment: Some(at_least_confirmed(self.commitment())),
+        };
+        self.get_transaction_with_config(&parsed, config)
+            .map_err(|e| Error::Rpc(format!("Failed to fetch transaction {}: {}", signature, e)))
+    }
+
+    fn account_data(&self, address: &Pubkey) -> Result<Vec<u8>, Error> {
+        self.get_account(address)
+            .map(|account| account.data)
+            .map_err(|e| Error::Rpc(format!("Failed to fetch account {}: {}", address, e)))
+    }
+
+    fn block_signatures(&self, slot: u64) -> Result<Vec<String>, Error> {
+        let config = RpcBlockConfig {
+            encoding: None,
+            transaction_details: Some(TransactionDetails::Signatures),
+            rewards: Some(false),
+            commitment: Some(at_least_confirmed(self.commitment())),
+            max_supported_transaction_version: Some(0),
+        };
+        self.get_block_with_config(slot, config)
+            .map(|block| block.signatures.unwrap_or_default())
+            .map_err(|e| Error::Rpc(format!("Failed to fetch block {}: {}", slot, e)))
+    }
+
+    fn block_compute_unit_prices(&self, slot: u64) -> Result<Vec<u64>, Error> {
+        let config = RpcBlockConfig {
+            encoding: Some(UiTransactionEncoding::Base64),
+            transaction_details: Some(TransactionDetails::Full),
+            rewards: Some(false),
+            commitment: Some(at_least_confirmed(self.commitment())),
+            max_supported_transaction_version: Some(0),
+        };
+        let block = self
+            .get_block_with_config(slot, config)
+            .map_err(|e| Error::Rpc(format!("Failed to fetch block {}: {}", slot, e)))?;
+        Ok(block
+            .transactions
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|tx| tx.transaction.decode())
+            .map(|tx| versioned_compute_unit_price(&tx))
+            .collect())
+    }
+
+    fn slot_leaders(&self, start_slot: u64, limit: u64) -> Result<Vec<String>, Error> {
+        self.get_slot_leaders(start_slot, limit)
+            .map(|leaders| leaders.iter().map(|leader| leader.to_string()).collect())
+            .map_err(|e| Error::Rpc(format!("Failed to fetch leaders from slot {}: {}", start_slot, e)))
+    }
+
+    fn epoch_schedule(&self) -> Result<EpochSchedule, Error> {
+        self.get_epoch_schedule()
+            .map_err(|e| Error::Rpc(format!("Failed to fetch epoch schedule: {}", e)))
+    }
+
+    fn leader_schedule(&self, slot: u64) -> Result<Option<HashMap<String, Vec<usize>>>, Error> {
+        self.get_leader_schedule(Some(slot))
+            .map_err(|e| Error::Rpc(format!("Failed to fetch leader schedule for slot {}: {}", slot, e)))
+    }
+}
+
+fn parse_signature(signature: &str) -> Result<Signature, Error> {
+    Signature::from_str(signature).map_err(|e| Error::InvalidSignature {
+        signature: signature.to_string(),
+        reason: e.to_string(),
+    })
+}
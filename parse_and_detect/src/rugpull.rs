@@ -0,0 +1,155 @@
+use crate::detect::{DetectorConfig, execution_price, order_key};
+use crate::parser::pumpfun::{ExecutionOutcome, LifecycleEvent, LifecycleKind, ParsedTransaction, TradeType};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+// pump.fun mints a fixed 1B supply with 6 decimals
+pub const PUMP_TOKEN_SUPPLY: u64 = 1_000_000_000_000_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum InsiderRole {
+    Creator,
+    EarlyHolder,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DevDumpEvent {
+    pub mint: String,
+    pub seller: String,
+    pub role: InsiderRole,
+    pub start_slot: u64,
+    pub end_slot: u64,
+    pub sells: Vec<ParsedTransaction>,
+    pub tokens_sold: u64,
+    pub supply_pct: f64,
+    pub sol_extracted: i64,
+    pub price_before: f64,
+    pub price_after: f64,
+    pub price_impact_pct: f64,
+}
+
+pub fn detect_dev_dumps(
+    trades: &[ParsedTransaction],
+    lifecycle: &[LifecycleEvent],
+    cfg: &DetectorConfig,
+) -> Vec<DevDumpEvent> {
+    let mut dumps = Vec::new();
+
+    for event in lifecycle {
+        let LifecycleKind::Create { creator, .. } = &event.kind else {
+            continue;
+        };
+
+        let mut mint_trades: Vec<&ParsedTransaction> = trades
+            .iter()
+            .filter(|tx| *tx.mint == *event.mint && tx.outcome == ExecutionOutcome::Succeeded)
+            .collect();
+        mint_trades.sort_by(|a, b| order_key(a).cmp(&order_key(b)));
+
+        let early_cutoff = event.slot.saturating_add(cfg.early_holder_slots);
+        let mut insiders: BTreeMap<&str, InsiderRole> = BTreeMap::new();
+        insiders.insert(creator.as_str(), InsiderRole::Creator);
+        let early_buyers: BTreeSet<&str> = mint_trades
+            .iter()
+            .filter(|tx| tx.trade_type == TradeType::Buy && tx.slot <= early_cutoff)
+            .map(|tx| tx.signer.as_ref())
+            .collect();
+        for buyer in early_buyers {
+            insiders.entry(buyer).or_insert(InsiderRole::EarlyHolder);
+        }
+
+        for (insider, role) in insiders {
+            let sells: Vec<&ParsedTransaction> = mint_trades
+                .iter()
+                .copied()
+                .filter(|tx| &*tx.signer == insider && tx.trade_type == TradeType::Sell)
+                .collect();
+            dumps.extend(
+                dump_windows(&sells, cfg)
+                    .into_iter()
+                    .map(|window| build_dump(&mint_trades, window, role)),
+            );
+        }
+    }
+
+    dumps.sort_by(|a, b| {
+        (a.start_slot, &a.mint, &a.seller).cmp(&(b.start_slot, &b.mint, &b.seller))
+    });
+    dumps
+}
+
+fn dump_windows<'a>(
+    sells: &[&'a ParsedTransaction],
+    cfg: &DetectorConfig,
+) -> Vec<Vec<&'a ParsedTransaction>> {
+    let threshold = PUMP_TOKEN_SUPPLY as f64 * cfg.dump_min_supply_pct / 100.0;
+    let mut windows = Vec::new();
+    let mut start = 0;
+
+    while start < sells.len() {
+        let end_slot = sells[start].slot.saturating_add(cfg.dump_window_slots);
+        let window: Vec<&ParsedTransaction> = sells[start..]
+            .iter()
+            .copied()
+            .take_while(|tx| tx.slot <= end_slot)
+            .collect();
+        let sold: u64 = window.iter().map(|tx| tokens_sold(tx)).sum();
+
+        if sold as f64 >= threshold {
+            start += window.len();
+            windows.push(window);
+        } else {
+            start += 1;
+        }
+    }
+
+    windows
+}
+
+fn build_dump(
+    mint_trades: &[&ParsedTransaction],
+    sells: Vec<&ParsedTransaction>,
+    role: InsiderRole,
+) -> DevDumpEvent {
+    let first = sells[0];
+    let last = sells[sells.len() - 1];
+
+    let price_before = mint_trades
+        .iter()
+        .rev()
+        .find(|tx| order_key(tx) < order_key(first))
+        .and_then(|tx| execution_price(tx))
+        .or_else(|| execution_price(first))
+        .unwrap_or(0.0);
+    let price_after = mint_trades
+        .iter()
+        .find(|tx| order_key(tx) > order_key(last))
+        .and_then(|tx| execution_price(tx))
+        .or_else(|| execution_price(last))
+        .unwrap_or(0.0);
+    let price_impact_pct = if price_before > 0.0 {
+        (price_after - price_before) * 100.0 / price_before
+    } else {
+        0.0
+    };
+
+    let tokens: u64 = sells.iter().map(|tx| tokens_sold(tx)).sum();
+    DevDumpEvent {
+        mint: first.mint.to_string(),
+        seller: first.signer.to_string(),
+        role,
+        start_slot: first.slot,
+        end_slot: last.slot,
+        tokens_sold: tokens,
+        supply_pct: tokens as f64 * 100.0 / PUMP_TOKEN_SUPPLY as f64,
+        sol_extracted: sells.iter().map(|tx| tx.trade_sol_change()).sum(),
+        price_before,
+        price_after,
+        price_impact_pct,
+        sells: sells.into_iter().cloned().collect(),
+    }
+}
+
+fn tokens_sold(tx: &ParsedTransaction) -> u64 {
+    if tx.token_change < 0 { tx.token_change.unsigned_abs() } else { 0 }
+}
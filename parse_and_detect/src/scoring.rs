@@ -0,0 +1,125 @@
+use crate::detect::{DetectionSummary, DetectorConfig, SandwichDetection};
+use crate::error::{Error, Result};
+use crate::parser::pumpfun::ParsedTransaction;
+use serde_json::Value;
+use std::io::Write;
+use std::process::{Command, Stdio};
+#[cfg(feature = "rpc")]
+use std::time::Duration;
+
+// An outside opinion on a sandwich candidate: an ML model, a proprietary rule set. It gets the
+// candidate as JSON (the same object as in `--output json`) and answers with a score, either a
+// bare number or `{"score": <number>}`.
+pub trait Scorer {
+    fn score(&self, candidate: &SandwichDetection) -> Result<f64>;
+}
+
+// runs `program args...` once per candidate, JSON on stdin, the score on stdout
+pub struct CommandScorer {
+    pub program: String,
+    pub args: Vec<String>,
+}
+
+impl CommandScorer {
+    // split on whitespace: "python3 score.py --model m.bin"
+    pub fn parse(command: &str) -> Result<Self> {
+        let mut parts = command.split_whitespace().map(str::to_string);
+        let program = parts
+            .next()
+            .ok_or_else(|| Error::Config("Scorer command is empty".to_string()))?;
+        Ok(Self {
+            program,
+            args: parts.collect(),
+        })
+    }
+}
+
+impl Scorer for CommandScorer {
+    fn score(&self, candidate: &SandwichDetection) -> Result<f64> {
+        let payload = serde_json::to_vec(candidate).map_err(|e| Error::json("scorer candidate", e))?;
+        let failed = |e: std::io::Error| Error::Scorer(format!("'{}': {}", self.program, e));
+        let mut child = Command::new(&self.program)
+            .args(&self.args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()
+            .map_err(failed)?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(&payload).map_err(failed)?;
+        }
+        let output = child.wait_with_output().map_err(failed)?;
+        if !output.status.success() {
+            return Err(Error::Scorer(format!("'{}' exited with {}", self.program, output.status)));
+        }
+        parse_score(&String::from_utf8_lossy(&output.stdout))
+    }
+}
+
+// POSTs each candidate to `url`
+#[cfg(feature = "rpc")]
+pub struct HttpScorer {
+    client: reqwest::blocking::Client,
+    url: String,
+}
+
+#[cfg(feature = "rpc")]
+impl HttpScorer {
+    pub fn new(url: &str, timeout: Duration) -> Result<Self> {
+        let client = reqwest::blocking::Client::builder()
+            .timeout(timeout)
+            .build()
+            .map_err(|e| Error::Scorer(e.to_string()))?;
+        Ok(Self {
+            client,
+            url: url.to_string(),
+        })
+    }
+}
+
+#[cfg(feature = "rpc")]
+impl Scorer for HttpScorer {
+    fn score(&self, candidate: &SandwichDetection) -> Result<f64> {
+        let body = self
+            .client
+            .post(&self.url)
+            .json(candidate)
+            .send()
+            .and_then(|response| response.error_for_status())
+            .and_then(|response| response.text())
+            .map_err(|e| Error::Scorer(format!("{}: {}", self.url, e)))?;
+        parse_score(&body)
+    }
+}
+
+fn parse_score(raw: &str) -> Result<f64> {
+    let value: Value = serde_json::from_str(raw.trim()).map_err(|e| Error::json("scorer response", e))?;
+    value
+        .as_f64()
+        .or_else(|| value.get("score").and_then(Value::as_f64))
+        .ok_or_else(|| Error::Scorer(format!("response '{}' has no numeric score", raw.trim())))
+}
+
+// Scores every sandwich and drops those under `min_score`, then rebuilds the aggregates. A
+// candidate the scorer fails on is kept unscored, so an outage never hides detections; the
+// failures are returned.
+pub fn apply_scorer(
+    summary: &mut DetectionSummary,
+    trades: &[ParsedTransaction],
+    cfg: &DetectorConfig,
+    scorer: &dyn Scorer,
+    min_score: f64,
+) -> Vec<Error> {
+    let mut failures = Vec::new();
+    for det in &mut summary.sandwiches {
+        match scorer.score(det) {
+            Ok(score) => det.external_score = Some(score),
+            Err(e) => failures.push(e),
+        }
+    }
+    summary
+        .sandwiches
+        .retain(|det| det.external_score.is_none_or(|score| score >= min_score));
+    summary.rebuild_aggregates(trades, cfg);
+    failures
+}
@@ -0,0 +1,147 @@
+use crate::amm::{FeeSchedule, PumpAmmState};
+use crate::detect::{DetectionSummary, order_key};
+use crate::error::Error;
+use crate::fetch::TransactionSource;
+use crate::parser::pumpfun::{ParsedTransaction, Venue};
+use borsh::BorshDeserialize;
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Arc;
+
+const BONDING_CURVE_DISCRIMINATOR: [u8; 8] = [23, 183, 248, 55, 96, 216, 172, 96];
+
+// the leading fields of pump.fun's BondingCurve account; newer versions append a creator
+#[derive(Debug, Clone, PartialEq, Eq, BorshDeserialize)]
+pub struct BondingCurveAccount {
+    pub virtual_token_reserves: u64,
+    pub virtual_sol_reserves: u64,
+    pub real_token_reserves: u64,
+    pub real_sol_reserves: u64,
+    pub token_total_supply: u64,
+    pub complete: bool,
+}
+
+impl BondingCurveAccount {
+    pub fn decode(data: &[u8]) -> Option<Self> {
+        let (discriminator, mut payload) = data.split_first_chunk::<8>()?;
+        if *discriminator != BONDING_CURVE_DISCRIMINATOR {
+            return None;
+        }
+        Self::deserialize(&mut payload).ok()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SnapshotSource {
+    // the curve's token balance in the victim's own transaction
+    CurveBalance,
+    // the curve account fetched after the scan, rewound over the later trades
+    Account,
+}
+
+// pool state right before the victim's trade
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReserveSnapshot {
+    pub slot: u64,
+    pub virtual_sol: u64,
+    pub virtual_token: u64,
+    pub real_sol: u64,
+    pub real_token: u64,
+    pub source: SnapshotSource,
+}
+
+impl ReserveSnapshot {
+    // lamports per raw token unit
+    pub fn price(&self) -> f64 {
+        if self.virtual_token == 0 {
+            return 0.0;
+        }
+        self.virtual_sol as f64 / self.virtual_token as f64
+    }
+}
+
+pub fn bonding_curve_address(mint: &str, program: &Pubkey) -> Option<Pubkey> {
+    let mint = Pubkey::from_str(mint).ok()?;
+    Some(Pubkey::find_program_address(&[b"bonding-curve", mint.as_ref()], program).0)
+}
+
+// Attaches the pre-trade reserves to every sandwich. The victim's own curve balance is exact;
+// otherwise the curve account is fetched once per mint and rewound over the scanned trades,
+// which is only right when `trades` holds every trade since the victim. Failed account fetches
+// are returned and the sandwiches left without a snapshot. Victims that traded on the graduated
+// pool get none, since the curve no longer prices them.
+pub fn attach_reserve_snapshots(
+    summary: &mut DetectionSummary,
+    trades: &[ParsedTransaction],
+    source: &dyn TransactionSource,
+    program: &Pubkey,
+) -> Vec<Error> {
+    let mut accounts: HashMap<Arc<str>, Option<BondingCurveAccount>> = HashMap::new();
+    let mut failures = Vec::new();
+    for det in &mut summary.sandwiches {
+        if det.victim.venue != Venue::PumpFun {
+            continue;
+        }
+        if let Some(snapshot) = from_curve_balance(&det.victim) {
+            det.victim_reserves = Some(snapshot);
+            continue;
+        }
+        let account = accounts.entry(det.victim.mint.clone()).or_insert_with(|| {
+            let address = bonding_curve_address(&det.victim.mint, program)?;
+            match source.account_data(&address) {
+                Ok(data) => BondingCurveAccount::decode(&data),
+                Err(e) => {
+                    failures.push(e);
+                    None
+                }
+            }
+        });
+        det.victim_reserves = account
+            .as_ref()
+            .and_then(|account| rewind(account, &det.victim, trades));
+    }
+    failures
+}
+
+fn from_curve_balance(victim: &ParsedTransaction) -> Option<ReserveSnapshot> {
+    let state = PumpAmmState::from_curve_snapshot(victim, &FeeSchedule::default())?;
+    Some(ReserveSnapshot {
+        slot: victim.slot,
+        virtual_sol: state.virtual_sol,
+        virtual_token: state.virtual_token,
+        real_sol: state.real_sol,
+        real_token: state.real_token,
+        source: SnapshotSource::CurveBalance,
+    })
+}
+
+// undoes the victim's trade and every later one on the same mint
+fn rewind(
+    account: &BondingCurveAccount,
+    victim: &ParsedTransaction,
+    trades: &[ParsedTransaction],
+) -> Option<ReserveSnapshot> {
+    let mut real_sol = account.real_sol_reserves as i128;
+    let mut real_token = account.real_token_reserves as i128;
+    for trade in trades
+        .iter()
+        .filter(|trade| trade.mint == victim.mint && trade.venue == Venue::PumpFun && order_key(trade) >= order_key(victim))
+    {
+        let curve = trade.curve.as_ref()?;
+        real_sol -= curve.sol_change as i128;
+        real_token -= curve.token_change as i128;
+    }
+    let real_sol = u64::try_from(real_sol).ok()?;
+    let real_token = u64::try_from(real_token).ok()?;
+    // virtual reserves sit a fixed offset above the real ones for the life of the curve
+    Some(ReserveSnapshot {
+        slot: victim.slot,
+        virtual_sol: real_sol + account.virtual_sol_reserves.checked_sub(account.real_sol_reserves)?,
+        virtual_token: real_token + account.virtual_token_reserves.checked_sub(account.real_token_reserves)?,
+        real_sol,
+        real_token,
+        source: SnapshotSource::Account,
+    })
+}
@@ -0,0 +1,90 @@
+use crate::detect::{DetectorConfig, execution_price, order_key};
+use crate::parser::pumpfun::{ExecutionOutcome, LifecycleEvent, LifecycleKind, ParsedTransaction, TradeType};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SniperEvent {
+    pub mint: String,
+    pub sniper: String,
+    pub create_slot: u64,
+    pub entry_slot: u64,
+    pub sol_spent: u64,
+    pub tokens_bought: u64,
+    pub entry_price: f64,
+    pub sol_received: u64,
+    pub tokens_sold: u64,
+    pub exit_profit_lamports: i64,
+    pub tokens_remaining: i64,
+    pub trades: Vec<ParsedTransaction>,
+}
+
+pub fn detect_snipers(
+    trades: &[ParsedTransaction],
+    lifecycle: &[LifecycleEvent],
+    cfg: &DetectorConfig,
+) -> Vec<SniperEvent> {
+    let mut snipers = Vec::new();
+
+    for event in lifecycle {
+        let LifecycleKind::Create { creator, .. } = &event.kind else {
+            continue;
+        };
+
+        let mut by_signer: BTreeMap<&str, Vec<&ParsedTransaction>> = BTreeMap::new();
+        for tx in trades {
+            if *tx.mint != *event.mint || tx.outcome == ExecutionOutcome::Reverted || *tx.signer == **creator {
+                continue;
+            }
+            by_signer.entry(tx.signer.as_ref()).or_default().push(tx);
+        }
+
+        let snipe_cutoff = event.slot.saturating_add(cfg.snipe_max_slot_delay);
+        for (signer, mut signer_trades) in by_signer {
+            signer_trades.sort_by(|a, b| order_key(a).cmp(&order_key(b)));
+
+            let entries: Vec<&ParsedTransaction> = signer_trades
+                .iter()
+                .copied()
+                .filter(|tx| tx.trade_type == TradeType::Buy && tx.slot >= event.slot && tx.slot <= snipe_cutoff)
+                .collect();
+            let sol_spent: u64 = entries.iter().map(|tx| tx.trade_sol_change().unsigned_abs()).sum();
+            if entries.is_empty() || sol_spent < cfg.snipe_min_lamports() {
+                continue;
+            }
+            let tokens_bought: u64 = entries.iter().map(|tx| tx.token_change.unsigned_abs()).sum();
+
+            let exits: Vec<&ParsedTransaction> = signer_trades
+                .iter()
+                .copied()
+                .filter(|tx| tx.trade_type == TradeType::Sell)
+                .collect();
+
+            snipers.push(SniperEvent {
+                mint: event.mint.clone(),
+                sniper: signer.to_string(),
+                create_slot: event.slot,
+                entry_slot: entries[0].slot,
+                sol_spent,
+                tokens_bought,
+                entry_price: if tokens_bought > 0 {
+                    sol_spent as f64 / tokens_bought as f64
+                } else {
+                    entries.iter().find_map(|tx| execution_price(tx)).unwrap_or(0.0)
+                },
+                sol_received: exits.iter().map(|tx| tx.trade_sol_change().max(0) as u64).sum(),
+                tokens_sold: exits.iter().map(|tx| tx.token_change.unsigned_abs()).sum(),
+                exit_profit_lamports: signer_trades.iter().map(|tx| tx.trade_sol_change()).sum(),
+                tokens_remaining: signer_trades.iter().map(|tx| tx.token_change).sum(),
+                trades: signer_trades.into_iter().cloned().collect(),
+            });
+        }
+    }
+
+    snipers.sort_by(|a, b| {
+        b.exit_profit_lamports
+            .cmp(&a.exit_profit_lamports)
+            .then_with(|| a.sniper.cmp(&b.sniper))
+    });
+    snipers
+}
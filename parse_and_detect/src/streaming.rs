@@ -0,0 +1,359 @@
+use crate::detect::{
+    BackRunEvent, BackrunArbEvent, DetectionSummary, DetectorConfig, FrontRunEvent, LegIndex, RevertedSandwichEvent,
+    SandwichDetection, adaptive_gap, arb_backruns, arb_config, backrun_arb, build_sandwich, is_bot, is_candidate_victim,
+    order_key, reverted_sandwich, victim_mode_loss,
+};
+use crate::detector::CustomDetection;
+use crate::parser::pumpfun::{ExecutionOutcome, ParsedTransaction};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::ops::Bound::{Excluded, Unbounded};
+use std::sync::Arc;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum DetectionEvent {
+    FrontRun(FrontRunEvent),
+    BackRun(BackRunEvent),
+    // boxed, these carry several full legs
+    Sandwich(Box<SandwichDetection>),
+    BackrunArb(Box<BackrunArbEvent>),
+    RevertedSandwich(RevertedSandwichEvent),
+    Custom(CustomDetection),
+}
+
+#[derive(Default)]
+struct VictimState {
+    frontruns: usize,
+    backruns: usize,
+    sandwich: bool,
+    reverted: bool,
+    // signers already taken as its arbitrage backruns
+    arb_signers: Vec<Arc<str>>,
+}
+
+// an arbitrage backrun whose closing leg has not arrived yet
+struct OpenArb {
+    victim: ParsedTransaction,
+    backrun: ParsedTransaction,
+}
+
+// A mint's distinct trading slots as a count of the distances between neighbours, which is all
+// `adaptive_gap` needs. Slots before the window are forgotten but the newest of them, the
+// neighbour of anything that may still arrive.
+#[derive(Default)]
+struct SlotSpacing {
+    slots: BTreeSet<u64>,
+    distances: BTreeMap<u64, usize>,
+    total: usize,
+}
+
+impl SlotSpacing {
+    fn insert(&mut self, slot: u64) {
+        if !self.slots.insert(slot) {
+            return;
+        }
+        let prev = self.slots.range(..slot).next_back().copied();
+        let next = self.slots.range((Excluded(slot), Unbounded)).next().copied();
+        if let (Some(prev), Some(next)) = (prev, next) {
+            self.remove_distance(next - prev);
+        }
+        if let Some(prev) = prev {
+            self.add_distance(slot - prev);
+        }
+        if let Some(next) = next {
+            self.add_distance(next - slot);
+        }
+    }
+
+    fn add_distance(&mut self, distance: u64) {
+        *self.distances.entry(distance).or_default() += 1;
+        self.total += 1;
+    }
+
+    fn remove_distance(&mut self, distance: u64) {
+        if let Some(count) = self.distances.get_mut(&distance) {
+            *count -= 1;
+            if *count == 0 {
+                self.distances.remove(&distance);
+            }
+            self.total -= 1;
+        }
+    }
+
+    fn forget_before(&mut self, start: u64) {
+        let retained = self.slots.split_off(&start);
+        let newest_expired = self.slots.last().copied();
+        self.slots = retained;
+        self.slots.extend(newest_expired);
+    }
+
+    // the median distance, as `detect::median` takes it over the sorted list
+    fn median(&self) -> Option<u64> {
+        let nth = |n: usize| {
+            let mut seen = 0;
+            self.distances.iter().find_map(|(&distance, &count)| {
+                seen += count;
+                (seen > n).then_some(distance)
+            })
+        };
+        match self.total {
+            0 => None,
+            total if total % 2 == 1 => nth(total / 2),
+            total => Some((nth(total / 2 - 1)? + nth(total / 2)?) / 2),
+        }
+    }
+}
+
+fn slot_gaps<'a>(spacing: &'a HashMap<Arc<str>, SlotSpacing>, cfg: &DetectorConfig) -> HashMap<&'a str, u64> {
+    if !cfg.adaptive_slot_gap {
+        return HashMap::new();
+    }
+    spacing
+        .iter()
+        .map(|(mint, spacing)| (mint.as_ref(), adaptive_gap(spacing.median(), cfg)))
+        .collect()
+}
+
+// Runs the batch rules over a sliding window of trades as they arrive. The window spans twice
+// the widest slot gap behind the newest slot, so a victim keeps being re-evaluated while legs
+// that could frontrun or backrun it can still land; its events repeat as they grow. Closing legs
+// of an arbitrage are matched for as long as the position stays open, as the batch run would.
+pub struct StreamingDetector {
+    cfg: DetectorConfig,
+    arb_cfg: DetectorConfig,
+    window: BTreeMap<u64, Vec<ParsedTransaction>>,
+    signer_counts: HashMap<Arc<str>, usize>,
+    bot_signers: HashSet<String>,
+    victims: HashMap<(Arc<str>, usize), VictimState>,
+    spacing: HashMap<Arc<str>, SlotSpacing>,
+    open_arbs: Vec<OpenArb>,
+    newest_slot: u64,
+}
+
+impl StreamingDetector {
+    pub fn new(cfg: DetectorConfig) -> Self {
+        Self {
+            arb_cfg: arb_config(&cfg),
+            cfg,
+            window: BTreeMap::new(),
+            signer_counts: HashMap::new(),
+            bot_signers: HashSet::new(),
+            victims: HashMap::new(),
+            spacing: HashMap::new(),
+            open_arbs: Vec::new(),
+            newest_slot: 0,
+        }
+    }
+
+    pub fn window_len(&self) -> usize {
+        self.window.values().map(Vec::len).sum()
+    }
+
+    // the per-mint windows `adaptive_slot_gap` worked out so far, empty when it is off
+    pub fn slot_gaps(&self) -> HashMap<&str, u64> {
+        slot_gaps(&self.spacing, &self.cfg)
+    }
+
+    pub fn push(&mut self, tx: ParsedTransaction) -> Vec<DetectionEvent> {
+        if tx.slot < self.window_start() {
+            return Vec::new();
+        }
+
+        let slot = tx.slot;
+        let count = self.signer_counts.entry(tx.signer.clone()).or_default();
+        *count += 1;
+        if is_bot(&tx.signer, *count, &self.cfg) && !self.bot_signers.contains(&*tx.signer) {
+            self.bot_signers.insert(tx.signer.to_string());
+        }
+        self.spacing.entry(tx.mint.clone()).or_default().insert(slot);
+        let mut events = self.close_arbs(&tx);
+        self.window.entry(slot).or_default().push(tx);
+
+        if slot > self.newest_slot {
+            self.newest_slot = slot;
+            self.evict();
+        }
+
+        events.extend(self.evaluate_around(slot));
+        events
+    }
+
+    fn window_start(&self) -> u64 {
+        self.newest_slot
+            .saturating_sub(self.cfg.widest_slot_gap().saturating_mul(2))
+    }
+
+    fn evict(&mut self) {
+        let start = self.window_start();
+        let retained = self.window.split_off(&start);
+        let expired = std::mem::replace(&mut self.window, retained);
+        for spacing in self.spacing.values_mut() {
+            spacing.forget_before(start);
+        }
+
+        for tx in expired.into_values().flatten() {
+            if let Some(count) = self.signer_counts.get_mut(&*tx.signer) {
+                *count -= 1;
+                if !is_bot(&tx.signer, *count, &self.cfg) {
+                    self.bot_signers.remove(&*tx.signer);
+                }
+                if *count == 0 {
+                    self.signer_counts.remove(&*tx.signer);
+                }
+            }
+            self.victims.remove(&(tx.signature, tx.leg_index));
+        }
+    }
+
+    // the open arbitrages `tx` closes: the signer's first leg after the backrun on the same
+    // mint and the opposite side, as `LegIndex::closing_leg` finds it
+    fn close_arbs(&mut self, tx: &ParsedTransaction) -> Vec<DetectionEvent> {
+        if tx.outcome == ExecutionOutcome::Reverted {
+            return Vec::new();
+        }
+        let mut events = Vec::new();
+        self.open_arbs.retain(|open| {
+            let closes = open.backrun.signer == tx.signer
+                && open.backrun.mint == tx.mint
+                && open.backrun.trade_type.opposite() == tx.trade_type
+                && order_key(tx) > order_key(&open.backrun);
+            if closes && let Some(event) = backrun_arb(&open.victim, &open.backrun, tx, &self.cfg) {
+                events.push(DetectionEvent::BackrunArb(Box::new(event)));
+            }
+            !closes
+        });
+        events
+    }
+
+    fn evaluate_around(&mut self, slot: u64) -> Vec<DetectionEvent> {
+        let widest = self.cfg.widest_slot_gap();
+        let start = slot.saturating_sub(widest);
+        let end = slot.saturating_add(widest);
+        let index = LegIndex::from_legs(self.window.values().flatten(), &self.bot_signers, slot_gaps(&self.spacing, &self.cfg));
+        let mut events = Vec::new();
+
+        for victim in self.window.range(start..=end).flat_map(|(_, txs)| txs.iter()) {
+            let key = (victim.signature.clone(), victim.leg_index);
+
+            if victim.outcome == ExecutionOutcome::Reverted {
+                let state = self.victims.entry(key).or_default();
+                if state.reverted {
+                    continue;
+                }
+                let frontruns = index.frontruns(victim, &self.cfg);
+                let backruns = index.backruns(victim, &self.cfg);
+                if let Some(event) = reverted_sandwich(victim, &frontruns, &backruns, &self.cfg) {
+                    state.reverted = true;
+                    events.push(DetectionEvent::RevertedSandwich(event));
+                }
+                continue;
+            }
+
+            let state = self.victims.entry(key).or_default();
+            for backrun in arb_backruns(&index, victim, &self.arb_cfg, &mut state.arb_signers) {
+                match index.closing_leg(&backrun) {
+                    Some(close) => {
+                        if let Some(event) = backrun_arb(victim, &backrun, close, &self.cfg) {
+                            events.push(DetectionEvent::BackrunArb(Box::new(event)));
+                        }
+                    }
+                    None => self.open_arbs.push(OpenArb {
+                        victim: victim.clone(),
+                        backrun,
+                    }),
+                }
+            }
+
+            if !is_candidate_victim(victim, &self.cfg) {
+                continue;
+            }
+
+            let frontruns = index.frontruns(victim, &self.cfg);
+            let backruns = index.backruns(victim, &self.cfg);
+            if victim_mode_loss(victim, &frontruns, &self.cfg).is_none() {
+                continue;
+            }
+
+            if frontruns.len() > state.frontruns {
+                state.frontruns = frontruns.len();
+                events.push(DetectionEvent::FrontRun(FrontRunEvent {
+                    victim: victim.clone(),
+                    frontruns: frontruns.clone(),
+                }));
+            }
+            if backruns.len() > state.backruns {
+                state.backruns = backruns.len();
+                events.push(DetectionEvent::BackRun(BackRunEvent {
+                    victim: victim.clone(),
+                    backruns: backruns.clone(),
+                }));
+            }
+            if !state.sandwich
+                && let Some(sandwich) = build_sandwich(victim, &frontruns, &backruns, &self.cfg)
+            {
+                state.sandwich = true;
+                events.push(DetectionEvent::Sandwich(Box::new(sandwich)));
+            }
+        }
+
+        events
+    }
+}
+
+// Folds a stream of trades and the detector's events into a summary without keeping the
+// trades; only the per-mint trade counts are. A victim's front- and back-run events grow as
+// more legs arrive, so only the latest of each is kept.
+#[derive(Clone, Default)]
+pub struct SummaryCollector {
+    summary: DetectionSummary,
+    trade_counts: BTreeMap<Arc<str>, usize>,
+    front_runs: HashMap<(Arc<str>, usize), usize>,
+    back_runs: HashMap<(Arc<str>, usize), usize>,
+}
+
+impl SummaryCollector {
+    pub fn count_trade(&mut self, tx: &ParsedTransaction) {
+        *self.trade_counts.entry(tx.mint.clone()).or_default() += 1;
+    }
+
+    pub fn trades(&self) -> usize {
+        self.trade_counts.values().sum()
+    }
+
+    pub fn record(&mut self, event: DetectionEvent) {
+        match event {
+            DetectionEvent::FrontRun(event) => {
+                let key = (event.victim.signature.clone(), event.victim.leg_index);
+                match self.front_runs.get(&key) {
+                    Some(&index) => self.summary.front_runs[index] = event,
+                    None => {
+                        self.front_runs.insert(key, self.summary.front_runs.len());
+                        self.summary.front_runs.push(event);
+                    }
+                }
+            }
+            DetectionEvent::BackRun(event) => {
+                let key = (event.victim.signature.clone(), event.victim.leg_index);
+                match self.back_runs.get(&key) {
+                    Some(&index) => self.summary.back_runs[index] = event,
+                    None => {
+                        self.back_runs.insert(key, self.summary.back_runs.len());
+                        self.summary.back_runs.push(event);
+                    }
+                }
+            }
+            other => self.summary.record(other),
+        }
+    }
+
+    // `gaps` are the detector's windows, from `StreamingDetector::slot_gaps`
+    pub fn finish(mut self, cfg: &DetectorConfig, gaps: &HashMap<&str, u64>) -> DetectionSummary {
+        let counts: BTreeMap<&str, usize> = self
+            .trade_counts
+            .iter()
+            .map(|(mint, &count)| (mint.as_ref(), count))
+            .collect();
+        self.summary.rebuild_with_counts(&counts, gaps, cfg);
+        self.summary
+    }
+}
@@ -0,0 +1,85 @@
+use crate::detect::{DetectionSummary, DetectorConfig, detect_wide_attacks};
+use crate::parser::pumpfun::ParsedTransaction;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::sync::Arc;
+
+// Values to try per threshold; an empty axis keeps the base config's value
+#[derive(Debug, Clone, Default)]
+pub struct SweepGrid {
+    pub max_slot_gaps: Vec<u64>,
+    pub min_profit_sol: Vec<f64>,
+    pub min_victim_abs_sol: Vec<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SweepPoint {
+    pub max_slot_gap: u64,
+    pub min_profit_sol: f64,
+    pub min_victim_abs_sol: f64,
+    pub sandwiches: usize,
+    pub front_runs: usize,
+    pub back_runs: usize,
+    // sandwiches the base config also found, matched by victim leg
+    pub shared_with_base: usize,
+    // |shared| / |union| against the base config's sandwiches, 1.0 when both are empty
+    pub overlap: f64,
+}
+
+// Re-runs detection over the same trades for every combination in the grid, so thresholds
+// can be compared on identical input.
+pub fn run_sweep(trades: &[ParsedTransaction], base: &DetectorConfig, grid: &SweepGrid) -> Vec<SweepPoint> {
+    let base_victims = victim_keys(&detect_wide_attacks(trades, base));
+    let gaps = axis(&grid.max_slot_gaps, base.max_slot_gap);
+    let profits = axis(&grid.min_profit_sol, base.min_profit_sol);
+    let victim_sols = axis(&grid.min_victim_abs_sol, base.min_victim_abs_sol);
+
+    let mut combinations = Vec::new();
+    for &max_slot_gap in &gaps {
+        for &min_profit_sol in &profits {
+            for &min_victim_abs_sol in &victim_sols {
+                combinations.push((max_slot_gap, min_profit_sol, min_victim_abs_sol));
+            }
+        }
+    }
+
+    // every grid point is an independent run over the same trades
+    combinations
+        .into_par_iter()
+        .map(|(max_slot_gap, min_profit_sol, min_victim_abs_sol)| {
+            let cfg = DetectorConfig {
+                max_slot_gap,
+                min_profit_sol,
+                min_victim_abs_sol,
+                ..base.clone()
+            };
+            let summary = detect_wide_attacks(trades, &cfg);
+            let victims = victim_keys(&summary);
+            let shared = victims.intersection(&base_victims).count();
+            let union = victims.union(&base_victims).count();
+            SweepPoint {
+                max_slot_gap,
+                min_profit_sol,
+                min_victim_abs_sol,
+                sandwiches: summary.sandwiches.len(),
+                front_runs: summary.front_runs.len(),
+                back_runs: summary.back_runs.len(),
+                shared_with_base: shared,
+                overlap: if union == 0 { 1.0 } else { shared as f64 / union as f64 },
+            }
+        })
+        .collect()
+}
+
+fn victim_keys(summary: &DetectionSummary) -> BTreeSet<(Arc<str>, usize)> {
+    summary
+        .sandwiches
+        .iter()
+        .map(|det| (det.victim.signature.clone(), det.victim.leg_index))
+        .collect()
+}
+
+fn axis<T: Copy>(values: &[T], base: T) -> Vec<T> {
+    if values.is_empty() { vec![base] } else { values.to_vec() }
+}
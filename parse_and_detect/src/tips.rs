@@ -0,0 +1,59 @@
+use crate::detect::SandwichDetection;
+use serde::{Deserialize, Serialize};
+
+// How much of sandwich revenue is paid to Jito. Revenue is the attackers' gross curve profit,
+// tips are what their legs sent to the tip accounts (`ParsedTransaction::tip`); a sandwich's
+// `net_profit_sol` is already after tips.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TipStatistics {
+    pub sandwiches: usize,
+    pub tipped_sandwiches: usize,
+    pub total_tips_lamports: u64,
+    pub gross_revenue_lamports: i64,
+    // share of gross revenue paid as tips
+    pub tip_share_pct: f64,
+    pub profit_before_tips_lamports: i64,
+    pub profit_after_tips_lamports: i64,
+    // over the tipped sandwiches
+    pub median_tip_lamports: u64,
+    pub max_tip_lamports: u64,
+    pub median_tip_share_pct: f64,
+}
+
+pub fn profit_before_tips(det: &SandwichDetection) -> i64 {
+    det.net_profit_sol.saturating_add(i64::try_from(det.costs.tips).unwrap_or(i64::MAX))
+}
+
+pub fn tip_statistics(sandwiches: &[SandwichDetection]) -> Option<TipStatistics> {
+    if sandwiches.is_empty() {
+        return None;
+    }
+    let mut stats = TipStatistics {
+        sandwiches: sandwiches.len(),
+        ..TipStatistics::default()
+    };
+    let mut tips: Vec<u64> = Vec::new();
+    let mut shares: Vec<f64> = Vec::new();
+    for det in sandwiches {
+        stats.total_tips_lamports = stats.total_tips_lamports.saturating_add(det.costs.tips);
+        stats.gross_revenue_lamports = stats.gross_revenue_lamports.saturating_add(det.gross_profit_sol);
+        stats.profit_before_tips_lamports = stats.profit_before_tips_lamports.saturating_add(profit_before_tips(det));
+        stats.profit_after_tips_lamports = stats.profit_after_tips_lamports.saturating_add(det.net_profit_sol);
+        if det.costs.tips > 0 {
+            tips.push(det.costs.tips);
+            if det.gross_profit_sol > 0 {
+                shares.push(det.costs.tips as f64 * 100.0 / det.gross_profit_sol as f64);
+            }
+        }
+    }
+    stats.tipped_sandwiches = tips.len();
+    if stats.gross_revenue_lamports > 0 {
+        stats.tip_share_pct = stats.total_tips_lamports as f64 * 100.0 / stats.gross_revenue_lamports as f64;
+    }
+    tips.sort_unstable();
+    shares.sort_by(f64::total_cmp);
+    stats.median_tip_lamports = tips.get(tips.len() / 2).copied().unwrap_or_default();
+    stats.max_tip_lamports = tips.last().copied().unwrap_or_default();
+    stats.median_tip_share_pct = shares.get(shares.len() / 2).copied().unwrap_or_default();
+    Some(stats)
+}
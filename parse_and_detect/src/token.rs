@@ -0,0 +1,133 @@
+use crate::error::{Error, Result};
+use crate::fetch::TransactionSource;
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+#[cfg(feature = "rpc")]
+use std::time::Duration;
+
+// every pump.fun mint has 6; assumed when the mint account can't be read
+pub const DEFAULT_DECIMALS: u8 = 6;
+// SPL mint layout (Token-2022 shares it): mint authority (4 + 32), supply (8), decimals
+const MINT_LEN: usize = 82;
+const DECIMALS_OFFSET: usize = 44;
+pub const METADATA_PROGRAM: Pubkey = Pubkey::from_str_const("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
+// key (1), update authority (32), mint (32), then the name, symbol and uri strings
+const METADATA_STRINGS_OFFSET: usize = 65;
+
+// the mint's Metaplex metadata account; `image` comes from the JSON its uri points at
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MintMetadata {
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image: Option<String>,
+}
+
+impl MintMetadata {
+    // "$WIF", or the name for a token without a symbol
+    pub fn label(&self) -> Option<String> {
+        match (self.symbol.is_empty(), self.name.is_empty()) {
+            (false, _) => Some(format!("${}", self.symbol)),
+            (true, false) => Some(self.name.clone()),
+            (true, true) => None,
+        }
+    }
+}
+
+pub fn decode_decimals(data: &[u8]) -> Option<u8> {
+    if data.len() < MINT_LEN {
+        return None;
+    }
+    data.get(DECIMALS_OFFSET).copied()
+}
+
+// one getAccountInfo on the mint
+pub fn fetch_decimals(source: &dyn TransactionSource, mint: &Pubkey) -> Result<u8> {
+    let data = source.account_data(mint)?;
+    decode_decimals(&data).ok_or_else(|| Error::Rpc(format!("Account {} is not a token mint", mint)))
+}
+
+// base units per whole token
+pub fn unit(decimals: u8) -> u64 {
+    10u64.pow(decimals.min(19) as u32)
+}
+
+// a raw base-unit amount in whole tokens
+pub fn ui_amount(raw: i64, decimals: u8) -> f64 {
+    raw as f64 / 10f64.powi(decimals as i32)
+}
+
+// exact decimal rendering of a raw amount, without trailing zeros: 19_500_000 at 6 decimals is
+// "19.5"
+pub fn format_tokens(raw: i64, decimals: u8) -> String {
+    let decimals = decimals.min(38);
+    let unit = 10u128.pow(decimals as u32);
+    let magnitude = raw.unsigned_abs() as u128;
+    let sign = if raw < 0 { "-" } else { "" };
+    let fraction = magnitude % unit;
+    if fraction == 0 {
+        return format!("{}{}", sign, magnitude / unit);
+    }
+    let digits = format!("{:0width$}", fraction, width = decimals as usize);
+    format!("{}{}.{}", sign, magnitude / unit, digits.trim_end_matches('0'))
+}
+
+pub fn metadata_address(mint: &Pubkey) -> Pubkey {
+    let (address, _) =
+        Pubkey::find_program_address(&[b"metadata", METADATA_PROGRAM.as_ref(), mint.as_ref()], &METADATA_PROGRAM);
+    address
+}
+
+// Borsh strings (u32 length, then bytes); Metaplex pads them with NULs to a fixed width
+pub fn decode_metadata(data: &[u8]) -> Option<MintMetadata> {
+    let mut offset = METADATA_STRINGS_OFFSET;
+    let mut string = || {
+        let len = u32::from_le_bytes(data.get(offset..offset + 4)?.try_into().ok()?) as usize;
+        let bytes = data.get(offset + 4..offset + 4 + len)?;
+        offset += 4 + len;
+        Some(String::from_utf8_lossy(bytes).trim_end_matches('\0').trim().to_string())
+    };
+    Some(MintMetadata {
+        name: string()?,
+        symbol: string()?,
+        uri: string()?,
+        image: None,
+    })
+}
+
+// one getAccountInfo on the metadata account
+pub fn fetch_metadata(source: &dyn TransactionSource, mint: &Pubkey) -> Result<MintMetadata> {
+    let address = metadata_address(mint);
+    let data = source.account_data(&address)?;
+    decode_metadata(&data).ok_or_else(|| Error::Rpc(format!("Account {} is not Metaplex metadata", address)))
+}
+
+// off-chain metadata is a few hundred bytes; the uri is the token creator's, so cap what we read
+#[cfg(feature = "rpc")]
+pub const MAX_METADATA_JSON: u64 = 64 * 1024;
+
+// The `image` field of the off-chain JSON, for http(s) uris only. The uri is whatever the token
+// creator wrote, so this only runs when asked for (`--token-image`).
+#[cfg(feature = "rpc")]
+pub fn fetch_image(uri: &str, timeout: Duration) -> Result<Option<String>> {
+    use std::io::Read;
+
+    if !uri.starts_with("https://") && !uri.starts_with("http://") {
+        return Ok(None);
+    }
+    let failed = |e: reqwest::Error| Error::Rpc(format!("Token metadata {}: {}", uri, e));
+    let client = reqwest::blocking::Client::builder().timeout(timeout).build().map_err(failed)?;
+    let response = client.get(uri).send().and_then(|response| response.error_for_status()).map_err(failed)?;
+    let mut body = Vec::new();
+    response
+        .take(MAX_METADATA_JSON + 1)
+        .read_to_end(&mut body)
+        .map_err(|e| Error::Rpc(format!("Token metadata {}: {}", uri, e)))?;
+    if body.len() as u64 > MAX_METADATA_JSON {
+        return Err(Error::Rpc(format!("Token metadata {}: larger than {} bytes", uri, MAX_METADATA_JSON)));
+    }
+    let json: serde_json::Value =
+        serde_json::from_slice(&body).map_err(|e| Error::Rpc(format!("Token metadata {}: {}", uri, e)))?;
+    Ok(json.get("image").and_then(|image| image.as_str()).map(str::to_string))
+}
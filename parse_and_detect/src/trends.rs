@@ -0,0 +1,85 @@
+use crate::detect::SandwichDetection;
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet};
+
+const SECS_PER_DAY: i64 = 86_400;
+// victim losses listed per period
+const TOP_VICTIMS: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Period {
+    Daily,
+    // ISO weeks, starting Monday 00:00 UTC
+    Weekly,
+}
+
+impl Period {
+    // unix seconds at the start of the period holding `time`
+    pub fn start_of(self, time: i64) -> i64 {
+        let day = time.div_euclid(SECS_PER_DAY);
+        let day = match self {
+            Period::Daily => day,
+            // 1970-01-01 was a Thursday
+            Period::Weekly => day - (day + 3).rem_euclid(7),
+        };
+        day * SECS_PER_DAY
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VictimLoss {
+    pub victim: String,
+    pub signature: String,
+    pub loss_lamports: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PeriodStats {
+    pub period_start: i64,
+    pub attacks: usize,
+    pub sol_extracted: i64,
+    pub unique_attackers: usize,
+    pub unique_victims: usize,
+    pub top_victim_losses: Vec<VictimLoss>,
+}
+
+// `sandwiches` pairs each detection with the unix time it happened at; periods come out oldest
+// first and periods without attacks are left out
+pub fn period_stats(sandwiches: &[(i64, SandwichDetection)], period: Period) -> Vec<PeriodStats> {
+    let mut buckets: BTreeMap<i64, Vec<&SandwichDetection>> = BTreeMap::new();
+    for (time, det) in sandwiches {
+        buckets.entry(period.start_of(*time)).or_default().push(det);
+    }
+
+    buckets
+        .into_iter()
+        .map(|(period_start, attacks)| {
+            let attackers: BTreeSet<&str> = attacks
+                .iter()
+                .flat_map(|det| det.frontruns.iter().chain(det.backruns.iter()))
+                .map(|leg| leg.signer.as_ref())
+                .collect();
+            let victims: BTreeSet<&str> = attacks.iter().map(|det| det.victim.signer.as_ref()).collect();
+            let mut losses: Vec<VictimLoss> = attacks
+                .iter()
+                .map(|det| VictimLoss {
+                    victim: det.victim.signer.to_string(),
+                    signature: det.victim.signature.to_string(),
+                    loss_lamports: det.victim_loss(),
+                })
+                .collect();
+            losses.sort_by_key(|loss| Reverse(loss.loss_lamports));
+            losses.truncate(TOP_VICTIMS);
+            PeriodStats {
+                period_start,
+                attacks: attacks.len(),
+                sol_extracted: attacks.iter().map(|det| det.net_profit_sol).sum(),
+                unique_attackers: attackers.len(),
+                unique_victims: victims.len(),
+                top_victim_losses: losses,
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,584 @@
+use crate::report::short_sig;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Paragraph, Row, Table, TableState, Tabs};
+use ratatui::{DefaultTerminal, Frame};
+use rusty::detect::{DetectionSummary, DetectorConfig, LamportsExt, Severity, victim_loss_lamports};
+use rusty::error::Error;
+use rusty::parser::pumpfun::ParsedTransaction;
+use rusty::price::format_utc;
+use rusty::streaming::{DetectionEvent, SummaryCollector};
+use rusty::token;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::io::{self, IsTerminal};
+use std::sync::Arc;
+use std::sync::mpsc::{Receiver, TryRecvError};
+use std::time::Duration;
+
+// Terminal dashboard over a scan: trades, attacks, attackers and a slot timeline, and the
+// leg-by-leg breakdown of any attack. A finished scan is browsed as it is; a `--stream` scan fills
+// it in as it runs, the pipeline feeding it every trade and detection.
+
+// trades the trades tab keeps while streaming; the counts and the timeline cover all of them
+const LIVE_TRADES: usize = 10_000;
+// feed messages taken in between two frames, so a fast scan can't starve the keys
+const FEED_PER_FRAME: usize = 5_000;
+// how long a frame waits for a key
+const FRAME: Duration = Duration::from_millis(100);
+const HELP: &str = "Tab/t a w l switch  ↑↓ j k move  PgUp PgDn  Home End  Enter open  Esc/b back  q quit";
+
+// what a streaming scan hands the dashboard; the scan is over once the sender is dropped
+pub enum Feed {
+    Trade(ParsedTransaction),
+    Event(DetectionEvent),
+    // a transaction the scan skipped, with why
+    Failure(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum View {
+    Trades,
+    Attacks,
+    Attackers,
+    Timeline,
+}
+
+impl View {
+    const ALL: [View; 4] = [View::Trades, View::Attacks, View::Attackers, View::Timeline];
+
+    fn title(self) -> &'static str {
+        match self {
+            View::Trades => "Trades",
+            View::Attacks => "Attacks",
+            View::Attackers => "Attackers",
+            View::Timeline => "Timeline",
+        }
+    }
+
+    fn position(self) -> usize {
+        View::ALL.iter().position(|view| *view == self).unwrap_or(0)
+    }
+}
+
+struct Attack {
+    kind: &'static str,
+    victim: ParsedTransaction,
+    // victim included, in execution order
+    legs: Vec<(&'static str, ParsedTransaction)>,
+    net_profit: i64,
+    notes: Vec<String>,
+}
+
+#[derive(PartialEq)]
+enum Scan {
+    Finished,
+    Streaming,
+    Stopped,
+}
+
+struct Dashboard {
+    mint: String,
+    trades: VecDeque<ParsedTransaction>,
+    // every trade seen, including the ones the trades tab let go
+    trade_count: usize,
+    slot_trades: BTreeMap<u64, usize>,
+    summary: DetectionSummary,
+    attacks: Vec<Attack>,
+    // role of every attack leg, keyed by signature and leg index
+    roles: HashMap<(Arc<str>, usize), &'static str>,
+    // slot, trades, attacks whose victim landed there
+    timeline: Vec<(u64, usize, usize)>,
+    scan: Scan,
+    failures: usize,
+    view: View,
+    cursors: [usize; 4],
+    offsets: [usize; 4],
+    detail: Option<usize>,
+    // table rows on screen at the last frame, for paging
+    page: usize,
+    status: String,
+}
+
+// before the scan, so a redirected run fails before it spends its RPC budget
+pub fn require_terminal() -> Result<(), Error> {
+    if io::stdout().is_terminal() {
+        Ok(())
+    } else {
+        Err(Error::Usage("--output tui needs a terminal on stdout".to_string()))
+    }
+}
+
+// browses a finished scan
+pub fn run(mint: &str, trades: &[ParsedTransaction], summary: &DetectionSummary) -> Result<(), Error> {
+    require_terminal()?;
+    let mut dashboard = Dashboard::new(mint, Scan::Finished);
+    for trade in trades {
+        dashboard.push_trade(trade.clone(), None);
+    }
+    dashboard.set_summary(summary.clone());
+    show(&mut dashboard, |_| {})
+}
+
+// Follows a streaming scan until the user quits, which also stops the scan: the pipeline's
+// next send fails. The summary shown is rebuilt from the detections at most once a frame.
+pub fn live(mint: &str, cfg: &DetectorConfig, min_severity: Severity, feed: Receiver<Feed>) -> Result<(), Error> {
+    let mut dashboard = Dashboard::new(mint, Scan::Streaming);
+    let mut collector = SummaryCollector::default();
+    show(&mut dashboard, |dashboard| {
+        if dashboard.scan != Scan::Streaming {
+            return;
+        }
+        let mut detected = false;
+        for _ in 0..FEED_PER_FRAME {
+            match feed.try_recv() {
+                Ok(Feed::Trade(trade)) => {
+                    collector.count_trade(&trade);
+                    dashboard.push_trade(trade, Some(LIVE_TRADES));
+                }
+                Ok(Feed::Event(event)) => {
+                    collector.record(event);
+                    detected = true;
+                }
+                Ok(Feed::Failure(message)) => {
+                    dashboard.failures += 1;
+                    dashboard.status = message;
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    dashboard.scan = Scan::Stopped;
+                    break;
+                }
+            }
+        }
+        if detected {
+            // the per-mint windows only show in the mint stats, which the dashboard leaves out
+            let mut summary = collector.clone().finish(cfg, &HashMap::new());
+            summary.retain_min_severity(min_severity, cfg);
+            summary.token_decimals = Some(cfg.token_decimals);
+            dashboard.set_summary(summary);
+        } else {
+            dashboard.rebuild_timeline();
+        }
+    })
+}
+
+// raw mode on the alternate screen until `q`; `take_feed` runs before every frame
+fn show(dashboard: &mut Dashboard, mut take_feed: impl FnMut(&mut Dashboard)) -> Result<(), Error> {
+    let mut terminal = ratatui::try_init().map_err(|e| Error::io("set up dashboard on", "the terminal", e))?;
+    let result = event_loop(&mut terminal, dashboard, &mut take_feed);
+    ratatui::restore();
+    result.map_err(|e| Error::io("draw dashboard on", "the terminal", e))
+}
+
+fn event_loop(
+    terminal: &mut DefaultTerminal,
+    dashboard: &mut Dashboard,
+    take_feed: &mut impl FnMut(&mut Dashboard),
+) -> io::Result<()> {
+    loop {
+        take_feed(dashboard);
+        terminal.draw(|frame| dashboard.draw(frame))?;
+        if !event::poll(FRAME)? {
+            continue;
+        }
+        if let Event::Key(key) = event::read()?
+            && key.kind == KeyEventKind::Press
+            && !dashboard.handle(key)
+        {
+            return Ok(());
+        }
+    }
+}
+
+impl Dashboard {
+    fn new(mint: &str, scan: Scan) -> Self {
+        Self {
+            mint: mint.to_string(),
+            trades: VecDeque::new(),
+            trade_count: 0,
+            slot_trades: BTreeMap::new(),
+            summary: DetectionSummary::default(),
+            attacks: Vec::new(),
+            roles: HashMap::new(),
+            timeline: Vec::new(),
+            scan,
+            failures: 0,
+            view: View::Attacks,
+            cursors: [0; 4],
+            offsets: [0; 4],
+            detail: None,
+            page: 1,
+            status: String::new(),
+        }
+    }
+
+    // a cursor on the newest trade stays on the newest trade
+    fn push_trade(&mut self, trade: ParsedTransaction, keep: Option<usize>) {
+        let trades = View::Trades.position();
+        let following = self.cursors[trades] + 1 >= self.trades.len();
+        self.trade_count += 1;
+        *self.slot_trades.entry(trade.slot).or_default() += 1;
+        self.trades.push_back(trade);
+        if keep.is_some_and(|keep| self.trades.len() > keep) {
+            self.trades.pop_front();
+            self.cursors[trades] = self.cursors[trades].saturating_sub(1);
+            self.offsets[trades] = self.offsets[trades].saturating_sub(1);
+        }
+        if following {
+            self.cursors[trades] = self.trades.len() - 1;
+        }
+    }
+
+    fn set_summary(&mut self, summary: DetectionSummary) {
+        let mut attacks = Vec::new();
+        for det in &summary.sandwiches {
+            let mut legs: Vec<(&'static str, ParsedTransaction)> =
+                det.frontruns.iter().map(|leg| ("frontrun", leg.clone())).collect();
+            legs.push(("victim", det.victim.clone()));
+            legs.extend(det.backruns.iter().map(|leg| ("backrun", leg.clone())));
+            let mut notes = vec![
+                format!("Confidence {:.0}%  severity {}", det.confidence * 100.0, det.severity.name()),
+                format!(
+                    "Gross {:.6} SOL  fees {:.6}  tips {:.6}",
+                    det.gross_profit_sol.as_sol(),
+                    (det.costs.fees as i64).as_sol(),
+                    (det.costs.tips as i64).as_sol()
+                ),
+            ];
+            if let Some(leader) = &det.leader {
+                notes.push(format!("Block leader {}", leader));
+            }
+            if let Some(atomic) = det.atomic {
+                notes.push(format!("Atomic ({})", atomic.name()));
+            }
+            if !det.known_attackers.is_empty() {
+                notes.push(format!("Known bots {}", det.known_attackers.join(", ")));
+            }
+            attacks.push(Attack {
+                kind: "sandwich",
+                victim: det.victim.clone(),
+                legs,
+                net_profit: det.net_profit_sol,
+                notes,
+            });
+        }
+        for arb in &summary.backrun_arbs {
+            attacks.push(Attack {
+                kind: "backrun arb",
+                victim: arb.victim.clone(),
+                legs: vec![
+                    ("victim", arb.victim.clone()),
+                    ("backrun", arb.backrun.clone()),
+                    ("close", arb.close.clone()),
+                ],
+                net_profit: arb.net_profit_sol,
+                notes: vec![format!("Gross {:.6} SOL", arb.gross_profit_sol.as_sol())],
+            });
+        }
+
+        self.roles = attacks
+            .iter()
+            .flat_map(|attack| attack.legs.iter())
+            .map(|(role, leg)| ((leg.signature.clone(), leg.leg_index), *role))
+            .collect();
+        self.attacks = attacks;
+        self.summary = summary;
+        self.rebuild_timeline();
+    }
+
+    fn rebuild_timeline(&mut self) {
+        let mut slots: BTreeMap<u64, (usize, usize)> =
+            self.slot_trades.iter().map(|(&slot, &trades)| (slot, (trades, 0))).collect();
+        for attack in &self.attacks {
+            slots.entry(attack.victim.slot).or_default().1 += 1;
+        }
+        self.timeline = slots.into_iter().map(|(slot, (trades, attacks))| (slot, trades, attacks)).collect();
+    }
+
+    fn rows(&self) -> usize {
+        match self.view {
+            View::Trades => self.trades.len(),
+            View::Attacks => self.attacks.len(),
+            View::Attackers => self.summary.attacker_leaderboard.len(),
+            View::Timeline => self.timeline.len(),
+        }
+    }
+
+    fn cursor(&mut self) -> &mut usize {
+        &mut self.cursors[self.view.position()]
+    }
+
+    fn move_cursor(&mut self, delta: isize) {
+        let last = self.rows().saturating_sub(1);
+        let cursor = self.cursor();
+        *cursor = cursor.saturating_add_signed(delta).min(last);
+    }
+
+    fn switch(&mut self, view: View) {
+        self.view = view;
+        self.detail = None;
+    }
+
+    // the attack under the cursor, or the one the selected trade is a leg of
+    fn open(&mut self) {
+        let cursor = *self.cursor();
+        let attack = match self.view {
+            View::Attacks => (cursor < self.attacks.len()).then_some(cursor),
+            View::Trades => self.trades.get(cursor).and_then(|trade| {
+                self.attacks.iter().position(|attack| {
+                    attack
+                        .legs
+                        .iter()
+                        .any(|(_, leg)| leg.signature == trade.signature && leg.leg_index == trade.leg_index)
+                })
+            }),
+            View::Attackers | View::Timeline => None,
+        };
+        match attack {
+            Some(index) => {
+                self.view = View::Attacks;
+                *self.cursor() = index;
+                self.detail = Some(index);
+            }
+            None if self.view == View::Trades => self.status = "Not part of an attack".to_string(),
+            None => {}
+        }
+    }
+
+    // false once the user quits
+    fn handle(&mut self, key: KeyEvent) -> bool {
+        self.status.clear();
+        let page = self.page.max(1) as isize;
+        match key.code {
+            KeyCode::Char('q') => return false,
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return false,
+            KeyCode::Char('t') => self.switch(View::Trades),
+            KeyCode::Char('a') => self.switch(View::Attacks),
+            KeyCode::Char('w') => self.switch(View::Attackers),
+            KeyCode::Char('l') => self.switch(View::Timeline),
+            KeyCode::Tab => self.switch(View::ALL[(self.view.position() + 1) % View::ALL.len()]),
+            KeyCode::BackTab => self.switch(View::ALL[(self.view.position() + View::ALL.len() - 1) % View::ALL.len()]),
+            KeyCode::Esc | KeyCode::Backspace | KeyCode::Char('b') => self.detail = None,
+            KeyCode::Down | KeyCode::Char('j') => self.move_cursor(1),
+            KeyCode::Up | KeyCode::Char('k') => self.move_cursor(-1),
+            KeyCode::PageDown => self.move_cursor(page),
+            KeyCode::PageUp => self.move_cursor(-page),
+            KeyCode::Home | KeyCode::Char('g') => *self.cursor() = 0,
+            KeyCode::End | KeyCode::Char('G') => *self.cursor() = self.rows().saturating_sub(1),
+            KeyCode::Enter if self.detail.is_none() => self.open(),
+            _ => {}
+        }
+        true
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [header, tabs, body, help] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Length(1),
+            Constraint::Fill(1),
+            Constraint::Length(2),
+        ])
+        .areas(frame.area());
+
+        let extracted: i64 = self.summary.sandwiches.iter().map(|det| det.net_profit_sol).sum();
+        let scan = match self.scan {
+            Scan::Finished => String::new(),
+            Scan::Streaming => "  scanning…".to_string(),
+            Scan::Stopped => "  scan finished".to_string(),
+        };
+        let failed = if self.failures > 0 {
+            format!("  failed {}", self.failures)
+        } else {
+            String::new()
+        };
+        frame.render_widget(
+            Paragraph::new(format!(
+                "Sandwich dashboard {}{}  trades {}  sandwiches {}  backrun arbs {}  extracted {:.4} SOL{}{}",
+                self.summary
+                    .token_metadata
+                    .as_ref()
+                    .and_then(|metadata| metadata.label())
+                    .map(|label| format!("{} ", label))
+                    .unwrap_or_default(),
+                self.mint,
+                self.trade_count,
+                self.summary.sandwiches.len(),
+                self.summary.backrun_arbs.len(),
+                extracted.as_sol(),
+                scan,
+                failed
+            ))
+            .style(Style::new().add_modifier(Modifier::BOLD)),
+            header,
+        );
+        frame.render_widget(
+            Tabs::new(View::ALL.iter().map(|view| view.title()))
+                .select(self.view.position())
+                .highlight_style(Style::new().add_modifier(Modifier::BOLD | Modifier::REVERSED)),
+            tabs,
+        );
+        match self.detail.and_then(|index| self.attacks.get(index)) {
+            Some(attack) => self.draw_detail(frame, body, attack),
+            None => self.draw_table(frame, body),
+        }
+        let dim = Style::new().add_modifier(Modifier::DIM);
+        frame.render_widget(
+            Paragraph::new(vec![Line::styled(HELP, dim), Line::raw(self.status.as_str())]),
+            help,
+        );
+    }
+
+    // only the rows on screen are built, so a long trade list stays cheap to redraw
+    fn draw_table(&mut self, frame: &mut Frame, area: Rect) {
+        let rows = self.rows();
+        // less the column names
+        self.page = (area.height as usize).saturating_sub(1).max(1);
+        let position = self.view.position();
+        let cursor = self.cursors[position].min(rows.saturating_sub(1));
+        let offset = &mut self.offsets[position];
+        if cursor < *offset {
+            *offset = cursor;
+        } else if cursor >= *offset + self.page {
+            *offset = cursor + 1 - self.page;
+        }
+        let offset = *offset;
+
+        let (header, widths): (&[&str], &[u16]) = match self.view {
+            View::Trades => (
+                &["slot", "index", "role", "side", "wallet", "ΔSOL", "Δtoken", "signature"],
+                &[11, 5, 9, 4, 11, 14, 16, 11],
+            ),
+            View::Attacks => (
+                &["#", "type", "slot", "time", "victim", "loss SOL", "profit SOL", "legs"],
+                &[4, 11, 11, 23, 11, 14, 14, 4],
+            ),
+            View::Attackers => (
+                &["attacker", "attacks", "victims", "profit SOL", "avg SOL", "win rate", "known bot"],
+                &[11, 7, 7, 14, 14, 8, 20],
+            ),
+            View::Timeline => (&["slot", "trades", "attacks", "█ attack  ░ trade"], &[11, 6, 7, 62]),
+        };
+        let visible: Vec<Row> = (offset..rows.min(offset + self.page))
+            .map(|index| Row::new(self.cells(index)))
+            .collect();
+        if visible.is_empty() {
+            frame.render_widget(
+                Paragraph::new("nothing to show").style(Style::new().add_modifier(Modifier::DIM)),
+                area,
+            );
+            return;
+        }
+        let table = Table::new(visible, widths.iter().map(|width| Constraint::Length(*width)))
+            .header(Row::new(header.to_vec()).style(Style::new().add_modifier(Modifier::BOLD)))
+            .row_highlight_style(Style::new().add_modifier(Modifier::REVERSED))
+            .highlight_symbol("> ");
+        let mut state = TableState::new().with_selected(cursor - offset);
+        frame.render_stateful_widget(table, area, &mut state);
+    }
+
+    fn cells(&self, index: usize) -> Vec<String> {
+        let decimals = self.summary.decimals();
+        match self.view {
+            View::Trades => {
+                let trade = &self.trades[index];
+                vec![
+                    trade.slot.to_string(),
+                    trade.tx_index.map(|index| index.to_string()).unwrap_or_else(|| "-".to_string()),
+                    self.roles
+                        .get(&(trade.signature.clone(), trade.leg_index))
+                        .copied()
+                        .unwrap_or("")
+                        .to_string(),
+                    trade.trade_type.name().to_string(),
+                    short_sig(&trade.signer),
+                    format!("{:+.6}", trade.sol_change.as_sol()),
+                    token::format_tokens(trade.token_change, decimals),
+                    short_sig(&trade.signature),
+                ]
+            }
+            View::Attacks => {
+                let attack = &self.attacks[index];
+                vec![
+                    (index + 1).to_string(),
+                    attack.kind.to_string(),
+                    attack.victim.slot.to_string(),
+                    attack.victim.block_time.map(format_utc).unwrap_or_default(),
+                    short_sig(&attack.victim.signer),
+                    format!("{:.6}", (victim_loss_lamports(&attack.victim) as i64).as_sol()),
+                    format!("{:.6}", attack.net_profit.as_sol()),
+                    (attack.legs.len() - 1).to_string(),
+                ]
+            }
+            View::Attackers => {
+                let stats = &self.summary.attacker_leaderboard[index];
+                let win_rate = self
+                    .summary
+                    .attacker_performance
+                    .iter()
+                    .find(|perf| perf.attacker == stats.attacker)
+                    .map(|perf| format!("{:.0}%", perf.win_rate * 100.0))
+                    .unwrap_or_default();
+                vec![
+                    short_sig(&stats.attacker),
+                    stats.attacks.to_string(),
+                    stats.victims.to_string(),
+                    format!("{:.6}", stats.total_profit_lamports.as_sol()),
+                    format!("{:.6}", stats.avg_profit_lamports.as_sol()),
+                    win_rate,
+                    stats.known_bot.clone().unwrap_or_default(),
+                ]
+            }
+            View::Timeline => {
+                let (slot, trades, attacks) = self.timeline[index];
+                vec![
+                    slot.to_string(),
+                    trades.to_string(),
+                    attacks.to_string(),
+                    format!("{}{}", "█".repeat(attacks), "░".repeat(trades.saturating_sub(attacks).min(60))),
+                ]
+            }
+        }
+    }
+
+    fn draw_detail(&self, frame: &mut Frame, area: Rect, attack: &Attack) {
+        let [notes, legs] =
+            Layout::vertical([Constraint::Length(attack.notes.len() as u16 + 2), Constraint::Fill(1)]).areas(area);
+        let mut lines = vec![Line::styled(
+            format!(
+                "{} at slot {}  victim {}  loss {:.6} SOL  attacker profit {:.6} SOL",
+                attack.kind,
+                attack.victim.slot,
+                attack.victim.signer,
+                (victim_loss_lamports(&attack.victim) as i64).as_sol(),
+                attack.net_profit.as_sol()
+            ),
+            Style::new().add_modifier(Modifier::BOLD),
+        )];
+        lines.extend(attack.notes.iter().map(|note| Line::raw(note.as_str())));
+        frame.render_widget(Paragraph::new(lines), notes);
+
+        let decimals = self.summary.decimals();
+        let rows = attack.legs.iter().map(|(role, leg)| {
+            Row::new(vec![
+                role.to_string(),
+                leg.slot.to_string(),
+                leg.tx_index.map(|index| index.to_string()).unwrap_or_else(|| "-".to_string()),
+                leg.venue.name().to_string(),
+                leg.trade_type.name().to_string(),
+                leg.signer.to_string(),
+                format!("{:+.6}", leg.sol_change.as_sol()),
+                token::format_tokens(leg.token_change, decimals),
+                format!("{:.6}", (leg.tip as i64).as_sol()),
+                leg.signature.to_string(),
+            ])
+        });
+        let widths = [9, 11, 5, 9, 4, 44, 14, 16, 10, 88].map(Constraint::Length);
+        let header = ["role", "slot", "index", "venue", "side", "wallet", "ΔSOL", "Δtoken", "tip SOL", "signature"];
+        frame.render_widget(
+            Table::new(rows, widths).header(Row::new(header).style(Style::new().add_modifier(Modifier::BOLD))),
+            legs,
+        );
+    }
+}
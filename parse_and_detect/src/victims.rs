@@ -0,0 +1,102 @@
+use crate::detect::{DetectionSummary, LamportsExt};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+
+// What one wallet lost to sandwiches, for outreach or restitution
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VictimStats {
+    pub victim: String,
+    pub times_sandwiched: usize,
+    pub total_loss_lamports: u64,
+    pub worst_loss_lamports: u64,
+    pub worst_signature: String,
+    pub worst_slot: u64,
+    pub attackers: Vec<String>,
+    pub mints: Vec<String>,
+    // each loss at the SOL price of its own block, when the summary has a valuation
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total_loss_usd: Option<f64>,
+}
+
+#[derive(Default)]
+struct VictimTally<'a> {
+    attacks: usize,
+    loss: u64,
+    worst: Option<(u64, &'a str, u64)>,
+    attackers: BTreeSet<&'a str>,
+    mints: BTreeSet<&'a str>,
+    loss_usd: Option<f64>,
+}
+
+// largest total loss first
+pub fn victim_losses(summary: &DetectionSummary) -> Vec<VictimStats> {
+    let mut tallies: BTreeMap<&str, VictimTally> = BTreeMap::new();
+    for det in &summary.sandwiches {
+        let loss = det.victim_loss();
+        let tally = tallies.entry(det.victim.signer.as_ref()).or_default();
+        tally.attacks += 1;
+        tally.loss += loss;
+        if tally.worst.is_none_or(|(worst, _, _)| loss > worst) {
+            tally.worst = Some((loss, det.victim.signature.as_ref(), det.victim.slot));
+        }
+        tally
+            .attackers
+            .extend(det.frontruns.iter().chain(det.backruns.iter()).map(|leg| leg.signer.as_ref()));
+        tally.mints.insert(det.victim.mint.as_ref());
+        if let Some(usd) = summary
+            .usd
+            .as_ref()
+            .and_then(|usd| usd.usd(&det.victim.signature, loss as i64))
+        {
+            *tally.loss_usd.get_or_insert(0.0) += usd;
+        }
+    }
+
+    let mut victims: Vec<VictimStats> = tallies
+        .into_iter()
+        .map(|(victim, tally)| {
+            let (worst_loss, worst_signature, worst_slot) = tally.worst.unwrap_or_default();
+            VictimStats {
+                victim: victim.to_string(),
+                times_sandwiched: tally.attacks,
+                total_loss_lamports: tally.loss,
+                worst_loss_lamports: worst_loss,
+                worst_signature: worst_signature.to_string(),
+                worst_slot,
+                attackers: tally.attackers.into_iter().map(str::to_string).collect(),
+                mints: tally.mints.into_iter().map(str::to_string).collect(),
+                total_loss_usd: tally.loss_usd,
+            }
+        })
+        .collect();
+    victims.sort_by(|a, b| {
+        b.total_loss_lamports
+            .cmp(&a.total_loss_lamports)
+            .then_with(|| a.victim.cmp(&b.victim))
+    });
+    victims
+}
+
+// one row per wallet; attackers and mints are `;`-separated so the file opens in a spreadsheet
+pub fn victims_csv(victims: &[VictimStats]) -> String {
+    let mut csv = String::from(
+        "victim,times_sandwiched,total_loss_sol,total_loss_usd,worst_loss_sol,worst_signature,worst_slot,attackers,mints\n",
+    );
+    for victim in victims {
+        let _ = writeln!(
+            csv,
+            "{},{},{:.9},{},{:.9},{},{},{},{}",
+            victim.victim,
+            victim.times_sandwiched,
+            (victim.total_loss_lamports as i64).as_sol(),
+            victim.total_loss_usd.map(|usd| format!("{:.2}", usd)).unwrap_or_default(),
+            (victim.worst_loss_lamports as i64).as_sol(),
+            victim.worst_signature,
+            victim.worst_slot,
+            victim.attackers.join(";"),
+            victim.mints.join(";")
+        );
+    }
+    csv
+}
@@ -0,0 +1,27 @@
+use crate::embed::{self, DetectOptions};
+use crate::error::Error;
+use wasm_bindgen::prelude::*;
+
+// Entry points for the wasm32 build (`--no-default-features --features wasm`). Everything crosses
+// the boundary as JSON text, and errors are thrown as their message.
+
+fn thrown(error: Error) -> JsValue {
+    JsValue::from_str(&error.to_string())
+}
+
+// A pasted `getTransaction` response (JSON encoding) -> the JSON array of its trades on `mint`
+#[wasm_bindgen(js_name = parseTransaction)]
+pub fn parse_transaction(response: &str, mint: &str) -> Result<String, JsValue> {
+    let trades = embed::parse_transaction_json(response, mint).map_err(thrown)?;
+    serde_json::to_string(&trades).map_err(|e| thrown(Error::json("trades", e)))
+}
+
+// A `--save-trades` file or an array of trades -> the detection summary as JSON. `options` is a
+// JSON object of `DetectOptions` keys, or empty.
+#[wasm_bindgen(js_name = detectSandwiches)]
+pub fn detect_sandwiches(trades: &str, options: &str) -> Result<String, JsValue> {
+    let saved = embed::read_trades(trades).map_err(thrown)?;
+    let options = DetectOptions::from_json(options).map_err(thrown)?;
+    let summary = embed::detect_saved(saved, options).map_err(thrown)?;
+    serde_json::to_string(&summary).map_err(|e| thrown(Error::json("detection summary", e)))
+}
@@ -0,0 +1,2992 @@
+use proptest::prelude::*;
+use rusty::amm::{FeeSchedule, FeeTier, PumpAmmState};
+use rusty::botlist::BotList;
+use rusty::collusion::AttackerEntities;
+use rusty::copytrade::detect_copy_traders;
+use rusty::cuprice::{SlotPrices, fetch_block_cu_prices, rank_cu_prices, trade_cu_prices};
+use rusty::dataset::CandidateFeatures;
+use rusty::detect::{
+    Atomicity, AttackCosts, DetectorConfig, FrontRunEvent, SandwichDetection, Severity, VictimMode, detect_parallel,
+    detect_wide_attacks, detect_with, direction_profile,
+};
+use rusty::detector::{CustomDetection, Detector, Window};
+use rusty::diff::diff_summaries;
+use rusty::embed::{self, DetectOptions};
+use rusty::error::Error;
+use rusty::failover::{FailoverSource, endpoint_name};
+use rusty::fetch::{FixtureSource, SavedTrades, SIGNATURE_PAGE, TransactionSource, decode_transaction};
+use rusty::funding::{FundingChain, TraceOptions, shared_funders, trace_funding};
+use rusty::leaderboard::{attacker_leaderboard, attacker_performance};
+use rusty::leaders::{LeaderStats, attach_slot_leaders, leader_stats};
+use rusty::leaderschedule::LeaderSchedules;
+use rusty::network::PUMP_PROGRAM_ID;
+use rusty::parser::pumpfun::{
+    CurveDelta, ExecutionOutcome, LifecycleEvent, LifecycleKind, ParsedTransaction, TradeType, Venue,
+    decode_instruction_data, parse_transaction,
+};
+use rusty::price::{FixedPrice, PriceFeed, format_usd, value_summary};
+use rusty::pricehistory::{CachedFeed, PriceHistory};
+use rusty::pumpswap::{
+    self, MIGRATION_FEE_LAMPORTS, MIGRATION_RESERVED_TOKENS, Market, PoolFees, PumpSwapPool,
+};
+use rusty::ratelimit::{RateLimitedSource, RateLimiter};
+use rusty::registry::AttackerRegistry;
+use rusty::rugpull::{InsiderRole, detect_dev_dumps};
+use rusty::scoring::{Scorer, apply_scorer};
+use rusty::sink::{self, EventSink};
+use rusty::snapshot::BondingCurveAccount;
+use rusty::sniping::detect_snipers;
+use rusty::sweep::{SweepGrid, run_sweep};
+use rusty::streaming::{DetectionEvent, StreamingDetector, SummaryCollector};
+use rusty::token::{self, MintMetadata};
+use rusty::trends::{Period, period_stats};
+use solana_sdk::epoch_schedule::EpochSchedule;
+use solana_sdk::pubkey::Pubkey;
+use solana_transaction_status::{
+    EncodedConfirmedTransactionWithStatusMeta, EncodedTransaction, UiTransactionEncoding,
+};
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::process::Command;
+use std::sync::{Arc, Mutex};
+use std::str::FromStr;
+use std::thread;
+use std::time::{Duration, Instant};
+
+const MINT: &str = "4LTksEDzHp7X2keAjU8kumFHtydEDkX2td43xx9w8B3G";
+const ATA_RENT: u64 = 2_039_280;
+
+fn load(name: &str) -> (EncodedConfirmedTransactionWithStatusMeta, String) {
+    let path = format!("{}/fixtures/{}.json", env!("CARGO_MANIFEST_DIR"), name);
+    let raw = fs::read_to_string(&path).unwrap_or_else(|e| panic!("reading {}: {}", path, e));
+    let tx: EncodedConfirmedTransactionWithStatusMeta =
+        serde_json::from_str(&raw).unwrap_or_else(|e| panic!("decoding {}: {}", path, e));
+    let EncodedTransaction::Json(ui) = &tx.transaction.transaction else {
+        panic!("fixture {} is not JSON-encoded", name);
+    };
+    let signature = ui.signatures[0].clone();
+    (tx, signature)
+}
+
+fn parse_fixture(name: &str) -> Vec<ParsedTransaction> {
+    let (tx, signature) = load(name);
+    parse_transaction(&tx, &signature, MINT, PUMP_PROGRAM_ID).unwrap_or_else(|e| panic!("parsing {}: {}", name, e))
+}
+
+#[test]
+fn parses_direct_buy_with_new_token_account() {
+    let legs = parse_fixture("buy");
+    assert_eq!(legs.len(), 1);
+    let leg = &legs[0];
+    assert_eq!(&*leg.signer, "3oi7bCYXnkuyZ5UnUc7JRUJMe69jnVMcpggHN3RjZLDE");
+    assert_eq!(leg.slot, 350_000_000);
+    assert_eq!(leg.block_time, Some(1_747_000_000));
+    assert_eq!(leg.tx_index, None);
+    assert_eq!(leg.trade_type, TradeType::Buy);
+    assert_eq!(leg.token_amount_requested, 35_000_000_000_000);
+    assert_eq!(leg.sol_limit_specified, 1_050_000_000);
+    assert_eq!(leg.token_change, 35_000_000_000_000);
+    assert_eq!(leg.fee, 5_000);
+    assert_eq!(leg.ata_rent, ATA_RENT);
+    assert_eq!(leg.sol_change, -(1_000_000_000 + ATA_RENT as i64 + 5_000));
+    assert_eq!(leg.trade_sol_change(), -1_000_000_000);
+    assert_eq!(leg.outcome, ExecutionOutcome::Succeeded);
+
+    let curve = leg.curve.as_ref().unwrap();
+    assert_eq!(curve.account, "EnnmSAG2VAF176zyhFF4h98z7cUJBhrvoPQqFJ6hJcpn");
+    assert_eq!(curve.sol_change, 990_000_000);
+    assert_eq!(curve.token_change, -35_000_000_000_000);
+    assert_eq!(leg.matches_curve(), Some(true));
+    let reserves = PumpAmmState::from_curve_snapshot(leg, &FeeSchedule::default()).unwrap();
+    assert_eq!(reserves.real_token, 693_100_000_000_000);
+}
+
+#[test]
+fn parses_direct_sell() {
+    let legs = parse_fixture("sell");
+    assert_eq!(legs.len(), 1);
+    let leg = &legs[0];
+    assert_eq!(leg.trade_type, TradeType::Sell);
+    assert_eq!(leg.token_amount_requested, 35_000_000_000_000);
+    assert_eq!(leg.sol_limit_specified, 950_000_000);
+    assert_eq!(leg.token_change, -35_000_000_000_000);
+    assert_eq!(leg.ata_rent, 0);
+    assert_eq!(leg.sol_change, 980_000_000 - 5_000);
+    assert_eq!(leg.trade_sol_change(), 980_000_000);
+}
+
+#[test]
+fn parses_buy_made_through_an_inner_cpi() {
+    let legs = parse_fixture("cpi_buy");
+    assert_eq!(legs.len(), 1);
+    let leg = &legs[0];
+    assert_eq!(&*leg.signer, "7VM3Wq7ejmSc1GQ993mjguzBhcm8pfLvCiU7dNoyeJwk");
+    assert_eq!(leg.trade_type, TradeType::Buy);
+    assert_eq!(leg.token_amount_requested, 10_000_000_000_000);
+    assert_eq!(leg.token_change, 10_000_000_000_000);
+    assert_eq!(leg.fee, 10_000);
+    assert_eq!(leg.trade_sol_change(), -300_000_000);
+}
+
+#[test]
+fn decodes_pasted_responses_with_or_without_their_envelope() {
+    let (_, signature) = load("buy");
+    let raw = fs::read_to_string(format!("{}/fixtures/buy.json", env!("CARGO_MANIFEST_DIR"))).unwrap();
+    let enveloped = format!(r#"{{"jsonrpc":"2.0","id":1,"result":{}}}"#, raw);
+    for response in [raw.as_str(), enveloped.as_str()] {
+        let (tx, decoded) = decode_transaction(response).unwrap();
+        assert_eq!(decoded, signature);
+        assert_eq!(parse_transaction(&tx, &decoded, MINT, PUMP_PROGRAM_ID).unwrap().len(), 1);
+    }
+    assert!(decode_transaction(r#"{"jsonrpc":"2.0","id":1,"result":null}"#).is_err());
+}
+
+#[test]
+fn splits_router_transaction_into_legs() {
+    let legs = parse_fixture("multi_leg");
+    assert_eq!(legs.len(), 2);
+    let (buy, sell) = (&legs[0], &legs[1]);
+    assert_eq!(buy.signature, sell.signature);
+    assert_eq!((buy.leg_index, sell.leg_index), (0, 1));
+
+    assert_eq!(buy.trade_type, TradeType::Buy);
+    assert_eq!(buy.token_change, 17_000_000_000_000);
+    assert_eq!(buy.fee, 10_000);
+    assert_eq!(buy.trade_sol_change(), -505_000_000);
+
+    // the sell's proceeds are never transferred by the system program, so they come from the residual
+    assert_eq!(sell.trade_type, TradeType::Sell);
+    assert_eq!(sell.token_change, -10_000_000_000_000);
+    assert_eq!(sell.fee, 0);
+    assert_eq!(sell.sol_change, 290_000_000);
+}
+
+#[test]
+fn saturates_balances_near_u64_max_instead_of_overflowing() {
+    let legs = parse_fixture("malformed/huge_balances");
+    assert_eq!(legs.len(), 2);
+    let (buy, sell) = (&legs[0], &legs[1]);
+    assert_eq!(buy.trade_type, TradeType::Buy);
+    assert_eq!(sell.trade_type, TradeType::Sell);
+    // the buy's two i64::MAX transfers out of the signer and the u64::MAX balance swing both pin
+    // at the i64 bounds; the sell keeps what is left of the signer's delta
+    assert_eq!(buy.sol_change, i64::MIN);
+    assert_eq!(buy.token_change, i64::MAX);
+    assert_eq!(sell.sol_change, 10_000);
+    assert_eq!(sell.token_change, -i64::MAX);
+    detect_wide_attacks(&legs, &DetectorConfig::default());
+}
+
+#[test]
+fn marks_failed_trade_as_reverted() {
+    let legs = parse_fixture("failed_buy");
+    assert_eq!(legs.len(), 1);
+    let leg = &legs[0];
+    assert_eq!(leg.outcome, ExecutionOutcome::Reverted);
+    assert_eq!(leg.token_amount_requested, 50_000_000_000_000);
+    assert_eq!(leg.token_change, 0);
+    assert_eq!(leg.sol_change, -5_000);
+    assert_eq!(leg.trade_sol_change(), 0);
+}
+
+#[test]
+fn reads_only_the_configured_pump_program() {
+    let (tx, signature) = load("buy");
+    let custom = "Pump9GkQm3ZZ6RmwDLuHQcZDtsQ6pBVi7TF1DZ9hzeN";
+    assert!(parse_transaction(&tx, &signature, MINT, custom).unwrap().is_empty());
+
+    // the same buy sent to a custom deployment is read once that deployment is named
+    let path = format!("{}/fixtures/buy.json", env!("CARGO_MANIFEST_DIR"));
+    let raw = fs::read_to_string(&path).unwrap().replace(PUMP_PROGRAM_ID, custom);
+    let tx: EncodedConfirmedTransactionWithStatusMeta = serde_json::from_str(&raw).unwrap();
+    assert!(parse_transaction(&tx, &signature, MINT, PUMP_PROGRAM_ID).unwrap().is_empty());
+    assert_eq!(parse_transaction(&tx, &signature, MINT, custom).unwrap().len(), 1);
+}
+
+#[test]
+fn ignores_other_mints() {
+    let (tx, signature) = load("buy");
+    let legs = parse_transaction(&tx, &signature, "So11111111111111111111111111111111111111112", PUMP_PROGRAM_ID).unwrap();
+    assert!(legs.is_empty());
+}
+
+#[test]
+fn drops_legs_trading_another_mint() {
+    // the router's sell leg, pointed at another token
+    let path = format!("{}/fixtures/multi_leg.json", env!("CARGO_MANIFEST_DIR"));
+    let mut value: serde_json::Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+    value["meta"]["innerInstructions"][0]["instructions"][4]["accounts"][2] =
+        "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v".into();
+    let tx: EncodedConfirmedTransactionWithStatusMeta = serde_json::from_value(value).unwrap();
+    let EncodedTransaction::Json(ui) = &tx.transaction.transaction else {
+        panic!("fixture is not JSON-encoded");
+    };
+
+    let legs = parse_transaction(&tx, &ui.signatures[0], MINT, PUMP_PROGRAM_ID).unwrap();
+    assert_eq!(legs.len(), 1);
+    assert_eq!(legs[0].trade_type, TradeType::Buy);
+    assert_eq!(legs[0].token_change, 17_000_000_000_000);
+    assert_eq!(legs[0].trade_sol_change(), -505_000_000);
+}
+
+#[test]
+fn detects_sandwich_across_fixture_transactions() {
+    let trades: Vec<ParsedTransaction> = ["sandwich_front", "sandwich_victim", "sandwich_back", "buy", "sell"]
+        .iter()
+        .flat_map(|name| parse_fixture(name))
+        .collect();
+    let summary = detect_wide_attacks(&trades, &DetectorConfig::default());
+
+    assert_eq!(summary.sandwiches.len(), 1);
+    let sandwich = &summary.sandwiches[0];
+    assert_eq!(&*sandwich.victim.signer, "A1DxnH9d8tfhxTHn3FL99aoF5rF4UqQRney5RpgmQRii");
+    assert_eq!(sandwich.frontruns.len(), 1);
+    assert_eq!(sandwich.backruns.len(), 1);
+    assert_eq!(&*sandwich.frontruns[0].signer, "4uHqkCs3Y4vZd3Ejax1uhpvDJiqMXsBQE9fxhGAJxq1V");
+    assert_eq!(sandwich.net_profit_sol, 2_100_000_000 - 5_000 - (2_000_000_000 + ATA_RENT as i64 + 5_000));
+    assert_eq!(sandwich.gross_profit_sol, 100_000_000);
+    assert_eq!(sandwich.costs.fees, 10_000);
+    assert_eq!(sandwich.costs.ata_rent, ATA_RENT);
+    assert_eq!(sandwich.net_token_delta, 0);
+    let span = sandwich.latency.span_slots();
+    assert_eq!(span, sandwich.backruns[0].slot - sandwich.frontruns[0].slot);
+    assert_eq!(summary.latency.as_ref().unwrap().span_slots.get(&span), Some(&1));
+
+    assert_eq!(summary.attacker_leaderboard.len(), 1);
+    assert_eq!(*summary.attacker_leaderboard[0].attacker, *sandwich.frontruns[0].signer);
+    assert!(summary.reverted_sandwiches.is_empty());
+}
+
+// a price feed with fixed quotes that remembers the range it was asked for
+struct Quotes {
+    points: Vec<(i64, f64)>,
+    asked: Cell<Option<(i64, i64)>>,
+}
+
+impl PriceFeed for &Quotes {
+    fn sol_usd_history(&self, from: i64, to: i64) -> rusty::error::Result<Vec<(i64, f64)>> {
+        self.asked.set(Some((from, to)));
+        Ok(self.points.clone())
+    }
+}
+
+#[test]
+fn usd_values_each_leg_at_the_nearest_quote_and_skips_untimed_ones() {
+    let trades: Vec<ParsedTransaction> = ["sandwich_front", "sandwich_victim", "sandwich_back"]
+        .iter()
+        .flat_map(|name| parse_fixture(name))
+        .collect();
+    let summary = detect_wide_attacks(&trades, &DetectorConfig::default());
+    let sandwich = &summary.sandwiches[0];
+    let (front, back) = (&sandwich.frontruns[0], &sandwich.backruns[0]);
+    let front_time = front.block_time.unwrap();
+    assert_eq!(back.block_time, Some(front_time + 2));
+
+    let usd = value_summary(&FixedPrice(150.0), &summary).unwrap();
+    let profit = sandwich.net_profit_sol as f64 / 1e9 * 150.0;
+    assert!((usd.total_extracted_usd - profit).abs() < 1e-9, "{}", usd.total_extracted_usd);
+    assert!((usd.sandwich_profit(sandwich).unwrap() - profit).abs() < 1e-9);
+    assert!((usd.attacker_profit(&summary.attacker_leaderboard[0]).unwrap() - profit).abs() < 1e-9);
+    assert_eq!(format_usd(usd.usd(&front.signature, front.sol_change).unwrap()), "-$300.31");
+
+    // the victim sits a second from either quote and takes the earlier one; both legs match theirs
+    let feed = Quotes {
+        points: vec![(front_time + 2, 200.0), (front_time - 30, 90.0), (front_time, 100.0)],
+        asked: Cell::new(None),
+    };
+    let usd = value_summary(&&feed, &summary).unwrap();
+    assert_eq!(feed.asked.get(), Some((front_time - 3_600, front_time + 2 + 3_600)));
+    assert_eq!(usd.price(&sandwich.victim.signature), Some(100.0));
+    assert_eq!((usd.price(&front.signature), usd.price(&back.signature)), (Some(100.0), Some(200.0)));
+    let profit = front.sol_change as f64 / 1e9 * 100.0 + back.sol_change as f64 / 1e9 * 200.0;
+    assert!((usd.total_extracted_usd - profit).abs() < 1e-9, "{}", usd.total_extracted_usd);
+
+    // a leg without a block time is left unpriced, and so is the sandwich it belongs to
+    let detect_untimed = |count: usize| {
+        let mut trades = trades.clone();
+        trades.iter_mut().rev().take(count).for_each(|tx| tx.block_time = None);
+        detect_wide_attacks(&trades, &DetectorConfig::default())
+    };
+    let summary = detect_untimed(1);
+    let usd = value_summary(&FixedPrice(150.0), &summary).unwrap();
+    assert_eq!(usd.price(&back.signature), None);
+    assert_eq!(usd.sandwich_profit(&summary.sandwiches[0]), None);
+    assert!((usd.total_extracted_usd - front.sol_change as f64 / 1e9 * 150.0).abs() < 1e-9);
+
+    // nothing timed asks the feed for nothing; a feed with no quotes is an error
+    let empty = Quotes { points: Vec::new(), asked: Cell::new(None) };
+    assert_eq!(value_summary(&&empty, &detect_untimed(3)).unwrap().total_extracted_usd, 0.0);
+    assert_eq!(empty.asked.get(), None);
+    assert!(matches!(value_summary(&&empty, &summary), Err(Error::Price(_))));
+}
+
+#[test]
+fn price_history_fetches_each_day_once_and_keeps_the_nearest_hourly_quote() {
+    let dir = std::env::temp_dir().join(format!("rusty-prices-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("prices.json");
+    let path = path.to_str().unwrap();
+    // 2025-05-11, long complete: quotes at 09:10, 09:50 and 12:00
+    let day = 20_219 * 86_400;
+    let quotes = Quotes {
+        points: vec![(day + 9 * 3_600 + 600, 150.0), (day + 9 * 3_600 + 3_000, 151.0), (day + 12 * 3_600, 160.0)],
+        asked: Cell::new(None),
+    };
+
+    let feed = CachedFeed::load(&quotes, path).unwrap();
+    let points = feed.sol_usd_history(day + 10 * 3_600, day + 11 * 3_600).unwrap();
+    // the whole day was fetched and bucketed by hour, the first quote in an hour winning
+    assert_eq!(quotes.asked.take(), Some((day, day + 86_400 - 1)));
+    assert_eq!(points, [(day + 9 * 3_600, 150.0), (day + 12 * 3_600, 160.0)]);
+    feed.sol_usd_history(day + 3_600, day + 20 * 3_600).unwrap();
+    assert_eq!(quotes.asked.take(), None);
+
+    // a later run reads the day from disk and only fetches the next one
+    let history = PriceHistory::load(path).unwrap();
+    assert_eq!(history.days.iter().collect::<Vec<_>>(), [&20_219]);
+    assert_eq!(history.missing_days(day, day + 86_400), Some((20_220, 20_220)));
+    let feed = CachedFeed::load(&quotes, path).unwrap();
+    let points = feed.sol_usd_history(day + 86_400, day + 86_400 + 60).unwrap();
+    assert_eq!(quotes.asked.take(), Some((day + 86_400, day + 2 * 86_400 - 1)));
+    assert_eq!(points.len(), 2);
+
+    // a transaction takes whichever cached hour is nearer, and a day without quotes stays empty
+    let trades: Vec<ParsedTransaction> = ["sandwich_front", "sandwich_victim", "sandwich_back"]
+        .iter()
+        .flat_map(|name| parse_fixture(name))
+        .map(|mut tx| {
+            tx.block_time = Some(day + 10 * 3_600 + 1_900);
+            tx
+        })
+        .collect();
+    let summary = detect_wide_attacks(&trades, &DetectorConfig::default());
+    let usd = value_summary(&feed, &summary).unwrap();
+    assert_eq!(usd.price(&summary.sandwiches[0].victim.signature), Some(160.0));
+    let empty = Quotes { points: Vec::new(), asked: Cell::new(None) };
+    let quiet = CachedFeed::load(&empty, dir.join("empty.json").to_str().unwrap()).unwrap();
+    assert!(quiet.sol_usd_history(day, day + 60).unwrap().is_empty());
+    assert!(matches!(value_summary(&quiet, &summary), Err(Error::Price(_))));
+    assert!(PriceHistory::load(dir.join("empty.json").to_str().unwrap()).unwrap().hourly.is_empty());
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+
+#[test]
+fn embedded_detection_reads_bare_trade_lists_and_options() {
+    let trades: Vec<ParsedTransaction> = ["sandwich_front", "sandwich_victim", "sandwich_back"]
+        .iter()
+        .flat_map(|name| parse_fixture(name))
+        .collect();
+    let saved = embed::read_trades(&serde_json::to_string(&trades).unwrap()).unwrap();
+    assert_eq!(saved.mint, MINT);
+
+    let detect = |options: &str| embed::detect_saved(saved.clone(), DetectOptions::from_json(options).unwrap());
+    assert_eq!(detect("").unwrap().sandwiches.len(), 1);
+    assert!(detect(r#"{"min_profit_sol": 1000}"#).unwrap().sandwiches.is_empty());
+    assert!(detect(r#"{"min_victim_abs_sol": -1}"#).is_err());
+    assert!(DetectOptions::from_json(r#"{"max_gap": 5}"#).is_err());
+}
+
+#[cfg(feature = "ffi")]
+#[test]
+fn c_api_returns_owned_json_or_the_last_error() {
+    use rusty::ffi::{rusty_detect, rusty_last_error, rusty_parse_transaction, rusty_string_free};
+    use std::ffi::CStr;
+
+    let raw = fs::read_to_string(format!("{}/fixtures/sandwich_victim.json", env!("CARGO_MANIFEST_DIR"))).unwrap();
+    unsafe {
+        let trades = rusty_parse_transaction(raw.as_ptr(), raw.len(), MINT.as_ptr(), MINT.len());
+        assert!(!trades.is_null());
+        let parsed: Vec<ParsedTransaction> = serde_json::from_slice(CStr::from_ptr(trades).to_bytes()).unwrap();
+        assert_eq!(parsed.len(), 1);
+        rusty_string_free(trades);
+
+        let options = r#"{"max_gap": 5}"#;
+        assert!(rusty_detect(b"[]".as_ptr(), 2, options.as_ptr(), options.len()).is_null());
+        assert!(CStr::from_ptr(rusty_last_error()).to_str().unwrap().contains("max_gap"));
+        assert!(rusty_detect(std::ptr::null(), 1, std::ptr::null(), 0).is_null());
+    }
+}
+
+#[test]
+fn excluded_wallets_are_never_attackers() {
+    let trades: Vec<ParsedTransaction> = ["sandwich_front", "sandwich_victim", "sandwich_back"]
+        .iter()
+        .flat_map(|name| parse_fixture(name))
+        .collect();
+    let mut cfg = DetectorConfig::default();
+    cfg.excluded_wallets.insert("4uHqkCs3Y4vZd3Ejax1uhpvDJiqMXsBQE9fxhGAJxq1V".to_string());
+    assert!(detect_wide_attacks(&trades, &cfg).sandwiches.is_empty());
+}
+
+#[test]
+fn undersized_victims_lower_confidence_before_dropping_out() {
+    let trades: Vec<ParsedTransaction> = ["sandwich_front", "sandwich_victim", "sandwich_back"]
+        .iter()
+        .flat_map(|name| parse_fixture(name))
+        .collect();
+    let baseline = &detect_wide_attacks(&trades, &DetectorConfig::default()).sandwiches[0];
+    let victim_sol = (baseline.victim.trade_sol_change() as f64).abs() / 1e9;
+    let sized = |factor: f64| DetectorConfig {
+        min_victim_abs_sol: victim_sol * factor,
+        min_victim_tokens: f64::MAX,
+        ..DetectorConfig::default()
+    };
+
+    // two thirds of the minimum size: still a sandwich, with less confidence
+    let summary = detect_wide_attacks(&trades, &sized(1.5));
+    assert_eq!(summary.sandwiches.len(), 1);
+    assert!(summary.sandwiches[0].confidence < baseline.confidence);
+    assert!(detect_wide_attacks(&trades, &sized(3.0)).sandwiches.is_empty());
+}
+
+#[test]
+fn direction_profiles_pick_which_legs_match() {
+    let trades: Vec<ParsedTransaction> = ["sandwich_front", "sandwich_victim", "sandwich_back"]
+        .iter()
+        .flat_map(|name| parse_fixture(name))
+        .collect();
+    let with_profile = |name: &str| {
+        let (frontrun_direction, backrun_direction) = direction_profile(name).unwrap();
+        DetectorConfig {
+            frontrun_direction,
+            backrun_direction,
+            ..DetectorConfig::default()
+        }
+    };
+    assert_eq!(detect_wide_attacks(&trades, &with_profile("sandwich")).sandwiches.len(), 1);
+    // the fixture's buy-buy-sell shape is not an inverse one
+    assert!(detect_wide_attacks(&trades, &with_profile("inverse")).sandwiches.is_empty());
+    let any = detect_wide_attacks(&trades, &with_profile("any"));
+    assert_eq!(any.sandwiches.len(), 1);
+    assert_eq!(any.sandwiches[0].frontruns.len(), 1);
+    assert!(direction_profile("sideways").is_err());
+}
+
+#[test]
+fn price_impact_mode_finds_victims_whose_limits_held() {
+    let mut trades: Vec<ParsedTransaction> = ["sandwich_front", "sandwich_victim", "sandwich_back"]
+        .iter()
+        .flat_map(|name| parse_fixture(name))
+        .collect();
+    // the victim asked for no more than it got, so its slippage limit held
+    trades[1].token_amount_requested = trades[1].token_change as u64;
+    let mut cfg = DetectorConfig::default();
+    assert!(detect_wide_attacks(&trades, &cfg).sandwiches.is_empty());
+
+    cfg.victim_mode = VictimMode::parse("price_impact").unwrap();
+    let summary = detect_wide_attacks(&trades, &cfg);
+    assert_eq!(summary.sandwiches.len(), 1);
+    let sandwich = &summary.sandwiches[0];
+    let loss = sandwich.impact_loss_lamports.unwrap();
+    assert!(loss > 0);
+    assert_eq!(sandwich.victim_loss(), loss);
+    assert_eq!(sandwich.severity, Severity::classify_loss(&sandwich.victim, loss));
+
+    // a bar above what the frontrun cost it
+    cfg.min_impact_loss_pct = loss as f64 * 100.0 / sandwich.victim.trade_sol_change().unsigned_abs() as f64 + 1.0;
+    assert!(detect_wide_attacks(&trades, &cfg).sandwiches.is_empty());
+}
+
+#[test]
+fn known_bots_are_tagged_and_raise_confidence() {
+    let trades: Vec<ParsedTransaction> = ["sandwich_front", "sandwich_victim", "sandwich_back"]
+        .iter()
+        .flat_map(|name| parse_fixture(name))
+        .collect();
+    let mut cfg = DetectorConfig::default();
+    let baseline = detect_wide_attacks(&trades, &cfg).sandwiches[0].confidence;
+
+    let bots = BotList::parse("4uHqkCs3Y4vZd3Ejax1uhpvDJiqMXsBQE9fxhGAJxq1V fixture bot # seen in tests\n", "test list")
+        .unwrap();
+    cfg.known_bots = bots.bots.into_iter().collect();
+    let summary = detect_wide_attacks(&trades, &cfg);
+    let sandwich = &summary.sandwiches[0];
+    assert_eq!(sandwich.known_attackers, vec!["4uHqkCs3Y4vZd3Ejax1uhpvDJiqMXsBQE9fxhGAJxq1V"]);
+    assert!(sandwich.confidence > baseline);
+    assert_eq!(summary.attacker_leaderboard[0].known_bot.as_deref(), Some("fixture bot"));
+    assert!(BotList::bundled().is_ok());
+}
+
+#[test]
+fn detects_backrun_arbitrage_without_a_frontrun() {
+    let mut trades: Vec<ParsedTransaction> = ["sandwich_victim", "sandwich_back", "sandwich_front"]
+        .iter()
+        .flat_map(|name| parse_fixture(name))
+        .collect();
+    // the bot buys back after its backrun instead of before the victim
+    let back_slot = trades[1].slot;
+    let close = &mut trades[2];
+    close.slot = back_slot + 1;
+
+    let summary = detect_wide_attacks(&trades, &DetectorConfig::default());
+    assert!(summary.sandwiches.is_empty());
+    assert_eq!(summary.backrun_arbs.len(), 1);
+    let arb = &summary.backrun_arbs[0];
+    assert_eq!(&*arb.victim.signer, "A1DxnH9d8tfhxTHn3FL99aoF5rF4UqQRney5RpgmQRii");
+    assert_eq!(arb.close.trade_type, TradeType::Buy);
+    assert_eq!(arb.gross_profit_sol, 100_000_000);
+    assert_eq!(arb.net_profit_sol, 100_000_000 - 10_000 - ATA_RENT as i64);
+}
+
+#[test]
+fn parallel_detection_matches_a_single_pass() {
+    let trades: Vec<ParsedTransaction> = ["sandwich_front", "sandwich_victim", "sandwich_back", "buy", "sell"]
+        .iter()
+        .flat_map(|name| parse_fixture(name))
+        .collect();
+    // the same trades again on a second mint, one slot later
+    let mut all = trades.clone();
+    all.extend(trades.iter().cloned().map(|mut tx| {
+        tx.mint = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v".into();
+        tx.signature = format!("{}2", tx.signature).into();
+        tx.slot += 1;
+        tx
+    }));
+
+    let cfg = DetectorConfig::default();
+    let sequential = detect_wide_attacks(&all, &cfg);
+    let parallel = detect_parallel(&all, &cfg);
+    assert_eq!(parallel.sandwiches.len(), 2);
+    assert_eq!(
+        serde_json::to_value(&parallel).unwrap(),
+        serde_json::to_value(&sequential).unwrap()
+    );
+}
+
+#[test]
+fn adaptive_gap_widens_the_window_on_quiet_mints() {
+    let mut trades: Vec<ParsedTransaction> = ["sandwich_front", "sandwich_victim", "sandwich_back"]
+        .iter()
+        .flat_map(|name| parse_fixture(name))
+        .collect();
+    assert_eq!(trades[1].slot - trades[0].slot, 1);
+    trades[2].slot = trades[1].slot + 6;
+
+    let mut cfg = DetectorConfig::default();
+    assert!(detect_wide_attacks(&trades, &cfg).sandwiches.is_empty());
+    cfg.adaptive_slot_gap = true;
+    let summary = detect_wide_attacks(&trades, &cfg);
+    assert_eq!(summary.sandwiches.len(), 1);
+    // median distance between trading slots is (1 + 6) / 2 = 3, doubled
+    assert_eq!(summary.mint_stats[0].slot_gap, 6);
+}
+
+// pushes `trades` in chain order and folds the events the way `--stream` does
+fn stream(mut trades: Vec<ParsedTransaction>, cfg: &DetectorConfig) -> rusty::detect::DetectionSummary {
+    trades.sort_by_key(|tx| tx.slot);
+    let mut detector = StreamingDetector::new(cfg.clone());
+    let mut collector = SummaryCollector::default();
+    for tx in trades {
+        collector.count_trade(&tx);
+        for event in detector.push(tx) {
+            collector.record(event);
+        }
+    }
+    collector.finish(cfg, &detector.slot_gaps())
+}
+
+#[test]
+fn streaming_uses_the_adaptive_gap_and_finds_backrun_arbitrage() {
+    let mut quiet: Vec<ParsedTransaction> = ["sandwich_front", "sandwich_victim", "sandwich_back"]
+        .iter()
+        .flat_map(|name| parse_fixture(name))
+        .collect();
+    quiet[2].slot = quiet[1].slot + 6;
+    let cfg = DetectorConfig {
+        adaptive_slot_gap: true,
+        ..DetectorConfig::default()
+    };
+    let streamed = stream(quiet.clone(), &cfg);
+    let batch = detect_wide_attacks(&quiet, &cfg);
+    assert_eq!(streamed.sandwiches.len(), 1);
+    assert_eq!(streamed.mint_stats[0].slot_gap, batch.mint_stats[0].slot_gap);
+
+    let mut arb: Vec<ParsedTransaction> = ["sandwich_victim", "sandwich_back", "sandwich_front"]
+        .iter()
+        .flat_map(|name| parse_fixture(name))
+        .collect();
+    arb[2].slot = arb[1].slot + 1;
+    let cfg = DetectorConfig::default();
+    let streamed = stream(arb.clone(), &cfg);
+    let batch = detect_wide_attacks(&arb, &cfg);
+    assert_eq!(streamed.backrun_arbs.len(), 1);
+    assert_eq!(
+        serde_json::to_value(&streamed.backrun_arbs).unwrap(),
+        serde_json::to_value(&batch.backrun_arbs).unwrap()
+    );
+}
+
+struct FixedScore(f64);
+
+impl Scorer for FixedScore {
+    fn score(&self, _candidate: &SandwichDetection) -> rusty::error::Result<f64> {
+        Ok(self.0)
+    }
+}
+
+#[test]
+fn external_scorer_filters_sandwiches() {
+    let trades: Vec<ParsedTransaction> = ["sandwich_front", "sandwich_victim", "sandwich_back"]
+        .iter()
+        .flat_map(|name| parse_fixture(name))
+        .collect();
+    let cfg = DetectorConfig::default();
+    let mut kept = detect_wide_attacks(&trades, &cfg);
+    assert!(apply_scorer(&mut kept, &trades, &cfg, &FixedScore(0.9), 0.5).is_empty());
+    assert_eq!(kept.sandwiches[0].external_score, Some(0.9));
+
+    let mut dropped = detect_wide_attacks(&trades, &cfg);
+    apply_scorer(&mut dropped, &trades, &cfg, &FixedScore(0.1), 0.5);
+    assert!(dropped.sandwiches.is_empty());
+    assert!(dropped.attacker_leaderboard.is_empty());
+}
+
+#[test]
+fn severity_tiers_follow_the_victims_loss() {
+    let mut trades: Vec<ParsedTransaction> = ["sandwich_front", "sandwich_victim", "sandwich_back"]
+        .iter()
+        .flat_map(|name| parse_fixture(name))
+        .collect();
+    // the victim paid 1 SOL for a fill quoted at 0.85: 0.15 SOL lost, 15% of the trade
+    trades[1].sol_limit_specified = 850_000_000;
+    let cfg = DetectorConfig::default();
+    let mut summary = detect_wide_attacks(&trades, &cfg);
+    assert_eq!(summary.sandwiches[0].severity, Severity::High);
+
+    summary.retain_min_severity(Severity::Critical, &cfg);
+    assert!(summary.sandwiches.is_empty());
+    assert!(summary.attacker_leaderboard.is_empty());
+    assert_eq!(summary.mint_stats[0].total_trades, 3);
+    assert_eq!(summary.mint_stats[0].total_victims, 0);
+}
+
+#[test]
+fn attack_costs_come_from_the_legs_fees_rent_and_tips_on_chain() {
+    let trades: Vec<ParsedTransaction> = ["sandwich_front", "sandwich_victim", "bundled/sandwich_back"]
+        .iter()
+        .flat_map(|name| parse_fixture(name))
+        .collect();
+    let (front, back) = (&trades[0], &trades[2]);
+    // the frontrun opens the attacker's token account; the backrun bids 1 lamport per compute
+    // unit and tips a Jito account from the same transaction
+    assert_eq!((front.fee, front.priority_fee, front.ata_rent, front.tip), (5_000, 0, ATA_RENT, 0));
+    assert_eq!((back.fee, back.priority_fee, back.ata_rent, back.tip), (205_000, 200_000, 0, 10_000_000));
+    assert_eq!(back.compute_unit_price, 1_000_000);
+    assert_eq!(back.trade_sol_change(), 2_100_000_000);
+
+    let summary = detect_wide_attacks(&trades, &DetectorConfig::default());
+    let sandwich = &summary.sandwiches[0];
+    assert_eq!(
+        sandwich.costs,
+        AttackCosts { fees: 210_000, priority_fees: 200_000, ata_rent: ATA_RENT, tips: 10_000_000 }
+    );
+    // priority fees are part of the fees, so they're taken off only once
+    assert_eq!(sandwich.costs.total(), 210_000 + ATA_RENT + 10_000_000);
+    assert_eq!(sandwich.gross_profit_sol, 100_000_000);
+    assert_eq!(sandwich.net_profit_sol, 100_000_000 - sandwich.costs.total() as i64);
+    assert_eq!(sandwich.net_profit_sol, front.sol_change + back.sol_change);
+}
+
+#[test]
+fn jito_tips_come_out_of_the_sandwich_profit() {
+    let mut trades: Vec<ParsedTransaction> = ["sandwich_front", "sandwich_victim", "sandwich_back"]
+        .iter()
+        .flat_map(|name| parse_fixture(name))
+        .collect();
+    let untipped = detect_wide_attacks(&trades, &DetectorConfig::default()).sandwiches[0].net_profit_sol;
+    // the backrun also pays a 0.02 SOL tip out of the signer's balance
+    trades[2].tip = 20_000_000;
+    trades[2].sol_change -= 20_000_000;
+
+    let summary = detect_wide_attacks(&trades, &DetectorConfig::default());
+    let sandwich = &summary.sandwiches[0];
+    assert_eq!(sandwich.costs.tips, 20_000_000);
+    assert_eq!(sandwich.net_profit_sol, untipped - 20_000_000);
+    assert_eq!(rusty::tips::profit_before_tips(sandwich), untipped);
+    let tips = summary.tips.as_ref().unwrap();
+    assert_eq!(tips.tipped_sandwiches, 1);
+    assert_eq!(tips.tip_share_pct, 20.0);
+}
+
+#[test]
+fn sandwiches_inside_one_transaction_or_bundle_are_atomic() {
+    let mut trades: Vec<ParsedTransaction> = ["sandwich_front", "sandwich_victim", "sandwich_back"]
+        .iter()
+        .flat_map(|name| parse_fixture(name))
+        .collect();
+    let cfg = DetectorConfig::default();
+    assert_eq!(detect_wide_attacks(&trades, &cfg).sandwiches[0].atomic, None);
+
+    // consecutive positions in the victim's block
+    let slot = trades[1].slot;
+    for (position, trade) in trades.iter_mut().enumerate() {
+        trade.slot = slot;
+        trade.tx_index = Some(40 + position);
+    }
+    assert_eq!(detect_wide_attacks(&trades, &cfg).sandwiches[0].atomic, Some(Atomicity::Bundle));
+
+    // the victim's swap wrapped between the attacker's legs of one transaction
+    let signature = trades[0].signature.clone();
+    let attacker = trades[0].signer.clone();
+    for (leg_index, trade) in trades.iter_mut().enumerate() {
+        trade.signature = signature.clone();
+        trade.tx_index = Some(40);
+        trade.leg_index = leg_index;
+    }
+    trades[1].fee_payer = Some(attacker);
+    let summary = detect_wide_attacks(&trades, &cfg);
+    assert_eq!(summary.sandwiches[0].atomic, Some(Atomicity::Transaction));
+    assert_ne!(summary.sandwiches[0].victim.signer, summary.sandwiches[0].frontruns[0].signer);
+}
+
+#[test]
+fn whole_token_thresholds_follow_mint_decimals() {
+    let cfg = DetectorConfig {
+        min_victim_tokens: 250.0,
+        token_decimals: 9,
+        min_profit_sol: 0.0015,
+        ..DetectorConfig::default()
+    };
+    assert_eq!(cfg.min_victim_token_units(), 250_000_000_000.0);
+    assert_eq!(cfg.min_profit_lamports(), 1_500_000);
+    assert!(cfg.validate().is_ok());
+
+    for bad in [
+        DetectorConfig { min_victim_tokens: -1.0, ..cfg.clone() },
+        DetectorConfig { copy_trade_min_consistency: 1.5, ..cfg.clone() },
+        DetectorConfig { min_adaptive_slot_gap: 30, ..cfg.clone() },
+        DetectorConfig { min_bot_trades: 0, ..cfg.clone() },
+    ] {
+        assert!(bad.validate().is_err());
+    }
+}
+
+#[test]
+fn sol_thresholds_convert_to_whole_lamports() {
+    let cfg = DetectorConfig {
+        // 0.1 + 0.2 is 0.30000000000000004 in f64; the lamport count must still come out exact
+        min_victim_abs_sol: 0.1 + 0.2,
+        backrun_min_victim_sol: 1.0,
+        snipe_min_sol: 0.000_000_000_4,
+        min_profit_sol: -0.25,
+        min_victim_tokens: 0.5,
+        token_decimals: 0,
+        ..DetectorConfig::default()
+    };
+    assert_eq!(cfg.min_victim_lamports(), 300_000_000);
+    assert_eq!(cfg.backrun_min_victim_lamports(), 1_000_000_000);
+    assert_eq!(cfg.snipe_min_lamports(), 0);
+    assert_eq!(cfg.min_profit_lamports(), -250_000_000);
+    assert_eq!(cfg.min_victim_token_units(), 0.5);
+}
+
+#[test]
+fn strict_ordering_needs_block_positions_within_a_slot() {
+    let mut trades: Vec<ParsedTransaction> = ["sandwich_front", "sandwich_victim", "sandwich_back"]
+        .iter()
+        .flat_map(|name| parse_fixture(name))
+        .collect();
+    let cfg = DetectorConfig {
+        strict_ordering: true,
+        ..DetectorConfig::default()
+    };
+    let slot = trades[1].slot;
+    for (position, trade) in trades.iter_mut().enumerate() {
+        trade.slot = slot;
+        trade.tx_index = Some(40 + position);
+    }
+    assert_eq!(detect_wide_attacks(&trades, &cfg).sandwiches.len(), 1);
+
+    // without positions the same-slot order is only the signature tiebreak
+    for trade in &mut trades {
+        trade.tx_index = None;
+    }
+    assert!(detect_wide_attacks(&trades, &cfg).sandwiches.is_empty());
+
+    // legs in the neighbouring slots still order themselves
+    trades[0].slot = slot - 1;
+    trades[2].slot = slot + 1;
+    assert_eq!(detect_wide_attacks(&trades, &cfg).sandwiches.len(), 1);
+}
+
+#[test]
+fn sandwich_legs_may_trade_on_different_venues() {
+    // the fixture backrun, sent to the PumpSwap program instead of the bonding curve with the
+    // pool, user and base mint where PumpSwap's sell takes them
+    let path = format!("{}/fixtures/sandwich_back.json", env!("CARGO_MANIFEST_DIR"));
+    let raw = fs::read_to_string(&path)
+        .unwrap()
+        .replace("6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P", "pAMMBay6oceH9fJKBRHGP5D4bD4sWpmSwMn52FMfXEA");
+    let mut value: serde_json::Value = serde_json::from_str(&raw).unwrap();
+    value["transaction"]["message"]["instructions"][2]["accounts"] = serde_json::json!([2, 0, 5, 6, 4, 1, 3, 7, 8, 10, 11]);
+    let tx: EncodedConfirmedTransactionWithStatusMeta = serde_json::from_value(value).unwrap();
+    let EncodedTransaction::Json(ui) = &tx.transaction.transaction else {
+        panic!("fixture is not JSON-encoded");
+    };
+    let backrun = parse_transaction(&tx, &ui.signatures[0], MINT, PUMP_PROGRAM_ID).unwrap();
+    assert_eq!(backrun[0].venue, Venue::PumpSwap);
+    assert!(backrun[0].curve.is_none());
+
+    let mut trades: Vec<ParsedTransaction> =
+        ["sandwich_front", "sandwich_victim"].iter().flat_map(|name| parse_fixture(name)).collect();
+    trades.extend(backrun);
+    let summary = detect_wide_attacks(&trades, &DetectorConfig::default());
+    assert_eq!(summary.sandwiches.len(), 1);
+    assert!(summary.sandwiches[0].cross_venue);
+}
+
+#[test]
+fn wallets_splitting_sandwiches_count_as_one_attacker() {
+    let sandwich: Vec<ParsedTransaction> = ["sandwich_front", "sandwich_victim", "sandwich_back"]
+        .iter()
+        .flat_map(|name| parse_fixture(name))
+        .collect();
+    let frontrunner = "4uHqkCs3Y4vZd3Ejax1uhpvDJiqMXsBQE9fxhGAJxq1V";
+    let backrunner = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+    let mut cfg = DetectorConfig::default();
+    let single_wallet = detect_wide_attacks(&sandwich, &cfg).sandwiches[0].confidence;
+    cfg.forced_bots = [frontrunner, backrunner].map(str::to_string).into();
+
+    // three copies of the sandwich, each backrun sent from a second wallet
+    let trades: Vec<ParsedTransaction> = (0..3u64)
+        .flat_map(|copy| {
+            sandwich.iter().cloned().map(move |mut tx| {
+                tx.slot += copy * 100;
+                tx.signature = format!("{}{}", tx.signature, copy).into();
+                if &*tx.signer == frontrunner && tx.trade_type == TradeType::Sell {
+                    tx.signer = backrunner.into();
+                }
+                tx
+            })
+        })
+        .collect();
+    let summary = detect_wide_attacks(&trades, &cfg);
+    assert_eq!(summary.sandwiches.len(), 3);
+    assert_eq!(summary.colluding_pairs.len(), 1);
+    assert_eq!(summary.colluding_pairs[0].sandwiches, 3);
+    assert_eq!(summary.attacker_leaderboard.len(), 1);
+    assert_eq!(summary.attacker_leaderboard[0].attacker, frontrunner);
+    assert_eq!(summary.attacker_leaderboard[0].linked_wallets, vec![backrunner]);
+    assert_eq!(summary.mint_stats[0].unique_attackers, 1);
+    assert_eq!(summary.sandwiches[0].confidence, single_wallet);
+
+    cfg.collusion_min_sandwiches = 4;
+    let summary = detect_wide_attacks(&trades, &cfg);
+    assert!(summary.colluding_pairs.is_empty());
+    assert_eq!(summary.attacker_leaderboard.len(), 2);
+    assert!(summary.sandwiches[0].confidence < single_wallet);
+}
+
+// the buy or sell fixture moved to `slot`, signed by `signer` and scaled to `size` times its SOL
+fn retimed(name: &str, signer: &str, slot: u64, size: f64) -> ParsedTransaction {
+    let mut tx = parse_fixture(name).remove(0);
+    tx.signature = format!("{}{}{}", &tx.signature[..40], signer, slot).into();
+    tx.signer = signer.into();
+    tx.slot = slot;
+    tx.sol_change = (tx.trade_sol_change() as f64 * size) as i64 - tx.costs() as i64;
+    tx
+}
+
+#[test]
+fn copy_traders_follow_a_leader_closely_and_consistently() {
+    let cfg = DetectorConfig::default();
+    let leader: Vec<ParsedTransaction> = [("buy", 100), ("buy", 200), ("buy", 300), ("sell", 400)]
+        .iter()
+        .map(|&(side, slot)| retimed(side, "Leader", slot, 1.0))
+        .collect();
+    // `follower` trades the leader's side `lag` slots after each of its trades, at these sizes
+    let following = |lag: u64, sizes: &[f64]| -> Vec<ParsedTransaction> {
+        leader
+            .iter()
+            .zip(sizes)
+            .map(|(tx, &size)| {
+                let side = if tx.trade_type == TradeType::Buy { "buy" } else { "sell" };
+                retimed(side, "Follower", tx.slot + lag, size)
+            })
+            .collect()
+    };
+    let detect = |follower: Vec<ParsedTransaction>| detect_copy_traders(&[leader.clone(), follower].concat(), &cfg);
+
+    let pairs = detect(following(1, &[0.5, 0.5, 0.55, 0.45]));
+    assert_eq!(pairs.len(), 1, "{:?}", pairs);
+    let pair = &pairs[0];
+    assert_eq!((pair.leader.as_str(), pair.follower.as_str()), ("Leader", "Follower"));
+    assert_eq!((pair.matched_trades, pair.follower_trades), (4, 4));
+    assert!((pair.mean_size_ratio - 0.5).abs() < 1e-6, "{}", pair.mean_size_ratio);
+    assert_eq!(pair.mints, [MINT]);
+    let summary = detect_wide_attacks(&[leader.clone(), following(2, &[0.5; 4])].concat(), &cfg);
+    assert_eq!(summary.copy_traders.len(), 1);
+
+    // trading the same way too late, too erratically in size, or mostly on its own is no copy
+    assert!(detect(following(3, &[0.5; 4])).is_empty());
+    assert!(detect(following(1, &[0.1, 1.0, 3.0, 0.2])).is_empty());
+    let mut independent = following(1, &[0.5; 3]);
+    independent.extend((0..3).map(|at| retimed("buy", "Follower", 10_000 + at * 100, 0.5)));
+    assert!(detect(independent).is_empty());
+    // reverted copies never traded
+    let mut reverted = following(1, &[0.5; 4]);
+    reverted.iter_mut().for_each(|tx| tx.outcome = ExecutionOutcome::Reverted);
+    assert!(detect(reverted).is_empty());
+}
+
+// `creator` launching MINT at `slot`
+fn launch(creator: &str, slot: u64) -> LifecycleEvent {
+    LifecycleEvent {
+        signature: format!("create{}", slot),
+        slot,
+        signer: creator.into(),
+        mint: MINT.into(),
+        kind: LifecycleKind::Create {
+            creator: creator.into(),
+            name: "Token".into(),
+            symbol: "TKN".into(),
+            uri: String::new(),
+        },
+    }
+}
+
+#[test]
+fn dev_dumps_flag_insiders_selling_supply_fast_but_not_holders() {
+    let cfg = DetectorConfig::default();
+    let lifecycle = [launch("Creator", 100)];
+    // each fixture sell moves 3.5% of supply, so two within 10 slots cross the 5% bar
+    let mut trades = vec![
+        retimed("buy", "Creator", 100, 1.0),
+        retimed("buy", "Early", 103, 1.0),
+        retimed("buy", "Late", 106, 1.0),
+        retimed("buy", "Market", 110, 1.0),
+        retimed("sell", "Creator", 120, 1.0),
+        retimed("sell", "Creator", 128, 1.0),
+    ];
+
+    let dumps = detect_dev_dumps(&trades, &lifecycle, &cfg);
+    assert_eq!(dumps.len(), 1, "{:?}", dumps);
+    let dump = &dumps[0];
+    assert_eq!((dump.seller.as_str(), dump.role), ("Creator", InsiderRole::Creator));
+    assert_eq!((dump.start_slot, dump.end_slot, dump.sells.len()), (120, 128, 2));
+    assert_eq!(dump.tokens_sold, 70_000_000_000_000);
+    assert!((dump.supply_pct - 7.0).abs() < 1e-9, "{}", dump.supply_pct);
+    assert_eq!(dump.sol_extracted, 2 * 980_000_000);
+    // bought at 1 SOL per 35M tokens before, last sold at 0.98 SOL with nothing after
+    assert!((dump.price_impact_pct + 2.0).abs() < 1e-9, "{}", dump.price_impact_pct);
+
+    // a creator who holds, sells under the bar, or spreads the same sells out isn't dumping
+    assert!(detect_dev_dumps(&trades[..4], &lifecycle, &cfg).is_empty());
+    assert!(detect_dev_dumps(&trades[..5], &lifecycle, &cfg).is_empty());
+    trades[5].slot = 131;
+    assert!(detect_dev_dumps(&trades, &lifecycle, &cfg).is_empty());
+
+    // buyers within 5 slots of the launch are insiders too; later buyers are just the market
+    let mut dumping = trades[..4].to_vec();
+    for signer in ["Early", "Late"] {
+        dumping.extend([retimed("sell", signer, 140, 1.0), retimed("sell", signer, 141, 1.0)]);
+    }
+    let dumps = detect_dev_dumps(&dumping, &lifecycle, &cfg);
+    assert_eq!(dumps.len(), 1, "{:?}", dumps);
+    assert_eq!((dumps[0].seller.as_str(), dumps[0].role), ("Early", InsiderRole::EarlyHolder));
+    // without a launch there is no insider to flag
+    assert!(detect_dev_dumps(&dumping, &[], &cfg).is_empty());
+}
+
+#[test]
+fn snipers_buy_within_a_slot_of_launch_and_are_ranked_by_exit_profit() {
+    let cfg = DetectorConfig::default();
+    let lifecycle = [launch("Creator", 100)];
+    let trades = vec![
+        retimed("buy", "Early", 99, 1.0),
+        retimed("buy", "Creator", 100, 1.0),
+        retimed("buy", "Sniper", 100, 1.0),
+        retimed("buy", "Sniper", 101, 0.5),
+        retimed("buy", "Flipper", 101, 1.0),
+        retimed("buy", "Dust", 100, 0.1),
+        retimed("buy", "Late", 102, 1.0),
+        retimed("sell", "Flipper", 150, 1.2),
+    ];
+
+    let snipers = detect_snipers(&trades, &lifecycle, &cfg);
+    let names: Vec<&str> = snipers.iter().map(|event| event.sniper.as_str()).collect();
+    // the creator, a buy before the launch, one two slots late and one under 0.5 SOL don't count
+    assert_eq!(names, ["Flipper", "Sniper"]);
+
+    let flipper = &snipers[0];
+    assert_eq!((flipper.create_slot, flipper.entry_slot), (100, 101));
+    assert_eq!((flipper.sol_spent, flipper.sol_received), (1_000_000_000, 1_176_000_000));
+    assert_eq!(flipper.exit_profit_lamports, 176_000_000);
+    assert_eq!((flipper.tokens_sold, flipper.tokens_remaining), (35_000_000_000_000, 0));
+
+    // both entries inside the window add up; the position is still held
+    let sniper = &snipers[1];
+    assert_eq!((sniper.entry_slot, sniper.sol_spent), (100, 1_500_000_000));
+    assert_eq!((sniper.tokens_bought, sniper.tokens_remaining), (70_000_000_000_000, 70_000_000_000_000));
+    assert_eq!((sniper.sol_received, sniper.exit_profit_lamports), (0, -1_500_000_000));
+    assert!((sniper.entry_price - 1.5e9 / 70e12).abs() < 1e-18, "{}", sniper.entry_price);
+
+    let mut wider = cfg.clone();
+    wider.snipe_max_slot_delay = 2;
+    assert!(detect_snipers(&trades, &lifecycle, &wider).iter().any(|event| event.sniper == "Late"));
+    assert!(detect_snipers(&trades, &[], &cfg).is_empty());
+}
+
+#[test]
+fn trends_bucket_sandwiches_by_utc_day_and_iso_week() {
+    let trades: Vec<ParsedTransaction> = ["sandwich_front", "sandwich_victim", "sandwich_back"]
+        .iter()
+        .flat_map(|name| parse_fixture(name))
+        .collect();
+    let fixture = detect_wide_attacks(&trades, &DetectorConfig::default()).sandwiches.remove(0);
+    // Sunday 2025-05-11 00:00 UTC
+    let sunday = 20_219 * 86_400;
+    let attack = |time: i64, victim: &str, attacker: &str, loss: u64, profit: i64| {
+        let mut det = fixture.clone();
+        det.victim.signer = victim.into();
+        det.victim.signature = format!("{}-{}", victim, time).into();
+        det.frontruns[0].signer = attacker.into();
+        det.backruns[0].signer = attacker.into();
+        det.impact_loss_lamports = Some(loss);
+        det.net_profit_sol = profit;
+        (time, det)
+    };
+    let sandwiches = [
+        attack(sunday + 36_000, "V1", "X", 5, 100),
+        attack(sunday + 86_399, "V1", "Y", 7, 50),
+        attack(sunday + 86_400, "V2", "X", 3, 20),
+        attack(sunday + 3 * 86_400, "V3", "X", 9, 1),
+        attack(sunday + 3 * 86_400 + 1, "V4", "X", 1, 1),
+        attack(sunday + 3 * 86_400 + 2, "V5", "X", 8, 1),
+        attack(sunday + 3 * 86_400 + 3, "V6", "X", 2, -4),
+    ];
+
+    let daily = period_stats(&sandwiches, Period::Daily);
+    let starts: Vec<i64> = daily.iter().map(|bucket| bucket.period_start).collect();
+    // the quiet Tuesday gets no row
+    assert_eq!(starts, [sunday, sunday + 86_400, sunday + 3 * 86_400]);
+    let counts: Vec<(usize, i64, usize, usize)> = daily
+        .iter()
+        .map(|bucket| (bucket.attacks, bucket.sol_extracted, bucket.unique_attackers, bucket.unique_victims))
+        .collect();
+    assert_eq!(counts, [(2, 150, 2, 1), (1, 20, 1, 1), (4, -1, 1, 4)]);
+    let top = |bucket: &rusty::trends::PeriodStats| -> Vec<(String, u64)> {
+        bucket.top_victim_losses.iter().map(|loss| (loss.victim.clone(), loss.loss_lamports)).collect()
+    };
+    assert_eq!(top(&daily[0]), [("V1".to_string(), 7), ("V1".to_string(), 5)]);
+    assert_eq!(top(&daily[2]), [("V3".to_string(), 9), ("V5".to_string(), 8), ("V6".to_string(), 2)]);
+
+    // weeks start on Monday, so Sunday closes one and Monday opens the next
+    let weekly = period_stats(&sandwiches, Period::Weekly);
+    let weeks: Vec<(i64, usize, usize)> = weekly
+        .iter()
+        .map(|bucket| (bucket.period_start, bucket.attacks, bucket.unique_victims))
+        .collect();
+    assert_eq!(weeks, [(sunday - 6 * 86_400, 2, 1), (sunday + 86_400, 5, 5)]);
+    assert_eq!(Period::Weekly.start_of(-1), -3 * 86_400);
+    assert!(period_stats(&[], Period::Daily).is_empty());
+}
+
+#[test]
+fn run_diff_splits_sandwiches_into_removed_added_and_common() {
+    let trades: Vec<ParsedTransaction> = ["sandwich_front", "sandwich_victim", "sandwich_back"]
+        .iter()
+        .flat_map(|name| parse_fixture(name))
+        .collect();
+    let summary = detect_wide_attacks(&trades, &DetectorConfig::default());
+    let victim = |name: &str, profit: i64| {
+        let mut det = summary.sandwiches[0].clone();
+        det.victim.signature = name.into();
+        det.net_profit_sol = profit;
+        det
+    };
+    let mut left = summary.clone();
+    left.sandwiches = vec![victim("gone", 10), victim("kept", 20), victim("repriced", 30)];
+    let mut right = summary.clone();
+    right.sandwiches = vec![victim("new", 40), victim("repriced", 35), victim("kept", 20)];
+    // the same victim signature's second leg is a different sandwich
+    let mut second_leg = victim("kept", 20);
+    second_leg.victim.leg_index = 1;
+    right.sandwiches.push(second_leg);
+
+    let diff = diff_summaries(&left, &right);
+    let victims = |dets: &[SandwichDetection]| -> Vec<(String, usize)> {
+        dets.iter().map(|det| (det.victim.signature.to_string(), det.victim.leg_index)).collect()
+    };
+    assert_eq!(victims(&diff.only_left), [("gone".to_string(), 0)]);
+    assert_eq!(victims(&diff.only_right), [("kept".to_string(), 1), ("new".to_string(), 0)]);
+    assert_eq!(diff.in_both, 2);
+    let changes: Vec<(&str, i64, i64)> = diff
+        .profit_changes
+        .iter()
+        .map(|change| (change.victim_signature.as_str(), change.left_profit_sol, change.right_profit_sol))
+        .collect();
+    assert_eq!(changes, [("repriced", 30, 35)]);
+
+    let same = diff_summaries(&summary, &summary);
+    assert!(same.only_left.is_empty() && same.only_right.is_empty() && same.profit_changes.is_empty());
+    assert_eq!(same.in_both, 1);
+
+    // the subcommand reads two saved JSON reports
+    let dir = std::env::temp_dir().join(format!("rusty-diff-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let (left_path, right_path) = (dir.join("left.json"), dir.join("right.json"));
+    fs::write(&left_path, serde_json::to_string(&left).unwrap()).unwrap();
+    fs::write(&right_path, serde_json::to_string(&right).unwrap()).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_rusty"))
+        .args(["diff", left_path.to_str().unwrap(), right_path.to_str().unwrap(), "--output", "json"])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let printed: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(printed["in_both"], 2);
+    assert_eq!(printed["only_right"].as_array().unwrap().len(), 2);
+    assert_eq!(printed["profit_changes"][0]["victim_signature"], "repriced");
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn sweep_runs_every_grid_point_and_thresholds_move_counts_one_way() {
+    let mut trades: Vec<ParsedTransaction> = ["sandwich_front", "sandwich_victim", "sandwich_back"]
+        .iter()
+        .flat_map(|name| parse_fixture(name))
+        .collect();
+    // the same attack much later with its legs 3 slots apart instead of 1
+    let spread: Vec<ParsedTransaction> = trades
+        .iter()
+        .enumerate()
+        .map(|(at, tx)| {
+            let mut tx = tx.clone();
+            tx.signature = format!("{}-spread", tx.signature).into();
+            tx.slot += 1_000 + 2 * at as u64;
+            tx
+        })
+        .collect();
+    trades.extend(spread);
+
+    let grid = SweepGrid {
+        max_slot_gaps: vec![1, 2, 3, 6],
+        min_profit_sol: vec![0.0, 0.05, 0.5],
+        min_victim_abs_sol: Vec::new(),
+    };
+    let points = run_sweep(&trades, &DetectorConfig::default(), &grid);
+    // one point per combination, in grid order; the empty axis keeps the base value
+    let combinations: Vec<(u64, f64, f64)> = points
+        .iter()
+        .map(|point| (point.max_slot_gap, point.min_profit_sol, point.min_victim_abs_sol))
+        .collect();
+    let expected: Vec<(u64, f64, f64)> = grid
+        .max_slot_gaps
+        .iter()
+        .flat_map(|&gap| grid.min_profit_sol.iter().map(move |&profit| (gap, profit, 0.01)))
+        .collect();
+    assert_eq!(combinations, expected);
+
+    // a wider window never finds fewer sandwiches, a higher profit bar never more
+    let count = |gap: u64, profit: f64| {
+        points
+            .iter()
+            .find(|point| point.max_slot_gap == gap && point.min_profit_sol == profit)
+            .unwrap()
+            .sandwiches
+    };
+    for pair in grid.max_slot_gaps.windows(2) {
+        for &profit in &grid.min_profit_sol {
+            assert!(count(pair[0], profit) <= count(pair[1], profit), "{:?} at {}", pair, profit);
+        }
+    }
+    for pair in grid.min_profit_sol.windows(2) {
+        for &gap in &grid.max_slot_gaps {
+            assert!(count(gap, pair[0]) >= count(gap, pair[1]), "{:?} at {}", pair, gap);
+        }
+    }
+
+    // the default 3-slot window finds both; narrower ones miss the spread attack
+    let at = |index: usize| (points[index].sandwiches, points[index].shared_with_base, points[index].overlap);
+    assert_eq!(at(0), (1, 1, 0.5));
+    assert_eq!(at(6), (2, 2, 1.0));
+    assert_eq!(at(11), (0, 0, 0.0));
+}
+
+#[test]
+fn attacker_roi_divides_profit_by_frontrun_capital_and_never_by_zero() {
+    let trades: Vec<ParsedTransaction> = ["sandwich_front", "sandwich_victim", "sandwich_back"]
+        .iter()
+        .flat_map(|name| parse_fixture(name))
+        .collect();
+    let summary = detect_wide_attacks(&trades, &DetectorConfig::default());
+    let sandwich = &summary.sandwiches[0];
+    let (front, back) = (&sandwich.frontruns[0], &sandwich.backruns[0]);
+    let attacker = front.signer.to_string();
+
+    let stats = &summary.attacker_performance;
+    assert_eq!(stats.len(), 1);
+    let capital = front.sol_change.unsigned_abs();
+    let profit = front.sol_change + back.sol_change;
+    assert_eq!((stats[0].attempted, stats[0].completed, stats[0].win_rate), (1, 1, 1.0));
+    assert_eq!((stats[0].capital_deployed_lamports, stats[0].total_profit_lamports), (capital, profit));
+    assert!((stats[0].roi_pct - profit as f64 * 100.0 / capital as f64).abs() < 1e-9, "{}", stats[0].roi_pct);
+    assert_eq!(stats[0].avg_holding_slots, 2.0);
+
+    // a frontrun on another victim that never closed halves the win rate
+    let mut missed = summary.front_runs.clone();
+    missed.push(FrontRunEvent { victim: back.clone(), frontruns: vec![front.clone()] });
+    let entities = AttackerEntities::default();
+    let stats = attacker_performance(&missed, &summary.sandwiches, &[], &entities);
+    assert_eq!((stats[0].attempted, stats[0].completed, stats[0].win_rate), (2, 1, 0.5));
+
+    // a wallet that only backran someone else's frontrun put up no capital: no ROI, no holding time
+    let mut shared = sandwich.clone();
+    shared.backruns[0].signer = "Closer".into();
+    let stats = attacker_performance(&[], &[shared], &[], &entities);
+    let closer = stats.iter().find(|stats| stats.attacker == "Closer").unwrap();
+    assert_eq!((closer.capital_deployed_lamports, closer.total_profit_lamports), (0, back.sol_change));
+    assert_eq!((closer.roi_pct, closer.avg_holding_slots, closer.win_rate), (0.0, 0.0, 1.0));
+    let opener = stats.iter().find(|stats| stats.attacker == attacker).unwrap();
+    assert_eq!(opener.total_profit_lamports, front.sol_change);
+    assert!((opener.roi_pct + 100.0).abs() < 1e-9, "{}", opener.roi_pct);
+    assert!(attacker_performance(&[], &[], &[], &entities).is_empty());
+}
+
+#[test]
+fn token_amounts_render_at_the_mint_decimals() {
+    let mut mint = vec![0u8; 82];
+    mint[44] = 9;
+    assert_eq!(token::decode_decimals(&mint), Some(9));
+    assert_eq!(token::decode_decimals(&mint[..44]), None);
+    assert_eq!(token::format_tokens(19_500_000, 6), "19.5");
+    assert_eq!(token::format_tokens(-60_000_000_000_000, 6), "-60000000");
+    assert_eq!(token::format_tokens(1, 9), "0.000000001");
+}
+
+#[test]
+fn decodes_metaplex_metadata() {
+    let mut data = vec![4u8];
+    data.extend_from_slice(&[1; 64]);
+    for (value, width) in [("dogwifhat", 32), ("WIF", 10), ("https://example.com/wif.json", 200)] {
+        data.extend_from_slice(&(width as u32).to_le_bytes());
+        let mut padded = value.as_bytes().to_vec();
+        padded.resize(width, 0);
+        data.extend_from_slice(&padded);
+    }
+
+    let metadata = token::decode_metadata(&data).unwrap();
+    assert_eq!(metadata.name, "dogwifhat");
+    assert_eq!(metadata.uri, "https://example.com/wif.json");
+    assert_eq!(metadata.label().as_deref(), Some("$WIF"));
+    assert!(token::decode_metadata(&data[..100]).is_none());
+}
+
+// answers one request with `body` and returns the url it listens on
+fn serve_once(body: Vec<u8>) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/meta.json", listener.local_addr().unwrap());
+    thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut request = [0u8; 1024];
+        let _ = stream.read(&mut request);
+        let head = format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n", body.len());
+        let _ = stream.write_all(head.as_bytes());
+        let _ = stream.write_all(&body);
+    });
+    url
+}
+
+// A JSON-RPC endpoint over fixture transactions that logs the size of every getTransaction call
+// (1 for a plain request). `error` gets an error back and `dropped` is left out of batches.
+fn serve_rpc(names: &[&str], error: &str, dropped: &str, calls: Arc<Mutex<Vec<usize>>>) -> String {
+    let mut transactions: Vec<(String, serde_json::Value)> = names
+        .iter()
+        .map(|name| {
+            let (tx, signature) = load(name);
+            (signature, serde_json::to_value(tx).unwrap())
+        })
+        .collect();
+    transactions.sort_by_key(|(_, tx)| std::cmp::Reverse(tx["slot"].as_u64()));
+    let (error, dropped) = (error.to_string(), dropped.to_string());
+    let answer = move |request: &serde_json::Value| -> Option<serde_json::Value> {
+        let result = match request["method"].as_str().unwrap() {
+            "getSignaturesForAddress" if request["params"][1]["before"].is_null() => serde_json::json!(
+                transactions
+                    .iter()
+                    .map(|(signature, tx)| serde_json::json!({"signature": signature, "slot": tx["slot"], "err": null,
+                        "memo": null, "blockTime": null, "confirmationStatus": "finalized"}))
+                    .collect::<Vec<_>>()
+            ),
+            "getSignaturesForAddress" => serde_json::json!([]),
+            "getTransaction" => {
+                let signature = request["params"][0].as_str().unwrap();
+                if signature == error {
+                    return Some(serde_json::json!({"jsonrpc": "2.0", "id": request["id"],
+                        "error": {"code": -32009, "message": "slot skipped"}}));
+                }
+                if signature == dropped {
+                    return None;
+                }
+                transactions.iter().find(|(known, _)| known == signature).unwrap().1.clone()
+            }
+            _ => return Some(serde_json::json!({"jsonrpc": "2.0", "id": request["id"],
+                "error": {"code": -32601, "message": "not stubbed"}})),
+        };
+        Some(serde_json::json!({"jsonrpc": "2.0", "id": request["id"], "result": result}))
+    };
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let mut raw = Vec::new();
+            let mut chunk = [0u8; 8192];
+            let body = loop {
+                let read = stream.read(&mut chunk).unwrap();
+                raw.extend_from_slice(&chunk[..read]);
+                let text = String::from_utf8_lossy(&raw).to_string();
+                let Some(end) = text.find("\r\n\r\n") else { continue };
+                let length: usize = text[..end]
+                    .to_lowercase()
+                    .lines()
+                    .find_map(|line| Some(line.strip_prefix("content-length:")?.trim().parse().unwrap()))
+                    .unwrap_or(0);
+                if raw.len() >= end + 4 + length || read == 0 {
+                    break raw[end + 4..].to_vec();
+                }
+            };
+            let response = match serde_json::from_slice::<serde_json::Value>(&body).unwrap() {
+                serde_json::Value::Array(requests) => {
+                    calls.lock().unwrap().push(requests.len());
+                    serde_json::Value::Array(requests.iter().filter_map(&answer).rev().collect())
+                }
+                request => {
+                    if request["method"] == "getTransaction" {
+                        calls.lock().unwrap().push(1);
+                    }
+                    answer(&request).unwrap_or(serde_json::Value::Null)
+                }
+            };
+            let body = response.to_string();
+            let head = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                body.len()
+            );
+            let _ = stream.write_all(head.as_bytes());
+            let _ = stream.write_all(body.as_bytes());
+        }
+    });
+    url
+}
+
+#[test]
+fn batched_fetches_chunk_signatures_and_keep_partial_failures() {
+    let names = ["sandwich_front", "sandwich_victim", "sandwich_back", "buy", "sell"];
+    let calls = Arc::new(Mutex::new(Vec::new()));
+    let (_, errored) = load("buy");
+    let (_, dropped) = load("sell");
+    let url = serve_rpc(&names, &errored, &dropped, calls.clone());
+
+    let output = Command::new(env!("CARGO_BIN_EXE_rusty"))
+        .args([MINT, "--rpc-url", &url, "--batch-size", "2", "--rps", "1000", "--output", "json", "--quiet"])
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "{}", stderr);
+    // five signatures in batches of two, the odd one fetched on its own
+    assert_eq!(*calls.lock().unwrap(), [2, 2, 1]);
+
+    // answers come back out of order and two are missing; the rest still parse and detect
+    assert!(stderr.contains(&format!("Failed {}", errored)) && stderr.contains("slot skipped"), "{}", stderr);
+    assert!(stderr.contains(&format!("Batch response is missing transaction {}", dropped)), "{}", stderr);
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["sandwiches"].as_array().unwrap().len(), 1, "{}", report);
+}
+
+#[test]
+fn token_image_comes_only_from_small_http_metadata() {
+    let timeout = Duration::from_secs(5);
+    // nothing is fetched for other schemes, so these never touch the network
+    for uri in ["ipfs://bafy/wif.json", "file:///etc/passwd", ""] {
+        assert_eq!(token::fetch_image(uri, timeout).unwrap(), None, "{}", uri);
+    }
+
+    let url = serve_once(br#"{"image":"https://example.com/wif.png"}"#.to_vec());
+    assert_eq!(token::fetch_image(&url, timeout).unwrap().as_deref(), Some("https://example.com/wif.png"));
+    let url = serve_once(br#"{"name":"dogwifhat"}"#.to_vec());
+    assert_eq!(token::fetch_image(&url, timeout).unwrap(), None);
+    let url = serve_once(br#"{"image":42}"#.to_vec());
+    assert_eq!(token::fetch_image(&url, timeout).unwrap(), None);
+
+    let mut huge = br#"{"image":""#.to_vec();
+    huge.resize(token::MAX_METADATA_JSON as usize + 10, b'a');
+    huge.extend_from_slice(br#""}"#);
+    let err = token::fetch_image(&serve_once(huge), timeout).unwrap_err();
+    assert!(err.to_string().contains("larger than"), "{}", err);
+}
+
+#[test]
+fn decodes_bonding_curve_account() {
+    let mut data = vec![23, 183, 248, 55, 96, 216, 172, 96];
+    for value in [1_000_000_000_000_000u64, 31_000_000_000, 720_000_000_000_000, 1_000_000_000, 1_000_000_000_000_000] {
+        data.extend_from_slice(&value.to_le_bytes());
+    }
+    data.push(0);
+    // creator appended by newer program versions
+    data.extend_from_slice(&[7; 32]);
+
+    let account = BondingCurveAccount::decode(&data).unwrap();
+    assert_eq!(account.virtual_sol_reserves, 31_000_000_000);
+    assert_eq!(account.real_token_reserves, 720_000_000_000_000);
+    assert!(!account.complete);
+    assert!(BondingCurveAccount::decode(&data[8..]).is_none());
+}
+
+#[test]
+fn decodes_leading_discriminators_and_rejects_truncated_arguments() {
+    let mut data = vec![102, 6, 61, 18, 1, 218, 235, 234];
+    data.extend_from_slice(&1_000u64.to_le_bytes());
+    data.extend_from_slice(&5_000u64.to_le_bytes());
+
+    let decoded = decode_instruction_data(&bs58::encode(&data).into_string(), &[]);
+    assert_eq!(decoded.trade, Some((TradeType::Buy, 1_000, 5_000)));
+
+    // a wrapper's tag byte in front is the wrapper's own instruction, not a pump.fun trade
+    let wrapped = [&[9], data.as_slice()].concat();
+    assert_eq!(decode_instruction_data(&bs58::encode(&wrapped).into_string(), &[]).trade, None);
+
+    data.truncate(data.len() - 1);
+    assert_eq!(decode_instruction_data(&bs58::encode(&data).into_string(), &[]).trade, None);
+    assert_eq!(decode_instruction_data("not base58: 0OIl", &[]).trade, None);
+}
+
+#[test]
+fn values_routed_attacker_legs_through_the_curve() {
+    const HOP_MINT: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+    let mut trades: Vec<ParsedTransaction> = ["sandwich_front", "sandwich_victim", "sandwich_back"]
+        .iter()
+        .flat_map(|name| parse_fixture(name))
+        .collect();
+    // the bot pays with and is paid in another token, so its SOL balance only moves by the fee
+    for (idx, hop) in [(0, -300_000_000i64), (2, 320_000_000)] {
+        let leg = &mut trades[idx];
+        let curve_sol = -leg.trade_sol_change();
+        leg.curve = Some(CurveDelta {
+            account: "EnnmSAG2VAF176zyhFF4h98z7cUJBhrvoPQqFJ6hJcpn".to_string(),
+            sol_change: curve_sol,
+            token_change: -leg.token_change,
+            pre_token_balance: None,
+        });
+        leg.sol_change = -(leg.fee as i64);
+        leg.ata_rent = 0;
+        leg.hop_token_changes.insert(HOP_MINT.to_string(), hop);
+    }
+
+    let summary = detect_wide_attacks(&trades, &DetectorConfig::default());
+    assert_eq!(summary.sandwiches.len(), 1);
+    let sandwich = &summary.sandwiches[0];
+    assert_eq!(sandwich.route_token_deltas.get(HOP_MINT), Some(&20_000_000));
+    assert!(sandwich.gross_profit_sol > 0);
+}
+
+#[test]
+fn fixture_source_serves_transactions_newest_first() {
+    let source = FixtureSource::load(concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures")).unwrap();
+    let mint = Pubkey::from_str(MINT).unwrap();
+    let signatures = source.signatures(&mint, 100).unwrap();
+    assert_eq!(signatures.len(), 8);
+    assert_eq!(source.signatures(&mint, 3).unwrap(), signatures[..3]);
+
+    let slots: Vec<u64> = signatures
+        .iter()
+        .map(|signature| source.transaction(signature, UiTransactionEncoding::Json).unwrap().slot)
+        .collect();
+    assert!(slots.windows(2).all(|pair| pair[0] >= pair[1]));
+    assert!(source.transaction("missing", UiTransactionEncoding::Json).is_err());
+
+    let trades: Vec<ParsedTransaction> = signatures
+        .iter()
+        .flat_map(|signature| {
+            let tx = source.transaction(signature, UiTransactionEncoding::Json).unwrap();
+            parse_transaction(&tx, signature, MINT, PUMP_PROGRAM_ID).unwrap()
+        })
+        .collect();
+    assert_eq!(trades.len(), 9);
+    let summary = detect_wide_attacks(&trades, &DetectorConfig::default());
+    assert_eq!(summary.sandwiches.len(), 1);
+}
+
+#[test]
+fn fixture_source_stops_at_checkpoint_signature() {
+    let source = FixtureSource::load(concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures")).unwrap();
+    let mint = Pubkey::from_str(MINT).unwrap();
+    let signatures = source.signatures(&mint, 100).unwrap();
+
+    assert_eq!(source.signatures_until(&mint, &signatures[3], 100).unwrap(), signatures[..3]);
+    assert_eq!(source.signatures_until(&mint, &signatures[3], 2).unwrap(), signatures[..2]);
+    assert!(source.signatures_until(&mint, &signatures[0], 100).unwrap().is_empty());
+    let page = source.signatures_page(&mint, Some(&signatures[1]), Some(&signatures[5]), 100).unwrap();
+    assert_eq!(page, signatures[2..5]);
+}
+
+// a long signature history with nothing else behind it; counts the getSignaturesForAddress calls
+struct LongHistory {
+    newest_first: Vec<String>,
+    pages: Cell<usize>,
+}
+
+impl TransactionSource for LongHistory {
+    fn signatures_page(
+        &self,
+        _address: &Pubkey,
+        before: Option<&str>,
+        until: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<String>, Error> {
+        assert!(limit <= SIGNATURE_PAGE);
+        self.pages.set(self.pages.get() + 1);
+        let start = before.map_or(0, |before| self.newest_first.iter().position(|s| s == before).unwrap() + 1);
+        Ok(self.newest_first[start..]
+            .iter()
+            .take_while(|signature| Some(signature.as_str()) != until)
+            .take(limit)
+            .cloned()
+            .collect())
+    }
+
+    fn transaction(&self, _: &str, _: UiTransactionEncoding) -> Result<EncodedConfirmedTransactionWithStatusMeta, Error> {
+        unreachable!()
+    }
+
+    fn account_data(&self, _: &Pubkey) -> Result<Vec<u8>, Error> {
+        unreachable!()
+    }
+
+    fn block_signatures(&self, _: u64) -> Result<Vec<String>, Error> {
+        unreachable!()
+    }
+
+    fn block_compute_unit_prices(&self, _: u64) -> Result<Vec<u64>, Error> {
+        unreachable!()
+    }
+
+    fn slot_leaders(&self, _: u64, _: u64) -> Result<Vec<String>, Error> {
+        unreachable!()
+    }
+
+    fn epoch_schedule(&self) -> Result<EpochSchedule, Error> {
+        unreachable!()
+    }
+
+    fn leader_schedule(&self, _: u64) -> Result<Option<HashMap<String, Vec<usize>>>, Error> {
+        unreachable!()
+    }
+}
+
+#[test]
+fn signature_listings_page_back_with_before() {
+    let source = LongHistory {
+        newest_first: (0..2_500).rev().map(|n| format!("sig-{:04}", n)).collect(),
+        pages: Cell::new(0),
+    };
+    let mint = Pubkey::from_str(MINT).unwrap();
+
+    let newest = source.signatures(&mint, 2_100).unwrap();
+    assert_eq!(newest, source.newest_first[..2_100]);
+    assert_eq!(source.pages.replace(0), 3);
+
+    // back to the checkpoint, however many pages that takes
+    let since = source.signatures_until(&mint, "sig-0300", usize::MAX).unwrap();
+    assert_eq!(since, source.newest_first[..2_199]);
+    assert_eq!(source.pages.replace(0), 3);
+
+    // a short history ends on its first, partial page
+    assert_eq!(source.signatures_until(&mint, "sig-2400", usize::MAX).unwrap().len(), 99);
+    assert_eq!(source.pages.get(), 1);
+}
+
+// the fixture RPC behind an endpoint that answers `error` to its first `failing` calls
+struct Flaky<'a> {
+    inner: &'a FixtureSource,
+    error: &'static str,
+    failing: Cell<usize>,
+    calls: &'a Cell<usize>,
+}
+
+impl<'a> Flaky<'a> {
+    fn new(inner: &'a FixtureSource, error: &'static str, failing: usize, calls: &'a Cell<usize>) -> Self {
+        Self { inner, error, failing: Cell::new(failing), calls }
+    }
+
+    fn answer(&self) -> Result<(), Error> {
+        self.calls.set(self.calls.get() + 1);
+        if self.failing.get() == 0 {
+            return Ok(());
+        }
+        self.failing.set(self.failing.get() - 1);
+        Err(Error::Rpc(self.error.to_string()))
+    }
+}
+
+impl TransactionSource for Flaky<'_> {
+    fn signatures_page(
+        &self,
+        address: &Pubkey,
+        before: Option<&str>,
+        until: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<String>, Error> {
+        self.answer()?;
+        self.inner.signatures_page(address, before, until, limit)
+    }
+
+    fn transaction(
+        &self,
+        signature: &str,
+        encoding: UiTransactionEncoding,
+    ) -> Result<EncodedConfirmedTransactionWithStatusMeta, Error> {
+        self.answer()?;
+        self.inner.transaction(signature, encoding)
+    }
+
+    fn account_data(&self, address: &Pubkey) -> Result<Vec<u8>, Error> {
+        self.answer()?;
+        self.inner.account_data(address)
+    }
+
+    fn block_signatures(&self, slot: u64) -> Result<Vec<String>, Error> {
+        self.answer()?;
+        self.inner.block_signatures(slot)
+    }
+
+    fn block_compute_unit_prices(&self, slot: u64) -> Result<Vec<u64>, Error> {
+        self.answer()?;
+        self.inner.block_compute_unit_prices(slot)
+    }
+
+    fn slot_leaders(&self, start_slot: u64, limit: u64) -> Result<Vec<String>, Error> {
+        self.answer()?;
+        self.inner.slot_leaders(start_slot, limit)
+    }
+
+    fn epoch_schedule(&self) -> Result<EpochSchedule, Error> {
+        self.answer()?;
+        self.inner.epoch_schedule()
+    }
+
+    fn leader_schedule(&self, slot: u64) -> Result<Option<HashMap<String, Vec<usize>>>, Error> {
+        self.answer()?;
+        self.inner.leader_schedule(slot)
+    }
+}
+
+// A ledger of plain SOL transfers, served by address the way getSignaturesForAddress would
+#[derive(Default)]
+struct Transfers {
+    // address -> signatures, newest first
+    history: HashMap<String, Vec<String>>,
+    // decoded again on every fetch, as the RPC client would
+    transactions: HashMap<String, serde_json::Value>,
+}
+
+impl Transfers {
+    fn send(&mut self, funder: &str, wallet: &str, lamports: u64, slot: u64) {
+        let signature = format!("transfer{}", slot);
+        let tx = serde_json::json!({
+            "slot": slot,
+            "blockTime": 1_746_999_000 + slot as i64,
+            "transaction": {
+                "signatures": [signature],
+                "message": {
+                    "header": {
+                        "numRequiredSignatures": 1,
+                        "numReadonlySignedAccounts": 0,
+                        "numReadonlyUnsignedAccounts": 0,
+                    },
+                    "accountKeys": [funder, wallet],
+                    "recentBlockhash": "11111111111111111111111111111111",
+                    "instructions": [],
+                },
+            },
+            "meta": {
+                "err": null,
+                "status": {"Ok": null},
+                "fee": 5_000,
+                "preBalances": [100_000_000_000u64, 1_000_000_000u64],
+                "postBalances": [100_000_000_000 - lamports - 5_000, 1_000_000_000 + lamports],
+                "innerInstructions": [],
+                "logMessages": [],
+                "preTokenBalances": [],
+                "postTokenBalances": [],
+                "rewards": [],
+            },
+        });
+        self.transactions.insert(signature.clone(), tx);
+        for address in [funder, wallet] {
+            self.history.entry(address.to_string()).or_default().insert(0, signature.clone());
+        }
+    }
+}
+
+impl TransactionSource for Transfers {
+    fn signatures_page(
+        &self,
+        address: &Pubkey,
+        before: Option<&str>,
+        _until: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<String>, Error> {
+        let history = self.history.get(&address.to_string()).cloned().unwrap_or_default();
+        let older = match before {
+            Some(before) => history.iter().skip_while(|signature| *signature != before).skip(1).cloned().collect(),
+            None => history,
+        };
+        Ok(older.into_iter().take(limit).collect())
+    }
+
+    fn transaction(
+        &self,
+        signature: &str,
+        _encoding: UiTransactionEncoding,
+    ) -> Result<EncodedConfirmedTransactionWithStatusMeta, Error> {
+        let tx = self
+            .transactions
+            .get(signature)
+            .ok_or_else(|| Error::Rpc(format!("Transaction {} not found", signature)))?;
+        Ok(serde_json::from_value(tx.clone()).unwrap())
+    }
+
+    fn account_data(&self, address: &Pubkey) -> Result<Vec<u8>, Error> {
+        Err(Error::Rpc(format!("No account {}", address)))
+    }
+
+    fn block_signatures(&self, slot: u64) -> Result<Vec<String>, Error> {
+        Err(Error::Rpc(format!("No block {}", slot)))
+    }
+
+    fn block_compute_unit_prices(&self, slot: u64) -> Result<Vec<u64>, Error> {
+        Err(Error::Rpc(format!("No block {}", slot)))
+    }
+
+    fn slot_leaders(&self, start_slot: u64, _limit: u64) -> Result<Vec<String>, Error> {
+        Err(Error::Rpc(format!("No leaders from slot {}", start_slot)))
+    }
+
+    fn epoch_schedule(&self) -> Result<EpochSchedule, Error> {
+        Err(Error::Rpc("No epoch schedule".to_string()))
+    }
+
+    fn leader_schedule(&self, _slot: u64) -> Result<Option<HashMap<String, Vec<usize>>>, Error> {
+        Ok(None)
+    }
+}
+
+#[test]
+fn funding_follows_the_largest_transfer_back_until_the_depth_limit_a_label_or_a_loop() {
+    let key = |seed: u8| Pubkey::new_from_array([seed; 32]).to_string();
+    let (attacker, a, b, c, d) = (key(1), key(2), key(3), key(4), key(5));
+    let (top_up, payee, sibling) = (key(6), key(7), key(8));
+    let sol = 1_000_000_000;
+    let mut ledger = Transfers::default();
+    ledger.send(&d, &c, 30 * sol, 10);
+    ledger.send(&c, &b, 20 * sol, 11);
+    ledger.send(&b, &a, 10 * sol, 12);
+    ledger.send(&top_up, &attacker, sol, 13);
+    ledger.send(&a, &attacker, 5 * sol, 14);
+    // money going out and a transaction the node can't return are both passed over
+    ledger.send(&attacker, &payee, 8 * sol, 15);
+    ledger.history.get_mut(&attacker).unwrap().insert(0, "pruned".to_string());
+    ledger.send(&b, &sibling, 3 * sol, 16);
+
+    let funders = |chain: &FundingChain| chain.hops.iter().map(|hop| hop.funder.clone()).collect::<Vec<_>>();
+    let options = TraceOptions::default();
+    let none = BTreeMap::new();
+    let chain = trace_funding(&ledger, &attacker, &options, &none).unwrap();
+    assert_eq!(funders(&chain), [a.clone(), b.clone(), c.clone()]);
+    assert_eq!(chain.hops[0].funded, attacker);
+    assert_eq!(chain.hops[0].signature, "transfer14");
+    assert_eq!(chain.hops.iter().map(|hop| hop.lamports).collect::<Vec<_>>(), [5 * sol, 10 * sol, 20 * sol]);
+    assert_eq!(chain.hops[2].funded, b);
+
+    let deeper = TraceOptions { max_depth: 10, ..TraceOptions::default() };
+    // the chain runs out at a wallet nobody funded
+    let whole = trace_funding(&ledger, &attacker, &deeper, &none).unwrap();
+    assert_eq!(funders(&whole), [a.clone(), b.clone(), c.clone(), d.clone()]);
+    let shallow = TraceOptions { max_depth: 1, ..TraceOptions::default() };
+    let nearest = trace_funding(&ledger, &attacker, &shallow, &none).unwrap();
+    assert_eq!((nearest.hops.len(), nearest.hops[0].funder.as_str()), (1, a.as_str()));
+    // both transfers in are top-ups under the threshold
+    let strict = TraceOptions { min_lamports: 6 * sol, ..TraceOptions::default() };
+    assert!(trace_funding(&ledger, &attacker, &strict, &none).unwrap().hops.is_empty());
+
+    // a labeled funder ends the chain and isn't shared
+    let labels = BTreeMap::from([(c.clone(), "Exchange".to_string())]);
+    let labeled = trace_funding(&ledger, &attacker, &deeper, &labels).unwrap();
+    assert_eq!(funders(&labeled), [a.clone(), b.clone(), c.clone()]);
+    assert_eq!(labeled.hops[2].label.as_deref(), Some("Exchange"));
+
+    let other = trace_funding(&ledger, &sibling, &options, &none).unwrap();
+    assert_eq!(funders(&other), [b.clone(), c.clone(), d.clone()]);
+    let shared = shared_funders(&[chain, other]);
+    let mut shared_by: Vec<(String, Vec<String>)> = shared.into_iter().map(|s| (s.funder, s.wallets)).collect();
+    shared_by.sort();
+    let mut both = vec![attacker.clone(), sibling.clone()];
+    both.sort();
+    let mut expected = vec![(b.clone(), both.clone()), (c.clone(), both.clone())];
+    expected.sort();
+    assert_eq!(shared_by, expected);
+    let other = trace_funding(&ledger, &sibling, &deeper, &labels).unwrap();
+    let shared = shared_funders(&[labeled, other]);
+    assert_eq!(shared.len(), 1);
+    assert_eq!((shared[0].funder.clone(), shared[0].wallets.clone()), (b.clone(), both));
+
+    // funds that went round in a loop stop at the first wallet seen twice
+    ledger.send(&a, &d, 50 * sol, 17);
+    let looped = trace_funding(&ledger, &attacker, &deeper, &none).unwrap();
+    assert_eq!(funders(&looped), [a.clone(), b, c, d, a]);
+
+    assert!(matches!(trace_funding(&ledger, "not-a-wallet", &options, &none), Err(Error::Config(_))));
+}
+
+// the fixture sandwich copied so each victim lands at `slot` on `mint`
+fn sandwich_at(mint: &str, slot: u64) -> Vec<ParsedTransaction> {
+    ["sandwich_front", "sandwich_victim", "sandwich_back"]
+        .iter()
+        .flat_map(|name| parse_fixture(name))
+        .map(|mut tx| {
+            tx.slot = tx.slot + slot - 360_000_001;
+            tx.signature = format!("{}{}{}", &tx.signature[..40], &mint[..4], slot).into();
+            tx.mint = mint.into();
+            tx
+        })
+        .collect()
+}
+
+const VALIDATORS: [&str; 3] = ["Alpha", "Bravo", "Charlie"];
+
+// A cluster leading four slots at a time in turn, with 1024-slot epochs. Without `schedules`
+// the node has pruned its leader schedules and only answers getSlotLeaders. Only the slots in
+// `blocks` have a block to price.
+struct Cluster {
+    schedules: bool,
+    blocks: BTreeMap<u64, Vec<u64>>,
+    calls: RefCell<Vec<String>>,
+}
+
+impl Cluster {
+    fn new(schedules: bool) -> Self {
+        Self { schedules, blocks: BTreeMap::new(), calls: RefCell::new(Vec::new()) }
+    }
+
+    fn leader(slot: u64) -> &'static str {
+        VALIDATORS[(slot / 4 % 3) as usize]
+    }
+}
+
+impl TransactionSource for Cluster {
+    fn signatures_page(
+        &self,
+        address: &Pubkey,
+        _before: Option<&str>,
+        _until: Option<&str>,
+        _limit: usize,
+    ) -> Result<Vec<String>, Error> {
+        Err(Error::Rpc(format!("No history for {}", address)))
+    }
+
+    fn transaction(
+        &self,
+        signature: &str,
+        _encoding: UiTransactionEncoding,
+    ) -> Result<EncodedConfirmedTransactionWithStatusMeta, Error> {
+        Err(Error::Rpc(format!("Transaction {} not found", signature)))
+    }
+
+    fn account_data(&self, address: &Pubkey) -> Result<Vec<u8>, Error> {
+        Err(Error::Rpc(format!("No account {}", address)))
+    }
+
+    fn block_signatures(&self, slot: u64) -> Result<Vec<String>, Error> {
+        Err(Error::Rpc(format!("No block {}", slot)))
+    }
+
+    fn block_compute_unit_prices(&self, slot: u64) -> Result<Vec<u64>, Error> {
+        self.calls.borrow_mut().push(format!("getBlock {}", slot));
+        self.blocks.get(&slot).cloned().ok_or_else(|| Error::Rpc(format!("Block {} not available", slot)))
+    }
+
+    fn slot_leaders(&self, start_slot: u64, limit: u64) -> Result<Vec<String>, Error> {
+        self.calls.borrow_mut().push(format!("getSlotLeaders {} {}", start_slot, limit));
+        Ok((start_slot..start_slot + limit).map(|slot| Cluster::leader(slot).to_string()).collect())
+    }
+
+    fn epoch_schedule(&self) -> Result<EpochSchedule, Error> {
+        self.calls.borrow_mut().push("getEpochSchedule".to_string());
+        Ok(EpochSchedule::custom(1024, 1024, false))
+    }
+
+    fn leader_schedule(&self, slot: u64) -> Result<Option<HashMap<String, Vec<usize>>>, Error> {
+        self.calls.borrow_mut().push(format!("getLeaderSchedule {}", slot));
+        if !self.schedules {
+            return Ok(None);
+        }
+        let mut schedule: HashMap<String, Vec<usize>> = HashMap::new();
+        for index in 0..1024 {
+            schedule.entry(Cluster::leader(slot + index as u64).to_string()).or_default().push(index);
+        }
+        Ok(Some(schedule))
+    }
+}
+
+#[test]
+fn leaders_rank_by_sandwiches_hosted_and_break_ties_by_address() {
+    // Charlie, Alpha, Bravo, Charlie, Alpha: Charlie is seen first but ties with Alpha
+    let trades: Vec<ParsedTransaction> =
+        (2..7).flat_map(|hundred| sandwich_at(MINT, 360_000_001 + hundred * 100)).collect();
+    let mut summary = detect_wide_attacks(&trades, &DetectorConfig::default());
+    assert_eq!(summary.sandwiches.len(), 5);
+    let cluster = Cluster::new(false);
+    let mut schedules = LeaderSchedules::load(None).unwrap();
+    assert!(attach_slot_leaders(&mut summary, &cluster, &mut schedules).is_empty());
+
+    // no schedule for either epoch: every victim slot comes from one getSlotLeaders call
+    assert_eq!(
+        *cluster.calls.borrow(),
+        [
+            "getEpochSchedule",
+            "getLeaderSchedule 359999488",
+            "getLeaderSchedule 360000512",
+            "getSlotLeaders 360000201 401"
+        ]
+    );
+    let hosts: Vec<_> = summary.sandwiches.iter().map(|det| det.leader.clone().unwrap()).collect();
+    assert_eq!(hosts, ["Charlie", "Alpha", "Bravo", "Charlie", "Alpha"]);
+
+    fn ranked(leaders: &[LeaderStats]) -> Vec<(&str, usize)> {
+        leaders.iter().map(|stats| (stats.leader.as_str(), stats.sandwiches)).collect()
+    }
+    assert_eq!(ranked(&summary.leaders), [("Alpha", 2), ("Charlie", 2), ("Bravo", 1)]);
+    let sandwich = &summary.sandwiches[0];
+    assert_eq!(summary.leaders[0].extracted_lamports, 2 * sandwich.net_profit_sol);
+    assert_eq!(summary.leaders[0].victim_loss_lamports, 2 * sandwich.victim_loss());
+
+    // a sandwich whose leader isn't known counts for nobody
+    summary.sandwiches[1].leader = None;
+    assert_eq!(ranked(&leader_stats(&summary.sandwiches)), [("Charlie", 2), ("Alpha", 1), ("Bravo", 1)]);
+    assert!(leader_stats(&[]).is_empty());
+}
+
+#[test]
+fn leader_targeting_adds_up_an_attackers_sandwiches_across_mints_from_cached_schedules() {
+    let other_mint = Pubkey::new_from_array([9; 32]).to_string();
+    // five victims in Alpha's slots, then one in Bravo's, all in the epoch from slot 359999488
+    let slots: Vec<u64> = (0..5).map(|at| 360_000_001 + at * 36).chain([360_000_221]).collect();
+    let first: Vec<ParsedTransaction> = slots[..3].iter().flat_map(|&slot| sandwich_at(MINT, slot)).collect();
+    let second: Vec<ParsedTransaction> = slots[3..].iter().flat_map(|&slot| sandwich_at(&other_mint, slot)).collect();
+    let cfg = DetectorConfig::default();
+    let dir = std::env::temp_dir().join(format!("rusty-leaders-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let cache = dir.join("schedules.json").display().to_string();
+    let _ = fs::remove_file(&cache);
+
+    // the first mint's run fetches the epoch's schedule once and saves it
+    let cluster = Cluster::new(true);
+    let mut summary = detect_wide_attacks(&first, &cfg);
+    let mut schedules = LeaderSchedules::load(Some(&cache)).unwrap();
+    assert!(attach_slot_leaders(&mut summary, &cluster, &mut schedules).is_empty());
+    assert_eq!(*cluster.calls.borrow(), ["getEpochSchedule", "getLeaderSchedule 359999488"]);
+    // three sandwiches are too few to say where an attacker lands
+    assert!(summary.leader_targeting.is_empty());
+
+    // later runs on another mint, or on both, read the cache and fetch nothing
+    let cluster = Cluster::new(true);
+    let mut summary = detect_wide_attacks(&second, &cfg);
+    let mut schedules = LeaderSchedules::load(Some(&cache)).unwrap();
+    assert!(attach_slot_leaders(&mut summary, &cluster, &mut schedules).is_empty());
+    assert!(summary.leader_targeting.is_empty());
+    let mut summary = detect_wide_attacks(&[first, second].concat(), &cfg);
+    assert_eq!(summary.sandwiches.len(), 6);
+    let mut schedules = LeaderSchedules::load(Some(&cache)).unwrap();
+    assert!(attach_slot_leaders(&mut summary, &cluster, &mut schedules).is_empty());
+    assert!(cluster.calls.borrow().is_empty(), "{:?}", cluster.calls.borrow());
+
+    let hosted: Vec<_> = summary.leaders.iter().map(|stats| (stats.leader.as_str(), stats.sandwiches)).collect();
+    assert_eq!(hosted, [("Alpha", 5), ("Bravo", 1)]);
+    assert_eq!(summary.leader_targeting.len(), 1);
+    let targeting = &summary.leader_targeting[0];
+    assert_eq!(targeting.attacker, "4uHqkCs3Y4vZd3Ejax1uhpvDJiqMXsBQE9fxhGAJxq1V");
+    assert_eq!((targeting.sandwiches, targeting.leader.as_str(), targeting.leader_sandwiches), (6, "Alpha", 5));
+    let led = (359_999_488..360_000_512).filter(|&slot| Cluster::leader(slot) == "Alpha").count();
+    let scheduled_share_pct = led as f64 / 1024.0 * 100.0;
+    assert!((targeting.share_pct - 500.0 / 6.0).abs() < 1e-9, "{}", targeting.share_pct);
+    assert!((targeting.scheduled_share_pct - scheduled_share_pct).abs() < 1e-9, "{}", targeting.scheduled_share_pct);
+    assert!((targeting.lift - 500.0 / 6.0 / scheduled_share_pct).abs() < 1e-9, "{}", targeting.lift);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn cu_prices_rank_the_victim_and_attackers_against_their_slot() {
+    let mut trades: Vec<ParsedTransaction> = ["sandwich_front", "sandwich_victim", "sandwich_back"]
+        .iter()
+        .flat_map(|name| parse_fixture(name))
+        .collect();
+    // each fixture bids through a SetComputeUnitPrice instruction
+    assert!(trades.iter().all(|tx| tx.compute_unit_price == 100_000));
+    let mut summary = detect_wide_attacks(&trades, &DetectorConfig::default());
+    let victim_slot = summary.sandwiches[0].victim.slot;
+
+    // the attacker legs sit in the slots either side, so only the victim is ranked
+    rank_cu_prices(&mut summary, &trade_cu_prices(&trades));
+    let ranking = summary.sandwiches[0].cu_price.clone().unwrap();
+    assert_eq!((ranking.ranked_transactions, ranking.full_block, ranking.victim_percentile), (1, false, 50.0));
+    assert_eq!((ranking.frontrun_cu_price, ranking.backrun_cu_price), (None, None));
+
+    // both legs landed in the victim's slot, next to a two-leg trade bidding the victim's price
+    (trades[0].slot, trades[0].compute_unit_price) = (victim_slot, 250_000);
+    (trades[2].slot, trades[2].compute_unit_price) = (victim_slot, 50_000);
+    let sandwich = &mut summary.sandwiches[0];
+    (sandwich.frontruns[0], sandwich.backruns[0]) = (trades[0].clone(), trades[2].clone());
+    trades.extend(parse_fixture("multi_leg").into_iter().map(|mut leg| {
+        (leg.slot, leg.compute_unit_price) = (victim_slot, 100_000);
+        leg
+    }));
+    let mut prices = trade_cu_prices(&trades);
+    assert_eq!(prices[&victim_slot].prices, [50_000, 100_000, 100_000, 250_000]);
+    rank_cu_prices(&mut summary, &prices);
+    let ranking = summary.sandwiches[0].cu_price.clone().unwrap();
+    // ties count half: the victim shares its price with one of four transactions
+    assert_eq!((ranking.ranked_transactions, ranking.victim_percentile), (4, 50.0));
+    assert_eq!((ranking.frontrun_cu_price, ranking.frontrun_percentile), (Some(250_000), Some(87.5)));
+    assert_eq!((ranking.backrun_cu_price, ranking.backrun_percentile), (Some(50_000), Some(12.5)));
+
+    // a block that can't be fetched keeps the scanned prices; a fetched one replaces them
+    let mut cluster = Cluster::new(false);
+    assert_eq!(fetch_block_cu_prices(&cluster, &summary, &mut prices).len(), 1);
+    assert!(!prices[&victim_slot].full_block);
+    cluster.blocks.insert(victim_slot, vec![400_000, 0, 100_000, 1_000_000, 50_000, 0, 120_000, 250_000]);
+    assert!(fetch_block_cu_prices(&cluster, &summary, &mut prices).is_empty());
+    rank_cu_prices(&mut summary, &prices);
+    let ranking = summary.sandwiches[0].cu_price.clone().unwrap();
+    assert_eq!((ranking.ranked_transactions, ranking.full_block), (8, true));
+    assert_eq!(ranking.victim_percentile, 43.75);
+    assert_eq!((ranking.frontrun_percentile, ranking.backrun_percentile), (Some(68.75), Some(31.25)));
+
+    let slot = SlotPrices { prices: vec![100, 200, 200, 300], full_block: true };
+    let percentiles: Vec<f64> = [50, 100, 200, 300, 400].iter().map(|&price| slot.percentile(price)).collect();
+    assert_eq!(percentiles, [0.0, 12.5, 50.0, 87.5, 100.0]);
+    assert_eq!(SlotPrices::default().percentile(100), 0.0);
+}
+
+#[test]
+fn mint_stats_count_each_mints_victims_attackers_and_median_loss() {
+    let other_mint = Pubkey::new_from_array([9; 32]).to_string();
+    let quiet_mint = Pubkey::new_from_array([10; 32]).to_string();
+    let mut trades: Vec<ParsedTransaction> =
+        [1, 101, 201].iter().flat_map(|&offset| sandwich_at(MINT, 360_000_000 + offset)).collect();
+    trades.push(retimed("buy", "Bystander", 360_000_600, 1.0));
+    trades.extend(sandwich_at(&other_mint, 360_000_301));
+    // the other mint's second sandwich is run by another bot
+    trades.extend(sandwich_at(&other_mint, 360_000_401).into_iter().map(|mut tx| {
+        if tx.signer.starts_with("4uHq") {
+            tx.signer = "Rival".into();
+        }
+        tx
+    }));
+    let mut quiet = retimed("sell", "Bystander", 360_000_700, 1.0);
+    quiet.mint = quiet_mint.as_str().into();
+    trades.push(quiet);
+
+    let mut cfg = DetectorConfig::default();
+    let mut summary = detect_wide_attacks(&trades, &cfg);
+    assert_eq!(summary.sandwiches.len(), 5);
+    let net_profit = summary.sandwiches[0].net_profit_sol;
+    for (det, loss) in summary.sandwiches.iter_mut().zip([30, 10, 20, 5, 15]) {
+        det.impact_loss_lamports = Some(loss);
+    }
+    summary.rebuild_aggregates(&trades, &cfg);
+    let stats = |summary: &rusty::detect::DetectionSummary, mint: &str| {
+        let stats = summary.mint_stats.iter().find(|stats| stats.mint == mint).unwrap();
+        let counts = (stats.total_trades, stats.total_victims, stats.unique_attackers);
+        (counts, stats.total_sol_extracted, stats.median_victim_loss)
+    };
+    assert_eq!(summary.mint_stats.len(), 3);
+    assert_eq!(stats(&summary, MINT), ((10, 3, 1), 3 * net_profit, 20));
+    // an even number of losses takes the mean of the middle two
+    assert_eq!(stats(&summary, &other_mint), ((6, 2, 2), 2 * net_profit, 10));
+    assert_eq!(stats(&summary, &quiet_mint), ((1, 0, 0), 0, 0));
+    for (mint, rate) in [(MINT, 30.0), (&other_mint, 100.0 / 3.0), (&quiet_mint, 0.0)] {
+        let stats = summary.mint_stats.iter().find(|stats| stats.mint == mint).unwrap();
+        assert!((stats.attack_rate_pct - rate).abs() < 1e-9, "{}: {}", mint, stats.attack_rate_pct);
+        assert_eq!(stats.slot_gap, cfg.max_slot_gap);
+    }
+
+    // wallets linked after the fact count once, with the trade counts kept from the stats
+    let attacker = "4uHqkCs3Y4vZd3Ejax1uhpvDJiqMXsBQE9fxhGAJxq1V";
+    cfg.linked_wallets = HashMap::from([("Rival".to_string(), attacker.to_string())]);
+    summary.rebuild_from_stats(&cfg);
+    assert_eq!(stats(&summary, &other_mint), ((6, 2, 1), 2 * net_profit, 10));
+}
+
+#[test]
+fn leaderboard_ranks_attackers_by_profit_then_address() {
+    let other_mint = Pubkey::new_from_array([9; 32]).to_string();
+    // the fixture sandwich at `slot` on `mint`, run by `attacker` against `victim`
+    let run = |attacker: &str, victim: &str, mint: &str, slot: u64| -> Vec<ParsedTransaction> {
+        sandwich_at(mint, slot)
+            .into_iter()
+            .map(|mut tx| {
+                tx.signer = if tx.signer.starts_with("A1Dx") { victim } else { attacker }.into();
+                tx
+            })
+            .collect()
+    };
+    let mut trades: Vec<ParsedTransaction> = [
+        run("Bravo", "Victim one", MINT, 360_000_001),
+        run("Bravo", "Victim one", MINT, 360_000_101),
+        run("Alpha", "Victim one", MINT, 360_000_201),
+        run("Alpha", "Victim two", &other_mint, 360_000_301),
+        run("Charlie", "Victim two", MINT, 360_000_401),
+        run("Delta", "Victim three", MINT, 360_000_501),
+    ]
+    .concat();
+    // Delta sold back for 0.05 SOL less
+    trades.last_mut().unwrap().sol_change -= 50_000_000;
+    let summary = detect_wide_attacks(&trades, &DetectorConfig::default());
+    assert_eq!(summary.sandwiches.len(), 6);
+    let profit = summary.sandwiches[0].net_profit_sol;
+
+    let board = &summary.attacker_leaderboard;
+    let ranked: Vec<(&str, i64, usize, usize)> = board
+        .iter()
+        .map(|stats| (stats.attacker.as_str(), stats.total_profit_lamports, stats.attacks, stats.victims))
+        .collect();
+    // Alpha and Bravo tie on profit and rank by address
+    assert_eq!(
+        ranked,
+        [
+            ("Alpha", 2 * profit, 2, 2),
+            ("Bravo", 2 * profit, 2, 1),
+            ("Charlie", profit, 1, 1),
+            ("Delta", profit - 50_000_000, 1, 1)
+        ]
+    );
+    let mut alpha_mints = vec![MINT.to_string(), other_mint.clone()];
+    alpha_mints.sort();
+    assert_eq!(board[0].mints, alpha_mints);
+    assert_eq!(board[1].mints, [MINT]);
+    assert_eq!(board[0].avg_profit_lamports, profit);
+
+    // a wallet seen in an earlier run, or on the known-bot list, is flagged without moving
+    let known_attackers = HashSet::from(["Charlie".to_string()]);
+    let known_bots = HashMap::from([("Delta".to_string(), "Sandwich bot".to_string())]);
+    let entities = AttackerEntities::default();
+    let board = attacker_leaderboard(&summary.sandwiches, &known_attackers, &known_bots, &entities);
+    let tags: Vec<(&str, String)> = board.iter().map(|stats| (stats.attacker.as_str(), stats.tag())).collect();
+    let tags: Vec<(&str, &str)> = tags.iter().map(|(attacker, tag)| (*attacker, tag.as_str())).collect();
+    assert_eq!(
+        tags,
+        [("Alpha", ""), ("Bravo", ""), ("Charlie", " (known)"), ("Delta", " (known attacker: Sandwich bot)")]
+    );
+    assert!(attacker_leaderboard(&[], &known_attackers, &known_bots, &entities).is_empty());
+}
+
+#[test]
+fn rate_limiter_spends_its_burst_then_waits_for_tokens() {
+    let limiter = RateLimiter::new(20.0, 5);
+    let started = Instant::now();
+    (0..5).for_each(|_| limiter.acquire());
+    assert!(started.elapsed() < Duration::from_millis(40), "{:?}", started.elapsed());
+    // the bucket is empty: the sixth request waits 1/20 s for a token
+    limiter.acquire();
+    assert!(started.elapsed() >= Duration::from_millis(40), "{:?}", started.elapsed());
+
+    // each 429 halves the rate down to a tenth of the target; 20 successes step it back up
+    limiter.on_rate_limited();
+    assert_eq!(limiter.rate(), 10.0);
+    (0..10).for_each(|_| limiter.on_rate_limited());
+    assert_eq!(limiter.rate(), 2.0);
+    (0..19).for_each(|_| limiter.on_success());
+    assert_eq!(limiter.rate(), 2.0);
+    limiter.on_success();
+    assert_eq!(limiter.rate(), 2.5);
+
+    let fixtures = FixtureSource::load(concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures")).unwrap();
+    let (_, signature) = load("buy");
+    let calls = Cell::new(0);
+    let fetch = |error, failing| {
+        calls.set(0);
+        let flaky = Flaky::new(&fixtures, error, failing, &calls);
+        let source = RateLimitedSource::new(flaky, RateLimiter::new(1_000.0, 10));
+        let fetched = source.transaction(&signature, UiTransactionEncoding::Json).map(|_| ());
+        (fetched, calls.get(), source.limiter().rate())
+    };
+    // 429s are retried at a lower rate, three times at most; other errors aren't retried
+    assert!(matches!(fetch("HTTP 429 Too Many Requests", 3), (Ok(()), 4, 125.0)));
+    assert!(matches!(fetch("HTTP 429 Too Many Requests", 4), (Err(Error::Rpc(_)), 4, 125.0)));
+    assert!(matches!(fetch("connection refused", 1), (Err(Error::Rpc(_)), 1, 1_000.0)));
+}
+
+#[test]
+fn failover_moves_past_a_failing_endpoint_and_cools_it_down() {
+    let fixtures = FixtureSource::load(concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures")).unwrap();
+    let (_, signature) = load("buy");
+    let (primary_calls, fallback_calls) = (Cell::new(0), Cell::new(0));
+    let source = FailoverSource::new(vec![
+        ("primary".to_string(), Flaky::new(&fixtures, "connection refused", usize::MAX, &primary_calls)),
+        ("fallback".to_string(), Flaky::new(&fixtures, "", 0, &fallback_calls)),
+    ]);
+    let fetch = || source.transaction(&signature, UiTransactionEncoding::Json).map(|_| ());
+
+    fetch().unwrap();
+    assert_eq!((primary_calls.get(), fallback_calls.get()), (1, 1));
+    // round-robin starts the next call on the fallback, which answers alone
+    fetch().unwrap();
+    assert_eq!((primary_calls.get(), fallback_calls.get()), (1, 2));
+    fetch().unwrap();
+    fetch().unwrap();
+    fetch().unwrap();
+    let health = source.health();
+    assert_eq!((health[0].failures, health[0].consecutive_failures, health[0].available), (3, 3, false));
+    assert_eq!((health[1].successes, health[1].failures, health[1].available), (5, 0, true));
+    // a cooling endpoint is tried last, so the fallback takes every call
+    let tried = primary_calls.get();
+    fetch().unwrap();
+    fetch().unwrap();
+    assert_eq!(primary_calls.get(), tried);
+
+    // with no endpoint answering, the last error names its endpoint
+    let down_calls = Cell::new(0);
+    let down = FailoverSource::new(vec![
+        ("a".to_string(), Flaky::new(&fixtures, "timed out", usize::MAX, &down_calls)),
+        ("b".to_string(), Flaky::new(&fixtures, "timed out", usize::MAX, &down_calls)),
+    ]);
+    let error = down.transaction(&signature, UiTransactionEncoding::Json).unwrap_err();
+    assert!(matches!(&error, Error::Rpc(message) if message == "b: timed out"), "{}", error);
+    assert_eq!(down_calls.get(), 2);
+    assert_eq!(endpoint_name("https://rpc.example.com/?api-key=secret"), "https://rpc.example.com/");
+}
+
+#[test]
+fn rpc_burst_must_fit_the_limiter() {
+    let dir = std::env::temp_dir().join(format!("rusty-burst-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let config = dir.join("rusty.toml");
+    let run = |burst: &str| {
+        fs::write(&config, format!("[rpc]\nburst = {}\n", burst)).unwrap();
+        Command::new(env!("CARGO_BIN_EXE_rusty"))
+            .current_dir(env!("CARGO_MANIFEST_DIR"))
+            .args([MINT, "--fixtures", "fixtures", "--quiet", "--config", config.to_str().unwrap()])
+            .output()
+            .unwrap()
+    };
+    assert!(run("25").status.success());
+    let too_big = run("4294967296");
+    assert_eq!(too_big.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&too_big.stderr).contains("'burst' must be at most 4294967295"));
+    assert_eq!(run("-1").status.code(), Some(2));
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+struct RevertedBuys;
+
+impl Detector for RevertedBuys {
+    fn name(&self) -> &str {
+        "reverted_buys"
+    }
+
+    fn on_window(&self, window: &Window<'_>) -> Vec<DetectionEvent> {
+        window
+            .trades
+            .iter()
+            .filter(|tx| tx.outcome == ExecutionOutcome::Reverted)
+            .map(|tx| {
+                DetectionEvent::Custom(CustomDetection {
+                    detector: self.name().to_string(),
+                    description: "reverted buy".to_string(),
+                    transactions: vec![tx.clone()],
+                })
+            })
+            .collect()
+    }
+}
+
+#[test]
+fn extra_detectors_run_alongside_the_builtin_rules() {
+    let trades: Vec<ParsedTransaction> = ["sandwich_front", "sandwich_victim", "sandwich_back", "failed_buy"]
+        .iter()
+        .flat_map(|name| parse_fixture(name))
+        .collect();
+    let extra: Vec<Box<dyn Detector>> = vec![Box::new(RevertedBuys)];
+    let summary = detect_with(&trades, &DetectorConfig::default(), &extra);
+
+    assert_eq!(summary.sandwiches.len(), 1);
+    assert_eq!(summary.custom.len(), 1);
+    assert_eq!(summary.custom[0].detector, "reverted_buys");
+    assert_eq!(summary.custom[0].transactions[0].outcome, ExecutionOutcome::Reverted);
+}
+
+// keeps what a real sink would have put on the wire
+#[derive(Default)]
+struct CapturedSink {
+    sent: Vec<(String, serde_json::Value)>,
+}
+
+impl EventSink for CapturedSink {
+    fn send(&mut self, channel: &str, payload: &str) -> rusty::error::Result<()> {
+        self.sent.push((channel.to_string(), serde_json::from_str(payload).unwrap()));
+        Ok(())
+    }
+
+    fn flush(&mut self) -> rusty::error::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn published_envelopes_carry_the_schema_version() {
+    let trades: Vec<ParsedTransaction> = ["sandwich_front", "sandwich_victim", "sandwich_back"]
+        .iter()
+        .flat_map(|name| parse_fixture(name))
+        .collect();
+    let summary = detect_wide_attacks(&trades, &DetectorConfig::default());
+    let mut sink = CapturedSink::default();
+    sink::publish_trades(&mut sink, MINT, &trades).unwrap();
+    sink::publish_events(&mut sink, MINT, &sink::summary_events(&summary)).unwrap();
+
+    assert_eq!(sink::SCHEMA_VERSION, 1);
+    assert!(sink.sent.len() > trades.len());
+    for (channel, envelope) in &sink.sent {
+        assert_eq!(envelope["schema_version"], sink::SCHEMA_VERSION, "{}", channel);
+        assert_eq!(envelope["mint"], MINT);
+        assert!(envelope["published_at"].as_u64().is_some());
+        assert!(envelope["data"].is_object());
+        let kind = envelope["kind"].as_str().unwrap();
+        let expected = if kind == "trade" { "trades".to_string() } else { format!("detections.{}", kind) };
+        assert_eq!(*channel, expected);
+    }
+    let sandwich = sink.sent.iter().find(|(channel, _)| channel == "detections.sandwich").unwrap();
+    assert_eq!(
+        sandwich.1["data"]["victim"]["signer"],
+        "A1DxnH9d8tfhxTHn3FL99aoF5rF4UqQRney5RpgmQRii"
+    );
+}
+
+// the binary's report over the fixture sandwich; a changed rendering fails here and is
+// accepted by reviewing the snapshot diff (`cargo insta review`, or INSTA_UPDATE=always)
+fn run_report(args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_rusty"))
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .args([MINT, "--fixtures", "fixtures", "--quiet"])
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn report_renderings_match_snapshots() {
+    insta::assert_snapshot!("text_report", run_report(&[]));
+
+    let dir = std::env::temp_dir().join(format!("rusty-snapshots-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let victims = dir.join("victims.csv");
+    let legs = dir.join("legs.csv");
+    let json = run_report(&[
+        "--output",
+        "json",
+        "--victims-file",
+        victims.to_str().unwrap(),
+        "--dune-file",
+        legs.to_str().unwrap(),
+    ]);
+    insta::assert_snapshot!("json_report", json);
+    insta::assert_snapshot!("victims_csv", fs::read_to_string(&victims).unwrap());
+    insta::assert_snapshot!("attack_legs_csv", fs::read_to_string(&legs).unwrap());
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+// The fixture scan replayed from `--input` after its mint, attacker and token metadata were
+// swapped for markup; the attacker's name ends in multi-byte characters where links shorten it.
+fn hostile_report(format: &str) -> String {
+    let dir = std::env::temp_dir().join(format!("rusty-hostile-{}-{}", format, std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("trades.json");
+    let path = path.to_str().unwrap();
+    run_report(&["--save-trades", path]);
+
+    let mut saved = SavedTrades::load(path).unwrap();
+    let mint = "<b>mint</b>&co";
+    saved.mint = mint.to_string();
+    for tx in &mut saved.trades {
+        tx.mint = mint.into();
+        if tx.signer.starts_with("4uHq") {
+            tx.signer = "<img src=x onerror=\"alert('|')\">€€é".into();
+        }
+    }
+    saved.token_metadata = Some(MintMetadata {
+        name: "<script>alert(1)</script>".to_string(),
+        symbol: "<i>|".to_string(),
+        uri: String::new(),
+        image: Some("https://example.com/logo.png\" onload=\"alert(1)".to_string()),
+    });
+    saved.save(path).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_rusty"))
+        .args(["--input", path, "--quiet", "--output", format])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    fs::remove_dir_all(&dir).unwrap();
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn html_report_matches_its_snapshot_and_escapes_chain_strings() {
+    insta::assert_snapshot!("html_report", run_report(&["--output", "html"]));
+
+    let html = hostile_report("html");
+    insta::assert_snapshot!("html_report_escaped", html);
+    for raw in ["<b>mint", "<img src=x", "<script>", "<i>", "\" onload="] {
+        assert!(!html.contains(raw), "{} reached the page", raw);
+    }
+    assert!(html.contains("&lt;img src=x onerror=&quot;alert('|')&quot;&gt;€€é"));
+
+    // bot labels come from lists that may be downloaded
+    let dir = std::env::temp_dir().join(format!("rusty-html-bots-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let bots = dir.join("bots.txt");
+    fs::write(&bots, "4uHqkCs3Y4vZd3Ejax1uhpvDJiqMXsBQE9fxhGAJxq1V <b>bot</b>\n").unwrap();
+    let html = run_report(&["--output", "html", "--bot-list", bots.to_str().unwrap()]);
+    assert!(html.contains("(known attacker: &lt;b&gt;bot&lt;/b&gt;)"), "{}", html);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn markdown_report_matches_its_snapshot_and_keeps_chain_strings_literal() {
+    insta::assert_snapshot!("markdown_report", run_report(&["--output", "markdown"]));
+
+    let md = hostile_report("markdown");
+    insta::assert_snapshot!("markdown_report_escaped", md);
+    // every table row keeps its column count: no chain string adds a `|` cell break
+    for table in md.split("\n\n").filter(|block| block.starts_with('|')) {
+        let cells = |row: &str| row.replace("\\|", "").matches('|').count();
+        let rows: Vec<&str> = table.lines().collect();
+        assert!(rows.iter().all(|row| cells(row) == cells(rows[0])), "{}", table);
+    }
+    assert!(md.contains("# Sandwich report for $\\<i\\>\\| [`<b>min…/b>&co`]("), "{}", md);
+    assert!(md.contains("[`<img s…)\">€€é`](https://solscan.io/account/%3Cimg%20src=x%20onerror=%22alert%28'%7C'%29"));
+
+    let dir = std::env::temp_dir().join(format!("rusty-markdown-bots-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let bots = dir.join("bots.txt");
+    fs::write(&bots, "4uHqkCs3Y4vZd3Ejax1uhpvDJiqMXsBQE9fxhGAJxq1V |*bot*|\n").unwrap();
+    let md = run_report(&["--output", "markdown", "--bot-list", bots.to_str().unwrap()]);
+    assert!(md.contains("(known attacker: \\|\\*bot\\*\\|)"), "{}", md);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn dataset_rows_read_back_the_same_from_csv_and_json() {
+    let dir = std::env::temp_dir().join(format!("rusty-dataset-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let (csv, json) = (dir.join("dataset.csv"), dir.join("dataset.json"));
+    run_report(&["--dataset", csv.to_str().unwrap()]);
+    run_report(&["--dataset", json.to_str().unwrap()]);
+
+    let rows: Vec<CandidateFeatures> = serde_json::from_str(&fs::read_to_string(&json).unwrap()).unwrap();
+    let labelled: Vec<&CandidateFeatures> = rows.iter().filter(|row| row.label).collect();
+    assert_eq!(labelled.len(), 1, "{:?}", rows);
+    let sandwich = labelled[0];
+    assert!(sandwich.victim_signature.starts_with("5sv2vj") && sandwich.same_signer);
+    assert_eq!((sandwich.front_slot_gap, sandwich.back_slot_gap), (1, 1));
+    assert_eq!(sandwich.profit_lamports, 97_950_720);
+
+    let csv = fs::read_to_string(&csv).unwrap();
+    let mut lines = csv.lines();
+    let header: Vec<&str> = lines.next().unwrap().split(',').collect();
+    // the same columns as the JSON fields, which serde_json lists sorted
+    let mut columns = header.clone();
+    columns.sort_unstable();
+    let fields: Vec<String> = serde_json::to_value(&rows[0]).unwrap().as_object().unwrap().keys().cloned().collect();
+    assert_eq!(columns, fields);
+    let read_back: Vec<Vec<String>> = lines.map(|line| line.split(',').map(str::to_string).collect()).collect();
+    assert_eq!(read_back.len(), rows.len());
+    for (cells, row) in read_back.iter().zip(&rows) {
+        let value = |name: &str| &cells[header.iter().position(|column| *column == name).unwrap()];
+        assert_eq!(value("victim_signature"), &row.victim_signature);
+        assert_eq!(value("front_signature"), &row.front_signature);
+        assert_eq!(value("back_signature"), &row.back_signature);
+        assert_eq!(value("victim_leg").parse::<usize>().unwrap(), row.victim_leg);
+        assert_eq!(value("front_slot_gap").parse::<u64>().unwrap(), row.front_slot_gap);
+        assert_eq!(value("back_slot_gap").parse::<u64>().unwrap(), row.back_slot_gap);
+        let ratio = value("front_victim_size_ratio").parse::<f64>().unwrap();
+        assert!((ratio - row.front_victim_size_ratio).abs() < 1e-6);
+        let ratio = value("back_front_token_ratio").parse::<f64>().unwrap();
+        assert!((ratio - row.back_front_token_ratio).abs() < 1e-6);
+        assert_eq!(value("priority_fee_delta").parse::<i64>().unwrap(), row.priority_fee_delta);
+        assert_eq!(value("same_signer"), if row.same_signer { "1" } else { "0" });
+        assert_eq!(value("profit_lamports").parse::<i64>().unwrap(), row.profit_lamports);
+        assert_eq!(value("label"), if row.label { "1" } else { "0" });
+    }
+
+    // Parquet isn't written, and a .parquet path is refused before anything is fetched
+    let parquet = dir.join("dataset.parquet");
+    let output = Command::new(env!("CARGO_BIN_EXE_rusty"))
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .args([MINT, "--fixtures", "fixtures", "--quiet", "--dataset", parquet.to_str().unwrap()])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("not Parquet"));
+    assert!(!parquet.exists());
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn replaying_saved_trades_gives_the_live_detections() {
+    let dir = std::env::temp_dir().join(format!("rusty-replay-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let saved = dir.join("trades.json");
+    let saved = saved.to_str().unwrap();
+    let live = run_report(&["--output", "json", "--save-trades", saved]);
+    assert_eq!(SavedTrades::load(saved).unwrap().mint, MINT);
+
+    // no fixtures and no RPC: everything comes from the capture
+    let replay = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_rusty"))
+            .args(["--input", saved, "--quiet", "--output", "json"])
+            .args(args)
+            .env_remove("HELIUS_API_KEY")
+            .output()
+            .unwrap()
+    };
+    let output = replay(&[]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), live);
+    assert!(replay(&[MINT]).status.success());
+
+    // a capture of another mint, or one that doesn't parse, is refused
+    let other = replay(&["So11111111111111111111111111111111111111112"]);
+    assert_eq!(other.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&other.stderr).contains("--input holds trades for"));
+    fs::write(saved, "{\"mint\": ").unwrap();
+    assert_eq!(replay(&[]).status.code(), Some(6));
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn attacker_registry_carries_attackers_across_runs() {
+    let dir = std::env::temp_dir().join(format!("rusty-registry-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("attackers.json");
+    let path = path.to_str().unwrap();
+    assert!(AttackerRegistry::load(path).unwrap().attackers.is_empty());
+
+    let attacker = "4uHqkCs3Y4vZd3Ejax1uhpvDJiqMXsBQE9fxhGAJxq1V";
+    let scan = || {
+        let report = run_report(&["--output", "json", "--registry", path]);
+        let report: serde_json::Value = serde_json::from_str(&report).unwrap();
+        report["attacker_leaderboard"][0]["previously_seen"].as_bool().unwrap()
+    };
+    assert!(!scan());
+    assert!(scan());
+
+    let registry = AttackerRegistry::load(path).unwrap();
+    assert_eq!(registry.known_signers().into_iter().collect::<Vec<_>>(), [attacker]);
+    let record = &registry.attackers[attacker];
+    assert_eq!((record.runs, record.attacks, record.victims), (2, 2, 2));
+    assert_eq!(record.total_profit_lamports, 2 * 97_950_720);
+    assert_eq!(record.mints.iter().collect::<Vec<_>>(), [MINT]);
+    registry.save(path).unwrap();
+    assert_eq!(AttackerRegistry::load(path).unwrap().attackers[attacker].runs, 2);
+
+    // a corrupt registry is a JSON error rather than a silently empty one
+    fs::write(path, "{\"attackers\": [").unwrap();
+    assert!(matches!(AttackerRegistry::load(path), Err(Error::Json { .. })));
+    let output = Command::new(env!("CARGO_BIN_EXE_rusty"))
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .args([MINT, "--fixtures", "fixtures", "--quiet", "--registry", path])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(6));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("attacker registry"));
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn daemon_skips_signatures_seen_in_earlier_runs() {
+    let dir = std::env::temp_dir().join(format!("rusty-daemon-seen-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let state = dir.join("state.json");
+    let events = dir.join("events.jsonl");
+    let cycle = || {
+        let output = Command::new(env!("CARGO_BIN_EXE_rusty"))
+            .current_dir(env!("CARGO_MANIFEST_DIR"))
+            .args(["daemon", MINT, "--fixtures", "fixtures", "--once", "--quiet"])
+            .args(["--state", state.to_str().unwrap(), "--events", events.to_str().unwrap()])
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        serde_json::from_str::<serde_json::Value>(&fs::read_to_string(&state).unwrap()).unwrap()
+    };
+    let first = cycle();
+    let logged = fs::read_to_string(&events).unwrap();
+    assert!(!logged.is_empty());
+
+    // a lost cursor hands the whole history out again; the remembered signatures are all skipped
+    let mut rewound = first.clone();
+    rewound["mints"][MINT]["last_signature"] = serde_json::Value::Null;
+    fs::write(&state, rewound.to_string()).unwrap();
+    let second = cycle();
+    let checkpoint = &second["mints"][MINT];
+    let remembered = first["mints"][MINT]["recent_signatures"].as_array().unwrap().len();
+    assert_eq!(checkpoint["duplicates"], remembered as u64);
+    assert_eq!(checkpoint["last_signature"], first["mints"][MINT]["last_signature"]);
+    assert_eq!(fs::read_to_string(&events).unwrap(), logged);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn daemon_works_off_a_backlog_oldest_first() {
+    let source = FixtureSource::load(concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures")).unwrap();
+    let newest_first = source.signatures(&Pubkey::from_str(MINT).unwrap(), 100).unwrap();
+    let dir = std::env::temp_dir().join(format!("rusty-daemon-backlog-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let state = dir.join("state.json");
+    let events = dir.join("events.jsonl");
+    // checkpointed at the oldest fixture, with seven newer signatures waiting
+    let oldest = &newest_first[newest_first.len() - 1];
+    fs::write(
+        &state,
+        serde_json::json!({ "mints": { MINT: { "last_signature": oldest, "last_slot": 0, "scans": 0, "detections": 0 } } })
+            .to_string(),
+    )
+    .unwrap();
+
+    let mut checkpoints = Vec::new();
+    for _ in 0..4 {
+        let output = Command::new(env!("CARGO_BIN_EXE_rusty"))
+            .current_dir(env!("CARGO_MANIFEST_DIR"))
+            .args(["daemon", MINT, "--fixtures", "fixtures", "--once", "--quiet", "--limit", "2"])
+            .args(["--state", state.to_str().unwrap(), "--events", events.to_str().unwrap()])
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        let saved: serde_json::Value = serde_json::from_str(&fs::read_to_string(&state).unwrap()).unwrap();
+        checkpoints.push(saved["mints"][MINT]["last_signature"].as_str().unwrap().to_string());
+    }
+    // two signatures a cycle, never past one that was not fetched
+    let expected: Vec<&String> = [5, 3, 1, 0].iter().map(|&n| &newest_first[n]).collect();
+    assert_eq!(checkpoints.iter().collect::<Vec<_>>(), expected);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn daemon_logs_a_growing_back_run_once_in_its_latest_form() {
+    let dir = std::env::temp_dir().join(format!("rusty-daemon-growing-{}", std::process::id()));
+    let fixtures = dir.join("fixtures");
+    fs::create_dir_all(&fixtures).unwrap();
+    for entry in fs::read_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures")).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_file() {
+            fs::copy(&path, fixtures.join(path.file_name().unwrap())).unwrap();
+        }
+    }
+    // the attacker sells again a slot later, so the victim's back-run event grows
+    let path = format!("{}/fixtures/sandwich_back.json", env!("CARGO_MANIFEST_DIR"));
+    let mut again: serde_json::Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+    again["slot"] = (again["slot"].as_u64().unwrap() + 1).into();
+    let signature = again["transaction"]["signatures"][0].as_str().unwrap().replacen('w', "x", 1);
+    again["transaction"]["signatures"][0] = signature.into();
+    fs::write(fixtures.join("sandwich_back_again.json"), again.to_string()).unwrap();
+
+    let events = dir.join("events.jsonl");
+    let output = Command::new(env!("CARGO_BIN_EXE_rusty"))
+        .args(["daemon", MINT, "--fixtures", fixtures.to_str().unwrap(), "--once", "--quiet"])
+        .args(["--state", dir.join("state.json").to_str().unwrap(), "--events", events.to_str().unwrap()])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let logged: Vec<serde_json::Value> = fs::read_to_string(&events)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    let back_runs: Vec<&serde_json::Value> = logged.iter().filter_map(|line| line["event"].get("BackRun")).collect();
+    assert_eq!(back_runs.len(), 1, "{:?}", logged);
+    assert_eq!(back_runs[0]["backruns"].as_array().unwrap().len(), 2);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn stream_refuses_arguments_that_need_every_trade() {
+    for args in [
+        &["--tx-index"][..],
+        &["--block-cu-prices"],
+        &["--nats", "nats://127.0.0.1:4222"],
+        &["--save-trades", "trades.json"],
+    ] {
+        let output = Command::new(env!("CARGO_BIN_EXE_rusty"))
+            .current_dir(env!("CARGO_MANIFEST_DIR"))
+            .args([MINT, "--fixtures", "fixtures", "--quiet", "--stream"])
+            .args(args)
+            .output()
+            .unwrap();
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert_eq!(output.status.code(), Some(2), "{:?}: {}", args, stderr);
+        assert!(stderr.contains("--stream keeps no trades"), "{:?}: {}", args, stderr);
+    }
+}
+
+#[test]
+fn threaded_scan_prints_the_same_summary() {
+    let scan = |args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_rusty"))
+            .current_dir(env!("CARGO_MANIFEST_DIR"))
+            .args([MINT, "--fixtures", "fixtures", "--quiet", "--output", "json"])
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        output.stdout
+    };
+    assert_eq!(scan(&["--threads", "3"]), scan(&[]));
+}
+
+#[test]
+fn dashboard_needs_a_terminal_before_anything_is_fetched() {
+    for args in [&["--output", "tui"][..], &["--output", "tui", "--stream"]] {
+        let output = Command::new(env!("CARGO_BIN_EXE_rusty"))
+            .current_dir(env!("CARGO_MANIFEST_DIR"))
+            .args([MINT, "--fixtures", "fixtures"])
+            .args(args)
+            .output()
+            .unwrap();
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert_eq!(output.status.code(), Some(2), "{:?}: {}", args, stderr);
+        assert!(stderr.contains("needs a terminal"), "{:?}: {}", args, stderr);
+        assert!(!stderr.contains("Found"), "{:?}: {}", args, stderr);
+    }
+}
+
+#[test]
+fn fees_split_between_protocol_creator_and_lp_on_known_trades() {
+    let schedule = FeeSchedule::default();
+    let buy = |slot: u64, sol_in: u64| {
+        let mut amm = PumpAmmState::new().with_fee(schedule.at(slot));
+        amm.simulate_buy(sol_in, 0);
+        (amm.virtual_sol - PumpAmmState::new().virtual_sol, amm.protocol_fees, amm.creator_fees)
+    };
+    // 1% before the creator-fee era, then 95 + 5 bps
+    assert_eq!(buy(339_999_999, 1_000_000_000), (990_000_000, 10_000_000, 0));
+    assert_eq!(buy(380_000_000, 1_000_000_000), (990_000_000, 9_500_000, 500_000));
+    // 123.45 lamports of fee floors to 123; the creator's 6.15 floors to 6 and protocol takes the rest
+    assert_eq!(buy(380_000_000, 12_345), (12_222, 117, 6));
+    // a fee never rounds to nothing, and the creator's share of one lamport does
+    assert_eq!(buy(380_000_000, 50), (49, 1, 0));
+
+    // a sell pays the fee on its gross SOL out
+    let mut amm = PumpAmmState::new().with_fee(schedule.at(380_000_000));
+    let (tokens, _) = amm.simulate_buy(1_000_000_000, 0);
+    let before = amm.clone();
+    let sol_out = amm.simulate_sell(tokens, 0);
+    let gross = before.virtual_sol - amm.virtual_sol;
+    let fee = gross / 100;
+    assert_eq!(sol_out, gross - fee);
+    assert_eq!(amm.creator_fees - before.creator_fees, fee * 5 / 100);
+    assert_eq!(amm.protocol_fees - before.protocol_fees, fee - fee * 5 / 100);
+
+    // PumpSwap: 20 bps LP fee stays in the pool, 5 + 5 bps of protocol and creator fee leave it
+    let pool = PumpSwapPool {
+        base_reserve: 1_000_000_000_000_000,
+        quote_reserve: 100_000_000_000,
+        fees: PoolFees::default(),
+    };
+    let pool_buy = |sol_in: u64| {
+        let mut after = pool.clone();
+        assert!(after.buy(sol_in, 0) > 0);
+        after.quote_reserve - pool.quote_reserve
+    };
+    assert_eq!(pool_buy(1_000_000_000), 999_000_000);
+    // 9.999 lamports of fee floor to 9 and 6.666 of LP fee to 6: 3 lamports leave the pool
+    assert_eq!(pool_buy(3_333), 3_330);
+
+    let mut sold = pool.clone();
+    let sol_out = sold.sell(10_000_000_000_000, 0);
+    let gross = (10_000_000_000_000u128 * pool.quote_reserve as u128 / (pool.base_reserve as u128 + 10_000_000_000_000)) as u64;
+    assert_eq!(sol_out, gross - gross * 30 / 10_000);
+    assert_eq!(pool.quote_reserve - sold.quote_reserve, gross - gross * 20 / 10_000);
+}
+
+#[test]
+fn buying_past_graduation_migrates_into_a_pumpswap_pool() {
+    let template = PumpAmmState::new().with_fee(FeeSchedule::default().at(380_000_000));
+    let mut market = Market::new(Market::near_graduation(500_000_000, &template), PoolFees::default());
+
+    let small = market.buy(100_000_000, 0);
+    assert!(!small.graduated && small.tokens_out > 0);
+    assert_eq!(market.venue(), pumpswap::Venue::BondingCurve);
+
+    // the crossing buy takes every token left, is refunded the rest and switches the market over
+    let Market::BondingCurve { curve: on_curve, .. } = market.clone() else { unreachable!() };
+    let crossing = market.buy(2_000_000_000, 0);
+    assert!(crossing.graduated);
+    assert_eq!(crossing.venue, pumpswap::Venue::BondingCurve);
+    assert_eq!(crossing.tokens_out, on_curve.real_token);
+    assert_eq!(crossing.sol_spent + crossing.sol_refunded, 2_000_000_000);
+    assert!(crossing.sol_refunded > 0);
+
+    let mut completed = on_curve.clone();
+    completed.simulate_buy(crossing.sol_spent, 0);
+    assert_eq!(completed.real_token, 0);
+    let Market::PumpSwap(pool) = market.clone() else {
+        panic!("still on the curve after graduating: {:?}", market);
+    };
+    assert_eq!(pool.base_reserve, MIGRATION_RESERVED_TOKENS);
+    assert_eq!(pool.quote_reserve, completed.real_sol - MIGRATION_FEE_LAMPORTS);
+    assert_eq!(market.price(), pool.quote_reserve as f64 / pool.base_reserve as f64);
+
+    // later buys price on the pool's constant product, after its 30 bps fee
+    let after_fee = 1_000_000_000u128 - 3_000_000;
+    let expected = (after_fee * pool.base_reserve as u128 / (pool.quote_reserve as u128 + after_fee)) as u64;
+    let next = market.buy(1_000_000_000, 0);
+    assert_eq!(next.tokens_out, expected);
+    assert_eq!(next.venue, pumpswap::Venue::PumpSwap);
+    assert!(!next.graduated);
+    assert_eq!((next.sol_spent, next.sol_refunded), (1_000_000_000, 0));
+    assert!(market.sell(next.tokens_out, 0) < 1_000_000_000);
+}
+
+// curves from near-empty to far past graduation, at up to a 5% fee
+fn any_curve() -> impl Strategy<Value = PumpAmmState> {
+    (1u64..=1 << 40, 1u64..=1 << 52, 0u64..=1 << 40, 0u64..=1 << 52, 0u64..=400, 0u64..=100).prop_map(
+        |(virtual_sol, virtual_token, real_sol, real_token, protocol_bps, creator_bps)| {
+            PumpAmmState::from_reserves(virtual_sol, virtual_token, real_sol, real_token).with_fee(FeeTier {
+                from_slot: 0,
+                protocol_bps,
+                creator_bps,
+            })
+        },
+    )
+}
+
+fn k(amm: &PumpAmmState) -> u128 {
+    amm.virtual_sol as u128 * amm.virtual_token as u128
+}
+
+// a leg's SOL delta, curve delta, fee, priority fee, ATA rent and tip, anywhere in their range
+fn any_lamports() -> impl Strategy<Value = (i64, i64, u64, u64, u64, u64)> {
+    (any::<i64>(), any::<i64>(), any::<u64>(), any::<u64>(), any::<u64>(), any::<u64>())
+}
+
+fn set_lamports(leg: &mut ParsedTransaction, lamports: (i64, i64, u64, u64, u64, u64)) {
+    let (sol_change, curve_sol_change, fee, priority_fee, ata_rent, tip) = lamports;
+    leg.sol_change = sol_change;
+    if let Some(curve) = leg.curve.as_mut() {
+        curve.sol_change = curve_sol_change;
+    }
+    leg.fee = fee;
+    leg.priority_fee = priority_fee;
+    leg.ata_rent = ata_rent;
+    leg.tip = tip;
+}
+
+proptest! {
+    #[test]
+    fn amm_trades_never_shrink_k(amm in any_curve(), sol_in in 0u64..=1 << 42, tokens_in in 0u64..=1 << 54) {
+        let mut bought = amm.clone();
+        bought.simulate_buy(sol_in, 0);
+        prop_assert!(k(&bought) >= k(&amm));
+        let mut sold = amm.clone();
+        sold.simulate_sell(tokens_in, 0);
+        prop_assert!(k(&sold) >= k(&amm));
+    }
+
+    #[test]
+    fn amm_buys_only_hand_out_reserve_tokens(amm in any_curve(), sol_in in 0u64..=1 << 42) {
+        let mut after = amm.clone();
+        let (tokens, _) = after.simulate_buy(sol_in, 0);
+        prop_assert_eq!(tokens, amm.quote_buy(sol_in));
+        prop_assert!(tokens < amm.virtual_token);
+        prop_assert_eq!(amm.virtual_token - after.virtual_token, tokens);
+        if tokens > 0 {
+            prop_assert!(after.virtual_sol > amm.virtual_sol);
+        }
+        // the fee alone swallows an input this small
+        if sol_in <= 1 && amm.fee.total_bps() > 0 {
+            prop_assert_eq!(tokens, 0);
+        }
+    }
+
+    #[test]
+    fn amm_round_trip_never_profits_at_zero_fee(amm in any_curve(), sol_in in 0u64..=1 << 42) {
+        let mut amm = amm.with_fee(FeeTier { from_slot: 0, protocol_bps: 0, creator_bps: 0 });
+        let (tokens, _) = amm.simulate_buy(sol_in, 0);
+        prop_assert!(amm.simulate_sell(tokens, 0) <= sol_in);
+    }
+
+    #[test]
+    fn sandwich_profit_saturates_on_extreme_lamports(
+        front in any_lamports(),
+        back in any_lamports(),
+        routed in any::<bool>(),
+    ) {
+        let mut trades: Vec<ParsedTransaction> = ["sandwich_front", "sandwich_victim", "sandwich_back"]
+            .iter()
+            .flat_map(|name| parse_fixture(name))
+            .collect();
+        set_lamports(&mut trades[0], front);
+        set_lamports(&mut trades[2], back);
+        if routed {
+            trades[0].hop_token_changes.insert("So11111111111111111111111111111111111111112".to_string(), i64::MAX);
+            trades[2].hop_token_changes.insert("So11111111111111111111111111111111111111112".to_string(), i64::MAX);
+        }
+        // keep every candidate, however much it lost
+        let cfg = DetectorConfig { min_profit_sol: -1e12, ..DetectorConfig::default() };
+        let summary = detect_wide_attacks(&trades, &cfg);
+        prop_assert_eq!(summary.sandwiches.len(), 1);
+        let sandwich = &summary.sandwiches[0];
+        prop_assert!(sandwich.net_profit_sol <= sandwich.gross_profit_sol);
+        prop_assert!(sandwich.costs.total() >= sandwich.costs.fees.max(sandwich.costs.tips).max(sandwich.costs.ata_rent));
+        detect_parallel(&trades, &cfg);
+    }
+
+    #[test]
+    fn detection_survives_extreme_lamports_on_every_leg(
+        legs in proptest::collection::vec((any_lamports(), any::<i64>(), any::<u64>(), any::<u64>()), 5),
+    ) {
+        let mut trades: Vec<ParsedTransaction> = ["sandwich_front", "sandwich_victim", "sandwich_back", "buy", "sell"]
+            .iter()
+            .flat_map(|name| parse_fixture(name))
+            .collect();
+        for (leg, (lamports, token_change, sol_limit, token_amount)) in trades.iter_mut().zip(legs) {
+            set_lamports(leg, lamports);
+            leg.token_change = token_change;
+            leg.sol_limit_specified = sol_limit;
+            leg.token_amount_requested = token_amount;
+        }
+        for victim_mode in [VictimMode::Limits, VictimMode::PriceImpact] {
+            let cfg = DetectorConfig { victim_mode, min_profit_sol: -1e12, ..DetectorConfig::default() };
+            detect_wide_attacks(&trades, &cfg);
+        }
+    }
+
+    #[test]
+    fn amm_math_survives_extreme_sizes(
+        amm in any_curve(),
+        reserves in (1u64.., 1u64.., any::<u64>(), any::<u64>()),
+        size in any::<u64>(),
+    ) {
+        let (virtual_sol, virtual_token, real_sol, real_token) = reserves;
+        let extreme = PumpAmmState::from_reserves(virtual_sol, virtual_token, real_sol, real_token).with_fee(amm.fee);
+        for amm in [amm, extreme] {
+            let mut trading = amm.clone();
+            let (tokens, _) = trading.simulate_buy(size, 0);
+            prop_assert!(tokens <= amm.virtual_token);
+            trading.simulate_sell(size, 0);
+            prop_assert!(amm.quote_sell(size) <= amm.virtual_sol);
+            prop_assert!(amm.sol_for_tokens(size) > 0);
+            amm.buy_price_impact_pct(size);
+            amm.sell_price_impact_pct(size);
+        }
+    }
+}
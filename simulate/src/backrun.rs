@@ -0,0 +1,128 @@
+use crate::cli::{SimArgs, VictimSide};
+use rusty::amm::{LAMPORTS_PER_SOL, PumpAmmState};
+
+// What a pure backrunner can take from a victim without frontrunning it: trade the other way
+// right after the victim, pushing the price back toward where it was, and value the position at
+// that pre-victim price, as if later flow (or the bot's own close) unwinds it there. After a buy
+// the bot sells tokens from inventory; after a sell it buys.
+
+#[derive(Debug, Clone, Copy)]
+pub struct BackrunOutcome {
+    // tokens sold after a victim buy, lamports spent after a victim sell
+    pub size: u64,
+    // SOL received or tokens bought
+    pub fill: u64,
+    pub price_after: f64,
+    pub gross_profit: i64,
+}
+
+fn evaluate(after_victim: &PumpAmmState, fair_price: f64, victim_side: VictimSide, size: u64) -> BackrunOutcome {
+    let mut amm = after_victim.clone();
+    let (fill, gross_profit) = match victim_side {
+        VictimSide::Buy => {
+            let sol_out = if size == 0 { 0 } else { amm.simulate_sell(size, 0) };
+            (sol_out, sol_out as i64 - (size as f64 * fair_price) as i64)
+        }
+        VictimSide::Sell => {
+            let (tokens, _) = if size == 0 { (0, 0) } else { amm.simulate_buy(size, 0) };
+            (tokens, (tokens as f64 * fair_price) as i64 - size as i64)
+        }
+    };
+    BackrunOutcome {
+        size,
+        fill,
+        price_after: amm.price(),
+        gross_profit,
+    }
+}
+
+// the trade that puts the spot price back at `fair_price`; on a constant-product curve that is
+// the point of the victim's k where virtual SOL / virtual tokens equals it
+fn restoring_size(after_victim: &PumpAmmState, fair_price: f64, victim_side: VictimSide) -> u64 {
+    let k = after_victim.virtual_sol as f64 * after_victim.virtual_token as f64;
+    match victim_side {
+        VictimSide::Buy => ((k / fair_price).sqrt() - after_victim.virtual_token as f64).max(0.0) as u64,
+        VictimSide::Sell => {
+            let sol_after_fee = ((k * fair_price).sqrt() - after_victim.virtual_sol as f64).max(0.0);
+            (sol_after_fee * 10_000.0 / (10_000 - after_victim.fee.total_bps()) as f64) as u64
+        }
+    }
+}
+
+// (restoring backrun, most profitable backrun); profit is concave in size, so ternary search up
+// to the restoring size finds the optimum, which stops short of it by about the fee
+pub fn best_backruns(
+    before_victim: &PumpAmmState,
+    after_victim: &PumpAmmState,
+    victim_side: VictimSide,
+) -> (BackrunOutcome, BackrunOutcome) {
+    let fair_price = before_victim.price();
+    let limit = restoring_size(after_victim, fair_price, victim_side);
+    let profit = |size: u64| evaluate(after_victim, fair_price, victim_side, size).gross_profit;
+
+    let (mut low, mut high) = (0u64, limit);
+    while high - low > 1_000 {
+        let third = (high - low) / 3;
+        let (m1, m2) = (low + third, high - third);
+        if profit(m1) < profit(m2) {
+            low = m1;
+        } else {
+            high = m2;
+        }
+    }
+    let best = [0, low, high, limit]
+        .into_iter()
+        .map(|size| evaluate(after_victim, fair_price, victim_side, size))
+        .max_by_key(|outcome| outcome.gross_profit)
+        .unwrap_or_else(|| evaluate(after_victim, fair_price, victim_side, 0));
+    (evaluate(after_victim, fair_price, victim_side, limit), best)
+}
+
+pub fn simulate_backrun_arb(amm: &PumpAmmState, sim: &SimArgs) {
+    let sol = |lamports: f64| lamports / LAMPORTS_PER_SOL as f64;
+    let victim_sol = (sim.victim_sol * LAMPORTS_PER_SOL as f64) as u64;
+    let mut after_victim = amm.clone();
+    let side = match sim.victim_side {
+        VictimSide::Buy => {
+            after_victim.simulate_buy(victim_sol, 0);
+            "buy"
+        }
+        VictimSide::Sell => {
+            after_victim.simulate_sell((victim_sol as f64 / amm.price()) as u64, 0);
+            "sell"
+        }
+    };
+    println!(
+        "\nVictim {} of {:.3} SOL moves the price {:.12} -> {:.12} SOL/token ({:+.2}%)",
+        side,
+        sim.victim_sol,
+        amm.price(),
+        after_victim.price(),
+        (after_victim.price() / amm.price() - 1.0) * 100.0
+    );
+
+    let (restoring, best) = best_backruns(amm, &after_victim, sim.victim_side);
+    for (label, outcome) in [("Restoring backrun", restoring), ("Optimal backrun", best)] {
+        let trade = match sim.victim_side {
+            VictimSide::Buy => format!(
+                "sell {:.0} tokens for {:.6} SOL",
+                outcome.size as f64 / sim.token_unit(),
+                sol(outcome.fill as f64)
+            ),
+            VictimSide::Sell => format!(
+                "buy {:.0} tokens for {:.6} SOL",
+                outcome.fill as f64 / sim.token_unit(),
+                sol(outcome.size as f64)
+            ),
+        };
+        println!(
+            "{}: {} | price after {:.12} | gross {:.6} SOL | net {:.6} SOL",
+            label,
+            trade,
+            outcome.price_after,
+            sol(outcome.gross_profit as f64),
+            sol(sim.fees.net_profit(outcome.gross_profit, 2) as f64)
+        );
+    }
+    println!("Positions are valued at the pre-victim price; net pays the backrun and its close");
+}
@@ -0,0 +1,69 @@
+use rusty::error::Error;
+use rusty::token;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+use std::str::FromStr;
+
+const PUMP_PROGRAM_ID: Pubkey = Pubkey::from_str_const("6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P");
+const BONDING_CURVE_SEED: &[u8] = b"bonding-curve";
+
+#[derive(Debug, Clone)]
+pub struct BondingCurveAccount {
+    pub address: Pubkey,
+    pub virtual_token_reserves: u64,
+    pub virtual_sol_reserves: u64,
+    pub real_token_reserves: u64,
+    pub real_sol_reserves: u64,
+    pub token_total_supply: u64,
+    pub complete: bool,
+}
+
+pub fn fetch_bonding_curve(rpc_url: &str, mint: &str) -> Result<BondingCurveAccount, Error> {
+    let mint = Pubkey::from_str(mint).map_err(|_| Error::InvalidMint(mint.to_string()))?;
+    let (address, _) = Pubkey::find_program_address(&[BONDING_CURVE_SEED, mint.as_ref()], &PUMP_PROGRAM_ID);
+
+    let client = RpcClient::new(rpc_url.to_string());
+    let account = client
+        .get_account(&address)
+        .map_err(|e| Error::Rpc(format!("Failed to fetch bonding curve {}: {}", address, e)))?;
+    if account.owner != PUMP_PROGRAM_ID {
+        return Err(Error::Rpc(format!("Account {} is not owned by the pump.fun program", address)));
+    }
+
+    decode_bonding_curve(address, &account.data)
+}
+
+pub fn fetch_mint_decimals(rpc_url: &str, mint: &str) -> Result<u8, Error> {
+    let mint = Pubkey::from_str(mint).map_err(|_| Error::InvalidMint(mint.to_string()))?;
+    let account = RpcClient::new(rpc_url.to_string())
+        .get_account(&mint)
+        .map_err(|e| Error::Rpc(format!("Failed to fetch mint {}: {}", mint, e)))?;
+    token::decode_decimals(&account.data).ok_or_else(|| Error::Rpc(format!("Account {} is not a token mint", mint)))
+}
+
+fn decode_bonding_curve(address: Pubkey, data: &[u8]) -> Result<BondingCurveAccount, Error> {
+    // 8-byte anchor discriminator, five u64 reserves/supply fields, then the `complete` flag
+    if data.len() < 8 + 5 * 8 + 1 {
+        return Err(Error::Rpc(format!(
+            "Bonding curve {} has {} bytes, too short to decode",
+            address,
+            data.len()
+        )));
+    }
+    let field = |idx: usize| {
+        let start = 8 + idx * 8;
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(&data[start..start + 8]);
+        u64::from_le_bytes(bytes)
+    };
+
+    Ok(BondingCurveAccount {
+        address,
+        virtual_token_reserves: field(0),
+        virtual_sol_reserves: field(1),
+        real_token_reserves: field(2),
+        real_sol_reserves: field(3),
+        token_total_supply: field(4),
+        complete: data[8 + 5 * 8] != 0,
+    })
+}
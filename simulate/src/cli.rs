@@ -0,0 +1,347 @@
+use rusty::amm::{FeeSchedule, INITIAL_REAL_TOKEN, INITIAL_VIRTUAL_SOL, INITIAL_VIRTUAL_TOKEN, LAMPORTS_PER_SOL, PumpAmmState};
+use crate::fees::FeeModel;
+use rusty::error::Error;
+use rusty::token;
+use std::str::FromStr;
+
+const USAGE: &str = "Usage: cargo run -- [impact [--max-sol SOL] [--steps N] [--csv] | backrun] [--victim-side buy|sell] [--victim-sol SOL] [--victim-slippage-bps BPS] \
+[--frontrun-fraction F] [--backrun-split F] [--mint MINT_ADDRESS] [--rpc-url URL] \
+[--optimize] [--frontier-steps N] [--monte-carlo RUNS] [--seed N] [--victim-sol-sigma S] \
+[--slippage-min-bps BPS] [--slippage-max-bps BPS] [--victim-sizes-file PATH] \
+[--replay DETECTIONS_JSON] [--replay-tolerance-pct PCT] [--slot N] [--fee-schedule SLOT:PROTOCOL_BPS:CREATOR_BPS,...] \
+[--protocol-fee-bps BPS] [--creator-fee-bps BPS] [--sol-to-graduation SOL] [--victims SOL[:BPS],...] \
+[--virtual-sol SOL] [--virtual-token TOKENS] [--fee-bps BPS] [--scenario PATH] \
+[--base-fee LAMPORTS] [--compute-units N] [--priority-fee MICRO_LAMPORTS] [--priority-fee-sigma S] [--tip-pct PCT] \
+[--emit-trades PATH]";
+
+// Lamport amounts stay within i64 so profits can be taken from them: no SOL amount is larger than
+// the whole supply, and no frontrun more than this many times the victim's trade.
+const MAX_SOL: f64 = 1_000_000_000.0;
+const MAX_FRONTRUN_FRACTION: f64 = 5.0;
+
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VictimSide {
+    Buy,
+    Sell,
+}
+
+// one buy in a --victims queue; without its own slippage it uses --victim-slippage-bps
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct QueuedVictim {
+    pub sol: f64,
+    pub slippage_bps: Option<u64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SimArgs {
+    pub victim_side: VictimSide,
+    pub victim_sol: f64,
+    pub victim_slippage_bps: u64,
+    pub frontrun_fraction: f64,
+    pub backrun_split: f64,
+    pub mint: Option<String>,
+    pub rpc_url: Option<String>,
+    pub optimize: bool,
+    pub frontier_steps: usize,
+    pub monte_carlo_runs: usize,
+    pub seed: u64,
+    pub victim_sol_sigma: f64,
+    pub slippage_min_bps: u64,
+    pub slippage_max_bps: u64,
+    pub victim_sizes_file: Option<String>,
+    pub replay_file: Option<String>,
+    pub replay_tolerance_pct: f64,
+    pub slot: u64,
+    pub fee_schedule: FeeSchedule,
+    pub sol_to_graduation: Option<f64>,
+    pub impact_curve: bool,
+    pub impact_max_sol: f64,
+    pub impact_steps: usize,
+    pub impact_csv: bool,
+    // `backrun`: the pure backrun value of one victim trade
+    pub backrun_only: bool,
+    // for printing token amounts; the live mint's own with --mint
+    pub token_decimals: u8,
+    pub victims: Vec<QueuedVictim>,
+    // starting reserves instead of a fresh launch; one alone is paired along pump.fun's curve
+    pub virtual_sol: Option<f64>,
+    pub virtual_token: Option<f64>,
+    // per bot transaction (per transaction of any actor in a scenario)
+    pub fees: FeeModel,
+    pub scenario_file: Option<String>,
+    // write the simulated legs as parsed trades for `rusty --input`
+    pub emit_trades: Option<String>,
+}
+
+impl Default for SimArgs {
+    fn default() -> Self {
+        Self {
+            victim_side: VictimSide::Buy,
+            victim_sol: 1.0,
+            victim_slippage_bps: 500,
+            frontrun_fraction: 0.2,
+            backrun_split: 0.5,
+            mint: None,
+            rpc_url: None,
+            optimize: false,
+            frontier_steps: 10,
+            monte_carlo_runs: 0,
+            seed: 42,
+            victim_sol_sigma: 1.0,
+            slippage_min_bps: 100,
+            slippage_max_bps: 1_000,
+            victim_sizes_file: None,
+            replay_file: None,
+            replay_tolerance_pct: 25.0,
+            slot: 380_000_000,
+            fee_schedule: FeeSchedule::default(),
+            sol_to_graduation: None,
+            impact_curve: false,
+            impact_max_sol: 10.0,
+            impact_steps: 20,
+            impact_csv: false,
+            backrun_only: false,
+            token_decimals: token::DEFAULT_DECIMALS,
+            victims: Vec::new(),
+            virtual_sol: None,
+            virtual_token: None,
+            fees: FeeModel::default(),
+            scenario_file: None,
+            emit_trades: None,
+        }
+    }
+}
+
+impl SimArgs {
+    pub fn token_unit(&self) -> f64 {
+        token::unit(self.token_decimals) as f64
+    }
+
+    // a fresh curve, or one moved to --virtual-sol / --virtual-token; the real reserves are what
+    // those virtual reserves imply on pump.fun's curve
+    pub fn initial_curve(&self) -> PumpAmmState {
+        if self.virtual_sol.is_none() && self.virtual_token.is_none() {
+            return PumpAmmState::new();
+        }
+        let k = INITIAL_VIRTUAL_SOL as f64 * INITIAL_VIRTUAL_TOKEN as f64;
+        let virtual_sol = self.virtual_sol.map(|sol| sol * LAMPORTS_PER_SOL as f64);
+        let virtual_token = self.virtual_token.map(|tokens| tokens * self.token_unit());
+        let (virtual_sol, virtual_token) = match (virtual_sol, virtual_token) {
+            (Some(sol), Some(tokens)) => (sol, tokens),
+            (Some(sol), None) => (sol, k / sol),
+            (None, Some(tokens)) => (k / tokens, tokens),
+            (None, None) => (INITIAL_VIRTUAL_SOL as f64, INITIAL_VIRTUAL_TOKEN as f64),
+        };
+        let (virtual_sol, virtual_token) = (virtual_sol as u64, virtual_token as u64);
+        PumpAmmState::from_reserves(
+            virtual_sol,
+            virtual_token,
+            virtual_sol.saturating_sub(INITIAL_VIRTUAL_SOL),
+            virtual_token.saturating_sub(INITIAL_VIRTUAL_TOKEN - INITIAL_REAL_TOKEN),
+        )
+    }
+}
+
+pub fn parse_args(args: &[String]) -> Result<SimArgs, Error> {
+    let mut sim = SimArgs::default();
+    let mut protocol_fee_bps: Option<u64> = None;
+    let mut creator_fee_bps: Option<u64> = None;
+    let mut fee_bps: Option<u64> = None;
+
+    let mut iter = args.iter().skip(1).peekable();
+    if iter.next_if(|arg| arg.as_str() == "impact").is_some() {
+        sim.impact_curve = true;
+    } else if iter.next_if(|arg| arg.as_str() == "backrun").is_some() {
+        sim.backrun_only = true;
+    }
+    while let Some(arg) = iter.next() {
+        let flag = arg.as_str();
+        let mut value = || {
+            iter.next()
+                .cloned()
+                .ok_or_else(|| Error::Usage(format!("{} requires a value. {}", flag, USAGE)))
+        };
+        match flag {
+            "--victim-side" => sim.victim_side = parse_side(&value()?)?,
+            "--victim-sol" => sim.victim_sol = parse_finite(flag, &value()?)?,
+            "--victim-slippage-bps" => sim.victim_slippage_bps = parse_number(flag, &value()?)?,
+            "--frontrun-fraction" => sim.frontrun_fraction = parse_finite(flag, &value()?)?,
+            "--backrun-split" => sim.backrun_split = parse_finite(flag, &value()?)?,
+            "--mint" => sim.mint = Some(value()?),
+            "--rpc-url" => sim.rpc_url = Some(value()?),
+            "--optimize" => sim.optimize = true,
+            "--frontier-steps" => sim.frontier_steps = parse_number(flag, &value()?)?,
+            "--monte-carlo" => sim.monte_carlo_runs = parse_number(flag, &value()?)?,
+            "--seed" => sim.seed = parse_number(flag, &value()?)?,
+            "--victim-sol-sigma" => sim.victim_sol_sigma = parse_finite(flag, &value()?)?,
+            "--slippage-min-bps" => sim.slippage_min_bps = parse_number(flag, &value()?)?,
+            "--slippage-max-bps" => sim.slippage_max_bps = parse_number(flag, &value()?)?,
+            "--victim-sizes-file" => sim.victim_sizes_file = Some(value()?),
+            "--replay" => sim.replay_file = Some(value()?),
+            "--replay-tolerance-pct" => sim.replay_tolerance_pct = parse_finite(flag, &value()?)?,
+            "--slot" => sim.slot = parse_number(flag, &value()?)?,
+            "--fee-schedule" => sim.fee_schedule = FeeSchedule::parse(&value()?)?,
+            "--protocol-fee-bps" => protocol_fee_bps = Some(parse_number(flag, &value()?)?),
+            "--creator-fee-bps" => creator_fee_bps = Some(parse_number(flag, &value()?)?),
+            "--sol-to-graduation" => sim.sol_to_graduation = Some(parse_finite(flag, &value()?)?),
+            "--victims" => sim.victims = parse_victims(&value()?)?,
+            "--virtual-sol" => sim.virtual_sol = Some(parse_finite(flag, &value()?)?),
+            "--virtual-token" => sim.virtual_token = Some(parse_finite(flag, &value()?)?),
+            "--fee-bps" => fee_bps = Some(parse_number(flag, &value()?)?),
+            "--base-fee" => sim.fees.base_fee = parse_number(flag, &value()?)?,
+            "--compute-units" => sim.fees.compute_units = parse_number(flag, &value()?)?,
+            "--priority-fee" => sim.fees.priority_fee = parse_finite(flag, &value()?)?,
+            "--priority-fee-sigma" => sim.fees.priority_sigma = parse_finite(flag, &value()?)?,
+            "--tip-pct" => sim.fees.tip_pct = parse_finite(flag, &value()?)?,
+            "--scenario" => sim.scenario_file = Some(value()?),
+            "--emit-trades" => sim.emit_trades = Some(value()?),
+            "--max-sol" if sim.impact_curve => sim.impact_max_sol = parse_finite(flag, &value()?)?,
+            "--steps" if sim.impact_curve => sim.impact_steps = parse_number(flag, &value()?)?,
+            "--csv" if sim.impact_curve => sim.impact_csv = true,
+            "--help" | "-h" => return Err(Error::Usage(USAGE.to_string())),
+            other => return Err(Error::Usage(format!("Unknown argument '{}'. {}", other, USAGE))),
+        }
+    }
+
+    if let Some(bps) = fee_bps {
+        if protocol_fee_bps.is_some() || creator_fee_bps.is_some() {
+            return Err(Error::Usage(
+                "--fee-bps sets the whole fee; use it or --protocol-fee-bps/--creator-fee-bps".to_string(),
+            ));
+        }
+        protocol_fee_bps = Some(bps);
+        creator_fee_bps = Some(0);
+    }
+    if protocol_fee_bps.is_some() || creator_fee_bps.is_some() {
+        let tier = sim.fee_schedule.at(sim.slot);
+        sim.fee_schedule = FeeSchedule::flat(
+            protocol_fee_bps.unwrap_or(tier.protocol_bps),
+            creator_fee_bps.unwrap_or(tier.creator_bps),
+        );
+        if sim.fee_schedule.current().total_bps() >= 10_000 {
+            return Err(Error::Usage("Fees must total less than 10000 bps".to_string()));
+        }
+    }
+
+    if sim.victim_sol <= 0.0 || sim.victim_sol > MAX_SOL {
+        return Err(Error::Usage(format!("--victim-sol must be positive and at most {} SOL", MAX_SOL)));
+    }
+    if sim.victim_slippage_bps > 10_000 {
+        return Err(Error::Usage("--victim-slippage-bps must be at most 10000".to_string()));
+    }
+    if !(0.0..=1.0).contains(&sim.backrun_split) {
+        return Err(Error::Usage("--backrun-split must be between 0 and 1".to_string()));
+    }
+    if sim.slippage_min_bps > sim.slippage_max_bps || sim.slippage_max_bps > 10_000 {
+        return Err(Error::Usage("slippage range must satisfy min <= max <= 10000 bps".to_string()));
+    }
+    if (sim.optimize || sim.monte_carlo_runs > 0 || sim.sol_to_graduation.is_some())
+        && sim.victim_side == VictimSide::Sell
+    {
+        return Err(Error::Usage("--optimize, --monte-carlo and --sol-to-graduation only support --victim-side buy".to_string()));
+    }
+    if !sim.victims.is_empty()
+        && (sim.victim_side == VictimSide::Sell || sim.optimize || sim.monte_carlo_runs > 0 || sim.sol_to_graduation.is_some())
+    {
+        return Err(Error::Usage(
+            "--victims queues buys and can't be combined with --victim-side sell, --optimize, --monte-carlo or --sol-to-graduation".to_string(),
+        ));
+    }
+    if sim.backrun_only
+        && (sim.scenario_file.is_some() || !sim.victims.is_empty() || sim.optimize || sim.monte_carlo_runs > 0 || sim.sol_to_graduation.is_some())
+    {
+        return Err(Error::Usage(
+            "backrun prices one victim trade and can't be combined with --scenario, --victims, --optimize, --monte-carlo or --sol-to-graduation".to_string(),
+        ));
+    }
+    if sim.scenario_file.is_some()
+        && (sim.impact_curve || !sim.victims.is_empty() || sim.optimize || sim.monte_carlo_runs > 0 || sim.sol_to_graduation.is_some())
+    {
+        return Err(Error::Usage(
+            "--scenario scripts its own trades and can't be combined with impact, --victims, --optimize, --monte-carlo or --sol-to-graduation".to_string(),
+        ));
+    }
+    if sim.emit_trades.is_some()
+        && (sim.impact_curve
+            || sim.backrun_only
+            || sim.replay_file.is_some()
+            || sim.optimize
+            || sim.monte_carlo_runs > 0
+            || sim.sol_to_graduation.is_some())
+    {
+        return Err(Error::Usage(
+            "--emit-trades writes a sandwich, --victims queue or --scenario run; drop impact, backrun, --replay, --optimize, --monte-carlo and --sol-to-graduation".to_string(),
+        ));
+    }
+    if sim.virtual_sol.is_some_and(|sol| sol <= 0.0 || sol > MAX_SOL) || sim.virtual_token.is_some_and(|tokens| tokens <= 0.0) {
+        return Err(Error::Usage(format!(
+            "--virtual-sol and --virtual-token must be positive, and --virtual-sol at most {} SOL",
+            MAX_SOL
+        )));
+    }
+    if sim.mint.is_some() && (sim.virtual_sol.is_some() || sim.virtual_token.is_some()) {
+        return Err(Error::Usage("--mint loads the live reserves; drop --virtual-sol/--virtual-token".to_string()));
+    }
+    if sim.fees.priority_fee < 0.0 || sim.fees.priority_sigma < 0.0 {
+        return Err(Error::Usage("--priority-fee and --priority-fee-sigma must not be negative".to_string()));
+    }
+    if !(0.0..=100.0).contains(&sim.fees.tip_pct) {
+        return Err(Error::Usage("--tip-pct must be between 0 and 100".to_string()));
+    }
+    if !(0.0..=MAX_FRONTRUN_FRACTION).contains(&sim.frontrun_fraction) {
+        return Err(Error::Usage(format!("--frontrun-fraction must be between 0 and {}", MAX_FRONTRUN_FRACTION)));
+    }
+
+    Ok(sim)
+}
+
+fn parse_number<T: FromStr>(flag: &str, value: &str) -> Result<T, Error>
+where
+    T::Err: std::fmt::Display,
+{
+    value
+        .parse()
+        .map_err(|e| Error::Usage(format!("Invalid value '{}' for {}: {}", value, flag, e)))
+}
+
+// "nan" and "inf" parse as f64, but no amount or ratio here can be either
+fn parse_finite(flag: &str, value: &str) -> Result<f64, Error> {
+    let number: f64 = parse_number(flag, value)?;
+    if !number.is_finite() {
+        return Err(Error::Usage(format!("Invalid value '{}' for {}: must be a finite number", value, flag)));
+    }
+    Ok(number)
+}
+
+// "0.5,2:300,1.2": SOL per buy, optionally with its slippage in bps
+fn parse_victims(value: &str) -> Result<Vec<QueuedVictim>, Error> {
+    value
+        .split(',')
+        .map(|entry| {
+            let (sol, slippage_bps) = match entry.split_once(':') {
+                Some((sol, bps)) => (sol, Some(parse_number("--victims", bps)?)),
+                None => (entry, None),
+            };
+            let sol = parse_finite("--victims", sol)?;
+            if sol <= 0.0 || sol > MAX_SOL {
+                return Err(Error::Usage(format!(
+                    "--victims sizes must be positive and at most {} SOL, got '{}'",
+                    MAX_SOL, entry
+                )));
+            }
+            if slippage_bps.is_some_and(|bps| bps > 10_000) {
+                return Err(Error::Usage(format!("--victims slippage must be at most 10000 bps, got '{}'", entry)));
+            }
+            Ok(QueuedVictim { sol, slippage_bps })
+        })
+        .collect()
+}
+
+fn parse_side(value: &str) -> Result<VictimSide, Error> {
+    match value {
+        "buy" => Ok(VictimSide::Buy),
+        "sell" => Ok(VictimSide::Sell),
+        other => Err(Error::Usage(format!("Unsupported victim side '{}'. Expected buy or sell", other))),
+    }
+}
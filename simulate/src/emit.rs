@@ -0,0 +1,148 @@
+use crate::cli::SimArgs;
+use crate::fees::FeeModel;
+use rusty::amm::{INITIAL_REAL_TOKEN, PumpAmmState};
+use rusty::error::Error;
+use rusty::fetch::SavedTrades;
+use rusty::parser::pumpfun::{CurveDelta, ExecutionOutcome, ParsedTransaction, TradeType, Venue};
+use rusty::rugpull::PUMP_TOKEN_SUPPLY;
+use solana_sdk::hash::hashv;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+// The simulated legs as the parser would have produced them, written in the `--save-trades`
+// format so `rusty --input` runs them through the detector as known positives. Wallets, the
+// mint and signatures are derived from names, so rerunning a simulation writes the same file.
+pub struct TradeLog {
+    mint: String,
+    fees: FeeModel,
+    decimals: u8,
+    trades: Vec<ParsedTransaction>,
+    // by leg, to print the wallet each name became
+    actors: Vec<String>,
+}
+
+// A leg as its instruction was sent. The quote is what the trader's wallet priced against the
+// curve it saw, which for a sandwich victim is the curve before the frontrun; a fill short of it
+// is the limit breach the detector looks for.
+#[derive(Debug, Clone, Copy)]
+pub enum Order {
+    Buy { sol_in: u64, quoted_tokens: u64 },
+    Sell { tokens_in: u64, quoted_sol: u64 },
+}
+
+impl Order {
+    pub fn buy(quoted_on: &PumpAmmState, sol_in: u64) -> Self {
+        Order::Buy {
+            sol_in,
+            quoted_tokens: quoted_on.quote_buy(sol_in),
+        }
+    }
+
+    pub fn sell(quoted_on: &PumpAmmState, tokens_in: u64) -> Self {
+        Order::Sell {
+            tokens_in,
+            quoted_sol: quoted_on.quote_sell(tokens_in),
+        }
+    }
+}
+
+// base58 of SHA-256("simulate" || name), shaped like a real address
+fn address(name: &str) -> String {
+    Pubkey::new_from_array(hashv(&[b"simulate", name.as_bytes()]).to_bytes()).to_string()
+}
+
+impl TradeLog {
+    pub fn new(sim: &SimArgs) -> Self {
+        Self {
+            mint: sim.mint.clone().unwrap_or_else(|| address("mint")),
+            fees: sim.fees,
+            decimals: sim.token_decimals,
+            trades: Vec::new(),
+            actors: Vec::new(),
+        }
+    }
+
+    // `before` and `after` are the curve around this leg, so a fill that left it unchanged is
+    // logged as reverted
+    pub fn record(&mut self, actor: &str, slot: u64, order: Order, before: &PumpAmmState, after: &PumpAmmState) {
+        let (trade_type, tokens_requested, sol_limit) = match order {
+            Order::Buy { sol_in, quoted_tokens } => (TradeType::Buy, quoted_tokens, sol_in),
+            Order::Sell { tokens_in, quoted_sol } => (TradeType::Sell, tokens_in, quoted_sol),
+        };
+        let curve_sol = after.virtual_sol as i64 - before.virtual_sol as i64;
+        let curve_tokens = after.virtual_token as i64 - before.virtual_token as i64;
+        let filled = curve_tokens != 0;
+        let trade_sol = match trade_type {
+            TradeType::Buy if filled => -(sol_limit as i64),
+            // the seller gets the curve's SOL less the fees taken from it
+            TradeType::Sell if filled => {
+                -curve_sol - (after.protocol_fees + after.creator_fees - before.protocol_fees - before.creator_fees) as i64
+            }
+            _ => 0,
+        };
+        let fee = self.fees.tx_cost();
+        let tx_index = self.trades.iter().filter(|trade| trade.slot == slot).count();
+        let signature = hashv(&[b"simulate", &(self.trades.len() as u64).to_le_bytes(), actor.as_bytes()]).to_bytes();
+        let mut signature_bytes = [0u8; 64];
+        signature_bytes[..32].copy_from_slice(&signature);
+        signature_bytes[32..].copy_from_slice(&hashv(&[&signature]).to_bytes());
+
+        self.actors.push(actor.to_string());
+        self.trades.push(ParsedTransaction {
+            signature: Arc::from(Signature::from(signature_bytes).to_string()),
+            slot,
+            block_time: None,
+            tx_index: Some(tx_index),
+            signer: Arc::from(address(actor)),
+            fee_payer: None,
+            mint: Arc::from(self.mint.as_str()),
+            leg_index: 0,
+            venue: Venue::PumpFun,
+            trade_type,
+            token_amount_requested: tokens_requested,
+            sol_limit_specified: sol_limit,
+            sol_change: trade_sol.saturating_sub(fee as i64),
+            token_change: -curve_tokens,
+            fee,
+            priority_fee: fee.saturating_sub(self.fees.base_fee),
+            tip: 0,
+            compute_unit_price: self.fees.priority_fee as u64,
+            ata_rent: 0,
+            outcome: if filled { ExecutionOutcome::Succeeded } else { ExecutionOutcome::Reverted },
+            curve: filled.then(|| CurveDelta {
+                account: address("curve"),
+                sol_change: curve_sol,
+                token_change: curve_tokens,
+                pre_token_balance: Some(before.real_token + (PUMP_TOKEN_SUPPLY - INITIAL_REAL_TOKEN)),
+            }),
+            hop_token_changes: BTreeMap::new(),
+        });
+    }
+
+    pub fn save(self, path: &str) -> Result<(), Error> {
+        let count = self.trades.len();
+        let mint = self.mint.clone();
+        let mut actors: Vec<String> = Vec::new();
+        for actor in &self.actors {
+            if !actors.contains(actor) {
+                actors.push(actor.clone());
+            }
+        }
+        SavedTrades {
+            mint: self.mint,
+            trades: self.trades,
+            lifecycle_events: Vec::new(),
+            token_decimals: Some(self.decimals),
+            token_metadata: None,
+        }
+        .save(path)?;
+        println!("\nWrote {} simulated trades to {}", count, path);
+        println!("mint: {}", mint);
+        for actor in actors {
+            println!("{}: {}", actor, address(&actor));
+        }
+        Ok(())
+    }
+}
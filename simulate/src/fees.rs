@@ -0,0 +1,82 @@
+use rusty::amm::LAMPORTS_PER_SOL;
+
+// What each bot transaction pays on top of its trade: the signature fee plus a priority fee on
+// its compute units, and once per attack an optional Jito tip that takes a share of the profit.
+// The priority fee is log-normal around its median; single runs price the mean, Monte Carlo
+// samples one per scenario.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FeeModel {
+    pub base_fee: u64,
+    pub compute_units: u64,
+    // micro-lamports per compute unit
+    pub priority_fee: f64,
+    pub priority_sigma: f64,
+    // percent of the attack's profit after transaction fees
+    pub tip_pct: f64,
+}
+
+impl Default for FeeModel {
+    fn default() -> Self {
+        Self {
+            base_fee: 5_000,
+            compute_units: 80_000,
+            priority_fee: 0.0,
+            priority_sigma: 0.0,
+            tip_pct: 0.0,
+        }
+    }
+}
+
+impl FeeModel {
+    // capped at i64::MAX so a profit can always pay it
+    fn cost_at(&self, priority_fee: f64) -> u64 {
+        self.base_fee
+            .saturating_add((self.compute_units as f64 * priority_fee / 1e6) as u64)
+            .min(i64::MAX as u64)
+    }
+
+    // at the log-normal's mean priority fee
+    pub fn tx_cost(&self) -> u64 {
+        self.cost_at(self.priority_fee * (self.priority_sigma * self.priority_sigma / 2.0).exp())
+    }
+
+    // one draw from the priority fee distribution, `normal` a standard normal sample
+    pub fn sampled(&self, normal: f64) -> Self {
+        Self {
+            priority_fee: self.priority_fee * (self.priority_sigma * normal).exp(),
+            priority_sigma: 0.0,
+            ..*self
+        }
+    }
+
+    pub fn tip(&self, profit_before_tip: i64) -> u64 {
+        (profit_before_tip.max(0) as f64 * self.tip_pct / 100.0) as u64
+    }
+
+    // what an attack of `txs` bot transactions clears from its trading P&L
+    pub fn net_profit(&self, trading_pnl: i64, txs: u64) -> i64 {
+        let costs = txs.saturating_mul(self.tx_cost()).min(i64::MAX as u64) as i64;
+        let before_tip = trading_pnl.saturating_sub(costs);
+        before_tip - self.tip(before_tip) as i64
+    }
+
+    pub fn describe(&self) -> String {
+        format!(
+            "Bot costs: {:.6} SOL per tx (base {} + {} CU at {:.0} micro-lamports{}){}",
+            self.tx_cost() as f64 / LAMPORTS_PER_SOL as f64,
+            self.base_fee,
+            self.compute_units,
+            self.priority_fee,
+            if self.priority_sigma > 0.0 {
+                format!(" median, sigma {:.2}", self.priority_sigma)
+            } else {
+                String::new()
+            },
+            if self.tip_pct > 0.0 {
+                format!(", Jito tip {:.1}% of profit", self.tip_pct)
+            } else {
+                String::new()
+            }
+        )
+    }
+}
@@ -0,0 +1,101 @@
+use super::pay_tip;
+use crate::cli::SimArgs;
+use rusty::amm::{LAMPORTS_PER_SOL, PumpAmmState};
+use rusty::pumpswap::{BuyFill, Market, PoolFees, Venue};
+
+fn venue_label(venue: Venue) -> &'static str {
+    match venue {
+        Venue::BondingCurve => "bonding curve",
+        Venue::PumpSwap => "PumpSwap",
+    }
+}
+
+fn print_fill(label: &str, slot: u64, fill: &BuyFill, market: &Market, token_unit: f64) {
+    println!(
+        "\nSlot {} {} on {}: Tokens {:.0} for {:.6} SOL{}",
+        slot,
+        label,
+        venue_label(fill.venue),
+        fill.tokens_out as f64 / token_unit,
+        fill.sol_spent as f64 / LAMPORTS_PER_SOL as f64,
+        if fill.sol_refunded > 0 {
+            format!(" ({:.6} SOL refunded)", fill.sol_refunded as f64 / LAMPORTS_PER_SOL as f64)
+        } else {
+            String::new()
+        }
+    );
+    if fill.graduated {
+        println!("Curve completed: liquidity migrated to PumpSwap");
+    }
+    println!("Price after: {:.12} SOL/token ({})", market.price(), venue_label(market.venue()));
+}
+
+pub fn simulate_graduation_sandwich(amm: &PumpAmmState, sim: &SimArgs, sol_to_graduation: f64, base_slot: u64) {
+    let curve = Market::near_graduation((sol_to_graduation * LAMPORTS_PER_SOL as f64) as u64, amm);
+    println!(
+        "Curve {:.3} SOL from graduation: real {:.3} SOL / {:.0} tokens left",
+        sol_to_graduation,
+        curve.real_sol as f64 / LAMPORTS_PER_SOL as f64,
+        curve.real_token as f64 / sim.token_unit()
+    );
+
+    let victim_sol_in = (sim.victim_sol * LAMPORTS_PER_SOL as f64) as u64;
+    let mut baseline = Market::new(curve.clone(), PoolFees::default());
+    let baseline_fill = baseline.buy(victim_sol_in, 0);
+    let victim_min_tokens =
+        (baseline_fill.tokens_out as u128 * (10_000 - sim.victim_slippage_bps) as u128 / 10_000) as u64;
+    println!(
+        "\nBaseline (No Attack): Victim gets {:.0} tokens for {:.6} SOL on {}",
+        baseline_fill.tokens_out as f64 / sim.token_unit(),
+        baseline_fill.sol_spent as f64 / LAMPORTS_PER_SOL as f64,
+        venue_label(baseline_fill.venue)
+    );
+
+    let mut market = Market::new(curve, PoolFees::default());
+    let bot_front_sol = (victim_sol_in as f64 * sim.frontrun_fraction) as u64;
+    let front = market.buy(bot_front_sol, 0);
+    print_fill("Bot Front-run Buy", base_slot, &front, &market, sim.token_unit());
+
+    let victim = market.buy(victim_sol_in, victim_min_tokens);
+    print_fill("Victim Buy", base_slot + 1, &victim, &market, sim.token_unit());
+    if victim.tokens_out == 0 {
+        println!("Victim buy reverted: slippage limit exceeded");
+    }
+
+    let back_venue = market.venue();
+    let back_sol = market.sell(front.tokens_out, 0);
+    println!(
+        "\nSlot {} Bot Back-run Sell on {}: Tokens {:.0} for {:.6} SOL",
+        base_slot + 2,
+        venue_label(back_venue),
+        front.tokens_out as f64 / sim.token_unit(),
+        back_sol as f64 / LAMPORTS_PER_SOL as f64
+    );
+    println!("Price after: {:.12} SOL/token ({})", market.price(), venue_label(market.venue()));
+
+    let victim_effective = |fill: &BuyFill| {
+        if fill.tokens_out == 0 {
+            0.0
+        } else {
+            fill.sol_spent as f64 / fill.tokens_out as f64
+        }
+    };
+    println!(
+        "\nVictim effective price: {:.12} SOL/token (baseline {:.12})",
+        victim_effective(&victim),
+        victim_effective(&baseline_fill)
+    );
+    println!(
+        "Graduation crossed by: {}",
+        match (front.graduated, victim.graduated) {
+            (true, _) => "bot front-run (victim filled on PumpSwap)",
+            (_, true) => "victim buy",
+            _ => "nobody (curve still active)",
+        }
+    );
+    let bot_net = pay_tip(
+        sim,
+        (back_sol as i64 - front.sol_spent as i64).saturating_sub((sim.fees.tx_cost() as i64).saturating_mul(2)),
+    );
+    println!("Bot Total Net Profit: {:.6} SOL", bot_net as f64 / LAMPORTS_PER_SOL as f64);
+}
@@ -0,0 +1,81 @@
+use crate::cli::SimArgs;
+use rusty::amm::{LAMPORTS_PER_SOL, PumpAmmState};
+
+const IMPACT_TARGETS_PCT: [f64; 6] = [1.0, 5.0, 10.0, 25.0, 50.0, 100.0];
+
+// None when the move needs more SOL than it takes to buy out the rest of the curve
+fn sol_to_move_price(amm: &PumpAmmState, target_pct: f64) -> Option<u64> {
+    let curve_limit = amm.sol_for_tokens(amm.real_token);
+    if amm.buy_price_impact_pct(curve_limit) < target_pct {
+        return None;
+    }
+    let mut low: u64 = 0;
+    let mut high = curve_limit;
+    while high - low > 1_000 {
+        let mid = low + (high - low) / 2;
+        if amm.buy_price_impact_pct(mid) < target_pct {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+    Some(high)
+}
+
+pub fn print_impact_curve(amm: &PumpAmmState, sim: &SimArgs) {
+    let spot = amm.price();
+    let max_sol = (sim.impact_max_sol * LAMPORTS_PER_SOL as f64) as u64;
+    let steps = sim.impact_steps.max(1);
+
+    if sim.impact_csv {
+        println!("sol_in,tokens_out,effective_price,price_after,price_impact_pct,slippage_pct");
+    } else {
+        println!("Spot price: {:.12} SOL/token", spot);
+        println!(
+            "\n{:>10} {:>16} {:>18} {:>18} {:>12} {:>12}",
+            "SOL in", "Tokens out", "Effective price", "Price after", "Impact %", "Slippage %"
+        );
+    }
+
+    for step in 1..=steps {
+        let sol_in = (max_sol as u128 * step as u128 / steps as u128) as u64;
+        let mut after = amm.clone();
+        let (tokens_out, _) = after.simulate_buy(sol_in, 0);
+        let effective = if tokens_out == 0 { 0.0 } else { sol_in as f64 / tokens_out as f64 };
+        let impact = amm.buy_price_impact_pct(sol_in);
+        let slippage = if spot == 0.0 { 0.0 } else { (effective - spot) * 100.0 / spot };
+
+        if sim.impact_csv {
+            println!(
+                "{:.6},{},{:.12},{:.12},{:.4},{:.4}",
+                sol_in as f64 / LAMPORTS_PER_SOL as f64,
+                tokens_out,
+                effective,
+                after.price(),
+                impact,
+                slippage
+            );
+        } else {
+            println!(
+                "{:>10.3} {:>16.0} {:>18.12} {:>18.12} {:>12.3} {:>12.3}",
+                sol_in as f64 / LAMPORTS_PER_SOL as f64,
+                tokens_out as f64 / sim.token_unit(),
+                effective,
+                after.price(),
+                impact,
+                slippage
+            );
+        }
+    }
+
+    if sim.impact_csv {
+        return;
+    }
+    println!("\nSOL needed to move the price:");
+    for target in IMPACT_TARGETS_PCT {
+        match sol_to_move_price(amm, target) {
+            Some(sol) => println!("  +{:>5.1}%: {:.4} SOL", target, sol as f64 / LAMPORTS_PER_SOL as f64),
+            None => println!("  +{:>5.1}%: out of range", target),
+        }
+    }
+}
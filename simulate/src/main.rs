@@ -1,146 +1,331 @@
+use dotenvy::dotenv;
+use rusty::amm::{LAMPORTS_PER_SOL, PumpAmmState};
+use emit::{Order, TradeLog};
+use rusty::error::Error;
+use rusty::token;
 use std::cmp::max;
-use std::io::{self, BufRead};
-
-const LAMPORTS_PER_SOL: u64 = 1_000_000_000;
-const TOKEN_DECIMALS: u64 = 1_000_000;
-const INITIAL_VIRTUAL_SOL: u64 = 30 * LAMPORTS_PER_SOL;
-const INITIAL_VIRTUAL_TOKEN: u64 = 1_073_000_000 * TOKEN_DECIMALS;
-const INITIAL_REAL_SOL: u64 = 0;
-const INITIAL_REAL_TOKEN: u64 = 793_100_000 * TOKEN_DECIMALS;
-const FEE_BPS: u64 = 30;
-const GAS_EST_PER_TX: u64 = 5_000;
-
-#[derive(Debug, Clone)]
-struct PumpAmmState {
-    virtual_sol: u64,
-    virtual_token: u64,
-    real_sol: u64,
-    real_token: u64,
+use std::env;
+use std::process::ExitCode;
+
+mod chain;
+mod backrun;
+mod cli;
+mod emit;
+mod fees;
+mod graduation;
+mod impact;
+mod montecarlo;
+mod queue;
+mod replay;
+mod scenario;
+mod solver;
+mod yaml;
+
+fn main() -> ExitCode {
+    dotenv().ok();
+
+    match run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            ExitCode::from(e.exit_code())
+        }
+    }
 }
 
-impl PumpAmmState {
-    fn new() -> Self {
-        Self {
-            virtual_sol: INITIAL_VIRTUAL_SOL,
-            virtual_token: INITIAL_VIRTUAL_TOKEN,
-            real_sol: INITIAL_REAL_SOL,
-            real_token: INITIAL_REAL_TOKEN,
+fn run() -> Result<(), Error> {
+    let args: Vec<String> = env::args().collect();
+    let mut sim = cli::parse_args(&args)?;
+
+    if let Some(path) = &sim.replay_file {
+        replay::run_replay(path, sim.replay_tolerance_pct, &sim.fee_schedule)?;
+        return Ok(());
+    }
+
+    let scenario = sim.scenario_file.as_deref().map(scenario::Scenario::load).transpose()?;
+    if let Some(scenario) = &scenario
+        && (scenario.virtual_sol.is_some() || scenario.virtual_token.is_some())
+    {
+        if sim.mint.is_some() {
+            return Err(Error::Usage("The scenario sets its own [curve]; drop --mint".to_string()));
         }
+        sim.virtual_sol = scenario.virtual_sol;
+        sim.virtual_token = scenario.virtual_token;
     }
 
-    fn get_price(&self) -> f64 {
-        if self.virtual_token == 0 {
-            0.0
-        } else {
-            (self.virtual_sol as f64) / (self.virtual_token as f64)
+    let fee = sim.fee_schedule.at(sim.slot);
+    let amm = match &sim.mint {
+        Some(mint) => {
+            let (amm, decimals) = load_live_state(mint, sim.rpc_url.as_deref())?;
+            sim.token_decimals = decimals;
+            amm
         }
+        None => sim.initial_curve(),
     }
+    .with_fee(fee);
+    let base_slot = sim.slot;
 
-    fn simulate_buy(&mut self, sol_in: u64, min_tokens_out: u64) -> (u64, u64) {
-        let fee = (sol_in * FEE_BPS / 10_000).max(1);
-        let sol_in_after_fee = sol_in.saturating_sub(fee);
+    if sim.impact_curve {
+        impact::print_impact_curve(&amm, &sim);
+        return Ok(());
+    }
 
-        let tokens_out = if self.virtual_sol == 0 {
-            0
-        } else {
-            (sol_in_after_fee as u128 * self.virtual_token as u128 / (self.virtual_sol as u128 + sol_in_after_fee as u128)) as u64
-        };
+    println!(
+        "Fee tier at slot {}: protocol {} bps + creator {} bps",
+        sim.slot, fee.protocol_bps, fee.creator_bps
+    );
+    println!("{}", sim.fees.describe());
+    if sim.virtual_sol.is_some() || sim.virtual_token.is_some() {
+        println!(
+            "Starting curve: virtual {:.3} SOL / {:.0} tokens, real {:.3} SOL / {:.0} tokens",
+            amm.virtual_sol as f64 / LAMPORTS_PER_SOL as f64,
+            amm.virtual_token as f64 / sim.token_unit(),
+            amm.real_sol as f64 / LAMPORTS_PER_SOL as f64,
+            amm.real_token as f64 / sim.token_unit()
+        );
+    }
+
+    if sim.backrun_only {
+        backrun::simulate_backrun_arb(&amm, &sim);
+        return Ok(());
+    }
 
-        let tokens_out = if tokens_out < min_tokens_out {
-            0
-        } else {
-            tokens_out
+    if let Some(scenario) = &scenario {
+        let mut log = TradeLog::new(&sim);
+        scenario::run_scenario(&amm, &sim, scenario, &mut log);
+        return save_trades(&sim, log);
+    }
+
+    if sim.monte_carlo_runs > 0 {
+        let empirical = match &sim.victim_sizes_file {
+            Some(path) => Some(montecarlo::load_victim_sizes(path)?),
+            None => None,
         };
+        montecarlo::run_monte_carlo(&amm, &sim, empirical.as_deref());
+        return Ok(());
+    }
 
-        if tokens_out > 0 {
-            self.virtual_sol += sol_in_after_fee;
-            self.virtual_token = self.virtual_token.saturating_sub(tokens_out);
-            self.real_sol += sol_in;
-            self.real_token = self.real_token.saturating_sub(tokens_out);
-        }
+    if let Some(sol_to_graduation) = sim.sol_to_graduation {
+        graduation::simulate_graduation_sandwich(&amm, &sim, sol_to_graduation, base_slot);
+        return Ok(());
+    }
 
-        (tokens_out, sol_in)
+    if !sim.victims.is_empty() {
+        let mut log = TradeLog::new(&sim);
+        queue::simulate_victim_queue(&amm, &sim, &sim.victims, base_slot, &mut log);
+        return save_trades(&sim, log);
     }
 
-    fn simulate_sell(&mut self, tokens_in: u64, min_sol_out: u64) -> u64 {
-        let fee = (tokens_in * FEE_BPS / 10_000).max(1);
-        let tokens_in_after_fee = tokens_in.saturating_sub(fee);
+    if sim.optimize {
+        optimize_frontrun(&amm, &sim);
+        return Ok(());
+    }
 
-        let sol_out = if self.virtual_token == 0 {
-            0
-        } else {
-            (tokens_in_after_fee as u128 * self.virtual_sol as u128 / (self.virtual_token as u128 + tokens_in_after_fee as u128)) as u64
-        };
+    let mut log = TradeLog::new(&sim);
+    match sim.victim_side {
+        cli::VictimSide::Buy => simulate_buy_sandwich(amm, &sim, base_slot, &mut log),
+        cli::VictimSide::Sell => simulate_sell_sandwich(amm, &sim, base_slot, &mut log),
+    }
+    save_trades(&sim, log)
+}
 
-        let sol_out = if sol_out < min_sol_out {
-            0
-        } else {
-            sol_out
-        };
+fn optimize_frontrun(amm: &PumpAmmState, sim: &cli::SimArgs) {
+    let victim_sol_in = (sim.victim_sol * LAMPORTS_PER_SOL as f64) as u64;
+    let (baseline_tokens, _) = amm.clone().simulate_buy(victim_sol_in, 0);
+    let victim_min_tokens =
+        (baseline_tokens as u128 * (10_000 - sim.victim_slippage_bps) as u128 / 10_000) as u64;
 
-        if sol_out > 0 {
-            self.virtual_sol = self.virtual_sol.saturating_sub(sol_out);
-            self.virtual_token += tokens_in_after_fee;
-            self.real_sol = self.real_sol.saturating_sub(sol_out);
-            self.real_token += tokens_in;
-        }
+    println!(
+        "Victim Buy: {:.3} SOL, min tokens {:.0} ({} bps slippage)",
+        sim.victim_sol,
+        victim_min_tokens as f64 / sim.token_unit(),
+        sim.victim_slippage_bps
+    );
+    solver::print_profit_frontier(
+        amm,
+        victim_sol_in,
+        victim_min_tokens,
+        sim.frontier_steps,
+        sim.token_unit(),
+        &sim.fees,
+    );
+}
 
-        sol_out
+fn save_trades(sim: &cli::SimArgs, log: TradeLog) -> Result<(), Error> {
+    match &sim.emit_trades {
+        Some(path) => log.save(path),
+        None => Ok(()),
     }
 }
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    println!("Enter hypothetical victim SOL input (e.g., 1 for 1 SOL): ");
-    let stdin = io::stdin();
-    let victim_sol_in_f: f64 = stdin
-        .lock()
-        .lines()
-        .next()
-        .ok_or("Error: No input provided")?
-        .map_err(|e| format!("Error: Failed to read input: {}", e))?
-        .trim()
-        .parse()
-        .map_err(|e| format!("Error: Invalid number format. Please enter a valid number: {}", e))?;
+fn simulate_buy_sandwich(mut amm: PumpAmmState, sim: &cli::SimArgs, base_slot: u64, log: &mut TradeLog) {
+    let victim_sol_in_f = sim.victim_sol;
     let victim_sol_in = (victim_sol_in_f * LAMPORTS_PER_SOL as f64) as u64;
-    let victim_min_tokens = (victim_sol_in / 2) * TOKEN_DECIMALS / LAMPORTS_PER_SOL;
 
-    let mut amm = PumpAmmState::new();
-    let base_slot: u64 = 380_000_000;
-
-    println!("\nHypothetical Victim TX: Buy with {:.3} SOL, min tokens {}", victim_sol_in_f, victim_min_tokens / TOKEN_DECIMALS);
     let mut no_attack_amm = amm.clone();
-    let (victim_tokens_no_attack, victim_sol_no_attack) = no_attack_amm.simulate_buy(victim_sol_in, victim_min_tokens);
-    println!("\nBaseline (No Attack): Tokens {} ({:.0} with dec) for {:.3} SOL", victim_tokens_no_attack, victim_tokens_no_attack as f64 / TOKEN_DECIMALS as f64, victim_sol_no_attack as f64 / LAMPORTS_PER_SOL as f64);
+    let (victim_tokens_no_attack, victim_sol_no_attack) = no_attack_amm.simulate_buy(victim_sol_in, 0);
+    let victim_min_tokens =
+        (victim_tokens_no_attack as u128 * (10_000 - sim.victim_slippage_bps) as u128 / 10_000) as u64;
+
+    println!("\nHypothetical Victim TX: Buy with {:.3} SOL, min tokens {:.0} ({} bps slippage)", victim_sol_in_f, victim_min_tokens as f64 / sim.token_unit(), sim.victim_slippage_bps);
+    println!("\nBaseline (No Attack): Tokens {} ({:.0} with dec) for {:.3} SOL", victim_tokens_no_attack, victim_tokens_no_attack as f64 / sim.token_unit(), victim_sol_no_attack as f64 / LAMPORTS_PER_SOL as f64);
 
-    let bot_front_sol = victim_sol_in / 5;
+    let bot_front_sol = (victim_sol_in as f64 * sim.frontrun_fraction) as u64;
     let bot_min_tokens_front = 0;
+    let before = amm.clone();
     let (bot_tokens_bought, bot_sol_paid_front) = amm.simulate_buy(bot_front_sol, bot_min_tokens_front);
-    println!("\nSlot n ({}): Bot Front-run Buy: Tokens {} for {:.3} SOL", base_slot, bot_tokens_bought as f64 / TOKEN_DECIMALS as f64, bot_front_sol as f64 / LAMPORTS_PER_SOL as f64);
-    println!("Price after front-run: {:.12} SOL/token", amm.get_price());
+    log.record("bot", base_slot, Order::buy(&before, bot_front_sol), &before, &amm);
+    println!("\nSlot n ({}): Bot Front-run Buy: Tokens {} for {:.3} SOL", base_slot, bot_tokens_bought as f64 / sim.token_unit(), bot_front_sol as f64 / LAMPORTS_PER_SOL as f64);
+    println!("Price after front-run: {:.12} SOL/token", amm.price());
 
+    let before = amm.clone();
     let (victim_tokens, victim_sol_paid) = amm.simulate_buy(victim_sol_in, victim_min_tokens);
-    println!("\nSlot n+1 ({}): Victim Buy: Tokens {} for {:.3} SOL", base_slot + 1, victim_tokens as f64 / TOKEN_DECIMALS as f64, victim_sol_paid as f64 / LAMPORTS_PER_SOL as f64);
-    println!("Price after victim: {:.12} SOL/token", amm.get_price());
+    let order = Order::Buy {
+        sol_in: victim_sol_in,
+        quoted_tokens: victim_tokens_no_attack,
+    };
+    log.record("victim", base_slot + 1, order, &before, &amm);
+    println!("\nSlot n+1 ({}): Victim Buy: Tokens {} for {:.3} SOL", base_slot + 1, victim_tokens as f64 / sim.token_unit(), victim_sol_paid as f64 / LAMPORTS_PER_SOL as f64);
+    println!("Price after victim: {:.12} SOL/token", amm.price());
 
     let extracted_value = max(0, victim_sol_paid as i64 - victim_sol_no_attack as i64) as u64;
     println!("Extracted Value: {:.6} SOL", extracted_value as f64 / LAMPORTS_PER_SOL as f64);
 
-    let break_even_needed = bot_sol_paid_front + GAS_EST_PER_TX * 2;
-    let tokens_to_sell_be = bot_tokens_bought / 2;
-    let min_sol_be = break_even_needed / 2;
+    let tx_cost = sim.fees.tx_cost();
+    let break_even_needed = bot_sol_paid_front.saturating_add(tx_cost.saturating_mul(2));
+    let tokens_to_sell_be = (bot_tokens_bought as f64 * sim.backrun_split) as u64;
+    let min_sol_be = (break_even_needed as f64 * sim.backrun_split) as u64;
+    let before = amm.clone();
     let bot_back1_sol = amm.simulate_sell(tokens_to_sell_be, min_sol_be);
-    let net_be = (bot_back1_sol as i64 - (bot_sol_paid_front as i64 / 2 + GAS_EST_PER_TX as i64)) as f64 / LAMPORTS_PER_SOL as f64;
-    println!("\nSlot n+2 ({}): Back-run 1 (Break Even): Sell {} tokens, Received {:.6} SOL (Net: {:.6})", base_slot + 2, tokens_to_sell_be as f64 / TOKEN_DECIMALS as f64, bot_back1_sol as f64 / LAMPORTS_PER_SOL as f64, net_be);
-    println!("Price after back-run 1: {:.12} SOL/token", amm.get_price());
+    log.record("bot", base_slot + 2, Order::sell(&before, tokens_to_sell_be), &before, &amm);
+    let front_cost_be = (bot_sol_paid_front as f64 * sim.backrun_split) as i64;
+    // the break-even leg also carries the front-run's transaction fee
+    let net_be = (bot_back1_sol as i64).saturating_sub(front_cost_be.saturating_add((tx_cost as i64).saturating_mul(2))) as f64
+        / LAMPORTS_PER_SOL as f64;
+    println!("\nSlot n+2 ({}): Back-run 1 (Break Even): Sell {} tokens, Received {:.6} SOL (Net: {:.6})", base_slot + 2, tokens_to_sell_be as f64 / sim.token_unit(), bot_back1_sol as f64 / LAMPORTS_PER_SOL as f64, net_be);
+    println!("Price after back-run 1: {:.12} SOL/token", amm.price());
     let remaining_tokens = bot_tokens_bought - tokens_to_sell_be;
     let min_sol_profit = 0;
+    let before = amm.clone();
     let bot_back2_sol = amm.simulate_sell(remaining_tokens, min_sol_profit);
-    let net_profit = (bot_back2_sol as i64 - (bot_sol_paid_front as i64 / 2 + GAS_EST_PER_TX as i64)) as f64 / LAMPORTS_PER_SOL as f64;
-    println!("\nSlot n+3 ({}): Back-run 2 (Profit): Sell {} tokens, Received {:.6} SOL (Net: {:.6})", base_slot + 3, remaining_tokens as f64 / TOKEN_DECIMALS as f64, bot_back2_sol as f64 / LAMPORTS_PER_SOL as f64, net_profit);
-    println!("Price after back-run 2: {:.12} SOL/token", amm.get_price());
+    log.record("bot", base_slot + 3, Order::sell(&before, remaining_tokens), &before, &amm);
+    let back2_cost = (bot_sol_paid_front as i64 - front_cost_be).saturating_add(tx_cost as i64);
+    let net_profit = (bot_back2_sol as i64).saturating_sub(back2_cost) as f64 / LAMPORTS_PER_SOL as f64;
+    println!("\nSlot n+3 ({}): Back-run 2 (Profit): Sell {} tokens, Received {:.6} SOL (Net: {:.6})", base_slot + 3, remaining_tokens as f64 / sim.token_unit(), bot_back2_sol as f64 / LAMPORTS_PER_SOL as f64, net_profit);
+    println!("Price after back-run 2: {:.12} SOL/token", amm.price());
+
+    let total_net = pay_tip(sim, ((net_be + net_profit) * LAMPORTS_PER_SOL as f64) as i64);
+    println!("\nBot Total Net Profit: {:.6} SOL", total_net as f64 / LAMPORTS_PER_SOL as f64);
+    print_fees_collected(&amm);
+}
+
+fn simulate_sell_sandwich(mut amm: PumpAmmState, sim: &cli::SimArgs, base_slot: u64, log: &mut TradeLog) {
+    let start_price = amm.price();
+    let victim_sol_value = (sim.victim_sol * LAMPORTS_PER_SOL as f64) as u64;
+    let victim_tokens_in = (victim_sol_value as f64 / start_price) as u64;
+
+    let mut no_attack_amm = amm.clone();
+    let victim_sol_no_attack = no_attack_amm.simulate_sell(victim_tokens_in, 0);
+    let victim_min_sol =
+        (victim_sol_no_attack as u128 * (10_000 - sim.victim_slippage_bps) as u128 / 10_000) as u64;
+
+    println!("\nHypothetical Victim TX: Sell {:.0} tokens, min SOL out {:.6} ({} bps slippage)", victim_tokens_in as f64 / sim.token_unit(), victim_min_sol as f64 / LAMPORTS_PER_SOL as f64, sim.victim_slippage_bps);
+    println!("\nBaseline (No Attack): Received {:.6} SOL for {:.0} tokens", victim_sol_no_attack as f64 / LAMPORTS_PER_SOL as f64, victim_tokens_in as f64 / sim.token_unit());
+
+    let bot_front_tokens = (victim_tokens_in as f64 * sim.frontrun_fraction) as u64;
+    let before = amm.clone();
+    let bot_sol_received_front = amm.simulate_sell(bot_front_tokens, 0);
+    log.record("bot", base_slot, Order::sell(&before, bot_front_tokens), &before, &amm);
+    println!("\nSlot n ({}): Bot Front-run Sell: Tokens {:.0} for {:.6} SOL", base_slot, bot_front_tokens as f64 / sim.token_unit(), bot_sol_received_front as f64 / LAMPORTS_PER_SOL as f64);
+    println!("Price after front-run: {:.12} SOL/token", amm.price());
+
+    let before = amm.clone();
+    let victim_sol_received = amm.simulate_sell(victim_tokens_in, victim_min_sol);
+    let order = Order::Sell {
+        tokens_in: victim_tokens_in,
+        quoted_sol: victim_sol_no_attack,
+    };
+    log.record("victim", base_slot + 1, order, &before, &amm);
+    println!("\nSlot n+1 ({}): Victim Sell: Tokens {:.0} for {:.6} SOL", base_slot + 1, victim_tokens_in as f64 / sim.token_unit(), victim_sol_received as f64 / LAMPORTS_PER_SOL as f64);
+    println!("Price after victim: {:.12} SOL/token", amm.price());
+    if victim_sol_received == 0 {
+        println!("Victim sell reverted: slippage limit exceeded, attacker is left unwinding its front-run");
+    }
+
+    let extracted_value = if victim_sol_received == 0 {
+        0
+    } else {
+        victim_sol_no_attack.saturating_sub(victim_sol_received)
+    };
+    println!("Extracted Value: {:.6} SOL", extracted_value as f64 / LAMPORTS_PER_SOL as f64);
 
-    let total_net = net_be + net_profit;
-    println!("\nBot Total Net Profit: {:.6} SOL", total_net);
+    let bot_buyback_sol = amm.sol_for_tokens(bot_front_tokens);
+    let before = amm.clone();
+    let (bot_tokens_back, bot_sol_paid_back) = amm.simulate_buy(bot_buyback_sol, 0);
+    log.record("bot", base_slot + 2, Order::buy(&before, bot_buyback_sol), &before, &amm);
+    println!("\nSlot n+2 ({}): Back-run Buy: Tokens {:.0} for {:.6} SOL", base_slot + 2, bot_tokens_back as f64 / sim.token_unit(), bot_sol_paid_back as f64 / LAMPORTS_PER_SOL as f64);
+    println!("Price after back-run: {:.12} SOL/token", amm.price());
 
-    Ok(())
-}
\ No newline at end of file
+    println!("\nBot Inventory Change: {:+.0} tokens", (bot_tokens_back as i64 - bot_front_tokens as i64) as f64 / sim.token_unit());
+    let total_net = pay_tip(
+        sim,
+        (bot_sol_received_front as i64 - bot_sol_paid_back as i64).saturating_sub((sim.fees.tx_cost() as i64).saturating_mul(2)),
+    );
+    println!("Bot Total Net Profit: {:.6} SOL", total_net as f64 / LAMPORTS_PER_SOL as f64);
+    print_fees_collected(&amm);
+}
+
+// takes the tip out of a profit that already paid its transaction fees
+fn pay_tip(sim: &cli::SimArgs, net_before_tip: i64) -> i64 {
+    let tip = sim.fees.tip(net_before_tip);
+    if tip > 0 {
+        println!("Jito tip: {:.6} SOL", tip as f64 / LAMPORTS_PER_SOL as f64);
+    }
+    net_before_tip - tip as i64
+}
+
+fn print_fees_collected(amm: &PumpAmmState) {
+    println!(
+        "Fees collected: protocol {:.6} SOL | creator {:.6} SOL",
+        amm.protocol_fees as f64 / LAMPORTS_PER_SOL as f64,
+        amm.creator_fees as f64 / LAMPORTS_PER_SOL as f64
+    );
+}
+
+// the curve's reserves and the mint's decimals
+fn load_live_state(mint: &str, rpc_url: Option<&str>) -> Result<(PumpAmmState, u8), Error> {
+    let rpc_url = match rpc_url {
+        Some(url) => url.to_string(),
+        None => {
+            let api_key = env::var("HELIUS_API_KEY").map_err(|_| Error::MissingEnv("HELIUS_API_KEY"))?;
+            format!("https://mainnet.helius-rpc.com/?api-key={}", api_key)
+        }
+    };
+
+    let curve = chain::fetch_bonding_curve(&rpc_url, mint)?;
+    let decimals = chain::fetch_mint_decimals(&rpc_url, mint)?;
+    let unit = token::unit(decimals) as f64;
+    if curve.complete {
+        return Err(Error::Usage(format!(
+            "Bonding curve {} for {} has completed; the token trades outside the curve now",
+            curve.address, mint
+        )));
+    }
+
+    println!(
+        "Loaded bonding curve {}: virtual {:.3} SOL / {:.0} tokens, real {:.3} SOL / {:.0} tokens (supply {:.0})",
+        curve.address,
+        curve.virtual_sol_reserves as f64 / LAMPORTS_PER_SOL as f64,
+        curve.virtual_token_reserves as f64 / unit,
+        curve.real_sol_reserves as f64 / LAMPORTS_PER_SOL as f64,
+        curve.real_token_reserves as f64 / unit,
+        curve.token_total_supply as f64 / unit
+    );
+    let amm = PumpAmmState::from_reserves(
+        curve.virtual_sol_reserves,
+        curve.virtual_token_reserves,
+        curve.real_sol_reserves,
+        curve.real_token_reserves,
+    );
+    Ok((amm, decimals))
+}
@@ -0,0 +1,115 @@
+use crate::cli::SimArgs;
+use crate::solver::solve_optimal_frontrun;
+use rusty::amm::{LAMPORTS_PER_SOL, PumpAmmState};
+use rusty::error::Error;
+use std::fs;
+
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    fn next_normal(&mut self) -> f64 {
+        let u1 = self.next_f64().max(f64::MIN_POSITIVE);
+        let u2 = self.next_f64();
+        (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+    }
+}
+
+pub fn load_victim_sizes(path: &str) -> Result<Vec<f64>, Error> {
+    let raw = fs::read_to_string(path).map_err(|e| Error::io("read victim sizes file", path, e))?;
+    let sizes: Vec<f64> = raw
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|value| !value.is_empty())
+        .map(|value| {
+            value
+                .parse::<f64>()
+                .map(f64::abs)
+                .map_err(|e| Error::Config(format!("Invalid victim size '{}' in '{}': {}", value, path, e)))
+        })
+        .collect::<Result<_, _>>()?;
+    if sizes.is_empty() {
+        return Err(Error::Config(format!("Victim sizes file '{}' is empty", path)));
+    }
+    Ok(sizes)
+}
+
+pub fn run_monte_carlo(amm: &PumpAmmState, sim: &SimArgs, empirical: Option<&[f64]>) {
+    let mut rng = SplitMix64(sim.seed);
+    let mut profits: Vec<i64> = Vec::with_capacity(sim.monte_carlo_runs);
+    let mut losses: Vec<i64> = Vec::with_capacity(sim.monte_carlo_runs);
+
+    for _ in 0..sim.monte_carlo_runs {
+        let victim_sol = match empirical {
+            Some(sizes) => sizes[(rng.next_u64() % sizes.len() as u64) as usize],
+            None => sim.victim_sol * (sim.victim_sol_sigma * rng.next_normal()).exp(),
+        };
+        let slippage_bps = sim.slippage_min_bps
+            + (rng.next_f64() * (sim.slippage_max_bps - sim.slippage_min_bps) as f64) as u64;
+
+        let victim_sol_in = (victim_sol * LAMPORTS_PER_SOL as f64) as u64;
+        let (baseline_tokens, _) = amm.clone().simulate_buy(victim_sol_in, 0);
+        if baseline_tokens == 0 {
+            continue;
+        }
+        let victim_min_tokens = (baseline_tokens as u128 * (10_000 - slippage_bps) as u128 / 10_000) as u64;
+
+        let fees = sim.fees.sampled(rng.next_normal());
+        let best = solve_optimal_frontrun(amm, victim_sol_in, victim_min_tokens, &fees);
+        let shortfall = baseline_tokens.saturating_sub(best.victim_tokens);
+        profits.push(best.bot_profit.max(0));
+        losses.push((shortfall as u128 * victim_sol_in as u128 / baseline_tokens as u128) as i64);
+    }
+
+    if profits.is_empty() {
+        println!("No scenarios could be simulated against this curve state");
+        return;
+    }
+
+    let profitable = profits.iter().filter(|profit| **profit > 0).count();
+    println!(
+        "Monte Carlo: {} scenarios (seed {}) | victim sizes {} | slippage {}-{} bps",
+        profits.len(),
+        sim.seed,
+        match empirical {
+            Some(sizes) => format!("sampled from {} observed trades", sizes.len()),
+            None => format!("log-normal, median {:.3} SOL, sigma {:.2}", sim.victim_sol, sim.victim_sol_sigma),
+        },
+        sim.slippage_min_bps,
+        sim.slippage_max_bps
+    );
+    println!(
+        "Profitable sandwiches: {} ({:.1}%)",
+        profitable,
+        profitable as f64 * 100.0 / profits.len() as f64
+    );
+    print_distribution("Attacker profit", &mut profits);
+    print_distribution("Victim loss", &mut losses);
+}
+
+fn print_distribution(label: &str, values: &mut [i64]) {
+    values.sort_unstable();
+    let mean = values.iter().map(|value| *value as f64).sum::<f64>() / values.len() as f64;
+    let pct = |p: usize| values[(values.len() - 1) * p / 100] as f64 / LAMPORTS_PER_SOL as f64;
+    println!(
+        "{} (SOL): mean {:.6} | p5 {:.6} | p25 {:.6} | p50 {:.6} | p75 {:.6} | p95 {:.6} | max {:.6}",
+        label,
+        mean / LAMPORTS_PER_SOL as f64,
+        pct(5),
+        pct(25),
+        pct(50),
+        pct(75),
+        pct(95),
+        pct(100)
+    );
+}
@@ -0,0 +1,110 @@
+use crate::cli::{QueuedVictim, SimArgs};
+use crate::emit::{Order, TradeLog};
+use crate::fees::FeeModel;
+use crate::solver::solve_optimal_frontrun;
+use rusty::amm::{LAMPORTS_PER_SOL, PumpAmmState};
+
+// A run of victim buys, one per slot. The bot sees each one coming, sizes the optimal frontrun
+// against the curve as earlier trades left it, and only sandwiches when that frontrun clears its costs;
+// skipped victims still move the price for the ones behind them.
+
+struct Step {
+    sandwiched: bool,
+    frontrun_sol: u64,
+    bot_profit: i64,
+    victim_loss: u64,
+}
+
+pub fn simulate_victim_queue(amm: &PumpAmmState, sim: &SimArgs, victims: &[QueuedVictim], base_slot: u64, log: &mut TradeLog) {
+    let mut amm = amm.clone();
+    let sol = |lamports: f64| lamports / LAMPORTS_PER_SOL as f64;
+
+    println!("\nVictim queue: {} buys from slot {}", victims.len(), base_slot);
+    println!(
+        "\n{:>11} {:>10} {:>6} {:>11} {:>13} {:>14} {:>14} {:>13} {:>15}",
+        "Slot", "Victim SOL", "Bps", "Action", "Frontrun SOL", "Bot profit", "Victim loss", "Flow SOL", "Cum. profit"
+    );
+    let (mut flow, mut profit, mut loss, mut sandwiched) = (0u64, 0i64, 0u64, 0usize);
+    for (index, victim) in victims.iter().enumerate() {
+        let slippage_bps = victim.slippage_bps.unwrap_or(sim.victim_slippage_bps);
+        let victim_sol_in = (victim.sol * LAMPORTS_PER_SOL as f64) as u64;
+        let slot = base_slot + index as u64;
+        let victim_name = format!("victim{}", index + 1);
+        let step = sandwich_or_skip(&mut amm, victim_sol_in, slippage_bps, &sim.fees, log, slot, &victim_name);
+
+        flow = flow.saturating_add(victim_sol_in);
+        profit = profit.saturating_add(step.bot_profit);
+        loss = loss.saturating_add(step.victim_loss);
+        sandwiched += step.sandwiched as usize;
+        println!(
+            "{:>11} {:>10.3} {:>6} {:>11} {:>13.6} {:>14.6} {:>14.6} {:>13.3} {:>15.6}",
+            slot,
+            victim.sol,
+            slippage_bps,
+            if step.sandwiched { "sandwich" } else { "skip" },
+            sol(step.frontrun_sol as f64),
+            sol(step.bot_profit as f64),
+            sol(step.victim_loss as f64),
+            sol(flow as f64),
+            sol(profit as f64)
+        );
+    }
+
+    println!("\nSandwiched {} of {} victims", sandwiched, victims.len());
+    println!(
+        "Bot Total Net Profit: {:.6} SOL from {:.3} SOL of victim flow ({:.3}%)",
+        sol(profit as f64),
+        sol(flow as f64),
+        if flow == 0 { 0.0 } else { profit as f64 / flow as f64 * 100.0 }
+    );
+    println!("Victim losses: {:.6} SOL", sol(loss as f64));
+    println!("Price after queue: {:.12} SOL/token", amm.price());
+}
+
+// the victim's loss is the SOL value of the tokens the frontrun cost it, at its unattacked price;
+// a sandwich lands frontrun, victim and backrun in the victim's slot
+fn sandwich_or_skip(
+    amm: &mut PumpAmmState,
+    victim_sol_in: u64,
+    slippage_bps: u64,
+    fees: &FeeModel,
+    log: &mut TradeLog,
+    slot: u64,
+    victim_name: &str,
+) -> Step {
+    let baseline_tokens = amm.quote_buy(victim_sol_in);
+    let victim_min_tokens = (baseline_tokens as u128 * (10_000 - slippage_bps) as u128 / 10_000) as u64;
+    let victim_order = Order::Buy {
+        sol_in: victim_sol_in,
+        quoted_tokens: baseline_tokens,
+    };
+    let best = solve_optimal_frontrun(amm, victim_sol_in, victim_min_tokens, fees);
+    if best.frontrun_sol == 0 || best.bot_profit <= 0 {
+        let before = amm.clone();
+        amm.simulate_buy(victim_sol_in, victim_min_tokens);
+        log.record(victim_name, slot, victim_order, &before, amm);
+        return Step {
+            sandwiched: false,
+            frontrun_sol: 0,
+            bot_profit: 0,
+            victim_loss: 0,
+        };
+    }
+
+    let before = amm.clone();
+    let (bot_tokens, _) = amm.simulate_buy(best.frontrun_sol, 0);
+    log.record("bot", slot, Order::buy(&before, best.frontrun_sol), &before, amm);
+    let before = amm.clone();
+    let (victim_tokens, _) = amm.simulate_buy(victim_sol_in, victim_min_tokens);
+    log.record(victim_name, slot, victim_order, &before, amm);
+    let before = amm.clone();
+    amm.simulate_sell(bot_tokens, 0);
+    log.record("bot", slot, Order::sell(&before, bot_tokens), &before, amm);
+    let shortfall = baseline_tokens.saturating_sub(victim_tokens);
+    Step {
+        sandwiched: true,
+        frontrun_sol: best.frontrun_sol,
+        bot_profit: best.bot_profit,
+        victim_loss: (shortfall as u128 * victim_sol_in as u128 / baseline_tokens.max(1) as u128) as u64,
+    }
+}
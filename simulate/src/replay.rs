@@ -0,0 +1,127 @@
+use crate::backrun::best_backruns;
+use crate::cli::VictimSide;
+use rusty::amm::{FeeSchedule, LAMPORTS_PER_SOL, PumpAmmState};
+use rusty::detect::{BackrunArbEvent, DetectionSummary, SandwichDetection};
+use rusty::parser::pumpfun::TradeType;
+use rusty::error::Error;
+use std::fs;
+
+pub struct ReplayResult {
+    pub observed_profit: i64,
+    pub simulated_profit: i64,
+    pub explained: bool,
+}
+
+pub fn load_summary(path: &str) -> Result<DetectionSummary, Error> {
+    let raw = fs::read_to_string(path).map_err(|e| Error::io("read detections file", path, e))?;
+    serde_json::from_str(&raw).map_err(|e| Error::json(format!("detections file '{}'", path), e))
+}
+
+pub fn replay_sandwich(det: &SandwichDetection, tolerance_pct: f64, schedule: &FeeSchedule) -> Option<ReplayResult> {
+    let mut amm = PumpAmmState::before_trade(det.frontruns.first()?, schedule)?;
+
+    let mut curve_profit: i64 = 0;
+    for leg in &det.frontruns {
+        curve_profit += amm.apply_trade(leg);
+    }
+    amm.apply_trade(&det.victim);
+    for leg in &det.backruns {
+        curve_profit += amm.apply_trade(leg);
+    }
+
+    let off_curve_costs: i64 = det
+        .frontruns
+        .iter()
+        .chain(det.backruns.iter())
+        .map(|leg| leg.costs() as i64)
+        .sum();
+    let simulated_profit = curve_profit - off_curve_costs;
+    let slack = (simulated_profit.unsigned_abs() as f64 * tolerance_pct / 100.0).max(LAMPORTS_PER_SOL as f64 / 1_000.0);
+
+    Some(ReplayResult {
+        observed_profit: det.net_profit_sol,
+        simulated_profit,
+        explained: ((det.net_profit_sol - simulated_profit).unsigned_abs() as f64) <= slack,
+    })
+}
+
+// the observed gross profit against the best pure backrun of the same victim; a close that lands
+// after favourable flow can beat it, so only a large excess is flagged
+pub fn replay_backrun_arb(arb: &BackrunArbEvent, tolerance_pct: f64, schedule: &FeeSchedule) -> Option<ReplayResult> {
+    let before = PumpAmmState::before_trade(&arb.victim, schedule)?;
+    let mut after = before.clone();
+    after.apply_trade(&arb.victim);
+    let side = match arb.victim.trade_type {
+        TradeType::Buy => VictimSide::Buy,
+        TradeType::Sell => VictimSide::Sell,
+    };
+    let (_, best) = best_backruns(&before, &after, side);
+    let slack = (best.gross_profit.unsigned_abs() as f64 * tolerance_pct / 100.0).max(LAMPORTS_PER_SOL as f64 / 1_000.0);
+
+    Some(ReplayResult {
+        observed_profit: arb.gross_profit_sol,
+        simulated_profit: best.gross_profit,
+        explained: ((arb.gross_profit_sol - best.gross_profit) as f64) <= slack,
+    })
+}
+
+pub fn run_replay(path: &str, tolerance_pct: f64, schedule: &FeeSchedule) -> Result<(), Error> {
+    let summary = load_summary(path)?;
+    println!(
+        "Replaying {} detected sandwiches from {} (tolerance {:.0}%)",
+        summary.sandwiches.len(),
+        path,
+        tolerance_pct
+    );
+
+    let mut explained = 0;
+    let mut unexplained = 0;
+    let mut skipped = 0;
+    for (idx, det) in summary.sandwiches.iter().enumerate() {
+        let Some(result) = replay_sandwich(det, tolerance_pct, schedule) else {
+            skipped += 1;
+            println!("#{:02} victim {} | could not reconstruct reserves", idx + 1, det.victim.signature);
+            continue;
+        };
+        if result.explained {
+            explained += 1;
+        } else {
+            unexplained += 1;
+        }
+        println!(
+            "#{:02} victim {} | observed {:+.6} SOL | simulated {:+.6} SOL | {}",
+            idx + 1,
+            det.victim.signature,
+            result.observed_profit as f64 / LAMPORTS_PER_SOL as f64,
+            result.simulated_profit as f64 / LAMPORTS_PER_SOL as f64,
+            if result.explained { "explained" } else { "UNEXPLAINED (likely false positive)" }
+        );
+    }
+
+    println!(
+        "\nExplained: {} | unexplained: {} | not reconstructable: {}",
+        explained, unexplained, skipped
+    );
+
+    if !summary.backrun_arbs.is_empty() {
+        println!(
+            "\nChecking {} backrun arbitrages against the best pure backrun of each victim (gross, before costs)",
+            summary.backrun_arbs.len()
+        );
+    }
+    for (idx, arb) in summary.backrun_arbs.iter().enumerate() {
+        let Some(result) = replay_backrun_arb(arb, tolerance_pct, schedule) else {
+            println!("#{:02} victim {} | could not reconstruct reserves", idx + 1, arb.victim.signature);
+            continue;
+        };
+        println!(
+            "#{:02} victim {} | observed {:+.6} SOL | theoretical max {:+.6} SOL | {}",
+            idx + 1,
+            arb.victim.signature,
+            result.observed_profit as f64 / LAMPORTS_PER_SOL as f64,
+            result.simulated_profit as f64 / LAMPORTS_PER_SOL as f64,
+            if result.explained { "plausible" } else { "ABOVE THEORETICAL (check the legs)" }
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,333 @@
+use crate::cli::SimArgs;
+use crate::emit::{Order, TradeLog};
+use rusty::amm::{LAMPORTS_PER_SOL, PumpAmmState};
+use crate::yaml::{self, Node};
+use rusty::error::Error;
+use std::fs;
+use toml_edit::{Document, Item, TableLike, Value};
+
+// A scripted run: trades executed in file order against one curve, each at its own slot's fee
+// tier and charged one transaction's base and priority fee, so a what-if can be checked in and
+// rerun unchanged.
+//
+//   [curve]                  # optional, like --virtual-sol / --virtual-token
+//   virtual_sol = 45.0
+//
+//   [[trade]]
+//   actor = "bot"
+//   side = "buy"
+//   sol = 0.3
+//   slot = 380000000         # optional, defaults to the previous trade's slot (--slot first)
+//
+//   [[trade]]
+//   actor = "bot"
+//   side = "sell"
+//   tokens = "all"           # or a number of whole tokens
+//   slippage_bps = 100       # optional, default 0: no limit
+//
+// or the same keys in a .yaml / .yml file:
+//
+//   curve:
+//     virtual_sol: 45.0
+//   trade:
+//     - actor: bot
+//       side: buy
+//       sol: 0.3
+//     - actor: bot
+//       side: sell
+//       tokens: all
+
+#[derive(Debug, Clone, Default)]
+pub struct Scenario {
+    pub virtual_sol: Option<f64>,
+    pub virtual_token: Option<f64>,
+    pub trades: Vec<ScriptedTrade>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ScriptedTrade {
+    pub actor: String,
+    pub size: TradeSize,
+    pub slippage_bps: u64,
+    pub slot: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TradeSize {
+    BuySol(f64),
+    SellTokens(f64),
+    // the actor's whole token balance at that point
+    SellAll,
+}
+
+#[derive(Debug, Default)]
+struct Position {
+    sol: i64,
+    tokens: i64,
+    trades: usize,
+    reverted: usize,
+}
+
+impl Scenario {
+    // YAML for a .yaml or .yml file, TOML otherwise
+    pub fn load(path: &str) -> Result<Self, Error> {
+        let raw = fs::read_to_string(path).map_err(|e| Error::io("read scenario", path, e))?;
+        let parsed = if path.ends_with(".yaml") || path.ends_with(".yml") {
+            Self::from_yaml(&raw)
+        } else {
+            Self::from_toml(&raw)
+        };
+        parsed.map_err(|e| Error::Config(format!("Invalid scenario '{}': {}", path, e)))
+    }
+
+    pub fn from_toml(raw: &str) -> Result<Self, String> {
+        let doc = Document::parse(raw).map_err(|e| e.to_string())?;
+        Self::from_node(&toml_node(doc.as_item())?)
+    }
+
+    pub fn from_yaml(raw: &str) -> Result<Self, String> {
+        Self::from_node(&yaml::parse(raw)?)
+    }
+
+    fn from_node(node: &Node) -> Result<Self, String> {
+        let Node::Mapping(sections) = node else {
+            return Err("expected a 'curve' table and 'trade' entries".to_string());
+        };
+        let mut scenario = Scenario::default();
+        for (key, item) in sections {
+            match key.as_str() {
+                "curve" => {
+                    let Node::Mapping(table) = item else {
+                        return Err("'curve' must be a table".to_string());
+                    };
+                    apply_curve_section(&mut scenario, table)?;
+                }
+                "trade" => {
+                    let Node::Sequence(trades) = item else {
+                        return Err("'trade' must be a list of trades".to_string());
+                    };
+                    for (index, table) in trades.iter().enumerate() {
+                        let trade = match table {
+                            Node::Mapping(table) => parse_trade(table),
+                            _ => Err("must be a table".to_string()),
+                        };
+                        scenario.trades.push(trade.map_err(|e| format!("trade {}: {}", index + 1, e))?);
+                    }
+                }
+                other => return Err(format!("unknown key '{}'", other)),
+            }
+        }
+        if scenario.trades.is_empty() {
+            return Err("no trade entries".to_string());
+        }
+        Ok(scenario)
+    }
+}
+
+// TOML read into the same tree a YAML scenario parses to, so both share one reader
+fn toml_node(item: &Item) -> Result<Node, String> {
+    if let Some(tables) = item.as_array_of_tables() {
+        return tables.iter().map(|table| table_node(table)).collect::<Result<_, _>>().map(Node::Sequence);
+    }
+    if let Some(table) = item.as_table_like() {
+        return table_node(table);
+    }
+    Ok(match item.as_value() {
+        Some(Value::String(value)) => Node::String(value.value().clone()),
+        Some(Value::Integer(value)) => Node::Integer(*value.value()),
+        Some(Value::Float(value)) => Node::Float(*value.value()),
+        Some(Value::Boolean(value)) => Node::Bool(*value.value()),
+        Some(Value::Datetime(value)) => Node::String(value.value().to_string()),
+        Some(Value::Array(values)) => Node::Sequence(
+            values
+                .iter()
+                .map(|value| toml_node(&Item::Value(value.clone())))
+                .collect::<Result<_, _>>()?,
+        ),
+        // inline tables are table-like and taken above
+        Some(Value::InlineTable(_)) | None => Node::Null,
+    })
+}
+
+fn table_node(table: &dyn TableLike) -> Result<Node, String> {
+    table
+        .iter()
+        .map(|(key, item)| Ok((key.to_string(), toml_node(item)?)))
+        .collect::<Result<_, String>>()
+        .map(Node::Mapping)
+}
+
+fn apply_curve_section(scenario: &mut Scenario, table: &[(String, Node)]) -> Result<(), String> {
+    for (key, item) in table {
+        match key.as_str() {
+            "virtual_sol" => scenario.virtual_sol = Some(positive(key, item)?),
+            "virtual_token" => scenario.virtual_token = Some(positive(key, item)?),
+            other => return Err(format!("unknown key curve.{}", other)),
+        }
+    }
+    Ok(())
+}
+
+fn parse_trade(table: &[(String, Node)]) -> Result<ScriptedTrade, String> {
+    let (mut actor, mut side, mut sol, mut tokens) = (None, None, None, None);
+    let mut trade = ScriptedTrade {
+        actor: String::new(),
+        size: TradeSize::SellAll,
+        slippage_bps: 0,
+        slot: None,
+    };
+    for (key, item) in table {
+        match key.as_str() {
+            "actor" => actor = Some(string(key, item)?.to_string()),
+            "side" => side = Some(string(key, item)?.to_string()),
+            "sol" => sol = Some(positive(key, item)?),
+            "tokens" if *item == Node::String("all".to_string()) => tokens = Some(None),
+            "tokens" => tokens = Some(Some(positive(key, item)?)),
+            "slippage_bps" => {
+                trade.slippage_bps = unsigned(key, item)?;
+                if trade.slippage_bps > 10_000 {
+                    return Err("'slippage_bps' must be at most 10000".to_string());
+                }
+            }
+            "slot" => trade.slot = Some(unsigned(key, item)?),
+            other => return Err(format!("unknown key '{}'", other)),
+        }
+    }
+    trade.actor = actor.ok_or("missing 'actor'")?;
+    trade.size = match (side.as_deref(), sol, tokens) {
+        (Some("buy"), Some(sol), None) => TradeSize::BuySol(sol),
+        (Some("sell"), None, Some(Some(tokens))) => TradeSize::SellTokens(tokens),
+        (Some("sell"), None, Some(None)) => TradeSize::SellAll,
+        (Some("buy"), _, _) => return Err("a buy takes 'sol' only".to_string()),
+        (Some("sell"), _, _) => return Err("a sell takes 'tokens' only".to_string()),
+        (Some(other), _, _) => return Err(format!("unknown side '{}': use buy or sell", other)),
+        (None, _, _) => return Err("missing 'side'".to_string()),
+    };
+    Ok(trade)
+}
+
+fn string<'a>(key: &str, item: &'a Node) -> Result<&'a str, String> {
+    match item {
+        Node::String(value) => Ok(value),
+        _ => Err(format!("'{}' must be a string", key)),
+    }
+}
+
+fn unsigned(key: &str, item: &Node) -> Result<u64, String> {
+    let Node::Integer(value) = item else {
+        return Err(format!("'{}' must be an integer", key));
+    };
+    u64::try_from(*value).map_err(|_| format!("'{}' must not be negative", key))
+}
+
+fn positive(key: &str, item: &Node) -> Result<f64, String> {
+    let value = match item {
+        Node::Float(value) => *value,
+        Node::Integer(value) => *value as f64,
+        _ => return Err(format!("'{}' must be a number", key)),
+    };
+    // both formats spell out nan and inf, and neither is a size
+    if !value.is_finite() || value <= 0.0 {
+        return Err(format!("'{}' must be a positive finite number", key));
+    }
+    Ok(value)
+}
+
+pub fn run_scenario(amm: &PumpAmmState, sim: &SimArgs, scenario: &Scenario, log: &mut TradeLog) {
+    let mut amm = amm.clone();
+    let sol = |lamports: i64| lamports as f64 / LAMPORTS_PER_SOL as f64;
+    // actors in order of first appearance
+    let mut positions: Vec<(String, Position)> = Vec::new();
+    let mut slot = sim.slot;
+    let tx_cost = sim.fees.tx_cost();
+
+    println!("\nScenario: {} trades, starting price {:.12} SOL/token", scenario.trades.len(), amm.price());
+    for (index, trade) in scenario.trades.iter().enumerate() {
+        slot = trade.slot.unwrap_or(slot);
+        amm.fee = sim.fee_schedule.at(slot);
+        let at = match positions.iter().position(|(actor, _)| *actor == trade.actor) {
+            Some(at) => at,
+            None => {
+                positions.push((trade.actor.clone(), Position::default()));
+                positions.len() - 1
+            }
+        };
+        let position = &mut positions[at].1;
+        let before = amm.clone();
+        position.trades += 1;
+        position.sol = position.sol.saturating_sub(tx_cost as i64);
+
+        let (label, filled) = match trade.size {
+            TradeSize::BuySol(size) => {
+                let sol_in = (size * LAMPORTS_PER_SOL as f64) as u64;
+                let min_tokens = (amm.quote_buy(sol_in) as u128 * (10_000 - trade.slippage_bps) as u128 / 10_000) as u64;
+                let (tokens, _) = amm.simulate_buy(sol_in, min_tokens);
+                log.record(&trade.actor, slot, Order::buy(&before, sol_in), &before, &amm);
+                let filled = tokens > 0;
+                if filled {
+                    position.sol = position.sol.saturating_sub(sol_in as i64);
+                    position.tokens += tokens as i64;
+                }
+                (
+                    format!("Buy {:.3} SOL -> {:.0} tokens", size, tokens as f64 / sim.token_unit()),
+                    filled,
+                )
+            }
+            TradeSize::SellTokens(_) | TradeSize::SellAll => {
+                let tokens_in = match trade.size {
+                    TradeSize::SellTokens(tokens) => (tokens * sim.token_unit()) as u64,
+                    _ => position.tokens.max(0) as u64,
+                };
+                let min_sol = (amm.quote_sell(tokens_in) as u128 * (10_000 - trade.slippage_bps) as u128 / 10_000) as u64;
+                let sol_out = if tokens_in == 0 { 0 } else { amm.simulate_sell(tokens_in, min_sol) };
+                log.record(&trade.actor, slot, Order::sell(&before, tokens_in), &before, &amm);
+                let filled = sol_out > 0;
+                if filled {
+                    position.sol = position.sol.saturating_add(sol_out as i64);
+                    position.tokens -= tokens_in as i64;
+                }
+                (
+                    format!("Sell {:.0} tokens -> {:.6} SOL", tokens_in as f64 / sim.token_unit(), sol(sol_out as i64)),
+                    filled,
+                )
+            }
+        };
+        if !filled {
+            position.reverted += 1;
+        }
+        println!(
+            "Step {} slot {} | {} {}{} | price after {:.12} SOL/token",
+            index + 1,
+            slot,
+            trade.actor,
+            label,
+            if filled { "" } else { " (reverted)" },
+            amm.price()
+        );
+    }
+
+    // open token positions are marked at what selling (or buying back) them would fetch at the end
+    println!("\n{:<12} {:>6} {:>9} {:>14} {:>16} {:>14} {:>14}", "Actor", "Trades", "Reverted", "SOL P&L", "Tokens held", "Marked SOL", "Total SOL");
+    for (actor, position) in &positions {
+        let marked = if position.tokens >= 0 {
+            amm.quote_sell(position.tokens as u64) as i64
+        } else {
+            -(amm.sol_for_tokens(position.tokens.unsigned_abs()).min(i64::MAX as u64) as i64)
+        };
+        println!(
+            "{:<12} {:>6} {:>9} {:>+14.6} {:>16.0} {:>+14.6} {:>+14.6}",
+            actor,
+            position.trades,
+            position.reverted,
+            sol(position.sol),
+            position.tokens as f64 / sim.token_unit(),
+            sol(marked),
+            sol(position.sol + marked)
+        );
+    }
+    println!(
+        "\nFees collected: protocol {:.6} SOL | creator {:.6} SOL",
+        sol(amm.protocol_fees as i64),
+        sol(amm.creator_fees as i64)
+    );
+}
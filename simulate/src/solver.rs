@@ -0,0 +1,110 @@
+use crate::fees::FeeModel;
+use rusty::amm::{LAMPORTS_PER_SOL, PumpAmmState};
+
+pub struct FrontrunOutcome {
+    pub frontrun_sol: u64,
+    pub victim_tokens: u64,
+    pub bot_profit: i64,
+}
+
+pub fn evaluate_frontrun(
+    amm: &PumpAmmState,
+    frontrun_sol: u64,
+    victim_sol_in: u64,
+    victim_min_tokens: u64,
+    fees: &FeeModel,
+) -> FrontrunOutcome {
+    let mut amm = amm.clone();
+    let (bot_tokens, bot_paid) = if frontrun_sol == 0 {
+        (0, 0)
+    } else {
+        amm.simulate_buy(frontrun_sol, 0)
+    };
+    let (victim_tokens, _) = amm.simulate_buy(victim_sol_in, victim_min_tokens);
+    let bot_received = if bot_tokens == 0 { 0 } else { amm.simulate_sell(bot_tokens, 0) };
+
+    FrontrunOutcome {
+        frontrun_sol,
+        victim_tokens,
+        bot_profit: if frontrun_sol == 0 { 0 } else { fees.net_profit(bot_received as i64 - bot_paid as i64, 2) },
+    }
+}
+
+// Largest frontrun that still lets the victim fill at its min_tokens_out; victim output falls
+// monotonically as the frontrun grows, so bisection up to ten times the virtual SOL reserve is enough.
+pub fn max_feasible_frontrun(amm: &PumpAmmState, victim_sol_in: u64, victim_min_tokens: u64) -> u64 {
+    if evaluate_frontrun(amm, 0, victim_sol_in, victim_min_tokens, &FeeModel::default()).victim_tokens == 0 {
+        return 0;
+    }
+
+    let mut low: u64 = 0;
+    let mut high: u64 = amm.virtual_sol.saturating_mul(10);
+    while high - low > 1_000 {
+        let mid = low + (high - low) / 2;
+        if evaluate_frontrun(amm, mid, victim_sol_in, victim_min_tokens, &FeeModel::default()).victim_tokens > 0 {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+    low
+}
+
+pub fn solve_optimal_frontrun(amm: &PumpAmmState, victim_sol_in: u64, victim_min_tokens: u64, fees: &FeeModel) -> FrontrunOutcome {
+    let limit = max_feasible_frontrun(amm, victim_sol_in, victim_min_tokens);
+    let profit = |sol: u64| evaluate_frontrun(amm, sol, victim_sol_in, victim_min_tokens, fees).bot_profit;
+
+    // profit is unimodal on the feasible range: ternary search, then keep the constraint edge if it wins
+    let mut low: u64 = 0;
+    let mut high = limit;
+    while high - low > 1_000 {
+        let third = (high - low) / 3;
+        let (m1, m2) = (low + third, high - third);
+        if profit(m1) < profit(m2) {
+            low = m1;
+        } else {
+            high = m2;
+        }
+    }
+
+    [low, high, limit]
+        .into_iter()
+        .map(|sol| evaluate_frontrun(amm, sol, victim_sol_in, victim_min_tokens, fees))
+        .max_by_key(|outcome| outcome.bot_profit)
+        .unwrap_or_else(|| evaluate_frontrun(amm, 0, victim_sol_in, victim_min_tokens, fees))
+}
+
+pub fn print_profit_frontier(
+    amm: &PumpAmmState,
+    victim_sol_in: u64,
+    victim_min_tokens: u64,
+    steps: usize,
+    token_unit: f64,
+    fees: &FeeModel,
+) {
+    let limit = max_feasible_frontrun(amm, victim_sol_in, victim_min_tokens);
+    println!(
+        "\nMax frontrun keeping victim within slippage: {:.6} SOL",
+        limit as f64 / LAMPORTS_PER_SOL as f64
+    );
+
+    println!("\n{:>14} {:>18} {:>16}", "Frontrun SOL", "Victim tokens", "Bot profit SOL");
+    for step in 0..=steps {
+        let sol = (limit as u128 * step as u128 / steps.max(1) as u128) as u64;
+        let outcome = evaluate_frontrun(amm, sol, victim_sol_in, victim_min_tokens, fees);
+        println!(
+            "{:>14.6} {:>18.0} {:>16.6}",
+            outcome.frontrun_sol as f64 / LAMPORTS_PER_SOL as f64,
+            outcome.victim_tokens as f64 / token_unit,
+            outcome.bot_profit as f64 / LAMPORTS_PER_SOL as f64
+        );
+    }
+
+    let best = solve_optimal_frontrun(amm, victim_sol_in, victim_min_tokens, fees);
+    println!(
+        "\nOptimal frontrun: {:.6} SOL | victim receives {:.0} tokens | bot profit {:.6} SOL",
+        best.frontrun_sol as f64 / LAMPORTS_PER_SOL as f64,
+        best.victim_tokens as f64 / token_unit,
+        best.bot_profit as f64 / LAMPORTS_PER_SOL as f64
+    );
+}
@@ -0,0 +1,219 @@
+// The block subset of YAML that scenario files use: nested mappings and `- ` sequences by
+// indentation, plain or quoted scalars and `#` comments. Flow collections (`[..]`, `{..}`),
+// anchors, tags and multi-line strings are rejected rather than misread.
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Node {
+    Null,
+    Bool(bool),
+    Integer(i64),
+    Float(f64),
+    String(String),
+    Sequence(Vec<Node>),
+    // keys in file order
+    Mapping(Vec<(String, Node)>),
+}
+
+struct Line {
+    number: usize,
+    indent: usize,
+    text: String,
+}
+
+pub fn parse(raw: &str) -> Result<Node, String> {
+    let mut lines = Vec::new();
+    for (index, line) in raw.lines().enumerate() {
+        let number = index + 1;
+        let text = strip_comment(line).trim_end();
+        let content = text.trim_start_matches(' ');
+        if content.is_empty() || (lines.is_empty() && content == "---") {
+            continue;
+        }
+        if content.starts_with('\t') {
+            return Err(format!("line {}: indent with spaces, not tabs", number));
+        }
+        lines.push(Line {
+            number,
+            indent: text.len() - content.len(),
+            text: content.to_string(),
+        });
+    }
+    if lines.is_empty() {
+        return Ok(Node::Null);
+    }
+
+    let mut parser = Parser { lines, at: 0 };
+    let node = parser.block(parser.lines[0].indent)?;
+    match parser.lines.get(parser.at) {
+        Some(line) => Err(format!("line {}: unexpected indentation", line.number)),
+        None => Ok(node),
+    }
+}
+
+struct Parser {
+    lines: Vec<Line>,
+    at: usize,
+}
+
+impl Parser {
+    fn block(&mut self, indent: usize) -> Result<Node, String> {
+        if is_item(&self.lines[self.at].text) {
+            self.sequence(indent)
+        } else {
+            self.mapping(indent)
+        }
+    }
+
+    fn sequence(&mut self, indent: usize) -> Result<Node, String> {
+        let mut items = Vec::new();
+        while let Some(line) = self.lines.get_mut(self.at)
+            && line.indent == indent
+            && is_item(&line.text)
+        {
+            let rest = line.text[1..].trim_start_matches(' ');
+            if rest.is_empty() {
+                self.at += 1;
+                items.push(self.child(indent)?);
+            } else {
+                // "- key: value" opens a mapping whose keys line up with `key`
+                line.indent += line.text.len() - rest.len();
+                line.text = rest.to_string();
+                let indent = line.indent;
+                items.push(self.block(indent)?);
+            }
+        }
+        Ok(Node::Sequence(items))
+    }
+
+    fn mapping(&mut self, indent: usize) -> Result<Node, String> {
+        let mut entries: Vec<(String, Node)> = Vec::new();
+        while let Some(line) = self.lines.get(self.at)
+            && line.indent == indent
+            && !is_item(&line.text)
+        {
+            let number = line.number;
+            let (key, value) = split_key(&line.text).ok_or_else(|| format!("line {}: expected 'key: value'", number))?;
+            let key = match scalar(key, number)? {
+                Node::String(key) => key,
+                _ => key.to_string(),
+            };
+            if entries.iter().any(|(seen, _)| *seen == key) {
+                return Err(format!("line {}: duplicate key '{}'", number, key));
+            }
+            let value = value.to_string();
+            self.at += 1;
+            let node = if value.is_empty() {
+                // a sequence may sit at its key's own indentation
+                match self.lines.get(self.at) {
+                    Some(next) if next.indent == indent && is_item(&next.text) => self.sequence(indent)?,
+                    _ => self.child(indent)?,
+                }
+            } else {
+                scalar(&value, number)?
+            };
+            entries.push((key, node));
+        }
+        Ok(Node::Mapping(entries))
+    }
+
+    // the block nested under a line at `indent`, or null when the next line isn't deeper
+    fn child(&mut self, indent: usize) -> Result<Node, String> {
+        match self.lines.get(self.at) {
+            Some(next) if next.indent > indent => {
+                let indent = next.indent;
+                self.block(indent)
+            }
+            _ => Ok(Node::Null),
+        }
+    }
+}
+
+fn is_item(text: &str) -> bool {
+    text == "-" || text.starts_with("- ")
+}
+
+// "key: value" or "key:", outside quotes
+fn split_key(text: &str) -> Option<(&str, &str)> {
+    let mut quote = None;
+    for (at, c) in text.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(open), c) if c == open => quote = None,
+            (None, ':') if text[at + 1..].is_empty() || text[at + 1..].starts_with(' ') => {
+                return Some((text[..at].trim_end(), text[at + 1..].trim()));
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+// a `#` starts a comment at the start of a line or after a space, outside quotes
+fn strip_comment(line: &str) -> &str {
+    let mut quote = None;
+    let mut previous = ' ';
+    for (at, c) in line.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(open), c) if c == open => quote = None,
+            (None, '#') if previous == ' ' || previous == '\t' => return &line[..at],
+            _ => {}
+        }
+        previous = c;
+    }
+    line
+}
+
+fn scalar(text: &str, number: usize) -> Result<Node, String> {
+    if let Some(inner) = text.strip_prefix('"') {
+        let inner = inner
+            .strip_suffix('"')
+            .ok_or_else(|| format!("line {}: unterminated string", number))?;
+        return unescape(inner).map(Node::String).ok_or_else(|| format!("line {}: bad escape in {}", number, text));
+    }
+    if let Some(inner) = text.strip_prefix('\'') {
+        let inner = inner
+            .strip_suffix('\'')
+            .ok_or_else(|| format!("line {}: unterminated string", number))?;
+        return Ok(Node::String(inner.replace("''", "'")));
+    }
+    if text.starts_with(['[', '{', '&', '*', '!', '|', '>']) {
+        return Err(format!("line {}: only plain and quoted scalars are supported, got '{}'", number, text));
+    }
+
+    Ok(match text {
+        "~" | "null" | "Null" | "NULL" => Node::Null,
+        "true" | "True" | "TRUE" => Node::Bool(true),
+        "false" | "False" | "FALSE" => Node::Bool(false),
+        ".inf" | ".Inf" | ".INF" | "+.inf" | "+.Inf" | "+.INF" => Node::Float(f64::INFINITY),
+        "-.inf" | "-.Inf" | "-.INF" => Node::Float(f64::NEG_INFINITY),
+        ".nan" | ".NaN" | ".NAN" => Node::Float(f64::NAN),
+        _ => {
+            let numeric = text.bytes().all(|b| b.is_ascii_digit() || b"+-.eE".contains(&b)) && text.bytes().any(|b| b.is_ascii_digit());
+            match (numeric, text.parse::<i64>(), text.parse::<f64>()) {
+                (true, Ok(integer), _) => Node::Integer(integer),
+                (true, _, Ok(float)) => Node::Float(float),
+                _ => Node::String(text.to_string()),
+            }
+        }
+    })
+}
+
+fn unescape(inner: &str) -> Option<String> {
+    let mut out = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        out.push(match chars.next()? {
+            '"' => '"',
+            '\\' => '\\',
+            'n' => '\n',
+            't' => '\t',
+            _ => return None,
+        });
+    }
+    Some(out)
+}
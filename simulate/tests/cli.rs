@@ -0,0 +1,724 @@
+use rusty::amm::{FeeSchedule, PumpAmmState};
+use rusty::detect::{DetectionSummary, DetectorConfig, SandwichDetection, detect_wide_attacks};
+use rusty::embed::{parse_transaction_json, read_trades};
+use rusty::fetch::SavedTrades;
+use rusty::parser::pumpfun::{ParsedTransaction, TradeType};
+use std::fs;
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::path::PathBuf;
+use std::process::{Command, Output};
+use std::thread;
+
+const FIXTURE_MINT: &str = "4LTksEDzHp7X2keAjU8kumFHtydEDkX2td43xx9w8B3G";
+
+fn simulate(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_grok_simulate"))
+        .args(args)
+        .env("RPC_URL", "")
+        .output()
+        .unwrap_or_else(|e| panic!("running grok_simulate: {}", e))
+}
+
+// a usage error: exit code 2, the message on stderr and no panic
+fn rejected(args: &[&str], message: &str) {
+    let output = simulate(args);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(2), "{:?} exited with {:?}: {}", args, output.status, stderr);
+    assert!(stderr.contains(message), "{:?} printed {}", args, stderr);
+    assert!(!stderr.contains("panicked"), "{:?} panicked: {}", args, stderr);
+}
+
+#[test]
+fn rejects_non_finite_numbers() {
+    for flag in ["--victim-sol", "--frontrun-fraction", "--backrun-split", "--tip-pct", "--priority-fee"] {
+        for value in ["nan", "inf", "-inf"] {
+            rejected(&[flag, value], "must be a finite number");
+        }
+    }
+    rejected(&["--victims", "1,NaN"], "must be a finite number");
+    rejected(&["impact", "--max-sol", "inf"], "must be a finite number");
+}
+
+#[test]
+fn rejects_out_of_range_amounts() {
+    rejected(&["--victim-sol", "0"], "--victim-sol must be positive");
+    rejected(&["--victim-sol", "1e30"], "--victim-sol must be positive");
+    rejected(&["--frontrun-fraction", "1e30"], "--frontrun-fraction must be between 0 and 5");
+    rejected(&["--frontrun-fraction", "-0.1"], "--frontrun-fraction must be between 0 and 5");
+    rejected(&["--backrun-split", "1.5"], "--backrun-split must be between 0 and 1");
+    rejected(&["--victim-slippage-bps", "10001"], "--victim-slippage-bps must be at most 10000");
+    rejected(&["--victims", "1,1e30"], "--victims sizes must be positive");
+    rejected(&["--virtual-sol", "1e30"], "--virtual-sol at most");
+    rejected(&["--victim-sol", "abc"], "Invalid value 'abc' for --victim-sol");
+    rejected(&["--victim-sol"], "--victim-sol requires a value");
+    rejected(&["--frobnicate"], "Unknown argument '--frobnicate'");
+}
+
+#[test]
+fn saturates_instead_of_overflowing_on_extreme_inputs() {
+    for args in [
+        &["--frontrun-fraction", "5", "--victim-sol", "1e9"][..],
+        &["--base-fee", "18446744073709551615"],
+        &["--priority-fee", "1e30", "--compute-units", "4294967295"],
+        &["--victims", "1e9,1e9"],
+        &["--victim-side", "sell", "--frontrun-fraction", "5", "--victim-sol", "1e9"],
+    ] {
+        let output = simulate(args);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(output.status.success(), "{:?} failed: {}", args, stderr);
+    }
+}
+
+// a successful run's stdout
+fn run_ok(args: &[&str]) -> String {
+    let output = simulate(args);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "{:?} failed: {}", args, stderr);
+    String::from_utf8(output.stdout).expect("stdout is utf-8")
+}
+
+// the number after `label` on the first line holding it, e.g. "bot profit 3.24 SOL"
+fn number_after(stdout: &str, label: &str) -> f64 {
+    let line = stdout
+        .lines()
+        .find(|line| line.contains(label))
+        .unwrap_or_else(|| panic!("no '{}' in\n{}", label, stdout));
+    let rest = &line[line.find(label).unwrap() + label.len()..];
+    let number = rest.split_whitespace().next().unwrap_or_default();
+    number
+        .trim_end_matches(['%', ',', ')'])
+        .parse()
+        .unwrap_or_else(|e| panic!("'{}' after '{}' in {}: {}", number, label, line, e))
+}
+
+// the --optimize table: (frontrun SOL, bot profit SOL) per row
+fn profit_frontier(stdout: &str) -> Vec<(f64, f64)> {
+    stdout
+        .lines()
+        .skip_while(|line| !line.contains("Bot profit SOL"))
+        .skip(1)
+        .map_while(|line| {
+            let fields: Vec<f64> = line.split_whitespace().map(|field| field.parse().ok()).collect::<Option<_>>()?;
+            (fields.len() == 3).then(|| (fields[0], fields[2]))
+        })
+        .collect()
+}
+
+#[test]
+fn optimal_frontrun_beats_a_brute_force_grid() {
+    let steps = 200;
+    let stdout = run_ok(&[
+        "--optimize",
+        "--victim-sol",
+        "5",
+        "--victim-slippage-bps",
+        "9000",
+        "--frontier-steps",
+        &steps.to_string(),
+    ]);
+    let grid = profit_frontier(&stdout);
+    assert_eq!(grid.len(), steps + 1, "{}", stdout);
+    let limit = number_after(&stdout, "Max frontrun keeping victim within slippage:");
+    let &(grid_sol, grid_profit) = grid.iter().max_by(|a, b| a.1.total_cmp(&b.1)).unwrap();
+    // an interior optimum, so the solver has something to find
+    assert!(grid_sol > 0.0 && grid_sol < limit, "{}", stdout);
+
+    let optimal_sol = number_after(&stdout, "Optimal frontrun:");
+    let optimal_profit = number_after(&stdout, "bot profit");
+    assert!(optimal_profit >= grid_profit - 1e-6, "solver {} < grid {}", optimal_profit, grid_profit);
+    assert!(
+        (optimal_sol - grid_sol).abs() <= limit / steps as f64,
+        "solver frontrun {} is more than a grid step from {}",
+        optimal_sol,
+        grid_sol
+    );
+    assert!(optimal_sol <= limit);
+}
+
+#[test]
+fn optimal_frontrun_is_zero_when_no_size_pays() {
+    let stdout = run_ok(&["--optimize", "--victim-sol", "0.001", "--victim-slippage-bps", "300"]);
+    let grid = profit_frontier(&stdout);
+    assert!(grid.len() > 1, "{}", stdout);
+    for &(sol, profit) in &grid[1..] {
+        assert!(sol > 0.0 && profit < 0.0, "a {} SOL frontrun made {} SOL", sol, profit);
+    }
+    assert_eq!(number_after(&stdout, "Optimal frontrun:"), 0.0);
+    assert_eq!(number_after(&stdout, "bot profit"), 0.0);
+}
+
+// a "label mean .. | p5 .. | ... | max .." line as (name, value) pairs
+fn distribution(stdout: &str, label: &str) -> Vec<(String, f64)> {
+    let line = stdout.lines().find(|line| line.starts_with(label)).unwrap_or_else(|| panic!("no '{}' in\n{}", label, stdout));
+    line[label.len()..]
+        .split('|')
+        .map(|stat| {
+            let (name, value) = stat.trim().split_once(' ').unwrap();
+            (name.to_string(), value.parse().unwrap())
+        })
+        .collect()
+}
+
+#[test]
+fn monte_carlo_is_seeded_and_bounded() {
+    let args = ["--monte-carlo", "200", "--seed", "7"];
+    let stdout = run_ok(&args);
+    assert_eq!(stdout, run_ok(&args), "the same seed must replay the same scenarios");
+    assert_ne!(stdout, run_ok(&["--monte-carlo", "200", "--seed", "8"]));
+
+    assert_eq!(number_after(&stdout, "Monte Carlo:"), 200.0);
+    assert!(stdout.contains("(seed 7)"), "{}", stdout);
+    let profitable = number_after(&stdout, "Profitable sandwiches:");
+    assert!((0.0..=200.0).contains(&profitable), "{}", stdout);
+
+    for label in ["Attacker profit (SOL):", "Victim loss (SOL):"] {
+        let stats = distribution(&stdout, label);
+        let names: Vec<&str> = stats.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["mean", "p5", "p25", "p50", "p75", "p95", "max"]);
+        let quantiles: Vec<f64> = stats[1..].iter().map(|(_, value)| *value).collect();
+        assert!(quantiles.windows(2).all(|pair| pair[0] <= pair[1]), "{} out of order: {:?}", label, quantiles);
+        // the bot skips victims it can't profit from, so no scenario loses it money
+        assert!(quantiles[0] >= 0.0, "{} {:?}", label, quantiles);
+        assert!(stats[0].1 <= quantiles[quantiles.len() - 1], "{} mean above max", label);
+    }
+}
+
+// a scratch file under the system temp dir, unique to this test process
+fn scratch(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("grok_simulate_{}_{}", std::process::id(), name))
+}
+
+fn only_sandwich(trades: &[ParsedTransaction]) -> (DetectionSummary, SandwichDetection) {
+    let summary = detect_wide_attacks(trades, &DetectorConfig::default());
+    assert_eq!(summary.sandwiches.len(), 1, "{:?}", summary.sandwiches);
+    let sandwich = summary.sandwiches[0].clone();
+    (summary, sandwich)
+}
+
+// the legs in the order --replay applies them, with the curve after each
+fn replay_steps(sandwich: &SandwichDetection) -> Vec<(ParsedTransaction, PumpAmmState)> {
+    let mut amm = PumpAmmState::before_trade(&sandwich.frontruns[0], &FeeSchedule::default()).unwrap();
+    let legs = sandwich.frontruns.iter().chain([&sandwich.victim]).chain(&sandwich.backruns);
+    legs.map(|leg| {
+        amm.apply_trade(leg);
+        (leg.clone(), amm.clone())
+    })
+    .collect()
+}
+
+fn replay_line(summary: &DetectionSummary, name: &str) -> String {
+    let path = scratch(name);
+    fs::write(&path, serde_json::to_string(summary).unwrap()).unwrap();
+    let stdout = run_ok(&["--replay", path.to_str().unwrap()]);
+    fs::remove_file(&path).ok();
+    stdout.lines().find(|line| line.starts_with("#01 victim")).unwrap_or_else(|| panic!("{}", stdout)).to_string()
+}
+
+#[test]
+fn replays_the_sandwich_fixture_step_by_step() {
+    let trades: Vec<ParsedTransaction> = ["sandwich_front", "sandwich_victim", "sandwich_back"]
+        .iter()
+        .flat_map(|name| {
+            let path = format!("{}/../parse_and_detect/fixtures/{}.json", env!("CARGO_MANIFEST_DIR"), name);
+            let raw = fs::read_to_string(&path).unwrap_or_else(|e| panic!("reading {}: {}", path, e));
+            parse_transaction_json(&raw, FIXTURE_MINT).unwrap()
+        })
+        .collect();
+    let (summary, sandwich) = only_sandwich(&trades);
+
+    // no curve snapshot in these legs, so the reserves are solved from the frontrun's fill
+    let steps = replay_steps(&sandwich);
+    let reserves: Vec<(u64, u64, u64, u64)> = steps
+        .iter()
+        .map(|(_, amm)| (amm.virtual_sol, amm.virtual_token, amm.protocol_fees, amm.creator_fees))
+        .collect();
+    assert_eq!(
+        reserves,
+        [
+            (33_597_516_004, 958_106_545_597_134, 19_000_000, 1_000_000),
+            (34_587_516_004, 930_682_619_576_212, 28_500_000, 1_500_000),
+            (32_492_747_287, 990_682_619_576_212, 48_400_303, 2_547_384),
+        ]
+    );
+    // the solved curve gives the frontrun the tokens it got, to rounding
+    let frontrun_tokens = 1_018_106_545_598_472 - reserves[0].1;
+    assert!(frontrun_tokens.abs_diff(sandwich.frontruns[0].token_change as u64) < 10_000);
+
+    // the fixture's victim got 19M tokens where the curve gives 27.4M, so the profit isn't the curve's
+    let line = replay_line(&summary, "fixture_detections.json");
+    assert!(
+        line.contains("observed +0.097951 SOL | simulated +0.071772 SOL | UNEXPLAINED"),
+        "{}",
+        line
+    );
+}
+
+#[test]
+fn replays_emitted_trades_onto_the_same_reserves_and_profit() {
+    let emitted = scratch("emitted_trades.json");
+    run_ok(&["--emit-trades", emitted.to_str().unwrap()]);
+    let saved = read_trades(&fs::read_to_string(&emitted).unwrap()).unwrap();
+    fs::remove_file(&emitted).ok();
+    let (summary, sandwich) = only_sandwich(&saved.trades);
+
+    // every emitted leg records the curve's token balance, so each step must land on the tokens
+    // the next leg found; the snapshot derives SOL from that balance, rounding it by a lamport
+    let steps = replay_steps(&sandwich);
+    assert_eq!(steps.len(), 4);
+    for (index, pair) in steps.windows(2).enumerate() {
+        let (_, after) = &pair[0];
+        let (next, _) = &pair[1];
+        let expected = PumpAmmState::from_curve_snapshot(next, &FeeSchedule::default()).unwrap();
+        assert_eq!(
+            (after.virtual_token, after.real_token),
+            (expected.virtual_token, expected.real_token),
+            "after step {}",
+            index + 1
+        );
+        assert!(after.virtual_sol.abs_diff(expected.virtual_sol) <= 1, "after step {}", index + 1);
+    }
+
+    let line = replay_line(&summary, "emitted_detections.json");
+    assert!(line.ends_with("| explained"), "{}", line);
+    let observed = number_after(&line, "observed");
+    assert_eq!(observed, number_after(&line, "simulated"), "{}", line);
+    assert!(observed > 0.0, "{}", line);
+}
+
+#[test]
+fn graduation_switches_the_back_run_to_pumpswap() {
+    let stdout = run_ok(&["--sol-to-graduation", "0.5", "--victim-sol", "1"]);
+    let crossing = stdout.find("Curve completed: liquidity migrated to PumpSwap").unwrap_or_else(|| panic!("{}", stdout));
+    let victim = stdout.find("Victim Buy on bonding curve").unwrap();
+    assert!(victim < crossing, "{}", stdout);
+    assert!(stdout[crossing..].contains("(PumpSwap)"), "{}", stdout);
+    assert!(stdout.contains("Bot Back-run Sell on PumpSwap"), "{}", stdout);
+    assert!(stdout.contains("Graduation crossed by: victim buy"), "{}", stdout);
+    // the victim's 1 SOL is capped at what completes the curve
+    assert!(stdout.contains("for 0.300000 SOL (0.700000 SOL refunded)"), "{}", stdout);
+
+    // a frontrun big enough to complete the curve itself leaves the victim trading on the pool
+    let stdout = run_ok(&["--sol-to-graduation", "0.1", "--victim-sol", "1", "--frontrun-fraction", "0.5"]);
+    assert!(stdout.contains("Graduation crossed by: bot front-run"), "{}", stdout);
+    assert!(stdout.contains("Victim Buy on PumpSwap"), "{}", stdout);
+
+    let stdout = run_ok(&["--sol-to-graduation", "50", "--victim-sol", "1"]);
+    assert!(stdout.contains("Graduation crossed by: nobody"), "{}", stdout);
+    assert!(!stdout.contains("PumpSwap)"), "{}", stdout);
+}
+
+#[test]
+fn scenarios_parse_from_toml_or_yaml_and_reject_malformed_files() {
+    let toml = run_ok(&["--scenario", "scenarios/sandwich.toml"]);
+    assert_eq!(toml, run_ok(&["--scenario", "scenarios/sandwich.yaml"]));
+    assert!(toml.contains("Scenario: 4 trades"), "{}", toml);
+    assert!(toml.contains("Step 3 slot 380000001 | bot Sell"), "{}", toml);
+    let bot = toml.lines().find(|line| line.starts_with("bot ")).unwrap();
+    assert_eq!(bot.split_whitespace().take(3).collect::<Vec<_>>(), ["bot", "2", "0"]);
+
+    // sequences at their key's indentation, quoted scalars and trailing comments
+    let compact = scratch("compact.yml");
+    let body = "---\ntrade:\n- actor: \"bot #1\"  # quoted, so the # stays\n  side: buy\n  sol: 1\n- actor: 'bot #1'\n  side: sell\n  tokens: \"all\"\n";
+    fs::write(&compact, body).unwrap();
+    let stdout = run_ok(&["--scenario", compact.to_str().unwrap()]);
+    fs::remove_file(&compact).ok();
+    assert!(stdout.contains("Scenario: 2 trades"), "{}", stdout);
+    assert!(stdout.contains("Step 2 slot 380000000 | bot #1 Sell"), "{}", stdout);
+
+    for (name, body, message) in [
+        ("broken.toml", "[[trade]\nactor = \"bot\"\n", "Invalid scenario"),
+        ("empty.toml", "", "no trade entries"),
+        ("unknown.toml", "[[trade]]\nactor = \"bot\"\nside = \"buy\"\nsol = 1\nsize = 2\n", "trade 1: unknown key 'size'"),
+        ("indent.yaml", "trade:\n  - actor: bot\n     side: buy\n", "line 3: unexpected indentation"),
+        ("tabs.yml", "trade:\n\t- actor: bot\n", "line 2: indent with spaces, not tabs"),
+        ("flow.yaml", "trade: [1, 2]\n", "line 1: only plain and quoted scalars are supported"),
+        ("quote.yaml", "trade:\n  - actor: \"bot\n", "line 2: unterminated string"),
+        ("type.yaml", "trade:\n  - actor: bot\n    side: buy\n    sol: lots\n", "trade 1: 'sol' must be a number"),
+        ("nan.yaml", "trade:\n  - actor: bot\n    side: buy\n    sol: .nan\n", "'sol' must be a positive finite number"),
+        ("side.yaml", "trade:\n  - actor: bot\n    sol: 1\n", "trade 1: missing 'side'"),
+        ("twice.yaml", "trade:\n  - actor: bot\n    actor: bot\n", "line 3: duplicate key 'actor'"),
+        ("empty.yaml", "# nothing here\n", "expected a 'curve' table and 'trade' entries"),
+    ] {
+        let path = scratch(name);
+        fs::write(&path, body).unwrap();
+        rejected(&["--scenario", path.to_str().unwrap()], message);
+        fs::remove_file(&path).ok();
+    }
+}
+
+#[test]
+fn victim_queue_sandwiches_only_profitable_buys_in_order() {
+    let stdout = run_ok(&["--victims", "0.001,2:300,1.5", "--slot", "1000"]);
+    let rows: Vec<Vec<&str>> = stdout
+        .lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>())
+        .filter(|fields| fields.len() == 9 && fields[0].parse::<u64>().is_ok())
+        .collect();
+    assert_eq!(rows.len(), 3, "{}", stdout);
+    let column = |row: &[&str], index: usize| row[index].parse::<f64>().unwrap();
+
+    // one slot per victim, each at its own slippage or --victim-slippage-bps
+    let slots: Vec<&str> = rows.iter().map(|row| row[0]).collect();
+    assert_eq!(slots, ["1000", "1001", "1002"]);
+    let bps: Vec<&str> = rows.iter().map(|row| row[2]).collect();
+    assert_eq!(bps, ["500", "300", "500"]);
+
+    // a dust buy isn't worth a sandwich and costs nobody anything
+    assert_eq!(rows[0][3], "skip");
+    assert_eq!((column(&rows[0], 4), column(&rows[0], 5), column(&rows[0], 6)), (0.0, 0.0, 0.0));
+    let (mut profit, mut loss) = (0.0, 0.0);
+    for row in &rows[1..] {
+        assert_eq!(row[3], "sandwich");
+        assert!(column(row, 4) > 0.0 && column(row, 5) > 0.0 && column(row, 6) > 0.0, "{:?}", row);
+        profit += column(row, 5);
+        loss += column(row, 6);
+        assert!((column(row, 8) - profit).abs() < 2e-6, "{:?}", row);
+    }
+    assert_eq!(column(&rows[2], 7), 3.501);
+
+    assert!(stdout.contains("Sandwiched 2 of 3 victims"), "{}", stdout);
+    assert!((number_after(&stdout, "Bot Total Net Profit:") - profit).abs() < 2e-6, "{}", stdout);
+    assert!((number_after(&stdout, "Victim losses:") - loss).abs() < 2e-6, "{}", stdout);
+
+    // each victim trades against the curve the earlier ones left: on a fresh curve the same
+    // 1.5 SOL buy pays the bot more than behind the 2 SOL buy that lifted the price
+    let alone = run_ok(&["--victims", "1.5"]);
+    assert!(number_after(&alone, "Bot Total Net Profit:") > column(&rows[2], 5), "{}", alone);
+}
+
+#[test]
+fn bot_costs_come_out_of_every_transaction_and_the_tip_out_of_profit() {
+    let stdout = run_ok(&["--base-fee", "0", "--compute-units", "1000000", "--priority-fee", "100000", "--priority-fee-sigma", "1"]);
+    // priced at the log-normal's mean: a million CU at 100000 micro-lamports is 100000 lamports, times e^0.5
+    assert!(
+        stdout.contains("Bot costs: 0.000165 SOL per tx (base 0 + 1000000 CU at 100000 micro-lamports median, sigma 1.00)"),
+        "{}",
+        stdout
+    );
+    let stdout = run_ok(&["--base-fee", "10000", "--compute-units", "100000", "--priority-fee", "100000", "--tip-pct", "50"]);
+    assert!(stdout.contains("Bot costs: 0.000020 SOL per tx (base 10000 + 100000 CU at 100000 micro-lamports), Jito tip 50.0% of profit"), "{}", stdout);
+
+    // a buy sandwich is three bot transactions, a sell sandwich two
+    let profit = |args: &[&str]| number_after(&run_ok(args), "Bot Total Net Profit:");
+    for (side, txs) in [("buy", 3.0), ("sell", 2.0)] {
+        let free = profit(&["--victim-side", side, "--base-fee", "0"]);
+        let paid = profit(&["--victim-side", side, "--base-fee", "5000"]);
+        assert!((free - paid - txs * 0.000005).abs() < 1.5e-6, "{}: {} -> {}", side, free, paid);
+    }
+
+    // the tip takes its share of what's left after fees, and nothing from a loss
+    let free = profit(&["--base-fee", "0"]);
+    let tipped = run_ok(&["--base-fee", "0", "--tip-pct", "50"]);
+    let (tip, net) = (number_after(&tipped, "Jito tip:"), number_after(&tipped, "Bot Total Net Profit:"));
+    assert!((tip - net).abs() <= 1e-6 && (tip + net - free).abs() <= 1e-6, "{}", tipped);
+    let losing = run_ok(&["--victim-sol", "0.001", "--tip-pct", "50"]);
+    assert!(number_after(&losing, "Bot Total Net Profit:") < 0.0, "{}", losing);
+    assert!(!losing.contains("Jito tip:"), "{}", losing);
+}
+
+#[test]
+fn backrun_restores_the_price_and_its_optimum_pays_at_least_as_much() {
+    for side in ["buy", "sell"] {
+        let stdout = run_ok(&["backrun", "--victim-side", side]);
+        let before = number_after(&stdout, "moves the price");
+        let after = number_after(&stdout, "->");
+        assert!(if side == "buy" { after > before } else { after < before }, "{}", stdout);
+
+        let line = |label: &str| stdout.lines().find(|line| line.starts_with(label)).unwrap_or_else(|| panic!("{}", stdout));
+        let (restoring, optimal) = (line("Restoring backrun:"), line("Optimal backrun:"));
+        assert!((number_after(restoring, "price after") - before).abs() < 1e-12, "{}", restoring);
+        // the optimum stops short of restoring the price, by about the fee
+        let optimal_price = number_after(optimal, "price after");
+        assert!((before.min(after)..=before.max(after)).contains(&optimal_price), "{}", optimal);
+        assert!(number_after(optimal, "gross") >= number_after(restoring, "gross"), "{}", stdout);
+        for outcome in [restoring, optimal] {
+            // net pays the backrun's and its close's 5000-lamport fees
+            assert!((number_after(outcome, "gross") - number_after(outcome, "net") - 0.00001).abs() < 1.5e-6, "{}", outcome);
+        }
+    }
+
+    rejected(&["backrun", "--optimize"], "backrun prices one victim trade");
+    rejected(&["backrun", "--victims", "1,2"], "backrun prices one victim trade");
+}
+
+// runs `args` with --emit-trades into a scratch file: (stdout, the trades written)
+fn emitted(name: &str, args: &[&str]) -> (String, SavedTrades) {
+    let path = scratch(name);
+    let mut args = args.to_vec();
+    args.extend(["--emit-trades", path.to_str().unwrap()]);
+    let stdout = run_ok(&args);
+    let raw = fs::read_to_string(&path).unwrap_or_else(|e| panic!("{:?} wrote nothing: {}", args, e));
+    fs::remove_file(&path).ok();
+    (stdout, read_trades(&raw).unwrap())
+}
+
+#[test]
+fn emitted_trades_are_deterministic_parsed_legs_the_detector_flags() {
+    let (stdout, saved) = emitted("sandwich.json", &["--slot", "5000"]);
+    let json = |saved: &SavedTrades| serde_json::to_string(&saved.trades).unwrap();
+    assert_eq!(json(&emitted("again.json", &["--slot", "5000"]).1), json(&saved));
+    assert!(stdout.contains("Wrote 4 simulated trades"), "{}", stdout);
+    let printed = |name: &str| rest_of_line(&stdout, &format!("{}: ", name));
+    assert_eq!(saved.mint, printed("mint"));
+    assert_eq!(saved.token_decimals, Some(6));
+
+    let legs: Vec<(&str, u64, TradeType)> =
+        saved.trades.iter().map(|trade| (&*trade.signer, trade.slot, trade.trade_type)).collect();
+    let (bot, victim) = (printed("bot"), printed("victim"));
+    assert_eq!(
+        legs,
+        [
+            (bot.as_str(), 5000, TradeType::Buy),
+            (victim.as_str(), 5001, TradeType::Buy),
+            (bot.as_str(), 5002, TradeType::Sell),
+            (bot.as_str(), 5003, TradeType::Sell),
+        ]
+    );
+    // the bot unwinds what it bought, every leg pays one transaction fee, and the victim's fill
+    // falls short of the quote it sent
+    let bot_tokens: i64 = saved.trades.iter().filter(|trade| *trade.signer == bot).map(|trade| trade.token_change).sum();
+    assert_eq!(bot_tokens, 0);
+    assert!(saved.trades.iter().all(|trade| trade.fee == 5_000));
+    assert!((saved.trades[1].token_change as u64) < saved.trades[1].token_amount_requested);
+    // each leg finds the curve where the one before left it
+    for pair in saved.trades.windows(2) {
+        let (curve, next) = (pair[0].curve.as_ref().unwrap(), pair[1].curve.as_ref().unwrap());
+        assert_eq!(
+            next.pre_token_balance,
+            curve.pre_token_balance.map(|held| held.checked_add_signed(curve.token_change).unwrap())
+        );
+    }
+
+    let (_, sandwich) = only_sandwich(&saved.trades);
+    assert_eq!(*sandwich.victim.signer, victim);
+    assert!(sandwich.frontruns.iter().chain(&sandwich.backruns).all(|leg| *leg.signer == bot));
+
+    // a skipped victim in a queue is logged alone in its slot, a sandwiched one with the bot around it
+    let (_, queue) = emitted("queue.json", &["--victims", "0.001,2", "--slot", "10"]);
+    let slots: Vec<u64> = queue.trades.iter().map(|trade| trade.slot).collect();
+    assert_eq!(slots, [10, 11, 11, 11]);
+    let tx_indexes: Vec<Option<usize>> = queue.trades.iter().map(|trade| trade.tx_index).collect();
+    assert_eq!(tx_indexes, [Some(0), Some(0), Some(1), Some(2)]);
+
+    let (_, scripted) = emitted("scenario.json", &["--scenario", "scenarios/sandwich.toml"]);
+    assert_eq!(scripted.trades.len(), 4);
+
+    rejected(&["--optimize", "--emit-trades", "unused.json"], "--emit-trades writes a sandwich");
+}
+
+// the rest of the first line starting with `label`
+fn rest_of_line(stdout: &str, label: &str) -> String {
+    let line = stdout.lines().find(|line| line.starts_with(label)).unwrap_or_else(|| panic!("no '{}' in\n{}", label, stdout));
+    line[label.len()..].trim().to_string()
+}
+
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::new();
+    for chunk in bytes.chunks(3) {
+        let word = chunk.iter().enumerate().fold(0u32, |word, (at, byte)| word | (*byte as u32) << (16 - 8 * at));
+        for at in 0..4 {
+            out.push(if at <= chunk.len() { ALPHABET[(word >> (18 - 6 * at) & 63) as usize] as char } else { '=' });
+        }
+    }
+    out
+}
+
+// a JSON-RPC endpoint answering getAccountInfo: the mint account when asked for `mint`, the
+// bonding curve for anything else
+fn serve_accounts(mint: &'static str, curve: Vec<u8>, decimals: u8) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let mut request = Vec::new();
+            let mut buffer = [0u8; 4096];
+            // headers, then as much body as Content-Length says
+            while let Ok(read) = stream.read(&mut buffer) {
+                if read == 0 {
+                    break;
+                }
+                request.extend_from_slice(&buffer[..read]);
+                let text = String::from_utf8_lossy(&request).to_lowercase();
+                if let Some(end) = text.find("\r\n\r\n") {
+                    let length = text
+                        .lines()
+                        .find_map(|line| line.strip_prefix("content-length:"))
+                        .and_then(|length| length.trim().parse::<usize>().ok())
+                        .unwrap_or(0);
+                    if request.len() >= end + 4 + length {
+                        break;
+                    }
+                }
+            }
+            let request = String::from_utf8_lossy(&request);
+            let (data, owner) = if request.contains(mint) {
+                let mut data = vec![0u8; 82];
+                data[44] = decimals;
+                data[45] = 1;
+                (data, "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA")
+            } else {
+                (curve.clone(), "6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P")
+            };
+            let body = format!(
+                r#"{{"jsonrpc":"2.0","id":1,"result":{{"context":{{"slot":1}},"value":{{"data":["{}","base64"],"executable":false,"lamports":1461600,"owner":"{}","rentEpoch":0,"space":{}}}}}}}"#,
+                base64(&data),
+                owner,
+                data.len()
+            );
+            let head = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                body.len()
+            );
+            let _ = stream.write_all(head.as_bytes());
+            let _ = stream.write_all(body.as_bytes());
+        }
+    });
+    url
+}
+
+// an 8-byte discriminator, then virtual tokens, virtual SOL, real tokens, real SOL, supply and `complete`
+fn curve_account(virtual_token: u64, virtual_sol: u64, real_token: u64, real_sol: u64, complete: bool) -> Vec<u8> {
+    let mut data = vec![0u8; 8];
+    for field in [virtual_token, virtual_sol, real_token, real_sol, 1_000_000_000_000_000] {
+        data.extend_from_slice(&field.to_le_bytes());
+    }
+    data.push(complete as u8);
+    data
+}
+
+#[test]
+fn live_mint_state_comes_from_the_rpc_curve_and_mint() {
+    let curve = curve_account(800_000_000_000_000, 40_000_000_000, 520_100_000_000_000, 10_000_000_000, false);
+    // 9 decimals rather than pump.fun's 6, so every token amount prints a thousand times smaller
+    let url = serve_accounts(FIXTURE_MINT, curve, 9);
+    let stdout = run_ok(&["--mint", FIXTURE_MINT, "--rpc-url", &url]);
+    let loaded = rest_of_line(&stdout, "Loaded bonding curve");
+    assert!(
+        loaded.ends_with("virtual 40.000 SOL / 800000 tokens, real 10.000 SOL / 520100 tokens (supply 1000000)"),
+        "{}",
+        loaded
+    );
+    // the trade runs on the loaded reserves: a 1 SOL buy at 40 SOL / 800k tokens gets about 19.3k
+    let baseline = number_after(&stdout, "Baseline (No Attack): Tokens");
+    let expected = 990_000_000u128 * 800_000_000_000_000 / (40_000_000_000 + 990_000_000);
+    assert_eq!(baseline as u128, expected);
+
+    let completed = curve_account(800_000_000_000_000, 40_000_000_000, 0, 85_000_000_000, true);
+    let url = serve_accounts(FIXTURE_MINT, completed, 6);
+    rejected(&["--mint", FIXTURE_MINT, "--rpc-url", &url], "has completed; the token trades outside the curve now");
+
+    rejected(&["--mint", "not-a-mint", "--rpc-url", "http://127.0.0.1:1"], "not-a-mint");
+    rejected(&["--mint", FIXTURE_MINT, "--virtual-sol", "40"], "--mint loads the live reserves");
+    let output = simulate(&["--mint", FIXTURE_MINT, "--rpc-url", "http://127.0.0.1:1"]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(4), "{}", stderr);
+    assert!(stderr.contains("Failed to fetch bonding curve"), "{}", stderr);
+}
+
+#[test]
+fn sell_side_sandwich_front_runs_the_victim_down_and_buys_back() {
+    let stdout = run_ok(&["--victim-side", "sell"]);
+    let slot = |label: &str| stdout.lines().find(|line| line.contains(label)).unwrap_or_else(|| panic!("{}", stdout));
+    let front_sol = number_after(slot("Bot Front-run Sell"), " for ");
+    let victim_sol = number_after(slot("Victim Sell: Tokens"), " for ");
+    let back_sol = number_after(slot("Back-run Buy"), " for ");
+
+    let baseline = number_after(&stdout, "Baseline (No Attack): Received");
+    let min_out = number_after(&stdout, "min SOL out");
+    assert!(min_out <= victim_sol && victim_sol < baseline, "{}", stdout);
+    assert!((number_after(&stdout, "Extracted Value:") - (baseline - victim_sol)).abs() < 1.5e-6, "{}", stdout);
+
+    let price = |label: &str| number_after(&stdout, label);
+    assert!(price("Price after victim:") < price("Price after front-run:"), "{}", stdout);
+    assert!(price("Price after back-run:") > price("Price after victim:"), "{}", stdout);
+    // the bot buys back exactly what it sold, two transactions' fees out of the spread
+    assert!(stdout.contains("Bot Inventory Change: +0 tokens"), "{}", stdout);
+    let net = number_after(&stdout, "Bot Total Net Profit:");
+    assert!((net - (front_sol - back_sol - 0.00001)).abs() < 2e-6, "{}", stdout);
+    assert!(net > 0.0);
+
+    // a victim too tight to absorb the front-run reverts, and the bot eats its round trip
+    let stdout = run_ok(&["--victim-side", "sell", "--victim-slippage-bps", "10"]);
+    assert!(stdout.contains("Victim sell reverted: slippage limit exceeded"), "{}", stdout);
+    assert_eq!(number_after(&stdout, "Extracted Value:"), 0.0);
+    assert!(number_after(&stdout, "Bot Total Net Profit:") < 0.0, "{}", stdout);
+
+    rejected(&["--victim-side", "sell", "--optimize"], "only support --victim-side buy");
+    rejected(&["--victim-side", "short"], "Unsupported victim side 'short'");
+}
+
+#[test]
+fn impact_curve_prints_a_table_or_csv_of_the_same_buys() {
+    let csv = run_ok(&["impact", "--max-sol", "2", "--steps", "4", "--csv"]);
+    let mut lines = csv.lines();
+    assert_eq!(lines.next(), Some("sol_in,tokens_out,effective_price,price_after,price_impact_pct,slippage_pct"));
+    let rows: Vec<Vec<f64>> = lines.map(|line| line.split(',').map(|field| field.parse().unwrap()).collect()).collect();
+    let sol_in: Vec<f64> = rows.iter().map(|row| row[0]).collect();
+    assert_eq!(sol_in, [0.5, 1.0, 1.5, 2.0]);
+    for row in &rows {
+        // lamports in over raw token units out
+        assert!((row[2] - row[0] * 1e9 / row[1]).abs() < 1e-12, "{:?}", row);
+        // the average fill is below the price the buy leaves behind
+        assert!(0.0 < row[5] && row[5] < row[4], "{:?}", row);
+    }
+    assert!(rows.windows(2).all(|pair| pair[1][1] > pair[0][1] && pair[1][4] > pair[0][4] && pair[1][5] > pair[0][5]));
+
+    let table = run_ok(&["impact", "--max-sol", "2", "--steps", "4"]);
+    let table_rows: Vec<Vec<f64>> = table
+        .lines()
+        .map(|line| line.split_whitespace().map(|field| field.parse().ok()).collect::<Option<Vec<f64>>>())
+        .filter_map(|row| row.filter(|row| row.len() == 6))
+        .collect();
+    assert_eq!(table_rows.len(), rows.len(), "{}", table);
+    for (table_row, row) in table_rows.iter().zip(&rows) {
+        assert_eq!(table_row[1], (row[1] / 1e6).round());
+        assert_eq!((table_row[2], table_row[3]), (row[2], row[3]));
+    }
+
+    // the targets cost more SOL the further they move the price; past graduation they can't be reached
+    let targets = |stdout: &str| -> Vec<Option<f64>> {
+        stdout
+            .lines()
+            .skip_while(|line| !line.starts_with("SOL needed to move the price:"))
+            .skip(1)
+            .map(|line| line.split(':').nth(1).unwrap().trim().trim_end_matches(" SOL").parse().ok())
+            .collect()
+    };
+    let fresh = targets(&table);
+    assert_eq!(fresh.len(), 6);
+    assert!(fresh.windows(2).all(|pair| pair[0].unwrap() < pair[1].unwrap()), "{:?}", fresh);
+    // +10% lands between the table's 1.0 SOL (6.7%) and 1.5 SOL (10.1%) buys
+    assert!((1.0..1.5).contains(&fresh[2].unwrap()), "{:?}", fresh);
+    let late = targets(&run_ok(&["impact", "--virtual-sol", "100", "--steps", "1"]));
+    assert!(late[..4].iter().all(Option::is_some) && late[4..].iter().all(Option::is_none), "{:?}", late);
+
+    rejected(&["--csv"], "Unknown argument '--csv'");
+}
+
+#[test]
+fn starting_reserves_and_fees_come_from_the_flags() {
+    let stdout = run_ok(&["--virtual-sol", "40", "--fee-bps", "200", "--base-fee", "7000"]);
+    assert!(stdout.starts_with("Fee tier at slot 380000000: protocol 200 bps + creator 0 bps\n"), "{}", stdout);
+    assert!(stdout.contains("Bot costs: 0.000007 SOL per tx (base 7000 + 80000 CU"), "{}", stdout);
+    // 40 SOL on pump.fun's k: 30 SOL x 1.073B tokens / 40 SOL, less the 279.9M tokens never sold
+    let curve = "Starting curve: virtual 40.000 SOL / 804750000 tokens, real 10.000 SOL / 524850000 tokens";
+    assert!(stdout.contains(curve), "{}", stdout);
+    // and the trades price on it, after the whole 2% fee
+    let expected = 980_000_000u128 * 804_750_000_000_000 / (40_000_000_000 + 980_000_000);
+    assert_eq!(number_after(&stdout, "Baseline (No Attack): Tokens") as u128, expected);
+
+    // either reserve alone is paired along the curve, and both together are taken as given
+    assert!(run_ok(&["--virtual-token", "804750000"]).contains(curve));
+    let both = run_ok(&["--virtual-sol", "50", "--virtual-token", "500000000"]);
+    assert!(both.contains("Starting curve: virtual 50.000 SOL / 500000000 tokens, real 20.000 SOL / 220100000 tokens"), "{}", both);
+    // without them the curve is a fresh launch and prints no starting line
+    assert!(!run_ok(&[]).contains("Starting curve"));
+
+    rejected(&["--fee-bps", "100", "--creator-fee-bps", "5"], "--fee-bps sets the whole fee");
+    rejected(&["--fee-bps", "10000"], "Fees must total less than 10000 bps");
+    rejected(&["--virtual-token", "0"], "--virtual-sol and --virtual-token must be positive");
+    rejected(&["--base-fee", "-1"], "Invalid value '-1' for --base-fee");
+}